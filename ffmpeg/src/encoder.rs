@@ -37,6 +37,9 @@ pub struct VideoEncoderSettings {
 	pub rc_max_rate: Option<i64>,
 	pub rc_buffer_size: Option<i32>,
 	pub max_b_frames: Option<i32>,
+	/// The fourcc the muxer writes for the stream, e.g. `hvc1` instead of the
+	/// default `hev1` for HEVC in mp4.
+	pub codec_tag: Option<[u8; 4]>,
 	pub codec_specific_options: Option<Dictionary>,
 	pub flags: Option<i32>,
 	pub flags2: Option<i32>,
@@ -60,6 +63,7 @@ impl Default for VideoEncoderSettings {
 			rc_max_rate: None,
 			rc_buffer_size: None,
 			max_b_frames: None,
+			codec_tag: None,
 			codec_specific_options: None,
 			flags: None,
 			flags2: None,
@@ -151,6 +155,11 @@ impl VideoEncoderBuilder {
 		self
 	}
 
+	pub fn codec_tag(mut self, codec_tag: [u8; 4]) -> Self {
+		self.0.codec_tag = Some(codec_tag);
+		self
+	}
+
 	pub fn codec_specific_options(mut self, codec_specific_options: Dictionary) -> Self {
 		self.0.codec_specific_options = Some(codec_specific_options);
 		self
@@ -199,6 +208,8 @@ impl VideoEncoderSettings {
 		encoder.rc_max_rate = self.rc_max_rate.unwrap_or(encoder.rc_max_rate);
 		encoder.rc_buffer_size = self.rc_buffer_size.unwrap_or(encoder.rc_buffer_size);
 		encoder.max_b_frames = self.max_b_frames.unwrap_or(encoder.max_b_frames);
+		// MKTAG, the first character is the least significant byte.
+		encoder.codec_tag = self.codec_tag.map(u32::from_le_bytes).unwrap_or(encoder.codec_tag);
 		encoder.flags = self.flags.unwrap_or(encoder.flags);
 		encoder.flags2 = self.flags2.unwrap_or(encoder.flags2);
 
@@ -32,6 +32,13 @@ struct ThumbnailResp {
 	idx: i32,
 }
 
+#[derive(postgres_from_row::FromRow)]
+struct ThumbnailSpriteResp {
+	recording_id: Ulid,
+	id: Ulid,
+	idx: i32,
+}
+
 #[derive(postgres_from_row::FromRow)]
 struct SegmentResp {
 	recording_id: Ulid,
@@ -82,6 +89,34 @@ impl UpdateBatch for ThumbnailResp {
 	}
 }
 
+impl UpdateBatch for ThumbnailSpriteResp {
+	const NAME: &'static str = "thumbnail sprite";
+
+	fn is_same_batch(&self, batch: &RecordingDeleteBatchTask) -> bool {
+		batch.recording_id.into_ulid() == self.recording_id
+			&& matches!(
+				batch.objects_type,
+				Some(recording_delete_batch_task::ObjectsType::ThumbnailSprites(_))
+			)
+	}
+
+	fn update_batch(&self, deleted_recordings: &HashMap<Ulid, Ulid>, batch: &mut RecordingDeleteBatchTask) {
+		batch.recording_id = Some(self.recording_id.into());
+		batch.s3_bucket_id = Some(deleted_recordings[&self.recording_id].into());
+		batch.objects_type = Some(recording_delete_batch_task::ObjectsType::ThumbnailSprites(
+			recording_delete_batch_task::ThumbnailSpriteType {},
+		));
+		batch.objects.clear();
+	}
+
+	fn to_object(&self) -> recording_delete_batch_task::Object {
+		recording_delete_batch_task::Object {
+			index: self.idx,
+			object_id: Some(self.id.into()),
+		}
+	}
+}
+
 impl UpdateBatch for SegmentResp {
 	const NAME: &'static str = "segment";
 
@@ -314,6 +349,23 @@ impl ApiRequest<RecordingDeleteResponse> for tonic::Request<RecordingDeleteReque
 
 			handle_end_of_stream(global, &mut batch).await?;
 
+			handle_query::<ThumbnailSpriteResp>(
+				global,
+				&client,
+				&deleted_recordings,
+				&mut batch,
+				utils::database::query("SELECT id, recording_id, idx FROM ")
+					.push(<video_common::database::RecordingThumbnailSprite as DatabaseTable>::NAME)
+					.push(" WHERE recording_id = ANY(")
+					.push_bind(&deleted_ids)
+					.push(") AND organization_id = ")
+					.push_bind(access_token.organization_id)
+					.push(" ORDER BY recording_id"),
+			)
+			.await?;
+
+			handle_end_of_stream(global, &mut batch).await?;
+
 			handle_query::<SegmentResp>(
 				global,
 				&client,
@@ -9,7 +9,10 @@ use tonic::Status;
 use ulid::Ulid;
 use video_common::database::{AccessToken, DatabaseTable, Rendition};
 
+use crate::api::utils::audio_settings::{validate_audio_normalization, validate_audio_settings};
 use crate::api::utils::tags::validate_tags;
+use crate::api::utils::video_settings::validate_video_settings;
+use crate::api::utils::watermark::{validate_watermark, validate_watermark_bucket};
 use crate::api::utils::{impl_request_scopes, ApiRequest, TonicRequest};
 use crate::global::ApiGlobal;
 use crate::ratelimit::RateLimitResource;
@@ -22,7 +25,11 @@ impl_request_scopes!(
 );
 
 pub fn validate(req: &TranscodingConfigCreateRequest) -> tonic::Result<()> {
-	validate_tags(req.tags.as_ref())
+	validate_tags(req.tags.as_ref())?;
+	validate_video_settings(&req.video_settings)?;
+	validate_audio_settings(&req.audio_settings)?;
+	validate_audio_normalization(req.audio_normalization.as_ref())?;
+	validate_watermark(req.watermark.as_ref())
 }
 
 pub fn build_query(
@@ -40,6 +47,10 @@ pub fn build_query(
 	seperated.push("id");
 	seperated.push("organization_id");
 	seperated.push("renditions");
+	seperated.push("video_settings");
+	seperated.push("audio_settings");
+	seperated.push("audio_normalization");
+	seperated.push("watermark");
 	seperated.push("tags");
 
 	qb.push(") VALUES (");
@@ -59,6 +70,22 @@ pub fn build_query(
 	seperated.push_bind(Ulid::new());
 	seperated.push_bind(access_token.organization_id);
 	seperated.push_bind(renditions.into_iter().collect::<Vec<_>>());
+	seperated.push_bind(
+		req.video_settings
+			.clone()
+			.into_iter()
+			.map(utils::database::Protobuf)
+			.collect::<Vec<_>>(),
+	);
+	seperated.push_bind(
+		req.audio_settings
+			.clone()
+			.into_iter()
+			.map(utils::database::Protobuf)
+			.collect::<Vec<_>>(),
+	);
+	seperated.push_bind(req.audio_normalization.clone().map(utils::database::Protobuf));
+	seperated.push_bind(req.watermark.clone().map(utils::database::Protobuf));
 	seperated.push_bind(utils::database::Json(req.tags.clone().unwrap_or_default().tags));
 
 	qb.push(") RETURNING *");
@@ -76,6 +103,8 @@ impl ApiRequest<TranscodingConfigCreateResponse> for tonic::Request<TranscodingC
 
 		validate(req)?;
 
+		validate_watermark_bucket(global.db(), access_token.organization_id, req.watermark.as_ref()).await?;
+
 		let query = build_query(req, access_token)?;
 
 		let result: video_common::database::TranscodingConfig =
@@ -10,7 +10,10 @@ use tonic::Status;
 use video_common::database::{AccessToken, DatabaseTable, Rendition};
 
 use crate::api::errors::MODIFY_NO_FIELDS;
+use crate::api::utils::audio_settings::{validate_audio_normalization, validate_audio_settings};
 use crate::api::utils::tags::validate_tags;
+use crate::api::utils::video_settings::validate_video_settings;
+use crate::api::utils::watermark::{validate_watermark, validate_watermark_bucket};
 use crate::api::utils::{impl_request_scopes, ApiRequest, TonicRequest};
 use crate::global::ApiGlobal;
 use crate::ratelimit::RateLimitResource;
@@ -23,7 +26,25 @@ impl_request_scopes!(
 );
 
 pub fn validate(req: &TranscodingConfigModifyRequest) -> tonic::Result<()> {
-	validate_tags(req.tags.as_ref())
+	validate_tags(req.tags.as_ref())?;
+
+	if let Some(video_settings) = &req.video_settings {
+		validate_video_settings(&video_settings.items)?;
+	}
+
+	if let Some(audio_settings) = &req.audio_settings {
+		validate_audio_settings(&audio_settings.items)?;
+	}
+
+	if let Some(audio_normalization) = &req.audio_normalization {
+		validate_audio_normalization(audio_normalization.value.as_ref())?;
+	}
+
+	if let Some(watermark) = &req.watermark {
+		validate_watermark(watermark.value.as_ref())?;
+	}
+
+	Ok(())
 }
 
 pub fn build_query<'a>(
@@ -54,13 +75,53 @@ pub fn build_query<'a>(
 			.push_bind_unseparated(renditions.into_iter().collect::<Vec<_>>());
 	}
 
+	if let Some(video_settings) = &req.video_settings {
+		seperated.push("video_settings = ").push_bind_unseparated(
+			video_settings
+				.items
+				.clone()
+				.into_iter()
+				.map(utils::database::Protobuf)
+				.collect::<Vec<_>>(),
+		);
+	}
+
+	if let Some(audio_settings) = &req.audio_settings {
+		seperated.push("audio_settings = ").push_bind_unseparated(
+			audio_settings
+				.items
+				.clone()
+				.into_iter()
+				.map(utils::database::Protobuf)
+				.collect::<Vec<_>>(),
+		);
+	}
+
+	if let Some(audio_normalization) = &req.audio_normalization {
+		seperated
+			.push("audio_normalization = ")
+			.push_bind_unseparated(audio_normalization.value.clone().map(utils::database::Protobuf));
+	}
+
+	if let Some(watermark) = &req.watermark {
+		seperated
+			.push("watermark = ")
+			.push_bind_unseparated(watermark.value.clone().map(utils::database::Protobuf));
+	}
+
 	if let Some(tags) = &req.tags {
 		seperated
 			.push("tags = ")
 			.push_bind_unseparated(utils::database::Json(&tags.tags));
 	}
 
-	if req.renditions.is_none() && req.tags.is_none() {
+	if req.renditions.is_none()
+		&& req.video_settings.is_none()
+		&& req.audio_settings.is_none()
+		&& req.audio_normalization.is_none()
+		&& req.watermark.is_none()
+		&& req.tags.is_none()
+	{
 		return Err(tonic::Status::invalid_argument(MODIFY_NO_FIELDS));
 	}
 
@@ -83,6 +144,10 @@ impl ApiRequest<TranscodingConfigModifyResponse> for tonic::Request<TranscodingC
 
 		validate(req)?;
 
+		if let Some(watermark) = &req.watermark {
+			validate_watermark_bucket(global.db(), access_token.organization_id, watermark.value.as_ref()).await?;
+		}
+
 		let query = build_query(req, access_token)?;
 
 		let result: Option<video_common::database::TranscodingConfig> =
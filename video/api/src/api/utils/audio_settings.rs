@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+
+use pb::scuffle::video::v1::types::{AudioCodec, AudioNormalization, AudioRenditionSettings};
+use tonic::Status;
+use video_common::database::Rendition;
+
+const MIN_BITRATE: i64 = 16_000;
+const MAX_BITRATE: i64 = 512_000;
+const MAX_CHANNELS: i32 = 2;
+
+/// The sampling frequencies of an AAC AudioSpecificConfig which have a
+/// sampling_frequency_index.
+/// ISO/IEC 14496-3 - 1.6.3.4
+const AAC_SAMPLE_RATES: [i32; 12] = [
+	96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000,
+];
+/// RFC 6716 - 2
+const OPUS_SAMPLE_RATES: [i32; 5] = [8000, 12000, 16000, 24000, 48000];
+
+/// The ranges the ffmpeg loudnorm filter accepts.
+const MIN_INTEGRATED_LOUDNESS: f64 = -70.0;
+const MAX_INTEGRATED_LOUDNESS: f64 = -5.0;
+const MIN_TRUE_PEAK: f64 = -9.0;
+const MAX_TRUE_PEAK: f64 = 0.0;
+const MIN_LOUDNESS_RANGE: f64 = 1.0;
+const MAX_LOUDNESS_RANGE: f64 = 50.0;
+
+/// Validates the audio encoder settings of a transcoding config.
+/// A field set to 0 means the default of the rendition is used, so it is
+/// always valid.
+pub fn validate_audio_settings(settings: &[AudioRenditionSettings]) -> tonic::Result<()> {
+	let mut seen = HashSet::new();
+
+	for settings in settings {
+		let rendition = Rendition::from(
+			pb::scuffle::video::v1::types::Rendition::try_from(settings.rendition)
+				.map_err(|_| Status::invalid_argument(format!("invalid rendition: {}", settings.rendition)))?,
+		);
+
+		if !rendition.is_audio() || rendition == Rendition::AudioSource {
+			return Err(Status::invalid_argument(format!(
+				"audio settings can only be set for transcoded audio renditions: {rendition}"
+			)));
+		}
+
+		if !seen.insert(rendition) {
+			return Err(Status::invalid_argument(format!(
+				"audio settings specified more than once for rendition: {rendition}"
+			)));
+		}
+
+		if settings.bitrate != 0 && !(MIN_BITRATE..=MAX_BITRATE).contains(&settings.bitrate) {
+			return Err(Status::invalid_argument(format!(
+				"{rendition}: bitrate must be between {MIN_BITRATE} and {MAX_BITRATE}"
+			)));
+		}
+
+		if settings.channels != 0 && !(1..=MAX_CHANNELS).contains(&settings.channels) {
+			return Err(Status::invalid_argument(format!(
+				"{rendition}: channels must be between 1 and {MAX_CHANNELS}"
+			)));
+		}
+
+		let codec = AudioCodec::try_from(settings.codec)
+			.map_err(|_| Status::invalid_argument(format!("{rendition}: invalid codec: {}", settings.codec)))?;
+
+		let (name, sample_rates) = match codec {
+			AudioCodec::Aac => ("aac", AAC_SAMPLE_RATES.as_slice()),
+			AudioCodec::Opus => ("opus", OPUS_SAMPLE_RATES.as_slice()),
+		};
+
+		if settings.sample_rate != 0 && !sample_rates.contains(&settings.sample_rate) {
+			return Err(Status::invalid_argument(format!(
+				"{rendition}: invalid {name} sample rate: {}",
+				settings.sample_rate
+			)));
+		}
+	}
+
+	Ok(())
+}
+
+/// Validates the loudness normalization of a transcoding config.
+/// A field left unset means the EBU R128 default is used.
+pub fn validate_audio_normalization(normalization: Option<&AudioNormalization>) -> tonic::Result<()> {
+	let Some(normalization) = normalization else {
+		return Ok(());
+	};
+
+	let in_range = |value: Option<f64>, min: f64, max: f64| match value {
+		Some(value) => (min..=max).contains(&value),
+		None => true,
+	};
+
+	if !in_range(
+		normalization.integrated_loudness,
+		MIN_INTEGRATED_LOUDNESS,
+		MAX_INTEGRATED_LOUDNESS,
+	) {
+		return Err(Status::invalid_argument(format!(
+			"audio normalization: integrated loudness must be between {MIN_INTEGRATED_LOUDNESS} and {MAX_INTEGRATED_LOUDNESS} LUFS"
+		)));
+	}
+
+	if !in_range(normalization.true_peak, MIN_TRUE_PEAK, MAX_TRUE_PEAK) {
+		return Err(Status::invalid_argument(format!(
+			"audio normalization: true peak must be between {MIN_TRUE_PEAK} and {MAX_TRUE_PEAK} dBTP"
+		)));
+	}
+
+	if !in_range(normalization.loudness_range, MIN_LOUDNESS_RANGE, MAX_LOUDNESS_RANGE) {
+		return Err(Status::invalid_argument(format!(
+			"audio normalization: loudness range must be between {MIN_LOUDNESS_RANGE} and {MAX_LOUDNESS_RANGE} LU"
+		)));
+	}
+
+	Ok(())
+}
@@ -1,8 +1,11 @@
 pub mod access_tokens;
+pub mod audio_settings;
 pub mod auth;
 pub mod get;
 pub mod ratelimit;
 pub mod tags;
+pub mod video_settings;
+pub mod watermark;
 
 use std::sync::Arc;
 
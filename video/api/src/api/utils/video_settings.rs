@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+
+use pb::scuffle::video::v1::types::{VideoCodec, VideoRenditionSettings};
+use tonic::Status;
+use video_common::database::Rendition;
+
+const MIN_SIDE: i32 = 100;
+const MAX_SIDE: i32 = 2160;
+const MAX_FPS: i32 = 120;
+const MIN_BITRATE: i64 = 100_000;
+const MAX_BITRATE: i64 = 50_000_000;
+const MIN_KEYFRAME_INTERVAL_MS: i32 = 250;
+const MAX_KEYFRAME_INTERVAL_MS: i32 = 10_000;
+
+const AVC_PROFILES: [i32; 3] = [66, 77, 100];
+const AVC_LEVELS: [i32; 12] = [30, 31, 32, 40, 41, 42, 50, 51, 52, 60, 61, 62];
+
+/// Only the 8-bit main profile, the transcoder does not convert the pixel
+/// format for the 10-bit profiles.
+const HEVC_PROFILES: [i32; 1] = [1];
+/// general_level_idc, 30 times the level, from level 3 to 6.2.
+const HEVC_LEVELS: [i32; 10] = [90, 93, 120, 123, 150, 153, 156, 180, 183, 186];
+
+/// Only the main profile, which is 8-bit or 10-bit 4:2:0.
+const AV1_PROFILES: [i32; 1] = [0];
+/// seq_level_idx from level 3.0 to 6.3, the levels 3.2, 3.3, 4.2 and 4.3 are
+/// not defined.
+/// AV1 Bitstream & Decoding Process Specification - Annex A.3
+const AV1_LEVELS: [i32; 12] = [4, 5, 8, 9, 12, 13, 14, 15, 16, 17, 18, 19];
+
+/// Validates the encoder settings of a transcoding config.
+/// A field set to 0 means the default of the rendition is used, so it is
+/// always valid.
+pub fn validate_video_settings(settings: &[VideoRenditionSettings]) -> tonic::Result<()> {
+	let mut seen = HashSet::new();
+
+	for settings in settings {
+		let rendition = Rendition::from(
+			pb::scuffle::video::v1::types::Rendition::try_from(settings.rendition)
+				.map_err(|_| Status::invalid_argument(format!("invalid rendition: {}", settings.rendition)))?,
+		);
+
+		if !rendition.is_video() || rendition == Rendition::VideoSource {
+			return Err(Status::invalid_argument(format!(
+				"video settings can only be set for transcoded video renditions: {rendition}"
+			)));
+		}
+
+		if !seen.insert(rendition) {
+			return Err(Status::invalid_argument(format!(
+				"video settings specified more than once for rendition: {rendition}"
+			)));
+		}
+
+		if settings.side != 0 && !(MIN_SIDE..=MAX_SIDE).contains(&settings.side) {
+			return Err(Status::invalid_argument(format!(
+				"{rendition}: side must be between {MIN_SIDE} and {MAX_SIDE}"
+			)));
+		}
+
+		if settings.max_fps != 0 && !(1..=MAX_FPS).contains(&settings.max_fps) {
+			return Err(Status::invalid_argument(format!(
+				"{rendition}: max fps must be between 1 and {MAX_FPS}"
+			)));
+		}
+
+		if settings.bitrate != 0 && !(MIN_BITRATE..=MAX_BITRATE).contains(&settings.bitrate) {
+			return Err(Status::invalid_argument(format!(
+				"{rendition}: bitrate must be between {MIN_BITRATE} and {MAX_BITRATE}"
+			)));
+		}
+
+		if settings.keyframe_interval_ms != 0
+			&& !(MIN_KEYFRAME_INTERVAL_MS..=MAX_KEYFRAME_INTERVAL_MS).contains(&settings.keyframe_interval_ms)
+		{
+			return Err(Status::invalid_argument(format!(
+				"{rendition}: keyframe interval must be between {MIN_KEYFRAME_INTERVAL_MS}ms and {MAX_KEYFRAME_INTERVAL_MS}ms"
+			)));
+		}
+
+		let codec = VideoCodec::try_from(settings.codec)
+			.map_err(|_| Status::invalid_argument(format!("{rendition}: invalid codec: {}", settings.codec)))?;
+
+		match codec {
+			VideoCodec::Avc => {
+				if settings.profile != 0 && !AVC_PROFILES.contains(&settings.profile) {
+					return Err(Status::invalid_argument(format!(
+						"{rendition}: invalid avc profile: {}",
+						settings.profile
+					)));
+				}
+
+				if settings.level != 0 && !AVC_LEVELS.contains(&settings.level) {
+					return Err(Status::invalid_argument(format!(
+						"{rendition}: invalid avc level: {}",
+						settings.level
+					)));
+				}
+			}
+			VideoCodec::Hevc => {
+				if settings.profile != 0 && !HEVC_PROFILES.contains(&settings.profile) {
+					return Err(Status::invalid_argument(format!(
+						"{rendition}: invalid hevc profile: {}",
+						settings.profile
+					)));
+				}
+
+				if settings.level != 0 && !HEVC_LEVELS.contains(&settings.level) {
+					return Err(Status::invalid_argument(format!(
+						"{rendition}: invalid hevc level: {}",
+						settings.level
+					)));
+				}
+			}
+			VideoCodec::Av1 => {
+				if settings.profile != 0 && !AV1_PROFILES.contains(&settings.profile) {
+					return Err(Status::invalid_argument(format!(
+						"{rendition}: invalid av1 profile: {}",
+						settings.profile
+					)));
+				}
+
+				if settings.level != 0 && !AV1_LEVELS.contains(&settings.level) {
+					return Err(Status::invalid_argument(format!(
+						"{rendition}: invalid av1 level: {}",
+						settings.level
+					)));
+				}
+			}
+		}
+	}
+
+	Ok(())
+}
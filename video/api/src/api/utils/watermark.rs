@@ -0,0 +1,160 @@
+use pb::ext::UlidExt;
+use pb::scuffle::video::v1::types::{watermark, Watermark, WatermarkPosition};
+use tonic::Status;
+use ulid::Ulid;
+use utils::database::IntoClient;
+use video_common::database::S3Bucket;
+
+const MAX_KEY_LENGTH: usize = 1024;
+const MAX_TEXT_LENGTH: usize = 64;
+
+const MIN_SCALE: f64 = 0.01;
+const MAX_SCALE: f64 = 1.0;
+const MIN_OPACITY: f64 = 0.01;
+const MAX_OPACITY: f64 = 1.0;
+const MIN_MARGIN: f64 = 0.001;
+const MAX_MARGIN: f64 = 0.25;
+const MIN_TEXT_SIZE: f64 = 0.01;
+const MAX_TEXT_SIZE: f64 = 0.25;
+
+/// A field set to 0 means the default is used, so it is always valid.
+fn in_range(value: f64, min: f64, max: f64) -> bool {
+	value == 0.0 || (min..=max).contains(&value)
+}
+
+fn validate_position(name: &str, position: i32) -> tonic::Result<()> {
+	WatermarkPosition::try_from(position)
+		.map(|_| ())
+		.map_err(|_| Status::invalid_argument(format!("watermark {name}: invalid position: {position}")))
+}
+
+fn validate_image(image: &watermark::Image) -> tonic::Result<()> {
+	if image.s3_bucket_id.is_none() {
+		return Err(Status::invalid_argument("watermark image: s3 bucket id is required"));
+	}
+
+	if image.key.is_empty() || image.key.len() > MAX_KEY_LENGTH {
+		return Err(Status::invalid_argument(format!(
+			"watermark image: key must be between 1 and {MAX_KEY_LENGTH} characters"
+		)));
+	}
+
+	validate_position("image", image.position)?;
+
+	if !in_range(image.scale, MIN_SCALE, MAX_SCALE) {
+		return Err(Status::invalid_argument(format!(
+			"watermark image: scale must be between {MIN_SCALE} and {MAX_SCALE}"
+		)));
+	}
+
+	if !in_range(image.opacity, MIN_OPACITY, MAX_OPACITY) {
+		return Err(Status::invalid_argument(format!(
+			"watermark image: opacity must be between {MIN_OPACITY} and {MAX_OPACITY}"
+		)));
+	}
+
+	if !in_range(image.margin, MIN_MARGIN, MAX_MARGIN) {
+		return Err(Status::invalid_argument(format!(
+			"watermark image: margin must be between {MIN_MARGIN} and {MAX_MARGIN}"
+		)));
+	}
+
+	Ok(())
+}
+
+fn validate_text(text: &watermark::Text) -> tonic::Result<()> {
+	if text.text.is_empty() && !text.timestamp {
+		return Err(Status::invalid_argument("watermark text: text or timestamp is required"));
+	}
+
+	if text.text.chars().count() > MAX_TEXT_LENGTH {
+		return Err(Status::invalid_argument(format!(
+			"watermark text: text must be at most {MAX_TEXT_LENGTH} characters"
+		)));
+	}
+
+	if text.text.chars().any(char::is_control) {
+		return Err(Status::invalid_argument(
+			"watermark text: text must not contain control characters",
+		));
+	}
+
+	validate_position("text", text.position)?;
+
+	if !in_range(text.size, MIN_TEXT_SIZE, MAX_TEXT_SIZE) {
+		return Err(Status::invalid_argument(format!(
+			"watermark text: size must be between {MIN_TEXT_SIZE} and {MAX_TEXT_SIZE}"
+		)));
+	}
+
+	if !text.color.is_empty()
+		&& !(text.color.len() == 7 && text.color.starts_with('#') && text.color[1..].chars().all(|c| c.is_ascii_hexdigit()))
+	{
+		return Err(Status::invalid_argument(format!(
+			"watermark text: invalid color: {}, expected a hex triplet such as #FFFFFF",
+			text.color
+		)));
+	}
+
+	if !in_range(text.opacity, MIN_OPACITY, MAX_OPACITY) {
+		return Err(Status::invalid_argument(format!(
+			"watermark text: opacity must be between {MIN_OPACITY} and {MAX_OPACITY}"
+		)));
+	}
+
+	if !in_range(text.margin, MIN_MARGIN, MAX_MARGIN) {
+		return Err(Status::invalid_argument(format!(
+			"watermark text: margin must be between {MIN_MARGIN} and {MAX_MARGIN}"
+		)));
+	}
+
+	Ok(())
+}
+
+/// Validates the watermark of a transcoding config.
+pub fn validate_watermark(watermark: Option<&Watermark>) -> tonic::Result<()> {
+	let Some(watermark) = watermark else {
+		return Ok(());
+	};
+
+	if watermark.image.is_none() && watermark.text.is_none() {
+		return Err(Status::invalid_argument("watermark: image or text is required"));
+	}
+
+	if let Some(image) = &watermark.image {
+		validate_image(image)?;
+	}
+
+	if let Some(text) = &watermark.text {
+		validate_text(text)?;
+	}
+
+	Ok(())
+}
+
+/// Checks that the S3 bucket of the watermark image belongs to the
+/// organization.
+pub async fn validate_watermark_bucket(
+	client: impl IntoClient,
+	organization_id: Ulid,
+	watermark: Option<&Watermark>,
+) -> tonic::Result<()> {
+	let Some(image) = watermark.and_then(|w| w.image.as_ref()) else {
+		return Ok(());
+	};
+
+	let bucket: Option<S3Bucket> = utils::database::query("SELECT * FROM s3_buckets WHERE id = $1 AND organization_id = $2")
+		.bind(image.s3_bucket_id.into_ulid())
+		.bind(organization_id)
+		.build_query_as()
+		.fetch_optional(client)
+		.await
+		.map_err(|err| {
+			tracing::error!(err = %err, "failed to query s3 bucket");
+			Status::internal("failed to query s3 buckets")
+		})?;
+
+	bucket.ok_or_else(|| Status::not_found("s3 bucket not found"))?;
+
+	Ok(())
+}
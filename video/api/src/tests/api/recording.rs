@@ -17,8 +17,8 @@ use video_common::database::{AccessToken, Rendition};
 use crate::api::recording::RecordingServer;
 use crate::config::ApiConfig;
 use crate::tests::api::utils::{
-	create_recording, create_recording_config, create_recording_segment, create_recording_thumbnail, create_room,
-	create_s3_bucket, process_request,
+	create_recording, create_recording_config, create_recording_segment, create_recording_thumbnail,
+	create_recording_thumbnail_sprite, create_room, create_s3_bucket, process_request,
 };
 use crate::tests::global::GlobalState;
 use crate::tests::utils;
@@ -361,6 +361,17 @@ async fn test_recording_delete() {
 	.map(|t| (t.id, t.idx))
 	.collect::<HashSet<_>>();
 
+	let mut sprites = create_recording_thumbnail_sprite(
+		&global,
+		access_token.organization_id,
+		recording.id,
+		(0..173).map(|i| (i, (0..25).map(|t| (i * 25 + t) as f32 * 5.0).collect())),
+	)
+	.await
+	.into_iter()
+	.map(|s| (s.id, s.idx))
+	.collect::<HashSet<_>>();
+
 	let mut segments = create_recording_segment(
 		&global,
 		access_token.organization_id,
@@ -426,11 +437,20 @@ async fn test_recording_delete() {
 					)
 				}
 			}
+			pb::scuffle::video::internal::events::recording_delete_batch_task::ObjectsType::ThumbnailSprites(_) => {
+				for obj in msg.objects {
+					assert!(
+						sprites.remove(&(obj.object_id.into_ulid(), obj.index)),
+						"expected thumbnail sprite to be deleted"
+					)
+				}
+			}
 		}
 	}
 
-	assert_eq!(count, 60, "expected 60 messages");
+	assert_eq!(count, 83, "expected 83 messages");
 	assert!(thumbnails.is_empty(), "expected all thumbnails to be deleted");
+	assert!(sprites.is_empty(), "expected all thumbnail sprites to be deleted");
 	assert!(segments.is_empty(), "expected all segments to be deleted");
 
 	utils::teardown(global, handler).await;
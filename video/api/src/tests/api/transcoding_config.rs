@@ -2,18 +2,24 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use pb::ext::UlidExt;
-use pb::scuffle::video::v1::transcoding_config_modify_request::RenditionList;
-use pb::scuffle::video::v1::types::{SearchOptions, Tags};
+use pb::scuffle::video::v1::transcoding_config_modify_request::{
+	AudioNormalizationUpdate, AudioSettingsList, RenditionList, VideoSettingsList, WatermarkUpdate,
+};
+use pb::scuffle::video::v1::types::{
+	watermark, AudioCodec, AudioNormalization, AudioRenditionSettings, Rendition, SearchOptions, Tags, VideoCodec,
+	VideoRenditionSettings, Watermark, WatermarkPosition,
+};
 use pb::scuffle::video::v1::{
 	TranscodingConfigCreateRequest, TranscodingConfigCreateResponse, TranscodingConfigDeleteRequest,
 	TranscodingConfigDeleteResponse, TranscodingConfigGetRequest, TranscodingConfigGetResponse,
 	TranscodingConfigModifyRequest, TranscodingConfigModifyResponse, TranscodingConfigTagRequest,
 	TranscodingConfigTagResponse, TranscodingConfigUntagRequest, TranscodingConfigUntagResponse,
 };
+use ulid::Ulid;
 use video_common::database::AccessToken;
 
 use crate::api::transcoding_config::{self, TranscodingConfigServer};
-use crate::tests::api::utils::{assert_query_matches, create_transcoding_config, process_request};
+use crate::tests::api::utils::{assert_query_matches, create_s3_bucket, create_transcoding_config, process_request};
 use crate::tests::global::GlobalState;
 use crate::tests::utils;
 
@@ -68,8 +74,14 @@ async fn test_transcoding_config_create_qb() {
 				pb::scuffle::video::v1::types::Rendition::VideoSource as i32,
 				pb::scuffle::video::v1::types::Rendition::AudioSource as i32,
 			],
+			video_settings: vec![],
+			audio_settings: vec![],
+			audio_normalization: None,
+			watermark: None,
 		},
-		Ok("INSERT INTO transcoding_configs (id,organization_id,renditions,tags) VALUES ($1,$2,$3,$4) RETURNING *"),
+		Ok(
+			"INSERT INTO transcoding_configs (id,organization_id,renditions,video_settings,audio_settings,audio_normalization,watermark,tags) VALUES ($1,$2,$3,$4,$5,$6,$7,$8) RETURNING *",
+		),
 	)];
 
 	for (req, expected) in test_cases {
@@ -96,6 +108,10 @@ async fn test_transcoding_config_modify_qb() {
 						pb::scuffle::video::v1::types::Rendition::AudioSource as i32,
 					],
 				}),
+				video_settings: None,
+				audio_settings: None,
+				audio_normalization: None,
+				watermark: None,
 			},
 			Ok(
 				"UPDATE transcoding_configs SET renditions = $1,updated_at = NOW() WHERE id = $2 AND organization_id = $3 RETURNING *",
@@ -110,6 +126,10 @@ async fn test_transcoding_config_modify_qb() {
 						.collect(),
 				}),
 				renditions: None,
+				video_settings: None,
+				audio_settings: None,
+				audio_normalization: None,
+				watermark: None,
 			},
 			Ok(
 				"UPDATE transcoding_configs SET tags = $1,updated_at = NOW() WHERE id = $2 AND organization_id = $3 RETURNING *",
@@ -129,6 +149,10 @@ async fn test_transcoding_config_modify_qb() {
 						pb::scuffle::video::v1::types::Rendition::AudioSource as i32,
 					],
 				}),
+				video_settings: None,
+				audio_settings: None,
+				audio_normalization: None,
+				watermark: None,
 			},
 			Ok(
 				"UPDATE transcoding_configs SET renditions = $1,tags = $2,updated_at = NOW() WHERE id = $3 AND organization_id = $4 RETURNING *",
@@ -139,6 +163,64 @@ async fn test_transcoding_config_modify_qb() {
 				id: Some(access_token.id.into()),
 				tags: None,
 				renditions: None,
+				video_settings: Some(VideoSettingsList {
+					items: vec![VideoRenditionSettings {
+						rendition: Rendition::VideoFhd as i32,
+						bitrate: 8000 * 1024,
+						..Default::default()
+					}],
+				}),
+				audio_settings: None,
+				audio_normalization: None,
+				watermark: None,
+			},
+			Ok(
+				"UPDATE transcoding_configs SET video_settings = $1,updated_at = NOW() WHERE id = $2 AND organization_id = $3 RETURNING *",
+			),
+		),
+		(
+			TranscodingConfigModifyRequest {
+				id: Some(access_token.id.into()),
+				tags: None,
+				renditions: None,
+				video_settings: None,
+				audio_settings: Some(AudioSettingsList {
+					items: vec![AudioRenditionSettings {
+						rendition: Rendition::AudioHigh as i32,
+						codec: AudioCodec::Opus as i32,
+						..Default::default()
+					}],
+				}),
+				audio_normalization: Some(AudioNormalizationUpdate { value: None }),
+				watermark: None,
+			},
+			Ok(
+				"UPDATE transcoding_configs SET audio_settings = $1,audio_normalization = $2,updated_at = NOW() WHERE id = $3 AND organization_id = $4 RETURNING *",
+			),
+		),
+		(
+			TranscodingConfigModifyRequest {
+				id: Some(access_token.id.into()),
+				tags: None,
+				renditions: None,
+				video_settings: None,
+				audio_settings: None,
+				audio_normalization: None,
+				watermark: Some(WatermarkUpdate { value: None }),
+			},
+			Ok(
+				"UPDATE transcoding_configs SET watermark = $1,updated_at = NOW() WHERE id = $2 AND organization_id = $3 RETURNING *",
+			),
+		),
+		(
+			TranscodingConfigModifyRequest {
+				id: Some(access_token.id.into()),
+				tags: None,
+				renditions: None,
+				video_settings: None,
+				audio_settings: None,
+				audio_normalization: None,
+				watermark: None,
 			},
 			Err("at least one field must be set to modify"),
 		),
@@ -153,6 +235,496 @@ async fn test_transcoding_config_modify_qb() {
 	utils::teardown(global, handler).await;
 }
 
+#[test]
+fn test_transcoding_config_video_settings_validate() {
+	let settings = |settings: VideoRenditionSettings| TranscodingConfigCreateRequest {
+		renditions: vec![
+			Rendition::VideoSource as i32,
+			Rendition::VideoQhd as i32,
+			Rendition::VideoFhd as i32,
+			Rendition::AudioSource as i32,
+		],
+		video_settings: vec![settings],
+		audio_settings: vec![],
+		audio_normalization: None,
+		watermark: None,
+		tags: None,
+	};
+
+	let test_cases = vec![
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoQhd as i32,
+				side: 1440,
+				max_fps: 60,
+				bitrate: 12_000_000,
+				profile: 100,
+				level: 51,
+				keyframe_interval_ms: 1000,
+				..Default::default()
+			}),
+			Ok(()),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				..Default::default()
+			}),
+			Ok(()),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoSource as i32,
+				..Default::default()
+			}),
+			Err("video settings can only be set for transcoded video renditions: video_source"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::AudioSource as i32,
+				..Default::default()
+			}),
+			Err("video settings can only be set for transcoded video renditions: audio_source"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				side: 4320,
+				..Default::default()
+			}),
+			Err("video_fhd: side must be between 100 and 2160"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				max_fps: 240,
+				..Default::default()
+			}),
+			Err("video_fhd: max fps must be between 1 and 120"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				bitrate: 1000,
+				..Default::default()
+			}),
+			Err("video_fhd: bitrate must be between 100000 and 50000000"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				profile: 44,
+				..Default::default()
+			}),
+			Err("video_fhd: invalid avc profile: 44"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				level: 53,
+				..Default::default()
+			}),
+			Err("video_fhd: invalid avc level: 53"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				keyframe_interval_ms: 60_000,
+				..Default::default()
+			}),
+			Err("video_fhd: keyframe interval must be between 250ms and 10000ms"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoQhd as i32,
+				codec: VideoCodec::Hevc as i32,
+				profile: 1,
+				level: 153,
+				..Default::default()
+			}),
+			Ok(()),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				codec: VideoCodec::Hevc as i32,
+				level: 51,
+				..Default::default()
+			}),
+			Err("video_fhd: invalid hevc level: 51"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				codec: VideoCodec::Hevc as i32,
+				profile: 2,
+				..Default::default()
+			}),
+			Err("video_fhd: invalid hevc profile: 2"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoQhd as i32,
+				codec: VideoCodec::Av1 as i32,
+				level: 13,
+				..Default::default()
+			}),
+			Ok(()),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				codec: VideoCodec::Av1 as i32,
+				level: 10,
+				..Default::default()
+			}),
+			Err("video_fhd: invalid av1 level: 10"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				codec: VideoCodec::Av1 as i32,
+				profile: 1,
+				..Default::default()
+			}),
+			Err("video_fhd: invalid av1 profile: 1"),
+		),
+		(
+			settings(VideoRenditionSettings {
+				rendition: Rendition::VideoFhd as i32,
+				codec: 7,
+				..Default::default()
+			}),
+			Err("video_fhd: invalid codec: 7"),
+		),
+		(
+			TranscodingConfigCreateRequest {
+				video_settings: vec![
+					VideoRenditionSettings {
+						rendition: Rendition::VideoFhd as i32,
+						..Default::default()
+					},
+					VideoRenditionSettings {
+						rendition: Rendition::VideoFhd as i32,
+						side: 900,
+						..Default::default()
+					},
+				],
+				..Default::default()
+			},
+			Err("video settings specified more than once for rendition: video_fhd"),
+		),
+	];
+
+	for (req, expected) in test_cases {
+		let result = transcoding_config::create::validate(&req);
+		match expected {
+			Ok(()) => assert!(result.is_ok(), "{result:?}"),
+			Err(message) => assert_eq!(result.unwrap_err().message(), message),
+		}
+	}
+}
+
+#[test]
+fn test_transcoding_config_audio_settings_validate() {
+	let settings = |settings: AudioRenditionSettings| TranscodingConfigCreateRequest {
+		renditions: vec![
+			Rendition::VideoSource as i32,
+			Rendition::AudioSource as i32,
+			Rendition::AudioHigh as i32,
+			Rendition::AudioLow as i32,
+		],
+		video_settings: vec![],
+		audio_settings: vec![settings],
+		audio_normalization: None,
+		watermark: None,
+		tags: None,
+	};
+
+	let normalization = |normalization: AudioNormalization| TranscodingConfigCreateRequest {
+		audio_normalization: Some(normalization),
+		..Default::default()
+	};
+
+	let test_cases = vec![
+		(
+			settings(AudioRenditionSettings {
+				rendition: Rendition::AudioHigh as i32,
+				codec: AudioCodec::Opus as i32,
+				bitrate: 128_000,
+				sample_rate: 48000,
+				channels: 2,
+			}),
+			Ok(()),
+		),
+		(
+			settings(AudioRenditionSettings {
+				rendition: Rendition::AudioLow as i32,
+				..Default::default()
+			}),
+			Ok(()),
+		),
+		(
+			settings(AudioRenditionSettings {
+				rendition: Rendition::AudioSource as i32,
+				..Default::default()
+			}),
+			Err("audio settings can only be set for transcoded audio renditions: audio_source"),
+		),
+		(
+			settings(AudioRenditionSettings {
+				rendition: Rendition::VideoHd as i32,
+				..Default::default()
+			}),
+			Err("audio settings can only be set for transcoded audio renditions: video_hd"),
+		),
+		(
+			settings(AudioRenditionSettings {
+				rendition: Rendition::AudioHigh as i32,
+				bitrate: 8000,
+				..Default::default()
+			}),
+			Err("audio_high: bitrate must be between 16000 and 512000"),
+		),
+		(
+			settings(AudioRenditionSettings {
+				rendition: Rendition::AudioHigh as i32,
+				channels: 6,
+				..Default::default()
+			}),
+			Err("audio_high: channels must be between 1 and 2"),
+		),
+		(
+			settings(AudioRenditionSettings {
+				rendition: Rendition::AudioLow as i32,
+				codec: 5,
+				..Default::default()
+			}),
+			Err("audio_low: invalid codec: 5"),
+		),
+		(
+			settings(AudioRenditionSettings {
+				rendition: Rendition::AudioLow as i32,
+				codec: AudioCodec::Aac as i32,
+				sample_rate: 44100,
+				..Default::default()
+			}),
+			Ok(()),
+		),
+		(
+			settings(AudioRenditionSettings {
+				rendition: Rendition::AudioLow as i32,
+				codec: AudioCodec::Opus as i32,
+				sample_rate: 44100,
+				..Default::default()
+			}),
+			Err("audio_low: invalid opus sample rate: 44100"),
+		),
+		(
+			TranscodingConfigCreateRequest {
+				audio_settings: vec![
+					AudioRenditionSettings {
+						rendition: Rendition::AudioHigh as i32,
+						..Default::default()
+					},
+					AudioRenditionSettings {
+						rendition: Rendition::AudioHigh as i32,
+						bitrate: 96_000,
+						..Default::default()
+					},
+				],
+				..Default::default()
+			},
+			Err("audio settings specified more than once for rendition: audio_high"),
+		),
+		(normalization(AudioNormalization::default()), Ok(())),
+		(
+			normalization(AudioNormalization {
+				integrated_loudness: Some(-16.0),
+				true_peak: Some(-1.5),
+				loudness_range: Some(11.0),
+			}),
+			Ok(()),
+		),
+		(
+			normalization(AudioNormalization {
+				true_peak: Some(0.0),
+				..Default::default()
+			}),
+			Ok(()),
+		),
+		(
+			normalization(AudioNormalization {
+				integrated_loudness: Some(-80.0),
+				..Default::default()
+			}),
+			Err("audio normalization: integrated loudness must be between -70 and -5 LUFS"),
+		),
+		(
+			normalization(AudioNormalization {
+				true_peak: Some(1.0),
+				..Default::default()
+			}),
+			Err("audio normalization: true peak must be between -9 and 0 dBTP"),
+		),
+		(
+			normalization(AudioNormalization {
+				loudness_range: Some(60.0),
+				..Default::default()
+			}),
+			Err("audio normalization: loudness range must be between 1 and 50 LU"),
+		),
+	];
+
+	for (req, expected) in test_cases {
+		let result = transcoding_config::create::validate(&req);
+		match expected {
+			Ok(()) => assert!(result.is_ok(), "{result:?}"),
+			Err(message) => assert_eq!(result.unwrap_err().message(), message),
+		}
+	}
+}
+
+#[test]
+fn test_transcoding_config_watermark_validate() {
+	let image = |image: watermark::Image| TranscodingConfigCreateRequest {
+		watermark: Some(Watermark {
+			image: Some(watermark::Image {
+				s3_bucket_id: Some(Ulid::new().into()),
+				key: "logo.png".to_string(),
+				..image
+			}),
+			text: None,
+		}),
+		..Default::default()
+	};
+
+	let text = |text: watermark::Text| TranscodingConfigCreateRequest {
+		watermark: Some(Watermark {
+			image: None,
+			text: Some(text),
+		}),
+		..Default::default()
+	};
+
+	let test_cases = vec![
+		(
+			image(watermark::Image {
+				position: WatermarkPosition::TopLeft as i32,
+				scale: 0.2,
+				opacity: 0.5,
+				margin: 0.01,
+				..Default::default()
+			}),
+			Ok(()),
+		),
+		(image(Default::default()), Ok(())),
+		(
+			TranscodingConfigCreateRequest {
+				watermark: Some(Watermark::default()),
+				..Default::default()
+			},
+			Err("watermark: image or text is required"),
+		),
+		(
+			TranscodingConfigCreateRequest {
+				watermark: Some(Watermark {
+					image: Some(watermark::Image {
+						key: "logo.png".to_string(),
+						..Default::default()
+					}),
+					text: None,
+				}),
+				..Default::default()
+			},
+			Err("watermark image: s3 bucket id is required"),
+		),
+		(
+			image(watermark::Image {
+				position: 9,
+				..Default::default()
+			}),
+			Err("watermark image: invalid position: 9"),
+		),
+		(
+			image(watermark::Image {
+				scale: 2.0,
+				..Default::default()
+			}),
+			Err("watermark image: scale must be between 0.01 and 1"),
+		),
+		(
+			image(watermark::Image {
+				opacity: -0.5,
+				..Default::default()
+			}),
+			Err("watermark image: opacity must be between 0.01 and 1"),
+		),
+		(
+			image(watermark::Image {
+				margin: 0.5,
+				..Default::default()
+			}),
+			Err("watermark image: margin must be between 0.001 and 0.25"),
+		),
+		(
+			text(watermark::Text {
+				text: "LIVE".to_string(),
+				color: "#FF0000".to_string(),
+				background: true,
+				..Default::default()
+			}),
+			Ok(()),
+		),
+		(
+			text(watermark::Text {
+				timestamp: true,
+				..Default::default()
+			}),
+			Ok(()),
+		),
+		(text(Default::default()), Err("watermark text: text or timestamp is required")),
+		(
+			text(watermark::Text {
+				text: "a".repeat(65),
+				..Default::default()
+			}),
+			Err("watermark text: text must be at most 64 characters"),
+		),
+		(
+			text(watermark::Text {
+				text: "LIVE\n".to_string(),
+				..Default::default()
+			}),
+			Err("watermark text: text must not contain control characters"),
+		),
+		(
+			text(watermark::Text {
+				text: "LIVE".to_string(),
+				size: 0.5,
+				..Default::default()
+			}),
+			Err("watermark text: size must be between 0.01 and 0.25"),
+		),
+		(
+			text(watermark::Text {
+				text: "LIVE".to_string(),
+				color: "red".to_string(),
+				..Default::default()
+			}),
+			Err("watermark text: invalid color: red, expected a hex triplet such as #FFFFFF"),
+		),
+	];
+
+	for (req, expected) in test_cases {
+		let result = transcoding_config::create::validate(&req);
+		match expected {
+			Ok(()) => assert!(result.is_ok(), "{result:?}"),
+			Err(message) => assert_eq!(result.unwrap_err().message(), message),
+		}
+	}
+}
+
 #[tokio::test]
 async fn test_transcoding_config_tag_qb() {
 	let (global, handler, access_token) = utils::setup(Default::default()).await;
@@ -280,6 +852,10 @@ async fn test_transcoding_config_create() {
 				pb::scuffle::video::v1::types::Rendition::AudioSource as i32,
 			],
 			tags: None,
+			video_settings: vec![],
+			audio_settings: vec![],
+			audio_normalization: None,
+			watermark: None,
 		},
 	)
 	.await
@@ -298,6 +874,10 @@ async fn test_transcoding_config_create() {
 			tags: Some(Tags {
 				tags: vec![("tag_key".to_string(), "tag_value".to_string())].into_iter().collect(),
 			}),
+			video_settings: vec![],
+			audio_settings: vec![],
+			audio_normalization: None,
+			watermark: None,
 		},
 	)
 	.await
@@ -314,6 +894,159 @@ async fn test_transcoding_config_create() {
 		]
 	);
 
+	let video_settings = vec![
+		VideoRenditionSettings {
+			rendition: Rendition::VideoQhd as i32,
+			bitrate: 12_000_000,
+			max_fps: 60,
+			..Default::default()
+		},
+		VideoRenditionSettings {
+			rendition: Rendition::VideoFhd as i32,
+			side: 1080,
+			profile: 77,
+			level: 42,
+			keyframe_interval_ms: 1000,
+			..Default::default()
+		},
+	];
+
+	let response: TranscodingConfigCreateResponse = process_request(
+		&global,
+		&access_token,
+		TranscodingConfigCreateRequest {
+			renditions: vec![
+				Rendition::VideoQhd as i32,
+				Rendition::VideoFhd as i32,
+				Rendition::AudioSource as i32,
+			],
+			video_settings: video_settings.clone(),
+			audio_settings: vec![],
+			audio_normalization: None,
+			watermark: None,
+			tags: None,
+		},
+	)
+	.await
+	.unwrap();
+	let created = response.transcoding_config.as_ref().unwrap();
+	assert_eq!(
+		created.renditions,
+		vec![
+			Rendition::AudioSource as i32,
+			Rendition::VideoFhd as i32,
+			Rendition::VideoQhd as i32,
+		]
+	);
+	assert_eq!(created.video_settings, video_settings);
+
+	let audio_settings = vec![
+		AudioRenditionSettings {
+			rendition: Rendition::AudioHigh as i32,
+			codec: AudioCodec::Opus as i32,
+			bitrate: 128_000,
+			..Default::default()
+		},
+		AudioRenditionSettings {
+			rendition: Rendition::AudioLow as i32,
+			codec: AudioCodec::Aac as i32,
+			bitrate: 64_000,
+			sample_rate: 44100,
+			channels: 1,
+		},
+	];
+
+	let audio_normalization = AudioNormalization {
+		integrated_loudness: Some(-16.0),
+		..Default::default()
+	};
+
+	let response: TranscodingConfigCreateResponse = process_request(
+		&global,
+		&access_token,
+		TranscodingConfigCreateRequest {
+			renditions: vec![
+				Rendition::VideoSource as i32,
+				Rendition::AudioHigh as i32,
+				Rendition::AudioLow as i32,
+			],
+			video_settings: vec![],
+			audio_settings: audio_settings.clone(),
+			audio_normalization: Some(audio_normalization.clone()),
+			watermark: None,
+			tags: None,
+		},
+	)
+	.await
+	.unwrap();
+	let created = response.transcoding_config.as_ref().unwrap();
+	assert_eq!(
+		created.renditions,
+		vec![
+			Rendition::VideoSource as i32,
+			Rendition::AudioHigh as i32,
+			Rendition::AudioLow as i32,
+		]
+	);
+	assert_eq!(created.audio_settings, audio_settings);
+	assert_eq!(created.audio_normalization, Some(audio_normalization));
+
+	let s3_bucket = create_s3_bucket(&global, access_token.organization_id, HashMap::new()).await;
+
+	let watermark = Watermark {
+		image: Some(watermark::Image {
+			s3_bucket_id: Some(s3_bucket.id.into()),
+			key: "logo.png".to_string(),
+			position: WatermarkPosition::TopRight as i32,
+			..Default::default()
+		}),
+		text: Some(watermark::Text {
+			text: "LIVE".to_string(),
+			..Default::default()
+		}),
+	};
+
+	let response: TranscodingConfigCreateResponse = process_request(
+		&global,
+		&access_token,
+		TranscodingConfigCreateRequest {
+			renditions: vec![Rendition::VideoSource as i32, Rendition::AudioSource as i32],
+			video_settings: vec![],
+			audio_settings: vec![],
+			audio_normalization: None,
+			watermark: Some(watermark.clone()),
+			tags: None,
+		},
+	)
+	.await
+	.unwrap();
+	let created = response.transcoding_config.as_ref().unwrap();
+	assert_eq!(created.watermark, Some(watermark.clone()));
+
+	// The image has to be in a bucket of the organization.
+	let err = process_request::<_, TranscodingConfigCreateResponse>(
+		&global,
+		&access_token,
+		TranscodingConfigCreateRequest {
+			renditions: vec![Rendition::VideoSource as i32, Rendition::AudioSource as i32],
+			video_settings: vec![],
+			audio_settings: vec![],
+			audio_normalization: None,
+			watermark: Some(Watermark {
+				image: Some(watermark::Image {
+					s3_bucket_id: Some(Ulid::new().into()),
+					..watermark.image.clone().unwrap()
+				}),
+				text: None,
+			}),
+			tags: None,
+		},
+	)
+	.await
+	.unwrap_err();
+	assert_eq!(err.code(), tonic::Code::NotFound);
+	assert_eq!(err.message(), "s3 bucket not found");
+
 	utils::teardown(global, handler).await;
 }
 
@@ -342,6 +1075,10 @@ async fn test_transcoding_config_modify() {
 			tags: Some(Tags {
 				tags: vec![("key3".to_string(), "value3".to_string())].into_iter().collect(),
 			}),
+			video_settings: None,
+			audio_settings: None,
+			audio_normalization: None,
+			watermark: None,
 		},
 	)
 	.await
@@ -366,6 +1103,10 @@ async fn test_transcoding_config_modify() {
 				],
 			}),
 			tags: None,
+			video_settings: None,
+			audio_settings: None,
+			audio_normalization: None,
+			watermark: None,
 		},
 	)
 	.await
@@ -595,6 +1336,10 @@ async fn test_transcoding_config_boiler_plate() {
 					pb::scuffle::video::v1::types::Rendition::AudioSource as i32,
 				],
 				tags: None,
+				video_settings: vec![],
+				audio_settings: vec![],
+				audio_normalization: None,
+				watermark: None,
 			},
 		))
 		.await
@@ -707,6 +1452,10 @@ async fn test_transcoding_config_boiler_plate() {
 					tags: vec![("key".to_string(), "value".to_string())].into_iter().collect(),
 				}),
 				renditions: None,
+				video_settings: None,
+				audio_settings: None,
+				audio_normalization: None,
+				watermark: None,
 			},
 		))
 		.await
@@ -118,6 +118,44 @@ pub async fn create_recording_thumbnail(
 	results
 }
 
+pub async fn create_recording_thumbnail_sprite(
+	global: &Arc<GlobalState>,
+	organization_id: Ulid,
+	recording_id: Ulid,
+	inserts: impl Iterator<Item = (i32, Vec<f32>)>,
+) -> Vec<video_common::database::RecordingThumbnailSprite> {
+	let mut results = Vec::new();
+
+	let client = global.db().get().await.unwrap();
+
+	for inserts in &inserts.chunks(u16::MAX as usize / 10) {
+		let mut qb = utils::database::QueryBuilder::default();
+
+		qb.push(
+			"INSERT INTO recording_thumbnail_sprites (organization_id, recording_id, idx, id, start_time, end_time, tile_width, tile_height, tile_columns, tile_start_times) ",
+		);
+
+		qb.push_values(inserts, |mut qb, values| {
+			qb.push_bind(organization_id);
+			qb.push_bind(recording_id);
+			qb.push_bind(values.0);
+			qb.push_bind(Ulid::new());
+			qb.push_bind(values.1.first().copied().unwrap_or_default());
+			qb.push_bind(values.1.last().copied().unwrap_or_default() + 5.0);
+			qb.push_bind(160);
+			qb.push_bind(90);
+			qb.push_bind(5);
+			qb.push_bind(values.1);
+		});
+
+		qb.push(" RETURNING *");
+
+		results.extend(qb.build_query_as().fetch_all(&client).await.unwrap());
+	}
+
+	results
+}
+
 pub async fn create_recording_segment(
 	global: &Arc<GlobalState>,
 	organization_id: Ulid,
@@ -0,0 +1,243 @@
+use std::path::{Path, PathBuf};
+
+use ulid::Ulid;
+use utils::database::tokio_postgres::{Client, Config, NoTls};
+
+fn migrations(direction: &str) -> Vec<PathBuf> {
+	let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../migrations");
+
+	let mut files = std::fs::read_dir(dir)
+		.expect("failed to read migrations")
+		.map(|entry| entry.unwrap().path())
+		.filter(|path| path.to_str().unwrap().ends_with(&format!(".{direction}.sql")))
+		.collect::<Vec<_>>();
+
+	files.sort();
+	files
+}
+
+/// Runs a migration in a transaction, the same way sqlx does.
+async fn run(client: &mut Client, path: &Path) {
+	let sql = std::fs::read_to_string(path).unwrap();
+
+	let tx = client.transaction().await.unwrap();
+	tx.batch_execute(&sql)
+		.await
+		.unwrap_or_else(|err| panic!("failed to run {}: {err}", path.display()));
+	tx.commit().await.unwrap();
+}
+
+async fn connect(config: &Config) -> Client {
+	let (client, connection) = config.connect(NoTls).await.expect("failed to connect to database");
+
+	tokio::spawn(connection);
+
+	client
+}
+
+async fn renditions(client: &Client, table: &str, id: Ulid) -> Vec<String> {
+	let mut renditions = utils::database::query(format!("SELECT unnest(renditions)::STRING FROM {table} WHERE id = $1"))
+		.bind(id)
+		.build_query_single_scalar::<String>()
+		.fetch_all(client)
+		.await
+		.unwrap();
+
+	renditions.sort();
+	renditions
+}
+
+async fn count(client: &Client, query: &str, id: Ulid) -> i64 {
+	utils::database::query(query)
+		.bind(id)
+		.build_query_single_scalar::<i64>()
+		.fetch_one(client)
+		.await
+		.unwrap()
+}
+
+async fn has_video_settings(client: &Client) -> bool {
+	utils::database::query(
+		"SELECT count(*) FROM information_schema.columns WHERE table_name = 'transcoding_configs' AND column_name = 'video_settings'",
+	)
+	.build_query_single_scalar::<i64>()
+	.fetch_one(client)
+	.await
+	.unwrap()
+		== 1
+}
+
+#[tokio::test]
+async fn test_video_rendition_settings_migration() {
+	dotenvy::dotenv().ok();
+
+	let database_uri = std::env::var("VIDEO_DATABASE_URL_TEST").expect("VIDEO_DATABASE_URL_TEST must be set");
+	let mut config: Config = database_uri.parse().expect("invalid database url");
+
+	// The migrations are run against a database of their own, so the schema of
+	// the other tests is never touched.
+	let admin = connect(&config).await;
+	let database = format!("scuffle_video_migrations_{}", Ulid::new().to_string().to_lowercase());
+	admin.batch_execute(&format!("CREATE DATABASE {database}")).await.unwrap();
+
+	config.dbname(&database);
+	let mut client = connect(&config).await;
+
+	for path in migrations("up") {
+		run(&mut client, &path).await;
+	}
+
+	let organization_id = Ulid::new();
+	let s3_bucket_id = Ulid::new();
+	let transcoding_config_id = Ulid::new();
+	let recording_config_id = Ulid::new();
+	let recording_id = Ulid::new();
+
+	utils::database::query("INSERT INTO organizations (id, name) VALUES ($1, 'test')")
+		.bind(organization_id)
+		.build()
+		.execute(&client)
+		.await
+		.unwrap();
+
+	utils::database::query(
+		"INSERT INTO s3_buckets (id, organization_id, name, region, access_key_id, secret_access_key, managed) VALUES ($1, $2, 'test', 'us-east-1', 'test', 'test', FALSE)",
+	)
+	.bind(s3_bucket_id)
+	.bind(organization_id)
+	.build()
+	.execute(&client)
+	.await
+	.unwrap();
+
+	utils::database::query(
+		"INSERT INTO transcoding_configs (id, organization_id, renditions) VALUES ($1, $2, ARRAY['VIDEO_SOURCE', 'VIDEO_FHD', 'AUDIO_SOURCE'])",
+	)
+	.bind(transcoding_config_id)
+	.bind(organization_id)
+	.build()
+	.execute(&client)
+	.await
+	.unwrap();
+
+	utils::database::query(
+		"INSERT INTO recording_configs (id, organization_id, s3_bucket_id, renditions) VALUES ($1, $2, $3, ARRAY['VIDEO_SOURCE', 'VIDEO_QHD', 'VIDEO_HD', 'AUDIO_SOURCE'])",
+	)
+	.bind(recording_config_id)
+	.bind(organization_id)
+	.bind(s3_bucket_id)
+	.build()
+	.execute(&client)
+	.await
+	.unwrap();
+
+	utils::database::query(
+		"INSERT INTO recordings (id, organization_id, s3_bucket_id, recording_config_id) VALUES ($1, $2, $3, $4)",
+	)
+	.bind(recording_id)
+	.bind(organization_id)
+	.bind(s3_bucket_id)
+	.bind(recording_config_id)
+	.build()
+	.execute(&client)
+	.await
+	.unwrap();
+
+	for rendition in ["VIDEO_SOURCE", "VIDEO_QHD"] {
+		utils::database::query(
+			"INSERT INTO recording_renditions (organization_id, recording_id, rendition, config) VALUES ($1, $2, $3::STRING::rendition, ''::BYTES)",
+		)
+		.bind(organization_id)
+		.bind(recording_id)
+		.bind(rendition)
+		.build()
+		.execute(&client)
+		.await
+		.unwrap();
+
+		utils::database::query(
+			"INSERT INTO recording_rendition_segments (organization_id, recording_id, rendition, idx, id, start_time, end_time) VALUES ($1, $2, $3::STRING::rendition, 0, $4, 0, 2)",
+		)
+		.bind(organization_id)
+		.bind(recording_id)
+		.bind(rendition)
+		.bind(Ulid::new())
+		.build()
+		.execute(&client)
+		.await
+		.unwrap();
+	}
+
+	// Roll back every migration down to and including the one which added the
+	// 1080p and 1440p renditions.
+	for path in migrations("down").into_iter().rev() {
+		let done = path.file_name().unwrap().to_str().unwrap().starts_with("20261017120000_");
+		run(&mut client, &path).await;
+		if done {
+			break;
+		}
+	}
+
+	assert!(!has_video_settings(&client).await);
+	assert!(client.query("SELECT 'VIDEO_FHD'::rendition", &[]).await.is_err());
+	assert!(client.query("SELECT 'VIDEO_QHD'::rendition", &[]).await.is_err());
+
+	// The configs fall back to the HD rendition, without listing it twice.
+	assert_eq!(
+		renditions(&client, "transcoding_configs", transcoding_config_id).await,
+		vec!["AUDIO_SOURCE", "VIDEO_HD", "VIDEO_SOURCE"]
+	);
+	assert_eq!(
+		renditions(&client, "recording_configs", recording_config_id).await,
+		vec!["AUDIO_SOURCE", "VIDEO_HD", "VIDEO_SOURCE"]
+	);
+
+	assert_eq!(
+		count(
+			&client,
+			"SELECT count(*) FROM recording_renditions WHERE recording_id = $1",
+			recording_id
+		)
+		.await,
+		1
+	);
+	assert_eq!(
+		count(
+			&client,
+			"SELECT count(*) FROM recording_rendition_segments WHERE recording_id = $1",
+			recording_id
+		)
+		.await,
+		1
+	);
+
+	// Migrating up again restores the schema.
+	for path in migrations("up")
+		.into_iter()
+		.filter(|path| path.file_name().unwrap().to_str().unwrap() >= "20261017120000_")
+	{
+		run(&mut client, &path).await;
+	}
+
+	assert!(has_video_settings(&client).await);
+
+	utils::database::query(
+		"UPDATE transcoding_configs SET renditions = array_append(renditions, 'VIDEO_QHD') WHERE id = $1",
+	)
+	.bind(transcoding_config_id)
+	.build()
+	.execute(&client)
+	.await
+	.unwrap();
+
+	assert_eq!(
+		renditions(&client, "transcoding_configs", transcoding_config_id).await,
+		vec!["AUDIO_SOURCE", "VIDEO_HD", "VIDEO_QHD", "VIDEO_SOURCE"]
+	);
+
+	drop(client);
+	admin
+		.batch_execute(&format!("DROP DATABASE {database} CASCADE"))
+		.await
+		.unwrap();
+}
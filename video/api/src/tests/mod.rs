@@ -2,4 +2,6 @@ mod api;
 
 mod global;
 
+mod migrations;
+
 mod utils;
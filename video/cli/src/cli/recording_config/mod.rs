@@ -37,20 +37,28 @@ pub enum Commands {
 #[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Rendition {
 	VideoSource,
+	VideoQhd,
+	VideoFhd,
 	VideoHd,
 	VideoSd,
 	VideoLd,
 	AudioSource,
+	AudioHigh,
+	AudioLow,
 }
 
 impl From<Rendition> for i32 {
 	fn from(rendition: Rendition) -> Self {
 		match rendition {
 			Rendition::VideoSource => pb::scuffle::video::v1::types::Rendition::VideoSource as i32,
+			Rendition::VideoQhd => pb::scuffle::video::v1::types::Rendition::VideoQhd as i32,
+			Rendition::VideoFhd => pb::scuffle::video::v1::types::Rendition::VideoFhd as i32,
 			Rendition::VideoHd => pb::scuffle::video::v1::types::Rendition::VideoHd as i32,
 			Rendition::VideoSd => pb::scuffle::video::v1::types::Rendition::VideoSd as i32,
 			Rendition::VideoLd => pb::scuffle::video::v1::types::Rendition::VideoLd as i32,
 			Rendition::AudioSource => pb::scuffle::video::v1::types::Rendition::AudioSource as i32,
+			Rendition::AudioHigh => pb::scuffle::video::v1::types::Rendition::AudioHigh as i32,
+			Rendition::AudioLow => pb::scuffle::video::v1::types::Rendition::AudioLow as i32,
 		}
 	}
 }
@@ -124,10 +132,14 @@ impl TryFrom<LifecyclePolicy> for pb::scuffle::video::v1::types::RecordingLifecy
 				.iter()
 				.map(|r| match r.to_lowercase().as_str() {
 					"video_source" => Ok(pb::scuffle::video::v1::types::Rendition::VideoSource as i32),
+					"video_qhd" => Ok(pb::scuffle::video::v1::types::Rendition::VideoQhd as i32),
+					"video_fhd" => Ok(pb::scuffle::video::v1::types::Rendition::VideoFhd as i32),
 					"video_hd" => Ok(pb::scuffle::video::v1::types::Rendition::VideoHd as i32),
 					"video_sd" => Ok(pb::scuffle::video::v1::types::Rendition::VideoSd as i32),
 					"video_ld" => Ok(pb::scuffle::video::v1::types::Rendition::VideoLd as i32),
 					"audio_source" => Ok(pb::scuffle::video::v1::types::Rendition::AudioSource as i32),
+					"audio_high" => Ok(pb::scuffle::video::v1::types::Rendition::AudioHigh as i32),
+					"audio_low" => Ok(pb::scuffle::video::v1::types::Rendition::AudioLow as i32),
 					_ => anyhow::bail!("invalid rendition: {}", r),
 				})
 				.collect::<Result<_, _>>()?,
@@ -1,6 +1,6 @@
 use anyhow::Context;
 
-use super::{Rendition, TranscodingConfig};
+use super::{AudioNormalization, AudioSettings, Rendition, TranscodingConfig, VideoSettings, Watermark};
 use crate::cli::{Cli, Invokable};
 use crate::invoker::Invoker;
 
@@ -10,6 +10,22 @@ pub struct Create {
 	#[clap(long, value_parser, num_args = 1.., value_delimiter = ' ', required = true)]
 	renditions: Vec<Rendition>,
 
+	/// The encoder settings of a transcoded video rendition (JSON)
+	#[clap(long)]
+	video_settings: Vec<String>,
+
+	/// The encoder settings of a transcoded audio rendition (JSON)
+	#[clap(long)]
+	audio_settings: Vec<String>,
+
+	/// The loudness normalization of the transcoded audio renditions (JSON)
+	#[clap(long)]
+	audio_normalization: Option<String>,
+
+	/// The watermark burned into the transcoded video renditions (JSON)
+	#[clap(long)]
+	watermark: Option<String>,
+
 	/// The tags for the transcoding config (JSON)
 	#[clap(long, default_value = "{}")]
 	tags: String,
@@ -20,6 +36,42 @@ impl Invokable for Create {
 		let resp = invoker
 			.invoke(pb::scuffle::video::v1::TranscodingConfigCreateRequest {
 				renditions: self.renditions.iter().copied().map(Into::into).collect(),
+				video_settings: self
+					.video_settings
+					.iter()
+					.map(|s| {
+						serde_json::from_str::<VideoSettings>(s)
+							.context("failed to parse video settings")
+							.and_then(TryInto::try_into)
+					})
+					.collect::<anyhow::Result<Vec<_>>>()?,
+				audio_settings: self
+					.audio_settings
+					.iter()
+					.map(|s| {
+						serde_json::from_str::<AudioSettings>(s)
+							.context("failed to parse audio settings")
+							.and_then(TryInto::try_into)
+					})
+					.collect::<anyhow::Result<Vec<_>>>()?,
+				audio_normalization: self
+					.audio_normalization
+					.as_ref()
+					.map(|n| {
+						serde_json::from_str::<AudioNormalization>(n)
+							.context("failed to parse audio normalization")
+							.map(Into::into)
+					})
+					.transpose()?,
+				watermark: self
+					.watermark
+					.as_ref()
+					.map(|w| {
+						serde_json::from_str::<Watermark>(w)
+							.context("failed to parse watermark")
+							.and_then(TryInto::try_into)
+					})
+					.transpose()?,
 				tags: Some(pb::scuffle::video::v1::types::Tags {
 					tags: serde_json::from_str(&self.tags).context("failed to parse tags")?,
 				}),
@@ -52,6 +52,14 @@ impl Invokable for Commands {
 pub struct TranscodingConfig {
 	id: ulid::Ulid,
 	renditions: Vec<String>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	video_settings: Vec<VideoSettings>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	audio_settings: Vec<AudioSettings>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	audio_normalization: Option<AudioNormalization>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	watermark: Option<Watermark>,
 	created_at: chrono::DateTime<chrono::Utc>,
 	updated_at: chrono::DateTime<chrono::Utc>,
 	#[serde(skip_serializing_if = "HashMap::is_empty")]
@@ -63,9 +71,288 @@ impl TranscodingConfig {
 		Self {
 			id: proto.id.into_ulid(),
 			renditions: proto.renditions().map(|r| r.as_str_name().to_string()).collect(),
+			video_settings: proto.video_settings.into_iter().map(VideoSettings::from_proto).collect(),
+			audio_settings: proto.audio_settings.into_iter().map(AudioSettings::from_proto).collect(),
+			audio_normalization: proto.audio_normalization.map(AudioNormalization::from_proto),
+			watermark: proto.watermark.map(Watermark::from_proto),
 			tags: proto.tags.map(|tags| tags.tags).unwrap_or_default(),
 			created_at: Utc.timestamp_millis_opt(proto.created_at).unwrap(),
 			updated_at: Utc.timestamp_millis_opt(proto.updated_at).unwrap(),
 		}
 	}
 }
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct VideoSettings {
+	pub rendition: String,
+	#[serde(default)]
+	pub side: i32,
+	#[serde(default)]
+	pub max_fps: i32,
+	#[serde(default)]
+	pub bitrate: i64,
+	#[serde(default = "default_codec")]
+	pub codec: String,
+	#[serde(default)]
+	pub profile: i32,
+	#[serde(default)]
+	pub level: i32,
+	#[serde(default)]
+	pub keyframe_interval_ms: i32,
+}
+
+fn default_codec() -> String {
+	"avc".to_string()
+}
+
+impl VideoSettings {
+	pub fn from_proto(proto: pb::scuffle::video::v1::types::VideoRenditionSettings) -> Self {
+		Self {
+			rendition: proto.rendition().as_str_name().to_string(),
+			codec: match proto.codec() {
+				pb::scuffle::video::v1::types::VideoCodec::Avc => "avc".to_string(),
+				pb::scuffle::video::v1::types::VideoCodec::Hevc => "hevc".to_string(),
+				pb::scuffle::video::v1::types::VideoCodec::Av1 => "av1".to_string(),
+			},
+			side: proto.side,
+			max_fps: proto.max_fps,
+			bitrate: proto.bitrate,
+			profile: proto.profile,
+			level: proto.level,
+			keyframe_interval_ms: proto.keyframe_interval_ms,
+		}
+	}
+}
+
+impl TryFrom<VideoSettings> for pb::scuffle::video::v1::types::VideoRenditionSettings {
+	type Error = anyhow::Error;
+
+	fn try_from(value: VideoSettings) -> Result<Self, Self::Error> {
+		Ok(Self {
+			rendition: pb::scuffle::video::v1::types::Rendition::from_str_name(&value.rendition.to_uppercase())
+				.ok_or_else(|| anyhow::anyhow!("invalid rendition: {}", value.rendition))? as i32,
+			codec: match value.codec.to_lowercase().as_str() {
+				"avc" | "h264" => pb::scuffle::video::v1::types::VideoCodec::Avc as i32,
+				"hevc" | "h265" => pb::scuffle::video::v1::types::VideoCodec::Hevc as i32,
+				"av1" => pb::scuffle::video::v1::types::VideoCodec::Av1 as i32,
+				_ => anyhow::bail!("invalid codec: {}", value.codec),
+			},
+			side: value.side,
+			max_fps: value.max_fps,
+			bitrate: value.bitrate,
+			profile: value.profile,
+			level: value.level,
+			keyframe_interval_ms: value.keyframe_interval_ms,
+		})
+	}
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct AudioSettings {
+	pub rendition: String,
+	#[serde(default = "default_audio_codec")]
+	pub codec: String,
+	#[serde(default)]
+	pub bitrate: i64,
+	#[serde(default)]
+	pub sample_rate: i32,
+	#[serde(default)]
+	pub channels: i32,
+}
+
+fn default_audio_codec() -> String {
+	"aac".to_string()
+}
+
+impl AudioSettings {
+	pub fn from_proto(proto: pb::scuffle::video::v1::types::AudioRenditionSettings) -> Self {
+		Self {
+			rendition: proto.rendition().as_str_name().to_string(),
+			codec: match proto.codec() {
+				pb::scuffle::video::v1::types::AudioCodec::Aac => "aac".to_string(),
+				pb::scuffle::video::v1::types::AudioCodec::Opus => "opus".to_string(),
+			},
+			bitrate: proto.bitrate,
+			sample_rate: proto.sample_rate,
+			channels: proto.channels,
+		}
+	}
+}
+
+impl TryFrom<AudioSettings> for pb::scuffle::video::v1::types::AudioRenditionSettings {
+	type Error = anyhow::Error;
+
+	fn try_from(value: AudioSettings) -> Result<Self, Self::Error> {
+		Ok(Self {
+			rendition: pb::scuffle::video::v1::types::Rendition::from_str_name(&value.rendition.to_uppercase())
+				.ok_or_else(|| anyhow::anyhow!("invalid rendition: {}", value.rendition))? as i32,
+			codec: match value.codec.to_lowercase().as_str() {
+				"aac" => pb::scuffle::video::v1::types::AudioCodec::Aac as i32,
+				"opus" => pb::scuffle::video::v1::types::AudioCodec::Opus as i32,
+				_ => anyhow::bail!("invalid codec: {}", value.codec),
+			},
+			bitrate: value.bitrate,
+			sample_rate: value.sample_rate,
+			channels: value.channels,
+		})
+	}
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct AudioNormalization {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub integrated_loudness: Option<f64>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub true_peak: Option<f64>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub loudness_range: Option<f64>,
+}
+
+impl AudioNormalization {
+	pub fn from_proto(proto: pb::scuffle::video::v1::types::AudioNormalization) -> Self {
+		Self {
+			integrated_loudness: proto.integrated_loudness,
+			true_peak: proto.true_peak,
+			loudness_range: proto.loudness_range,
+		}
+	}
+}
+
+impl From<AudioNormalization> for pb::scuffle::video::v1::types::AudioNormalization {
+	fn from(value: AudioNormalization) -> Self {
+		Self {
+			integrated_loudness: value.integrated_loudness,
+			true_peak: value.true_peak,
+			loudness_range: value.loudness_range,
+		}
+	}
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Watermark {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub image: Option<WatermarkImage>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub text: Option<WatermarkText>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct WatermarkImage {
+	pub s3_bucket_id: ulid::Ulid,
+	pub key: String,
+	#[serde(default = "default_watermark_position")]
+	pub position: String,
+	#[serde(default)]
+	pub scale: f64,
+	#[serde(default)]
+	pub opacity: f64,
+	#[serde(default)]
+	pub margin: f64,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct WatermarkText {
+	#[serde(default)]
+	pub text: String,
+	#[serde(default)]
+	pub timestamp: bool,
+	#[serde(default = "default_watermark_position")]
+	pub position: String,
+	#[serde(default)]
+	pub size: f64,
+	#[serde(default)]
+	pub color: String,
+	#[serde(default)]
+	pub opacity: f64,
+	#[serde(default)]
+	pub background: bool,
+	#[serde(default)]
+	pub margin: f64,
+}
+
+fn default_watermark_position() -> String {
+	"bottom_right".to_string()
+}
+
+fn watermark_position_to_string(position: pb::scuffle::video::v1::types::WatermarkPosition) -> String {
+	match position {
+		pb::scuffle::video::v1::types::WatermarkPosition::BottomRight => "bottom_right".to_string(),
+		pb::scuffle::video::v1::types::WatermarkPosition::BottomLeft => "bottom_left".to_string(),
+		pb::scuffle::video::v1::types::WatermarkPosition::TopRight => "top_right".to_string(),
+		pb::scuffle::video::v1::types::WatermarkPosition::TopLeft => "top_left".to_string(),
+		pb::scuffle::video::v1::types::WatermarkPosition::Center => "center".to_string(),
+	}
+}
+
+fn watermark_position_from_str(position: &str) -> anyhow::Result<i32> {
+	Ok(match position.to_lowercase().as_str() {
+		"bottom_right" => pb::scuffle::video::v1::types::WatermarkPosition::BottomRight as i32,
+		"bottom_left" => pb::scuffle::video::v1::types::WatermarkPosition::BottomLeft as i32,
+		"top_right" => pb::scuffle::video::v1::types::WatermarkPosition::TopRight as i32,
+		"top_left" => pb::scuffle::video::v1::types::WatermarkPosition::TopLeft as i32,
+		"center" => pb::scuffle::video::v1::types::WatermarkPosition::Center as i32,
+		_ => anyhow::bail!("invalid watermark position: {position}"),
+	})
+}
+
+impl Watermark {
+	pub fn from_proto(proto: pb::scuffle::video::v1::types::Watermark) -> Self {
+		Self {
+			image: proto.image.map(|image| WatermarkImage {
+				s3_bucket_id: image.s3_bucket_id.into_ulid(),
+				position: watermark_position_to_string(image.position()),
+				key: image.key,
+				scale: image.scale,
+				opacity: image.opacity,
+				margin: image.margin,
+			}),
+			text: proto.text.map(|text| WatermarkText {
+				position: watermark_position_to_string(text.position()),
+				text: text.text,
+				timestamp: text.timestamp,
+				size: text.size,
+				color: text.color,
+				opacity: text.opacity,
+				background: text.background,
+				margin: text.margin,
+			}),
+		}
+	}
+}
+
+impl TryFrom<Watermark> for pb::scuffle::video::v1::types::Watermark {
+	type Error = anyhow::Error;
+
+	fn try_from(value: Watermark) -> Result<Self, Self::Error> {
+		Ok(Self {
+			image: value
+				.image
+				.map(|image| {
+					anyhow::Ok(pb::scuffle::video::v1::types::watermark::Image {
+						s3_bucket_id: Some(image.s3_bucket_id.into()),
+						key: image.key,
+						position: watermark_position_from_str(&image.position)?,
+						scale: image.scale,
+						opacity: image.opacity,
+						margin: image.margin,
+					})
+				})
+				.transpose()?,
+			text: value
+				.text
+				.map(|text| {
+					anyhow::Ok(pb::scuffle::video::v1::types::watermark::Text {
+						text: text.text,
+						timestamp: text.timestamp,
+						position: watermark_position_from_str(&text.position)?,
+						size: text.size,
+						color: text.color,
+						opacity: text.opacity,
+						background: text.background,
+						margin: text.margin,
+					})
+				})
+				.transpose()?,
+		})
+	}
+}
@@ -1,7 +1,7 @@
 use anyhow::Context;
 use ulid::Ulid;
 
-use super::{Rendition, TranscodingConfig};
+use super::{AudioNormalization, AudioSettings, Rendition, TranscodingConfig, VideoSettings, Watermark};
 use crate::cli::{Cli, Invokable};
 use crate::invoker::Invoker;
 
@@ -15,6 +15,30 @@ pub struct Modify {
 	/// Renditions to transcode to
 	renditions: Option<Vec<Rendition>>,
 
+	/// The encoder settings of a transcoded video rendition (JSON)
+	#[clap(long)]
+	video_settings: Option<Vec<String>>,
+
+	/// The encoder settings of a transcoded audio rendition (JSON)
+	#[clap(long)]
+	audio_settings: Option<Vec<String>>,
+
+	/// The loudness normalization of the transcoded audio renditions (JSON)
+	#[clap(long, conflicts_with = "no_audio_normalization")]
+	audio_normalization: Option<String>,
+
+	/// Disable loudness normalization
+	#[clap(long)]
+	no_audio_normalization: bool,
+
+	/// The watermark burned into the transcoded video renditions (JSON)
+	#[clap(long, conflicts_with = "no_watermark")]
+	watermark: Option<String>,
+
+	/// Remove the watermark
+	#[clap(long)]
+	no_watermark: bool,
+
 	/// The tags for the transcoding config (JSON)
 	#[clap(long)]
 	tags: Option<String>,
@@ -22,10 +46,56 @@ pub struct Modify {
 
 impl Invokable for Modify {
 	async fn invoke(&self, invoker: &mut Invoker, _: &Cli) -> anyhow::Result<()> {
-		if self.renditions.is_none() && self.tags.is_none() {
-			anyhow::bail!("at least one flag must be set, --renditions or --tags");
+		if self.renditions.is_none()
+			&& self.video_settings.is_none()
+			&& self.audio_settings.is_none()
+			&& self.audio_normalization.is_none()
+			&& !self.no_audio_normalization
+			&& self.watermark.is_none()
+			&& !self.no_watermark
+			&& self.tags.is_none()
+		{
+			anyhow::bail!(
+				"at least one flag must be set, --renditions, --video-settings, --audio-settings, --audio-normalization, --no-audio-normalization, --watermark, --no-watermark or --tags"
+			);
 		}
 
+		let audio_normalization = if self.no_audio_normalization {
+			Some(pb::scuffle::video::v1::transcoding_config_modify_request::AudioNormalizationUpdate { value: None })
+		} else {
+			self.audio_normalization
+				.as_ref()
+				.map(|n| {
+					anyhow::Ok(
+						pb::scuffle::video::v1::transcoding_config_modify_request::AudioNormalizationUpdate {
+							value: Some(
+								serde_json::from_str::<AudioNormalization>(n)
+									.context("failed to parse audio normalization")?
+									.into(),
+							),
+						},
+					)
+				})
+				.transpose()?
+		};
+
+		let watermark = if self.no_watermark {
+			Some(pb::scuffle::video::v1::transcoding_config_modify_request::WatermarkUpdate { value: None })
+		} else {
+			self.watermark
+				.as_ref()
+				.map(|w| {
+					anyhow::Ok(pb::scuffle::video::v1::transcoding_config_modify_request::WatermarkUpdate {
+						value: Some(
+							serde_json::from_str::<Watermark>(w)
+								.context("failed to parse watermark")?
+								.try_into()?,
+						),
+					})
+				})
+				.transpose()?
+		};
+
 		let resp = invoker
 			.invoke(pb::scuffle::video::v1::TranscodingConfigModifyRequest {
 				id: Some(self.id.into()),
@@ -34,6 +104,40 @@ impl Invokable for Modify {
 						items: r.iter().copied().map(Into::into).collect(),
 					}
 				}),
+				video_settings: self
+					.video_settings
+					.as_ref()
+					.map(|s| {
+						anyhow::Ok(pb::scuffle::video::v1::transcoding_config_modify_request::VideoSettingsList {
+							items: s
+								.iter()
+								.map(|s| {
+									serde_json::from_str::<VideoSettings>(s)
+										.context("failed to parse video settings")
+										.and_then(TryInto::try_into)
+								})
+								.collect::<anyhow::Result<Vec<_>>>()?,
+						})
+					})
+					.transpose()?,
+				audio_settings: self
+					.audio_settings
+					.as_ref()
+					.map(|s| {
+						anyhow::Ok(pb::scuffle::video::v1::transcoding_config_modify_request::AudioSettingsList {
+							items: s
+								.iter()
+								.map(|s| {
+									serde_json::from_str::<AudioSettings>(s)
+										.context("failed to parse audio settings")
+										.and_then(TryInto::try_into)
+								})
+								.collect::<anyhow::Result<Vec<_>>>()?,
+						})
+					})
+					.transpose()?,
+				audio_normalization,
+				watermark,
 				tags: self
 					.tags
 					.as_ref()
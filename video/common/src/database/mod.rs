@@ -10,6 +10,7 @@ mod recording_config;
 mod recording_rendition;
 mod recording_rendition_segment;
 mod recording_thumbnail;
+mod recording_thumbnail_sprite;
 mod rendition;
 mod room;
 mod room_status;
@@ -30,6 +31,7 @@ pub use recording_config::*;
 pub use recording_rendition::*;
 pub use recording_rendition_segment::*;
 pub use recording_thumbnail::*;
+pub use recording_thumbnail_sprite::*;
 pub use rendition::*;
 pub use room::*;
 pub use room_status::*;
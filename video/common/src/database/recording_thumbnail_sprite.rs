@@ -0,0 +1,43 @@
+use postgres_from_row::FromRow;
+use ulid::Ulid;
+
+use super::DatabaseTable;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct RecordingThumbnailSprite {
+	/// The organization this sprite sheet belongs to (primary key)
+	pub organization_id: Ulid,
+	/// The recording this sprite sheet belongs to (primary key)
+	pub recording_id: Ulid,
+	/// The index of the sprite sheet (primary key)
+	pub idx: i32,
+
+	/// The unique id for the sprite sheet
+	pub id: Ulid,
+
+	/// The time the first tile was taken (relative to the start of the
+	/// recording)
+	pub start_time: f32,
+	/// The time the last tile stops being shown (relative to the start of the
+	/// recording)
+	pub end_time: f32,
+
+	/// The width of a single tile in pixels
+	pub tile_width: i32,
+	/// The height of a single tile in pixels
+	pub tile_height: i32,
+	/// The number of tiles in each row of the sheet, tiles are laid out left to
+	/// right, top to bottom
+	pub tile_columns: i32,
+	/// The time each tile was taken, in the order they are laid out (relative
+	/// to the start of the recording)
+	pub tile_start_times: Vec<f32>,
+
+	/// The size of the sprite sheet in bytes
+	pub size_bytes: i64,
+}
+
+impl DatabaseTable for RecordingThumbnailSprite {
+	const FRIENDLY_NAME: &'static str = "recording thumbnail sprite";
+	const NAME: &'static str = "recording_thumbnail_sprites";
+}
@@ -6,6 +6,10 @@ use postgres_types::{FromSql, ToSql};
 pub enum Rendition {
 	#[postgres(name = "VIDEO_SOURCE")]
 	VideoSource,
+	#[postgres(name = "VIDEO_QHD")]
+	VideoQhd,
+	#[postgres(name = "VIDEO_FHD")]
+	VideoFhd,
 	#[postgres(name = "VIDEO_HD")]
 	VideoHd,
 	#[postgres(name = "VIDEO_SD")]
@@ -14,30 +18,38 @@ pub enum Rendition {
 	VideoLd,
 	#[postgres(name = "AUDIO_SOURCE")]
 	AudioSource,
+	#[postgres(name = "AUDIO_HIGH")]
+	AudioHigh,
+	#[postgres(name = "AUDIO_LOW")]
+	AudioLow,
 }
 
 impl Rendition {
 	pub fn is_video(self) -> bool {
 		match self {
-			Self::VideoSource | Self::VideoHd | Self::VideoSd | Self::VideoLd => true,
-			Self::AudioSource => false,
+			Self::VideoSource | Self::VideoQhd | Self::VideoFhd | Self::VideoHd | Self::VideoSd | Self::VideoLd => true,
+			Self::AudioSource | Self::AudioHigh | Self::AudioLow => false,
 		}
 	}
 
 	pub fn is_audio(self) -> bool {
 		match self {
-			Self::VideoSource | Self::VideoHd | Self::VideoSd | Self::VideoLd => false,
-			Self::AudioSource => true,
+			Self::VideoSource | Self::VideoQhd | Self::VideoFhd | Self::VideoHd | Self::VideoSd | Self::VideoLd => false,
+			Self::AudioSource | Self::AudioHigh | Self::AudioLow => true,
 		}
 	}
 
-	pub const fn variants() -> [Rendition; 5] {
+	pub const fn variants() -> [Rendition; 9] {
 		[
 			Self::VideoSource,
+			Self::VideoQhd,
+			Self::VideoFhd,
 			Self::VideoHd,
 			Self::VideoSd,
 			Self::VideoLd,
 			Self::AudioSource,
+			Self::AudioHigh,
+			Self::AudioLow,
 		]
 	}
 }
@@ -46,10 +58,14 @@ impl From<Rendition> for pb::scuffle::video::v1::types::Rendition {
 	fn from(value: Rendition) -> Self {
 		match value {
 			Rendition::VideoSource => Self::VideoSource,
+			Rendition::VideoQhd => Self::VideoQhd,
+			Rendition::VideoFhd => Self::VideoFhd,
 			Rendition::VideoHd => Self::VideoHd,
 			Rendition::VideoSd => Self::VideoSd,
 			Rendition::VideoLd => Self::VideoLd,
 			Rendition::AudioSource => Self::AudioSource,
+			Rendition::AudioHigh => Self::AudioHigh,
+			Rendition::AudioLow => Self::AudioLow,
 		}
 	}
 }
@@ -58,10 +74,14 @@ impl From<pb::scuffle::video::v1::types::Rendition> for Rendition {
 	fn from(value: pb::scuffle::video::v1::types::Rendition) -> Self {
 		match value {
 			pb::scuffle::video::v1::types::Rendition::VideoSource => Self::VideoSource,
+			pb::scuffle::video::v1::types::Rendition::VideoQhd => Self::VideoQhd,
+			pb::scuffle::video::v1::types::Rendition::VideoFhd => Self::VideoFhd,
 			pb::scuffle::video::v1::types::Rendition::VideoHd => Self::VideoHd,
 			pb::scuffle::video::v1::types::Rendition::VideoSd => Self::VideoSd,
 			pb::scuffle::video::v1::types::Rendition::VideoLd => Self::VideoLd,
 			pb::scuffle::video::v1::types::Rendition::AudioSource => Self::AudioSource,
+			pb::scuffle::video::v1::types::Rendition::AudioHigh => Self::AudioHigh,
+			pb::scuffle::video::v1::types::Rendition::AudioLow => Self::AudioLow,
 		}
 	}
 }
@@ -70,10 +90,14 @@ impl std::fmt::Display for Rendition {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
 			Self::VideoSource => write!(f, "video_source"),
+			Self::VideoQhd => write!(f, "video_qhd"),
+			Self::VideoFhd => write!(f, "video_fhd"),
 			Self::VideoHd => write!(f, "video_hd"),
 			Self::VideoSd => write!(f, "video_sd"),
 			Self::VideoLd => write!(f, "video_ld"),
 			Self::AudioSource => write!(f, "audio_source"),
+			Self::AudioHigh => write!(f, "audio_high"),
+			Self::AudioLow => write!(f, "audio_low"),
 		}
 	}
 }
@@ -84,10 +108,14 @@ impl std::str::FromStr for Rendition {
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
 		match s {
 			"video_source" => Ok(Self::VideoSource),
+			"video_qhd" => Ok(Self::VideoQhd),
+			"video_fhd" => Ok(Self::VideoFhd),
 			"video_hd" => Ok(Self::VideoHd),
 			"video_sd" => Ok(Self::VideoSd),
 			"video_ld" => Ok(Self::VideoLd),
 			"audio_source" => Ok(Self::AudioSource),
+			"audio_high" => Ok(Self::AudioHigh),
+			"audio_low" => Ok(Self::AudioLow),
 			_ => Err(()),
 		}
 	}
@@ -1,9 +1,11 @@
 use std::collections::HashMap;
 
-use pb::scuffle::video::v1::types::Rendition as PbRendition;
+use pb::scuffle::video::v1::types::{
+	AudioNormalization, AudioRenditionSettings, Rendition as PbRendition, VideoRenditionSettings, Watermark,
+};
 use postgres_from_row::FromRow;
 use ulid::Ulid;
-use utils::database::json;
+use utils::database::{json, protobuf_opt, protobuf_vec};
 
 use super::{DatabaseTable, Rendition};
 
@@ -17,6 +19,22 @@ pub struct TranscodingConfig {
 	/// The renditions this transcoding config uses
 	pub renditions: Vec<Rendition>,
 
+	/// The encoder settings of the transcoded video renditions
+	#[from_row(from_fn = "protobuf_vec")]
+	pub video_settings: Vec<VideoRenditionSettings>,
+
+	/// The encoder settings of the transcoded audio renditions
+	#[from_row(from_fn = "protobuf_vec")]
+	pub audio_settings: Vec<AudioRenditionSettings>,
+
+	/// The loudness normalization of the transcoded audio renditions
+	#[from_row(from_fn = "protobuf_opt")]
+	pub audio_normalization: Option<AudioNormalization>,
+
+	/// The watermark burned into the transcoded video renditions
+	#[from_row(from_fn = "protobuf_opt")]
+	pub watermark: Option<Watermark>,
+
 	/// The date and time the transcoding config was last updated
 	pub updated_at: chrono::DateTime<chrono::Utc>,
 
@@ -38,6 +56,10 @@ impl TranscodingConfig {
 		pb::scuffle::video::v1::types::TranscodingConfig {
 			id: Some(self.id.into()),
 			renditions,
+			video_settings: self.video_settings,
+			audio_settings: self.audio_settings,
+			audio_normalization: self.audio_normalization,
+			watermark: self.watermark,
 			created_at: self.id.timestamp_ms() as i64,
 			updated_at: self.updated_at.timestamp_micros(),
 			tags: Some(self.tags.into()),
@@ -36,6 +36,10 @@ pub fn s3_thumbnail(organization_id: Ulid, recording_id: Ulid, thumbnail_idx: u3
 	format!("{organization_id}/{recording_id}/thumbnails/{thumbnail_idx}.{thumbnail_id}.jpg",)
 }
 
+pub fn s3_thumbnail_sprite(organization_id: Ulid, recording_id: Ulid, sprite_idx: u32, sprite_id: Ulid) -> String {
+	format!("{organization_id}/{recording_id}/sprites/{sprite_idx}.{sprite_id}.jpg",)
+}
+
 pub fn s3_init(organization_id: Ulid, recording_id: Ulid, rendition: Rendition) -> String {
 	format!("{organization_id}/{recording_id}/{rendition}/init.mp4",)
 }
@@ -22,7 +22,7 @@ use crate::config::EdgeConfig;
 use crate::global::EdgeGlobal;
 
 mod error;
-mod stream;
+pub(crate) mod stream;
 
 pub use error::EdgeError;
 
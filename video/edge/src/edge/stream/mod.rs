@@ -34,8 +34,8 @@ use crate::global::EdgeGlobal;
 
 mod block_style;
 mod hls_config;
-mod playlist;
-mod tokens;
+pub(crate) mod playlist;
+pub(crate) mod tokens;
 
 fn organization_id(req: &Request<Incoming>) -> Result<Ulid> {
 	Ulid::from_string(req.param("organization_id").unwrap())
@@ -311,7 +311,7 @@ async fn recording_playlist<G: EdgeGlobal>(req: Request<Incoming>) -> Result<Res
 		.collect::<Result<Vec<_>, _>>()
 		.map_err_route((StatusCode::INTERNAL_SERVER_ERROR, "failed to decode audio configs"))?;
 
-	let video_output = recording
+	let mut video_output = recording
 		.renditions
 		.iter()
 		.zip(recording.configs.iter())
@@ -325,6 +325,10 @@ async fn recording_playlist<G: EdgeGlobal>(req: Request<Incoming>) -> Result<Res
 		.collect::<Result<Vec<_>, _>>()
 		.map_err_route((StatusCode::INTERNAL_SERVER_ERROR, "failed to decode video configs"))?;
 
+	// The renditions are aggregated in no particular order, the playlist lists the
+	// largest rendition first like the live playlist does.
+	video_output.sort_by(|a, b| b.width.cmp(&a.width));
+
 	let id = Ulid::new();
 
 	let global_config = global.config();
@@ -583,6 +587,51 @@ async fn session_refresh<G: EdgeGlobal>(req: Request<Incoming>) -> Result<Respon
 	Ok(resp)
 }
 
+async fn session_thumbnails<G: EdgeGlobal>(req: Request<Incoming>) -> Result<Response<Body>> {
+	let global = req.get_global::<G, _>()?;
+
+	let organization_id = organization_id(&req)?;
+
+	let session = req.param("session").unwrap();
+
+	let session = SessionClaims::verify(&global, organization_id, session)?;
+
+	let client = global
+		.db()
+		.get()
+		.await
+		.map_err_route((StatusCode::INTERNAL_SERVER_ERROR, "failed to get database"))?;
+
+	let resp = utils::database::query(
+		r#"
+		UPDATE playback_sessions SET
+			expires_at = NOW() + INTERVAL '10 minutes'
+		WHERE
+			id = $1 AND
+			organization_id = $2 AND
+			expires_at > NOW()
+		"#,
+	)
+	.bind(session.id)
+	.bind(session.organization_id)
+	.build()
+	.execute(&client)
+	.await
+	.map_err_route((StatusCode::INTERNAL_SERVER_ERROR, "failed to update session"))?;
+
+	if resp == 0 {
+		return Err((StatusCode::BAD_REQUEST, "invalid session, expired or not found").into());
+	}
+
+	let track = playlist::thumbnail_track(&client, &session).await?;
+
+	let mut resp = Response::new(Body::from(track.to_vtt()));
+	resp.headers_mut().insert("Content-Type", "text/vtt".parse().unwrap());
+	resp.headers_mut().insert("Cache-Control", "no-cache".parse().unwrap());
+
+	Ok(resp)
+}
+
 async fn room_media<G: EdgeGlobal>(req: Request<Incoming>) -> Result<Response<Body>> {
 	let global = req.get_global::<G, _>()?;
 
@@ -842,6 +891,7 @@ pub fn routes<G: EdgeGlobal>(_: &Arc<G>) -> RouterBuilder<Incoming, Body, RouteE
 		.get("/:organization_id/r/:recording_id.m3u8", recording_playlist::<G>)
 		.get("/:organization_id/:session/:rendition.m3u8", session_playlist::<G>)
 		.get("/:organization_id/:session/refresh", session_refresh::<G>)
+		.get("/:organization_id/:session/thumbnails.vtt", session_thumbnails::<G>)
 		.get("/:organization_id/:room_id.jpg", room_screenshot::<G>)
 		.get("/:organization_id/:room_id/:media.mp4", room_media::<G>)
 		.get("/:organization_id/:room_id/:screenshot.jpg", room_screenshot_media::<G>)
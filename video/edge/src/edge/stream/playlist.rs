@@ -7,10 +7,11 @@ use pb::scuffle::video::v1::types::{AudioConfig, VideoConfig};
 use ulid::Ulid;
 use utils::database::non_null_vec;
 use utils::http::ext::*;
-use video_common::database::{Recording, RecordingThumbnail, Rendition, Visibility};
+use video_common::database::{Recording, RecordingThumbnail, RecordingThumbnailSprite, Rendition, Visibility};
 use video_player_types::{
 	RenditionPlaylist, RenditionPlaylistRendition, RenditionPlaylistSegment, RenditionPlaylistSegmentPart,
-	RoomPlaylistTrack, RoomPlaylistTrackAudio, RoomPlaylistTrackVideo, SessionPlaylist, ThumbnailRange,
+	RoomPlaylistTrack, RoomPlaylistTrackAudio, RoomPlaylistTrackVideo, SessionPlaylist, ThumbnailRange, ThumbnailTrack,
+	ThumbnailTrackSprite,
 };
 
 use super::hls_config::HlsConfig;
@@ -203,7 +204,7 @@ pub async fn rendition_playlist<G: EdgeGlobal>(
             WHERE
                 r.id = $1
                 AND r.organization_id = $2
-                AND r.deleted = FALSE
+                AND r.deleted_at IS NULL
                 AND r.allow_dvr = TRUE
         	"#,
 		)
@@ -471,3 +472,108 @@ pub async fn rendition_playlist<G: EdgeGlobal>(
 
 	Ok(playlist)
 }
+
+pub async fn thumbnail_track(
+	client: &utils::database::tokio_postgres::Client,
+	session: &SessionClaims,
+) -> Result<ThumbnailTrack> {
+	let organization_id = session.organization_id;
+
+	// Rooms only have sprites while they are being recorded, in which case we
+	// serve the sprites of the active recording as the DVR window.
+	let recording_id = match session.ty {
+		SessionClaimsType::Recording { recording_id } => Some(recording_id),
+		SessionClaimsType::Room { room_id, connection_id } => utils::database::query(
+			r#"
+            SELECT
+                active_recording_id
+            FROM rooms
+            WHERE
+                id = $1
+                AND organization_id = $2
+                AND active_ingest_connection_id = $3
+            "#,
+		)
+		.bind(room_id)
+		.bind(organization_id)
+		.bind(connection_id)
+		.build_query_single_scalar::<Option<Ulid>>()
+		.fetch_optional(client)
+		.await
+		.map_err_route((StatusCode::INTERNAL_SERVER_ERROR, "failed to query database"))?
+		.flatten(),
+	};
+
+	let Some(recording_id) = recording_id else {
+		return Ok(ThumbnailTrack::default());
+	};
+
+	let recording: Option<RecordingExt> = utils::database::query(
+		r#"
+        SELECT
+            s.public_url,
+            r.*
+        FROM recordings r
+        INNER JOIN s3_buckets s
+            ON s.id = r.s3_bucket_id
+        WHERE
+            r.id = $1
+            AND r.organization_id = $2
+            AND r.deleted_at IS NULL
+            AND r.allow_dvr = TRUE
+        "#,
+	)
+	.bind(recording_id)
+	.bind(organization_id)
+	.build_query_as()
+	.fetch_optional(client)
+	.await
+	.map_err_route((StatusCode::INTERNAL_SERVER_ERROR, "failed to query database"))?;
+
+	let Some(recording) = recording.filter(|r| r.recording.visibility == Visibility::Public || session.was_authenticated)
+	else {
+		return Ok(ThumbnailTrack::default());
+	};
+
+	let sprites: Vec<RecordingThumbnailSprite> = utils::database::query(
+		r#"
+        SELECT
+            *
+        FROM recording_thumbnail_sprites
+        WHERE
+            recording_id = $1
+            AND organization_id = $2
+        ORDER BY idx
+        "#,
+	)
+	.bind(recording_id)
+	.bind(organization_id)
+	.build_query_as()
+	.fetch_all(client)
+	.await
+	.map_err_route((StatusCode::INTERNAL_SERVER_ERROR, "failed to query database"))?;
+
+	let public_url = recording.public_url;
+
+	Ok(ThumbnailTrack {
+		sprites: sprites
+			.into_iter()
+			.map(|s| ThumbnailTrackSprite {
+				// The newest sheet is replaced every time a tile is added to it, the tile
+				// count in the query keeps clients from showing a cached copy.
+				url: format!(
+					"{public_url}/{}?tiles={}",
+					video_common::keys::s3_thumbnail_sprite(organization_id, recording_id, s.idx as u32, s.id),
+					s.tile_start_times.len(),
+				)
+				.parse()
+				.unwrap(),
+				tile_width: s.tile_width as u32,
+				tile_height: s.tile_height as u32,
+				columns: s.tile_columns as u32,
+				tile_start_times: s.tile_start_times.into_iter().map(|t| normalize_float(t as f64)).collect(),
+				end_time: normalize_float(s.end_time as f64),
+			})
+			.collect(),
+	})
+}
@@ -1 +1 @@
-
+mod playlist;
@@ -0,0 +1,250 @@
+use ulid::Ulid;
+use utils::database::tokio_postgres::{Client, NoTls};
+use video_common::database::Visibility;
+
+use crate::edge::stream::playlist::thumbnail_track;
+use crate::edge::stream::tokens::{SessionClaims, SessionClaimsType};
+
+async fn connect() -> Client {
+	let database_uri = std::env::var("VIDEO_DATABASE_URL_TEST").expect("VIDEO_DATABASE_URL_TEST must be set");
+
+	let (client, connection) = utils::database::tokio_postgres::connect(&database_uri, NoTls)
+		.await
+		.expect("failed to connect to database");
+
+	tokio::spawn(connection);
+
+	client
+}
+
+struct Fixture {
+	organization_id: Ulid,
+	room_id: Ulid,
+	connection_id: Ulid,
+	recording_id: Ulid,
+	sprite_ids: Vec<Ulid>,
+}
+
+async fn setup(client: &Client, visibility: Visibility, deleted: bool) -> Fixture {
+	let organization_id = Ulid::new();
+	let s3_bucket_id = Ulid::new();
+	let room_id = Ulid::new();
+	let connection_id = Ulid::new();
+	let recording_id = Ulid::new();
+
+	utils::database::query("INSERT INTO organizations (id, name) VALUES ($1, $2)")
+		.bind(organization_id)
+		.bind("test")
+		.build()
+		.execute(client)
+		.await
+		.unwrap();
+
+	utils::database::query(
+		"INSERT INTO s3_buckets (id, organization_id, name, region, access_key_id, secret_access_key, public_url, managed) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+	)
+	.bind(s3_bucket_id)
+	.bind(organization_id)
+	.bind("test")
+	.bind("us-east-1")
+	.bind("test")
+	.bind("test")
+	.bind("https://cdn.example.com")
+	.bind(false)
+	.build()
+	.execute(client)
+	.await
+	.unwrap();
+
+	utils::database::query(
+		"INSERT INTO recordings (id, organization_id, room_id, s3_bucket_id, visibility, allow_dvr, deleted_at) VALUES ($1, $2, $3, $4, $5, TRUE, CASE WHEN $6 THEN NOW() END)",
+	)
+	.bind(recording_id)
+	.bind(organization_id)
+	.bind(room_id)
+	.bind(s3_bucket_id)
+	.bind(visibility)
+	.bind(deleted)
+	.build()
+	.execute(client)
+	.await
+	.unwrap();
+
+	utils::database::query(
+		"INSERT INTO rooms (id, organization_id, stream_key, active_ingest_connection_id, active_recording_id) VALUES ($1, $2, $3, $4, $5)",
+	)
+	.bind(room_id)
+	.bind(organization_id)
+	.bind(format!("{:032x}", Ulid::new().0))
+	.bind(connection_id)
+	.bind(recording_id)
+	.build()
+	.execute(client)
+	.await
+	.unwrap();
+
+	// Inserted out of order, the track is ordered by the index of the sheet.
+	let sprites = [(1, vec![10.0_f32, 15.0], 20.0_f32), (0, vec![0.0, 5.0], 10.0)];
+	let mut sprite_ids = vec![Ulid::nil(); sprites.len()];
+
+	for (idx, tile_start_times, end_time) in sprites {
+		let id = Ulid::new();
+		sprite_ids[idx as usize] = id;
+
+		utils::database::query(
+			"INSERT INTO recording_thumbnail_sprites (organization_id, recording_id, idx, id, start_time, end_time, tile_width, tile_height, tile_columns, tile_start_times) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+		)
+		.bind(organization_id)
+		.bind(recording_id)
+		.bind(idx)
+		.bind(id)
+		.bind(tile_start_times[0])
+		.bind(end_time)
+		.bind(160)
+		.bind(90)
+		.bind(5)
+		.bind(tile_start_times)
+		.build()
+		.execute(client)
+		.await
+		.unwrap();
+	}
+
+	Fixture {
+		organization_id,
+		room_id,
+		connection_id,
+		recording_id,
+		sprite_ids,
+	}
+}
+
+fn session(organization_id: Ulid, ty: SessionClaimsType, was_authenticated: bool) -> SessionClaims {
+	SessionClaims {
+		id: Ulid::new(),
+		organization_id,
+		ty,
+		iat: chrono::Utc::now().timestamp(),
+		was_authenticated,
+	}
+}
+
+#[tokio::test]
+async fn test_thumbnail_track_recording() {
+	let client = connect().await;
+	let fixture = setup(&client, Visibility::Public, false).await;
+
+	let track = thumbnail_track(
+		&client,
+		&session(
+			fixture.organization_id,
+			SessionClaimsType::Recording {
+				recording_id: fixture.recording_id,
+			},
+			false,
+		),
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(track.sprites.len(), 2);
+
+	for (idx, (sprite, id)) in track.sprites.iter().zip(&fixture.sprite_ids).enumerate() {
+		assert_eq!(
+			sprite.url.as_str(),
+			format!(
+				"https://cdn.example.com/{}?tiles=2",
+				video_common::keys::s3_thumbnail_sprite(fixture.organization_id, fixture.recording_id, idx as u32, *id)
+			)
+		);
+		assert_eq!(sprite.tile_width, 160);
+		assert_eq!(sprite.tile_height, 90);
+		assert_eq!(sprite.columns, 5);
+	}
+
+	assert_eq!(track.sprites[0].tile_start_times, vec![0.0, 5.0]);
+	assert_eq!(track.sprites[0].end_time, 10.0);
+	assert_eq!(track.sprites[1].tile_start_times, vec![10.0, 15.0]);
+	assert_eq!(track.sprites[1].end_time, 20.0);
+}
+
+#[tokio::test]
+async fn test_thumbnail_track_room() {
+	let client = connect().await;
+	let fixture = setup(&client, Visibility::Public, false).await;
+
+	let track = thumbnail_track(
+		&client,
+		&session(
+			fixture.organization_id,
+			SessionClaimsType::Room {
+				room_id: fixture.room_id,
+				connection_id: fixture.connection_id,
+			},
+			false,
+		),
+	)
+	.await
+	.unwrap();
+
+	assert_eq!(track.sprites.len(), 2);
+
+	// A session of a previous connection does not get the sprites of the
+	// current recording.
+	let track = thumbnail_track(
+		&client,
+		&session(
+			fixture.organization_id,
+			SessionClaimsType::Room {
+				room_id: fixture.room_id,
+				connection_id: Ulid::new(),
+			},
+			false,
+		),
+	)
+	.await
+	.unwrap();
+
+	assert!(track.sprites.is_empty());
+}
+
+#[tokio::test]
+async fn test_thumbnail_track_private() {
+	let client = connect().await;
+	let fixture = setup(&client, Visibility::Private, false).await;
+
+	let ty = SessionClaimsType::Recording {
+		recording_id: fixture.recording_id,
+	};
+
+	let track = thumbnail_track(&client, &session(fixture.organization_id, ty, false))
+		.await
+		.unwrap();
+	assert!(track.sprites.is_empty());
+
+	let track = thumbnail_track(&client, &session(fixture.organization_id, ty, true))
+		.await
+		.unwrap();
+	assert_eq!(track.sprites.len(), 2);
+}
+
+#[tokio::test]
+async fn test_thumbnail_track_deleted() {
+	let client = connect().await;
+	let fixture = setup(&client, Visibility::Public, true).await;
+
+	let track = thumbnail_track(
+		&client,
+		&session(
+			fixture.organization_id,
+			SessionClaimsType::Recording {
+				recording_id: fixture.recording_id,
+			},
+			true,
+		),
+	)
+	.await
+	.unwrap();
+
+	assert!(track.sprites.is_empty());
+}
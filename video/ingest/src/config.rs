@@ -1,4 +1,4 @@
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::time::Duration;
 
 use binary_helper::config::TlsConfig;
@@ -22,6 +22,59 @@ impl Default for RtmpConfig {
 	}
 }
 
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct SrtConfig {
+	/// The bind address for the SRT listener
+	pub bind_address: SocketAddr,
+
+	/// The minimum latency of SRT connections, callers may ask for a higher
+	/// one
+	pub latency: Duration,
+}
+
+impl Default for SrtConfig {
+	fn default() -> Self {
+		Self {
+			bind_address: "[::]:9000".to_string().parse().unwrap(),
+			latency: Duration::from_millis(120),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct WhipConfig {
+	/// The bind address for the WHIP HTTP server
+	pub bind_address: SocketAddr,
+
+	/// The public IPs to advertise in the ICE candidates, the local addresses
+	/// are used if this is empty
+	pub public_ips: Vec<IpAddr>,
+
+	/// The UDP port range to use for the media, any port is used if this is
+	/// not set
+	pub udp_port_min: Option<u16>,
+
+	/// The upper end of the UDP port range
+	pub udp_port_max: Option<u16>,
+
+	/// How long we wait for the peer to connect after answering its offer
+	pub connect_timeout: Duration,
+}
+
+impl Default for WhipConfig {
+	fn default() -> Self {
+		Self {
+			bind_address: "[::]:8080".to_string().parse().unwrap(),
+			public_ips: Vec::new(),
+			udp_port_min: None,
+			udp_port_max: None,
+			connect_timeout: Duration::from_secs(10),
+		}
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
 #[serde(default)]
 pub struct IngestConfig {
@@ -49,6 +102,12 @@ pub struct IngestConfig {
 	/// The config for the RTMP server
 	pub rtmp: RtmpConfig,
 
+	/// The config for the SRT listener, SRT is disabled if this is not set
+	pub srt: Option<SrtConfig>,
+
+	/// The config for the WHIP server, WHIP is disabled if this is not set
+	pub whip: Option<WhipConfig>,
+
 	/// The address to advertise for the gRPC server which is used by
 	/// transcoders to connect to
 	pub grpc_advertise_address: String,
@@ -65,6 +124,8 @@ impl Default for IngestConfig {
 			max_time_between_keyframes: Duration::from_secs(10),
 			transcoder_timeout: Duration::from_secs(60),
 			rtmp: Default::default(),
+			srt: None,
+			whip: None,
 			grpc_advertise_address: "".to_string(),
 		}
 	}
@@ -8,14 +8,14 @@ use base64::Engine;
 use bytes::Bytes;
 use bytesio::bytesio::AsyncReadWrite;
 use flv::{FlvTag, FlvTagData, FlvTagType};
-use futures::Future;
 use futures_util::StreamExt;
 use pb::scuffle::video::internal::events::TranscoderRequestTask;
 use pb::scuffle::video::internal::{ingest_watch_request, ingest_watch_response, IngestWatchRequest, IngestWatchResponse};
 use pb::scuffle::video::v1::events_fetch_request::Target;
 use pb::scuffle::video::v1::types::{event, Rendition};
 use prost::Message as _;
-use rtmp::{ChannelData, PublishRequest, Session, SessionError};
+use rtmp::{ChannelData, Session};
+use srt::{reject_reason, SrtRequest};
 use tokio::select;
 use tokio::sync::mpsc;
 use tokio::time::Instant;
@@ -26,11 +26,15 @@ use utils::context::ContextExt;
 use utils::prelude::FutureTimeout;
 use video_common::database::RoomStatus;
 use video_common::{events, keys};
+use whip::{WhipError, WhipRequest};
 
 use super::bytes_tracker::BytesTracker;
 use super::errors::IngestError;
-use super::rtmp_session::{Data, RtmpSession};
+use super::rtmp_session::RtmpSession;
+use super::session::{Data, IngestSession};
+use super::srt_session::SrtSession;
 use super::update::{update_db, Update};
+use super::whip_session::WhipSession;
 use crate::config::IngestConfig;
 use crate::global::{IncomingTranscoder, IngestGlobal};
 
@@ -106,7 +110,15 @@ pub async fn handle<G: IngestGlobal, S: AsyncReadWrite>(global: Arc<G>, socket:
 		return;
 	};
 
-	let mut connection = match Connection::new(&global, event, ip).await {
+	if event.app_name != "live" {
+		return;
+	}
+
+	let Some((organization_id, room_id, room_secret)) = parse_stream_key(&event.stream_name) else {
+		return;
+	};
+
+	let mut connection = match Connection::new(&global, organization_id, room_id, &room_secret, ip).await {
 		Ok(Some(c)) => c,
 		Ok(None) => return,
 		Err(e) => {
@@ -115,6 +127,8 @@ pub async fn handle<G: IngestGlobal, S: AsyncReadWrite>(global: Arc<G>, socket:
 		}
 	};
 
+	event.response.send(connection.id.into()).ok();
+
 	let clean_disconnect = connection.run(&global, session).await;
 
 	if let Err(err) = connection.cleanup(&global, clean_disconnect).await {
@@ -122,53 +136,153 @@ pub async fn handle<G: IngestGlobal, S: AsyncReadWrite>(global: Arc<G>, socket:
 	}
 }
 
-impl Connection {
-	#[tracing::instrument(
-        level = "debug",
-        skip(global, event, _ip),
-        fields(app = %event.app_name, stream = %event.stream_name)
-    )]
-	async fn new<G: IngestGlobal>(global: &Arc<G>, event: PublishRequest, _ip: IpAddr) -> Result<Option<Self>> {
-		if event.app_name != "live" {
-			return Ok(None);
+#[tracing::instrument(skip(global, request), fields(stream_id = %request.stream_id()))]
+pub async fn handle_srt<G: IngestGlobal>(global: Arc<G>, request: SrtRequest, ip: IpAddr) {
+	let Some((organization_id, room_id, room_secret)) = parse_srt_stream_id(request.stream_id()) else {
+		tracing::debug!("invalid stream id");
+		request.reject(reject_reason::BAD_REQUEST);
+		return;
+	};
+
+	let mut connection = match Connection::new(&global, organization_id, room_id, &room_secret, ip).await {
+		Ok(Some(c)) => c,
+		Ok(None) => {
+			request.reject(reject_reason::UNAUTHORIZED);
+			return;
+		}
+		Err(e) => {
+			tracing::error!(error = %e, "failed to create connection");
+			request.reject(reject_reason::RESOURCE);
+			return;
 		}
+	};
 
-		let mut parts = event.stream_name.split('_');
-		if parts.next() != Some("live") {
-			return Ok(None);
+	let session = SrtSession::new(request.accept());
+
+	let clean_disconnect = connection.run(&global, session).await;
+
+	if let Err(err) = connection.cleanup(&global, clean_disconnect).await {
+		tracing::error!(error = %err, "failed to cleanup connection")
+	}
+}
+
+#[tracing::instrument(skip(global, request), fields(peer_addr = %request.peer_addr()))]
+pub async fn handle_whip<G: IngestGlobal>(global: Arc<G>, request: WhipRequest, ip: IpAddr) {
+	// The stream key is sent as the bearer token.
+	let Some((organization_id, room_id, room_secret)) = request.token().and_then(parse_stream_key) else {
+		tracing::debug!("invalid stream key");
+		request.reject(hyper::StatusCode::UNAUTHORIZED);
+		return;
+	};
+
+	let mut connection = match Connection::new(&global, organization_id, room_id, &room_secret, ip).await {
+		Ok(Some(c)) => c,
+		Ok(None) => {
+			request.reject(hyper::StatusCode::UNAUTHORIZED);
+			return;
+		}
+		Err(e) => {
+			tracing::error!(error = %e, "failed to create connection");
+			request.reject(hyper::StatusCode::SERVICE_UNAVAILABLE);
+			return;
 		}
+	};
 
-		let organization_id = match parts.next().and_then(|id| Ulid::from_string(id).ok()) {
-			Some(id) => id,
-			None => return Ok(None),
-		};
+	let clean_disconnect = match request.accept().await {
+		Ok(mut session) => {
+			// We only start waiting for data once the peer has connected, ICE and
+			// DTLS can take longer than the data timeout.
+			match session.connected().await {
+				Ok(()) => connection.run(&global, WhipSession::new(session)).await,
+				Err(err) => {
+					tracing::debug!(error = %err, "peer did not connect");
+					connection.error = Some(match err {
+						WhipError::Timeout => IngestError::WhipConnectionTimeout,
+						_ => IngestError::WhipConnectionError,
+					});
 
-		let parse_room_secret = |name: &str| {
-			if name.len() > 512 {
-				return None;
+					false
+				}
 			}
+		}
+		Err(err) => {
+			tracing::debug!(error = %err, "failed to accept offer");
+			connection.error = Some(IngestError::WhipConnectionError);
+
+			false
+		}
+	};
+
+	if let Err(err) = connection.cleanup(&global, clean_disconnect).await {
+		tracing::error!(error = %err, "failed to cleanup connection")
+	}
+}
 
-			let name = base64::engine::general_purpose::URL_SAFE_NO_PAD
-				.decode(name.as_bytes())
-				.ok()?;
+/// Parse a stream key of the form
+/// `live_{organization_id}_{base64(room_id+room_secret)}`.
+fn parse_stream_key(stream_key: &str) -> Option<(Ulid, Ulid, String)> {
+	let mut parts = stream_key.split('_');
+	if parts.next() != Some("live") {
+		return None;
+	}
 
-			let room_name_secret = std::str::from_utf8(&name).ok()?;
+	let organization_id = Ulid::from_string(parts.next()?).ok()?;
 
-			let mut parts = room_name_secret.split('+');
-			let room_id = Ulid::from_string(parts.next()?).ok()?;
-			let room_secret = parts.next()?;
-			if parts.next().is_some() {
-				return None;
-			}
+	let parse_room_secret = |name: &str| {
+		if name.len() > 512 {
+			return None;
+		}
 
-			Some((room_id, room_secret.to_string()))
-		};
+		let name = base64::engine::general_purpose::URL_SAFE_NO_PAD
+			.decode(name.as_bytes())
+			.ok()?;
 
-		let (room_id, room_secret) = match parts.next().and_then(parse_room_secret) {
-			Some(name) => name,
-			None => return Ok(None),
-		};
+		let room_name_secret = std::str::from_utf8(&name).ok()?;
 
+		let mut parts = room_name_secret.split('+');
+		let room_id = Ulid::from_string(parts.next()?).ok()?;
+		let room_secret = parts.next()?;
+		if parts.next().is_some() {
+			return None;
+		}
+
+		Some((room_id, room_secret.to_string()))
+	};
+
+	let (room_id, room_secret) = parts.next().and_then(parse_room_secret)?;
+
+	Some((organization_id, room_id, room_secret))
+}
+
+/// Parse the stream id of a SRT connection. This is either the stream key,
+/// or the SRT access control syntax `#!::r={stream_key},m=publish`.
+fn parse_srt_stream_id(stream_id: &str) -> Option<(Ulid, Ulid, String)> {
+	let Some(keys) = stream_id.strip_prefix("#!::") else {
+		return parse_stream_key(stream_id);
+	};
+
+	let mut resource = None;
+	for pair in keys.split(',') {
+		match pair.split_once('=')? {
+			("r", value) => resource = Some(value),
+			// We only accept streams that are published to us.
+			("m", mode) if mode != "publish" => return None,
+			_ => {}
+		}
+	}
+
+	parse_stream_key(resource?)
+}
+
+impl Connection {
+	#[tracing::instrument(level = "debug", skip(global, room_secret, _ip))]
+	async fn new<G: IngestGlobal>(
+		global: &Arc<G>,
+		organization_id: Ulid,
+		room_id: Ulid,
+		room_secret: &str,
+		_ip: IpAddr,
+	) -> Result<Option<Self>> {
 		#[derive(postgres_from_row::FromRow)]
 		struct Response {
 			id: Option<Ulid>,
@@ -209,7 +323,7 @@ impl Connection {
 		.bind(RoomStatus::Offline)
 		.bind(organization_id)
 		.bind(room_id)
-		.bind(&room_secret)
+		.bind(room_secret)
 		.build_query_as()
 		.fetch_optional(global.db())
 		.await?;
@@ -225,8 +339,6 @@ impl Connection {
 			}
 		}
 
-		event.response.send(id.into()).ok();
-
 		let (update_sender, update_reciever) = mpsc::channel(15);
 		let (incoming_sender, incoming_reciever) = mpsc::channel(15);
 
@@ -260,11 +372,7 @@ impl Connection {
         skip(self, global, session),
         fields(organization_id = %self.organization_id, room_id = %self.room_id)
     )]
-	async fn run<'a, G: IngestGlobal>(
-		&mut self,
-		global: &Arc<G>,
-		mut session: RtmpSession<'a, impl Future<Output = Result<bool, SessionError>>>,
-	) -> bool {
+	async fn run<G: IngestGlobal, S: IngestSession>(&mut self, global: &Arc<G>, mut session: S) -> bool {
 		tracing::info!("new publish request");
 
 		// At this point we have a stream that is publishing to us
@@ -312,7 +420,7 @@ impl Connection {
 					Err(e) => {
 						tracing::error!(error = %e, "session error");
 
-						self.error = Some(IngestError::RtmpConnectionError);
+						self.error = Some(S::CONNECTION_ERROR);
 
 						false
 					},
@@ -349,7 +457,7 @@ impl Connection {
 			_ = tokio::time::sleep_until(next_timeout) => {
 				tracing::debug!("session timed out during data");
 
-				self.error = Some(IngestError::RtmpConnectionTimeout);
+				self.error = Some(S::CONNECTION_TIMEOUT);
 
 				false
 			},
@@ -695,6 +803,7 @@ impl Connection {
 			height: video_settings.height as i32,
 			width: video_settings.width as i32,
 			rendition: Rendition::VideoSource.into(),
+			gop_size: 0,
 		}
 		.encode_to_vec();
 
@@ -865,6 +974,7 @@ impl Connection {
 				video_settings,
 				audio_settings,
 				data,
+				..
 			})) => {
 				let bitrate = video_settings.bitrate as u64 + audio_settings.bitrate as u64;
 				if bitrate >= config.max_bitrate {
@@ -17,6 +17,10 @@ pub enum IngestError {
 	SubscriptionClosedUnexpectedly,
 	FailedToRequestTranscoder,
 	FailedToUpdateRoom,
+	SrtConnectionError,
+	SrtConnectionTimeout,
+	WhipConnectionError,
+	WhipConnectionTimeout,
 }
 
 impl std::fmt::Display for IngestError {
@@ -55,6 +59,10 @@ impl std::fmt::Display for IngestError {
 			}
 			Self::FailedToRequestTranscoder => write!(f, "I16: Failed to request transcoder"),
 			Self::FailedToUpdateRoom => write!(f, "I17: Failed to update room"),
+			Self::SrtConnectionError => write!(f, "I18: SRT connection error"),
+			Self::SrtConnectionTimeout => write!(f, "I19: SRT connection timeout"),
+			Self::WhipConnectionError => write!(f, "I20: WHIP connection error"),
+			Self::WhipConnectionTimeout => write!(f, "I21: WHIP connection timeout"),
 		}
 	}
 }
@@ -3,21 +3,94 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
+use srt::SrtListener;
 use tokio::net::TcpSocket;
 use utils::context::ContextExt;
 use utils::prelude::FutureTimeout;
+use whip::WhipListener;
 
-use crate::config::IngestConfig;
+use crate::config::{IngestConfig, SrtConfig, WhipConfig};
 use crate::global::IngestGlobal;
 
 mod bytes_tracker;
 mod connection;
 mod errors;
 mod rtmp_session;
+mod session;
+mod srt_session;
 mod update;
+mod whip_session;
 
 pub async fn run<G: IngestGlobal>(global: Arc<G>) -> Result<()> {
 	let config = global.config::<IngestConfig>();
+
+	let srt = async {
+		match config.srt.clone() {
+			Some(srt) => run_srt(global.clone(), srt).await,
+			None => Ok(()),
+		}
+	};
+
+	let whip = async {
+		match config.whip.clone() {
+			Some(whip) => run_whip(global.clone(), whip).await,
+			None => Ok(()),
+		}
+	};
+
+	tokio::try_join!(run_rtmp(global.clone()), srt, whip)?;
+
+	Ok(())
+}
+
+async fn run_whip<G: IngestGlobal>(global: Arc<G>, config: WhipConfig) -> Result<()> {
+	tracing::info!("Ingest(WHIP) listening on {}", config.bind_address);
+
+	let mut listener = WhipListener::bind(
+		config.bind_address,
+		whip::WhipConfig {
+			public_ips: config.public_ips,
+			udp_ports: config.udp_port_min.zip(config.udp_port_max),
+			connect_timeout: config.connect_timeout,
+			..Default::default()
+		},
+	)
+	.await?;
+
+	while let Ok(Some(request)) = listener.accept().context(global.ctx()).await {
+		tracing::debug!("WHIP offer from {}", request.peer_addr());
+
+		let ip = request.peer_addr().ip();
+		tokio::spawn(connection::handle_whip(global.clone(), request, ip));
+	}
+
+	Ok(())
+}
+
+async fn run_srt<G: IngestGlobal>(global: Arc<G>, config: SrtConfig) -> Result<()> {
+	tracing::info!("Ingest(SRT) listening on {}", config.bind_address);
+
+	let mut listener = SrtListener::bind(
+		config.bind_address,
+		srt::SrtConfig {
+			latency: config.latency,
+			..Default::default()
+		},
+	)
+	.await?;
+
+	while let Ok(Some(request)) = listener.accept().context(global.ctx()).await {
+		tracing::debug!("SRT connection request from {}", request.peer_addr());
+
+		let ip = request.peer_addr().ip();
+		tokio::spawn(connection::handle_srt(global.clone(), request, ip));
+	}
+
+	Ok(())
+}
+
+async fn run_rtmp<G: IngestGlobal>(global: Arc<G>) -> Result<()> {
+	let config = global.config::<IngestConfig>();
 	tracing::info!("Ingest(RTMP) listening on {}", config.rtmp.bind_address);
 	let socket = if config.rtmp.bind_address.is_ipv6() {
 		TcpSocket::new_v6()?
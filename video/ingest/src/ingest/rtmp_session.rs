@@ -5,17 +5,15 @@ use rtmp::{ChannelData, PublishRequest, SessionError};
 use tokio::select;
 use tokio::sync::mpsc;
 
+use super::errors::IngestError;
+use super::session::{Data, IngestSession};
+
 pub struct RtmpSession<'a, F> {
 	future: Pin<&'a mut F>,
 	publish: mpsc::Receiver<PublishRequest>,
 	data: mpsc::Receiver<ChannelData>,
 }
 
-pub enum Data {
-	Data(Option<ChannelData>),
-	Closed(bool),
-}
-
 impl<'a, F: Future<Output = Result<bool, SessionError>>> RtmpSession<'a, F> {
 	pub fn new(future: Pin<&'a mut F>, publish: mpsc::Receiver<PublishRequest>, data: mpsc::Receiver<ChannelData>) -> Self {
 		Self { future, publish, data }
@@ -27,8 +25,15 @@ impl<'a, F: Future<Output = Result<bool, SessionError>>> RtmpSession<'a, F> {
 			publish = self.publish.recv() => Ok(publish),
 		}
 	}
+}
+
+impl<F: Future<Output = Result<bool, SessionError>>> IngestSession for RtmpSession<'_, F> {
+	type Error = SessionError;
+
+	const CONNECTION_ERROR: IngestError = IngestError::RtmpConnectionError;
+	const CONNECTION_TIMEOUT: IngestError = IngestError::RtmpConnectionTimeout;
 
-	pub async fn data(&mut self) -> Result<Data, SessionError> {
+	async fn data(&mut self) -> Result<Data, SessionError> {
 		select! {
 			r = self.future.as_mut() => Ok(r.map(Data::Closed)?),
 			data = self.data.recv() => Ok(Data::Data(data)),
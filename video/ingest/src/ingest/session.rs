@@ -0,0 +1,46 @@
+use std::fmt::Display;
+
+use bytes::Bytes;
+use flv::{FlvTag, FlvTagData};
+use rtmp::ChannelData;
+
+use super::errors::IngestError;
+
+pub enum Data {
+	Data(Option<ChannelData>),
+	Closed(bool),
+}
+
+/// A publishing session, which provides the FLV data of the stream
+/// regardless of the protocol it was ingested with.
+pub trait IngestSession {
+	type Error: Display;
+
+	/// The error reported when the session fails.
+	const CONNECTION_ERROR: IngestError;
+	/// The error reported when the session stops sending data.
+	const CONNECTION_TIMEOUT: IngestError;
+
+	/// Get the next piece of data, or if the session was closed. The boolean in
+	/// `Data::Closed` is true if the session was closed cleanly.
+	async fn data(&mut self) -> Result<Data, Self::Error>;
+}
+
+/// Mux the tag the way a RTMP session would send it.
+pub fn channel_data(tag: FlvTag) -> Option<ChannelData> {
+	let mut data = Vec::new();
+	if let Err(err) = tag.data.mux(&mut data) {
+		tracing::debug!(error = %err, "failed to mux tag");
+		return None;
+	}
+
+	let data = Bytes::from(data);
+	let timestamp = tag.timestamp;
+
+	match tag.data {
+		FlvTagData::Video { .. } => Some(ChannelData::Video { timestamp, data }),
+		FlvTagData::Audio { .. } => Some(ChannelData::Audio { timestamp, data }),
+		FlvTagData::ScriptData { .. } => Some(ChannelData::Metadata { timestamp, data }),
+		FlvTagData::Unknown { .. } => None,
+	}
+}
@@ -0,0 +1,66 @@
+use srt::{SrtError, SrtSocket};
+use transmuxer::TsRemuxer;
+
+use super::errors::IngestError;
+use super::session::{channel_data, Data, IngestSession};
+
+/// A SRT session, the transport stream it sends is converted into the same
+/// FLV data a RTMP session provides.
+pub struct SrtSession {
+	socket: SrtSocket,
+	remuxer: TsRemuxer,
+	closed: bool,
+}
+
+impl SrtSession {
+	pub fn new(socket: SrtSocket) -> Self {
+		Self {
+			socket,
+			remuxer: TsRemuxer::new(),
+			closed: false,
+		}
+	}
+}
+
+impl IngestSession for SrtSession {
+	type Error = SrtError;
+
+	const CONNECTION_ERROR: IngestError = IngestError::SrtConnectionError;
+	const CONNECTION_TIMEOUT: IngestError = IngestError::SrtConnectionTimeout;
+
+	async fn data(&mut self) -> Result<Data, SrtError> {
+		loop {
+			match self.remuxer.read_tag() {
+				Ok(Some(tag)) => {
+					if let Some(data) = channel_data(tag) {
+						return Ok(Data::Data(Some(data)));
+					}
+
+					continue;
+				}
+				Ok(None) => {}
+				Err(err) => {
+					// Packets which arrived too late are skipped by SRT, so the stream
+					// can have gaps. The invalid data is dropped and we keep going.
+					tracing::debug!(error = %err, "invalid transport stream");
+					continue;
+				}
+			}
+
+			if self.closed {
+				return Ok(Data::Closed(true));
+			}
+
+			match self.socket.recv().await? {
+				Some(data) => self.remuxer.extend_data(&data),
+				None => {
+					self.closed = true;
+
+					if let Err(err) = self.remuxer.flush() {
+						tracing::debug!(error = %err, "invalid transport stream");
+					}
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use transmuxer::{EsRemuxer, ES_TIMESCALE};
+use whip::{Frame, WhipError};
+
+use super::errors::IngestError;
+use super::session::{channel_data, Data, IngestSession};
+
+/// A WHIP session, the frames it receives are converted into the same FLV
+/// data a RTMP session provides.
+pub struct WhipSession {
+	session: whip::WhipSession,
+	remuxer: EsRemuxer,
+	closed: bool,
+}
+
+impl WhipSession {
+	pub fn new(session: whip::WhipSession) -> Self {
+		Self {
+			session,
+			remuxer: EsRemuxer::new(),
+			closed: false,
+		}
+	}
+}
+
+impl IngestSession for WhipSession {
+	type Error = WhipError;
+
+	const CONNECTION_ERROR: IngestError = IngestError::WhipConnectionError;
+	const CONNECTION_TIMEOUT: IngestError = IngestError::WhipConnectionTimeout;
+
+	async fn data(&mut self) -> Result<Data, WhipError> {
+		loop {
+			if let Some(tag) = self.remuxer.read_tag() {
+				if let Some(data) = channel_data(tag) {
+					return Ok(Data::Data(Some(data)));
+				}
+
+				continue;
+			}
+
+			if self.closed {
+				return Ok(Data::Closed(true));
+			}
+
+			match self.session.recv().await? {
+				Some(Frame::H264 {
+					timestamp,
+					keyframe,
+					nalus,
+				}) => {
+					// WebRTC does not send B-frames, so the frames are in presentation
+					// order.
+					let timestamp = es_timestamp(timestamp);
+					if let Err(err) = self.remuxer.push_h264(timestamp, timestamp, nalus, keyframe) {
+						tracing::debug!(error = %err, "invalid h264 frame");
+					}
+				}
+				Some(Frame::Opus {
+					timestamp,
+					channels,
+					data,
+				}) => self.remuxer.push_opus(es_timestamp(timestamp), channels, data),
+				None => {
+					self.closed = true;
+					self.remuxer.flush();
+				}
+			}
+		}
+	}
+}
+
+fn es_timestamp(timestamp: Duration) -> u64 {
+	(timestamp.as_nanos() * ES_TIMESCALE as u128 / 1_000_000_000) as u64
+}
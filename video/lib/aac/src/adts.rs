@@ -0,0 +1,256 @@
+use std::io;
+
+use bytes::Bytes;
+use bytesio::bit_reader::BitReader;
+use bytesio::bit_writer::BitWriter;
+use num_traits::FromPrimitive;
+
+use crate::{AudioObjectType, AudioSpecificConfig, SampleFrequencyIndex};
+
+const SYNCWORD: u64 = 0xFFF;
+
+/// The buffer fullness which signals a variable bitrate stream.
+const VARIABLE_BITRATE_BUFFER_FULLNESS: u16 = 0x7FF;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// ADTS header
+/// ISO/IEC 14496-3:2019(E) - 1.A.2.2
+///
+/// ADTS can only describe AAC Main, LC, SSR and LTP with a sampling frequency
+/// from the table, HE-AAC is signalled implicitly with the core config.
+pub struct AdtsHeader {
+	/// Set for MPEG-2 AAC, unset for MPEG-4 AAC
+	pub id: bool,
+	pub protection_absent: bool,
+	pub audio_object_type: AudioObjectType,
+	pub sampling_frequency_index: u8,
+	pub private_bit: bool,
+	pub channel_configuration: u8,
+	pub original_copy: bool,
+	pub home: bool,
+	pub copyright_identification_bit: bool,
+	pub copyright_identification_start: bool,
+	/// The length of the frame, including the header.
+	pub frame_length: u16,
+	/// 0x7FF for variable bitrate streams
+	pub adts_buffer_fullness: u16,
+	/// The number of raw data blocks in the frame minus one
+	pub number_of_raw_data_blocks_in_frame: u8,
+	/// Only present if protection_absent is not set and there is more than one
+	/// raw data block in the frame.
+	pub raw_data_block_position: Vec<u16>,
+	/// Only present if protection_absent is not set
+	pub crc_check: Option<u16>,
+}
+
+impl AdtsHeader {
+	/// The size of a header without a CRC
+	pub const MIN_SIZE: usize = 7;
+
+	/// Creates the header of a frame with a single raw data block for the
+	/// given config, the payload length is the length of the raw data block.
+	pub fn new(config: &AudioSpecificConfig, payload_length: usize) -> io::Result<Self> {
+		if !matches!(u16::from(config.audio_object_type), 1..=4) {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"audio object type cannot be signalled in ADTS",
+			));
+		}
+
+		let sampling_frequency_index = SampleFrequencyIndex::from_freq(config.sampling_frequency)
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "sampling frequency cannot be signalled in ADTS"))?;
+
+		let frame_length = u16::try_from(payload_length + Self::MIN_SIZE)
+			.ok()
+			.filter(|frame_length| *frame_length < 1 << 13)
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "ADTS frame is too long"))?;
+
+		Ok(Self {
+			id: false,
+			protection_absent: true,
+			audio_object_type: config.audio_object_type,
+			sampling_frequency_index: sampling_frequency_index as u8,
+			private_bit: false,
+			channel_configuration: config.channel_configuration,
+			original_copy: false,
+			home: false,
+			copyright_identification_bit: false,
+			copyright_identification_start: false,
+			frame_length,
+			adts_buffer_fullness: VARIABLE_BITRATE_BUFFER_FULLNESS,
+			number_of_raw_data_blocks_in_frame: 0,
+			raw_data_block_position: Vec::new(),
+			crc_check: None,
+		})
+	}
+
+	pub fn parse(data: &[u8]) -> io::Result<Self> {
+		let mut bit_reader = BitReader::new(io::Cursor::new(data));
+
+		// adts_fixed_header
+		if bit_reader.read_bits(12)? != SYNCWORD {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid ADTS syncword"));
+		}
+
+		let id = bit_reader.read_bit()?;
+		if bit_reader.read_bits(2)? != 0 {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "ADTS layer must be 0"));
+		}
+
+		let protection_absent = bit_reader.read_bit()?;
+		let audio_object_type = AudioObjectType::from(bit_reader.read_bits(2)? as u16 + 1);
+		let sampling_frequency_index = bit_reader.read_bits(4)? as u8;
+		if sampling_frequency_index > SampleFrequencyIndex::Freq7350 as u8 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"invalid ADTS sampling frequency index",
+			));
+		}
+
+		let private_bit = bit_reader.read_bit()?;
+		let channel_configuration = bit_reader.read_bits(3)? as u8;
+		let original_copy = bit_reader.read_bit()?;
+		let home = bit_reader.read_bit()?;
+
+		// adts_variable_header
+		let copyright_identification_bit = bit_reader.read_bit()?;
+		let copyright_identification_start = bit_reader.read_bit()?;
+		let frame_length = bit_reader.read_bits(13)? as u16;
+		let adts_buffer_fullness = bit_reader.read_bits(11)? as u16;
+		let number_of_raw_data_blocks_in_frame = bit_reader.read_bits(2)? as u8;
+
+		// adts_error_check or adts_header_error_check
+		let mut raw_data_block_position = Vec::new();
+		let mut crc_check = None;
+		if !protection_absent {
+			for _ in 0..number_of_raw_data_blocks_in_frame {
+				raw_data_block_position.push(bit_reader.read_bits(16)? as u16);
+			}
+
+			crc_check = Some(bit_reader.read_bits(16)? as u16);
+		}
+
+		let header = Self {
+			id,
+			protection_absent,
+			audio_object_type,
+			sampling_frequency_index,
+			private_bit,
+			channel_configuration,
+			original_copy,
+			home,
+			copyright_identification_bit,
+			copyright_identification_start,
+			frame_length,
+			adts_buffer_fullness,
+			number_of_raw_data_blocks_in_frame,
+			raw_data_block_position,
+			crc_check,
+		};
+
+		if (frame_length as usize) < header.size() {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"ADTS frame length is shorter than the header",
+			));
+		}
+
+		Ok(header)
+	}
+
+	/// The size of the header, including the CRC and raw data block positions.
+	pub fn size(&self) -> usize {
+		if self.protection_absent {
+			Self::MIN_SIZE
+		} else {
+			Self::MIN_SIZE + 2 * self.number_of_raw_data_blocks_in_frame as usize + 2
+		}
+	}
+
+	pub fn sampling_frequency(&self) -> u32 {
+		SampleFrequencyIndex::from_u8(self.sampling_frequency_index)
+			.map(|index| index.to_freq())
+			.unwrap_or(0)
+	}
+
+	/// The AudioSpecificConfig which describes the frames.
+	pub fn audio_specific_config(&self) -> io::Result<AudioSpecificConfig> {
+		AudioSpecificConfig::new(self.audio_object_type, self.sampling_frequency(), self.channel_configuration)
+	}
+
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		let mut bit_writer = BitWriter::default();
+
+		bit_writer.write_bits(SYNCWORD, 12)?;
+		bit_writer.write_bit(self.id)?;
+		bit_writer.write_bits(0, 2)?; // layer
+		bit_writer.write_bit(self.protection_absent)?;
+		bit_writer.write_bits(u16::from(self.audio_object_type).saturating_sub(1) as u64, 2)?;
+		bit_writer.write_bits(self.sampling_frequency_index as u64, 4)?;
+		bit_writer.write_bit(self.private_bit)?;
+		bit_writer.write_bits(self.channel_configuration as u64, 3)?;
+		bit_writer.write_bit(self.original_copy)?;
+		bit_writer.write_bit(self.home)?;
+
+		bit_writer.write_bit(self.copyright_identification_bit)?;
+		bit_writer.write_bit(self.copyright_identification_start)?;
+		bit_writer.write_bits(self.frame_length as u64, 13)?;
+		bit_writer.write_bits(self.adts_buffer_fullness as u64, 11)?;
+		bit_writer.write_bits(self.number_of_raw_data_blocks_in_frame as u64, 2)?;
+
+		if !self.protection_absent {
+			for position in &self.raw_data_block_position {
+				bit_writer.write_bits(*position as u64, 16)?;
+			}
+
+			bit_writer.write_bits(self.crc_check.unwrap_or(0) as u64, 16)?;
+		}
+
+		writer.write_all(&bit_writer.into_inner())
+	}
+}
+
+/// An iterator over the ADTS frames in a buffer, yields the header and the
+/// raw data blocks of every frame.
+///
+/// The iterator stops after the first error.
+#[derive(Debug, Clone)]
+pub struct AdtsFrames {
+	data: Bytes,
+}
+
+impl AdtsFrames {
+	pub fn new(data: Bytes) -> Self {
+		Self { data }
+	}
+}
+
+impl Iterator for AdtsFrames {
+	type Item = io::Result<(AdtsHeader, Bytes)>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.data.is_empty() {
+			return None;
+		}
+
+		let header = match AdtsHeader::parse(&self.data) {
+			Ok(header) => header,
+			Err(err) => {
+				self.data.clear();
+				return Some(Err(err));
+			}
+		};
+
+		if header.frame_length as usize > self.data.len() {
+			self.data.clear();
+			return Some(Err(io::Error::new(
+				io::ErrorKind::UnexpectedEof,
+				"ADTS frame is longer than the remaining data",
+			)));
+		}
+
+		let frame = self.data.split_to(header.frame_length as usize);
+		let payload = frame.slice(header.size()..);
+		Some(Ok((header, payload)))
+	}
+}
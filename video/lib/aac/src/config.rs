@@ -1,7 +1,11 @@
 use std::io;
 
+use std::io::{Read, Write};
+
+use byteorder::ReadBytesExt;
 use bytes::Bytes;
 use bytesio::bit_reader::BitReader;
+use bytesio::bit_writer::BitWriter;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 
@@ -9,18 +13,148 @@ use num_traits::FromPrimitive;
 /// Audio Specific Config
 /// ISO/IEC 14496-3:2019(E) - 1.6
 pub struct AudioSpecificConfig {
+	/// The object type of the core decoder, for HE-AAC this is AAC LC.
 	pub audio_object_type: AudioObjectType,
+	/// The sampling frequency of the core decoder
 	pub sampling_frequency: u32,
 	pub channel_configuration: u8,
+	/// The explicitly signalled SBR and PS extension, `None` if the extension
+	/// is not signalled and SBR can only be signalled implicitly.
+	pub sbr: Option<SbrConfig>,
+	/// Only present for general audio object types, like AAC LC
+	pub ga_specific_config: Option<GaSpecificConfig>,
+	/// Only present for error resilient general audio object types
+	pub ep_config: Option<u8>,
 	pub data: Bytes,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The explicit signalling of SBR (HE-AAC) and PS (HE-AACv2)
+/// ISO/IEC 14496-3:2019(E) - 1.6.5
+pub struct SbrConfig {
+	pub signalling: SbrSignalling,
+	/// Always set for hierarchical signalling, backward compatible signalling
+	/// can also signal that SBR is not present.
+	pub sbr_present: bool,
+	pub ps_present: bool,
+	/// The output sampling frequency of the SBR decoder, this is the core
+	/// sampling frequency if SBR is not present.
+	pub sampling_frequency: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// ISO/IEC 14496-3:2019(E) - 1.6.5.2
+pub enum SbrSignalling {
+	/// The SBR or PS object type is followed by the core object type.
+	Hierarchical,
+	/// A sync extension after the core config, which decoders without SBR
+	/// support ignore.
+	BackwardCompatible,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// GA Specific Config
+/// ISO/IEC 14496-3:2019(E) - 4.4.1
+pub struct GaSpecificConfig {
+	/// 960 instead of 1024 samples per frame
+	pub frame_length_flag: bool,
+	/// Only present if the config depends on a core coder
+	pub core_coder_delay: Option<u16>,
+	/// Only present if the channel configuration is 0
+	pub program_config_element: Option<ProgramConfigElement>,
+	/// Only present for AAC scalable object types
+	pub layer_nr: Option<u8>,
+	/// Only present if the extension flag is set, which is required for error
+	/// resilient object types.
+	pub extension: Option<GaExtension>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// ISO/IEC 14496-3:2019(E) - 4.4.1
+pub struct GaExtension {
+	/// Only present for ER BSAC
+	pub num_of_sub_frame: Option<u8>,
+	/// Only present for ER BSAC
+	pub layer_length: Option<u16>,
+	/// Only present for ER AAC object types
+	pub resilience_flags: Option<ResilienceFlags>,
+	pub extension_flag3: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// ISO/IEC 14496-3:2019(E) - 4.4.1
+pub struct ResilienceFlags {
+	pub aac_section_data_resilience_flag: bool,
+	pub aac_scalefactor_data_resilience_flag: bool,
+	pub aac_spectral_data_resilience_flag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Program Config Element
+/// ISO/IEC 14496-3:2019(E) - 4.4.1.1
+///
+/// Describes the channel layout if the channel configuration is 0, for
+/// example for layouts with more than 8 channels.
+pub struct ProgramConfigElement {
+	pub element_instance_tag: u8,
+	pub object_type: u8,
+	pub sampling_frequency_index: u8,
+	pub front_channel_elements: Vec<ChannelElement>,
+	pub side_channel_elements: Vec<ChannelElement>,
+	pub back_channel_elements: Vec<ChannelElement>,
+	/// The instance tags of the LFE channel elements
+	pub lfe_element_tags: Vec<u8>,
+	/// The instance tags of the data stream elements
+	pub assoc_data_element_tags: Vec<u8>,
+	pub valid_cc_elements: Vec<CcElement>,
+	pub mono_mixdown_element_number: Option<u8>,
+	pub stereo_mixdown_element_number: Option<u8>,
+	pub matrix_mixdown: Option<MatrixMixdown>,
+	pub comment: Bytes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A single channel element or channel pair element in a program config
+/// element
+pub struct ChannelElement {
+	/// A channel pair element with two channels
+	pub is_cpe: bool,
+	pub tag_select: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A coupling channel element in a program config element
+pub struct CcElement {
+	pub is_ind_sw: bool,
+	pub tag_select: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// ISO/IEC 14496-3:2019(E) - 4.5.1.2.2
+pub struct MatrixMixdown {
+	pub matrix_mixdown_idx: u8,
+	pub pseudo_surround_enable: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Copy, Eq)]
-/// SBR Audio Object Type
-/// ISO/IEC 14496-3:2019(E) - 1.5.1.2.6
+/// Audio Object Type
+/// ISO/IEC 14496-3:2019(E) - 1.5.1.1 (Table 1.1)
 pub enum AudioObjectType {
 	AacMain,
 	AacLowComplexity,
+	AacScalableSampleRate,
+	AacLongTermPrediction,
+	/// HE-AAC
+	SpectralBandReplication,
+	AacScalable,
+	ErAacLowComplexity,
+	ErAacLongTermPrediction,
+	ErAacScalable,
+	ErBsac,
+	ErAacLowDelay,
+	/// HE-AACv2
+	ParametricStereo,
+	ErAacEnhancedLowDelay,
 	Unknown(u16),
 }
 
@@ -29,6 +163,17 @@ impl From<u16> for AudioObjectType {
 		match value {
 			1 => AudioObjectType::AacMain,
 			2 => AudioObjectType::AacLowComplexity,
+			3 => AudioObjectType::AacScalableSampleRate,
+			4 => AudioObjectType::AacLongTermPrediction,
+			5 => AudioObjectType::SpectralBandReplication,
+			6 => AudioObjectType::AacScalable,
+			17 => AudioObjectType::ErAacLowComplexity,
+			19 => AudioObjectType::ErAacLongTermPrediction,
+			20 => AudioObjectType::ErAacScalable,
+			22 => AudioObjectType::ErBsac,
+			23 => AudioObjectType::ErAacLowDelay,
+			29 => AudioObjectType::ParametricStereo,
+			39 => AudioObjectType::ErAacEnhancedLowDelay,
 			_ => AudioObjectType::Unknown(value),
 		}
 	}
@@ -39,11 +184,35 @@ impl From<AudioObjectType> for u16 {
 		match value {
 			AudioObjectType::AacMain => 1,
 			AudioObjectType::AacLowComplexity => 2,
+			AudioObjectType::AacScalableSampleRate => 3,
+			AudioObjectType::AacLongTermPrediction => 4,
+			AudioObjectType::SpectralBandReplication => 5,
+			AudioObjectType::AacScalable => 6,
+			AudioObjectType::ErAacLowComplexity => 17,
+			AudioObjectType::ErAacLongTermPrediction => 19,
+			AudioObjectType::ErAacScalable => 20,
+			AudioObjectType::ErBsac => 22,
+			AudioObjectType::ErAacLowDelay => 23,
+			AudioObjectType::ParametricStereo => 29,
+			AudioObjectType::ErAacEnhancedLowDelay => 39,
 			AudioObjectType::Unknown(value) => value,
 		}
 	}
 }
 
+impl AudioObjectType {
+	/// Object types which use the GASpecificConfig
+	/// ISO/IEC 14496-3:2019(E) - 1.6.2.1
+	pub fn is_general_audio(&self) -> bool {
+		matches!(u16::from(*self), 1..=4 | 6 | 7 | 17 | 19..=23)
+	}
+
+	/// Error resilient object types
+	pub fn is_error_resilient(&self) -> bool {
+		matches!(u16::from(*self), 17 | 19..=27 | 39)
+	}
+}
+
 #[derive(FromPrimitive)]
 #[repr(u8)]
 /// Sampling Frequency Index
@@ -90,28 +259,540 @@ impl SampleFrequencyIndex {
 	}
 }
 
+impl SampleFrequencyIndex {
+	/// The index of a sampling frequency, `None` if the frequency has to be
+	/// escaped.
+	pub fn from_freq(freq: u32) -> Option<Self> {
+		(0x0..=0xC).filter_map(Self::from_u8).find(|index| index.to_freq() == freq)
+	}
+}
+
+const SYNC_EXTENSION_SBR: u64 = 0x2B7;
+const SYNC_EXTENSION_PS: u64 = 0x548;
+
 impl AudioSpecificConfig {
+	/// Creates the config of a general audio object type without any
+	/// extensions, the raw config is written to `data`.
+	pub fn new(audio_object_type: AudioObjectType, sampling_frequency: u32, channel_configuration: u8) -> io::Result<Self> {
+		let mut config = Self {
+			audio_object_type,
+			sampling_frequency,
+			channel_configuration,
+			sbr: None,
+			ga_specific_config: Some(GaSpecificConfig {
+				frame_length_flag: false,
+				core_coder_delay: None,
+				program_config_element: None,
+				layer_nr: None,
+				extension: None,
+			}),
+			ep_config: None,
+			data: Bytes::new(),
+		};
+
+		config.data = config.build()?;
+		Ok(config)
+	}
+
 	pub fn parse(data: Bytes) -> io::Result<Self> {
 		let mut bitreader = BitReader::from(data);
-		let mut audio_object_type = bitreader.read_bits(5)? as u16;
-		if audio_object_type == 31 {
-			audio_object_type = 32 + bitreader.read_bits(6)? as u16;
+		let mut audio_object_type = read_audio_object_type(&mut bitreader)?;
+		let sampling_frequency = read_sampling_frequency(&mut bitreader)?;
+		let channel_configuration = bitreader.read_bits(4)? as u8;
+
+		// Explicit hierarchical signalling of SBR and PS
+		let mut sbr = None;
+		if matches!(
+			audio_object_type,
+			AudioObjectType::SpectralBandReplication | AudioObjectType::ParametricStereo
+		) {
+			sbr = Some(SbrConfig {
+				signalling: SbrSignalling::Hierarchical,
+				sbr_present: true,
+				ps_present: audio_object_type == AudioObjectType::ParametricStereo,
+				sampling_frequency: read_sampling_frequency(&mut bitreader)?,
+			});
+
+			audio_object_type = read_audio_object_type(&mut bitreader)?;
+			if audio_object_type == AudioObjectType::ErBsac {
+				// extensionChannelConfiguration
+				bitreader.read_bits(4)?;
+			}
 		}
 
-		let sampling_frequency_index = SampleFrequencyIndex::from_u8(bitreader.read_bits(4)? as u8)
-			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid sampling frequency index"))?;
-		let sampling_frequency = match sampling_frequency_index {
-			SampleFrequencyIndex::FreqEscape => bitreader.read_bits(24)? as u32,
-			_ => sampling_frequency_index.to_freq(),
-		};
+		// The configs of other object types are not parsed, so we cannot read
+		// anything after them.
+		let mut ga_specific_config = None;
+		let mut ep_config = None;
+		if audio_object_type.is_general_audio() {
+			ga_specific_config = Some(GaSpecificConfig::parse(
+				&mut bitreader,
+				audio_object_type,
+				channel_configuration,
+			)?);
 
-		let channel_configuration = bitreader.read_bits(4)? as u8;
+			if audio_object_type.is_error_resilient() {
+				ep_config = Some(bitreader.read_bits(2)? as u8);
+			}
+
+			// Explicit backward compatible signalling of SBR and PS
+			if sbr.is_none()
+				&& bitreader.remaining_bits() >= 16
+				&& bitreader.read_bits(11)? == SYNC_EXTENSION_SBR
+				&& read_audio_object_type(&mut bitreader)? == AudioObjectType::SpectralBandReplication
+			{
+				let sbr_present = bitreader.read_bit()?;
+				let mut sampling_frequency = sampling_frequency;
+				let mut ps_present = false;
+				if sbr_present {
+					sampling_frequency = read_sampling_frequency(&mut bitreader)?;
+					if bitreader.remaining_bits() >= 12 && bitreader.read_bits(11)? == SYNC_EXTENSION_PS {
+						ps_present = bitreader.read_bit()?;
+					}
+				}
+
+				sbr = Some(SbrConfig {
+					signalling: SbrSignalling::BackwardCompatible,
+					sbr_present,
+					ps_present,
+					sampling_frequency,
+				});
+			}
+		}
 
 		Ok(Self {
-			audio_object_type: audio_object_type.into(),
+			audio_object_type,
 			sampling_frequency,
 			channel_configuration,
+			sbr,
+			ga_specific_config,
+			ep_config,
 			data: bitreader.into_inner().into_inner(),
 		})
 	}
+
+	/// Writes the config, only general audio object types can be written.
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		let Some(ga_specific_config) = &self.ga_specific_config else {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"only general audio object types can be written",
+			));
+		};
+
+		let mut bit_writer = BitWriter::default();
+
+		match self.sbr {
+			Some(sbr) if sbr.signalling == SbrSignalling::Hierarchical => {
+				let extension_object_type = if sbr.ps_present {
+					AudioObjectType::ParametricStereo
+				} else {
+					AudioObjectType::SpectralBandReplication
+				};
+
+				write_audio_object_type(&mut bit_writer, extension_object_type)?;
+				write_sampling_frequency(&mut bit_writer, self.sampling_frequency)?;
+				bit_writer.write_bits(self.channel_configuration as u64, 4)?;
+				write_sampling_frequency(&mut bit_writer, sbr.sampling_frequency)?;
+				write_audio_object_type(&mut bit_writer, self.audio_object_type)?;
+				if self.audio_object_type == AudioObjectType::ErBsac {
+					// extensionChannelConfiguration
+					bit_writer.write_bits(self.channel_configuration as u64, 4)?;
+				}
+			}
+			_ => {
+				write_audio_object_type(&mut bit_writer, self.audio_object_type)?;
+				write_sampling_frequency(&mut bit_writer, self.sampling_frequency)?;
+				bit_writer.write_bits(self.channel_configuration as u64, 4)?;
+			}
+		}
+
+		ga_specific_config.mux(&mut bit_writer, self.audio_object_type, self.channel_configuration)?;
+
+		if self.audio_object_type.is_error_resilient() {
+			bit_writer.write_bits(self.ep_config.unwrap_or(0) as u64, 2)?;
+		}
+
+		if let Some(sbr) = self.sbr.filter(|sbr| sbr.signalling == SbrSignalling::BackwardCompatible) {
+			bit_writer.write_bits(SYNC_EXTENSION_SBR, 11)?;
+			write_audio_object_type(&mut bit_writer, AudioObjectType::SpectralBandReplication)?;
+			bit_writer.write_bit(sbr.sbr_present)?;
+			if sbr.sbr_present {
+				write_sampling_frequency(&mut bit_writer, sbr.sampling_frequency)?;
+				if sbr.ps_present {
+					bit_writer.write_bits(SYNC_EXTENSION_PS, 11)?;
+					bit_writer.write_bit(true)?;
+				}
+			}
+		}
+
+		bit_writer.align()?;
+		writer.write_all(&bit_writer.into_inner())
+	}
+
+	/// Writes the config to a new buffer.
+	pub fn build(&self) -> io::Result<Bytes> {
+		let mut data = Vec::new();
+		self.mux(&mut data)?;
+		Ok(Bytes::from(data))
+	}
+
+	/// The sampling frequency of the decoded audio, which is the SBR sampling
+	/// frequency if SBR is explicitly signalled.
+	pub fn output_sampling_frequency(&self) -> u32 {
+		match self.sbr {
+			Some(sbr) if sbr.sbr_present => sbr.sampling_frequency,
+			_ => self.sampling_frequency,
+		}
+	}
+
+	/// If SBR is not explicitly signalled it can still be present in the
+	/// frames of an AAC LC stream (implicit signalling), in which case the
+	/// decoder outputs twice the sampling frequency.
+	/// ISO/IEC 14496-3:2019(E) - 1.6.5.3
+	pub fn implicit_sbr_possible(&self) -> bool {
+		self.sbr.is_none() && self.audio_object_type == AudioObjectType::AacLowComplexity && self.sampling_frequency <= 24000
+	}
+
+	/// The object type used in codec strings (RFC 6381), which is the SBR or
+	/// PS object type for HE-AAC.
+	pub fn codec_object_type(&self) -> AudioObjectType {
+		match self.sbr {
+			Some(sbr) if sbr.ps_present => AudioObjectType::ParametricStereo,
+			Some(sbr) if sbr.sbr_present => AudioObjectType::SpectralBandReplication,
+			_ => self.audio_object_type,
+		}
+	}
+
+	/// The number of channels of the core decoder, `None` for reserved
+	/// channel configurations.
+	/// ISO/IEC 14496-3:2019(E) - 1.6.3.4 (Table 1.19)
+	pub fn channels(&self) -> Option<u8> {
+		match self.channel_configuration {
+			0 => self
+				.ga_specific_config
+				.as_ref()
+				.and_then(|config| config.program_config_element.as_ref())
+				.map(ProgramConfigElement::channels),
+			1..=6 => Some(self.channel_configuration),
+			7 | 12 | 14 => Some(8),
+			11 => Some(7),
+			13 => Some(24),
+			_ => None,
+		}
+	}
+}
+
+impl GaSpecificConfig {
+	pub fn parse<T: io::Read + io::Seek>(
+		bitreader: &mut BitReader<T>,
+		audio_object_type: AudioObjectType,
+		channel_configuration: u8,
+	) -> io::Result<Self> {
+		let audio_object_type = u16::from(audio_object_type);
+
+		let frame_length_flag = bitreader.read_bit()?;
+		let core_coder_delay = if bitreader.read_bit()? {
+			Some(bitreader.read_bits(14)? as u16)
+		} else {
+			None
+		};
+		let extension_flag = bitreader.read_bit()?;
+
+		let program_config_element = if channel_configuration == 0 {
+			Some(ProgramConfigElement::parse(bitreader)?)
+		} else {
+			None
+		};
+
+		let layer_nr = if audio_object_type == 6 || audio_object_type == 20 {
+			Some(bitreader.read_bits(3)? as u8)
+		} else {
+			None
+		};
+
+		let extension = if extension_flag {
+			let (num_of_sub_frame, layer_length) = if audio_object_type == 22 {
+				(Some(bitreader.read_bits(5)? as u8), Some(bitreader.read_bits(11)? as u16))
+			} else {
+				(None, None)
+			};
+
+			let resilience_flags = if matches!(audio_object_type, 17 | 19 | 20 | 23) {
+				Some(ResilienceFlags {
+					aac_section_data_resilience_flag: bitreader.read_bit()?,
+					aac_scalefactor_data_resilience_flag: bitreader.read_bit()?,
+					aac_spectral_data_resilience_flag: bitreader.read_bit()?,
+				})
+			} else {
+				None
+			};
+
+			Some(GaExtension {
+				num_of_sub_frame,
+				layer_length,
+				resilience_flags,
+				extension_flag3: bitreader.read_bit()?,
+			})
+		} else {
+			None
+		};
+
+		Ok(Self {
+			frame_length_flag,
+			core_coder_delay,
+			program_config_element,
+			layer_nr,
+			extension,
+		})
+	}
+
+	pub fn mux(
+		&self,
+		bit_writer: &mut BitWriter,
+		audio_object_type: AudioObjectType,
+		channel_configuration: u8,
+	) -> io::Result<()> {
+		bit_writer.write_bit(self.frame_length_flag)?;
+		bit_writer.write_bit(self.core_coder_delay.is_some())?;
+		if let Some(core_coder_delay) = self.core_coder_delay {
+			bit_writer.write_bits(core_coder_delay as u64, 14)?;
+		}
+		bit_writer.write_bit(self.extension.is_some())?;
+
+		if channel_configuration == 0 {
+			let Some(program_config_element) = &self.program_config_element else {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidInput,
+					"channel configuration 0 requires a program config element",
+				));
+			};
+
+			program_config_element.mux(bit_writer)?;
+		}
+
+		let audio_object_type = u16::from(audio_object_type);
+		if audio_object_type == 6 || audio_object_type == 20 {
+			bit_writer.write_bits(self.layer_nr.unwrap_or(0) as u64, 3)?;
+		}
+
+		if let Some(extension) = &self.extension {
+			if audio_object_type == 22 {
+				bit_writer.write_bits(extension.num_of_sub_frame.unwrap_or(0) as u64, 5)?;
+				bit_writer.write_bits(extension.layer_length.unwrap_or(0) as u64, 11)?;
+			}
+
+			if matches!(audio_object_type, 17 | 19 | 20 | 23) {
+				let flags = extension.resilience_flags.unwrap_or(ResilienceFlags {
+					aac_section_data_resilience_flag: false,
+					aac_scalefactor_data_resilience_flag: false,
+					aac_spectral_data_resilience_flag: false,
+				});
+
+				bit_writer.write_bit(flags.aac_section_data_resilience_flag)?;
+				bit_writer.write_bit(flags.aac_scalefactor_data_resilience_flag)?;
+				bit_writer.write_bit(flags.aac_spectral_data_resilience_flag)?;
+			}
+
+			bit_writer.write_bit(extension.extension_flag3)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl ProgramConfigElement {
+	/// The byte alignment is relative to the start of the reader, which has to
+	/// be the start of the AudioSpecificConfig.
+	pub fn parse<T: io::Read + io::Seek>(bitreader: &mut BitReader<T>) -> io::Result<Self> {
+		let element_instance_tag = bitreader.read_bits(4)? as u8;
+		let object_type = bitreader.read_bits(2)? as u8;
+		let sampling_frequency_index = bitreader.read_bits(4)? as u8;
+		let num_front_channel_elements = bitreader.read_bits(4)?;
+		let num_side_channel_elements = bitreader.read_bits(4)?;
+		let num_back_channel_elements = bitreader.read_bits(4)?;
+		let num_lfe_channel_elements = bitreader.read_bits(2)?;
+		let num_assoc_data_elements = bitreader.read_bits(3)?;
+		let num_valid_cc_elements = bitreader.read_bits(4)?;
+
+		let mono_mixdown_element_number = if bitreader.read_bit()? {
+			Some(bitreader.read_bits(4)? as u8)
+		} else {
+			None
+		};
+
+		let stereo_mixdown_element_number = if bitreader.read_bit()? {
+			Some(bitreader.read_bits(4)? as u8)
+		} else {
+			None
+		};
+
+		let matrix_mixdown = if bitreader.read_bit()? {
+			Some(MatrixMixdown {
+				matrix_mixdown_idx: bitreader.read_bits(2)? as u8,
+				pseudo_surround_enable: bitreader.read_bit()?,
+			})
+		} else {
+			None
+		};
+
+		let mut read_channel_elements = |count| {
+			(0..count)
+				.map(|_| {
+					Ok(ChannelElement {
+						is_cpe: bitreader.read_bit()?,
+						tag_select: bitreader.read_bits(4)? as u8,
+					})
+				})
+				.collect::<io::Result<Vec<_>>>()
+		};
+
+		let front_channel_elements = read_channel_elements(num_front_channel_elements)?;
+		let side_channel_elements = read_channel_elements(num_side_channel_elements)?;
+		let back_channel_elements = read_channel_elements(num_back_channel_elements)?;
+
+		let lfe_element_tags = (0..num_lfe_channel_elements)
+			.map(|_| Ok(bitreader.read_bits(4)? as u8))
+			.collect::<io::Result<Vec<_>>>()?;
+		let assoc_data_element_tags = (0..num_assoc_data_elements)
+			.map(|_| Ok(bitreader.read_bits(4)? as u8))
+			.collect::<io::Result<Vec<_>>>()?;
+		let valid_cc_elements = (0..num_valid_cc_elements)
+			.map(|_| {
+				Ok(CcElement {
+					is_ind_sw: bitreader.read_bit()?,
+					tag_select: bitreader.read_bits(4)? as u8,
+				})
+			})
+			.collect::<io::Result<Vec<_>>>()?;
+
+		bitreader.align()?;
+
+		let comment_field_bytes = bitreader.read_u8()?;
+		let mut comment = vec![0; comment_field_bytes as usize];
+		bitreader.read_exact(&mut comment)?;
+
+		Ok(Self {
+			element_instance_tag,
+			object_type,
+			sampling_frequency_index,
+			front_channel_elements,
+			side_channel_elements,
+			back_channel_elements,
+			lfe_element_tags,
+			assoc_data_element_tags,
+			valid_cc_elements,
+			mono_mixdown_element_number,
+			stereo_mixdown_element_number,
+			matrix_mixdown,
+			comment: Bytes::from(comment),
+		})
+	}
+
+	/// The byte alignment is relative to the start of the writer, which has to
+	/// be the start of the AudioSpecificConfig.
+	pub fn mux(&self, bit_writer: &mut BitWriter) -> io::Result<()> {
+		bit_writer.write_bits(self.element_instance_tag as u64, 4)?;
+		bit_writer.write_bits(self.object_type as u64, 2)?;
+		bit_writer.write_bits(self.sampling_frequency_index as u64, 4)?;
+		bit_writer.write_bits(self.front_channel_elements.len() as u64, 4)?;
+		bit_writer.write_bits(self.side_channel_elements.len() as u64, 4)?;
+		bit_writer.write_bits(self.back_channel_elements.len() as u64, 4)?;
+		bit_writer.write_bits(self.lfe_element_tags.len() as u64, 2)?;
+		bit_writer.write_bits(self.assoc_data_element_tags.len() as u64, 3)?;
+		bit_writer.write_bits(self.valid_cc_elements.len() as u64, 4)?;
+
+		bit_writer.write_bit(self.mono_mixdown_element_number.is_some())?;
+		if let Some(element_number) = self.mono_mixdown_element_number {
+			bit_writer.write_bits(element_number as u64, 4)?;
+		}
+
+		bit_writer.write_bit(self.stereo_mixdown_element_number.is_some())?;
+		if let Some(element_number) = self.stereo_mixdown_element_number {
+			bit_writer.write_bits(element_number as u64, 4)?;
+		}
+
+		bit_writer.write_bit(self.matrix_mixdown.is_some())?;
+		if let Some(matrix_mixdown) = self.matrix_mixdown {
+			bit_writer.write_bits(matrix_mixdown.matrix_mixdown_idx as u64, 2)?;
+			bit_writer.write_bit(matrix_mixdown.pseudo_surround_enable)?;
+		}
+
+		for element in self
+			.front_channel_elements
+			.iter()
+			.chain(&self.side_channel_elements)
+			.chain(&self.back_channel_elements)
+		{
+			bit_writer.write_bit(element.is_cpe)?;
+			bit_writer.write_bits(element.tag_select as u64, 4)?;
+		}
+
+		for tag in self.lfe_element_tags.iter().chain(&self.assoc_data_element_tags) {
+			bit_writer.write_bits(*tag as u64, 4)?;
+		}
+
+		for element in &self.valid_cc_elements {
+			bit_writer.write_bit(element.is_ind_sw)?;
+			bit_writer.write_bits(element.tag_select as u64, 4)?;
+		}
+
+		bit_writer.align()?;
+
+		bit_writer.write_bits(self.comment.len() as u64, 8)?;
+		bit_writer.write_all(&self.comment)
+	}
+
+	/// The number of channels including the LFE channels
+	pub fn channels(&self) -> u8 {
+		let channel_elements = self
+			.front_channel_elements
+			.iter()
+			.chain(&self.side_channel_elements)
+			.chain(&self.back_channel_elements)
+			.map(|element| if element.is_cpe { 2 } else { 1 })
+			.sum::<u8>();
+
+		channel_elements + self.lfe_element_tags.len() as u8
+	}
+}
+
+/// ISO/IEC 14496-3:2019(E) - 1.6.2.1 (GetAudioObjectType)
+fn read_audio_object_type<T: io::Read + io::Seek>(bitreader: &mut BitReader<T>) -> io::Result<AudioObjectType> {
+	let mut audio_object_type = bitreader.read_bits(5)? as u16;
+	if audio_object_type == 31 {
+		audio_object_type = 32 + bitreader.read_bits(6)? as u16;
+	}
+
+	Ok(audio_object_type.into())
+}
+
+fn write_audio_object_type(bit_writer: &mut BitWriter, audio_object_type: AudioObjectType) -> io::Result<()> {
+	let audio_object_type = u16::from(audio_object_type);
+	if audio_object_type >= 31 {
+		bit_writer.write_bits(31, 5)?;
+		bit_writer.write_bits((audio_object_type - 32) as u64, 6)
+	} else {
+		bit_writer.write_bits(audio_object_type as u64, 5)
+	}
+}
+
+fn read_sampling_frequency<T: io::Read + io::Seek>(bitreader: &mut BitReader<T>) -> io::Result<u32> {
+	let sampling_frequency_index = SampleFrequencyIndex::from_u8(bitreader.read_bits(4)? as u8)
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid sampling frequency index"))?;
+
+	Ok(match sampling_frequency_index {
+		SampleFrequencyIndex::FreqEscape => bitreader.read_bits(24)? as u32,
+		_ => sampling_frequency_index.to_freq(),
+	})
+}
+
+fn write_sampling_frequency(bit_writer: &mut BitWriter, sampling_frequency: u32) -> io::Result<()> {
+	match SampleFrequencyIndex::from_freq(sampling_frequency) {
+		Some(index) => bit_writer.write_bits(index as u64, 4),
+		None => {
+			bit_writer.write_bits(SampleFrequencyIndex::FreqEscape as u64, 4)?;
+			bit_writer.write_bits(sampling_frequency as u64, 24)
+		}
+	}
 }
@@ -1,6 +1,11 @@
+mod adts;
 mod config;
 
-pub use config::{AudioObjectType, AudioSpecificConfig, SampleFrequencyIndex};
+pub use adts::{AdtsFrames, AdtsHeader};
+pub use config::{
+	AudioObjectType, AudioSpecificConfig, CcElement, ChannelElement, GaExtension, GaSpecificConfig, MatrixMixdown,
+	ProgramConfigElement, ResilienceFlags, SampleFrequencyIndex, SbrConfig, SbrSignalling,
+};
 
 #[cfg(test)]
 mod tests;
@@ -1,7 +1,10 @@
 use bytes::Bytes;
 
 use crate::config::SampleFrequencyIndex;
-use crate::{AudioObjectType, AudioSpecificConfig};
+use crate::{
+	AdtsFrames, AdtsHeader, AudioObjectType, AudioSpecificConfig, ChannelElement, ProgramConfigElement, SbrConfig,
+	SbrSignalling,
+};
 
 #[test]
 fn test_aac_config_parse() {
@@ -13,6 +16,139 @@ fn test_aac_config_parse() {
 	assert_eq!(config.audio_object_type, AudioObjectType::AacLowComplexity);
 	assert_eq!(config.sampling_frequency, 44100);
 	assert_eq!(config.channel_configuration, 2);
+
+	// The config signals that SBR is not present with a backward compatible
+	// sync extension.
+	assert_eq!(
+		config.sbr,
+		Some(SbrConfig {
+			signalling: SbrSignalling::BackwardCompatible,
+			sbr_present: false,
+			ps_present: false,
+			sampling_frequency: 44100,
+		})
+	);
+	assert_eq!(config.codec_object_type(), AudioObjectType::AacLowComplexity);
+	assert_eq!(config.output_sampling_frequency(), 44100);
+	assert_eq!(config.channels(), Some(2));
+}
+
+#[test]
+fn test_aac_config_parse_hierarchical_sbr() {
+	// HE-AAC, 24kHz core, 48kHz output, stereo
+	let config = AudioSpecificConfig::parse(Bytes::from_static(&[0x2B, 0x11, 0x88, 0x00])).unwrap();
+	assert_eq!(config.audio_object_type, AudioObjectType::AacLowComplexity);
+	assert_eq!(config.sampling_frequency, 24000);
+	assert_eq!(config.channel_configuration, 2);
+	assert_eq!(
+		config.sbr,
+		Some(SbrConfig {
+			signalling: SbrSignalling::Hierarchical,
+			sbr_present: true,
+			ps_present: false,
+			sampling_frequency: 48000,
+		})
+	);
+	assert_eq!(config.codec_object_type(), AudioObjectType::SpectralBandReplication);
+	assert_eq!(config.output_sampling_frequency(), 48000);
+	assert!(!config.implicit_sbr_possible());
+	assert_eq!(config.build().unwrap(), config.data);
+
+	// HE-AACv2, 24kHz core, 48kHz output, mono core
+	let config = AudioSpecificConfig::parse(Bytes::from_static(&[0xEB, 0x09, 0x88, 0x00])).unwrap();
+	assert_eq!(config.audio_object_type, AudioObjectType::AacLowComplexity);
+	assert_eq!(config.channel_configuration, 1);
+	assert!(config.sbr.is_some_and(|sbr| sbr.ps_present));
+	assert_eq!(config.codec_object_type(), AudioObjectType::ParametricStereo);
+	assert_eq!(config.build().unwrap(), config.data);
+}
+
+#[test]
+fn test_aac_config_backward_compatible_ps() {
+	let mut config = AudioSpecificConfig::new(AudioObjectType::AacLowComplexity, 24000, 1).unwrap();
+	assert!(config.implicit_sbr_possible());
+
+	config.sbr = Some(SbrConfig {
+		signalling: SbrSignalling::BackwardCompatible,
+		sbr_present: true,
+		ps_present: true,
+		sampling_frequency: 48000,
+	});
+	config.data = config.build().unwrap();
+
+	// The core config is followed by the SBR and PS sync extensions.
+	assert_eq!(config.data, Bytes::from_static(&[0x13, 0x08, 0x56, 0xE5, 0x9D, 0x48, 0x80]));
+	assert_eq!(AudioSpecificConfig::parse(config.data.clone()).unwrap(), config);
+}
+
+#[test]
+fn test_aac_config_program_config_element() {
+	let element = |is_cpe, tag_select| ChannelElement { is_cpe, tag_select };
+
+	// 10.2 layout
+	let mut config = AudioSpecificConfig::new(AudioObjectType::AacLowComplexity, 48000, 2).unwrap();
+	config.channel_configuration = 0;
+	config.ga_specific_config.as_mut().unwrap().program_config_element = Some(ProgramConfigElement {
+		element_instance_tag: 0,
+		object_type: 1,
+		sampling_frequency_index: SampleFrequencyIndex::Freq48000 as u8,
+		front_channel_elements: vec![element(false, 0), element(true, 0), element(true, 1)],
+		side_channel_elements: vec![element(true, 2)],
+		back_channel_elements: vec![element(true, 3), element(false, 1)],
+		lfe_element_tags: vec![0, 1],
+		assoc_data_element_tags: Vec::new(),
+		valid_cc_elements: Vec::new(),
+		mono_mixdown_element_number: None,
+		stereo_mixdown_element_number: None,
+		matrix_mixdown: None,
+		comment: Bytes::from_static(b"10.2"),
+	});
+	config.data = config.build().unwrap();
+
+	let parsed = AudioSpecificConfig::parse(config.data.clone()).unwrap();
+	assert_eq!(parsed, config);
+	assert_eq!(parsed.channels(), Some(12));
+	assert_eq!(&parsed.data[parsed.data.len() - 4..], b"10.2");
+}
+
+#[test]
+fn test_adts_header() {
+	let config = AudioSpecificConfig::new(AudioObjectType::AacLowComplexity, 44100, 2).unwrap();
+	assert_eq!(config.data, Bytes::from_static(&[0x12, 0x10]));
+
+	let header = AdtsHeader::new(&config, 100).unwrap();
+	let mut data = Vec::new();
+	header.mux(&mut data).unwrap();
+	assert_eq!(data, vec![0xFF, 0xF1, 0x50, 0x80, 0x0D, 0x7F, 0xFC]);
+
+	let parsed = AdtsHeader::parse(&data).unwrap();
+	assert_eq!(parsed, header);
+	assert_eq!(parsed.size(), 7);
+	assert_eq!(parsed.sampling_frequency(), 44100);
+	assert_eq!(parsed.audio_specific_config().unwrap(), config);
+
+	// Two frames followed by a truncated one
+	data.extend_from_slice(&[0xAA; 100]);
+	data.extend_from_within(..);
+	data.extend_from_slice(&[0xFF, 0xF1, 0x50, 0x80, 0x0D, 0x7F, 0xFC, 0xAA]);
+
+	let frames = AdtsFrames::new(Bytes::from(data)).collect::<Vec<_>>();
+	assert_eq!(frames.len(), 3);
+	for frame in &frames[..2] {
+		let (frame_header, payload) = frame.as_ref().unwrap();
+		assert_eq!(frame_header, &header);
+		assert_eq!(payload, &Bytes::from_static(&[0xAA; 100]));
+	}
+	assert!(frames[2].is_err());
+
+	// HE-AAC is signalled with the core config in ADTS
+	let config = AudioSpecificConfig::parse(Bytes::from_static(&[0x2B, 0x11, 0x88, 0x00])).unwrap();
+	let header = AdtsHeader::new(&config, 100).unwrap();
+	assert_eq!(header.audio_object_type, AudioObjectType::AacLowComplexity);
+	assert_eq!(header.sampling_frequency(), 24000);
+
+	assert!(AdtsHeader::new(&config, 8192).is_err());
+	assert!(AdtsHeader::parse(&[0xFF, 0xF1, 0x50]).is_err());
 }
 
 #[test]
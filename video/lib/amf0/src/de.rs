@@ -0,0 +1,172 @@
+use bytes::Bytes;
+use serde::de::value::{MapDeserializer, SeqDeserializer, StringDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use super::{Amf0ReadError, Amf0Reader, Amf0Value};
+
+/// Deserializes a single AMF0 value from bytes.
+pub fn from_bytes<T: DeserializeOwned>(bytes: Bytes) -> Result<T, Amf0ReadError> {
+	from_value(Amf0Reader::new(bytes).read_any()?)
+}
+
+/// Deserializes an [`Amf0Value`].
+/// Objects, ECMA arrays and typed objects can all be read as structs or
+/// maps. Values switched to AMF3 are converted to AMF0 first.
+pub fn from_value<T: DeserializeOwned>(value: Amf0Value) -> Result<T, Amf0ReadError> {
+	T::deserialize(value)
+}
+
+impl de::Error for Amf0ReadError {
+	fn custom<T: std::fmt::Display>(msg: T) -> Self {
+		Self::Custom(msg.to_string())
+	}
+}
+
+impl<'de> IntoDeserializer<'de, Amf0ReadError> for Amf0Value {
+	type Deserializer = Self;
+
+	fn into_deserializer(self) -> Self {
+		self
+	}
+}
+
+/// Numbers are always doubles in AMF0, so integer types accept any number
+/// without a fractional part that fits the type.
+macro_rules! deserialize_integer {
+	($method:ident, $visit:ident, $ty:ty) => {
+		fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Amf0ReadError> {
+			match self {
+				Amf0Value::Number(n) if n.fract() == 0.0 && n >= <$ty>::MIN as f64 && n <= <$ty>::MAX as f64 => {
+					visitor.$visit(n as $ty)
+				}
+				value => value.deserialize_any(visitor),
+			}
+		}
+	};
+}
+
+impl<'de> de::Deserializer<'de> for Amf0Value {
+	type Error = Amf0ReadError;
+
+	forward_to_deserialize_any! {
+		bool f32 f64 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+	}
+
+	deserialize_integer!(deserialize_i8, visit_i8, i8);
+
+	deserialize_integer!(deserialize_i16, visit_i16, i16);
+
+	deserialize_integer!(deserialize_i32, visit_i32, i32);
+
+	deserialize_integer!(deserialize_i64, visit_i64, i64);
+
+	deserialize_integer!(deserialize_u8, visit_u8, u8);
+
+	deserialize_integer!(deserialize_u16, visit_u16, u16);
+
+	deserialize_integer!(deserialize_u32, visit_u32, u32);
+
+	deserialize_integer!(deserialize_u64, visit_u64, u64);
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Amf0ReadError> {
+		match self {
+			Amf0Value::Number(n) => visitor.visit_f64(n),
+			Amf0Value::Boolean(b) => visitor.visit_bool(b),
+			Amf0Value::String(s) | Amf0Value::LongString(s) | Amf0Value::XmlDocument(s) => visitor.visit_string(s),
+			Amf0Value::Object(properties) | Amf0Value::EcmaArray(properties) | Amf0Value::TypedObject { properties, .. } => {
+				let mut map = MapDeserializer::new(properties.into_iter());
+				let value = visitor.visit_map(&mut map)?;
+				map.end()?;
+				Ok(value)
+			}
+			Amf0Value::StrictArray(values) => {
+				let mut seq = SeqDeserializer::new(values.into_iter());
+				let value = visitor.visit_seq(&mut seq)?;
+				seq.end()?;
+				Ok(value)
+			}
+			Amf0Value::Date { time, .. } => visitor.visit_f64(time),
+			Amf0Value::Null | Amf0Value::Undefined | Amf0Value::Unsupported | Amf0Value::ObjectEnd => visitor.visit_unit(),
+			Amf0Value::Reference(_) => Err(Amf0ReadError::Custom("references are not supported".to_string())),
+			Amf0Value::AVMPlusObject(value) => match Amf0Value::AVMPlusObject(value).normalize_amf3() {
+				Amf0Value::AVMPlusObject(value) => {
+					Err(Amf0ReadError::Custom(format!("unsupported amf3 value: {:?}", value)))
+				}
+				value => value.deserialize_any(visitor),
+			},
+		}
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Amf0ReadError> {
+		match self {
+			Amf0Value::Null | Amf0Value::Undefined => visitor.visit_none(),
+			value => visitor.visit_some(value),
+		}
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Amf0ReadError> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Amf0ReadError> {
+		match self {
+			// Unit variants are sent as their name
+			Amf0Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+			// Other variants are an object with a single property named after the variant
+			Amf0Value::Object(properties) if properties.len() == 1 => {
+				let (variant, value) = properties.into_iter().next().expect("object has one property");
+				visitor.visit_enum(EnumDeserializer { variant, value })
+			}
+			_ => Err(Amf0ReadError::WrongType),
+		}
+	}
+}
+
+struct EnumDeserializer {
+	variant: String,
+	value: Amf0Value,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+	type Error = Amf0ReadError;
+	type Variant = Amf0Value;
+
+	fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Amf0Value), Amf0ReadError> {
+		let deserializer: StringDeserializer<Amf0ReadError> = self.variant.into_deserializer();
+		Ok((seed.deserialize(deserializer)?, self.value))
+	}
+}
+
+impl<'de> de::VariantAccess<'de> for Amf0Value {
+	type Error = Amf0ReadError;
+
+	fn unit_variant(self) -> Result<(), Amf0ReadError> {
+		de::Deserialize::deserialize(self)
+	}
+
+	fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Amf0ReadError> {
+		seed.deserialize(self)
+	}
+
+	fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Amf0ReadError> {
+		de::Deserializer::deserialize_seq(self, visitor)
+	}
+
+	fn struct_variant<V: Visitor<'de>>(
+		self,
+		_fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Amf0ReadError> {
+		de::Deserializer::deserialize_map(self, visitor)
+	}
+}
@@ -1,7 +1,8 @@
-use std::collections::HashMap;
-
+use amf3::Amf3Value;
 use num_derive::FromPrimitive;
 
+use super::Amf0Object;
+
 /// AMF0 marker types.
 /// Defined in amf0_spec_121207.pdf section 2.1
 #[derive(Debug, PartialEq, Eq, Clone, Copy, FromPrimitive)]
@@ -36,11 +37,106 @@ pub enum Amf0Value {
 	/// String Type defined section 2.4
 	String(String),
 	/// Object Type defined section 2.5
-	Object(HashMap<String, Amf0Value>),
+	Object(Amf0Object),
 	/// Null Type defined section 2.7
 	Null,
 	/// Undefined Type defined section 2.8
+	Undefined,
+	/// Reference Type defined section 2.9
+	/// The index of a complex value (object, ECMA array, strict array or
+	/// typed object) that was previously sent.
+	Reference(u16),
+	/// ECMA Array Type defined section 2.10
+	EcmaArray(Amf0Object),
+	/// Object End Type defined section 2.11
 	ObjectEnd,
+	/// Strict Array Type defined section 2.12
+	StrictArray(Vec<Amf0Value>),
+	/// Date Type defined section 2.13
+	/// `time` is milliseconds since the unix epoch, `timezone` is reserved
+	/// and should be 0.
+	Date { time: f64, timezone: i16 },
 	/// LongString Type defined section 2.14
 	LongString(String),
+	/// Unsupported Type defined section 2.15
+	Unsupported,
+	/// XML Document Type defined section 2.17
+	XmlDocument(String),
+	/// Typed Object Type defined section 2.18
+	TypedObject { class_name: String, properties: Amf0Object },
+	/// AVMPlus Type defined section 3.1, the value is AMF3 encoded
+	AVMPlusObject(Box<Amf3Value>),
+}
+
+impl Amf0Value {
+	/// Converts values that were switched to AMF3 (`AVMPlusObject`) back to
+	/// their AMF0 equivalent, recursing into complex values. AMF3 values
+	/// without an AMF0 equivalent are left as they are.
+	pub fn normalize_amf3(self) -> Self {
+		let normalize = |properties: Amf0Object| {
+			properties
+				.into_iter()
+				.map(|(key, value)| (key, value.normalize_amf3()))
+				.collect()
+		};
+
+		match self {
+			Self::AVMPlusObject(value) => Self::from_amf3(*value),
+			Self::Object(properties) => Self::Object(normalize(properties)),
+			Self::EcmaArray(properties) => Self::EcmaArray(normalize(properties)),
+			Self::TypedObject { class_name, properties } => Self::TypedObject {
+				class_name,
+				properties: normalize(properties),
+			},
+			Self::StrictArray(values) => Self::StrictArray(values.into_iter().map(Self::normalize_amf3).collect()),
+			value => value,
+		}
+	}
+
+	fn from_amf3(value: Amf3Value) -> Self {
+		let convert = |properties: Vec<(String, Amf3Value)>| {
+			properties
+				.into_iter()
+				.map(|(key, value)| (key, Self::from_amf3(value)))
+				.collect::<Amf0Object>()
+		};
+
+		match value {
+			Amf3Value::Undefined => Self::Undefined,
+			Amf3Value::Null => Self::Null,
+			Amf3Value::Boolean(value) => Self::Boolean(value),
+			Amf3Value::Integer(value) => Self::Number(value as f64),
+			Amf3Value::Double(value) => Self::Number(value),
+			Amf3Value::String(value) if value.len() > u16::MAX as usize => Self::LongString(value),
+			Amf3Value::String(value) => Self::String(value),
+			Amf3Value::XmlDocument(value) => Self::XmlDocument(value),
+			Amf3Value::Date(time) => Self::Date { time, timezone: 0 },
+			Amf3Value::Object(object) if !object.traits.externalizable => {
+				let properties = convert(
+					object
+						.traits
+						.sealed_members
+						.into_iter()
+						.zip(object.sealed_values)
+						.chain(object.dynamic_members)
+						.collect(),
+				);
+
+				if object.traits.class_name.is_empty() {
+					Self::Object(properties)
+				} else {
+					Self::TypedObject {
+						class_name: object.traits.class_name,
+						properties,
+					}
+				}
+			}
+			// An array without dense values is how ActionScript sends maps
+			Amf3Value::Array { associative, dense } if dense.is_empty() => Self::EcmaArray(convert(associative)),
+			Amf3Value::Array { associative, dense } if associative.is_empty() => {
+				Self::StrictArray(dense.into_iter().map(Self::from_amf3).collect())
+			}
+			value => Self::AVMPlusObject(Box::new(value)),
+		}
+	}
 }
@@ -1,5 +1,7 @@
 use std::{fmt, io, str};
 
+use amf3::{Amf3ReadError, Amf3WriteError};
+
 use super::define::Amf0Marker;
 use super::Amf0Value;
 
@@ -9,7 +11,9 @@ pub enum Amf0ReadError {
 	UnsupportedType(Amf0Marker),
 	StringParseError(str::Utf8Error),
 	IO(io::Error),
+	Amf3Read(Amf3ReadError),
 	WrongType,
+	Custom(String),
 }
 
 macro_rules! from_error {
@@ -24,15 +28,19 @@ macro_rules! from_error {
 
 from_error!(Amf0ReadError, Self::StringParseError, str::Utf8Error);
 from_error!(Amf0ReadError, Self::IO, io::Error);
+from_error!(Amf0ReadError, Self::Amf3Read, Amf3ReadError);
 
 #[derive(Debug)]
 pub enum Amf0WriteError {
 	NormalStringTooLong,
 	IO(io::Error),
+	Amf3Write(Amf3WriteError),
 	UnsupportedType(Amf0Value),
+	Custom(String),
 }
 
 from_error!(Amf0WriteError, Self::IO, io::Error);
+from_error!(Amf0WriteError, Self::Amf3Write, Amf3WriteError);
 
 impl fmt::Display for Amf0ReadError {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -46,10 +54,14 @@ impl fmt::Display for Amf0ReadError {
 			Self::WrongType => write!(f, "wrong type"),
 			Self::StringParseError(err) => write!(f, "string parse error: {}", err),
 			Self::IO(err) => write!(f, "io error: {}", err),
+			Self::Amf3Read(err) => write!(f, "amf3 read error: {}", err),
+			Self::Custom(msg) => write!(f, "{}", msg),
 		}
 	}
 }
 
+impl std::error::Error for Amf0ReadError {}
+
 impl fmt::Display for Amf0WriteError {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self {
@@ -60,6 +72,10 @@ impl fmt::Display for Amf0WriteError {
 				write!(f, "unsupported type: {:?}", value_type)
 			}
 			Self::IO(error) => write!(f, "io error: {}", error),
+			Self::Amf3Write(error) => write!(f, "amf3 write error: {}", error),
+			Self::Custom(msg) => write!(f, "{}", msg),
 		}
 	}
 }
+
+impl std::error::Error for Amf0WriteError {}
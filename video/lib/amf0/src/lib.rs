@@ -1,11 +1,17 @@
+mod de;
 mod define;
 mod errors;
+mod object;
 mod reader;
+mod ser;
 mod writer;
 
+pub use crate::de::{from_bytes, from_value};
 pub use crate::define::{Amf0Marker, Amf0Value};
 pub use crate::errors::{Amf0ReadError, Amf0WriteError};
+pub use crate::object::Amf0Object;
 pub use crate::reader::Amf0Reader;
+pub use crate::ser::{to_bytes, to_value};
 pub use crate::writer::Amf0Writer;
 
 #[cfg(test)]
@@ -0,0 +1,108 @@
+use super::Amf0Value;
+
+/// The properties of an object, ECMA array or typed object.
+/// AMF0 sends properties as a list of key value pairs, we keep them in the
+/// order they were received so a value can be written back byte for byte.
+/// Objects only have a handful of properties so lookups are a linear scan.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Amf0Object {
+	properties: Vec<(String, Amf0Value)>,
+}
+
+impl Amf0Object {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn len(&self) -> usize {
+		self.properties.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.properties.is_empty()
+	}
+
+	pub fn get(&self, key: &str) -> Option<&Amf0Value> {
+		self.properties.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+	}
+
+	pub fn get_mut(&mut self, key: &str) -> Option<&mut Amf0Value> {
+		self.properties.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
+	}
+
+	pub fn contains_key(&self, key: &str) -> bool {
+		self.get(key).is_some()
+	}
+
+	/// Inserts a property, if the key already exists the value is replaced in
+	/// place and the old value is returned.
+	pub fn insert(&mut self, key: impl Into<String>, value: Amf0Value) -> Option<Amf0Value> {
+		let key = key.into();
+
+		match self.get_mut(&key) {
+			Some(old) => Some(std::mem::replace(old, value)),
+			None => {
+				self.properties.push((key, value));
+				None
+			}
+		}
+	}
+
+	/// Removes a property, keeping the order of the remaining properties.
+	pub fn remove(&mut self, key: &str) -> Option<Amf0Value> {
+		let index = self.properties.iter().position(|(k, _)| k == key)?;
+		Some(self.properties.remove(index).1)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&String, &Amf0Value)> {
+		self.properties.iter().map(|(k, v)| (k, v))
+	}
+
+	pub fn keys(&self) -> impl Iterator<Item = &String> {
+		self.properties.iter().map(|(k, _)| k)
+	}
+
+	pub fn values(&self) -> impl Iterator<Item = &Amf0Value> {
+		self.properties.iter().map(|(_, v)| v)
+	}
+}
+
+impl<K: Into<String>> FromIterator<(K, Amf0Value)> for Amf0Object {
+	fn from_iter<I: IntoIterator<Item = (K, Amf0Value)>>(iter: I) -> Self {
+		let mut object = Self::new();
+		object.extend(iter);
+		object
+	}
+}
+
+impl<K: Into<String>> Extend<(K, Amf0Value)> for Amf0Object {
+	fn extend<I: IntoIterator<Item = (K, Amf0Value)>>(&mut self, iter: I) {
+		for (key, value) in iter {
+			self.insert(key, value);
+		}
+	}
+}
+
+impl<K: Into<String>, const N: usize> From<[(K, Amf0Value); N]> for Amf0Object {
+	fn from(properties: [(K, Amf0Value); N]) -> Self {
+		properties.into_iter().collect()
+	}
+}
+
+impl IntoIterator for Amf0Object {
+	type IntoIter = std::vec::IntoIter<(String, Amf0Value)>;
+	type Item = (String, Amf0Value);
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.properties.into_iter()
+	}
+}
+
+impl<'a> IntoIterator for &'a Amf0Object {
+	type IntoIter = std::iter::Map<std::slice::Iter<'a, (String, Amf0Value)>, fn(&'a (String, Amf0Value)) -> Self::Item>;
+	type Item = (&'a String, &'a Amf0Value);
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.properties.iter().map(|(k, v)| (k, v))
+	}
+}
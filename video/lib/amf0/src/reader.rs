@@ -1,11 +1,11 @@
-use std::collections::HashMap;
 use std::io::{Cursor, Seek, SeekFrom};
 
+use amf3::Amf3Reader;
 use byteorder::{BigEndian, ReadBytesExt};
 use bytes::Bytes;
 use num_traits::FromPrimitive;
 
-use super::{Amf0Marker, Amf0ReadError, Amf0Value};
+use super::{Amf0Marker, Amf0Object, Amf0ReadError, Amf0Value};
 
 pub struct Amf0Reader {
 	cursor: Cursor<Bytes>,
@@ -61,8 +61,16 @@ impl Amf0Reader {
 			Amf0Marker::String => self.read_string(),
 			Amf0Marker::Object => self.read_object(),
 			Amf0Marker::Null => self.read_null(),
+			Amf0Marker::Undefined => self.read_undefined(),
+			Amf0Marker::Reference => self.read_reference(),
 			Amf0Marker::EcmaArray => self.read_ecma_array(),
+			Amf0Marker::StrictArray => self.read_strict_array(),
+			Amf0Marker::Date => self.read_date(),
 			Amf0Marker::LongString => self.read_long_string(),
+			Amf0Marker::Unsupported => self.read_unsupported(),
+			Amf0Marker::XmlDocument => self.read_xml_document(),
+			Amf0Marker::TypedObject => self.read_typed_object(),
+			Amf0Marker::AVMPlusObject => self.read_avmplus_object(),
 			_ => Err(Amf0ReadError::UnsupportedType(marker)),
 		}
 	}
@@ -125,8 +133,8 @@ impl Amf0Reader {
 		}
 	}
 
-	pub fn read_object(&mut self) -> Result<Amf0Value, Amf0ReadError> {
-		let mut properties = HashMap::new();
+	fn read_properties(&mut self) -> Result<Amf0Object, Amf0ReadError> {
+		let mut properties = Amf0Object::new();
 
 		loop {
 			let is_eof = self.is_read_object_eof()?;
@@ -141,13 +149,26 @@ impl Amf0Reader {
 			properties.insert(key, val);
 		}
 
-		Ok(Amf0Value::Object(properties))
+		Ok(properties)
+	}
+
+	pub fn read_object(&mut self) -> Result<Amf0Value, Amf0ReadError> {
+		Ok(Amf0Value::Object(self.read_properties()?))
+	}
+
+	pub fn read_undefined(&mut self) -> Result<Amf0Value, Amf0ReadError> {
+		Ok(Amf0Value::Undefined)
+	}
+
+	pub fn read_reference(&mut self) -> Result<Amf0Value, Amf0ReadError> {
+		let index = self.cursor.read_u16::<BigEndian>()?;
+		Ok(Amf0Value::Reference(index))
 	}
 
 	pub fn read_ecma_array(&mut self) -> Result<Amf0Value, Amf0ReadError> {
 		let len = self.cursor.read_u32::<BigEndian>()?;
 
-		let mut properties = HashMap::new();
+		let mut properties = Amf0Object::new();
 
 		for _ in 0..len {
 			let key = self.read_raw_string()?;
@@ -159,7 +180,25 @@ impl Amf0Reader {
 		// If it is there just read it, if not then we are done.
 		self.is_read_object_eof().ok(); // ignore the result
 
-		Ok(Amf0Value::Object(properties))
+		Ok(Amf0Value::EcmaArray(properties))
+	}
+
+	pub fn read_strict_array(&mut self) -> Result<Amf0Value, Amf0ReadError> {
+		let len = self.cursor.read_u32::<BigEndian>()?;
+
+		let mut values = Vec::new();
+		for _ in 0..len {
+			values.push(self.read_any()?);
+		}
+
+		Ok(Amf0Value::StrictArray(values))
+	}
+
+	pub fn read_date(&mut self) -> Result<Amf0Value, Amf0ReadError> {
+		let time = self.cursor.read_f64::<BigEndian>()?;
+		let timezone = self.cursor.read_i16::<BigEndian>()?;
+
+		Ok(Amf0Value::Date { time, timezone })
 	}
 
 	pub fn read_long_string(&mut self) -> Result<Amf0Value, Amf0ReadError> {
@@ -170,4 +209,37 @@ impl Amf0Reader {
 
 		Ok(Amf0Value::LongString(val.to_string()))
 	}
+
+	pub fn read_unsupported(&mut self) -> Result<Amf0Value, Amf0ReadError> {
+		Ok(Amf0Value::Unsupported)
+	}
+
+	pub fn read_xml_document(&mut self) -> Result<Amf0Value, Amf0ReadError> {
+		let l = self.cursor.read_u32::<BigEndian>()?;
+
+		let buff = self.read_bytes(l as usize)?;
+		let val = std::str::from_utf8(&buff)?;
+
+		Ok(Amf0Value::XmlDocument(val.to_string()))
+	}
+
+	pub fn read_typed_object(&mut self) -> Result<Amf0Value, Amf0ReadError> {
+		let class_name = self.read_raw_string()?;
+		let properties = self.read_properties()?;
+
+		Ok(Amf0Value::TypedObject { class_name, properties })
+	}
+
+	pub fn read_avmplus_object(&mut self) -> Result<Amf0Value, Amf0ReadError> {
+		// The AMF3 value starts right after the marker and has its own
+		// reference tables, so we read it with a fresh AMF3 reader and then
+		// skip over the bytes it consumed.
+		let pos = self.cursor.position() as usize;
+		let mut amf3_reader = Amf3Reader::new(self.cursor.get_ref().slice(pos..));
+		let value = amf3_reader.read_any()?;
+
+		self.cursor.seek(SeekFrom::Current(amf3_reader.position() as i64))?;
+
+		Ok(Amf0Value::AVMPlusObject(Box::new(value)))
+	}
 }
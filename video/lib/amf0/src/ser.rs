@@ -0,0 +1,366 @@
+use bytes::Bytes;
+use bytesio::bytes_writer::BytesWriter;
+use serde::ser::{self, Serialize};
+
+use super::{Amf0Object, Amf0Value, Amf0WriteError, Amf0Writer};
+
+/// Serializes a value into AMF0 bytes.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Bytes, Amf0WriteError> {
+	let mut writer = BytesWriter::default();
+	Amf0Writer::write_any(&mut writer, &to_value(value)?)?;
+	Ok(writer.dispose())
+}
+
+/// Serializes a value into an [`Amf0Value`].
+/// Structs become objects, maps become ECMA arrays and sequences become
+/// strict arrays. Enum variants with data are written as an object with a
+/// single property named after the variant.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Amf0Value, Amf0WriteError> {
+	value.serialize(Serializer)
+}
+
+impl ser::Error for Amf0WriteError {
+	fn custom<T: std::fmt::Display>(msg: T) -> Self {
+		Self::Custom(msg.to_string())
+	}
+}
+
+fn string_value(value: String) -> Amf0Value {
+	if value.len() > u16::MAX as usize {
+		Amf0Value::LongString(value)
+	} else {
+		Amf0Value::String(value)
+	}
+}
+
+fn variant_object(variant: &str, value: Amf0Value) -> Amf0Value {
+	Amf0Value::Object(Amf0Object::from([(variant, value)]))
+}
+
+struct Serializer;
+
+impl ser::Serializer for Serializer {
+	type Error = Amf0WriteError;
+	type Ok = Amf0Value;
+	type SerializeMap = SerializeMap;
+	type SerializeSeq = SerializeSeq;
+	type SerializeStruct = SerializeStruct;
+	type SerializeStructVariant = SerializeStruct;
+	type SerializeTuple = SerializeSeq;
+	type SerializeTupleStruct = SerializeSeq;
+	type SerializeTupleVariant = SerializeSeq;
+
+	fn serialize_bool(self, v: bool) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Boolean(v))
+	}
+
+	fn serialize_i8(self, v: i8) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Number(v as f64))
+	}
+
+	fn serialize_i16(self, v: i16) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Number(v as f64))
+	}
+
+	fn serialize_i32(self, v: i32) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Number(v as f64))
+	}
+
+	fn serialize_i64(self, v: i64) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Number(v as f64))
+	}
+
+	fn serialize_u8(self, v: u8) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Number(v as f64))
+	}
+
+	fn serialize_u16(self, v: u16) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Number(v as f64))
+	}
+
+	fn serialize_u32(self, v: u32) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Number(v as f64))
+	}
+
+	fn serialize_u64(self, v: u64) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Number(v as f64))
+	}
+
+	fn serialize_f32(self, v: f32) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Number(v as f64))
+	}
+
+	fn serialize_f64(self, v: f64) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Number(v))
+	}
+
+	fn serialize_char(self, v: char) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::String(v.to_string()))
+	}
+
+	fn serialize_str(self, v: &str) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(string_value(v.to_string()))
+	}
+
+	fn serialize_bytes(self, v: &[u8]) -> Result<Amf0Value, Amf0WriteError> {
+		// AMF0 has no byte array type
+		Ok(Amf0Value::StrictArray(
+			v.iter().map(|b| Amf0Value::Number(*b as f64)).collect(),
+		))
+	}
+
+	fn serialize_none(self) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Null)
+	}
+
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Amf0Value, Amf0WriteError> {
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Null)
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::Null)
+	}
+
+	fn serialize_unit_variant(
+		self,
+		_name: &'static str,
+		_variant_index: u32,
+		variant: &'static str,
+	) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::String(variant.to_string()))
+	}
+
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		value: &T,
+	) -> Result<Amf0Value, Amf0WriteError> {
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		_variant_index: u32,
+		variant: &'static str,
+		value: &T,
+	) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(variant_object(variant, value.serialize(self)?))
+	}
+
+	fn serialize_seq(self, len: Option<usize>) -> Result<SerializeSeq, Amf0WriteError> {
+		Ok(SerializeSeq {
+			variant: None,
+			values: Vec::with_capacity(len.unwrap_or_default()),
+		})
+	}
+
+	fn serialize_tuple(self, len: usize) -> Result<SerializeSeq, Amf0WriteError> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeSeq, Amf0WriteError> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_variant(
+		self,
+		_name: &'static str,
+		_variant_index: u32,
+		variant: &'static str,
+		len: usize,
+	) -> Result<SerializeSeq, Amf0WriteError> {
+		Ok(SerializeSeq {
+			variant: Some(variant),
+			values: Vec::with_capacity(len),
+		})
+	}
+
+	fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap, Amf0WriteError> {
+		Ok(SerializeMap {
+			properties: Amf0Object::new(),
+			next_key: None,
+		})
+	}
+
+	fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<SerializeStruct, Amf0WriteError> {
+		Ok(SerializeStruct {
+			variant: None,
+			properties: Amf0Object::new(),
+		})
+	}
+
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_variant_index: u32,
+		variant: &'static str,
+		_len: usize,
+	) -> Result<SerializeStruct, Amf0WriteError> {
+		Ok(SerializeStruct {
+			variant: Some(variant),
+			properties: Amf0Object::new(),
+		})
+	}
+}
+
+struct SerializeSeq {
+	variant: Option<&'static str>,
+	values: Vec<Amf0Value>,
+}
+
+impl SerializeSeq {
+	fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Amf0WriteError> {
+		self.values.push(to_value(value)?);
+		Ok(())
+	}
+
+	fn finish(self) -> Result<Amf0Value, Amf0WriteError> {
+		let value = Amf0Value::StrictArray(self.values);
+
+		Ok(match self.variant {
+			Some(variant) => variant_object(variant, value),
+			None => value,
+		})
+	}
+}
+
+impl ser::SerializeSeq for SerializeSeq {
+	type Error = Amf0WriteError;
+	type Ok = Amf0Value;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Amf0WriteError> {
+		self.push(value)
+	}
+
+	fn end(self) -> Result<Amf0Value, Amf0WriteError> {
+		self.finish()
+	}
+}
+
+impl ser::SerializeTuple for SerializeSeq {
+	type Error = Amf0WriteError;
+	type Ok = Amf0Value;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Amf0WriteError> {
+		self.push(value)
+	}
+
+	fn end(self) -> Result<Amf0Value, Amf0WriteError> {
+		self.finish()
+	}
+}
+
+impl ser::SerializeTupleStruct for SerializeSeq {
+	type Error = Amf0WriteError;
+	type Ok = Amf0Value;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Amf0WriteError> {
+		self.push(value)
+	}
+
+	fn end(self) -> Result<Amf0Value, Amf0WriteError> {
+		self.finish()
+	}
+}
+
+impl ser::SerializeTupleVariant for SerializeSeq {
+	type Error = Amf0WriteError;
+	type Ok = Amf0Value;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Amf0WriteError> {
+		self.push(value)
+	}
+
+	fn end(self) -> Result<Amf0Value, Amf0WriteError> {
+		self.finish()
+	}
+}
+
+struct SerializeMap {
+	properties: Amf0Object,
+	next_key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+	type Error = Amf0WriteError;
+	type Ok = Amf0Value;
+
+	fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Amf0WriteError> {
+		// AMF0 property names are always strings, numbers are converted the
+		// same way ActionScript does.
+		self.next_key = Some(match to_value(key)? {
+			Amf0Value::String(key) | Amf0Value::LongString(key) => key,
+			Amf0Value::Number(key) => key.to_string(),
+			Amf0Value::Boolean(key) => key.to_string(),
+			_ => return Err(Amf0WriteError::Custom("map keys must be strings".to_string())),
+		});
+
+		Ok(())
+	}
+
+	fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Amf0WriteError> {
+		let key = self
+			.next_key
+			.take()
+			.ok_or_else(|| Amf0WriteError::Custom("serialize_value called before serialize_key".to_string()))?;
+
+		self.properties.insert(key, to_value(value)?);
+		Ok(())
+	}
+
+	fn end(self) -> Result<Amf0Value, Amf0WriteError> {
+		Ok(Amf0Value::EcmaArray(self.properties))
+	}
+}
+
+struct SerializeStruct {
+	variant: Option<&'static str>,
+	properties: Amf0Object,
+}
+
+impl SerializeStruct {
+	fn field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Amf0WriteError> {
+		self.properties.insert(key, to_value(value)?);
+		Ok(())
+	}
+
+	fn finish(self) -> Result<Amf0Value, Amf0WriteError> {
+		let value = Amf0Value::Object(self.properties);
+
+		Ok(match self.variant {
+			Some(variant) => variant_object(variant, value),
+			None => value,
+		})
+	}
+}
+
+impl ser::SerializeStruct for SerializeStruct {
+	type Error = Amf0WriteError;
+	type Ok = Amf0Value;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Amf0WriteError> {
+		self.field(key, value)
+	}
+
+	fn end(self) -> Result<Amf0Value, Amf0WriteError> {
+		self.finish()
+	}
+}
+
+impl ser::SerializeStructVariant for SerializeStruct {
+	type Error = Amf0WriteError;
+	type Ok = Amf0Value;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Amf0WriteError> {
+		self.field(key, value)
+	}
+
+	fn end(self) -> Result<Amf0Value, Amf0WriteError> {
+		self.finish()
+	}
+}
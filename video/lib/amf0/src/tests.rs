@@ -1,10 +1,10 @@
-use std::collections::HashMap;
 use std::io::Cursor;
 
+use amf3::{Amf3Object, Amf3ReadError, Amf3Value, Amf3WriteError};
 use byteorder::ReadBytesExt;
 use bytesio::bytes_writer::BytesWriter;
 
-use crate::{Amf0Marker, Amf0ReadError, Amf0Reader, Amf0Value, Amf0WriteError, Amf0Writer};
+use crate::{Amf0Marker, Amf0Object, Amf0ReadError, Amf0Reader, Amf0Value, Amf0WriteError, Amf0Writer};
 
 #[test]
 fn test_reader_bool() {
@@ -56,7 +56,7 @@ fn test_reader_object() {
 
 	assert_eq!(
 		value,
-		Amf0Value::Object(HashMap::from([("test".to_string(), Amf0Value::Null)]))
+		Amf0Value::Object(Amf0Object::from([("test".to_string(), Amf0Value::Null)]))
 	);
 }
 
@@ -72,7 +72,7 @@ fn test_reader_ecma_array() {
 
 	assert_eq!(
 		value,
-		Amf0Value::Object(HashMap::from([("test".to_string(), Amf0Value::Null)]))
+		Amf0Value::EcmaArray(Amf0Object::from([("test".to_string(), Amf0Value::Null)]))
 	);
 }
 
@@ -98,7 +98,25 @@ fn test_reader_multi_value() {
 	assert_eq!(values[2], Amf0Value::String("Hello World".to_string()));
 	assert_eq!(
 		values[3],
-		Amf0Value::Object(HashMap::from([("test".to_string(), Amf0Value::Null)]))
+		Amf0Value::Object(Amf0Object::from([("test".to_string(), Amf0Value::Null)]))
+	);
+}
+
+#[test]
+fn test_reader_avmplus_object() {
+	let mut amf0_avmplus = vec![0x11, 0x06, 0x09]; // AMF3 string with 4 bytes
+	amf0_avmplus.extend_from_slice(b"live");
+	amf0_avmplus.extend_from_slice(&[0x05]); // AMF0 null after the AMF3 value
+
+	let mut amf_reader = Amf0Reader::new(amf0_avmplus.into());
+	let values = amf_reader.read_all().unwrap();
+
+	assert_eq!(
+		values,
+		vec![
+			Amf0Value::AVMPlusObject(Box::new(Amf3Value::String("live".to_string()))),
+			Amf0Value::Null,
+		]
 	);
 }
 
@@ -107,8 +125,8 @@ fn test_read_error_display() {
 	assert_eq!(Amf0ReadError::UnknownMarker(100).to_string(), "unknown marker: 100");
 
 	assert_eq!(
-		Amf0ReadError::UnsupportedType(Amf0Marker::Reference).to_string(),
-		"unsupported type: Reference"
+		Amf0ReadError::UnsupportedType(Amf0Marker::MovieClipMarker).to_string(),
+		"unsupported type: MovieClipMarker"
 	);
 
 	assert_eq!(Amf0ReadError::WrongType.to_string(), "wrong type");
@@ -126,6 +144,16 @@ fn test_read_error_display() {
 		Amf0ReadError::IO(Cursor::new(Vec::<u8>::new()).read_u8().unwrap_err()).to_string(),
 		"io error: failed to fill whole buffer"
 	);
+
+	assert_eq!(
+		Amf0ReadError::Amf3Read(Amf3ReadError::WrongType).to_string(),
+		"amf3 read error: wrong type"
+	);
+
+	assert_eq!(
+		Amf0ReadError::Custom("missing field `width`".to_string()).to_string(),
+		"missing field `width`"
+	);
 }
 
 #[test]
@@ -141,6 +169,16 @@ fn test_write_error_display() {
 	);
 
 	assert_eq!(Amf0WriteError::NormalStringTooLong.to_string(), "normal string too long");
+
+	assert_eq!(
+		Amf0WriteError::Amf3Write(Amf3WriteError::TraitsMismatch).to_string(),
+		"amf3 write error: sealed values do not match traits"
+	);
+
+	assert_eq!(
+		Amf0WriteError::Custom("map keys must be strings".to_string()).to_string(),
+		"map keys must be strings"
+	);
 }
 
 #[test]
@@ -198,7 +236,291 @@ fn test_write_object() {
 
 	let mut writer = BytesWriter::default();
 
-	Amf0Writer::write_object(&mut writer, &HashMap::from([("test".to_string(), Amf0Value::Null)])).unwrap();
+	Amf0Writer::write_object(&mut writer, &Amf0Object::from([("test".to_string(), Amf0Value::Null)])).unwrap();
 
 	assert_eq!(writer.dispose(), amf0_object);
 }
+
+#[test]
+fn test_write_avmplus_object() {
+	let amf0_avmplus = vec![0x11, 0x04, 0x81, 0x00]; // AMF3 integer 128
+
+	let mut writer = BytesWriter::default();
+
+	Amf0Writer::write_any(&mut writer, &Amf0Value::AVMPlusObject(Box::new(Amf3Value::Integer(128)))).unwrap();
+
+	assert_eq!(writer.dispose(), amf0_avmplus);
+}
+
+#[test]
+fn test_normalize_amf3() {
+	let value = Amf0Value::Object(Amf0Object::from([(
+		"nested".to_string(),
+		Amf0Value::AVMPlusObject(Box::new(Amf3Value::Object(Amf3Object::anonymous(vec![
+			("app".to_string(), Amf3Value::String("live".to_string())),
+			("objectEncoding".to_string(), Amf3Value::Integer(3)),
+			(
+				"flags".to_string(),
+				Amf3Value::VectorInt {
+					fixed: false,
+					values: vec![1],
+				},
+			),
+		])))),
+	)]));
+
+	assert_eq!(
+		value.normalize_amf3(),
+		Amf0Value::Object(Amf0Object::from([(
+			"nested".to_string(),
+			Amf0Value::Object(Amf0Object::from([
+				("app".to_string(), Amf0Value::String("live".to_string())),
+				("objectEncoding".to_string(), Amf0Value::Number(3.0)),
+				(
+					"flags".to_string(),
+					Amf0Value::AVMPlusObject(Box::new(Amf3Value::VectorInt {
+						fixed: false,
+						values: vec![1],
+					}))
+				),
+			])),
+		)]))
+	);
+}
+
+#[test]
+fn test_round_trip_all_types() {
+	let mut amf0_all = vec![0x06]; // undefined
+	amf0_all.extend_from_slice(&[0x07, 0x00, 0x01]); // reference to value 1
+	amf0_all.extend_from_slice(&[0x0a, 0x00, 0x00, 0x00, 0x02]); // strict array with 2 values
+	amf0_all.extend_from_slice(&[0x01, 0x00]); // false
+	amf0_all.extend_from_slice(&[0x05]); // null
+	amf0_all.extend_from_slice(&[0x0b]); // date
+	amf0_all.extend_from_slice(&1_700_000_000_000_f64.to_be_bytes());
+	amf0_all.extend_from_slice(&[0x00, 0x00]); // timezone
+	amf0_all.extend_from_slice(&[0x0d]); // unsupported
+	amf0_all.extend_from_slice(&[0x0f, 0x00, 0x00, 0x00, 0x04]); // xml document with 4 bytes
+	amf0_all.extend_from_slice(b"<a/>");
+	amf0_all.extend_from_slice(&[0x10, 0x00, 0x05]); // typed object with a class name of 5 bytes
+	amf0_all.extend_from_slice(b"Point");
+	amf0_all.extend_from_slice(&[0x00, 0x01, b'y', 0x05]); // y: null
+	amf0_all.extend_from_slice(&[0x00, 0x01, b'x', 0x05]); // x: null
+	amf0_all.extend_from_slice(&[0x00, 0x00, 0x09]); // object end
+	amf0_all.extend_from_slice(&[0x0c, 0x00, 0x00, 0x00, 0x02]); // long string with 2 bytes
+	amf0_all.extend_from_slice(b"hi");
+
+	let mut amf_reader = Amf0Reader::new(amf0_all.clone().into());
+	let values = amf_reader.read_all().unwrap();
+
+	assert_eq!(
+		values,
+		vec![
+			Amf0Value::Undefined,
+			Amf0Value::Reference(1),
+			Amf0Value::StrictArray(vec![Amf0Value::Boolean(false), Amf0Value::Null]),
+			Amf0Value::Date {
+				time: 1_700_000_000_000.0,
+				timezone: 0,
+			},
+			Amf0Value::Unsupported,
+			Amf0Value::XmlDocument("<a/>".to_string()),
+			Amf0Value::TypedObject {
+				class_name: "Point".to_string(),
+				properties: Amf0Object::from([("y", Amf0Value::Null), ("x", Amf0Value::Null)]),
+			},
+			Amf0Value::LongString("hi".to_string()),
+		]
+	);
+
+	let mut writer = BytesWriter::default();
+	for value in &values {
+		Amf0Writer::write_any(&mut writer, value).unwrap();
+	}
+
+	assert_eq!(writer.dispose(), amf0_all);
+}
+
+#[test]
+fn test_round_trip_ecma_array_order() {
+	// onMetaData is usually sent as an ECMA array, the key order must be kept
+	let mut amf0_metadata = vec![0x02, 0x00, 0x0a];
+	amf0_metadata.extend_from_slice(b"onMetaData");
+	amf0_metadata.extend_from_slice(&[0x08, 0x00, 0x00, 0x00, 0x03]); // 3 properties
+	for key in [b"width", b"zzzzz", b"aaaaa"] {
+		amf0_metadata.extend_from_slice(&[0x00, 0x05]);
+		amf0_metadata.extend_from_slice(key);
+		amf0_metadata.push(0x00);
+		amf0_metadata.extend_from_slice(&1280_f64.to_be_bytes());
+	}
+	amf0_metadata.extend_from_slice(&[0x00, 0x00, 0x09]);
+
+	let values = Amf0Reader::new(amf0_metadata.clone().into()).read_all().unwrap();
+
+	let Amf0Value::EcmaArray(properties) = &values[1] else {
+		panic!("expected ecma array, got {:?}", values[1]);
+	};
+	assert_eq!(properties.keys().collect::<Vec<_>>(), vec!["width", "zzzzz", "aaaaa"]);
+
+	let mut writer = BytesWriter::default();
+	for value in &values {
+		Amf0Writer::write_any(&mut writer, value).unwrap();
+	}
+
+	assert_eq!(writer.dispose(), amf0_metadata);
+}
+
+#[test]
+fn test_object() {
+	let mut object = Amf0Object::from([("b", Amf0Value::Number(1.0)), ("a", Amf0Value::Null)]);
+
+	assert_eq!(object.len(), 2);
+	assert_eq!(object.get("b"), Some(&Amf0Value::Number(1.0)));
+	assert!(!object.contains_key("c"));
+
+	// Replacing a value keeps its position
+	assert_eq!(object.insert("b", Amf0Value::Boolean(true)), Some(Amf0Value::Number(1.0)));
+	object.insert("c", Amf0Value::Undefined);
+	assert_eq!(object.keys().collect::<Vec<_>>(), vec!["b", "a", "c"]);
+
+	assert_eq!(object.remove("a"), Some(Amf0Value::Null));
+	assert_eq!(object.remove("a"), None);
+	assert_eq!(
+		object.into_iter().collect::<Vec<_>>(),
+		vec![
+			("b".to_string(), Amf0Value::Boolean(true)),
+			("c".to_string(), Amf0Value::Undefined),
+		]
+	);
+}
+
+#[test]
+fn test_write_string_too_long() {
+	let mut writer = BytesWriter::default();
+
+	assert!(matches!(
+		Amf0Writer::write_string(&mut writer, &"a".repeat(u16::MAX as usize + 1)),
+		Err(Amf0WriteError::NormalStringTooLong)
+	));
+	assert!(writer.dispose().is_empty());
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Metadata {
+	width: u32,
+	height: u32,
+	framerate: f64,
+	stereo: bool,
+	encoder: String,
+	#[serde(default)]
+	videocodecid: Option<u8>,
+	codec: Codec,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+enum Codec {
+	Avc,
+	Custom { name: String },
+}
+
+#[test]
+fn test_deserialize_struct() {
+	let value = Amf0Value::EcmaArray(Amf0Object::from([
+		("width", Amf0Value::Number(1280.0)),
+		("height", Amf0Value::Number(720.0)),
+		("framerate", Amf0Value::Number(29.97)),
+		("stereo", Amf0Value::Boolean(true)),
+		("encoder", Amf0Value::String("obs-output module".to_string())),
+		("videocodecid", Amf0Value::Null),
+		("codec", Amf0Value::String("Avc".to_string())),
+		("unknown", Amf0Value::StrictArray(vec![Amf0Value::Undefined])),
+	]));
+
+	let metadata: Metadata = crate::from_value(value).unwrap();
+
+	assert_eq!(
+		metadata,
+		Metadata {
+			width: 1280,
+			height: 720,
+			framerate: 29.97,
+			stereo: true,
+			encoder: "obs-output module".to_string(),
+			videocodecid: None,
+			codec: Codec::Avc,
+		}
+	);
+}
+
+#[test]
+fn test_deserialize_errors() {
+	let value = Amf0Value::Object(Amf0Object::from([("width", Amf0Value::Number(1280.5))]));
+	let err = crate::from_value::<Metadata>(value).unwrap_err();
+	assert_eq!(err.to_string(), "invalid type: floating point `1280.5`, expected u32");
+
+	let value = Amf0Value::Object(Amf0Object::from([("width", Amf0Value::Number(1280.0))]));
+	let err = crate::from_value::<Metadata>(value).unwrap_err();
+	assert_eq!(err.to_string(), "missing field `height`");
+}
+
+#[test]
+fn test_serialize_round_trip() {
+	let metadata = Metadata {
+		width: 1920,
+		height: 1080,
+		framerate: 60.0,
+		stereo: false,
+		encoder: "Lavf".to_string(),
+		videocodecid: Some(7),
+		codec: Codec::Custom {
+			name: "hvc1".to_string(),
+		},
+	};
+
+	let value = crate::to_value(&metadata).unwrap();
+	assert_eq!(
+		value,
+		Amf0Value::Object(Amf0Object::from([
+			("width", Amf0Value::Number(1920.0)),
+			("height", Amf0Value::Number(1080.0)),
+			("framerate", Amf0Value::Number(60.0)),
+			("stereo", Amf0Value::Boolean(false)),
+			("encoder", Amf0Value::String("Lavf".to_string())),
+			("videocodecid", Amf0Value::Number(7.0)),
+			(
+				"codec",
+				Amf0Value::Object(Amf0Object::from([(
+					"Custom",
+					Amf0Value::Object(Amf0Object::from([("name", Amf0Value::String("hvc1".to_string()))])),
+				)])),
+			),
+		]))
+	);
+
+	let bytes = crate::to_bytes(&metadata).unwrap();
+	assert_eq!(crate::from_bytes::<Metadata>(bytes).unwrap(), metadata);
+}
+
+#[test]
+fn test_serialize_collections() {
+	let value = crate::to_value(&std::collections::BTreeMap::from([(1, "one"), (2, "two")])).unwrap();
+	assert_eq!(
+		value,
+		Amf0Value::EcmaArray(Amf0Object::from([
+			("1", Amf0Value::String("one".to_string())),
+			("2", Amf0Value::String("two".to_string())),
+		]))
+	);
+
+	let value = crate::to_value(&(1, "two", None::<bool>)).unwrap();
+	assert_eq!(
+		value,
+		Amf0Value::StrictArray(vec![
+			Amf0Value::Number(1.0),
+			Amf0Value::String("two".to_string()),
+			Amf0Value::Null,
+		])
+	);
+
+	let err = crate::to_value(&std::collections::BTreeMap::from([((1, 2), "one")])).unwrap_err();
+	assert!(matches!(err, Amf0WriteError::Custom(_)));
+}
@@ -1,11 +1,11 @@
-use std::collections::HashMap;
 use std::io::Write;
 
+use amf3::{Amf3Value, Amf3Writer};
 use byteorder::{BigEndian, WriteBytesExt};
 use bytesio::bytes_writer::BytesWriter;
 
 use super::define::Amf0Marker;
-use super::{Amf0Value, Amf0WriteError};
+use super::{Amf0Object, Amf0Value, Amf0WriteError};
 
 pub struct Amf0Writer;
 
@@ -17,7 +17,17 @@ impl Amf0Writer {
 			Amf0Value::Number(val) => Self::write_number(writer, *val),
 			Amf0Value::String(val) => Self::write_string(writer, val.as_str()),
 			Amf0Value::Object(val) => Self::write_object(writer, val),
-			_ => Err(Amf0WriteError::UnsupportedType(value.clone())),
+			Amf0Value::Undefined => Self::write_undefined(writer),
+			Amf0Value::Reference(val) => Self::write_reference(writer, *val),
+			Amf0Value::EcmaArray(val) => Self::write_ecma_array(writer, val),
+			Amf0Value::StrictArray(val) => Self::write_strict_array(writer, val),
+			Amf0Value::Date { time, timezone } => Self::write_date(writer, *time, *timezone),
+			Amf0Value::LongString(val) => Self::write_long_string(writer, val),
+			Amf0Value::Unsupported => Self::write_unsupported(writer),
+			Amf0Value::XmlDocument(val) => Self::write_xml_document(writer, val),
+			Amf0Value::TypedObject { class_name, properties } => Self::write_typed_object(writer, class_name, properties),
+			Amf0Value::AVMPlusObject(val) => Self::write_avmplus_object(writer, val),
+			Amf0Value::ObjectEnd => Err(Amf0WriteError::UnsupportedType(value.clone())),
 		}
 	}
 
@@ -43,9 +53,7 @@ impl Amf0Writer {
 			return Err(Amf0WriteError::NormalStringTooLong);
 		}
 		writer.write_u8(Amf0Marker::String as u8)?;
-		writer.write_u16::<BigEndian>(value.len() as u16)?;
-		writer.write_all(value.as_bytes())?;
-		Ok(())
+		Self::write_raw_string(writer, value)
 	}
 
 	pub fn write_null(writer: &mut BytesWriter) -> Result<(), Amf0WriteError> {
@@ -53,15 +61,95 @@ impl Amf0Writer {
 		Ok(())
 	}
 
-	pub fn write_object(writer: &mut BytesWriter, properties: &HashMap<String, Amf0Value>) -> Result<(), Amf0WriteError> {
-		writer.write_u8(Amf0Marker::Object as u8)?;
+	fn write_raw_string(writer: &mut BytesWriter, value: &str) -> Result<(), Amf0WriteError> {
+		if value.len() > (u16::MAX as usize) {
+			return Err(Amf0WriteError::NormalStringTooLong);
+		}
+		writer.write_u16::<BigEndian>(value.len() as u16)?;
+		writer.write_all(value.as_bytes())?;
+		Ok(())
+	}
+
+	fn write_properties(writer: &mut BytesWriter, properties: &Amf0Object) -> Result<(), Amf0WriteError> {
 		for (key, value) in properties {
-			writer.write_u16::<BigEndian>(key.len() as u16)?;
-			writer.write_all(key.as_bytes())?;
+			Self::write_raw_string(writer, key)?;
 			Self::write_any(writer, value)?;
 		}
 
 		Self::write_object_eof(writer)?;
 		Ok(())
 	}
+
+	pub fn write_object(writer: &mut BytesWriter, properties: &Amf0Object) -> Result<(), Amf0WriteError> {
+		writer.write_u8(Amf0Marker::Object as u8)?;
+		Self::write_properties(writer, properties)
+	}
+
+	pub fn write_undefined(writer: &mut BytesWriter) -> Result<(), Amf0WriteError> {
+		writer.write_u8(Amf0Marker::Undefined as u8)?;
+		Ok(())
+	}
+
+	pub fn write_reference(writer: &mut BytesWriter, index: u16) -> Result<(), Amf0WriteError> {
+		writer.write_u8(Amf0Marker::Reference as u8)?;
+		writer.write_u16::<BigEndian>(index)?;
+		Ok(())
+	}
+
+	pub fn write_ecma_array(writer: &mut BytesWriter, properties: &Amf0Object) -> Result<(), Amf0WriteError> {
+		writer.write_u8(Amf0Marker::EcmaArray as u8)?;
+		writer.write_u32::<BigEndian>(properties.len() as u32)?;
+		Self::write_properties(writer, properties)
+	}
+
+	pub fn write_strict_array(writer: &mut BytesWriter, values: &[Amf0Value]) -> Result<(), Amf0WriteError> {
+		writer.write_u8(Amf0Marker::StrictArray as u8)?;
+		writer.write_u32::<BigEndian>(values.len() as u32)?;
+		for value in values {
+			Self::write_any(writer, value)?;
+		}
+		Ok(())
+	}
+
+	pub fn write_date(writer: &mut BytesWriter, time: f64, timezone: i16) -> Result<(), Amf0WriteError> {
+		writer.write_u8(Amf0Marker::Date as u8)?;
+		writer.write_f64::<BigEndian>(time)?;
+		writer.write_i16::<BigEndian>(timezone)?;
+		Ok(())
+	}
+
+	pub fn write_long_string(writer: &mut BytesWriter, value: &str) -> Result<(), Amf0WriteError> {
+		writer.write_u8(Amf0Marker::LongString as u8)?;
+		writer.write_u32::<BigEndian>(value.len() as u32)?;
+		writer.write_all(value.as_bytes())?;
+		Ok(())
+	}
+
+	pub fn write_unsupported(writer: &mut BytesWriter) -> Result<(), Amf0WriteError> {
+		writer.write_u8(Amf0Marker::Unsupported as u8)?;
+		Ok(())
+	}
+
+	pub fn write_xml_document(writer: &mut BytesWriter, value: &str) -> Result<(), Amf0WriteError> {
+		writer.write_u8(Amf0Marker::XmlDocument as u8)?;
+		writer.write_u32::<BigEndian>(value.len() as u32)?;
+		writer.write_all(value.as_bytes())?;
+		Ok(())
+	}
+
+	pub fn write_typed_object(
+		writer: &mut BytesWriter,
+		class_name: &str,
+		properties: &Amf0Object,
+	) -> Result<(), Amf0WriteError> {
+		writer.write_u8(Amf0Marker::TypedObject as u8)?;
+		Self::write_raw_string(writer, class_name)?;
+		Self::write_properties(writer, properties)
+	}
+
+	pub fn write_avmplus_object(writer: &mut BytesWriter, value: &Amf3Value) -> Result<(), Amf0WriteError> {
+		writer.write_u8(Amf0Marker::AVMPlusObject as u8)?;
+		Amf3Writer::default().write_any(writer, value)?;
+		Ok(())
+	}
 }
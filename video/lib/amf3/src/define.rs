@@ -0,0 +1,148 @@
+use bytes::Bytes;
+use num_derive::FromPrimitive;
+
+/// AMF3 marker types.
+/// Defined in amf-file-format-spec.pdf section 3.1
+#[derive(Debug, PartialEq, Eq, Clone, Copy, FromPrimitive)]
+#[repr(u8)]
+pub enum Amf3Marker {
+	Undefined = 0x00,
+	Null = 0x01,
+	False = 0x02,
+	True = 0x03,
+	Integer = 0x04,
+	Double = 0x05,
+	String = 0x06,
+	XmlDocument = 0x07,
+	Date = 0x08,
+	Array = 0x09,
+	Object = 0x0a,
+	Xml = 0x0b,
+	ByteArray = 0x0c,
+	VectorInt = 0x0d,
+	VectorUInt = 0x0e,
+	VectorDouble = 0x0f,
+	VectorObject = 0x10,
+	Dictionary = 0x11,
+}
+
+/// The smallest value that can be encoded as an AMF3 integer (29 bit signed).
+pub(crate) const AMF3_INTEGER_MIN: i32 = -(1 << 28);
+/// The largest value that can be encoded as an AMF3 integer (29 bit signed).
+pub(crate) const AMF3_INTEGER_MAX: i32 = (1 << 28) - 1;
+/// The largest value that fits in a U29.
+pub(crate) const AMF3_U29_MAX: u32 = (1 << 29) - 1;
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Amf3Value {
+	/// Undefined Type defined section 3.2
+	Undefined,
+	/// Null Type defined section 3.3
+	Null,
+	/// False and True Types defined section 3.4 and 3.5
+	Boolean(bool),
+	/// Integer Type defined section 3.6
+	Integer(i32),
+	/// Double Type defined section 3.7
+	Double(f64),
+	/// String Type defined section 3.8
+	String(String),
+	/// XMLDocument Type defined section 3.9
+	XmlDocument(String),
+	/// Date Type defined section 3.10, milliseconds since the unix epoch
+	Date(f64),
+	/// Array Type defined section 3.11
+	Array {
+		associative: Vec<(String, Amf3Value)>,
+		dense: Vec<Amf3Value>,
+	},
+	/// Object Type defined section 3.12
+	Object(Amf3Object),
+	/// XML Type defined section 3.13
+	Xml(String),
+	/// ByteArray Type defined section 3.14
+	ByteArray(Bytes),
+	/// Vector Type defined section 3.15
+	VectorInt { fixed: bool, values: Vec<i32> },
+	/// Vector Type defined section 3.15
+	VectorUInt { fixed: bool, values: Vec<u32> },
+	/// Vector Type defined section 3.15
+	VectorDouble { fixed: bool, values: Vec<f64> },
+	/// Vector Type defined section 3.15
+	VectorObject {
+		fixed: bool,
+		type_name: String,
+		values: Vec<Amf3Value>,
+	},
+	/// Dictionary Type defined section 3.16
+	Dictionary {
+		weak_keys: bool,
+		entries: Vec<(Amf3Value, Amf3Value)>,
+	},
+}
+
+/// The traits of an object, defined section 3.12
+/// Traits describe the class of an object and are shared by reference
+/// between objects of the same class.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Amf3Traits {
+	/// The class name, empty for anonymous objects.
+	pub class_name: String,
+	/// Dynamic objects can have members which are not part of the traits.
+	pub dynamic: bool,
+	/// Externalizable objects serialize their own data.
+	pub externalizable: bool,
+	/// The names of the members that every object of this class has.
+	pub sealed_members: Vec<String>,
+}
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Amf3Object {
+	pub traits: Amf3Traits,
+	/// Values of the sealed members, in the same order as `traits.sealed_members`.
+	pub sealed_values: Vec<Amf3Value>,
+	/// Members that are not part of the traits, only used for dynamic objects.
+	pub dynamic_members: Vec<(String, Amf3Value)>,
+	/// The data of an externalizable object.
+	/// We only know the format of the flex collection classes, which wrap a
+	/// single AMF3 value.
+	pub externalized: Option<Box<Amf3Value>>,
+}
+
+impl Amf3Object {
+	/// Creates an anonymous dynamic object, which is what ActionScript creates
+	/// for object literals.
+	pub fn anonymous(members: Vec<(String, Amf3Value)>) -> Self {
+		Self {
+			traits: Amf3Traits {
+				dynamic: true,
+				..Default::default()
+			},
+			dynamic_members: members,
+			..Default::default()
+		}
+	}
+
+	/// Looks up a member by name, sealed members first.
+	pub fn get(&self, key: &str) -> Option<&Amf3Value> {
+		self.members().find(|(name, _)| *name == key).map(|(_, value)| value)
+	}
+
+	/// Iterates over all members of the object, sealed members first.
+	pub fn members(&self) -> impl Iterator<Item = (&str, &Amf3Value)> {
+		self.traits
+			.sealed_members
+			.iter()
+			.map(String::as_str)
+			.zip(self.sealed_values.iter())
+			.chain(self.dynamic_members.iter().map(|(name, value)| (name.as_str(), value)))
+	}
+}
+
+/// Class names of the externalizable flex classes we know how to read.
+/// All of them are serialized as a single AMF3 value.
+pub(crate) const FLEX_EXTERNALIZABLE_CLASSES: [&str; 3] = [
+	"flex.messaging.io.ArrayCollection",
+	"flex.messaging.io.ArrayList",
+	"flex.messaging.io.ObjectProxy",
+];
@@ -0,0 +1,64 @@
+use std::{fmt, io, str};
+
+#[derive(Debug)]
+pub enum Amf3ReadError {
+	UnknownMarker(u8),
+	InvalidReference(usize),
+	CyclicReference(usize),
+	UnsupportedExternalizable(String),
+	StringParseError(str::Utf8Error),
+	IO(io::Error),
+	WrongType,
+}
+
+macro_rules! from_error {
+	($tt:ty, $val:expr, $err:ty) => {
+		impl From<$err> for $tt {
+			fn from(error: $err) -> Self {
+				$val(error)
+			}
+		}
+	};
+}
+
+from_error!(Amf3ReadError, Self::StringParseError, str::Utf8Error);
+from_error!(Amf3ReadError, Self::IO, io::Error);
+
+#[derive(Debug)]
+pub enum Amf3WriteError {
+	LengthTooLong(usize),
+	TraitsMismatch,
+	UnsupportedExternalizable(String),
+	IO(io::Error),
+}
+
+from_error!(Amf3WriteError, Self::IO, io::Error);
+
+impl fmt::Display for Amf3ReadError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::UnknownMarker(marker) => write!(f, "unknown marker: {}", marker),
+			Self::InvalidReference(index) => write!(f, "invalid reference: {}", index),
+			Self::CyclicReference(index) => write!(f, "cyclic reference: {}", index),
+			Self::UnsupportedExternalizable(class_name) => {
+				write!(f, "unsupported externalizable class: {}", class_name)
+			}
+			Self::WrongType => write!(f, "wrong type"),
+			Self::StringParseError(err) => write!(f, "string parse error: {}", err),
+			Self::IO(err) => write!(f, "io error: {}", err),
+		}
+	}
+}
+
+impl fmt::Display for Amf3WriteError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::LengthTooLong(length) => write!(f, "length too long: {}", length),
+			Self::TraitsMismatch => write!(f, "sealed values do not match traits"),
+			Self::UnsupportedExternalizable(class_name) => {
+				write!(f, "unsupported externalizable class: {}", class_name)
+			}
+			Self::IO(error) => write!(f, "io error: {}", error),
+		}
+	}
+}
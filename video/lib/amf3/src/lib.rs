@@ -0,0 +1,12 @@
+mod define;
+mod errors;
+mod reader;
+mod writer;
+
+pub use crate::define::{Amf3Marker, Amf3Object, Amf3Traits, Amf3Value};
+pub use crate::errors::{Amf3ReadError, Amf3WriteError};
+pub use crate::reader::Amf3Reader;
+pub use crate::writer::Amf3Writer;
+
+#[cfg(test)]
+mod tests;
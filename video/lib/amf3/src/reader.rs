@@ -0,0 +1,392 @@
+use std::io::{Cursor, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+use bytes::Bytes;
+use num_traits::FromPrimitive;
+
+use super::define::FLEX_EXTERNALIZABLE_CLASSES;
+use super::{Amf3Marker, Amf3Object, Amf3ReadError, Amf3Traits, Amf3Value};
+
+/// Reads AMF3 values.
+/// AMF3 deduplicates strings, complex objects and traits by sending
+/// references to values that were already sent. The reference tables live as
+/// long as the reader, so a single reader must be used for a single AMF3
+/// context (for example one RTMP message).
+pub struct Amf3Reader {
+	cursor: Cursor<Bytes>,
+
+	string_table: Vec<String>,
+	// Objects are registered before their members are read, since members are
+	// allowed to reference the object they are part of. We do not model cyclic
+	// graphs so such a slot stays `None` until the object is complete.
+	object_table: Vec<Option<Amf3Value>>,
+	trait_table: Vec<Amf3Traits>,
+}
+
+/// A U29 value that is either a reference into one of the tables or an
+/// inline value with the remaining 28 bits of data.
+enum U29Ref {
+	Reference(usize),
+	Value(u32),
+}
+
+impl Amf3Reader {
+	pub fn new(buff: Bytes) -> Self {
+		Self {
+			cursor: Cursor::new(buff),
+			string_table: Vec::new(),
+			object_table: Vec::new(),
+			trait_table: Vec::new(),
+		}
+	}
+
+	/// The number of bytes read so far.
+	pub fn position(&self) -> u64 {
+		self.cursor.position()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.cursor.get_ref().len() <= self.cursor.position() as usize
+	}
+
+	fn read_bytes(&mut self, len: usize) -> Result<Bytes, Amf3ReadError> {
+		let pos = self.cursor.position() as usize;
+		if self.cursor.get_ref().len() < pos + len {
+			return Err(Amf3ReadError::IO(std::io::ErrorKind::UnexpectedEof.into()));
+		}
+
+		self.cursor.seek(SeekFrom::Current(len as i64))?;
+		Ok(self.cursor.get_ref().slice(pos..pos + len))
+	}
+
+	pub fn read_all(&mut self) -> Result<Vec<Amf3Value>, Amf3ReadError> {
+		let mut results = vec![];
+
+		while !self.is_empty() {
+			results.push(self.read_any()?);
+		}
+
+		Ok(results)
+	}
+
+	pub fn read_any(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		let marker = self.cursor.read_u8()?;
+		let marker = Amf3Marker::from_u8(marker).ok_or(Amf3ReadError::UnknownMarker(marker))?;
+
+		match marker {
+			Amf3Marker::Undefined => Ok(Amf3Value::Undefined),
+			Amf3Marker::Null => Ok(Amf3Value::Null),
+			Amf3Marker::False => Ok(Amf3Value::Boolean(false)),
+			Amf3Marker::True => Ok(Amf3Value::Boolean(true)),
+			Amf3Marker::Integer => self.read_integer(),
+			Amf3Marker::Double => self.read_double(),
+			Amf3Marker::String => self.read_string(),
+			Amf3Marker::XmlDocument => self.read_xml_document(),
+			Amf3Marker::Date => self.read_date(),
+			Amf3Marker::Array => self.read_array(),
+			Amf3Marker::Object => self.read_object(),
+			Amf3Marker::Xml => self.read_xml(),
+			Amf3Marker::ByteArray => self.read_byte_array(),
+			Amf3Marker::VectorInt => self.read_vector_int(),
+			Amf3Marker::VectorUInt => self.read_vector_uint(),
+			Amf3Marker::VectorDouble => self.read_vector_double(),
+			Amf3Marker::VectorObject => self.read_vector_object(),
+			Amf3Marker::Dictionary => self.read_dictionary(),
+		}
+	}
+
+	pub fn read_with_type(&mut self, specified_marker: Amf3Marker) -> Result<Amf3Value, Amf3ReadError> {
+		let marker = self.cursor.read_u8()?;
+		self.cursor.seek(SeekFrom::Current(-1))?; // seek back to the original position
+
+		let marker = Amf3Marker::from_u8(marker).ok_or(Amf3ReadError::UnknownMarker(marker))?;
+		// True and False are two markers for the same type
+		let matches = match specified_marker {
+			Amf3Marker::True | Amf3Marker::False => matches!(marker, Amf3Marker::True | Amf3Marker::False),
+			_ => marker == specified_marker,
+		};
+
+		if !matches {
+			return Err(Amf3ReadError::WrongType);
+		}
+
+		self.read_any()
+	}
+
+	/// Variable length unsigned 29-bit integer, defined section 1.3.1
+	/// The first 3 bytes use their high bit to signal that another byte
+	/// follows, the 4th byte (if present) contributes all 8 bits.
+	fn read_u29(&mut self) -> Result<u32, Amf3ReadError> {
+		let mut value = 0;
+
+		for _ in 0..3 {
+			let byte = self.cursor.read_u8()? as u32;
+			value = (value << 7) | (byte & 0x7f);
+
+			if byte & 0x80 == 0 {
+				return Ok(value);
+			}
+		}
+
+		let byte = self.cursor.read_u8()? as u32;
+		Ok((value << 8) | byte)
+	}
+
+	/// Most complex types start with a U29 whose low bit tells us if this is
+	/// an inline value or a reference to a previously read value.
+	fn read_u29_ref(&mut self) -> Result<U29Ref, Amf3ReadError> {
+		let value = self.read_u29()?;
+
+		if value & 0x01 == 0 {
+			Ok(U29Ref::Reference((value >> 1) as usize))
+		} else {
+			Ok(U29Ref::Value(value >> 1))
+		}
+	}
+
+	fn object_reference(&self, index: usize) -> Result<Amf3Value, Amf3ReadError> {
+		self.object_table
+			.get(index)
+			.ok_or(Amf3ReadError::InvalidReference(index))?
+			.clone()
+			.ok_or(Amf3ReadError::CyclicReference(index))
+	}
+
+	/// Reads a value that is stored in the object table, either by reference
+	/// or inline. `read` is called with the remaining bits of the U29 header
+	/// when the value is sent inline.
+	fn read_complex(
+		&mut self,
+		read: impl FnOnce(&mut Self, u32) -> Result<Amf3Value, Amf3ReadError>,
+	) -> Result<Amf3Value, Amf3ReadError> {
+		match self.read_u29_ref()? {
+			U29Ref::Reference(index) => self.object_reference(index),
+			U29Ref::Value(header) => {
+				let index = self.object_table.len();
+				self.object_table.push(None);
+
+				let value = read(self, header)?;
+				self.object_table[index] = Some(value.clone());
+
+				Ok(value)
+			}
+		}
+	}
+
+	fn read_utf8(&mut self, len: usize) -> Result<String, Amf3ReadError> {
+		let bytes = self.read_bytes(len)?;
+		Ok(std::str::from_utf8(&bytes)?.to_string())
+	}
+
+	/// UTF-8-vr, defined section 1.3.2
+	fn read_raw_string(&mut self) -> Result<String, Amf3ReadError> {
+		match self.read_u29_ref()? {
+			U29Ref::Reference(index) => self
+				.string_table
+				.get(index)
+				.cloned()
+				.ok_or(Amf3ReadError::InvalidReference(index)),
+			U29Ref::Value(len) => {
+				let value = self.read_utf8(len as usize)?;
+
+				// The empty string is never sent by reference.
+				if !value.is_empty() {
+					self.string_table.push(value.clone());
+				}
+
+				Ok(value)
+			}
+		}
+	}
+
+	pub fn read_integer(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		let value = self.read_u29()?;
+
+		// Sign extend the 29 bit value
+		let value = ((value << 3) as i32) >> 3;
+
+		Ok(Amf3Value::Integer(value))
+	}
+
+	pub fn read_double(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		Ok(Amf3Value::Double(self.cursor.read_f64::<BigEndian>()?))
+	}
+
+	pub fn read_string(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		Ok(Amf3Value::String(self.read_raw_string()?))
+	}
+
+	pub fn read_xml_document(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		// XML values use the object table and not the string table.
+		self.read_complex(|reader, len| Ok(Amf3Value::XmlDocument(reader.read_utf8(len as usize)?)))
+	}
+
+	pub fn read_xml(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		self.read_complex(|reader, len| Ok(Amf3Value::Xml(reader.read_utf8(len as usize)?)))
+	}
+
+	pub fn read_date(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		self.read_complex(|reader, _| Ok(Amf3Value::Date(reader.cursor.read_f64::<BigEndian>()?)))
+	}
+
+	pub fn read_array(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		self.read_complex(|reader, dense_len| {
+			// The associative part is terminated by an empty key
+			let mut associative = Vec::new();
+			loop {
+				let key = reader.read_raw_string()?;
+				if key.is_empty() {
+					break;
+				}
+
+				associative.push((key, reader.read_any()?));
+			}
+
+			let mut dense = Vec::new();
+			for _ in 0..dense_len {
+				dense.push(reader.read_any()?);
+			}
+
+			Ok(Amf3Value::Array { associative, dense })
+		})
+	}
+
+	/// U29O-traits, U29O-traits-ref and U29O-traits-ext, defined section 3.12
+	/// `flags` is the U29 with the object reference bit already removed.
+	fn read_traits(&mut self, flags: u32) -> Result<Amf3Traits, Amf3ReadError> {
+		if flags & 0x01 == 0 {
+			let index = (flags >> 1) as usize;
+			return self
+				.trait_table
+				.get(index)
+				.cloned()
+				.ok_or(Amf3ReadError::InvalidReference(index));
+		}
+
+		let externalizable = flags & 0x02 != 0;
+		let dynamic = flags & 0x04 != 0;
+		let sealed_count = if externalizable { 0 } else { flags >> 3 };
+
+		let class_name = self.read_raw_string()?;
+
+		let mut sealed_members = Vec::new();
+		for _ in 0..sealed_count {
+			sealed_members.push(self.read_raw_string()?);
+		}
+
+		let traits = Amf3Traits {
+			class_name,
+			dynamic: dynamic && !externalizable,
+			externalizable,
+			sealed_members,
+		};
+
+		self.trait_table.push(traits.clone());
+
+		Ok(traits)
+	}
+
+	pub fn read_object(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		self.read_complex(|reader, flags| {
+			let traits = reader.read_traits(flags)?;
+
+			let mut object = Amf3Object::default();
+
+			if traits.externalizable {
+				if !FLEX_EXTERNALIZABLE_CLASSES.contains(&traits.class_name.as_str()) {
+					// The format of the data is only known to the class itself, so
+					// we cannot continue reading.
+					return Err(Amf3ReadError::UnsupportedExternalizable(traits.class_name));
+				}
+
+				object.externalized = Some(Box::new(reader.read_any()?));
+			} else {
+				for _ in 0..traits.sealed_members.len() {
+					object.sealed_values.push(reader.read_any()?);
+				}
+
+				// Dynamic members are terminated by an empty key
+				if traits.dynamic {
+					loop {
+						let key = reader.read_raw_string()?;
+						if key.is_empty() {
+							break;
+						}
+
+						object.dynamic_members.push((key, reader.read_any()?));
+					}
+				}
+			}
+
+			object.traits = traits;
+
+			Ok(Amf3Value::Object(object))
+		})
+	}
+
+	pub fn read_byte_array(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		self.read_complex(|reader, len| Ok(Amf3Value::ByteArray(reader.read_bytes(len as usize)?)))
+	}
+
+	pub fn read_vector_int(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		self.read_complex(|reader, len| {
+			let fixed = reader.cursor.read_u8()? != 0;
+			let values = (0..len)
+				.map(|_| reader.cursor.read_i32::<BigEndian>())
+				.collect::<Result<_, _>>()?;
+
+			Ok(Amf3Value::VectorInt { fixed, values })
+		})
+	}
+
+	pub fn read_vector_uint(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		self.read_complex(|reader, len| {
+			let fixed = reader.cursor.read_u8()? != 0;
+			let values = (0..len)
+				.map(|_| reader.cursor.read_u32::<BigEndian>())
+				.collect::<Result<_, _>>()?;
+
+			Ok(Amf3Value::VectorUInt { fixed, values })
+		})
+	}
+
+	pub fn read_vector_double(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		self.read_complex(|reader, len| {
+			let fixed = reader.cursor.read_u8()? != 0;
+			let values = (0..len)
+				.map(|_| reader.cursor.read_f64::<BigEndian>())
+				.collect::<Result<_, _>>()?;
+
+			Ok(Amf3Value::VectorDouble { fixed, values })
+		})
+	}
+
+	pub fn read_vector_object(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		self.read_complex(|reader, len| {
+			let fixed = reader.cursor.read_u8()? != 0;
+			let type_name = reader.read_raw_string()?;
+			let values = (0..len).map(|_| reader.read_any()).collect::<Result<_, _>>()?;
+
+			Ok(Amf3Value::VectorObject {
+				fixed,
+				type_name,
+				values,
+			})
+		})
+	}
+
+	pub fn read_dictionary(&mut self) -> Result<Amf3Value, Amf3ReadError> {
+		self.read_complex(|reader, len| {
+			let weak_keys = reader.cursor.read_u8()? != 0;
+
+			let mut entries = Vec::new();
+			for _ in 0..len {
+				let key = reader.read_any()?;
+				let value = reader.read_any()?;
+				entries.push((key, value));
+			}
+
+			Ok(Amf3Value::Dictionary { weak_keys, entries })
+		})
+	}
+}
@@ -0,0 +1,348 @@
+use bytes::Bytes;
+use bytesio::bytes_writer::BytesWriter;
+
+use crate::{Amf3Marker, Amf3Object, Amf3ReadError, Amf3Reader, Amf3Traits, Amf3Value, Amf3WriteError, Amf3Writer};
+
+fn write(value: &Amf3Value) -> Bytes {
+	let mut writer = BytesWriter::default();
+	Amf3Writer::default().write_any(&mut writer, value).unwrap();
+	writer.dispose()
+}
+
+fn read(bytes: Vec<u8>) -> Amf3Value {
+	let mut reader = Amf3Reader::new(bytes.into());
+	let value = reader.read_any().unwrap();
+	assert!(reader.is_empty());
+	value
+}
+
+#[test]
+fn test_reader_simple_types() {
+	let mut reader = Amf3Reader::new(Bytes::from_static(&[0x00, 0x01, 0x02, 0x03]));
+	assert_eq!(
+		reader.read_all().unwrap(),
+		vec![
+			Amf3Value::Undefined,
+			Amf3Value::Null,
+			Amf3Value::Boolean(false),
+			Amf3Value::Boolean(true),
+		]
+	);
+}
+
+#[test]
+fn test_reader_integer() {
+	let cases: [(&[u8], i32); 6] = [
+		(&[0x04, 0x00], 0),
+		(&[0x04, 0x7f], 127),
+		(&[0x04, 0x81, 0x00], 128),
+		(&[0x04, 0xbf, 0xff, 0xff, 0xff], (1 << 28) - 1),
+		(&[0x04, 0xff, 0xff, 0xff, 0xff], -1),
+		(&[0x04, 0xc0, 0x80, 0x80, 0x00], -(1 << 28)),
+	];
+
+	for (bytes, expected) in cases {
+		let mut reader = Amf3Reader::new(Bytes::copy_from_slice(bytes));
+		let value = reader.read_with_type(Amf3Marker::Integer).unwrap();
+		assert_eq!(value, Amf3Value::Integer(expected));
+	}
+}
+
+#[test]
+fn test_reader_string_reference() {
+	let mut bytes = vec![0x06, 0x0b]; // 5 bytes, inline
+	bytes.extend_from_slice(b"hello");
+	bytes.extend_from_slice(&[0x06, 0x00]); // reference to string 0
+	bytes.extend_from_slice(&[0x06, 0x01]); // empty string
+
+	let mut reader = Amf3Reader::new(bytes.into());
+	assert_eq!(
+		reader.read_all().unwrap(),
+		vec![
+			Amf3Value::String("hello".to_string()),
+			Amf3Value::String("hello".to_string()),
+			Amf3Value::String("".to_string()),
+		]
+	);
+}
+
+#[test]
+fn test_reader_anonymous_object() {
+	let bytes = vec![
+		0x0a, 0x0b, // object, inline traits, dynamic, 0 sealed members
+		0x01, // anonymous class name
+		0x07, b'f', b'o', b'o', // key "foo"
+		0x04, 0x05, // integer 5
+		0x01, // end of dynamic members
+	];
+
+	let value = read(bytes);
+	let Amf3Value::Object(object) = value else {
+		panic!("expected object, got {:?}", value);
+	};
+
+	assert_eq!(
+		object,
+		Amf3Object::anonymous(vec![("foo".to_string(), Amf3Value::Integer(5))])
+	);
+	assert_eq!(object.get("foo"), Some(&Amf3Value::Integer(5)));
+	assert_eq!(object.get("bar"), None);
+}
+
+#[test]
+fn test_reader_traits_and_object_reference() {
+	let bytes = vec![
+		0x09, 0x07, // array, 3 dense values
+		0x01, // no associative values
+		// object with inline sealed traits: class "Point" with members "x" and "y"
+		0x0a, 0x23, 0x0b, b'P', b'o', b'i', b'n', b't', 0x03, b'x', 0x03, b'y', 0x04, 0x01, 0x04, 0x02,
+		// object with the same traits by reference
+		0x0a, 0x01, 0x04, 0x03, 0x04, 0x04, // reference to the second object (index 2, index 0 is the array)
+		0x0a, 0x04,
+	];
+
+	let traits = Amf3Traits {
+		class_name: "Point".to_string(),
+		dynamic: false,
+		externalizable: false,
+		sealed_members: vec!["x".to_string(), "y".to_string()],
+	};
+
+	let point = |x, y| {
+		Amf3Value::Object(Amf3Object {
+			traits: traits.clone(),
+			sealed_values: vec![Amf3Value::Integer(x), Amf3Value::Integer(y)],
+			..Default::default()
+		})
+	};
+
+	assert_eq!(
+		read(bytes),
+		Amf3Value::Array {
+			associative: vec![],
+			dense: vec![point(1, 2), point(3, 4), point(3, 4)],
+		}
+	);
+}
+
+#[test]
+fn test_reader_cyclic_reference() {
+	let bytes = vec![
+		0x0a, 0x0b, 0x01, // anonymous dynamic object
+		0x09, b's', b'e', b'l', b'f', // key "self"
+		0x0a, 0x00, // reference to the object we are reading
+	];
+
+	let mut reader = Amf3Reader::new(bytes.into());
+	assert!(matches!(reader.read_any(), Err(Amf3ReadError::CyclicReference(0))));
+}
+
+#[test]
+fn test_reader_invalid_reference() {
+	let mut reader = Amf3Reader::new(Bytes::from_static(&[0x06, 0x02]));
+	assert!(matches!(reader.read_any(), Err(Amf3ReadError::InvalidReference(1))));
+
+	let mut reader = Amf3Reader::new(Bytes::from_static(&[0x0a, 0x00]));
+	assert!(matches!(reader.read_any(), Err(Amf3ReadError::InvalidReference(0))));
+}
+
+#[test]
+fn test_reader_unsupported_externalizable() {
+	let mut bytes = vec![0x0a, 0x07, 0x09]; // externalizable traits, class name with 4 bytes
+	bytes.extend_from_slice(b"Blob");
+
+	let mut reader = Amf3Reader::new(bytes.into());
+	assert!(matches!(
+		reader.read_any(),
+		Err(Amf3ReadError::UnsupportedExternalizable(name)) if name == "Blob"
+	));
+}
+
+#[test]
+fn test_reader_wrong_type() {
+	let mut reader = Amf3Reader::new(Bytes::from_static(&[0x03]));
+	assert!(matches!(
+		reader.read_with_type(Amf3Marker::Integer),
+		Err(Amf3ReadError::WrongType)
+	));
+
+	let mut reader = Amf3Reader::new(Bytes::from_static(&[0x03]));
+	assert_eq!(reader.read_with_type(Amf3Marker::False).unwrap(), Amf3Value::Boolean(true));
+}
+
+#[test]
+fn test_reader_unknown_marker() {
+	let mut reader = Amf3Reader::new(Bytes::from_static(&[0x20]));
+	assert!(matches!(reader.read_any(), Err(Amf3ReadError::UnknownMarker(0x20))));
+}
+
+#[test]
+fn test_writer_integer() {
+	assert_eq!(write(&Amf3Value::Integer(128)), vec![0x04, 0x81, 0x00]);
+	assert_eq!(write(&Amf3Value::Integer(-1)), vec![0x04, 0xff, 0xff, 0xff, 0xff]);
+
+	// Out of range integers are written as doubles
+	let mut expected = vec![0x05];
+	expected.extend_from_slice(&((1 << 28) as f64).to_be_bytes());
+	assert_eq!(write(&Amf3Value::Integer(1 << 28)), expected);
+}
+
+#[test]
+fn test_writer_references() {
+	let traits = Amf3Traits {
+		class_name: "Point".to_string(),
+		sealed_members: vec!["x".to_string()],
+		..Default::default()
+	};
+
+	let object = Amf3Value::Object(Amf3Object {
+		traits,
+		sealed_values: vec![Amf3Value::String("x".to_string())],
+		..Default::default()
+	});
+
+	let value = Amf3Value::Array {
+		associative: vec![],
+		dense: vec![object.clone(), object],
+	};
+
+	assert_eq!(
+		write(&value),
+		vec![
+			0x09, 0x05, 0x01, // array with 2 dense values
+			0x0a, 0x13, 0x0b, b'P', b'o', b'i', b'n', b't', 0x03, b'x', // inline traits
+			0x06, 0x02, // the value "x" is a reference to the member name
+			0x0a, 0x01, // traits reference
+			0x06, 0x02,
+		]
+	);
+}
+
+#[test]
+fn test_round_trip() {
+	let values = vec![
+		Amf3Value::Undefined,
+		Amf3Value::Null,
+		Amf3Value::Boolean(true),
+		Amf3Value::Integer(-12345),
+		Amf3Value::Double(3.5),
+		Amf3Value::String("hello".to_string()),
+		Amf3Value::XmlDocument("<a/>".to_string()),
+		Amf3Value::Date(1_700_000_000_000.0),
+		Amf3Value::Array {
+			associative: vec![("key".to_string(), Amf3Value::String("hello".to_string()))],
+			dense: vec![Amf3Value::Integer(1), Amf3Value::Null],
+		},
+		Amf3Value::Object(Amf3Object::anonymous(vec![
+			("app".to_string(), Amf3Value::String("live".to_string())),
+			("objectEncoding".to_string(), Amf3Value::Integer(3)),
+		])),
+		Amf3Value::Object(Amf3Object {
+			traits: Amf3Traits {
+				class_name: "flex.messaging.io.ArrayCollection".to_string(),
+				externalizable: true,
+				..Default::default()
+			},
+			externalized: Some(Box::new(Amf3Value::Array {
+				associative: vec![],
+				dense: vec![Amf3Value::Integer(1)],
+			})),
+			..Default::default()
+		}),
+		Amf3Value::Xml("<b/>".to_string()),
+		Amf3Value::ByteArray(Bytes::from_static(&[1, 2, 3])),
+		Amf3Value::VectorInt {
+			fixed: false,
+			values: vec![-1, 2],
+		},
+		Amf3Value::VectorUInt {
+			fixed: true,
+			values: vec![u32::MAX],
+		},
+		Amf3Value::VectorDouble {
+			fixed: false,
+			values: vec![0.5],
+		},
+		Amf3Value::VectorObject {
+			fixed: false,
+			type_name: "".to_string(),
+			values: vec![Amf3Value::String("hello".to_string())],
+		},
+		Amf3Value::Dictionary {
+			weak_keys: false,
+			entries: vec![(Amf3Value::Integer(1), Amf3Value::String("one".to_string()))],
+		},
+	];
+
+	// Write everything with a single writer so references are used
+	let mut writer = BytesWriter::default();
+	let mut amf3_writer = Amf3Writer::default();
+	for value in &values {
+		amf3_writer.write_any(&mut writer, value).unwrap();
+	}
+
+	let mut reader = Amf3Reader::new(writer.dispose());
+	assert_eq!(reader.read_all().unwrap(), values);
+}
+
+#[test]
+fn test_writer_errors() {
+	let mut writer = BytesWriter::default();
+
+	let value = Amf3Value::Object(Amf3Object {
+		traits: Amf3Traits {
+			sealed_members: vec!["x".to_string()],
+			..Default::default()
+		},
+		..Default::default()
+	});
+	assert!(matches!(
+		Amf3Writer::default().write_any(&mut writer, &value),
+		Err(Amf3WriteError::TraitsMismatch)
+	));
+
+	let value = Amf3Value::Object(Amf3Object {
+		traits: Amf3Traits {
+			class_name: "Blob".to_string(),
+			externalizable: true,
+			..Default::default()
+		},
+		..Default::default()
+	});
+	assert!(matches!(
+		Amf3Writer::default().write_any(&mut writer, &value),
+		Err(Amf3WriteError::UnsupportedExternalizable(name)) if name == "Blob"
+	));
+}
+
+#[test]
+fn test_read_error_display() {
+	assert_eq!(Amf3ReadError::UnknownMarker(100).to_string(), "unknown marker: 100");
+	assert_eq!(Amf3ReadError::InvalidReference(1).to_string(), "invalid reference: 1");
+	assert_eq!(Amf3ReadError::CyclicReference(2).to_string(), "cyclic reference: 2");
+	assert_eq!(
+		Amf3ReadError::UnsupportedExternalizable("Blob".to_string()).to_string(),
+		"unsupported externalizable class: Blob"
+	);
+	assert_eq!(Amf3ReadError::WrongType.to_string(), "wrong type");
+	assert_eq!(
+		Amf3ReadError::IO(std::io::ErrorKind::UnexpectedEof.into()).to_string(),
+		"io error: unexpected end of file"
+	);
+}
+
+#[test]
+fn test_write_error_display() {
+	assert_eq!(
+		Amf3WriteError::LengthTooLong(1 << 29).to_string(),
+		"length too long: 536870912"
+	);
+	assert_eq!(
+		Amf3WriteError::TraitsMismatch.to_string(),
+		"sealed values do not match traits"
+	);
+	assert_eq!(
+		Amf3WriteError::UnsupportedExternalizable("Blob".to_string()).to_string(),
+		"unsupported externalizable class: Blob"
+	);
+}
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use byteorder::{BigEndian, WriteBytesExt};
+use bytesio::bytes_writer::BytesWriter;
+
+use super::define::{AMF3_INTEGER_MAX, AMF3_INTEGER_MIN, AMF3_U29_MAX, FLEX_EXTERNALIZABLE_CLASSES};
+use super::{Amf3Marker, Amf3Object, Amf3Traits, Amf3Value, Amf3WriteError};
+
+/// Writes AMF3 values.
+/// Strings and traits are sent by reference when they were already written
+/// by this writer, so a single writer must be used for a single AMF3 context
+/// (for example one RTMP message). Complex objects are always written inline.
+#[derive(Default)]
+pub struct Amf3Writer {
+	string_table: HashMap<String, usize>,
+	trait_table: Vec<Amf3Traits>,
+}
+
+impl Amf3Writer {
+	pub fn write_any(&mut self, writer: &mut BytesWriter, value: &Amf3Value) -> Result<(), Amf3WriteError> {
+		match value {
+			Amf3Value::Undefined => Self::write_undefined(writer),
+			Amf3Value::Null => Self::write_null(writer),
+			Amf3Value::Boolean(val) => Self::write_bool(writer, *val),
+			Amf3Value::Integer(val) => Self::write_integer(writer, *val),
+			Amf3Value::Double(val) => Self::write_double(writer, *val),
+			Amf3Value::String(val) => self.write_string(writer, val),
+			Amf3Value::XmlDocument(val) => Self::write_xml_document(writer, val),
+			Amf3Value::Date(val) => Self::write_date(writer, *val),
+			Amf3Value::Array { associative, dense } => self.write_array(writer, associative, dense),
+			Amf3Value::Object(val) => self.write_object(writer, val),
+			Amf3Value::Xml(val) => Self::write_xml(writer, val),
+			Amf3Value::ByteArray(val) => Self::write_byte_array(writer, val),
+			Amf3Value::VectorInt { fixed, values } => Self::write_vector_int(writer, *fixed, values),
+			Amf3Value::VectorUInt { fixed, values } => Self::write_vector_uint(writer, *fixed, values),
+			Amf3Value::VectorDouble { fixed, values } => Self::write_vector_double(writer, *fixed, values),
+			Amf3Value::VectorObject {
+				fixed,
+				type_name,
+				values,
+			} => self.write_vector_object(writer, *fixed, type_name, values),
+			Amf3Value::Dictionary { weak_keys, entries } => self.write_dictionary(writer, *weak_keys, entries),
+		}
+	}
+
+	/// Variable length unsigned 29-bit integer, defined section 1.3.1
+	fn write_u29(writer: &mut BytesWriter, value: u32) -> Result<(), Amf3WriteError> {
+		if value > AMF3_U29_MAX {
+			return Err(Amf3WriteError::LengthTooLong(value as usize));
+		}
+
+		if value < 0x80 {
+			writer.write_u8(value as u8)?;
+		} else if value < 0x4000 {
+			writer.write_u8(((value >> 7) | 0x80) as u8)?;
+			writer.write_u8((value & 0x7f) as u8)?;
+		} else if value < 0x200000 {
+			writer.write_u8(((value >> 14) | 0x80) as u8)?;
+			writer.write_u8(((value >> 7) | 0x80) as u8)?;
+			writer.write_u8((value & 0x7f) as u8)?;
+		} else {
+			writer.write_u8(((value >> 22) | 0x80) as u8)?;
+			writer.write_u8(((value >> 15) | 0x80) as u8)?;
+			writer.write_u8(((value >> 8) | 0x80) as u8)?;
+			writer.write_u8(value as u8)?;
+		}
+
+		Ok(())
+	}
+
+	/// Writes the U29 header of an inline value, the low bit marks the value
+	/// as not being a reference.
+	fn write_inline_header(writer: &mut BytesWriter, value: usize) -> Result<(), Amf3WriteError> {
+		if value > (AMF3_U29_MAX >> 1) as usize {
+			return Err(Amf3WriteError::LengthTooLong(value));
+		}
+
+		Self::write_u29(writer, ((value as u32) << 1) | 0x01)
+	}
+
+	/// UTF-8-vr, defined section 1.3.2
+	fn write_raw_string(&mut self, writer: &mut BytesWriter, value: &str) -> Result<(), Amf3WriteError> {
+		// The empty string is never sent by reference.
+		if value.is_empty() {
+			return Self::write_inline_header(writer, 0);
+		}
+
+		if let Some(index) = self.string_table.get(value) {
+			return Self::write_u29(writer, (*index as u32) << 1);
+		}
+
+		Self::write_inline_header(writer, value.len())?;
+		writer.write_all(value.as_bytes())?;
+
+		self.string_table.insert(value.to_string(), self.string_table.len());
+
+		Ok(())
+	}
+
+	pub fn write_undefined(writer: &mut BytesWriter) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::Undefined as u8)?;
+		Ok(())
+	}
+
+	pub fn write_null(writer: &mut BytesWriter) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::Null as u8)?;
+		Ok(())
+	}
+
+	pub fn write_bool(writer: &mut BytesWriter, value: bool) -> Result<(), Amf3WriteError> {
+		let marker = if value { Amf3Marker::True } else { Amf3Marker::False };
+		writer.write_u8(marker as u8)?;
+		Ok(())
+	}
+
+	/// Integers outside of the 29 bit range are written as doubles, as
+	/// required by section 3.6
+	pub fn write_integer(writer: &mut BytesWriter, value: i32) -> Result<(), Amf3WriteError> {
+		if !(AMF3_INTEGER_MIN..=AMF3_INTEGER_MAX).contains(&value) {
+			return Self::write_double(writer, value as f64);
+		}
+
+		writer.write_u8(Amf3Marker::Integer as u8)?;
+		Self::write_u29(writer, (value as u32) & AMF3_U29_MAX)
+	}
+
+	pub fn write_double(writer: &mut BytesWriter, value: f64) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::Double as u8)?;
+		writer.write_f64::<BigEndian>(value)?;
+		Ok(())
+	}
+
+	pub fn write_string(&mut self, writer: &mut BytesWriter, value: &str) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::String as u8)?;
+		self.write_raw_string(writer, value)
+	}
+
+	pub fn write_xml_document(writer: &mut BytesWriter, value: &str) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::XmlDocument as u8)?;
+		Self::write_inline_header(writer, value.len())?;
+		writer.write_all(value.as_bytes())?;
+		Ok(())
+	}
+
+	pub fn write_date(writer: &mut BytesWriter, value: f64) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::Date as u8)?;
+		Self::write_inline_header(writer, 0)?;
+		writer.write_f64::<BigEndian>(value)?;
+		Ok(())
+	}
+
+	pub fn write_array(
+		&mut self,
+		writer: &mut BytesWriter,
+		associative: &[(String, Amf3Value)],
+		dense: &[Amf3Value],
+	) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::Array as u8)?;
+		Self::write_inline_header(writer, dense.len())?;
+
+		for (key, value) in associative {
+			self.write_raw_string(writer, key)?;
+			self.write_any(writer, value)?;
+		}
+		self.write_raw_string(writer, "")?;
+
+		for value in dense {
+			self.write_any(writer, value)?;
+		}
+
+		Ok(())
+	}
+
+	/// U29O-traits, U29O-traits-ref and U29O-traits-ext, defined section 3.12
+	fn write_traits(&mut self, writer: &mut BytesWriter, traits: &Amf3Traits) -> Result<(), Amf3WriteError> {
+		if let Some(index) = self.trait_table.iter().position(|t| t == traits) {
+			// 0b01 marks an inline object with referenced traits
+			return Self::write_u29(writer, ((index as u32) << 2) | 0x01);
+		}
+
+		let sealed_count = if traits.externalizable {
+			0
+		} else {
+			traits.sealed_members.len()
+		};
+
+		let mut flags = (sealed_count << 4) | 0x03;
+		if traits.externalizable {
+			flags |= 0x04;
+		}
+		if traits.dynamic {
+			flags |= 0x08;
+		}
+
+		if flags > AMF3_U29_MAX as usize {
+			return Err(Amf3WriteError::LengthTooLong(sealed_count));
+		}
+
+		Self::write_u29(writer, flags as u32)?;
+		self.write_raw_string(writer, &traits.class_name)?;
+		for name in traits.sealed_members.iter().take(sealed_count) {
+			self.write_raw_string(writer, name)?;
+		}
+
+		self.trait_table.push(traits.clone());
+
+		Ok(())
+	}
+
+	pub fn write_object(&mut self, writer: &mut BytesWriter, object: &Amf3Object) -> Result<(), Amf3WriteError> {
+		let traits = &object.traits;
+
+		if traits.externalizable {
+			let externalized = object
+				.externalized
+				.as_deref()
+				.filter(|_| FLEX_EXTERNALIZABLE_CLASSES.contains(&traits.class_name.as_str()))
+				.ok_or_else(|| Amf3WriteError::UnsupportedExternalizable(traits.class_name.clone()))?;
+
+			writer.write_u8(Amf3Marker::Object as u8)?;
+			self.write_traits(writer, traits)?;
+			return self.write_any(writer, externalized);
+		}
+
+		if traits.sealed_members.len() != object.sealed_values.len() {
+			return Err(Amf3WriteError::TraitsMismatch);
+		}
+
+		writer.write_u8(Amf3Marker::Object as u8)?;
+		self.write_traits(writer, traits)?;
+
+		for value in &object.sealed_values {
+			self.write_any(writer, value)?;
+		}
+
+		if traits.dynamic {
+			for (key, value) in &object.dynamic_members {
+				self.write_raw_string(writer, key)?;
+				self.write_any(writer, value)?;
+			}
+			self.write_raw_string(writer, "")?;
+		}
+
+		Ok(())
+	}
+
+	pub fn write_xml(writer: &mut BytesWriter, value: &str) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::Xml as u8)?;
+		Self::write_inline_header(writer, value.len())?;
+		writer.write_all(value.as_bytes())?;
+		Ok(())
+	}
+
+	pub fn write_byte_array(writer: &mut BytesWriter, value: &[u8]) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::ByteArray as u8)?;
+		Self::write_inline_header(writer, value.len())?;
+		writer.write_all(value)?;
+		Ok(())
+	}
+
+	pub fn write_vector_int(writer: &mut BytesWriter, fixed: bool, values: &[i32]) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::VectorInt as u8)?;
+		Self::write_inline_header(writer, values.len())?;
+		writer.write_u8(fixed as u8)?;
+		for value in values {
+			writer.write_i32::<BigEndian>(*value)?;
+		}
+		Ok(())
+	}
+
+	pub fn write_vector_uint(writer: &mut BytesWriter, fixed: bool, values: &[u32]) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::VectorUInt as u8)?;
+		Self::write_inline_header(writer, values.len())?;
+		writer.write_u8(fixed as u8)?;
+		for value in values {
+			writer.write_u32::<BigEndian>(*value)?;
+		}
+		Ok(())
+	}
+
+	pub fn write_vector_double(writer: &mut BytesWriter, fixed: bool, values: &[f64]) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::VectorDouble as u8)?;
+		Self::write_inline_header(writer, values.len())?;
+		writer.write_u8(fixed as u8)?;
+		for value in values {
+			writer.write_f64::<BigEndian>(*value)?;
+		}
+		Ok(())
+	}
+
+	pub fn write_vector_object(
+		&mut self,
+		writer: &mut BytesWriter,
+		fixed: bool,
+		type_name: &str,
+		values: &[Amf3Value],
+	) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::VectorObject as u8)?;
+		Self::write_inline_header(writer, values.len())?;
+		writer.write_u8(fixed as u8)?;
+		self.write_raw_string(writer, type_name)?;
+		for value in values {
+			self.write_any(writer, value)?;
+		}
+		Ok(())
+	}
+
+	pub fn write_dictionary(
+		&mut self,
+		writer: &mut BytesWriter,
+		weak_keys: bool,
+		entries: &[(Amf3Value, Amf3Value)],
+	) -> Result<(), Amf3WriteError> {
+		writer.write_u8(Amf3Marker::Dictionary as u8)?;
+		Self::write_inline_header(writer, entries.len())?;
+		writer.write_u8(weak_keys as u8)?;
+		for (key, value) in entries {
+			self.write_any(writer, key)?;
+			self.write_any(writer, value)?;
+		}
+		Ok(())
+	}
+}
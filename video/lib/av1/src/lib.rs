@@ -2,7 +2,7 @@ mod config;
 mod obu;
 
 pub use config::AV1CodecConfigurationRecord;
-pub use obu::{seq, ObuHeader, ObuType};
+pub use obu::{frame, metadata, seq, ObuHeader, ObuType, Obus};
 
 #[cfg(test)]
 mod tests;
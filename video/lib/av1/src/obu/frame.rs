@@ -0,0 +1,183 @@
+use std::io;
+
+use bytes::Bytes;
+use bytesio::bit_reader::BitReader;
+
+use super::seq::SequenceHeaderObu;
+use super::{ObuHeader, ObuType};
+
+#[derive(Debug, Clone, PartialEq)]
+/// Frame Header OBU
+/// AV1-Spec-2 - 5.9.1
+///
+/// Only the start of the uncompressed header is parsed, the rest depends on
+/// the state of the decoder. This also works for the frame header at the
+/// start of a Frame OBU.
+pub struct FrameHeaderObu {
+	pub header: ObuHeader,
+	pub show_existing_frame: bool,
+	/// Only present if show_existing_frame is set
+	pub frame_to_show_map_idx: Option<u8>,
+	/// The frame type of a shown existing frame is only known to the decoder,
+	/// so this is `None` if show_existing_frame is set.
+	pub frame_type: Option<FrameType>,
+	pub show_frame: bool,
+	pub showable_frame: bool,
+	pub error_resilient_mode: bool,
+	/// Only present if the sequence header has a decoder model without an
+	/// equal picture interval
+	pub frame_presentation_time: Option<u64>,
+	/// Only present for shown existing frames if the sequence header has
+	/// frame ids
+	pub display_frame_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Frame Type
+/// AV1-Spec-2 - 6.8.2
+pub enum FrameType {
+	Key,
+	Inter,
+	IntraOnly,
+	Switch,
+}
+
+impl From<u8> for FrameType {
+	fn from(value: u8) -> Self {
+		match value & 0b11 {
+			0 => FrameType::Key,
+			1 => FrameType::Inter,
+			2 => FrameType::IntraOnly,
+			_ => FrameType::Switch,
+		}
+	}
+}
+
+impl From<FrameType> for u8 {
+	fn from(value: FrameType) -> Self {
+		match value {
+			FrameType::Key => 0,
+			FrameType::Inter => 1,
+			FrameType::IntraOnly => 2,
+			FrameType::Switch => 3,
+		}
+	}
+}
+
+impl FrameHeaderObu {
+	pub fn header(&self) -> &ObuHeader {
+		&self.header
+	}
+
+	/// The sequence header is the one which is active for the frame.
+	pub fn parse(header: ObuHeader, data: Bytes, sequence_header: &SequenceHeaderObu) -> io::Result<Self> {
+		if !matches!(
+			header.obu_type,
+			ObuType::FrameHeader | ObuType::RedundantFrameHeader | ObuType::Frame
+		) {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "obu_type is not a frame header"));
+		}
+
+		if sequence_header.reduced_still_picture_header {
+			return Ok(Self {
+				header,
+				show_existing_frame: false,
+				frame_to_show_map_idx: None,
+				frame_type: Some(FrameType::Key),
+				show_frame: true,
+				showable_frame: false,
+				error_resilient_mode: true,
+				frame_presentation_time: None,
+				display_frame_id: None,
+			});
+		}
+
+		let mut bit_reader = BitReader::from(data);
+
+		// temporal_point_info is only present if there is a decoder model and the
+		// pictures are not equally spaced.
+		let equal_picture_interval = sequence_header
+			.timing_info
+			.as_ref()
+			.is_some_and(|timing_info| timing_info.num_ticks_per_picture.is_some());
+		let frame_presentation_time_length = sequence_header
+			.decoder_model_info
+			.as_ref()
+			.filter(|_| !equal_picture_interval)
+			.map(|decoder_model_info| decoder_model_info.frame_presentation_time_length);
+
+		let show_existing_frame = bit_reader.read_bit()?;
+		if show_existing_frame {
+			let frame_to_show_map_idx = bit_reader.read_bits(3)? as u8;
+
+			let frame_presentation_time = frame_presentation_time_length
+				.map(|length| bit_reader.read_bits(length))
+				.transpose()?;
+
+			let display_frame_id = sequence_header
+				.frame_ids
+				.as_ref()
+				.map(|frame_ids| {
+					bit_reader.read_bits(frame_ids.additional_frame_id_length + frame_ids.delta_frame_id_length)
+				})
+				.transpose()?;
+
+			return Ok(Self {
+				header,
+				show_existing_frame,
+				frame_to_show_map_idx: Some(frame_to_show_map_idx),
+				frame_type: None,
+				show_frame: true,
+				showable_frame: false,
+				error_resilient_mode: false,
+				frame_presentation_time,
+				display_frame_id,
+			});
+		}
+
+		let frame_type = FrameType::from(bit_reader.read_bits(2)? as u8);
+		let show_frame = bit_reader.read_bit()?;
+
+		let frame_presentation_time = if show_frame {
+			frame_presentation_time_length
+				.map(|length| bit_reader.read_bits(length))
+				.transpose()?
+		} else {
+			None
+		};
+
+		let showable_frame = if show_frame {
+			frame_type != FrameType::Key
+		} else {
+			bit_reader.read_bit()?
+		};
+
+		let error_resilient_mode = if frame_type == FrameType::Switch || (frame_type == FrameType::Key && show_frame) {
+			true
+		} else {
+			bit_reader.read_bit()?
+		};
+
+		Ok(Self {
+			header,
+			show_existing_frame,
+			frame_to_show_map_idx: None,
+			frame_type: Some(frame_type),
+			show_frame,
+			showable_frame,
+			error_resilient_mode,
+			frame_presentation_time,
+			display_frame_id: None,
+		})
+	}
+
+	/// A shown key frame, decoding can start at this frame.
+	pub fn is_keyframe(&self) -> bool {
+		self.frame_type == Some(FrameType::Key) && self.show_frame
+	}
+
+	/// Intra frames do not reference any other frames.
+	pub fn is_intra(&self) -> bool {
+		matches!(self.frame_type, Some(FrameType::Key | FrameType::IntraOnly))
+	}
+}
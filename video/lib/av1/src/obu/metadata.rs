@@ -0,0 +1,166 @@
+use std::io;
+
+use byteorder::{BigEndian, ReadBytesExt};
+use bytes::{Buf, Bytes};
+use bytesio::bit_reader::BitReader;
+
+use super::{read_leb128, ObuHeader, ObuType};
+
+#[derive(Debug, Clone, PartialEq)]
+/// Metadata OBU
+/// AV1-Spec-2 - 5.8.1
+pub struct MetadataObu {
+	pub header: ObuHeader,
+	pub metadata: Metadata,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// AV1-Spec-2 - 6.7.1
+pub enum Metadata {
+	HdrCll(HdrCll),
+	HdrMdcv(HdrMdcv),
+	ItutT35(ItutT35),
+	/// Scalability, timecode and unregistered metadata
+	Other {
+		metadata_type: u64,
+		data: Bytes,
+	},
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// High dynamic range content light level metadata
+/// AV1-Spec-2 - 5.8.3
+///
+/// Both values are in candelas per square metre.
+pub struct HdrCll {
+	pub max_cll: u16,
+	pub max_fall: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// High dynamic range mastering display colour volume metadata
+/// AV1-Spec-2 - 5.8.4
+pub struct HdrMdcv {
+	/// The (x, y) coordinates of the red, green and blue primaries as 0.16
+	/// fixed point numbers.
+	pub primary_chromaticity: [(u16, u16); 3],
+	/// The (x, y) coordinates of the white point as 0.16 fixed point numbers.
+	pub white_point_chromaticity: (u16, u16),
+	/// 24.8 fixed point number in candelas per square metre
+	pub luminance_max: u32,
+	/// 18.14 fixed point number in candelas per square metre
+	pub luminance_min: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// ITU-T T.35 metadata, for example HDR10+ dynamic metadata
+/// AV1-Spec-2 - 5.8.2
+pub struct ItutT35 {
+	pub itu_t_t35_country_code: u8,
+	/// Only present if the country code is 0xFF
+	pub itu_t_t35_country_code_extension_byte: Option<u8>,
+	/// The payload without the trailing bits
+	pub itu_t_t35_payload_bytes: Bytes,
+}
+
+impl MetadataObu {
+	pub fn header(&self) -> &ObuHeader {
+		&self.header
+	}
+
+	pub fn parse(header: ObuHeader, data: Bytes) -> io::Result<Self> {
+		if header.obu_type != ObuType::Metadata {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "obu_type is not metadata"));
+		}
+
+		let mut bit_reader = BitReader::from(data);
+		let metadata_type = read_leb128(&mut bit_reader)?;
+		let mut reader = bit_reader.into_inner();
+
+		let metadata = match metadata_type {
+			// METADATA_TYPE_HDR_CLL
+			1 => Metadata::HdrCll(HdrCll {
+				max_cll: reader.read_u16::<BigEndian>()?,
+				max_fall: reader.read_u16::<BigEndian>()?,
+			}),
+			// METADATA_TYPE_HDR_MDCV
+			2 => {
+				let mut primary_chromaticity = [(0, 0); 3];
+				for primary in primary_chromaticity.iter_mut() {
+					*primary = (reader.read_u16::<BigEndian>()?, reader.read_u16::<BigEndian>()?);
+				}
+
+				Metadata::HdrMdcv(HdrMdcv {
+					primary_chromaticity,
+					white_point_chromaticity: (reader.read_u16::<BigEndian>()?, reader.read_u16::<BigEndian>()?),
+					luminance_max: reader.read_u32::<BigEndian>()?,
+					luminance_min: reader.read_u32::<BigEndian>()?,
+				})
+			}
+			// METADATA_TYPE_ITUT_T35
+			4 => {
+				let itu_t_t35_country_code = reader.read_u8()?;
+				let itu_t_t35_country_code_extension_byte = if itu_t_t35_country_code == 0xFF {
+					Some(reader.read_u8()?)
+				} else {
+					None
+				};
+
+				// The payload is a whole number of bytes, so the trailing bits are
+				// the last non zero byte which has to be 0x80.
+				let payload = reader.copy_to_bytes(reader.remaining());
+				let end = payload.iter().rposition(|byte| *byte != 0);
+				let Some(end) = end.filter(|end| payload[*end] == 0x80) else {
+					return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid trailing bits"));
+				};
+
+				Metadata::ItutT35(ItutT35 {
+					itu_t_t35_country_code,
+					itu_t_t35_country_code_extension_byte,
+					itu_t_t35_payload_bytes: payload.slice(..end),
+				})
+			}
+			_ => Metadata::Other {
+				metadata_type,
+				data: reader.copy_to_bytes(reader.remaining()),
+			},
+		};
+
+		Ok(Self { header, metadata })
+	}
+}
+
+impl HdrMdcv {
+	/// The primaries in the order and units of the HEVC mastering display
+	/// colour volume SEI message (green, blue, red in units of 0.00002) as
+	/// used by the `mdcv` box.
+	pub fn display_primaries(&self) -> [(u16, u16); 3] {
+		let [red, green, blue] = self.primary_chromaticity;
+		[green, blue, red].map(|(x, y)| (chromaticity(x), chromaticity(y)))
+	}
+
+	/// The white point in units of 0.00002 as used by the `mdcv` box.
+	pub fn white_point(&self) -> (u16, u16) {
+		(
+			chromaticity(self.white_point_chromaticity.0),
+			chromaticity(self.white_point_chromaticity.1),
+		)
+	}
+
+	/// The max luminance in units of 0.0001 candelas per square metre as
+	/// used by the `mdcv` box.
+	pub fn max_display_mastering_luminance(&self) -> u32 {
+		(self.luminance_max as u64 * 10_000 / (1 << 8)) as u32
+	}
+
+	/// The min luminance in units of 0.0001 candelas per square metre as
+	/// used by the `mdcv` box.
+	pub fn min_display_mastering_luminance(&self) -> u32 {
+		(self.luminance_min as u64 * 10_000 / (1 << 14)) as u32
+	}
+}
+
+/// Converts a 0.16 fixed point chromaticity coordinate to units of 0.00002.
+fn chromaticity(value: u16) -> u16 {
+	((value as u32 * 50_000 + (1 << 15)) >> 16) as u16
+}
@@ -4,7 +4,10 @@ use std::io::{
 
 use bytes::Bytes;
 use bytesio::bit_reader::BitReader;
+use bytesio::bit_writer::BitWriter;
 
+pub mod frame;
+pub mod metadata;
 pub mod seq;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -73,6 +76,29 @@ impl ObuHeader {
 			Bytes::from(data),
 		))
 	}
+
+	/// Writes the header followed by the payload, the obu_size field is
+	/// always written.
+	pub fn mux<T: io::Write>(&self, payload: &[u8], writer: &mut T) -> io::Result<()> {
+		let mut bit_writer = BitWriter::default();
+
+		bit_writer.write_bit(false)?; // obu_forbidden_bit
+		bit_writer.write_bits(u8::from(self.obu_type.clone()) as u64, 4)?;
+		bit_writer.write_bit(self.extension_header.is_some())?; // obu_extension_flag
+		bit_writer.write_bit(true)?; // obu_has_size_field
+		bit_writer.write_bit(false)?; // obu_reserved_1bit
+
+		if let Some(extension_header) = &self.extension_header {
+			bit_writer.write_bits(extension_header.temporal_id as u64, 3)?;
+			bit_writer.write_bits(extension_header.spatial_id as u64, 2)?;
+			bit_writer.write_bits(0, 3)?; // extension_header_reserved_3bits
+		}
+
+		write_leb128(&mut bit_writer, payload.len() as u64)?; // obu_size
+
+		writer.write_all(&bit_writer.into_inner())?;
+		writer.write_all(payload)
+	}
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -125,6 +151,40 @@ impl From<ObuType> for u8 {
 	}
 }
 
+/// An iterator over the OBUs of a temporal unit or a whole stream in the low
+/// overhead bitstream format, like in MP4 and FLV. Every OBU has to have an
+/// obu_size field.
+/// AV1-Spec-2 - 5.2
+pub struct Obus {
+	bit_reader: BitReader,
+}
+
+impl Obus {
+	pub fn new(data: Bytes) -> Self {
+		Self {
+			bit_reader: BitReader::from(data),
+		}
+	}
+}
+
+impl Iterator for Obus {
+	type Item = io::Result<(ObuHeader, Bytes)>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.bit_reader.is_empty() {
+			return None;
+		}
+
+		let result = ObuHeader::parse(&mut self.bit_reader);
+		if result.is_err() {
+			// We can not find the next OBU after an error
+			self.bit_reader = BitReader::from(Bytes::new());
+		}
+
+		Some(result)
+	}
+}
+
 /// Read a little-endian variable-length integer.
 /// AV1-Spec-2 - 4.10.5
 fn read_leb128<T: io::Read>(reader: &mut BitReader<T>) -> io::Result<u64> {
@@ -139,6 +199,20 @@ fn read_leb128<T: io::Read>(reader: &mut BitReader<T>) -> io::Result<u64> {
 	Ok(result)
 }
 
+/// Write a little-endian variable-length integer with the fewest bytes.
+/// AV1-Spec-2 - 4.10.5
+fn write_leb128(writer: &mut BitWriter, mut value: u64) -> io::Result<()> {
+	loop {
+		let byte = value & 0x7f;
+		value >>= 7;
+		if value == 0 {
+			return writer.write_bits(byte, 8);
+		}
+
+		writer.write_bits(byte | 0x80, 8)?;
+	}
+}
+
 /// Read a variable-length unsigned integer.
 /// AV1-Spec-2 - 4.10.3
 fn read_uvlc<T: io::Read>(reader: &mut BitReader<T>) -> io::Result<u64> {
@@ -154,3 +228,14 @@ fn read_uvlc<T: io::Read>(reader: &mut BitReader<T>) -> io::Result<u64> {
 	let value = reader.read_bits(leading_zeros)?;
 	Ok(value + (1 << leading_zeros) - 1)
 }
+
+/// Write a variable-length unsigned integer.
+/// AV1-Spec-2 - 4.10.3
+fn write_uvlc(writer: &mut BitWriter, value: u64) -> io::Result<()> {
+	let value = value + 1;
+	let leading_zeros = 63 - value.leading_zeros() as usize;
+
+	writer.write_bits(0, leading_zeros)?;
+	writer.write_bit(true)?;
+	writer.write_bits(value - (1 << leading_zeros), leading_zeros)
+}
@@ -1,11 +1,12 @@
 use std::io;
 
-use byteorder::{BigEndian, ReadBytesExt};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use bytes::Bytes;
 use bytesio::bit_reader::BitReader;
+use bytesio::bit_writer::BitWriter;
 
 use super::ObuHeader;
-use crate::obu::read_uvlc;
+use crate::obu::{read_uvlc, write_uvlc};
 
 #[derive(Debug, Clone, PartialEq)]
 /// Sequence Header OBU
@@ -18,6 +19,10 @@ pub struct SequenceHeaderObu {
 	pub timing_info: Option<TimingInfo>,
 	pub decoder_model_info: Option<DecoderModelInfo>,
 	pub operating_points: Vec<OperatingPoint>,
+	/// The number of bits used for max_frame_width_minus_1
+	pub frame_width_bits: u8,
+	/// The number of bits used for max_frame_height_minus_1
+	pub frame_height_bits: u8,
 	pub max_frame_width: u64,
 	pub max_frame_height: u64,
 	pub frame_ids: Option<FrameIds>,
@@ -37,6 +42,8 @@ pub struct SequenceHeaderObu {
 	pub enable_superres: bool,
 	pub enable_cdef: bool,
 	pub enable_restoration: bool,
+	/// The colour description is only written if one of the values is not 2
+	/// (unspecified).
 	pub color_config: ColorConfig,
 	pub film_grain_params_present: bool,
 }
@@ -401,6 +408,8 @@ impl SequenceHeaderObu {
 			reduced_still_picture_header,
 			operating_points,
 			decoder_model_info,
+			frame_width_bits,
+			frame_height_bits,
 			max_frame_width,
 			max_frame_height,
 			frame_ids,
@@ -425,4 +434,205 @@ impl SequenceHeaderObu {
 			film_grain_params_present,
 		})
 	}
+
+	/// Writes the whole OBU, including the OBU header and the trailing bits.
+	///
+	/// The initial_display_delay_present_flag is only set if one of the
+	/// operating points has an initial display delay.
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		let mut bit_writer = BitWriter::default();
+
+		bit_writer.write_bits(self.seq_profile as u64, 3)?;
+		bit_writer.write_bit(self.still_picture)?;
+		bit_writer.write_bit(self.reduced_still_picture_header)?;
+
+		if self.reduced_still_picture_header {
+			let [operating_point] = self.operating_points.as_slice() else {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidInput,
+					"reduced still picture header must have exactly one operating point",
+				));
+			};
+
+			bit_writer.write_bits(operating_point.seq_level_idx as u64, 5)?;
+		} else {
+			bit_writer.write_bit(self.timing_info.is_some())?; // timing_info_present_flag
+			if let Some(timing_info) = &self.timing_info {
+				bit_writer.write_u32::<BigEndian>(timing_info.num_units_in_display_tick)?;
+				bit_writer.write_u32::<BigEndian>(timing_info.time_scale)?;
+				bit_writer.write_bit(timing_info.num_ticks_per_picture.is_some())?; // equal_picture_interval
+				if let Some(num_ticks_per_picture) = timing_info.num_ticks_per_picture {
+					write_uvlc(&mut bit_writer, num_ticks_per_picture.saturating_sub(1))?;
+				}
+
+				bit_writer.write_bit(self.decoder_model_info.is_some())?; // decoder_model_info_present_flag
+				if let Some(decoder_model_info) = &self.decoder_model_info {
+					bit_writer.write_bits(decoder_model_info.buffer_delay_length.saturating_sub(1) as u64, 5)?;
+					bit_writer.write_u32::<BigEndian>(decoder_model_info.num_units_in_decoding_tick)?;
+					bit_writer.write_bits(decoder_model_info.buffer_removal_time_length.saturating_sub(1) as u64, 5)?;
+					bit_writer.write_bits(decoder_model_info.frame_presentation_time_length.saturating_sub(1) as u64, 5)?;
+				}
+			} else if self.decoder_model_info.is_some() {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidInput,
+					"decoder model info requires timing info",
+				));
+			}
+
+			if self.operating_points.is_empty() || self.operating_points.len() > 32 {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidInput,
+					"there must be between 1 and 32 operating points",
+				));
+			}
+
+			let initial_display_delay_present_flag = self
+				.operating_points
+				.iter()
+				.any(|operating_point| operating_point.initial_display_delay.is_some());
+
+			bit_writer.write_bit(initial_display_delay_present_flag)?;
+			bit_writer.write_bits(self.operating_points.len() as u64 - 1, 5)?; // operating_points_cnt_minus_1
+			for operating_point in &self.operating_points {
+				bit_writer.write_bits(operating_point.idc as u64, 12)?;
+				bit_writer.write_bits(operating_point.seq_level_idx as u64, 5)?;
+				if operating_point.seq_level_idx > 7 {
+					bit_writer.write_bit(operating_point.seq_tier)?;
+				}
+
+				if let Some(decoder_model_info) = &self.decoder_model_info {
+					// decoder_model_present_for_this_op
+					bit_writer.write_bit(operating_point.operating_parameters_info.is_some())?;
+					if let Some(operating_parameters_info) = &operating_point.operating_parameters_info {
+						let length = decoder_model_info.buffer_delay_length as usize;
+						bit_writer.write_bits(operating_parameters_info.decoder_buffer_delay, length)?;
+						bit_writer.write_bits(operating_parameters_info.encoder_buffer_delay, length)?;
+						bit_writer.write_bit(operating_parameters_info.low_delay_mode_flag)?;
+					}
+				}
+
+				if initial_display_delay_present_flag {
+					// initial_display_delay_present_for_this_op
+					bit_writer.write_bit(operating_point.initial_display_delay.is_some())?;
+					if let Some(initial_display_delay) = operating_point.initial_display_delay {
+						bit_writer.write_bits(initial_display_delay.saturating_sub(1) as u64, 4)?;
+					}
+				}
+			}
+		}
+
+		bit_writer.write_bits(self.frame_width_bits.saturating_sub(1) as u64, 4)?;
+		bit_writer.write_bits(self.frame_height_bits.saturating_sub(1) as u64, 4)?;
+		bit_writer.write_bits(self.max_frame_width.saturating_sub(1), self.frame_width_bits as usize)?;
+		bit_writer.write_bits(self.max_frame_height.saturating_sub(1), self.frame_height_bits as usize)?;
+
+		if !self.reduced_still_picture_header {
+			bit_writer.write_bit(self.frame_ids.is_some())?; // frame_id_numbers_present_flag
+			if let Some(frame_ids) = &self.frame_ids {
+				bit_writer.write_bits(frame_ids.delta_frame_id_length.saturating_sub(2) as u64, 4)?;
+				bit_writer.write_bits(frame_ids.additional_frame_id_length.saturating_sub(1) as u64, 3)?;
+			}
+		}
+
+		bit_writer.write_bit(self.use_128x128_superblock)?;
+		bit_writer.write_bit(self.enable_filter_intra)?;
+		bit_writer.write_bit(self.enable_intra_edge_filter)?;
+
+		if !self.reduced_still_picture_header {
+			bit_writer.write_bit(self.enable_interintra_compound)?;
+			bit_writer.write_bit(self.enable_masked_compound)?;
+			bit_writer.write_bit(self.enable_warped_motion)?;
+			bit_writer.write_bit(self.enable_dual_filter)?;
+			bit_writer.write_bit(self.enable_order_hint)?;
+			if self.enable_order_hint {
+				bit_writer.write_bit(self.enable_jnt_comp)?;
+				bit_writer.write_bit(self.enable_ref_frame_mvs)?;
+			}
+
+			// seq_choose_screen_content_tools
+			bit_writer.write_bit(self.seq_force_screen_content_tools == 2)?;
+			if self.seq_force_screen_content_tools != 2 {
+				bit_writer.write_bits(self.seq_force_screen_content_tools as u64, 1)?;
+			}
+
+			if self.seq_force_screen_content_tools > 0 {
+				// seq_choose_integer_mv
+				bit_writer.write_bit(self.seq_force_integer_mv == 2)?;
+				if self.seq_force_integer_mv != 2 {
+					bit_writer.write_bits(self.seq_force_integer_mv as u64, 1)?;
+				}
+			}
+
+			if self.enable_order_hint {
+				bit_writer.write_bits(self.order_hint_bits.saturating_sub(1) as u64, 3)?;
+			}
+		}
+
+		bit_writer.write_bit(self.enable_superres)?;
+		bit_writer.write_bit(self.enable_cdef)?;
+		bit_writer.write_bit(self.enable_restoration)?;
+
+		self.color_config.mux(&mut bit_writer, self.seq_profile)?;
+
+		bit_writer.write_bit(self.film_grain_params_present)?;
+
+		// trailing_bits
+		bit_writer.write_bit(true)?;
+		bit_writer.align()?;
+
+		self.header.mux(&bit_writer.into_inner(), writer)
+	}
+
+	/// Writes the whole OBU to a new buffer.
+	pub fn build(&self) -> io::Result<Bytes> {
+		let mut data = Vec::new();
+		self.mux(&mut data)?;
+		Ok(Bytes::from(data))
+	}
+}
+
+impl ColorConfig {
+	/// AV1-Spec-2 - 5.5.2
+	pub fn mux(&self, bit_writer: &mut BitWriter, seq_profile: u8) -> io::Result<()> {
+		let high_bitdepth = self.bit_depth > 8;
+		bit_writer.write_bit(high_bitdepth)?;
+		if seq_profile == 2 && high_bitdepth {
+			bit_writer.write_bit(self.bit_depth == 12)?; // twelve_bit
+		}
+
+		if seq_profile != 1 {
+			bit_writer.write_bit(self.mono_chrome)?;
+		}
+
+		let color_description_present_flag =
+			self.color_primaries != 2 || self.transfer_characteristics != 2 || self.matrix_coefficients != 2;
+		bit_writer.write_bit(color_description_present_flag)?;
+		if color_description_present_flag {
+			bit_writer.write_u8(self.color_primaries)?;
+			bit_writer.write_u8(self.transfer_characteristics)?;
+			bit_writer.write_u8(self.matrix_coefficients)?;
+		}
+
+		if self.mono_chrome {
+			return bit_writer.write_bit(self.full_color_range);
+		}
+
+		// sRGB is always full range 4:4:4
+		let srgb = self.color_primaries == 1 && self.transfer_characteristics == 13 && self.matrix_coefficients == 0;
+		if !srgb {
+			bit_writer.write_bit(self.full_color_range)?;
+			if seq_profile == 2 && self.bit_depth == 12 {
+				bit_writer.write_bit(self.subsampling_x)?;
+				if self.subsampling_x {
+					bit_writer.write_bit(self.subsampling_y)?;
+				}
+			}
+		}
+
+		if self.subsampling_x && self.subsampling_y {
+			bit_writer.write_bits(self.chroma_sample_position as u64, 2)?;
+		}
+
+		bit_writer.write_bit(self.separate_uv_delta_q)
+	}
 }
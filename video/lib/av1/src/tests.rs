@@ -1,10 +1,13 @@
 use std::io;
 
+use bytes::Bytes;
 use bytesio::bit_reader::BitReader;
 
 use crate::config::AV1CodecConfigurationRecord;
-use crate::seq::{ColorConfig, OperatingPoint, SequenceHeaderObu};
-use crate::{ObuHeader, ObuType};
+use crate::frame::{FrameHeaderObu, FrameType};
+use crate::metadata::{HdrCll, HdrMdcv, Metadata, MetadataObu};
+use crate::seq::{ColorConfig, OperatingPoint, SequenceHeaderObu, TimingInfo};
+use crate::{ObuHeader, ObuType, Obus};
 
 #[test]
 fn test_config_demux() {
@@ -51,6 +54,8 @@ fn test_config_demux() {
 				operating_parameters_info: None,
 				initial_display_delay: None,
 			}],
+			frame_width_bits: 12,
+			frame_height_bits: 12,
 			max_frame_width: 3840,
 			max_frame_height: 2160,
 			frame_ids: None,
@@ -101,3 +106,134 @@ fn test_config_mux() {
 
 	assert_eq!(buf, data);
 }
+
+#[test]
+fn test_sequence_header_build() {
+	let data = b"\x81\r\x0c\0\n\x0f\0\0\0j\xef\xbf\xe1\xbc\x02\x19\x90\x10\x10\x10@".to_vec();
+
+	let config = AV1CodecConfigurationRecord::demux(&mut io::Cursor::new(data.into())).unwrap();
+	let (header, data) = ObuHeader::parse(&mut BitReader::from(config.config_obu.clone())).unwrap();
+	let mut obu = SequenceHeaderObu::parse(header, data).unwrap();
+
+	assert_eq!(obu.build().unwrap(), config.config_obu);
+
+	// Fix the colour description and add timing info
+	obu.color_config.color_primaries = 9;
+	obu.color_config.transfer_characteristics = 16;
+	obu.color_config.matrix_coefficients = 9;
+	obu.timing_info = Some(TimingInfo {
+		num_units_in_display_tick: 1001,
+		time_scale: 60000,
+		num_ticks_per_picture: Some(1),
+	});
+
+	let (header, data) = ObuHeader::parse(&mut BitReader::from(obu.build().unwrap())).unwrap();
+	assert_eq!(SequenceHeaderObu::parse(header, data).unwrap(), obu);
+
+	// The sequence header from av1_aac.flv
+	let data = Bytes::from(b"\x0a\x0f\0\0\0\x6a\xea\x7f\xec\xf8\x04\x33\x20\x20\x20\x20\x80".to_vec());
+	let (header, payload) = ObuHeader::parse(&mut BitReader::from(data.clone())).unwrap();
+	assert_eq!(SequenceHeaderObu::parse(header, payload).unwrap().build().unwrap(), data);
+}
+
+#[test]
+fn test_obus_frame_header() {
+	// A temporal delimiter, the sequence header and the start of a key frame
+	// from av1_aac.flv, the frame OBU is cut off after 4 bytes.
+	let data = Bytes::from(
+		b"\x12\0\x0a\x0f\0\0\0\x6a\xea\x7f\xec\xf8\x04\x33\x20\x20\x20\x20\x80\x32\x04\x10\x01\x2d\x81".to_vec(),
+	);
+
+	let obus = Obus::new(data).collect::<io::Result<Vec<_>>>().unwrap();
+	assert_eq!(obus.len(), 3);
+	assert_eq!(obus[0].0.obu_type, ObuType::TemporalDelimiter);
+	assert!(obus[0].1.is_empty());
+	assert_eq!(obus[1].0.obu_type, ObuType::SequenceHeader);
+	assert_eq!(obus[2].0.obu_type, ObuType::Frame);
+
+	let seq_obu = SequenceHeaderObu::parse(obus[1].0.clone(), obus[1].1.clone()).unwrap();
+	assert_eq!(seq_obu.max_frame_width, 2560);
+
+	let frame_header = FrameHeaderObu::parse(obus[2].0.clone(), obus[2].1.clone(), &seq_obu).unwrap();
+	assert!(!frame_header.show_existing_frame);
+	assert_eq!(frame_header.frame_type, Some(FrameType::Key));
+	assert!(frame_header.show_frame);
+	assert!(!frame_header.showable_frame);
+	assert!(frame_header.error_resilient_mode);
+	assert!(frame_header.is_keyframe());
+	assert!(frame_header.is_intra());
+
+	// A hidden inter frame (alt-ref) and a shown existing frame
+	let data = Bytes::from(b"\x32\x04\x28\x07\xe0\x05\x1a\x01\xf8".to_vec());
+	let obus = Obus::new(data).collect::<io::Result<Vec<_>>>().unwrap();
+	assert_eq!(obus.len(), 2);
+
+	let frame_header = FrameHeaderObu::parse(obus[0].0.clone(), obus[0].1.clone(), &seq_obu).unwrap();
+	assert_eq!(frame_header.frame_type, Some(FrameType::Inter));
+	assert!(!frame_header.show_frame);
+	assert!(frame_header.showable_frame);
+	assert!(!frame_header.error_resilient_mode);
+	assert!(!frame_header.is_keyframe());
+
+	assert_eq!(obus[1].0.obu_type, ObuType::FrameHeader);
+	let frame_header = FrameHeaderObu::parse(obus[1].0.clone(), obus[1].1.clone(), &seq_obu).unwrap();
+	assert!(frame_header.show_existing_frame);
+	assert_eq!(frame_header.frame_to_show_map_idx, Some(7));
+	assert_eq!(frame_header.frame_type, None);
+	assert!(!frame_header.is_keyframe());
+
+	// A truncated OBU is an error and ends the iterator
+	let mut obus = Obus::new(Bytes::from(b"\x12\0\x32\x04\x10".to_vec()));
+	assert!(obus.next().unwrap().is_ok());
+	assert!(obus.next().unwrap().is_err());
+	assert!(obus.next().is_none());
+}
+
+#[test]
+fn test_metadata_obu() {
+	let data = Bytes::from(
+		b"\x2a\x06\x01\x03\xe8\x01\x90\x80\
+		\x2a\x1a\x02\xb5\x3f\x4a\xc1\x2b\x85\xcc\x08\x21\x89\x0b\xc7\x50\x0d\x54\x39\x00\x03\xe8\x00\x00\x00\x00\x52\x80\
+		\x2a\x09\x04\xb5\x00\x3c\x00\x01\x04\x01\x80"
+			.to_vec(),
+	);
+
+	let obus = Obus::new(data)
+		.map(|obu| obu.and_then(|(header, data)| MetadataObu::parse(header, data)))
+		.collect::<io::Result<Vec<_>>>()
+		.unwrap();
+	assert_eq!(obus.len(), 3);
+
+	assert_eq!(
+		obus[0].metadata,
+		Metadata::HdrCll(HdrCll {
+			max_cll: 1000,
+			max_fall: 400,
+		})
+	);
+
+	let Metadata::HdrMdcv(mdcv) = &obus[1].metadata else {
+		panic!("expected mdcv metadata");
+	};
+	assert_eq!(
+		mdcv,
+		&HdrMdcv {
+			primary_chromaticity: [(46399, 19137), (11141, 52232), (8585, 3015)],
+			white_point_chromaticity: (20493, 21561),
+			luminance_max: 1000 << 8,
+			luminance_min: 82,
+		}
+	);
+	// BT.2020 primaries and D65 in the units of the mdcv box
+	assert_eq!(mdcv.display_primaries(), [(8500, 39850), (6550, 2300), (35400, 14600)]);
+	assert_eq!(mdcv.white_point(), (15635, 16450));
+	assert_eq!(mdcv.max_display_mastering_luminance(), 10_000_000);
+	assert_eq!(mdcv.min_display_mastering_luminance(), 50);
+
+	let Metadata::ItutT35(t35) = &obus[2].metadata else {
+		panic!("expected itu-t t.35 metadata");
+	};
+	assert_eq!(t35.itu_t_t35_country_code, 0xB5);
+	assert_eq!(t35.itu_t_t35_country_code_extension_byte, None);
+	assert_eq!(t35.itu_t_t35_payload_bytes, Bytes::from_static(b"\x00\x3c\x00\x01\x04\x01"));
+}
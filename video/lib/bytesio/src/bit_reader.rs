@@ -5,6 +5,8 @@ use std::io::{
 use byteorder::ReadBytesExt;
 use bytes::{Buf, Bytes};
 
+use crate::emulation_prevention::EmulationPreventionReader;
+
 pub struct BitReader<T: io::Read = io::Cursor<Bytes>> {
 	data: T,
 	bit_pos: usize,
@@ -113,14 +115,41 @@ impl<T: io::Read> BitReader<T> {
 	}
 
 	pub fn align(&mut self) -> io::Result<()> {
-		let amount_to_read = 8 - self.bit_pos;
-		self.read_bits(amount_to_read as u8)?;
+		if !self.is_aligned() {
+			let amount_to_read = 8 - self.bit_pos;
+			self.read_bits(amount_to_read as u8)?;
+		}
+
 		Ok(())
 	}
 
 	pub fn is_aligned(&self) -> bool {
 		self.bit_pos == 0
 	}
+
+	/// Reads the rbsp_stop_one_bit and the zero bits up to the next byte.
+	/// ISO/IEC-14496-10-2022 - 7.3.2.11
+	pub fn read_rbsp_trailing_bits(&mut self) -> io::Result<()> {
+		if !self.read_bit()? {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "rbsp_stop_one_bit is not 1"));
+		}
+
+		while !self.is_aligned() {
+			if self.read_bit()? {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, "rbsp_alignment_zero_bit is not 0"));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl<T: io::Read> BitReader<EmulationPreventionReader<T>> {
+	/// Reads the RBSP of a H.264 or H.265 NAL unit, the emulation prevention
+	/// bytes are skipped while reading.
+	pub fn new_rbsp(data: T) -> Self {
+		Self::new(EmulationPreventionReader::new(data))
+	}
 }
 
 impl<T: io::Read> io::Read for BitReader<T> {
@@ -159,6 +188,21 @@ impl<T: AsRef<[u8]>> BitReader<io::Cursor<T>> {
 			remaining * 8 + 8 - self.bit_pos
 		}
 	}
+
+	/// Whether there is more data before the rbsp_trailing_bits, the data
+	/// has to be the RBSP without emulation prevention bytes.
+	/// ISO/IEC-14496-10-2022 - 7.2
+	pub fn more_rbsp_data(&self) -> bool {
+		let data = self.data.get_ref().as_ref();
+
+		// The rbsp_stop_one_bit is the last bit which is set
+		let Some(last) = data.iter().rposition(|byte| *byte != 0) else {
+			return false;
+		};
+		let stop_bit_pos = last * 8 + 7 - data[last].trailing_zeros() as usize;
+
+		stop_bit_pos > data.len() * 8 - self.remaining_bits()
+	}
 }
 
 impl<T: io::Seek + io::Read> io::Seek for BitReader<T> {
@@ -64,6 +64,13 @@ impl BitWriter {
 		Ok(())
 	}
 
+	/// Writes the rbsp_stop_one_bit and the zero bits up to the next byte.
+	/// ISO/IEC-14496-10-2022 - 7.3.2.11
+	pub fn write_rbsp_trailing_bits(&mut self) -> io::Result<()> {
+		self.write_bit(true)?;
+		self.align()
+	}
+
 	pub fn seek_bits(&mut self, count: i64) {
 		if count < 0 {
 			if self.bit_pos < (-count) as usize {
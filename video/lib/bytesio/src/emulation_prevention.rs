@@ -0,0 +1,166 @@
+use std::io;
+
+use byteorder::ReadBytesExt;
+
+/// Removes the emulation prevention bytes of a H.264 or H.265 NAL unit, every
+/// `0x03` following two zero bytes, which turns the NAL unit into the raw byte
+/// sequence payload (RBSP).
+/// ISO/IEC-14496-10-2022 - 7.4.1, ISO/IEC 23008-2:2020(E) - 7.4.2
+pub fn remove_emulation_prevention(data: &[u8]) -> Vec<u8> {
+	let mut rbsp = Vec::with_capacity(data.len());
+
+	let mut zeros = 0;
+	for &byte in data {
+		if zeros >= 2 && byte == 0x03 {
+			zeros = 0;
+			continue;
+		}
+
+		if byte == 0x00 {
+			zeros += 1;
+		} else {
+			zeros = 0;
+		}
+
+		rbsp.push(byte);
+	}
+
+	rbsp
+}
+
+/// Inserts the emulation prevention bytes, a `0x03` after every two zero
+/// bytes which are followed by a byte smaller than 4, and a final `0x03` if
+/// the RBSP ends with a zero byte.
+/// ISO/IEC-14496-10-2022 - 7.4.1, ISO/IEC 23008-2:2020(E) - 7.4.2
+pub fn add_emulation_prevention(rbsp: &[u8]) -> Vec<u8> {
+	let mut data = Vec::with_capacity(rbsp.len() + rbsp.len() / 64);
+
+	let mut zeros = 0;
+	for &byte in rbsp {
+		if zeros >= 2 && byte <= 0x03 {
+			data.push(0x03);
+			zeros = 0;
+		}
+
+		if byte == 0x00 {
+			zeros += 1;
+		} else {
+			zeros = 0;
+		}
+
+		data.push(byte);
+	}
+
+	if data.last() == Some(&0x00) {
+		data.push(0x03);
+	}
+
+	data
+}
+
+/// A reader which skips the emulation prevention bytes of the inner reader,
+/// so it reads the RBSP of a NAL unit without copying it first.
+///
+/// Positions are in bytes of the RBSP. Seeking backwards starts over from the
+/// position the inner reader had when it was wrapped.
+pub struct EmulationPreventionReader<T: io::Read> {
+	inner: T,
+	start: u64,
+	position: u64,
+	zeros: u8,
+}
+
+impl<T: io::Read> EmulationPreventionReader<T> {
+	pub fn new(inner: T) -> Self {
+		Self {
+			inner,
+			start: 0,
+			position: 0,
+			zeros: 0,
+		}
+	}
+
+	pub fn into_inner(self) -> T {
+		self.inner
+	}
+
+	pub fn get_ref(&self) -> &T {
+		&self.inner
+	}
+
+	fn read_rbsp_byte(&mut self) -> io::Result<u8> {
+		let mut byte = self.inner.read_u8()?;
+		if self.zeros >= 2 && byte == 0x03 {
+			self.zeros = 0;
+			byte = self.inner.read_u8()?;
+		}
+
+		if byte == 0x00 {
+			self.zeros += 1;
+		} else {
+			self.zeros = 0;
+		}
+
+		self.position += 1;
+
+		Ok(byte)
+	}
+}
+
+impl<T: io::Read + io::Seek> EmulationPreventionReader<T> {
+	/// Wraps a reader which is not at its start, seeking back to the start of
+	/// the RBSP returns to the current position of the inner reader.
+	pub fn with_start(mut inner: T) -> io::Result<Self> {
+		let start = inner.stream_position()?;
+		Ok(Self {
+			inner,
+			start,
+			position: 0,
+			zeros: 0,
+		})
+	}
+}
+
+impl<T: io::Read> io::Read for EmulationPreventionReader<T> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		for (read, b) in buf.iter_mut().enumerate() {
+			match self.read_rbsp_byte() {
+				Ok(byte) => *b = byte,
+				Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(read),
+				Err(err) => return Err(err),
+			}
+		}
+
+		Ok(buf.len())
+	}
+}
+
+impl<T: io::Read + io::Seek> io::Seek for EmulationPreventionReader<T> {
+	fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+		let target = match pos {
+			io::SeekFrom::Start(pos) => pos,
+			io::SeekFrom::Current(pos) => self
+				.position
+				.checked_add_signed(pos)
+				.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Cannot seek to a negative position"))?,
+			io::SeekFrom::End(_) => {
+				return Err(io::Error::new(
+					io::ErrorKind::Unsupported,
+					"Cannot seek from the end of an RBSP",
+				));
+			}
+		};
+
+		if target < self.position {
+			self.inner.seek(io::SeekFrom::Start(self.start))?;
+			self.position = 0;
+			self.zeros = 0;
+		}
+
+		while self.position < target {
+			self.read_rbsp_byte()?;
+		}
+
+		Ok(self.position)
+	}
+}
@@ -2,6 +2,7 @@ pub mod bit_reader;
 pub mod bit_writer;
 pub mod bytes_reader;
 pub mod bytes_writer;
+pub mod emulation_prevention;
 
 #[cfg(feature = "tokio")]
 pub mod bytesio;
@@ -89,4 +89,36 @@ fn test_bit_reader_align() {
 
 	assert!(reader.is_aligned());
 	assert_eq!(reader.get_bit_pos(), 0);
+
+	// Aligning an aligned reader does not read anything
+	reader.align().unwrap();
+	assert_eq!(reader.read_u8().unwrap(), 0b01011000);
+}
+
+#[test]
+fn test_bit_reader_rbsp_trailing_bits() {
+	let data = Bytes::from(vec![0b10110100, 0b01000000, 0b00000000]);
+
+	let mut reader = BitReader::from(data.clone());
+
+	assert!(reader.more_rbsp_data());
+	reader.seek_bits(9).unwrap();
+	assert!(!reader.more_rbsp_data());
+	reader.read_rbsp_trailing_bits().unwrap();
+	assert!(reader.is_aligned());
+	assert!(!reader.more_rbsp_data());
+
+	// The stop bit has to be set
+	let mut reader = BitReader::from(data.clone());
+	assert!(reader.read_rbsp_trailing_bits().is_err());
+
+	// The alignment bits have to be zero
+	let mut reader = BitReader::from(data);
+	reader.seek_bits(2).unwrap();
+	assert!(reader.more_rbsp_data());
+	assert!(reader.read_rbsp_trailing_bits().is_err());
+
+	// Without a stop bit there is no more data
+	let reader = BitReader::from(Bytes::from(vec![0, 0]));
+	assert!(!reader.more_rbsp_data());
 }
@@ -122,3 +122,16 @@ fn test_bit_writer_seek() {
 	// 1 bytes + 4 bits
 	assert_eq!(data, &[0b11110110, 0b01000000]);
 }
+
+#[test]
+fn test_bit_writer_rbsp_trailing_bits() {
+	let mut bit_writer = BitWriter::default();
+
+	bit_writer.write_bits(0b101, 3).unwrap();
+	bit_writer.write_rbsp_trailing_bits().unwrap();
+
+	// An aligned writer gets a whole byte
+	bit_writer.write_rbsp_trailing_bits().unwrap();
+
+	assert_eq!(bit_writer.into_inner(), vec![0b10110000, 0b10000000]);
+}
@@ -0,0 +1,62 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::bit_reader::BitReader;
+use crate::emulation_prevention::{add_emulation_prevention, remove_emulation_prevention, EmulationPreventionReader};
+
+#[test]
+fn test_emulation_prevention() {
+	let rbsp = [0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x03, 0x00, 0x00, 0x04, 0x00, 0x00];
+	let data = [
+		0x00, 0x00, 0x03, 0x00, 0x01, 0x00, 0x00, 0x03, 0x03, 0x00, 0x00, 0x04, 0x00, 0x00, 0x03,
+	];
+
+	assert_eq!(add_emulation_prevention(&rbsp), data);
+	assert_eq!(remove_emulation_prevention(&data), rbsp);
+
+	// Data without two zeros in a row is not changed
+	let rbsp = [0x01, 0x00, 0x02, 0x00, 0x03];
+	assert_eq!(add_emulation_prevention(&rbsp), rbsp);
+	assert_eq!(remove_emulation_prevention(&rbsp), rbsp);
+
+	// An RBSP ending with a zero byte gets a final 0x03
+	assert_eq!(add_emulation_prevention(&[0x80, 0x00, 0x00]), [0x80, 0x00, 0x00, 0x03]);
+	assert_eq!(remove_emulation_prevention(&[0x80, 0x00, 0x00, 0x03]), [0x80, 0x00, 0x00]);
+}
+
+#[test]
+fn test_emulation_prevention_reader() {
+	let data = [0x67, 0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x03, 0x00, 0x80];
+
+	let mut reader = EmulationPreventionReader::new(io::Cursor::new(data));
+	let mut rbsp = Vec::new();
+	reader.read_to_end(&mut rbsp).unwrap();
+	assert_eq!(rbsp, remove_emulation_prevention(&data));
+
+	// Seeking is in bytes of the RBSP
+	assert_eq!(reader.seek(SeekFrom::Start(3)).unwrap(), 3);
+	let mut buf = [0; 3];
+	reader.read_exact(&mut buf).unwrap();
+	assert_eq!(buf, [0x01, 0x00, 0x00]);
+
+	assert_eq!(reader.seek(SeekFrom::Current(-4)).unwrap(), 2);
+	assert_eq!(reader.seek(SeekFrom::Current(4)).unwrap(), 6);
+	reader.read_exact(&mut buf[..2]).unwrap();
+	assert_eq!(buf[..2], [0x00, 0x80]);
+}
+
+#[test]
+fn test_bit_reader_rbsp() {
+	let data = [0x00, 0x00, 0x03, 0x01, 0xFF, 0x80];
+
+	let mut reader = BitReader::new_rbsp(io::Cursor::new(data));
+	assert_eq!(reader.read_bits(24).unwrap(), 0x000001);
+
+	reader.seek_bits(4).unwrap();
+	assert_eq!(reader.read_bits(4).unwrap(), 0xF);
+	reader.read_rbsp_trailing_bits().unwrap();
+	assert!(reader.read_bit().is_err());
+
+	// Seeking backwards reads the RBSP again
+	reader.seek_bits(-20).unwrap();
+	assert_eq!(reader.read_bits(8).unwrap(), 0x1F);
+}
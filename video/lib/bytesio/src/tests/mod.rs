@@ -3,4 +3,5 @@ mod bit_writer;
 mod bytes_reader;
 mod bytes_writer;
 mod bytesio;
+mod emulation_prevention;
 mod errors;
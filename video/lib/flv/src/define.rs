@@ -53,6 +53,9 @@ pub enum FlvTagType {
 /// Defined in the FLV specification. Chapter 1 - FLV tags
 pub enum FlvTagData {
 	/// AudioData defined in the FLV specification. Chapter 1 - FLV Audio Tags
+	/// Enhanced audio reuses the sound rate, size and type bits for the
+	/// packet type, so for enhanced audio they are always 44kHz 16-bit
+	/// stereo and the real values are found in the codec configuration.
 	Audio {
 		sound_rate: SoundRate,
 		sound_size: SoundSize,
@@ -76,10 +79,48 @@ pub enum FlvTagAudioData {
 	/// AAC Audio Packet defined in the FLV specification. Chapter 1 -
 	/// AACAUDIODATA
 	Aac(AacPacket),
+	/// MP3 frames, defined in the FLV specification. Chapter 1 - AudioTags
+	/// MP3 has no sequence header, every frame starts with a header which
+	/// describes it.
+	Mp3(Bytes),
+	/// Enhanced Audio Packet defined in the enhanced RTMP v2 specification
+	Enhanced(EnhancedAudioPacket),
+	/// Enhanced RTMP v2 multitrack audio, each track has its own packet
+	Multitrack(Vec<AudioTrack>),
 	/// Data we don't know how to parse
 	Unknown { sound_format: u8, data: Bytes },
 }
 
+#[derive(Debug, Clone, PartialEq)]
+/// Enhanced Audio Packet
+/// The codec configuration and frames are passed on as is, the format
+/// depends on the codec. For example the sequence start of Opus is the
+/// `OpusHead` header and the sequence start of AAC is the
+/// `AudioSpecificConfig`.
+pub enum EnhancedAudioPacket {
+	/// Codec configuration
+	SequenceStart { audio_codec: AudioFourCC, data: Bytes },
+	/// Audio frames
+	CodedFrames { audio_codec: AudioFourCC, data: Bytes },
+	/// Sequence End
+	SequenceEnd { audio_codec: AudioFourCC },
+	/// Channel layout of the audio
+	MultichannelConfig { audio_codec: AudioFourCC, data: Bytes },
+	/// We don't know how to parse it
+	Unknown {
+		packet_type: u8,
+		audio_codec: AudioFourCC,
+		data: Bytes,
+	},
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A single track of an enhanced RTMP v2 multitrack audio tag
+pub struct AudioTrack {
+	pub track_id: u8,
+	pub packet: EnhancedAudioPacket,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 /// AAC Packet
 /// This is a container for aac data.
@@ -105,16 +146,27 @@ pub enum FlvTagVideoData {
 	Avc(AvcPacket),
 	/// Enhanced Packet
 	Enhanced(EnhancedPacket),
+	/// Enhanced RTMP v2 multitrack video, each track has its own packet
+	Multitrack(Vec<VideoTrack>),
 	/// Data we don't know how to parse
 	Unknown { codec_id: u8, data: Bytes },
 }
 
+#[derive(Debug, Clone, PartialEq)]
+/// A single track of an enhanced RTMP v2 multitrack video tag
+pub struct VideoTrack {
+	pub track_id: u8,
+	pub packet: EnhancedPacket,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum EnhancedPacket {
 	/// Metadata
-	Metadata(Bytes),
+	Metadata { video_codec: [u8; 4], data: Bytes },
 	/// Sequence End
-	SequenceEnd,
+	SequenceEnd { video_codec: [u8; 4] },
+	/// Avc (H.264) Video Packet
+	Avc(AvcPacket),
 	/// Av1 Video Packet
 	Av1(Av1Packet),
 	/// Hevc (H.265) Video Packet
@@ -169,6 +221,33 @@ pub(crate) enum EnhancedPacketType {
 	CodedFramesX = 0x03,
 	Metadata = 0x04,
 	Mpeg2SequenceStart = 0x05,
+	Multitrack = 0x06,
+	ModEx = 0x07,
+}
+
+#[derive(Debug, Clone, Copy, FromPrimitive, PartialEq, Eq)]
+#[repr(u8)]
+/// Enhanced RTMP v2 audio packet types, these take the place of the sound
+/// rate, size and type bits.
+pub(crate) enum EnhancedAudioPacketType {
+	SequenceStart = 0x00,
+	CodedFrames = 0x01,
+	SequenceEnd = 0x02,
+	MultichannelConfig = 0x04,
+	Multitrack = 0x05,
+	ModEx = 0x07,
+}
+
+#[derive(Debug, Clone, Copy, FromPrimitive, PartialEq, Eq)]
+#[repr(u8)]
+/// Enhanced RTMP v2 multitrack types
+pub(crate) enum MultitrackType {
+	/// A single track with a track id
+	OneTrack = 0x00,
+	/// Several tracks with the same codec
+	ManyTracks = 0x01,
+	/// Several tracks, each with its own codec
+	ManyTracksManyCodecs = 0x02,
 }
 
 #[derive(Debug, Clone, Copy, FromPrimitive, PartialEq, Eq)]
@@ -186,7 +265,8 @@ pub(crate) enum SoundCodecId {
 	Nellymoser = 0x6,
 	G711ALaw = 0x7,
 	G711MuLaw = 0x8,
-	Reserved = 0x9,
+	/// Enhanced RTMP v2 audio, the codec is a FourCC after the packet type
+	ExHeader = 0x9,
 	Aac = 0xA,
 	Speex = 0xB,
 	Mp38Khz = 0xE,
@@ -252,7 +332,9 @@ pub(crate) enum VideoCodecId {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum VideoFourCC {
 	Av1,
+	Vp8,
 	Vp9,
+	Avc,
 	Hevc,
 	Unknown([u8; 4]),
 }
@@ -261,7 +343,9 @@ impl From<[u8; 4]> for VideoFourCC {
 	fn from(fourcc: [u8; 4]) -> Self {
 		match &fourcc {
 			b"av01" => VideoFourCC::Av1,
+			b"vp08" => VideoFourCC::Vp8,
 			b"vp09" => VideoFourCC::Vp9,
+			b"avc1" => VideoFourCC::Avc,
 			b"hvc1" => VideoFourCC::Hevc,
 			_ => VideoFourCC::Unknown(fourcc),
 		}
@@ -272,13 +356,55 @@ impl From<VideoFourCC> for [u8; 4] {
 	fn from(fourcc: VideoFourCC) -> Self {
 		match fourcc {
 			VideoFourCC::Av1 => *b"av01",
+			VideoFourCC::Vp8 => *b"vp08",
 			VideoFourCC::Vp9 => *b"vp09",
+			VideoFourCC::Avc => *b"avc1",
 			VideoFourCC::Hevc => *b"hvc1",
 			VideoFourCC::Unknown(fourcc) => fourcc,
 		}
 	}
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Enhanced RTMP v2 audio codecs
+pub enum AudioFourCC {
+	Ac3,
+	Eac3,
+	Opus,
+	Mp3,
+	Flac,
+	Aac,
+	Unknown([u8; 4]),
+}
+
+impl From<[u8; 4]> for AudioFourCC {
+	fn from(fourcc: [u8; 4]) -> Self {
+		match &fourcc {
+			b"ac-3" => AudioFourCC::Ac3,
+			b"ec-3" => AudioFourCC::Eac3,
+			b"Opus" => AudioFourCC::Opus,
+			b".mp3" => AudioFourCC::Mp3,
+			b"fLaC" => AudioFourCC::Flac,
+			b"mp4a" => AudioFourCC::Aac,
+			_ => AudioFourCC::Unknown(fourcc),
+		}
+	}
+}
+
+impl From<AudioFourCC> for [u8; 4] {
+	fn from(fourcc: AudioFourCC) -> Self {
+		match fourcc {
+			AudioFourCC::Ac3 => *b"ac-3",
+			AudioFourCC::Eac3 => *b"ec-3",
+			AudioFourCC::Opus => *b"Opus",
+			AudioFourCC::Mp3 => *b".mp3",
+			AudioFourCC::Flac => *b"fLaC",
+			AudioFourCC::Aac => *b"mp4a",
+			AudioFourCC::Unknown(fourcc) => fourcc,
+		}
+	}
+}
+
 #[derive(Debug, Clone, Copy, FromPrimitive, PartialEq, Eq)]
 #[repr(u8)]
 /// FLV Frame Type
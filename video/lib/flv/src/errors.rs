@@ -7,6 +7,8 @@ pub enum FlvDemuxerError {
 	InvalidFlvHeader,
 	InvalidScriptDataName,
 	InvalidEnhancedPacketType(u8),
+	InvalidEnhancedAudioPacketType(u8),
+	InvalidMultitrackType(u8),
 	InvalidSoundRate(u8),
 	InvalidSoundSize(u8),
 	InvalidSoundType(u8),
@@ -35,6 +37,12 @@ impl std::fmt::Display for FlvDemuxerError {
 			Self::InvalidEnhancedPacketType(error) => {
 				write!(f, "invalid enhanced packet type: {}", error)
 			}
+			Self::InvalidEnhancedAudioPacketType(error) => {
+				write!(f, "invalid enhanced audio packet type: {}", error)
+			}
+			Self::InvalidMultitrackType(error) => {
+				write!(f, "invalid multitrack type: {}", error)
+			}
 			Self::InvalidSoundRate(error) => {
 				write!(f, "invalid sound rate: {}", error)
 			}
@@ -50,3 +58,39 @@ impl std::fmt::Display for FlvDemuxerError {
 		}
 	}
 }
+
+#[derive(Debug)]
+pub enum FlvMuxerError {
+	IO(io::Error),
+	Amf0Write(amf0::Amf0WriteError),
+	/// The data of a tag or track does not fit in 24 bits
+	TagTooLarge(usize),
+	/// A multitrack packet without any tracks
+	EmptyMultitrack,
+	/// All tracks of a multitrack packet must have the same packet type
+	MultitrackPacketTypeMismatch,
+}
+
+impl From<io::Error> for FlvMuxerError {
+	fn from(error: io::Error) -> Self {
+		Self::IO(error)
+	}
+}
+
+impl From<amf0::Amf0WriteError> for FlvMuxerError {
+	fn from(value: amf0::Amf0WriteError) -> Self {
+		Self::Amf0Write(value)
+	}
+}
+
+impl std::fmt::Display for FlvMuxerError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::IO(error) => write!(f, "io error: {}", error),
+			Self::Amf0Write(error) => write!(f, "amf0 write error: {}", error),
+			Self::TagTooLarge(size) => write!(f, "tag too large: {}", size),
+			Self::EmptyMultitrack => write!(f, "empty multitrack"),
+			Self::MultitrackPacketTypeMismatch => write!(f, "multitrack packet type mismatch"),
+		}
+	}
+}
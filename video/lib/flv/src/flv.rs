@@ -13,9 +13,10 @@ use num_traits::FromPrimitive;
 
 use crate::define::Flv;
 use crate::{
-	AacPacket, AacPacketType, Av1Packet, AvcPacket, AvcPacketType, EnhancedPacket, EnhancedPacketType, FlvDemuxerError,
-	FlvHeader, FlvTag, FlvTagAudioData, FlvTagData, FlvTagType, FlvTagVideoData, FrameType, HevcPacket, SoundCodecId,
-	SoundRate, SoundSize, SoundType, VideoCodecId, VideoFourCC,
+	AacPacket, AacPacketType, AudioFourCC, AudioTrack, Av1Packet, AvcPacket, AvcPacketType, EnhancedAudioPacket,
+	EnhancedAudioPacketType, EnhancedPacket, EnhancedPacketType, FlvDemuxerError, FlvHeader, FlvTag, FlvTagAudioData,
+	FlvTagData, FlvTagType, FlvTagVideoData, FrameType, HevcPacket, MultitrackType, SoundCodecId, SoundRate, SoundSize,
+	SoundType, VideoCodecId, VideoFourCC, VideoTrack,
 };
 
 impl Flv {
@@ -98,6 +99,16 @@ impl FlvTagData {
 
 				let sound_format = (flags & 0b1111_0000) >> 4;
 
+				if sound_format == SoundCodecId::ExHeader as u8 {
+					// In the enhanced spec the lower 4 bits are the packet type
+					return Ok(FlvTagData::Audio {
+						sound_rate: SoundRate::Hz44000,
+						sound_size: SoundSize::Bit16,
+						sound_type: SoundType::Stereo,
+						data: FlvTagAudioData::demux_enhanced(flags & 0b0000_1111, &mut reader)?,
+					});
+				}
+
 				let sound_rate = (flags & 0b0000_1100) >> 2;
 				let sound_rate =
 					SoundRate::from_u8(sound_rate).ok_or_else(|| FlvDemuxerError::InvalidSoundRate(sound_rate))?;
@@ -176,12 +187,69 @@ impl FlvTagAudioData {
 				let aac_packet_type = reader.read_u8()?;
 				Ok(Self::Aac(AacPacket::demux(aac_packet_type, reader)?))
 			}
+			Some(SoundCodecId::Mp3) => Ok(Self::Mp3(reader.extract_remaining())),
 			_ => Ok(Self::Unknown {
 				sound_format,
 				data: reader.extract_remaining(),
 			}),
 		}
 	}
+
+	pub fn demux_enhanced(packet_type: u8, reader: &mut io::Cursor<Bytes>) -> Result<Self, FlvDemuxerError> {
+		let packet_type = demux_mod_ex(packet_type, EnhancedAudioPacketType::ModEx as u8, reader)?;
+		let packet_type = EnhancedAudioPacketType::from_u8(packet_type)
+			.ok_or(FlvDemuxerError::InvalidEnhancedAudioPacketType(packet_type))?;
+
+		if packet_type == EnhancedAudioPacketType::Multitrack {
+			let tracks = demux_multitrack(reader, |packet_type, audio_codec, reader| {
+				let packet_type = EnhancedAudioPacketType::from_u8(packet_type)
+					.ok_or(FlvDemuxerError::InvalidEnhancedAudioPacketType(packet_type))?;
+				EnhancedAudioPacket::demux(packet_type, audio_codec.into(), reader)
+			})?;
+
+			return Ok(Self::Multitrack(
+				tracks
+					.into_iter()
+					.map(|(track_id, packet)| AudioTrack { track_id, packet })
+					.collect(),
+			));
+		}
+
+		let mut audio_codec = [0; 4];
+		reader.read_exact(&mut audio_codec)?;
+		let packet = EnhancedAudioPacket::demux(packet_type, audio_codec.into(), reader)?;
+
+		Ok(Self::Enhanced(packet))
+	}
+}
+
+impl EnhancedAudioPacket {
+	pub(crate) fn demux(
+		packet_type: EnhancedAudioPacketType,
+		audio_codec: AudioFourCC,
+		reader: &mut io::Cursor<Bytes>,
+	) -> Result<Self, FlvDemuxerError> {
+		match packet_type {
+			EnhancedAudioPacketType::SequenceStart => Ok(Self::SequenceStart {
+				audio_codec,
+				data: reader.extract_remaining(),
+			}),
+			EnhancedAudioPacketType::CodedFrames => Ok(Self::CodedFrames {
+				audio_codec,
+				data: reader.extract_remaining(),
+			}),
+			EnhancedAudioPacketType::SequenceEnd => Ok(Self::SequenceEnd { audio_codec }),
+			EnhancedAudioPacketType::MultichannelConfig => Ok(Self::MultichannelConfig {
+				audio_codec,
+				data: reader.extract_remaining(),
+			}),
+			_ => Ok(Self::Unknown {
+				packet_type: packet_type as u8,
+				audio_codec,
+				data: reader.extract_remaining(),
+			}),
+		}
+	}
 }
 
 impl AacPacket {
@@ -213,52 +281,150 @@ impl FlvTagVideoData {
 
 	pub fn demux_enhanced(packet_type: u8, reader: &mut io::Cursor<Bytes>) -> Result<Self, FlvDemuxerError> {
 		// In the enhanced spec the codec id is the packet type
+		let packet_type = demux_mod_ex(packet_type, EnhancedPacketType::ModEx as u8, reader)?;
 		let packet_type = EnhancedPacketType::from_u8(packet_type)
 			.ok_or_else(|| FlvDemuxerError::InvalidEnhancedPacketType(packet_type))?;
+
+		if packet_type == EnhancedPacketType::Multitrack {
+			let tracks = demux_multitrack(reader, |packet_type, video_codec, reader| {
+				let packet_type = EnhancedPacketType::from_u8(packet_type)
+					.ok_or(FlvDemuxerError::InvalidEnhancedPacketType(packet_type))?;
+				EnhancedPacket::demux(packet_type, video_codec.into(), reader)
+			})?;
+
+			return Ok(Self::Multitrack(
+				tracks
+					.into_iter()
+					.map(|(track_id, packet)| VideoTrack { track_id, packet })
+					.collect(),
+			));
+		}
+
 		let mut video_codec = [0; 4];
 		reader.read_exact(&mut video_codec)?;
-		let video_codec = VideoFourCC::from(video_codec);
+		let packet = EnhancedPacket::demux(packet_type, video_codec.into(), reader)?;
 
+		Ok(Self::Enhanced(packet))
+	}
+}
+
+impl EnhancedPacket {
+	pub(crate) fn demux(
+		packet_type: EnhancedPacketType,
+		video_codec: VideoFourCC,
+		reader: &mut io::Cursor<Bytes>,
+	) -> Result<Self, FlvDemuxerError> {
 		match packet_type {
 			EnhancedPacketType::SequenceEnd => {
-				return Ok(Self::Enhanced(EnhancedPacket::SequenceEnd));
+				return Ok(Self::SequenceEnd {
+					video_codec: video_codec.into(),
+				});
 			}
 			EnhancedPacketType::Metadata => {
-				return Ok(Self::Enhanced(EnhancedPacket::Metadata(reader.extract_remaining())));
+				return Ok(Self::Metadata {
+					video_codec: video_codec.into(),
+					data: reader.extract_remaining(),
+				});
 			}
 			_ => {}
 		}
 
 		match (video_codec, packet_type) {
-			(VideoFourCC::Av1, EnhancedPacketType::SequenceStart) => Ok(Self::Enhanced(EnhancedPacket::Av1(
-				Av1Packet::SequenceStart(AV1CodecConfigurationRecord::demux(reader)?),
+			(VideoFourCC::Av1, EnhancedPacketType::SequenceStart) => Ok(Self::Av1(Av1Packet::SequenceStart(
+				AV1CodecConfigurationRecord::demux(reader)?,
 			))),
-			(VideoFourCC::Av1, EnhancedPacketType::CodedFrames) => Ok(Self::Enhanced(EnhancedPacket::Av1(Av1Packet::Raw(
-				reader.extract_remaining(),
-			)))),
-			(VideoFourCC::Hevc, EnhancedPacketType::SequenceStart) => Ok(Self::Enhanced(EnhancedPacket::Hevc(
-				HevcPacket::SequenceStart(HEVCDecoderConfigurationRecord::demux(reader)?),
+			(VideoFourCC::Av1, EnhancedPacketType::CodedFrames) => Ok(Self::Av1(Av1Packet::Raw(reader.extract_remaining()))),
+			(VideoFourCC::Avc, EnhancedPacketType::SequenceStart) => Ok(Self::Avc(AvcPacket::SequenceHeader(
+				AVCDecoderConfigurationRecord::demux(reader)?,
+			))),
+			(VideoFourCC::Avc, EnhancedPacketType::CodedFrames) => Ok(Self::Avc(AvcPacket::Nalu {
+				composition_time: reader.read_u24::<BigEndian>()?,
+				data: reader.extract_remaining(),
+			})),
+			(VideoFourCC::Avc, EnhancedPacketType::CodedFramesX) => Ok(Self::Avc(AvcPacket::Nalu {
+				composition_time: 0,
+				data: reader.extract_remaining(),
+			})),
+			(VideoFourCC::Hevc, EnhancedPacketType::SequenceStart) => Ok(Self::Hevc(HevcPacket::SequenceStart(
+				HEVCDecoderConfigurationRecord::demux(reader)?,
 			))),
 			(VideoFourCC::Hevc, EnhancedPacketType::CodedFrames) => {
 				let composition_time = reader.read_i24::<BigEndian>()?;
-				Ok(Self::Enhanced(EnhancedPacket::Hevc(HevcPacket::Nalu {
+				Ok(Self::Hevc(HevcPacket::Nalu {
 					composition_time: Some(composition_time),
 					data: reader.extract_remaining(),
-				})))
-			}
-			(VideoFourCC::Hevc, EnhancedPacketType::CodedFramesX) => {
-				Ok(Self::Enhanced(EnhancedPacket::Hevc(HevcPacket::Nalu {
-					composition_time: None,
-					data: reader.extract_remaining(),
-				})))
+				}))
 			}
-			_ => Ok(Self::Enhanced(EnhancedPacket::Unknown {
+			(VideoFourCC::Hevc, EnhancedPacketType::CodedFramesX) => Ok(Self::Hevc(HevcPacket::Nalu {
+				composition_time: None,
+				data: reader.extract_remaining(),
+			})),
+			_ => Ok(Self::Unknown {
 				packet_type: packet_type as u8,
 				video_codec: video_codec.into(),
 				data: reader.extract_remaining(),
-			})),
+			}),
+		}
+	}
+}
+
+/// Skip over any ModEx packets and return the packet type that follows them.
+/// ModEx packets only carry a nanosecond offset for the timestamp, which we
+/// have no use for.
+fn demux_mod_ex(mut packet_type: u8, mod_ex: u8, reader: &mut io::Cursor<Bytes>) -> Result<u8, FlvDemuxerError> {
+	while packet_type == mod_ex {
+		let mut size = reader.read_u8()? as usize + 1;
+		if size == 256 {
+			size = reader.read_u16::<BigEndian>()? as usize + 1;
 		}
+
+		reader.read_slice(size)?;
+
+		// The upper 4 bits are the ModEx type
+		packet_type = reader.read_u8()? & 0b0000_1111;
+	}
+
+	Ok(packet_type)
+}
+
+/// Demux the tracks of a multitrack packet, `demux_track` is called with the
+/// packet type, codec and data of every track. Returns the track ids and the
+/// demuxed packets in the order they appear.
+fn demux_multitrack<T>(
+	reader: &mut io::Cursor<Bytes>,
+	mut demux_track: impl FnMut(u8, [u8; 4], &mut io::Cursor<Bytes>) -> Result<T, FlvDemuxerError>,
+) -> Result<Vec<(u8, T)>, FlvDemuxerError> {
+	let flags = reader.read_u8()?;
+	let multitrack_type = flags >> 4;
+	let multitrack_type =
+		MultitrackType::from_u8(multitrack_type).ok_or(FlvDemuxerError::InvalidMultitrackType(multitrack_type))?;
+	let packet_type = flags & 0b0000_1111;
+
+	// All tracks share a codec unless each track has its own
+	let mut codec = [0; 4];
+	if multitrack_type != MultitrackType::ManyTracksManyCodecs {
+		reader.read_exact(&mut codec)?;
 	}
+
+	let mut tracks = Vec::new();
+	while reader.has_remaining() {
+		if multitrack_type == MultitrackType::ManyTracksManyCodecs {
+			reader.read_exact(&mut codec)?;
+		}
+
+		let track_id = reader.read_u8()?;
+
+		let data = if multitrack_type == MultitrackType::OneTrack {
+			reader.extract_remaining()
+		} else {
+			let size = reader.read_u24::<BigEndian>()?;
+			reader.read_slice(size as usize)?
+		};
+
+		tracks.push((track_id, demux_track(packet_type, codec, &mut io::Cursor::new(data))?));
+	}
+
+	Ok(tracks)
 }
 
 impl AvcPacket {
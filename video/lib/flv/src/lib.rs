@@ -1,9 +1,12 @@
 mod define;
 mod errors;
 mod flv;
+mod muxer;
+mod stream;
 
 pub use define::*;
-pub use errors::FlvDemuxerError;
+pub use errors::{FlvDemuxerError, FlvMuxerError};
+pub use stream::FlvStreamDemuxer;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,414 @@
+use std::io;
+
+use amf0::Amf0Writer;
+use byteorder::{BigEndian, WriteBytesExt};
+use bytesio::bytes_writer::BytesWriter;
+
+use crate::define::Flv;
+use crate::{
+	AacPacket, AacPacketType, AudioFourCC, Av1Packet, AvcPacket, AvcPacketType, EnhancedAudioPacket,
+	EnhancedAudioPacketType, EnhancedPacket, EnhancedPacketType, FlvHeader, FlvMuxerError, FlvTag, FlvTagAudioData,
+	FlvTagData, FlvTagType, FlvTagVideoData, FrameType, HevcPacket, MultitrackType, SoundCodecId, SoundRate, SoundSize,
+	SoundType, VideoCodecId, VideoFourCC,
+};
+
+/// The largest size which fits in the 24 bit size fields.
+const MAX_DATA_SIZE: usize = 0xFF_FFFF;
+
+impl Flv {
+	/// Mux a FLV file.
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> Result<(), FlvMuxerError> {
+		self.header.mux(writer)?;
+
+		writer.write_u32::<BigEndian>(0)?; // previous tag size
+
+		for tag in &self.tags {
+			let size = tag.mux(writer)?;
+			writer.write_u32::<BigEndian>(size)?; // previous tag size
+		}
+
+		Ok(())
+	}
+}
+
+impl FlvHeader {
+	/// Mux the header, the data offset is calculated from the size of the
+	/// extra data so `data_offset` is ignored.
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> Result<(), FlvMuxerError> {
+		writer.write_all(b"FLV")?;
+		writer.write_u8(self.version)?;
+
+		let mut flags = 0;
+		if self.has_audio {
+			flags |= 0b0000_0100;
+		}
+		if self.has_video {
+			flags |= 0b0000_0001;
+		}
+		writer.write_u8(flags)?;
+
+		writer.write_u32::<BigEndian>(9 + self.extra.len() as u32)?;
+		writer.write_all(&self.extra)?;
+
+		Ok(())
+	}
+}
+
+impl FlvTag {
+	/// Mux a tag. Returns the size of the tag, which has to be written as the
+	/// previous tag size after it.
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> Result<u32, FlvMuxerError> {
+		let mut data = Vec::new();
+		self.data.mux(&mut data)?;
+
+		if data.len() > MAX_DATA_SIZE {
+			return Err(FlvMuxerError::TagTooLarge(data.len()));
+		}
+
+		writer.write_u8(self.data.tag_type())?;
+		writer.write_u24::<BigEndian>(data.len() as u32)?;
+		// The lower 24 bits of the timestamp come first, followed by the upper 8 bits
+		writer.write_u24::<BigEndian>(self.timestamp & 0xFF_FFFF)?;
+		writer.write_u8((self.timestamp >> 24) as u8)?;
+		writer.write_u24::<BigEndian>(self.stream_id & 0xFF_FFFF)?;
+		writer.write_all(&data)?;
+
+		Ok(11 + data.len() as u32)
+	}
+}
+
+impl FlvTagData {
+	/// The tag type this data is muxed with.
+	pub fn tag_type(&self) -> u8 {
+		match self {
+			Self::Audio { .. } => FlvTagType::Audio as u8,
+			Self::Video { .. } => FlvTagType::Video as u8,
+			Self::ScriptData { .. } => FlvTagType::ScriptData as u8,
+			Self::Unknown { tag_type, .. } => *tag_type,
+		}
+	}
+
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> Result<(), FlvMuxerError> {
+		match self {
+			Self::Audio {
+				sound_rate,
+				sound_size,
+				sound_type,
+				data,
+			} => data.mux(*sound_rate, *sound_size, *sound_type, writer),
+			Self::Video { frame_type, data } => data.mux(*frame_type, writer),
+			Self::ScriptData { name, data } => {
+				let mut amf0_writer = BytesWriter::default();
+
+				Amf0Writer::write_string(&mut amf0_writer, name)?;
+				for value in data {
+					Amf0Writer::write_any(&mut amf0_writer, value)?;
+				}
+
+				writer.write_all(&amf0_writer.dispose())?;
+
+				Ok(())
+			}
+			Self::Unknown { data, .. } => {
+				writer.write_all(data)?;
+
+				Ok(())
+			}
+		}
+	}
+}
+
+impl FlvTagAudioData {
+	/// Mux the audio data, the sound rate, size and type are only used for
+	/// legacy audio since enhanced audio uses those bits for the packet type.
+	pub fn mux<T: io::Write>(
+		&self,
+		sound_rate: SoundRate,
+		sound_size: SoundSize,
+		sound_type: SoundType,
+		writer: &mut T,
+	) -> Result<(), FlvMuxerError> {
+		let flags = (sound_rate as u8) << 2 | (sound_size as u8) << 1 | sound_type as u8;
+
+		match self {
+			Self::Aac(packet) => {
+				writer.write_u8((SoundCodecId::Aac as u8) << 4 | flags)?;
+				packet.mux(writer)
+			}
+			Self::Mp3(data) => {
+				writer.write_u8((SoundCodecId::Mp3 as u8) << 4 | flags)?;
+				writer.write_all(data)?;
+
+				Ok(())
+			}
+			Self::Enhanced(packet) => {
+				writer.write_u8((SoundCodecId::ExHeader as u8) << 4 | packet.packet_type())?;
+				writer.write_all(&<[u8; 4]>::from(packet.audio_codec()))?;
+				packet.mux(writer)
+			}
+			Self::Multitrack(tracks) => {
+				writer.write_u8((SoundCodecId::ExHeader as u8) << 4 | EnhancedAudioPacketType::Multitrack as u8)?;
+
+				let tracks = tracks
+					.iter()
+					.map(|track| {
+						let mut data = Vec::new();
+						track.packet.mux(&mut data)?;
+						Ok((
+							track.track_id,
+							track.packet.packet_type(),
+							track.packet.audio_codec().into(),
+							data,
+						))
+					})
+					.collect::<Result<Vec<_>, FlvMuxerError>>()?;
+
+				mux_multitrack(writer, &tracks)
+			}
+			Self::Unknown { sound_format, data } => {
+				writer.write_u8((sound_format & 0b0000_1111) << 4 | flags)?;
+				writer.write_all(data)?;
+
+				Ok(())
+			}
+		}
+	}
+}
+
+impl AacPacket {
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> Result<(), FlvMuxerError> {
+		match self {
+			Self::SequenceHeader(data) => {
+				writer.write_u8(AacPacketType::SeqHdr as u8)?;
+				writer.write_all(data)?;
+			}
+			Self::Raw(data) => {
+				writer.write_u8(AacPacketType::Raw as u8)?;
+				writer.write_all(data)?;
+			}
+			Self::Unknown { aac_packet_type, data } => {
+				writer.write_u8(*aac_packet_type)?;
+				writer.write_all(data)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl EnhancedAudioPacket {
+	pub(crate) fn packet_type(&self) -> u8 {
+		match self {
+			Self::SequenceStart { .. } => EnhancedAudioPacketType::SequenceStart as u8,
+			Self::CodedFrames { .. } => EnhancedAudioPacketType::CodedFrames as u8,
+			Self::SequenceEnd { .. } => EnhancedAudioPacketType::SequenceEnd as u8,
+			Self::MultichannelConfig { .. } => EnhancedAudioPacketType::MultichannelConfig as u8,
+			Self::Unknown { packet_type, .. } => *packet_type,
+		}
+	}
+
+	pub fn audio_codec(&self) -> AudioFourCC {
+		match self {
+			Self::SequenceStart { audio_codec, .. }
+			| Self::CodedFrames { audio_codec, .. }
+			| Self::SequenceEnd { audio_codec }
+			| Self::MultichannelConfig { audio_codec, .. }
+			| Self::Unknown { audio_codec, .. } => *audio_codec,
+		}
+	}
+
+	/// Mux the body of the packet, the packet type and codec are written by
+	/// the caller.
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> Result<(), FlvMuxerError> {
+		match self {
+			Self::SequenceStart { data, .. }
+			| Self::CodedFrames { data, .. }
+			| Self::MultichannelConfig { data, .. }
+			| Self::Unknown { data, .. } => writer.write_all(data)?,
+			Self::SequenceEnd { .. } => {}
+		}
+
+		Ok(())
+	}
+}
+
+impl FlvTagVideoData {
+	pub fn mux<T: io::Write>(&self, frame_type: FrameType, writer: &mut T) -> Result<(), FlvMuxerError> {
+		let frame_type = frame_type as u8 & 0b0000_1111;
+
+		match self {
+			Self::Avc(packet) => {
+				writer.write_u8(frame_type << 4 | VideoCodecId::Avc as u8)?;
+				packet.mux(writer)
+			}
+			Self::Enhanced(packet) => {
+				// The top bit of the frame type marks the tag as enhanced
+				writer.write_u8((0b1000 | frame_type) << 4 | packet.packet_type())?;
+				writer.write_all(&packet.video_codec())?;
+				packet.mux(writer)
+			}
+			Self::Multitrack(tracks) => {
+				writer.write_u8((0b1000 | frame_type) << 4 | EnhancedPacketType::Multitrack as u8)?;
+
+				let tracks = tracks
+					.iter()
+					.map(|track| {
+						let mut data = Vec::new();
+						track.packet.mux(&mut data)?;
+						Ok((track.track_id, track.packet.packet_type(), track.packet.video_codec(), data))
+					})
+					.collect::<Result<Vec<_>, FlvMuxerError>>()?;
+
+				mux_multitrack(writer, &tracks)
+			}
+			Self::Unknown { codec_id, data } => {
+				writer.write_u8(frame_type << 4 | codec_id & 0b0000_1111)?;
+				writer.write_all(data)?;
+
+				Ok(())
+			}
+		}
+	}
+}
+
+impl AvcPacket {
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> Result<(), FlvMuxerError> {
+		match self {
+			Self::SequenceHeader(config) => {
+				writer.write_u8(AvcPacketType::SeqHdr as u8)?;
+				writer.write_u24::<BigEndian>(0)?; // composition time (always 0)
+				config.mux(writer)?;
+			}
+			Self::Nalu { composition_time, data } => {
+				writer.write_u8(AvcPacketType::Nalu as u8)?;
+				writer.write_u24::<BigEndian>(*composition_time & 0xFF_FFFF)?;
+				writer.write_all(data)?;
+			}
+			Self::EndOfSequence => {
+				writer.write_u8(AvcPacketType::EndOfSequence as u8)?;
+				writer.write_u24::<BigEndian>(0)?; // composition time (always 0)
+			}
+			Self::Unknown {
+				avc_packet_type,
+				composition_time,
+				data,
+			} => {
+				writer.write_u8(*avc_packet_type)?;
+				writer.write_u24::<BigEndian>(*composition_time & 0xFF_FFFF)?;
+				writer.write_all(data)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl EnhancedPacket {
+	pub(crate) fn packet_type(&self) -> u8 {
+		match self {
+			Self::Metadata { .. } => EnhancedPacketType::Metadata as u8,
+			Self::SequenceEnd { .. } | Self::Avc(AvcPacket::EndOfSequence) => EnhancedPacketType::SequenceEnd as u8,
+			Self::Avc(AvcPacket::SequenceHeader(_))
+			| Self::Av1(Av1Packet::SequenceStart(_))
+			| Self::Hevc(HevcPacket::SequenceStart(_)) => EnhancedPacketType::SequenceStart as u8,
+			Self::Avc(AvcPacket::Nalu { .. })
+			| Self::Av1(Av1Packet::Raw(_))
+			| Self::Hevc(HevcPacket::Nalu {
+				composition_time: Some(_),
+				..
+			}) => EnhancedPacketType::CodedFrames as u8,
+			Self::Hevc(HevcPacket::Nalu {
+				composition_time: None, ..
+			}) => EnhancedPacketType::CodedFramesX as u8,
+			Self::Avc(AvcPacket::Unknown { avc_packet_type, .. }) => *avc_packet_type,
+			Self::Unknown { packet_type, .. } => *packet_type,
+		}
+	}
+
+	pub(crate) fn video_codec(&self) -> [u8; 4] {
+		match self {
+			Self::Metadata { video_codec, .. } | Self::SequenceEnd { video_codec } | Self::Unknown { video_codec, .. } => {
+				*video_codec
+			}
+			Self::Avc(_) => VideoFourCC::Avc.into(),
+			Self::Av1(_) => VideoFourCC::Av1.into(),
+			Self::Hevc(_) => VideoFourCC::Hevc.into(),
+		}
+	}
+
+	/// Mux the body of the packet, the packet type and codec are written by
+	/// the caller.
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> Result<(), FlvMuxerError> {
+		match self {
+			Self::Metadata { data, .. } | Self::Unknown { data, .. } | Self::Av1(Av1Packet::Raw(data)) => {
+				writer.write_all(data)?;
+			}
+			Self::SequenceEnd { .. } | Self::Avc(AvcPacket::EndOfSequence) => {}
+			Self::Avc(AvcPacket::SequenceHeader(config)) => config.mux(writer)?,
+			Self::Avc(AvcPacket::Nalu { composition_time, data })
+			| Self::Avc(AvcPacket::Unknown {
+				composition_time, data, ..
+			}) => {
+				writer.write_u24::<BigEndian>(*composition_time & 0xFF_FFFF)?;
+				writer.write_all(data)?;
+			}
+			Self::Av1(Av1Packet::SequenceStart(config)) => config.mux(writer)?,
+			Self::Hevc(HevcPacket::SequenceStart(config)) => config.mux(writer)?,
+			Self::Hevc(HevcPacket::Nalu { composition_time, data }) => {
+				if let Some(composition_time) = composition_time {
+					writer.write_i24::<BigEndian>(*composition_time)?;
+				}
+				writer.write_all(data)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Mux the tracks of a multitrack packet. Every track is given as its track
+/// id, packet type, codec and muxed packet. The most compact multitrack type
+/// that can represent the tracks is used.
+fn mux_multitrack<T: io::Write>(writer: &mut T, tracks: &[(u8, u8, [u8; 4], Vec<u8>)]) -> Result<(), FlvMuxerError> {
+	let Some((_, packet_type, codec, _)) = tracks.first() else {
+		return Err(FlvMuxerError::EmptyMultitrack);
+	};
+
+	if tracks.iter().any(|(_, ty, _, _)| ty != packet_type) {
+		return Err(FlvMuxerError::MultitrackPacketTypeMismatch);
+	}
+
+	let multitrack_type = if tracks.len() == 1 {
+		MultitrackType::OneTrack
+	} else if tracks.iter().all(|(_, _, c, _)| c == codec) {
+		MultitrackType::ManyTracks
+	} else {
+		MultitrackType::ManyTracksManyCodecs
+	};
+
+	writer.write_u8((multitrack_type as u8) << 4 | packet_type)?;
+
+	if multitrack_type != MultitrackType::ManyTracksManyCodecs {
+		writer.write_all(codec)?;
+	}
+
+	for (track_id, _, codec, data) in tracks {
+		if multitrack_type == MultitrackType::ManyTracksManyCodecs {
+			writer.write_all(codec)?;
+		}
+
+		writer.write_u8(*track_id)?;
+
+		if multitrack_type != MultitrackType::OneTrack {
+			if data.len() > MAX_DATA_SIZE {
+				return Err(FlvMuxerError::TagTooLarge(data.len()));
+			}
+
+			writer.write_u24::<BigEndian>(data.len() as u32)?;
+		}
+
+		writer.write_all(data)?;
+	}
+
+	Ok(())
+}
@@ -0,0 +1,224 @@
+use std::io;
+
+use byteorder::{BigEndian, ByteOrder};
+use bytes::{Buf, BytesMut};
+use num_traits::FromPrimitive;
+
+use crate::{FlvDemuxerError, FlvHeader, FlvTag, FlvTagType};
+
+/// The size of the tag header, everything before the tag data.
+const TAG_HEADER_SIZE: usize = 11;
+
+/// The size of the previous tag size field that follows every tag.
+const PREVIOUS_TAG_SIZE_SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+	/// Waiting for the FLV header
+	Header,
+	/// Waiting for the previous tag size of the first tag, which is always 0
+	FirstPreviousTagSize,
+	/// Waiting for tags
+	Tags,
+}
+
+enum Candidate {
+	/// A tag followed by a matching previous tag size, with the size of the tag
+	Intact(usize),
+	/// We need more data to know if this is a tag
+	Incomplete,
+	/// This is not a tag
+	Invalid,
+}
+
+/// An incremental FLV demuxer.
+/// Data can be fed to it in chunks of any size and complete tags are returned
+/// as soon as they and the previous tag size that follows them are buffered.
+/// The previous tag size is used to check that a tag is intact, if it does
+/// not match we resync by skipping ahead until we find a tag that does.
+#[derive(Debug)]
+pub struct FlvStreamDemuxer {
+	buffer: BytesMut,
+	state: State,
+	header: Option<FlvHeader>,
+	/// If we lost track of where the next tag starts
+	resyncing: bool,
+	skipped_bytes: u64,
+}
+
+impl Default for FlvStreamDemuxer {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl FlvStreamDemuxer {
+	/// Create a demuxer for a stream which starts with a FLV header.
+	pub fn new() -> Self {
+		Self {
+			buffer: BytesMut::new(),
+			state: State::Header,
+			header: None,
+			resyncing: false,
+			skipped_bytes: 0,
+		}
+	}
+
+	/// Create a demuxer for a stream of tags without a FLV header, such as
+	/// the tag data after a header has already been read. The stream should
+	/// start with the previous tag size of the first tag.
+	pub fn without_header() -> Self {
+		Self {
+			state: State::FirstPreviousTagSize,
+			..Self::new()
+		}
+	}
+
+	/// Add data to the demuxer.
+	pub fn extend_data(&mut self, data: &[u8]) {
+		self.buffer.extend_from_slice(data);
+	}
+
+	/// The FLV header, available once it has been read.
+	pub fn header(&self) -> Option<&FlvHeader> {
+		self.header.as_ref()
+	}
+
+	/// The number of bytes which were skipped to recover from corrupt tags.
+	pub fn skipped_bytes(&self) -> u64 {
+		self.skipped_bytes
+	}
+
+	/// Read the next tag.
+	/// - will return Ok(None) if we need more data.
+	/// - will return Ok(Some(FlvTag)) if we have a full tag.
+	/// - will return Err if the header is invalid, or if a tag is intact but
+	///   its data could not be demuxed. The tag is skipped in that case so we
+	///   can keep reading tags after it.
+	pub fn read_tag(&mut self) -> Result<Option<FlvTag>, FlvDemuxerError> {
+		loop {
+			match self.state {
+				State::Header => {
+					if !self.read_header()? {
+						return Ok(None);
+					}
+				}
+				State::FirstPreviousTagSize => {
+					if self.buffer.len() < PREVIOUS_TAG_SIZE_SIZE {
+						return Ok(None);
+					}
+
+					self.buffer.advance(PREVIOUS_TAG_SIZE_SIZE);
+					self.state = State::Tags;
+				}
+				State::Tags => {
+					let Some(tag_size) = self.next_tag_size() else {
+						return Ok(None);
+					};
+
+					// The tag is intact, so the previous tag size is dropped along with it.
+					let data = self.buffer.split_to(tag_size + PREVIOUS_TAG_SIZE_SIZE).freeze();
+
+					return FlvTag::demux(&mut io::Cursor::new(data)).map(Some);
+				}
+			}
+		}
+	}
+
+	/// Read the header, returns false if we need more data.
+	fn read_header(&mut self) -> Result<bool, FlvDemuxerError> {
+		// The fixed part of the header is 9 bytes, the last 4 bytes are the size of the
+		// whole header.
+		if self.buffer.len() < 9 {
+			return Ok(false);
+		}
+
+		if &self.buffer[..3] != b"FLV" {
+			return Err(FlvDemuxerError::InvalidFlvHeader);
+		}
+
+		let data_offset = BigEndian::read_u32(&self.buffer[5..9]) as usize;
+		if data_offset < 9 {
+			return Err(FlvDemuxerError::InvalidFlvHeader);
+		}
+
+		if self.buffer.len() < data_offset {
+			return Ok(false);
+		}
+
+		let data = self.buffer.split_to(data_offset).freeze();
+		self.header = Some(FlvHeader::demux(&mut io::Cursor::new(data))?);
+		self.state = State::FirstPreviousTagSize;
+
+		Ok(true)
+	}
+
+	/// Find the size of the next intact tag, skipping over any corrupt data in
+	/// front of it. Returns None if we need more data.
+	fn next_tag_size(&mut self) -> Option<usize> {
+		if !self.resyncing {
+			match self.check_tag(0) {
+				Candidate::Intact(tag_size) => return Some(tag_size),
+				Candidate::Incomplete => return None,
+				Candidate::Invalid => self.resyncing = true,
+			}
+		}
+
+		// While resyncing we cannot trust the size of a tag which is not complete yet,
+		// since it might be garbage which claims to be a huge tag. So we take the first
+		// intact tag and otherwise keep everything from the first tag which might still
+		// turn out to be intact.
+		let mut first_incomplete = None;
+		for offset in 0..self.buffer.len() {
+			match self.check_tag(offset) {
+				Candidate::Intact(tag_size) => {
+					self.skip(offset);
+					self.resyncing = false;
+					return Some(tag_size);
+				}
+				Candidate::Incomplete => {
+					first_incomplete.get_or_insert(offset);
+				}
+				Candidate::Invalid => {}
+			}
+		}
+
+		self.skip(first_incomplete.unwrap_or(self.buffer.len()));
+
+		None
+	}
+
+	/// Check if there is a tag at `offset` in the buffer.
+	fn check_tag(&self, offset: usize) -> Candidate {
+		let buffer = &self.buffer[offset..];
+		if buffer.len() < TAG_HEADER_SIZE {
+			return Candidate::Incomplete;
+		}
+
+		let tag_type = buffer[0];
+		let data_size = BigEndian::read_u24(&buffer[1..4]) as usize;
+		let stream_id = BigEndian::read_u24(&buffer[8..11]);
+
+		// The stream id is always 0, and we only accept the tag types defined by the spec.
+		if FlvTagType::from_u8(tag_type).is_none() || stream_id != 0 {
+			return Candidate::Invalid;
+		}
+
+		let tag_size = TAG_HEADER_SIZE + data_size;
+		if buffer.len() < tag_size + PREVIOUS_TAG_SIZE_SIZE {
+			return Candidate::Incomplete;
+		}
+
+		let previous_tag_size = BigEndian::read_u32(&buffer[tag_size..tag_size + PREVIOUS_TAG_SIZE_SIZE]);
+		if previous_tag_size as usize != tag_size {
+			return Candidate::Invalid;
+		}
+
+		Candidate::Intact(tag_size)
+	}
+
+	fn skip(&mut self, size: usize) {
+		self.buffer.advance(size);
+		self.skipped_bytes += size as u64;
+	}
+}
@@ -9,8 +9,8 @@ use bytesio::bit_reader::BitReader;
 use h264::{Sps, SpsExtended};
 
 use crate::{
-	AacPacket, Av1Packet, AvcPacket, EnhancedPacket, Flv, FlvTagAudioData, FlvTagData, FlvTagVideoData, FrameType,
-	HevcPacket, SoundRate, SoundSize, SoundType,
+	AacPacket, AudioFourCC, AudioTrack, Av1Packet, AvcPacket, EnhancedAudioPacket, EnhancedPacket, Flv, FlvDemuxerError,
+	FlvTagAudioData, FlvTagData, FlvTagVideoData, FrameType, HevcPacket, SoundRate, SoundSize, SoundType, VideoTrack,
 };
 
 #[test]
@@ -45,10 +45,10 @@ fn test_demux_flv_avc_aac() {
 			_ => panic!("expected script data"),
 		};
 
-		// Script data should be an AMF0 object
+		// Script data should be an AMF0 ECMA array
 		let object = match &script_data[0] {
-			amf0::Amf0Value::Object(object) => object,
-			_ => panic!("expected object"),
+			amf0::Amf0Value::EcmaArray(object) => object,
+			_ => panic!("expected ecma array"),
 		};
 
 		// Should have a audio sample size property
@@ -204,8 +204,11 @@ fn test_demux_flv_avc_aac() {
 			sps.ext,
 			Some(SpsExtended {
 				chroma_format_idc: 1,
+				separate_colour_plane_flag: false,
 				bit_depth_luma_minus8: 0,
 				bit_depth_chroma_minus8: 0,
+				qpprime_y_zero_transform_bypass_flag: false,
+				seq_scaling_lists: None,
 			})
 		)
 	}
@@ -531,7 +534,7 @@ fn test_demux_flv_av1_aac() {
 					FlvTagVideoData::Enhanced(EnhancedPacket::Av1(Av1Packet::Raw(_))) => {
 						assert!(!read_seq_end)
 					}
-					FlvTagVideoData::Enhanced(EnhancedPacket::SequenceEnd) => {
+					FlvTagVideoData::Enhanced(EnhancedPacket::SequenceEnd { .. }) => {
 						assert!(!read_seq_end);
 						read_seq_end = true;
 					}
@@ -804,7 +807,7 @@ fn test_demux_flv_hevc_aac() {
 
 				match data {
 					FlvTagVideoData::Enhanced(EnhancedPacket::Hevc(HevcPacket::Nalu { .. })) => assert!(!read_seq_end),
-					FlvTagVideoData::Enhanced(EnhancedPacket::SequenceEnd) => {
+					FlvTagVideoData::Enhanced(EnhancedPacket::SequenceEnd { .. }) => {
 						assert!(!read_seq_end);
 						read_seq_end = true;
 					}
@@ -817,3 +820,270 @@ fn test_demux_flv_hevc_aac() {
 
 	assert!(read_seq_end);
 }
+
+#[test]
+fn test_demux_enhanced_multitrack_video() {
+	// Keyframe, multitrack, one track
+	#[rustfmt::skip]
+	let data = Bytes::from_static(&[
+		0b1001_0110, // enhanced keyframe, multitrack
+		0b0000_0001, // one track, coded frames
+		b'a', b'v', b'0', b'1', // codec
+		0x01, // track id
+		0x12, 0x34, // data
+	]);
+
+	let data = FlvTagData::demux(9, data).unwrap();
+	assert_eq!(
+		data,
+		FlvTagData::Video {
+			frame_type: FrameType::Keyframe,
+			data: FlvTagVideoData::Multitrack(vec![VideoTrack {
+				track_id: 1,
+				packet: EnhancedPacket::Av1(Av1Packet::Raw(Bytes::from_static(&[0x12, 0x34]))),
+			}]),
+		}
+	);
+
+	// Interframe, multitrack, many tracks with many codecs
+	#[rustfmt::skip]
+	let data = Bytes::from_static(&[
+		0b1010_0110, // enhanced interframe, multitrack
+		0b0010_0011, // many tracks many codecs, coded frames x
+		b'a', b'v', b'c', b'1', // codec
+		0x00, // track id
+		0x00, 0x00, 0x02, // size
+		0x12, 0x34, // data
+		b'h', b'v', b'c', b'1', // codec
+		0x02, // track id
+		0x00, 0x00, 0x01, // size
+		0x56, // data
+		b'v', b'p', b'0', b'9', // codec
+		0x03, // track id
+		0x00, 0x00, 0x00, // size
+	]);
+
+	let data = FlvTagData::demux(9, data).unwrap();
+	assert_eq!(
+		data,
+		FlvTagData::Video {
+			frame_type: FrameType::Interframe,
+			data: FlvTagVideoData::Multitrack(vec![
+				VideoTrack {
+					track_id: 0,
+					packet: EnhancedPacket::Avc(AvcPacket::Nalu {
+						composition_time: 0,
+						data: Bytes::from_static(&[0x12, 0x34]),
+					}),
+				},
+				VideoTrack {
+					track_id: 2,
+					packet: EnhancedPacket::Hevc(HevcPacket::Nalu {
+						composition_time: None,
+						data: Bytes::from_static(&[0x56]),
+					}),
+				},
+				VideoTrack {
+					track_id: 3,
+					packet: EnhancedPacket::Unknown {
+						packet_type: 3,
+						video_codec: *b"vp09",
+						data: Bytes::new(),
+					},
+				},
+			]),
+		}
+	);
+
+	// Invalid multitrack type
+	let data = Bytes::from_static(&[0b1001_0110, 0b0011_0001, b'a', b'v', b'0', b'1']);
+	assert!(matches!(
+		FlvTagData::demux(9, data),
+		Err(FlvDemuxerError::InvalidMultitrackType(3))
+	));
+}
+
+#[test]
+fn test_demux_enhanced_mod_ex() {
+	#[rustfmt::skip]
+	let data = Bytes::from_static(&[
+		0b1001_0111, // enhanced keyframe, mod ex
+		0x02,        // mod ex data size - 1
+		0x00, 0x01, 0x02, // mod ex data (timestamp offset nano)
+		0b0000_0001, // timestamp offset nano, coded frames
+		b'a', b'v', b'c', b'1', // codec
+		0x00, 0x00, 0x21, // composition time
+		0x12, 0x34, // data
+	]);
+
+	let data = FlvTagData::demux(9, data).unwrap();
+	assert_eq!(
+		data,
+		FlvTagData::Video {
+			frame_type: FrameType::Keyframe,
+			data: FlvTagVideoData::Enhanced(EnhancedPacket::Avc(AvcPacket::Nalu {
+				composition_time: 0x21,
+				data: Bytes::from_static(&[0x12, 0x34]),
+			})),
+		}
+	);
+
+	#[rustfmt::skip]
+	let data = Bytes::from_static(&[
+		0b1001_0111, // enhanced audio, mod ex
+		0x00,        // mod ex data size - 1
+		0x00,        // mod ex data
+		0b0000_0001, // timestamp offset nano, coded frames
+		b'O', b'p', b'u', b's', // codec
+		0x12, // data
+	]);
+
+	let data = FlvTagData::demux(8, data).unwrap();
+	assert_eq!(
+		data,
+		FlvTagData::Audio {
+			sound_rate: SoundRate::Hz44000,
+			sound_size: SoundSize::Bit16,
+			sound_type: SoundType::Stereo,
+			data: FlvTagAudioData::Enhanced(EnhancedAudioPacket::CodedFrames {
+				audio_codec: AudioFourCC::Opus,
+				data: Bytes::from_static(&[0x12]),
+			}),
+		}
+	);
+}
+
+#[test]
+fn test_demux_enhanced_audio() {
+	let cases: [(&[u8; 4], AudioFourCC); 7] = [
+		(b"Opus", AudioFourCC::Opus),
+		(b"fLaC", AudioFourCC::Flac),
+		(b"ac-3", AudioFourCC::Ac3),
+		(b"ec-3", AudioFourCC::Eac3),
+		(b".mp3", AudioFourCC::Mp3),
+		(b"mp4a", AudioFourCC::Aac),
+		(b"abcd", AudioFourCC::Unknown(*b"abcd")),
+	];
+
+	for (fourcc, audio_codec) in cases {
+		let mut data = vec![0b1001_0000]; // enhanced audio, sequence start
+		data.extend_from_slice(fourcc);
+		data.extend_from_slice(&[0x01, 0x02]);
+
+		let data = FlvTagData::demux(8, Bytes::from(data)).unwrap();
+		assert_eq!(
+			data,
+			FlvTagData::Audio {
+				sound_rate: SoundRate::Hz44000,
+				sound_size: SoundSize::Bit16,
+				sound_type: SoundType::Stereo,
+				data: FlvTagAudioData::Enhanced(EnhancedAudioPacket::SequenceStart {
+					audio_codec,
+					data: Bytes::from_static(&[0x01, 0x02]),
+				}),
+			}
+		);
+		assert_eq!(<[u8; 4]>::from(audio_codec), *fourcc);
+	}
+
+	let data = Bytes::from_static(&[0b1001_0010, b'O', b'p', b'u', b's']);
+	assert_eq!(
+		FlvTagData::demux(8, data).unwrap(),
+		FlvTagData::Audio {
+			sound_rate: SoundRate::Hz44000,
+			sound_size: SoundSize::Bit16,
+			sound_type: SoundType::Stereo,
+			data: FlvTagAudioData::Enhanced(EnhancedAudioPacket::SequenceEnd {
+				audio_codec: AudioFourCC::Opus,
+			}),
+		}
+	);
+
+	let data = Bytes::from_static(&[0b1001_0011, b'O', b'p', b'u', b's']);
+	assert!(matches!(
+		FlvTagData::demux(8, data),
+		Err(FlvDemuxerError::InvalidEnhancedAudioPacketType(3))
+	));
+}
+
+#[test]
+fn test_demux_enhanced_multitrack_audio() {
+	#[rustfmt::skip]
+	let data = Bytes::from_static(&[
+		0b1001_0101, // enhanced audio, multitrack
+		0b0001_0001, // many tracks, coded frames
+		b'm', b'p', b'4', b'a', // codec
+		0x00, // track id
+		0x00, 0x00, 0x02, // size
+		0x12, 0x34, // data
+		0x01, // track id
+		0x00, 0x00, 0x01, // size
+		0x56, // data
+	]);
+
+	let data = FlvTagData::demux(8, data).unwrap();
+	assert_eq!(
+		data,
+		FlvTagData::Audio {
+			sound_rate: SoundRate::Hz44000,
+			sound_size: SoundSize::Bit16,
+			sound_type: SoundType::Stereo,
+			data: FlvTagAudioData::Multitrack(vec![
+				AudioTrack {
+					track_id: 0,
+					packet: EnhancedAudioPacket::CodedFrames {
+						audio_codec: AudioFourCC::Aac,
+						data: Bytes::from_static(&[0x12, 0x34]),
+					},
+				},
+				AudioTrack {
+					track_id: 1,
+					packet: EnhancedAudioPacket::CodedFrames {
+						audio_codec: AudioFourCC::Aac,
+						data: Bytes::from_static(&[0x56]),
+					},
+				},
+			]),
+		}
+	);
+
+	#[rustfmt::skip]
+	let data = Bytes::from_static(&[
+		0b1001_0101, // enhanced audio, multitrack
+		0b0010_0000, // many tracks many codecs, sequence start
+		b'O', b'p', b'u', b's', // codec
+		0x00, // track id
+		0x00, 0x00, 0x01, // size
+		0x12, // data
+		b'f', b'L', b'a', b'C', // codec
+		0x01, // track id
+		0x00, 0x00, 0x01, // size
+		0x34, // data
+	]);
+
+	let data = FlvTagData::demux(8, data).unwrap();
+	assert_eq!(
+		data,
+		FlvTagData::Audio {
+			sound_rate: SoundRate::Hz44000,
+			sound_size: SoundSize::Bit16,
+			sound_type: SoundType::Stereo,
+			data: FlvTagAudioData::Multitrack(vec![
+				AudioTrack {
+					track_id: 0,
+					packet: EnhancedAudioPacket::SequenceStart {
+						audio_codec: AudioFourCC::Opus,
+						data: Bytes::from_static(&[0x12]),
+					},
+				},
+				AudioTrack {
+					track_id: 1,
+					packet: EnhancedAudioPacket::SequenceStart {
+						audio_codec: AudioFourCC::Flac,
+						data: Bytes::from_static(&[0x34]),
+					},
+				},
+			]),
+		}
+	);
+}
@@ -1,4 +1,4 @@
-use crate::FlvDemuxerError;
+use crate::{FlvDemuxerError, FlvMuxerError};
 
 #[test]
 fn test_error_display() {
@@ -20,6 +20,12 @@ fn test_error_display() {
 	let error = FlvDemuxerError::InvalidEnhancedPacketType(0);
 	assert_eq!(error.to_string(), "invalid enhanced packet type: 0");
 
+	let error = FlvDemuxerError::InvalidEnhancedAudioPacketType(3);
+	assert_eq!(error.to_string(), "invalid enhanced audio packet type: 3");
+
+	let error = FlvDemuxerError::InvalidMultitrackType(3);
+	assert_eq!(error.to_string(), "invalid multitrack type: 3");
+
 	let error = FlvDemuxerError::InvalidSoundRate(0);
 	assert_eq!(error.to_string(), "invalid sound rate: 0");
 
@@ -29,3 +35,21 @@ fn test_error_display() {
 	let error = FlvDemuxerError::InvalidSoundType(0);
 	assert_eq!(error.to_string(), "invalid sound type: 0");
 }
+
+#[test]
+fn test_muxer_error_display() {
+	let error = FlvMuxerError::IO(std::io::Error::other("test"));
+	assert_eq!(error.to_string(), "io error: test");
+
+	let error = FlvMuxerError::Amf0Write(amf0::Amf0WriteError::NormalStringTooLong);
+	assert_eq!(error.to_string(), "amf0 write error: normal string too long");
+
+	let error = FlvMuxerError::TagTooLarge(0x1000000);
+	assert_eq!(error.to_string(), "tag too large: 16777216");
+
+	let error = FlvMuxerError::EmptyMultitrack;
+	assert_eq!(error.to_string(), "empty multitrack");
+
+	let error = FlvMuxerError::MultitrackPacketTypeMismatch;
+	assert_eq!(error.to_string(), "multitrack packet type mismatch");
+}
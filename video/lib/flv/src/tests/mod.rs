@@ -1,2 +1,4 @@
 mod demuxer;
 mod error;
+mod muxer;
+mod stream;
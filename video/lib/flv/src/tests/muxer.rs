@@ -0,0 +1,306 @@
+use std::io;
+use std::path::PathBuf;
+
+use amf0::{Amf0Object, Amf0Value};
+use bytes::Bytes;
+
+use crate::{
+	AacPacket, AudioFourCC, AudioTrack, Av1Packet, AvcPacket, EnhancedAudioPacket, EnhancedPacket, Flv, FlvHeader,
+	FlvMuxerError, FlvTag, FlvTagAudioData, FlvTagData, FlvTagVideoData, FrameType, HevcPacket, SoundRate, SoundSize,
+	SoundType, VideoTrack,
+};
+
+fn roundtrip(tag: FlvTag) {
+	let mut writer = Vec::new();
+	let size = tag.mux(&mut writer).unwrap();
+	assert_eq!(size as usize, writer.len());
+
+	let demuxed = FlvTag::demux(&mut io::Cursor::new(Bytes::from(writer))).unwrap();
+	assert_eq!(demuxed, tag);
+}
+
+#[test]
+fn test_mux_flv_files() {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../assets");
+
+	for file in ["avc_aac.flv", "av1_aac.flv", "hevc_aac.flv"] {
+		let data = Bytes::from(std::fs::read(dir.join(file)).expect("failed to read file"));
+		let flv = Flv::demux(&mut io::Cursor::new(data.clone())).expect("failed to demux flv");
+
+		let mut writer = Vec::new();
+		flv.mux(&mut writer).expect("failed to mux flv");
+
+		assert_eq!(Bytes::from(writer), data, "{file} was not muxed back to the same bytes");
+	}
+}
+
+#[test]
+fn test_mux_header() {
+	let header = FlvHeader {
+		version: 1,
+		has_audio: true,
+		has_video: false,
+		data_offset: 0,
+		extra: Bytes::from_static(&[0x01, 0x02]),
+	};
+
+	let mut writer = Vec::new();
+	header.mux(&mut writer).unwrap();
+
+	assert_eq!(writer, b"FLV\x01\x04\x00\x00\x00\x0b\x01\x02");
+
+	let demuxed = FlvHeader::demux(&mut io::Cursor::new(Bytes::from(writer))).unwrap();
+	assert_eq!(
+		demuxed,
+		FlvHeader {
+			data_offset: 11,
+			..header
+		}
+	);
+}
+
+#[test]
+fn test_mux_metadata() {
+	let tag = FlvTag {
+		timestamp: 0,
+		stream_id: 0,
+		data: FlvTagData::ScriptData {
+			name: "onMetaData".to_string(),
+			data: vec![Amf0Value::EcmaArray(Amf0Object::from([
+				("width".to_string(), Amf0Value::Number(1920.0)),
+				("height".to_string(), Amf0Value::Number(1080.0)),
+				("framerate".to_string(), Amf0Value::Number(30.0)),
+				("encoder".to_string(), Amf0Value::String("scuffle".to_string())),
+			]))],
+		},
+	};
+
+	roundtrip(tag);
+}
+
+#[test]
+fn test_mux_timestamp_extended() {
+	roundtrip(FlvTag {
+		timestamp: 0x1234_5678,
+		stream_id: 0,
+		data: FlvTagData::Audio {
+			sound_rate: SoundRate::Hz22000,
+			sound_size: SoundSize::Bit8,
+			sound_type: SoundType::Mono,
+			data: FlvTagAudioData::Aac(AacPacket::Raw(Bytes::from_static(&[0x01, 0x02, 0x03]))),
+		},
+	});
+}
+
+#[test]
+fn test_mux_legacy_video() {
+	for packet in [
+		AvcPacket::Nalu {
+			composition_time: 0x21,
+			data: Bytes::from_static(&[0x00, 0x00, 0x00, 0x01, 0x65]),
+		},
+		AvcPacket::EndOfSequence,
+	] {
+		roundtrip(FlvTag {
+			timestamp: 33,
+			stream_id: 0,
+			data: FlvTagData::Video {
+				frame_type: FrameType::Interframe,
+				data: FlvTagVideoData::Avc(packet),
+			},
+		});
+	}
+}
+
+#[test]
+fn test_mux_enhanced_video() {
+	for packet in [
+		EnhancedPacket::Avc(AvcPacket::Nalu {
+			composition_time: 0x21,
+			data: Bytes::from_static(&[0x01]),
+		}),
+		EnhancedPacket::Hevc(HevcPacket::Nalu {
+			composition_time: Some(-2),
+			data: Bytes::from_static(&[0x02]),
+		}),
+		EnhancedPacket::Hevc(HevcPacket::Nalu {
+			composition_time: None,
+			data: Bytes::from_static(&[0x03]),
+		}),
+		EnhancedPacket::Av1(Av1Packet::Raw(Bytes::from_static(&[0x04]))),
+		EnhancedPacket::SequenceEnd { video_codec: *b"av01" },
+		EnhancedPacket::Unknown {
+			packet_type: 1,
+			video_codec: *b"vp09",
+			data: Bytes::from_static(&[0x05]),
+		},
+	] {
+		roundtrip(FlvTag {
+			timestamp: 0,
+			stream_id: 0,
+			data: FlvTagData::Video {
+				frame_type: FrameType::Keyframe,
+				data: FlvTagVideoData::Enhanced(packet),
+			},
+		});
+	}
+
+	roundtrip(FlvTag {
+		timestamp: 0,
+		stream_id: 0,
+		data: FlvTagData::Video {
+			frame_type: FrameType::EnhancedMetadata,
+			data: FlvTagVideoData::Enhanced(EnhancedPacket::Metadata {
+				video_codec: *b"hvc1",
+				data: Bytes::from_static(&[0x02, 0x00, 0x09, b'c', b'o', b'l', b'o', b'r', b'I', b'n', b'f', b'o']),
+			}),
+		},
+	});
+}
+
+#[test]
+fn test_mux_multitrack() {
+	// One track, many tracks and many tracks with many codecs
+	for tracks in [
+		vec![VideoTrack {
+			track_id: 1,
+			packet: EnhancedPacket::Av1(Av1Packet::Raw(Bytes::from_static(&[0x01]))),
+		}],
+		vec![
+			VideoTrack {
+				track_id: 0,
+				packet: EnhancedPacket::Av1(Av1Packet::Raw(Bytes::from_static(&[0x01]))),
+			},
+			VideoTrack {
+				track_id: 1,
+				packet: EnhancedPacket::Av1(Av1Packet::Raw(Bytes::from_static(&[0x02, 0x03]))),
+			},
+		],
+		vec![
+			VideoTrack {
+				track_id: 0,
+				packet: EnhancedPacket::Avc(AvcPacket::Nalu {
+					composition_time: 0,
+					data: Bytes::from_static(&[0x01]),
+				}),
+			},
+			VideoTrack {
+				track_id: 1,
+				packet: EnhancedPacket::Hevc(HevcPacket::Nalu {
+					composition_time: Some(0),
+					data: Bytes::from_static(&[0x02]),
+				}),
+			},
+		],
+	] {
+		roundtrip(FlvTag {
+			timestamp: 0,
+			stream_id: 0,
+			data: FlvTagData::Video {
+				frame_type: FrameType::Keyframe,
+				data: FlvTagVideoData::Multitrack(tracks),
+			},
+		});
+	}
+
+	roundtrip(FlvTag {
+		timestamp: 0,
+		stream_id: 0,
+		data: FlvTagData::Audio {
+			sound_rate: SoundRate::Hz44000,
+			sound_size: SoundSize::Bit16,
+			sound_type: SoundType::Stereo,
+			data: FlvTagAudioData::Multitrack(vec![
+				AudioTrack {
+					track_id: 0,
+					packet: EnhancedAudioPacket::CodedFrames {
+						audio_codec: AudioFourCC::Opus,
+						data: Bytes::from_static(&[0x01]),
+					},
+				},
+				AudioTrack {
+					track_id: 1,
+					packet: EnhancedAudioPacket::CodedFrames {
+						audio_codec: AudioFourCC::Flac,
+						data: Bytes::from_static(&[0x02]),
+					},
+				},
+			]),
+		},
+	});
+
+	// All tracks must share a packet type
+	let tag = FlvTag {
+		timestamp: 0,
+		stream_id: 0,
+		data: FlvTagData::Video {
+			frame_type: FrameType::Keyframe,
+			data: FlvTagVideoData::Multitrack(vec![
+				VideoTrack {
+					track_id: 0,
+					packet: EnhancedPacket::Av1(Av1Packet::Raw(Bytes::new())),
+				},
+				VideoTrack {
+					track_id: 1,
+					packet: EnhancedPacket::SequenceEnd { video_codec: *b"av01" },
+				},
+			]),
+		},
+	};
+	assert!(matches!(
+		tag.mux(&mut Vec::new()),
+		Err(FlvMuxerError::MultitrackPacketTypeMismatch)
+	));
+
+	let tag = FlvTag {
+		timestamp: 0,
+		stream_id: 0,
+		data: FlvTagData::Video {
+			frame_type: FrameType::Keyframe,
+			data: FlvTagVideoData::Multitrack(vec![]),
+		},
+	};
+	assert!(matches!(tag.mux(&mut Vec::new()), Err(FlvMuxerError::EmptyMultitrack)));
+}
+
+#[test]
+fn test_mux_enhanced_audio() {
+	for packet in [
+		EnhancedAudioPacket::SequenceStart {
+			audio_codec: AudioFourCC::Opus,
+			data: Bytes::from_static(b"OpusHead"),
+		},
+		EnhancedAudioPacket::CodedFrames {
+			audio_codec: AudioFourCC::Ac3,
+			data: Bytes::from_static(&[0x0b, 0x77]),
+		},
+		EnhancedAudioPacket::SequenceEnd {
+			audio_codec: AudioFourCC::Flac,
+		},
+	] {
+		roundtrip(FlvTag {
+			timestamp: 0,
+			stream_id: 0,
+			data: FlvTagData::Audio {
+				sound_rate: SoundRate::Hz44000,
+				sound_size: SoundSize::Bit16,
+				sound_type: SoundType::Stereo,
+				data: FlvTagAudioData::Enhanced(packet),
+			},
+		});
+	}
+}
+
+#[test]
+fn test_mux_mp3() {
+	roundtrip(FlvTag {
+		timestamp: 26,
+		stream_id: 0,
+		data: FlvTagData::Audio {
+			sound_rate: SoundRate::Hz44000,
+			sound_size: SoundSize::Bit16,
+			sound_type: SoundType::Stereo,
+			data: FlvTagAudioData::Mp3(Bytes::from_static(&[0xFF, 0xFB, 0x90, 0x64])),
+		},
+	});
+}
@@ -0,0 +1,113 @@
+use std::io;
+use std::path::PathBuf;
+
+use bytes::Bytes;
+
+use crate::{Flv, FlvDemuxerError, FlvStreamDemuxer, FlvTag};
+
+fn read_asset(name: &str) -> Bytes {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../assets");
+	Bytes::from(std::fs::read(dir.join(name)).expect("failed to read file"))
+}
+
+fn read_tags(demuxer: &mut FlvStreamDemuxer, data: &[u8], chunk_size: usize) -> Vec<FlvTag> {
+	let mut tags = Vec::new();
+
+	for chunk in data.chunks(chunk_size) {
+		demuxer.extend_data(chunk);
+		while let Some(tag) = demuxer.read_tag().expect("failed to read tag") {
+			tags.push(tag);
+		}
+	}
+
+	tags
+}
+
+#[test]
+fn test_stream_demux_chunks() {
+	for file in ["avc_aac.flv", "av1_aac.flv", "hevc_aac.flv"] {
+		let data = read_asset(file);
+		let flv = Flv::demux(&mut io::Cursor::new(data.clone())).expect("failed to demux flv");
+
+		for chunk_size in [1, 7, 188, 4096, data.len()] {
+			let mut demuxer = FlvStreamDemuxer::new();
+			let tags = read_tags(&mut demuxer, &data, chunk_size);
+
+			assert_eq!(demuxer.header(), Some(&flv.header));
+			assert_eq!(tags, flv.tags, "{file} with chunks of {chunk_size} bytes");
+			assert_eq!(demuxer.skipped_bytes(), 0);
+		}
+	}
+}
+
+#[test]
+fn test_stream_demux_without_header() {
+	let data = read_asset("avc_aac.flv");
+	let flv = Flv::demux(&mut io::Cursor::new(data.clone())).expect("failed to demux flv");
+
+	let mut demuxer = FlvStreamDemuxer::without_header();
+	let tags = read_tags(&mut demuxer, &data[flv.header.data_offset as usize..], 1000);
+
+	assert_eq!(demuxer.header(), None);
+	assert_eq!(tags, flv.tags);
+}
+
+#[test]
+fn test_stream_demux_resync() {
+	let data = read_asset("avc_aac.flv");
+	let flv = Flv::demux(&mut io::Cursor::new(data.clone())).expect("failed to demux flv");
+
+	// The offset of the 4th tag, after the header and the previous tag size of the first tag.
+	let mut offset = flv.header.data_offset as usize + 4;
+	for tag in &flv.tags[..3] {
+		let mut writer = Vec::new();
+		offset += tag.mux(&mut writer).unwrap() as usize + 4;
+	}
+
+	// Garbage between two tags, the first part is a tag with the wrong previous tag size
+	// and the second part claims to be a huge tag.
+	#[rustfmt::skip]
+	let garbage = [
+		0x08, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xAA, 0x00, 0x00, 0x00, 0x00,
+		0x09, 0xFF, 0x00, 0x12, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	];
+
+	let mut corrupt = data[..offset].to_vec();
+	corrupt.extend_from_slice(&garbage);
+	corrupt.extend_from_slice(&data[offset..]);
+
+	for chunk_size in [1, 100, 1024] {
+		let mut demuxer = FlvStreamDemuxer::new();
+		let tags = read_tags(&mut demuxer, &corrupt, chunk_size);
+
+		assert_eq!(tags, flv.tags);
+		assert_eq!(demuxer.skipped_bytes(), garbage.len() as u64);
+	}
+
+	// A tag with a corrupt data size is dropped
+	let mut corrupt = data.to_vec();
+	corrupt[offset + 3] ^= 0x01;
+
+	let mut demuxer = FlvStreamDemuxer::new();
+	let tags = read_tags(&mut demuxer, &corrupt, 1024);
+
+	let mut expected = flv.tags.clone();
+	expected.remove(3);
+	assert_eq!(tags, expected);
+	assert!(demuxer.skipped_bytes() > 0);
+}
+
+#[test]
+fn test_stream_demux_invalid_header() {
+	let mut demuxer = FlvStreamDemuxer::new();
+
+	demuxer.extend_data(b"FL");
+	assert!(demuxer.read_tag().unwrap().is_none());
+
+	demuxer.extend_data(b"X\x01\x05\x00\x00\x00\x09");
+	assert!(matches!(demuxer.read_tag(), Err(FlvDemuxerError::InvalidFlvHeader)));
+
+	let mut demuxer = FlvStreamDemuxer::new();
+	demuxer.extend_data(b"FLV\x01\x05\x00\x00\x00\x08");
+	assert!(matches!(demuxer.read_tag(), Err(FlvDemuxerError::InvalidFlvHeader)));
+}
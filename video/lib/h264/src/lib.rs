@@ -1,8 +1,20 @@
 mod config;
+mod nal;
+mod pps;
+mod sei;
+mod slice;
 mod sps;
+mod vui;
 
 pub use self::config::{AVCDecoderConfigurationRecord, AvccExtendedConfig};
-pub use self::sps::{ColorConfig, Sps, SpsExtended};
+pub use self::nal::{NalUnit, NalUnitType, NalUnits};
+pub use self::pps::Pps;
+pub use self::sei::{
+	CaptionData, CcData, CcType, ClockTimestamp, PicTiming, SeiMessage, SeiPayloadType, UserDataUnregistered,
+};
+pub use self::slice::{SliceHeader, SliceType};
+pub use self::sps::{ColorConfig, FrameCropping, Sps, SpsExtended};
+pub use self::vui::{BitstreamRestriction, CpbSpecification, HrdParameters, TimingInfo, Vui};
 
 #[cfg(test)]
 mod tests;
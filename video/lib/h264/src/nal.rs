@@ -0,0 +1,243 @@
+use std::io;
+
+use bytes::Bytes;
+use bytesio::emulation_prevention::remove_emulation_prevention;
+
+#[derive(Debug, Clone, PartialEq)]
+/// NAL unit
+/// ISO/IEC-14496-10-2022 - 7.3.1
+pub struct NalUnit {
+	pub nal_ref_idc: u8,
+	pub nal_unit_type: NalUnitType,
+	/// The whole NAL unit including the header
+	pub data: Bytes,
+}
+
+impl NalUnit {
+	pub fn parse(data: Bytes) -> io::Result<Self> {
+		let Some(&header) = data.first() else {
+			return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "NAL unit is empty"));
+		};
+
+		if header & 0b1000_0000 != 0 {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "Forbidden zero bit is set"));
+		}
+
+		Ok(Self {
+			nal_ref_idc: (header >> 5) & 0b11,
+			nal_unit_type: NalUnitType::from(header & 0b1_1111),
+			data,
+		})
+	}
+
+	/// If this NAL unit contains a slice of an IDR picture
+	pub fn is_idr(&self) -> bool {
+		self.nal_unit_type == NalUnitType::IdrSlice
+	}
+
+	/// The raw byte sequence payload, this is the NAL unit without the header
+	/// and without the emulation prevention bytes.
+	pub fn rbsp(&self) -> Bytes {
+		let header_len = match self.nal_unit_type {
+			// The SVC and MVC extensions have 3 more header bytes
+			// ISO/IEC-14496-10-2022 - 7.3.1
+			NalUnitType::Prefix | NalUnitType::SliceExtension | NalUnitType::SliceExtensionDepth => 4,
+			_ => 1,
+		};
+
+		Bytes::from(remove_emulation_prevention(self.data.get(header_len..).unwrap_or_default()))
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// NAL unit type
+/// ISO/IEC-14496-10-2022 - 7.4.1 - Table 7-1
+pub enum NalUnitType {
+	NonIdrSlice,
+	SliceDataPartitionA,
+	SliceDataPartitionB,
+	SliceDataPartitionC,
+	IdrSlice,
+	Sei,
+	Sps,
+	Pps,
+	AccessUnitDelimiter,
+	EndOfSequence,
+	EndOfStream,
+	FillerData,
+	SpsExtension,
+	Prefix,
+	SubsetSps,
+	DepthParameterSet,
+	AuxiliarySlice,
+	SliceExtension,
+	SliceExtensionDepth,
+	Unspecified(u8),
+	Reserved(u8),
+}
+
+impl From<u8> for NalUnitType {
+	fn from(value: u8) -> Self {
+		match value {
+			1 => NalUnitType::NonIdrSlice,
+			2 => NalUnitType::SliceDataPartitionA,
+			3 => NalUnitType::SliceDataPartitionB,
+			4 => NalUnitType::SliceDataPartitionC,
+			5 => NalUnitType::IdrSlice,
+			6 => NalUnitType::Sei,
+			7 => NalUnitType::Sps,
+			8 => NalUnitType::Pps,
+			9 => NalUnitType::AccessUnitDelimiter,
+			10 => NalUnitType::EndOfSequence,
+			11 => NalUnitType::EndOfStream,
+			12 => NalUnitType::FillerData,
+			13 => NalUnitType::SpsExtension,
+			14 => NalUnitType::Prefix,
+			15 => NalUnitType::SubsetSps,
+			16 => NalUnitType::DepthParameterSet,
+			19 => NalUnitType::AuxiliarySlice,
+			20 => NalUnitType::SliceExtension,
+			21 => NalUnitType::SliceExtensionDepth,
+			0 | 24..=31 => NalUnitType::Unspecified(value),
+			_ => NalUnitType::Reserved(value),
+		}
+	}
+}
+
+impl From<NalUnitType> for u8 {
+	fn from(value: NalUnitType) -> Self {
+		match value {
+			NalUnitType::NonIdrSlice => 1,
+			NalUnitType::SliceDataPartitionA => 2,
+			NalUnitType::SliceDataPartitionB => 3,
+			NalUnitType::SliceDataPartitionC => 4,
+			NalUnitType::IdrSlice => 5,
+			NalUnitType::Sei => 6,
+			NalUnitType::Sps => 7,
+			NalUnitType::Pps => 8,
+			NalUnitType::AccessUnitDelimiter => 9,
+			NalUnitType::EndOfSequence => 10,
+			NalUnitType::EndOfStream => 11,
+			NalUnitType::FillerData => 12,
+			NalUnitType::SpsExtension => 13,
+			NalUnitType::Prefix => 14,
+			NalUnitType::SubsetSps => 15,
+			NalUnitType::DepthParameterSet => 16,
+			NalUnitType::AuxiliarySlice => 19,
+			NalUnitType::SliceExtension => 20,
+			NalUnitType::SliceExtensionDepth => 21,
+			NalUnitType::Unspecified(value) => value,
+			NalUnitType::Reserved(value) => value,
+		}
+	}
+}
+
+/// An iterator over the NAL units of an access unit.
+pub struct NalUnits {
+	data: Bytes,
+	framing: Framing,
+}
+
+enum Framing {
+	/// Every NAL unit has a big endian length prefix of this size
+	/// ISO/IEC 14496-15:2022(E) - 5.3.3
+	Avcc(u8),
+	/// NAL units are separated by start codes
+	/// ISO/IEC-14496-10-2022 - B.1
+	AnnexB,
+}
+
+impl NalUnits {
+	/// NAL units with a length prefix, like in MP4 and FLV. The length size is
+	/// `length_size_minus_one + 1` from the decoder configuration record.
+	pub fn avcc(data: Bytes, length_size: u8) -> Self {
+		Self {
+			data,
+			framing: Framing::Avcc(length_size),
+		}
+	}
+
+	/// NAL units with start codes, like in MPEG-TS. Anything in front of the
+	/// first start code is ignored.
+	pub fn annex_b(mut data: Bytes) -> Self {
+		let start = find_start_code(&data).map_or(data.len(), |(_, end)| end);
+		data = data.slice(start..);
+
+		Self {
+			data,
+			framing: Framing::AnnexB,
+		}
+	}
+
+	fn next_avcc(&mut self, length_size: u8) -> io::Result<Bytes> {
+		let length_size = length_size as usize;
+		if length_size == 0 || length_size > 4 || self.data.len() < length_size {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid NAL unit length prefix"));
+		}
+
+		let length = self.data[..length_size]
+			.iter()
+			.fold(0, |length, byte| (length << 8) | *byte as usize);
+
+		if self.data.len() < length_size + length {
+			return Err(io::Error::new(
+				io::ErrorKind::UnexpectedEof,
+				"NAL unit is longer than the remaining data",
+			));
+		}
+
+		let nal_unit = self.data.slice(length_size..length_size + length);
+		self.data = self.data.slice(length_size + length..);
+
+		Ok(nal_unit)
+	}
+
+	fn next_annex_b(&mut self) -> Bytes {
+		let nal_unit = match find_start_code(&self.data) {
+			Some((start, end)) => {
+				let nal_unit = self.data.slice(..start);
+				self.data = self.data.slice(end..);
+				nal_unit
+			}
+			None => std::mem::take(&mut self.data),
+		};
+
+		// The zero of a 4 byte start code and trailing_zero_8bits end up at the
+		// end of the NAL unit, which never ends with a zero byte itself.
+		let end = nal_unit.iter().rposition(|b| *b != 0).map_or(0, |end| end + 1);
+		nal_unit.slice(..end)
+	}
+}
+
+impl Iterator for NalUnits {
+	type Item = io::Result<NalUnit>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while !self.data.is_empty() {
+			let nal_unit = match self.framing {
+				Framing::Avcc(length_size) => match self.next_avcc(length_size) {
+					Ok(nal_unit) => nal_unit,
+					Err(err) => {
+						// We can not find the next NAL unit after an error
+						self.data.clear();
+						return Some(Err(err));
+					}
+				},
+				Framing::AnnexB => self.next_annex_b(),
+			};
+
+			if !nal_unit.is_empty() {
+				return Some(NalUnit::parse(nal_unit));
+			}
+		}
+
+		None
+	}
+}
+
+/// Returns the start and end of the first 3 byte start code.
+fn find_start_code(data: &[u8]) -> Option<(usize, usize)> {
+	data.windows(3)
+		.position(|window| window == [0, 0, 1])
+		.map(|start| (start, start + 3))
+}
@@ -0,0 +1,112 @@
+use std::io;
+
+use bytes::Bytes;
+use bytesio::bit_reader::BitReader;
+use exp_golomb::{read_exp_golomb, read_signed_exp_golomb};
+
+use crate::nal::{NalUnit, NalUnitType};
+
+#[derive(Debug, Clone, PartialEq)]
+/// Picture parameter set
+/// ISO/IEC-14496-10-2022 - 7.3.2.2
+///
+/// The fields after `redundant_pic_cnt_present_flag` are only used for
+/// decoding and are not parsed.
+pub struct Pps {
+	pub pic_parameter_set_id: u64,
+	pub seq_parameter_set_id: u64,
+	pub entropy_coding_mode_flag: bool,
+	pub bottom_field_pic_order_in_frame_present_flag: bool,
+	pub num_slice_groups_minus1: u64,
+	pub num_ref_idx_l0_default_active_minus1: u64,
+	pub num_ref_idx_l1_default_active_minus1: u64,
+	pub weighted_pred_flag: bool,
+	pub weighted_bipred_idc: u8,
+	pub pic_init_qp_minus26: i64,
+	pub pic_init_qs_minus26: i64,
+	pub chroma_qp_index_offset: i64,
+	pub deblocking_filter_control_present_flag: bool,
+	pub constrained_intra_pred_flag: bool,
+	pub redundant_pic_cnt_present_flag: bool,
+}
+
+impl Pps {
+	pub fn parse(data: Bytes) -> io::Result<Self> {
+		let nal_unit = NalUnit::parse(data)?;
+		if nal_unit.nal_unit_type != NalUnitType::Pps {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "NAL unit type is not PPS"));
+		}
+
+		let mut bit_reader = BitReader::from(nal_unit.rbsp());
+
+		let pic_parameter_set_id = read_exp_golomb(&mut bit_reader)?;
+		let seq_parameter_set_id = read_exp_golomb(&mut bit_reader)?;
+		let entropy_coding_mode_flag = bit_reader.read_bit()?;
+		let bottom_field_pic_order_in_frame_present_flag = bit_reader.read_bit()?;
+
+		let num_slice_groups_minus1 = read_exp_golomb(&mut bit_reader)?;
+		if num_slice_groups_minus1 > 7 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"num_slice_groups_minus1 is out of range",
+			));
+		}
+
+		if num_slice_groups_minus1 > 0 {
+			let slice_group_map_type = read_exp_golomb(&mut bit_reader)?;
+			match slice_group_map_type {
+				0 => {
+					for _ in 0..=num_slice_groups_minus1 {
+						read_exp_golomb(&mut bit_reader)?; // run_length_minus1
+					}
+				}
+				2 => {
+					for _ in 0..num_slice_groups_minus1 {
+						read_exp_golomb(&mut bit_reader)?; // top_left
+						read_exp_golomb(&mut bit_reader)?; // bottom_right
+					}
+				}
+				3..=5 => {
+					bit_reader.seek_bits(1)?; // slice_group_change_direction_flag
+					read_exp_golomb(&mut bit_reader)?; // slice_group_change_rate_minus1
+				}
+				6 => {
+					let pic_size_in_map_units_minus1 = read_exp_golomb(&mut bit_reader)?;
+					// Ceil(Log2(num_slice_groups_minus1 + 1))
+					let bits = 64 - num_slice_groups_minus1.leading_zeros() as i64;
+					bit_reader.seek_bits(bits * (pic_size_in_map_units_minus1 as i64 + 1))?; // slice_group_id
+				}
+				_ => {}
+			}
+		}
+
+		let num_ref_idx_l0_default_active_minus1 = read_exp_golomb(&mut bit_reader)?;
+		let num_ref_idx_l1_default_active_minus1 = read_exp_golomb(&mut bit_reader)?;
+		let weighted_pred_flag = bit_reader.read_bit()?;
+		let weighted_bipred_idc = bit_reader.read_bits(2)? as u8;
+		let pic_init_qp_minus26 = read_signed_exp_golomb(&mut bit_reader)?;
+		let pic_init_qs_minus26 = read_signed_exp_golomb(&mut bit_reader)?;
+		let chroma_qp_index_offset = read_signed_exp_golomb(&mut bit_reader)?;
+		let deblocking_filter_control_present_flag = bit_reader.read_bit()?;
+		let constrained_intra_pred_flag = bit_reader.read_bit()?;
+		let redundant_pic_cnt_present_flag = bit_reader.read_bit()?;
+
+		Ok(Pps {
+			pic_parameter_set_id,
+			seq_parameter_set_id,
+			entropy_coding_mode_flag,
+			bottom_field_pic_order_in_frame_present_flag,
+			num_slice_groups_minus1,
+			num_ref_idx_l0_default_active_minus1,
+			num_ref_idx_l1_default_active_minus1,
+			weighted_pred_flag,
+			weighted_bipred_idc,
+			pic_init_qp_minus26,
+			pic_init_qs_minus26,
+			chroma_qp_index_offset,
+			deblocking_filter_control_present_flag,
+			constrained_intra_pred_flag,
+			redundant_pic_cnt_present_flag,
+		})
+	}
+}
@@ -0,0 +1,360 @@
+use std::io::{
+	Read, {self},
+};
+
+use byteorder::{BigEndian, ReadBytesExt};
+use bytes::{Buf, Bytes};
+use bytesio::bit_reader::BitReader;
+
+use crate::nal::{NalUnit, NalUnitType};
+use crate::sps::Sps;
+
+#[derive(Debug, Clone, PartialEq)]
+/// SEI message
+/// ISO/IEC-14496-10-2022 - 7.3.2.3.1
+pub struct SeiMessage {
+	pub payload_type: SeiPayloadType,
+	pub payload: Bytes,
+}
+
+impl SeiMessage {
+	/// Parses every SEI message in a SEI NAL unit.
+	pub fn parse(data: Bytes) -> io::Result<Vec<Self>> {
+		let nal_unit = NalUnit::parse(data)?;
+		if nal_unit.nal_unit_type != NalUnitType::Sei {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "NAL unit type is not SEI"));
+		}
+
+		let mut reader = BitReader::from(nal_unit.rbsp());
+		let mut messages = Vec::new();
+
+		while reader.more_rbsp_data() {
+			let payload_type = read_sei_value(&mut reader)?;
+			let payload_size = read_sei_value(&mut reader)? as usize;
+
+			if reader.remaining_bits() / 8 < payload_size {
+				return Err(io::Error::new(
+					io::ErrorKind::UnexpectedEof,
+					"SEI payload is longer than the remaining data",
+				));
+			}
+
+			messages.push(SeiMessage {
+				payload_type: SeiPayloadType::from(payload_type),
+				payload: reader.get_mut().copy_to_bytes(payload_size),
+			});
+		}
+
+		Ok(messages)
+	}
+}
+
+/// The payload type and size are coded as a sum of bytes, every 0xFF byte
+/// adds 255 and the first other byte ends the value.
+fn read_sei_value(reader: &mut impl io::Read) -> io::Result<u32> {
+	let mut value = 0;
+	loop {
+		let byte = reader.read_u8()?;
+		value += byte as u32;
+		if byte != 0xFF {
+			return Ok(value);
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// SEI payload type
+/// ISO/IEC-14496-10-2022 - 7.3.2.3.1
+pub enum SeiPayloadType {
+	BufferingPeriod,
+	PicTiming,
+	UserDataRegisteredItuTT35,
+	UserDataUnregistered,
+	RecoveryPoint,
+	Other(u32),
+}
+
+impl From<u32> for SeiPayloadType {
+	fn from(value: u32) -> Self {
+		match value {
+			0 => SeiPayloadType::BufferingPeriod,
+			1 => SeiPayloadType::PicTiming,
+			4 => SeiPayloadType::UserDataRegisteredItuTT35,
+			5 => SeiPayloadType::UserDataUnregistered,
+			6 => SeiPayloadType::RecoveryPoint,
+			_ => SeiPayloadType::Other(value),
+		}
+	}
+}
+
+impl From<SeiPayloadType> for u32 {
+	fn from(value: SeiPayloadType) -> Self {
+		match value {
+			SeiPayloadType::BufferingPeriod => 0,
+			SeiPayloadType::PicTiming => 1,
+			SeiPayloadType::UserDataRegisteredItuTT35 => 4,
+			SeiPayloadType::UserDataUnregistered => 5,
+			SeiPayloadType::RecoveryPoint => 6,
+			SeiPayloadType::Other(value) => value,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Picture timing SEI message
+/// ISO/IEC-14496-10-2022 - D.1.3
+pub struct PicTiming {
+	/// Only present if the SPS has HRD parameters
+	pub cpb_removal_delay: Option<u32>,
+	/// Only present if the SPS has HRD parameters
+	pub dpb_output_delay: Option<u32>,
+	/// Only present if the pic_struct_present_flag is set in the SPS
+	pub pic_struct: Option<u8>,
+	pub clock_timestamps: Vec<ClockTimestamp>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A timecode of a field or frame in a picture timing SEI message. The
+/// seconds, minutes and hours which are not present have the value of the
+/// previous timestamp.
+/// ISO/IEC-14496-10-2022 - D.1.3
+pub struct ClockTimestamp {
+	pub ct_type: u8,
+	pub nuit_field_based_flag: bool,
+	pub counting_type: u8,
+	pub discontinuity_flag: bool,
+	pub cnt_dropped_flag: bool,
+	pub n_frames: u8,
+	pub seconds: Option<u8>,
+	pub minutes: Option<u8>,
+	pub hours: Option<u8>,
+	pub time_offset: i32,
+}
+
+impl PicTiming {
+	/// The SPS is the one which is active for the picture.
+	pub fn parse(payload: Bytes, sps: &Sps) -> io::Result<Self> {
+		let mut bit_reader = BitReader::from(payload);
+
+		let (cpb_removal_delay, dpb_output_delay) = match &sps.hrd_parameters {
+			Some(hrd) => (
+				Some(bit_reader.read_bits(hrd.cpb_removal_delay_length_minus1 + 1)? as u32),
+				Some(bit_reader.read_bits(hrd.dpb_output_delay_length_minus1 + 1)? as u32),
+			),
+			None => (None, None),
+		};
+
+		let mut pic_struct = None;
+		let mut clock_timestamps = Vec::new();
+
+		if sps.pic_struct_present_flag {
+			let value = bit_reader.read_bits(4)? as u8;
+
+			// NumClockTS
+			// ISO/IEC-14496-10-2022 - D.2.3 - Table D-1
+			let num_clock_ts = match value {
+				0..=2 => 1,
+				3 | 4 | 7 => 2,
+				5 | 6 | 8 => 3,
+				_ => return Err(io::Error::new(io::ErrorKind::InvalidData, "pic_struct is reserved")),
+			};
+
+			let time_offset_length = sps.hrd_parameters.as_ref().map_or(24, |hrd| hrd.time_offset_length);
+
+			for _ in 0..num_clock_ts {
+				// clock_timestamp_flag
+				if bit_reader.read_bit()? {
+					clock_timestamps.push(ClockTimestamp::parse(&mut bit_reader, time_offset_length)?);
+				}
+			}
+
+			pic_struct = Some(value);
+		}
+
+		Ok(PicTiming {
+			cpb_removal_delay,
+			dpb_output_delay,
+			pic_struct,
+			clock_timestamps,
+		})
+	}
+}
+
+impl ClockTimestamp {
+	fn parse(bit_reader: &mut BitReader, time_offset_length: u8) -> io::Result<Self> {
+		let ct_type = bit_reader.read_bits(2)? as u8;
+		let nuit_field_based_flag = bit_reader.read_bit()?;
+		let counting_type = bit_reader.read_bits(5)? as u8;
+		let full_timestamp_flag = bit_reader.read_bit()?;
+		let discontinuity_flag = bit_reader.read_bit()?;
+		let cnt_dropped_flag = bit_reader.read_bit()?;
+		let n_frames = bit_reader.read_u8()?;
+
+		let mut seconds = None;
+		let mut minutes = None;
+		let mut hours = None;
+
+		if full_timestamp_flag {
+			seconds = Some(bit_reader.read_bits(6)? as u8);
+			minutes = Some(bit_reader.read_bits(6)? as u8);
+			hours = Some(bit_reader.read_bits(5)? as u8);
+		} else if bit_reader.read_bit()? {
+			// seconds_flag
+			seconds = Some(bit_reader.read_bits(6)? as u8);
+			// minutes_flag
+			if bit_reader.read_bit()? {
+				minutes = Some(bit_reader.read_bits(6)? as u8);
+				// hours_flag
+				if bit_reader.read_bit()? {
+					hours = Some(bit_reader.read_bits(5)? as u8);
+				}
+			}
+		}
+
+		let time_offset = if time_offset_length > 0 {
+			// i(v), sign extend the value
+			let value = bit_reader.read_bits(time_offset_length)? as i64;
+			let shift = 64 - time_offset_length as u32;
+			((value << shift) >> shift) as i32
+		} else {
+			0
+		};
+
+		Ok(ClockTimestamp {
+			ct_type,
+			nuit_field_based_flag,
+			counting_type,
+			discontinuity_flag,
+			cnt_dropped_flag,
+			n_frames,
+			seconds,
+			minutes,
+			hours,
+			time_offset,
+		})
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// User data unregistered SEI message, encoders use it for their version
+/// string and other private data.
+/// ISO/IEC-14496-10-2022 - D.1.7
+pub struct UserDataUnregistered {
+	pub uuid_iso_iec_11578: [u8; 16],
+	pub data: Bytes,
+}
+
+impl UserDataUnregistered {
+	pub fn parse(mut payload: Bytes) -> io::Result<Self> {
+		if payload.len() < 16 {
+			return Err(io::Error::new(
+				io::ErrorKind::UnexpectedEof,
+				"user data unregistered SEI message is too short",
+			));
+		}
+
+		let mut uuid_iso_iec_11578 = [0; 16];
+		payload.copy_to_slice(&mut uuid_iso_iec_11578);
+
+		Ok(UserDataUnregistered {
+			uuid_iso_iec_11578,
+			data: payload,
+		})
+	}
+}
+
+/// The ITU-T T.35 country code of the United States
+const ITU_T_T35_COUNTRY_CODE_US: u8 = 0xB5;
+/// The ITU-T T.35 provider code of the ATSC
+const ITU_T_T35_PROVIDER_CODE_ATSC: u16 = 0x0031;
+/// ATSC A/53 Part 4 - 6.2.3 - Table 6.4
+const ATSC_USER_IDENTIFIER: u32 = u32::from_be_bytes(*b"GA94");
+/// ATSC A/53 Part 4 - 6.2.3 - Table 6.5
+const ATSC_USER_DATA_TYPE_CC_DATA: u8 = 0x03;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Closed captions in a user data registered by ITU-T T.35 SEI message.
+/// ATSC A/53 Part 4 - 6.2.3
+/// ANSI/CTA-708-E - 4.4
+pub struct CaptionData {
+	pub process_cc_data_flag: bool,
+	pub em_data: u8,
+	pub cc_data: Vec<CcData>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A caption data packet, this is a pair of CEA-608 bytes or part of a CEA-708
+/// caption channel packet.
+/// ANSI/CTA-708-E - 4.4
+pub struct CcData {
+	pub cc_valid: bool,
+	pub cc_type: CcType,
+	pub cc_data_1: u8,
+	pub cc_data_2: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// ANSI/CTA-708-E - 4.4 - Table 3
+pub enum CcType {
+	/// CEA-608 field 1
+	Ntsc608Field1,
+	/// CEA-608 field 2
+	Ntsc608Field2,
+	/// Continuation of a CEA-708 caption channel packet
+	DtvccPacketData,
+	/// Start of a CEA-708 caption channel packet
+	DtvccPacketStart,
+}
+
+impl CaptionData {
+	/// Parses the payload of a user data registered by ITU-T T.35 SEI message,
+	/// returns `None` if the payload is not ATSC closed caption data.
+	pub fn parse(payload: Bytes) -> io::Result<Option<Self>> {
+		let mut reader = io::Cursor::new(payload);
+
+		let itu_t_t35_country_code = reader.read_u8()?;
+		let itu_t_t35_provider_code = reader.read_u16::<BigEndian>()?;
+		if itu_t_t35_country_code != ITU_T_T35_COUNTRY_CODE_US || itu_t_t35_provider_code != ITU_T_T35_PROVIDER_CODE_ATSC {
+			return Ok(None);
+		}
+
+		let user_identifier = reader.read_u32::<BigEndian>()?;
+		let user_data_type_code = reader.read_u8()?;
+		if user_identifier != ATSC_USER_IDENTIFIER || user_data_type_code != ATSC_USER_DATA_TYPE_CC_DATA {
+			return Ok(None);
+		}
+
+		let mut bit_reader = BitReader::new(reader);
+
+		bit_reader.seek_bits(1)?; // reserved
+		let process_cc_data_flag = bit_reader.read_bit()?;
+		bit_reader.seek_bits(1)?; // zero_bit
+		let cc_count = bit_reader.read_bits(5)?;
+		let em_data = bit_reader.read_u8()?;
+
+		let mut cc_data = Vec::with_capacity(cc_count as usize);
+		for _ in 0..cc_count {
+			let mut data = [0; 3];
+			bit_reader.read_exact(&mut data)?;
+
+			// The first 5 bits are marker_bits
+			cc_data.push(CcData {
+				cc_valid: data[0] & 0b100 != 0,
+				cc_type: match data[0] & 0b11 {
+					0 => CcType::Ntsc608Field1,
+					1 => CcType::Ntsc608Field2,
+					2 => CcType::DtvccPacketData,
+					_ => CcType::DtvccPacketStart,
+				},
+				cc_data_1: data[1],
+				cc_data_2: data[2],
+			});
+		}
+
+		Ok(Some(CaptionData {
+			process_cc_data_flag,
+			em_data,
+			cc_data,
+		}))
+	}
+}
@@ -0,0 +1,173 @@
+use std::io;
+
+use bytesio::bit_reader::BitReader;
+use bytesio::emulation_prevention::remove_emulation_prevention;
+use exp_golomb::{read_exp_golomb, read_signed_exp_golomb};
+
+use crate::nal::{NalUnit, NalUnitType};
+use crate::pps::Pps;
+use crate::sps::Sps;
+
+/// The parsed fields of a slice header are always within this many bytes, so
+/// we do not have to remove the emulation prevention bytes of the whole slice.
+const MAX_SLICE_HEADER_SIZE: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Slice header
+/// ISO/IEC-14496-10-2022 - 7.3.3
+///
+/// Only the fields up to `redundant_pic_cnt` are parsed, these are the ones
+/// needed to find the first slice of a picture and its picture order count.
+pub struct SliceHeader {
+	pub first_mb_in_slice: u64,
+	pub slice_type: SliceType,
+	/// If every slice of the picture has the same slice type
+	pub slice_type_fixed: bool,
+	pub pic_parameter_set_id: u64,
+	/// Only used if the separate_colour_plane_flag is set in the SPS
+	pub colour_plane_id: u8,
+	pub frame_num: u64,
+	pub field_pic_flag: bool,
+	pub bottom_field_flag: bool,
+	/// Only present in IDR pictures
+	pub idr_pic_id: Option<u64>,
+	/// Only used if the pic_order_cnt_type is 0
+	pub pic_order_cnt_lsb: u64,
+	pub delta_pic_order_cnt_bottom: i64,
+	/// Only used if the pic_order_cnt_type is 1
+	pub delta_pic_order_cnt: [i64; 2],
+	pub redundant_pic_cnt: u64,
+}
+
+impl SliceHeader {
+	/// Parses the header of a slice NAL unit, the SPS and PPS are the ones
+	/// referenced by the `pic_parameter_set_id` of the slice.
+	pub fn parse(nal_unit: &NalUnit, sps: &Sps, pps: &Pps) -> io::Result<Self> {
+		if !matches!(
+			nal_unit.nal_unit_type,
+			NalUnitType::NonIdrSlice | NalUnitType::SliceDataPartitionA | NalUnitType::IdrSlice
+		) {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "NAL unit type is not a slice"));
+		}
+
+		let end = nal_unit.data.len().min(MAX_SLICE_HEADER_SIZE + 1);
+		let mut bit_reader = BitReader::from(remove_emulation_prevention(&nal_unit.data[1..end]));
+
+		let first_mb_in_slice = read_exp_golomb(&mut bit_reader)?;
+
+		let slice_type = read_exp_golomb(&mut bit_reader)?;
+		if slice_type > 9 {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "slice_type is out of range"));
+		}
+
+		let pic_parameter_set_id = read_exp_golomb(&mut bit_reader)?;
+		if pic_parameter_set_id != pps.pic_parameter_set_id {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "slice references a different PPS"));
+		}
+
+		let separate_colour_plane_flag = sps.ext.as_ref().is_some_and(|ext| ext.separate_colour_plane_flag);
+		let colour_plane_id = if separate_colour_plane_flag {
+			bit_reader.read_bits(2)? as u8
+		} else {
+			0
+		};
+
+		let frame_num = bit_reader.read_bits(sps.log2_max_frame_num_minus4 as u8 + 4)?;
+
+		let mut field_pic_flag = false;
+		let mut bottom_field_flag = false;
+		if !sps.frame_mbs_only_flag {
+			field_pic_flag = bit_reader.read_bit()?;
+			if field_pic_flag {
+				bottom_field_flag = bit_reader.read_bit()?;
+			}
+		}
+
+		let idr_pic_id = if nal_unit.is_idr() {
+			Some(read_exp_golomb(&mut bit_reader)?)
+		} else {
+			None
+		};
+
+		let mut pic_order_cnt_lsb = 0;
+		let mut delta_pic_order_cnt_bottom = 0;
+		let mut delta_pic_order_cnt = [0; 2];
+
+		if sps.pic_order_cnt_type == 0 {
+			pic_order_cnt_lsb = bit_reader.read_bits(sps.log2_max_pic_order_cnt_lsb_minus4 as u8 + 4)?;
+			if pps.bottom_field_pic_order_in_frame_present_flag && !field_pic_flag {
+				delta_pic_order_cnt_bottom = read_signed_exp_golomb(&mut bit_reader)?;
+			}
+		} else if sps.pic_order_cnt_type == 1 && !sps.delta_pic_order_always_zero_flag {
+			delta_pic_order_cnt[0] = read_signed_exp_golomb(&mut bit_reader)?;
+			if pps.bottom_field_pic_order_in_frame_present_flag && !field_pic_flag {
+				delta_pic_order_cnt[1] = read_signed_exp_golomb(&mut bit_reader)?;
+			}
+		}
+
+		let redundant_pic_cnt = if pps.redundant_pic_cnt_present_flag {
+			read_exp_golomb(&mut bit_reader)?
+		} else {
+			0
+		};
+
+		Ok(SliceHeader {
+			first_mb_in_slice,
+			slice_type: SliceType::from(slice_type as u8 % 5),
+			slice_type_fixed: slice_type > 4,
+			pic_parameter_set_id,
+			colour_plane_id,
+			frame_num,
+			field_pic_flag,
+			bottom_field_flag,
+			idr_pic_id,
+			pic_order_cnt_lsb,
+			delta_pic_order_cnt_bottom,
+			delta_pic_order_cnt,
+			redundant_pic_cnt,
+		})
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Slice type
+/// ISO/IEC-14496-10-2022 - 7.4.3 - Table 7-6
+pub enum SliceType {
+	P,
+	B,
+	I,
+	Sp,
+	Si,
+}
+
+impl SliceType {
+	/// If the slice only uses intra prediction
+	pub fn is_intra(&self) -> bool {
+		matches!(self, SliceType::I | SliceType::Si)
+	}
+}
+
+impl From<u8> for SliceType {
+	/// The value is the slice_type modulo 5
+	fn from(value: u8) -> Self {
+		match value {
+			0 => SliceType::P,
+			1 => SliceType::B,
+			2 => SliceType::I,
+			3 => SliceType::Sp,
+			_ => SliceType::Si,
+		}
+	}
+}
+
+impl From<SliceType> for u8 {
+	fn from(value: SliceType) -> Self {
+		match value {
+			SliceType::P => 0,
+			SliceType::B => 1,
+			SliceType::I => 2,
+			SliceType::Sp => 3,
+			SliceType::Si => 4,
+		}
+	}
+}
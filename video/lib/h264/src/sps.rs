@@ -1,21 +1,59 @@
 use std::io;
 
-use byteorder::{BigEndian, ReadBytesExt};
+use byteorder::{ReadBytesExt, WriteBytesExt};
 use bytes::Bytes;
 use bytesio::bit_reader::BitReader;
-use exp_golomb::{read_exp_golomb, read_signed_exp_golomb};
+use bytesio::bit_writer::BitWriter;
+use bytesio::emulation_prevention::{add_emulation_prevention, remove_emulation_prevention};
+use exp_golomb::{read_exp_golomb, read_signed_exp_golomb, write_exp_golomb, write_signed_exp_golomb};
+
+use crate::vui::{HrdParameters, Vui};
 
 #[derive(Debug, Clone, PartialEq)]
 /// Sequence parameter set
 /// ISO/IEC-14496-10-2022 - 7.3.2
+///
+/// The fields after `vui` are derived from the syntax elements when the SPS is
+/// parsed, they are not used when the SPS is written.
 pub struct Sps {
 	pub profile_idc: u8,
+	/// The constraint_set0_flag to constraint_set5_flag and the
+	/// reserved_zero_2bits
+	pub constraint_set_flags: u8,
 	pub level_idc: u8,
+	pub seq_parameter_set_id: u64,
 	pub ext: Option<SpsExtended>,
+	pub log2_max_frame_num_minus4: u64,
+	pub pic_order_cnt_type: u64,
+	/// Only used if the pic_order_cnt_type is 0
+	pub log2_max_pic_order_cnt_lsb_minus4: u64,
+	/// Only used if the pic_order_cnt_type is 1
+	pub delta_pic_order_always_zero_flag: bool,
+	/// Only used if the pic_order_cnt_type is 1
+	pub offset_for_non_ref_pic: i64,
+	/// Only used if the pic_order_cnt_type is 1
+	pub offset_for_top_to_bottom_field: i64,
+	/// Only used if the pic_order_cnt_type is 1
+	pub offset_for_ref_frame: Vec<i64>,
+	pub max_num_ref_frames: u64,
+	pub gaps_in_frame_num_value_allowed_flag: bool,
+	pub pic_width_in_mbs_minus1: u64,
+	pub pic_height_in_map_units_minus1: u64,
+	pub frame_mbs_only_flag: bool,
+	/// Only used if the frame_mbs_only_flag is not set
+	pub mb_adaptive_frame_field_flag: bool,
+	pub direct_8x8_inference_flag: bool,
+	/// Only present if the frame_cropping_flag is set
+	pub frame_cropping: Option<FrameCropping>,
+	/// Only present if the vui_parameters_present_flag is set
+	pub vui: Option<Vui>,
 	pub width: u64,
 	pub height: u64,
 	pub frame_rate: f64,
 	pub color_config: Option<ColorConfig>,
+	/// The NAL or VCL HRD parameters from the VUI
+	pub hrd_parameters: Option<HrdParameters>,
+	pub pic_struct_present_flag: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -27,24 +65,21 @@ pub struct ColorConfig {
 	pub matrix_coefficients: u8,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+/// The frame cropping offsets in units of chroma samples
+pub struct FrameCropping {
+	pub frame_crop_left_offset: u64,
+	pub frame_crop_right_offset: u64,
+	pub frame_crop_top_offset: u64,
+	pub frame_crop_bottom_offset: u64,
+}
+
 impl Sps {
 	pub fn parse(data: Bytes) -> io::Result<Self> {
-		let mut vec = Vec::with_capacity(data.len());
-
 		// We need to remove the emulation prevention byte
 		// This is BARELY documented in the spec, but it's there.
 		// ISO/IEC-14496-10-2022 - 3.1.48
-		let mut i = 0;
-		while i < data.len() - 3 {
-			if data[i] == 0x00 && data[i + 1] == 0x00 && data[i + 2] == 0x03 {
-				vec.push(0x00);
-				vec.push(0x00);
-				i += 3;
-			} else {
-				vec.push(data[i]);
-				i += 1;
-			}
-		}
+		let vec = remove_emulation_prevention(&data);
 
 		let mut bit_reader = BitReader::from(vec);
 
@@ -61,188 +96,312 @@ impl Sps {
 		}
 
 		let profile_idc = bit_reader.read_u8()?;
-		bit_reader.seek_bits(
-			1 // constraint_set0_flag
-            + 1 // constraint_set1_flag
-            + 1 // constraint_set2_flag
-            + 1 // constraint_set3_flag
-            + 4, // reserved_zero_4bits
-		)?;
-
+		let constraint_set_flags = bit_reader.read_u8()?;
 		let level_idc = bit_reader.read_u8()?;
-		read_exp_golomb(&mut bit_reader)?; // seq_parameter_set_id
+		let seq_parameter_set_id = read_exp_golomb(&mut bit_reader)?;
 
-		let sps_ext = match profile_idc {
-			100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135 => {
-				Some(SpsExtended::parse(&mut bit_reader)?)
-			}
-			_ => None,
+		let sps_ext = if has_sps_extended(profile_idc) {
+			Some(SpsExtended::parse(&mut bit_reader)?)
+		} else {
+			None
 		};
 
-		read_exp_golomb(&mut bit_reader)?; // log2_max_frame_num_minus4
+		let log2_max_frame_num_minus4 = read_exp_golomb(&mut bit_reader)?;
+		if log2_max_frame_num_minus4 > 12 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"log2_max_frame_num_minus4 is out of range",
+			));
+		}
+
 		let pic_order_cnt_type = read_exp_golomb(&mut bit_reader)?;
+		let mut log2_max_pic_order_cnt_lsb_minus4 = 0;
+		let mut delta_pic_order_always_zero_flag = false;
+		let mut offset_for_non_ref_pic = 0;
+		let mut offset_for_top_to_bottom_field = 0;
+		let mut offset_for_ref_frame = Vec::new();
 		if pic_order_cnt_type == 0 {
-			read_exp_golomb(&mut bit_reader)?; // log2_max_pic_order_cnt_lsb_minus4
+			log2_max_pic_order_cnt_lsb_minus4 = read_exp_golomb(&mut bit_reader)?;
+			if log2_max_pic_order_cnt_lsb_minus4 > 12 {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					"log2_max_pic_order_cnt_lsb_minus4 is out of range",
+				));
+			}
 		} else if pic_order_cnt_type == 1 {
-			bit_reader.seek_bits(1)?; // delta_pic_order_always_zero_flag
-			read_signed_exp_golomb(&mut bit_reader)?; // offset_for_non_ref_pic
-			read_signed_exp_golomb(&mut bit_reader)?; // offset_for_top_to_bottom_field
+			delta_pic_order_always_zero_flag = bit_reader.read_bit()?;
+			offset_for_non_ref_pic = read_signed_exp_golomb(&mut bit_reader)?;
+			offset_for_top_to_bottom_field = read_signed_exp_golomb(&mut bit_reader)?;
 			let num_ref_frames_in_pic_order_cnt_cycle = read_exp_golomb(&mut bit_reader)?;
+			if num_ref_frames_in_pic_order_cnt_cycle > 255 {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					"num_ref_frames_in_pic_order_cnt_cycle is out of range",
+				));
+			}
+
 			for _ in 0..num_ref_frames_in_pic_order_cnt_cycle {
-				read_signed_exp_golomb(&mut bit_reader)?; // offset_for_ref_frame
+				offset_for_ref_frame.push(read_signed_exp_golomb(&mut bit_reader)?);
 			}
 		}
 
-		read_exp_golomb(&mut bit_reader)?; // max_num_ref_frames
-		bit_reader.read_bit()?; // gaps_in_frame_num_value_allowed_flag
-		let pic_width_in_mbs_minus1 = read_exp_golomb(&mut bit_reader)?; // pic_width_in_mbs_minus1
-		let pic_height_in_map_units_minus1 = read_exp_golomb(&mut bit_reader)?; // pic_height_in_map_units_minus1
+		let max_num_ref_frames = read_exp_golomb(&mut bit_reader)?;
+		let gaps_in_frame_num_value_allowed_flag = bit_reader.read_bit()?;
+		let pic_width_in_mbs_minus1 = read_exp_golomb(&mut bit_reader)?;
+		let pic_height_in_map_units_minus1 = read_exp_golomb(&mut bit_reader)?;
 		let frame_mbs_only_flag = bit_reader.read_bit()?;
-		if !frame_mbs_only_flag {
-			bit_reader.seek_bits(1)?; // mb_adaptive_frame_field_flag
-		}
-
-		bit_reader.seek_bits(1)?; // direct_8x8_inference_flag
-
-		let mut frame_crop_left_offset = 0;
-		let mut frame_crop_right_offset = 0;
-		let mut frame_crop_top_offset = 0;
-		let mut frame_crop_bottom_offset = 0;
-
-		if bit_reader.read_bit()? {
-			// frame_cropping_flag
-			frame_crop_left_offset = read_exp_golomb(&mut bit_reader)?; // frame_crop_left_offset
-			frame_crop_right_offset = read_exp_golomb(&mut bit_reader)?; // frame_crop_right_offset
-			frame_crop_top_offset = read_exp_golomb(&mut bit_reader)?; // frame_crop_top_offset
-			frame_crop_bottom_offset = read_exp_golomb(&mut bit_reader)?; // frame_crop_bottom_offset
-		}
+		let mb_adaptive_frame_field_flag = if !frame_mbs_only_flag { bit_reader.read_bit()? } else { false };
+
+		let direct_8x8_inference_flag = bit_reader.read_bit()?;
+
+		// frame_cropping_flag
+		let frame_cropping = if bit_reader.read_bit()? {
+			Some(FrameCropping {
+				frame_crop_left_offset: read_exp_golomb(&mut bit_reader)?,
+				frame_crop_right_offset: read_exp_golomb(&mut bit_reader)?,
+				frame_crop_top_offset: read_exp_golomb(&mut bit_reader)?,
+				frame_crop_bottom_offset: read_exp_golomb(&mut bit_reader)?,
+			})
+		} else {
+			None
+		};
 
-		let width = ((pic_width_in_mbs_minus1 + 1) * 16) - frame_crop_right_offset * 2 - frame_crop_left_offset * 2;
-		let height = ((2 - frame_mbs_only_flag as u64) * (pic_height_in_map_units_minus1 + 1) * 16)
-			- frame_crop_bottom_offset * 2
-			- frame_crop_top_offset * 2;
+		let (crop_width, crop_height) = frame_cropping.as_ref().map_or((0, 0), |cropping| {
+			(
+				cropping.frame_crop_left_offset + cropping.frame_crop_right_offset,
+				cropping.frame_crop_top_offset + cropping.frame_crop_bottom_offset,
+			)
+		});
 
-		let mut frame_rate = 0.0;
+		let width = ((pic_width_in_mbs_minus1 + 1) * 16) - crop_width * 2;
+		let height = ((2 - frame_mbs_only_flag as u64) * (pic_height_in_map_units_minus1 + 1) * 16) - crop_height * 2;
 
 		let vui_parameters_present_flag = bit_reader.read_bit()?;
 
-		let mut color_config = None;
+		// We do want to read the VUI parameters to get the frame rate.
+		let vui = if vui_parameters_present_flag {
+			Some(Vui::parse(&mut bit_reader)?)
+		} else {
+			None
+		};
 
-		if vui_parameters_present_flag {
-			// We do want to read the VUI parameters to get the frame rate.
+		let frame_rate = vui.as_ref().map_or(0.0, Vui::frame_rate);
+		let color_config = vui.as_ref().and_then(|vui| vui.color_config.clone());
+		// The NAL and VCL HRD parameters must use the same field lengths, so
+		// we only keep the first one.
+		let hrd_parameters = vui
+			.as_ref()
+			.and_then(|vui| vui.nal_hrd_parameters.clone().or_else(|| vui.vcl_hrd_parameters.clone()));
+		let pic_struct_present_flag = vui.as_ref().is_some_and(|vui| vui.pic_struct_present_flag);
 
-			// aspect_ratio_info_present_flag
-			if bit_reader.read_bit()? {
-				let aspect_ratio_idc = bit_reader.read_u8()?;
-				if aspect_ratio_idc == 255 {
-					bit_reader.seek_bits(16)?; // sar_width
-					bit_reader.seek_bits(16)?; // sar_height
-				}
+		Ok(Sps {
+			profile_idc,
+			constraint_set_flags,
+			level_idc,
+			seq_parameter_set_id,
+			ext: sps_ext,
+			log2_max_frame_num_minus4,
+			pic_order_cnt_type,
+			log2_max_pic_order_cnt_lsb_minus4,
+			delta_pic_order_always_zero_flag,
+			offset_for_non_ref_pic,
+			offset_for_top_to_bottom_field,
+			offset_for_ref_frame,
+			max_num_ref_frames,
+			gaps_in_frame_num_value_allowed_flag,
+			pic_width_in_mbs_minus1,
+			pic_height_in_map_units_minus1,
+			frame_mbs_only_flag,
+			mb_adaptive_frame_field_flag,
+			direct_8x8_inference_flag,
+			frame_cropping,
+			vui,
+			width,
+			height,
+			frame_rate,
+			color_config,
+			hrd_parameters,
+			pic_struct_present_flag,
+		})
+	}
+
+	/// Writes the SPS NAL unit with emulation prevention, the NAL unit header
+	/// is written with a nal_ref_idc of 3.
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		let mut bit_writer = BitWriter::default();
+
+		bit_writer.write_bit(false)?; // forbidden_zero_bit
+		bit_writer.write_bits(3, 2)?; // nal_ref_idc
+		bit_writer.write_bits(7, 5)?; // nal_unit_type
+
+		bit_writer.write_u8(self.profile_idc)?;
+		bit_writer.write_u8(self.constraint_set_flags)?;
+		bit_writer.write_u8(self.level_idc)?;
+		write_exp_golomb(&mut bit_writer, self.seq_parameter_set_id)?;
+
+		match (&self.ext, has_sps_extended(self.profile_idc)) {
+			(Some(ext), true) => ext.mux(&mut bit_writer)?,
+			(None, false) => {}
+			_ => {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidInput,
+					"the SPS extension does not match the profile_idc",
+				));
 			}
+		}
 
-			// overscan_info_present_flag
-			if bit_reader.read_bit()? {
-				bit_reader.seek_bits(1)?; // overscan_appropriate_flag
+		write_exp_golomb(&mut bit_writer, self.log2_max_frame_num_minus4)?;
+		write_exp_golomb(&mut bit_writer, self.pic_order_cnt_type)?;
+		if self.pic_order_cnt_type == 0 {
+			write_exp_golomb(&mut bit_writer, self.log2_max_pic_order_cnt_lsb_minus4)?;
+		} else if self.pic_order_cnt_type == 1 {
+			bit_writer.write_bit(self.delta_pic_order_always_zero_flag)?;
+			write_signed_exp_golomb(&mut bit_writer, self.offset_for_non_ref_pic)?;
+			write_signed_exp_golomb(&mut bit_writer, self.offset_for_top_to_bottom_field)?;
+			write_exp_golomb(&mut bit_writer, self.offset_for_ref_frame.len() as u64)?;
+			for offset_for_ref_frame in &self.offset_for_ref_frame {
+				write_signed_exp_golomb(&mut bit_writer, *offset_for_ref_frame)?;
 			}
+		}
 
-			// video_signal_type_present_flag
-			if bit_reader.read_bit()? {
-				bit_reader.seek_bits(3)?; // video_format
-				let full_range = bit_reader.read_bit()?; // video_full_range_flag
+		write_exp_golomb(&mut bit_writer, self.max_num_ref_frames)?;
+		bit_writer.write_bit(self.gaps_in_frame_num_value_allowed_flag)?;
+		write_exp_golomb(&mut bit_writer, self.pic_width_in_mbs_minus1)?;
+		write_exp_golomb(&mut bit_writer, self.pic_height_in_map_units_minus1)?;
+		bit_writer.write_bit(self.frame_mbs_only_flag)?;
+		if !self.frame_mbs_only_flag {
+			bit_writer.write_bit(self.mb_adaptive_frame_field_flag)?;
+		}
 
-				let color_primaries;
-				let transfer_characteristics;
-				let matrix_coefficients;
+		bit_writer.write_bit(self.direct_8x8_inference_flag)?;
 
-				if bit_reader.read_bit()? {
-					// colour_description_present_flag
-					color_primaries = bit_reader.read_u8()?; // colour_primaries
-					transfer_characteristics = bit_reader.read_u8()?; // transfer_characteristics
-					matrix_coefficients = bit_reader.read_u8()?; // matrix_coefficients
-				} else {
-					color_primaries = 2; // UNSPECIFIED
-					transfer_characteristics = 2; // UNSPECIFIED
-					matrix_coefficients = 2; // UNSPECIFIED
-				}
+		bit_writer.write_bit(self.frame_cropping.is_some())?;
+		if let Some(cropping) = &self.frame_cropping {
+			write_exp_golomb(&mut bit_writer, cropping.frame_crop_left_offset)?;
+			write_exp_golomb(&mut bit_writer, cropping.frame_crop_right_offset)?;
+			write_exp_golomb(&mut bit_writer, cropping.frame_crop_top_offset)?;
+			write_exp_golomb(&mut bit_writer, cropping.frame_crop_bottom_offset)?;
+		}
 
-				color_config = Some(ColorConfig {
-					full_range,
-					color_primaries,
-					transfer_characteristics,
-					matrix_coefficients,
-				});
-			}
+		bit_writer.write_bit(self.vui.is_some())?;
+		if let Some(vui) = &self.vui {
+			vui.mux(&mut bit_writer)?;
+		}
 
-			// chroma_loc_info_present_flag
-			if bit_reader.read_bit()? {
-				read_exp_golomb(&mut bit_reader)?; // chroma_sample_loc_type_top_field
-				read_exp_golomb(&mut bit_reader)?; // chroma_sample_loc_type_bottom_field
-			}
+		bit_writer.write_rbsp_trailing_bits()?;
 
-			// timing_info_present_flag
-			if bit_reader.read_bit()? {
-				let num_units_in_tick = bit_reader.read_u32::<BigEndian>()?;
-				let time_scale = bit_reader.read_u32::<BigEndian>()?;
-				frame_rate = time_scale as f64 / (2.0 * num_units_in_tick as f64);
-			}
-		}
+		writer.write_all(&add_emulation_prevention(&bit_writer.into_inner()))
+	}
 
-		Ok(Sps {
-			profile_idc,
-			level_idc,
-			ext: sps_ext,
-			width,
-			height,
-			frame_rate,
-			color_config,
-		})
+	/// Writes the SPS NAL unit to a new buffer.
+	pub fn build(&self) -> io::Result<Bytes> {
+		let mut data = Vec::new();
+		self.mux(&mut data)?;
+		Ok(Bytes::from(data))
 	}
 }
 
+/// The profiles which have the chroma format and bit depth in the SPS
+fn has_sps_extended(profile_idc: u8) -> bool {
+	matches!(
+		profile_idc,
+		100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135
+	)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 /// Sequence parameter set extension.
 /// ISO/IEC-14496-10-2022 - 7.3.2
 pub struct SpsExtended {
-	pub chroma_format_idc: u64,       // ue(v)
-	pub bit_depth_luma_minus8: u64,   // ue(v)
-	pub bit_depth_chroma_minus8: u64, // ue(v)
+	pub chroma_format_idc: u64,                     // ue(v)
+	pub separate_colour_plane_flag: bool,           // u(1)
+	pub bit_depth_luma_minus8: u64,                 // ue(v)
+	pub bit_depth_chroma_minus8: u64,               // ue(v)
+	pub qpprime_y_zero_transform_bypass_flag: bool, // u(1)
+	/// The delta_scale values of every scaling list, only present if the
+	/// seq_scaling_matrix_present_flag is set. A list is `None` if its
+	/// seq_scaling_list_present_flag is not set.
+	pub seq_scaling_lists: Option<Vec<Option<Vec<i64>>>>,
 }
 
 impl SpsExtended {
 	pub fn parse(reader: &mut BitReader) -> io::Result<Self> {
 		let chroma_format_idc = read_exp_golomb(reader)?;
-		if chroma_format_idc == 3 {
-			reader.seek_bits(1)?;
-		}
+		let separate_colour_plane_flag = if chroma_format_idc == 3 { reader.read_bit()? } else { false };
 
 		let bit_depth_luma_minus8 = read_exp_golomb(reader)?;
 		let bit_depth_chroma_minus8 = read_exp_golomb(reader)?;
-		reader.seek_bits(1)?; // qpprime_y_zero_transform_bypass_flag
+		let qpprime_y_zero_transform_bypass_flag = reader.read_bit()?;
 
-		if reader.read_bit()? {
+		let seq_scaling_lists = if reader.read_bit()? {
 			// seq_scaling_matrix_present_flag
-			// We need to read the scaling matrices here, but we don't need them
-			// for decoding, so we just skip them.
+			// We don't need the scaling matrices for anything, but we keep the
+			// delta values so that the SPS can be written again.
 			let count = if chroma_format_idc != 3 { 8 } else { 12 };
+			let mut scaling_lists = Vec::with_capacity(count);
 			for i in 0..count {
 				if reader.read_bit()? {
 					let size = if i < 6 { 16 } else { 64 };
 					let mut next_scale = 8;
+					let mut delta_scales = Vec::new();
 					for _ in 0..size {
 						let delta_scale = read_signed_exp_golomb(reader)?;
+						delta_scales.push(delta_scale);
 						next_scale = (next_scale + delta_scale + 256) % 256;
 						if next_scale == 0 {
 							break;
 						}
 					}
+
+					scaling_lists.push(Some(delta_scales));
+				} else {
+					scaling_lists.push(None);
 				}
 			}
-		}
+
+			Some(scaling_lists)
+		} else {
+			None
+		};
 
 		Ok(SpsExtended {
 			chroma_format_idc,
+			separate_colour_plane_flag,
 			bit_depth_luma_minus8,
 			bit_depth_chroma_minus8,
+			qpprime_y_zero_transform_bypass_flag,
+			seq_scaling_lists,
 		})
 	}
+
+	pub fn mux(&self, bit_writer: &mut BitWriter) -> io::Result<()> {
+		write_exp_golomb(bit_writer, self.chroma_format_idc)?;
+		if self.chroma_format_idc == 3 {
+			bit_writer.write_bit(self.separate_colour_plane_flag)?;
+		}
+
+		write_exp_golomb(bit_writer, self.bit_depth_luma_minus8)?;
+		write_exp_golomb(bit_writer, self.bit_depth_chroma_minus8)?;
+		bit_writer.write_bit(self.qpprime_y_zero_transform_bypass_flag)?;
+
+		bit_writer.write_bit(self.seq_scaling_lists.is_some())?;
+		if let Some(scaling_lists) = &self.seq_scaling_lists {
+			let count = if self.chroma_format_idc != 3 { 8 } else { 12 };
+			if scaling_lists.len() != count {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidInput,
+					"the number of scaling lists does not match the chroma_format_idc",
+				));
+			}
+
+			for scaling_list in scaling_lists {
+				bit_writer.write_bit(scaling_list.is_some())?;
+				for delta_scale in scaling_list.iter().flatten() {
+					write_signed_exp_golomb(bit_writer, *delta_scale)?;
+				}
+			}
+		}
+
+		Ok(())
+	}
 }
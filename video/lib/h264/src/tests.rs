@@ -3,7 +3,12 @@ use std::io;
 use bytes::Bytes;
 
 use crate::config::{AVCDecoderConfigurationRecord, AvccExtendedConfig};
+use crate::nal::{NalUnitType, NalUnits};
+use crate::pps::Pps;
+use crate::sei::{CaptionData, CcData, CcType, PicTiming, SeiMessage, SeiPayloadType, UserDataUnregistered};
+use crate::slice::{SliceHeader, SliceType};
 use crate::sps::{ColorConfig, Sps, SpsExtended};
+use crate::vui::TimingInfo;
 
 #[test]
 fn test_parse_sps() {
@@ -19,8 +24,11 @@ fn test_parse_sps() {
 		sps.ext,
 		Some(SpsExtended {
 			chroma_format_idc: 1,
+			separate_colour_plane_flag: false,
 			bit_depth_luma_minus8: 0,
 			bit_depth_chroma_minus8: 0,
+			qpprime_y_zero_transform_bypass_flag: false,
+			seq_scaling_lists: None,
 		})
 	);
 	assert_eq!(sps.width, 3840);
@@ -60,8 +68,11 @@ fn test_parse_sps3() {
 		sps.ext,
 		Some(SpsExtended {
 			chroma_format_idc: 1,
+			separate_colour_plane_flag: false,
 			bit_depth_luma_minus8: 0,
 			bit_depth_chroma_minus8: 0,
+			qpprime_y_zero_transform_bypass_flag: false,
+			seq_scaling_lists: None,
 		})
 	);
 	assert_eq!(sps.width, 1920);
@@ -111,8 +122,11 @@ fn test_config_demux() {
 		sps.ext,
 		Some(SpsExtended {
 			chroma_format_idc: 1,
+			separate_colour_plane_flag: false,
 			bit_depth_luma_minus8: 0,
 			bit_depth_chroma_minus8: 0,
+			qpprime_y_zero_transform_bypass_flag: false,
+			seq_scaling_lists: None,
 		})
 	);
 
@@ -143,3 +157,240 @@ fn test_config_mux() {
 
 	assert_eq!(buf, data.to_vec());
 }
+
+const SPS: &[u8] = &[
+	103, 100, 0, 51, 172, 202, 80, 15, 0, 16, 251, 1, 16, 0, 0, 3, 0, 16, 0, 0, 7, 136, 241, 131, 25, 96,
+];
+const PPS: &[u8] = &[0x68, 0xe9, 0x3b, 0x2c, 0x8b];
+/// The start of the first IDR and non-IDR slices of avc_aac.flv
+const IDR_SLICE: &[u8] = &[0x65, 0x88, 0x84, 0x00, 0x54, 0xbf, 0xfe, 0xb5, 0xdf, 0x32, 0xc5, 0x44];
+const NON_IDR_SLICE: &[u8] = &[0x41, 0x9a, 0x20, 0x5b, 0x11, 0x14, 0xbf, 0x00, 0x00, 0x03, 0x00, 0x00];
+
+#[test]
+fn test_parse_sps_slice_fields() {
+	let sps = Sps::parse(Bytes::from_static(SPS)).unwrap();
+
+	assert_eq!(sps.seq_parameter_set_id, 0);
+	assert_eq!(sps.log2_max_frame_num_minus4, 0);
+	assert_eq!(sps.pic_order_cnt_type, 0);
+	assert_eq!(sps.log2_max_pic_order_cnt_lsb_minus4, 4);
+	assert!(sps.frame_mbs_only_flag);
+	assert_eq!(sps.hrd_parameters, None);
+	assert!(!sps.pic_struct_present_flag);
+}
+
+#[test]
+fn test_sps_build() {
+	let sps_list: [&[u8]; 3] = [
+		SPS,
+		&[
+			0x67, 0x42, 0xc0, 0x1f, 0x8c, 0x8d, 0x40, 0x50, 0x1e, 0x90, 0x0f, 0x08, 0x84, 0x6a,
+		],
+		&[
+			103, 100, 0, 42, 172, 178, 0, 240, 4, 79, 203, 128, 181, 1, 1, 1, 64, 0, 0, 3, 0, 64, 0, 0, 30, 35, 198, 12, 146,
+		],
+	];
+
+	for data in sps_list {
+		let sps = Sps::parse(Bytes::from_static(data)).unwrap();
+		assert_eq!(sps.build().unwrap(), data);
+	}
+}
+
+#[test]
+fn test_sps_rewrite_vui() {
+	// Fix the colour description
+	let mut sps = Sps::parse(Bytes::from_static(SPS)).unwrap();
+	sps.vui.as_mut().unwrap().color_config = Some(ColorConfig {
+		full_range: true,
+		color_primaries: 9,
+		transfer_characteristics: 16,
+		matrix_coefficients: 9,
+	});
+
+	let rewritten = Sps::parse(sps.build().unwrap()).unwrap();
+	assert_eq!(rewritten.color_config, sps.vui.as_ref().unwrap().color_config);
+	assert_eq!(rewritten.vui, sps.vui);
+	assert_eq!(rewritten.width, 3840);
+	assert_eq!(rewritten.height, 2160);
+	assert_eq!(rewritten.frame_rate, 60.0);
+
+	// Add the timing info to an SPS which only has the bitstream restriction
+	let mut sps = Sps::parse(Bytes::from_static(&[
+		0x67, 0x42, 0xc0, 0x1f, 0x8c, 0x8d, 0x40, 0x50, 0x1e, 0x90, 0x0f, 0x08, 0x84, 0x6a,
+	]))
+	.unwrap();
+	assert_eq!(sps.frame_rate, 0.0);
+
+	let vui = sps.vui.as_mut().unwrap();
+	assert!(vui.bitstream_restriction.is_some());
+	vui.timing_info = Some(TimingInfo {
+		num_units_in_tick: 1001,
+		time_scale: 60000,
+		fixed_frame_rate_flag: true,
+	});
+
+	let rewritten = Sps::parse(sps.build().unwrap()).unwrap();
+	assert_eq!(rewritten.vui, sps.vui);
+	assert_eq!(rewritten.width, 640);
+	assert_eq!(rewritten.height, 480);
+	assert_eq!(rewritten.frame_rate, 60000.0 / 2002.0);
+}
+
+#[test]
+fn test_parse_pps() {
+	let pps = Pps::parse(Bytes::from_static(PPS)).unwrap();
+
+	assert_eq!(pps.pic_parameter_set_id, 0);
+	assert_eq!(pps.seq_parameter_set_id, 0);
+	assert!(pps.entropy_coding_mode_flag);
+	assert!(!pps.bottom_field_pic_order_in_frame_present_flag);
+	assert_eq!(pps.num_slice_groups_minus1, 0);
+	assert_eq!(pps.num_ref_idx_l0_default_active_minus1, 3);
+	assert_eq!(pps.num_ref_idx_l1_default_active_minus1, 0);
+	assert!(pps.weighted_pred_flag);
+	assert_eq!(pps.weighted_bipred_idc, 2);
+	assert_eq!(pps.pic_init_qp_minus26, 0);
+	assert_eq!(pps.pic_init_qs_minus26, 0);
+	assert_eq!(pps.chroma_qp_index_offset, -2);
+	assert!(pps.deblocking_filter_control_present_flag);
+	assert!(!pps.constrained_intra_pred_flag);
+	assert!(!pps.redundant_pic_cnt_present_flag);
+
+	assert!(Pps::parse(Bytes::from_static(SPS)).is_err());
+}
+
+#[test]
+fn test_parse_slice_header() {
+	let sps = Sps::parse(Bytes::from_static(SPS)).unwrap();
+	let pps = Pps::parse(Bytes::from_static(PPS)).unwrap();
+
+	let mut nal_units = NalUnits::annex_b(Bytes::from([&[0, 0, 0, 1], IDR_SLICE, &[0, 0, 1], NON_IDR_SLICE].concat()));
+
+	let idr = nal_units.next().unwrap().unwrap();
+	assert!(idr.is_idr());
+
+	let header = SliceHeader::parse(&idr, &sps, &pps).unwrap();
+	assert_eq!(header.first_mb_in_slice, 0);
+	assert_eq!(header.slice_type, SliceType::I);
+	assert!(header.slice_type_fixed);
+	assert_eq!(header.frame_num, 0);
+	assert_eq!(header.idr_pic_id, Some(0));
+	assert_eq!(header.pic_order_cnt_lsb, 0);
+
+	let non_idr = nal_units.next().unwrap().unwrap();
+	assert!(!non_idr.is_idr());
+
+	let header = SliceHeader::parse(&non_idr, &sps, &pps).unwrap();
+	assert_eq!(header.first_mb_in_slice, 0);
+	assert_eq!(header.slice_type, SliceType::P);
+	assert_eq!(header.frame_num, 1);
+	assert_eq!(header.idr_pic_id, None);
+	assert_eq!(header.pic_order_cnt_lsb, 2);
+
+	assert!(nal_units.next().is_none());
+}
+
+#[test]
+fn test_nal_units_annex_b() {
+	let data = Bytes::from([&[0, 0, 0, 1], SPS, &[0, 0, 0, 1], PPS, &[0, 0, 1], IDR_SLICE, &[0, 0]].concat());
+
+	let nal_units = NalUnits::annex_b(data).collect::<io::Result<Vec<_>>>().unwrap();
+
+	assert_eq!(nal_units.len(), 3);
+	assert_eq!(nal_units[0].nal_unit_type, NalUnitType::Sps);
+	assert_eq!(nal_units[0].nal_ref_idc, 3);
+	assert_eq!(nal_units[0].data, SPS);
+	assert_eq!(nal_units[1].nal_unit_type, NalUnitType::Pps);
+	assert_eq!(nal_units[1].data, PPS);
+	assert_eq!(nal_units[2].nal_unit_type, NalUnitType::IdrSlice);
+	assert_eq!(nal_units[2].data, IDR_SLICE);
+}
+
+#[test]
+fn test_nal_units_avcc() {
+	let data = Bytes::from([&[0, 0, 0, 5], PPS, &[0, 0, 0, 12], IDR_SLICE].concat());
+
+	let nal_units = NalUnits::avcc(data.clone(), 4).collect::<io::Result<Vec<_>>>().unwrap();
+
+	assert_eq!(nal_units.len(), 2);
+	assert_eq!(nal_units[0].nal_unit_type, NalUnitType::Pps);
+	assert_eq!(nal_units[0].data, PPS);
+	assert_eq!(nal_units[1].nal_unit_type, NalUnitType::IdrSlice);
+	assert_eq!(nal_units[1].data, IDR_SLICE);
+
+	// The length of the last NAL unit is longer than the data
+	let mut nal_units = NalUnits::avcc(data.slice(..data.len() - 1), 4);
+	assert!(nal_units.next().unwrap().is_ok());
+	assert!(nal_units.next().unwrap().is_err());
+	assert!(nal_units.next().is_none());
+}
+
+#[test]
+fn test_parse_sei() {
+	// A user data unregistered, a CEA-608 caption and a picture timing message
+	let data = Bytes::from_static(&[
+		0x06, 0x05, 0x1f, 0xdc, 0x45, 0xe9, 0xbd, 0xe6, 0xd9, 0x48, 0xb7, 0x96, 0x2c, 0xd8, 0x20, 0xd9, 0x23, 0xee, 0xef,
+		0x78, 0x32, 0x36, 0x34, 0x20, 0x2d, 0x20, 0x63, 0x6f, 0x72, 0x65, 0x20, 0x31, 0x36, 0x34, 0x04, 0x11, 0xb5, 0x00,
+		0x31, 0x47, 0x41, 0x39, 0x34, 0x03, 0xc2, 0xff, 0xfc, 0x94, 0x2c, 0xfd, 0x80, 0x80, 0xff, 0x01, 0x09, 0x09, 0x04,
+		0x0c, 0x78, 0xf0, 0x80, 0x00, 0x00, 0x03, 0x00, 0x80,
+	]);
+
+	let messages = SeiMessage::parse(data).unwrap();
+	assert_eq!(messages.len(), 3);
+
+	assert_eq!(messages[0].payload_type, SeiPayloadType::UserDataUnregistered);
+	let user_data = UserDataUnregistered::parse(messages[0].payload.clone()).unwrap();
+	assert_eq!(
+		user_data.uuid_iso_iec_11578,
+		[
+			0xdc, 0x45, 0xe9, 0xbd, 0xe6, 0xd9, 0x48, 0xb7, 0x96, 0x2c, 0xd8, 0x20, 0xd9, 0x23, 0xee, 0xef
+		]
+	);
+	assert_eq!(user_data.data, "x264 - core 164");
+
+	assert_eq!(messages[1].payload_type, SeiPayloadType::UserDataRegisteredItuTT35);
+	let captions = CaptionData::parse(messages[1].payload.clone()).unwrap().unwrap();
+	assert!(captions.process_cc_data_flag);
+	assert_eq!(
+		captions.cc_data,
+		vec![
+			CcData {
+				cc_valid: true,
+				cc_type: CcType::Ntsc608Field1,
+				cc_data_1: 0x94,
+				cc_data_2: 0x2c,
+			},
+			CcData {
+				cc_valid: true,
+				cc_type: CcType::Ntsc608Field2,
+				cc_data_1: 0x80,
+				cc_data_2: 0x80,
+			},
+		]
+	);
+
+	assert_eq!(messages[2].payload_type, SeiPayloadType::PicTiming);
+	let sps = Sps {
+		pic_struct_present_flag: true,
+		..Sps::parse(Bytes::from_static(SPS)).unwrap()
+	};
+	let pic_timing = PicTiming::parse(messages[2].payload.clone(), &sps).unwrap();
+	assert_eq!(pic_timing.cpb_removal_delay, None);
+	assert_eq!(pic_timing.pic_struct, Some(0));
+	assert_eq!(pic_timing.clock_timestamps.len(), 1);
+
+	let timestamp = &pic_timing.clock_timestamps[0];
+	assert!(timestamp.nuit_field_based_flag);
+	assert_eq!(timestamp.n_frames, 12);
+	assert_eq!(timestamp.seconds, Some(30));
+	assert_eq!(timestamp.minutes, Some(15));
+	assert_eq!(timestamp.hours, Some(1));
+	assert_eq!(timestamp.time_offset, 0);
+
+	// Not a caption payload
+	assert_eq!(
+		CaptionData::parse(Bytes::from_static(&[0xb5, 0x00, 0x2f, 0x00])).unwrap(),
+		None
+	);
+}
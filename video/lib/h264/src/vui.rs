@@ -0,0 +1,363 @@
+use std::io;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use bytesio::bit_reader::BitReader;
+use bytesio::bit_writer::BitWriter;
+use exp_golomb::{read_exp_golomb, write_exp_golomb};
+
+use crate::sps::ColorConfig;
+
+#[derive(Debug, Clone, PartialEq)]
+/// VUI parameters
+/// ISO/IEC-14496-10-2022 - E.1.1
+pub struct Vui {
+	/// Only present if the aspect_ratio_info_present_flag is set
+	pub aspect_ratio_idc: Option<u8>,
+	/// Only used if the aspect_ratio_idc is 255 (Extended_SAR)
+	pub sar_width: u16,
+	pub sar_height: u16,
+	/// Only present if the overscan_info_present_flag is set
+	pub overscan_appropriate_flag: Option<bool>,
+	/// 5 means unspecified
+	pub video_format: u8,
+	/// Only present if the video_signal_type_present_flag is set. The colour
+	/// description is only written if one of the values is not 2
+	/// (unspecified).
+	pub color_config: Option<ColorConfig>,
+	/// Only present if the chroma_loc_info_present_flag is set
+	pub chroma_sample_loc_type: Option<(u64, u64)>,
+	/// Only present if the timing_info_present_flag is set
+	pub timing_info: Option<TimingInfo>,
+	/// Only present if the nal_hrd_parameters_present_flag is set
+	pub nal_hrd_parameters: Option<HrdParameters>,
+	/// Only present if the vcl_hrd_parameters_present_flag is set
+	pub vcl_hrd_parameters: Option<HrdParameters>,
+	/// Only used if there are NAL or VCL HRD parameters
+	pub low_delay_hrd_flag: bool,
+	pub pic_struct_present_flag: bool,
+	/// Only present if the bitstream_restriction_flag is set
+	pub bitstream_restriction: Option<BitstreamRestriction>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// ISO/IEC-14496-10-2022 - E.1.1
+pub struct TimingInfo {
+	pub num_units_in_tick: u32,
+	pub time_scale: u32,
+	pub fixed_frame_rate_flag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// ISO/IEC-14496-10-2022 - E.1.1
+pub struct BitstreamRestriction {
+	pub motion_vectors_over_pic_boundaries_flag: bool,
+	pub max_bytes_per_pic_denom: u64,
+	pub max_bits_per_mb_denom: u64,
+	pub log2_max_mv_length_horizontal: u64,
+	pub log2_max_mv_length_vertical: u64,
+	pub max_num_reorder_frames: u64,
+	pub max_dec_frame_buffering: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// HRD parameters
+/// ISO/IEC-14496-10-2022 - E.1.2
+///
+/// The lengths are used for the delay and offset fields in buffering period
+/// and picture timing SEI messages.
+pub struct HrdParameters {
+	pub bit_rate_scale: u8,
+	pub cpb_size_scale: u8,
+	/// One entry for every CPB, there are at most 32.
+	pub cpb_specifications: Vec<CpbSpecification>,
+	pub initial_cpb_removal_delay_length_minus1: u8,
+	pub cpb_removal_delay_length_minus1: u8,
+	pub dpb_output_delay_length_minus1: u8,
+	pub time_offset_length: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The bit rate and buffer size of a CPB
+/// ISO/IEC-14496-10-2022 - E.1.2
+pub struct CpbSpecification {
+	pub bit_rate_value_minus1: u64,
+	pub cpb_size_value_minus1: u64,
+	pub cbr_flag: bool,
+}
+
+impl Vui {
+	pub fn parse(bit_reader: &mut BitReader) -> io::Result<Self> {
+		let mut aspect_ratio_idc = None;
+		let mut sar_width = 0;
+		let mut sar_height = 0;
+
+		// aspect_ratio_info_present_flag
+		if bit_reader.read_bit()? {
+			let idc = bit_reader.read_u8()?;
+			if idc == 255 {
+				sar_width = bit_reader.read_u16::<BigEndian>()?;
+				sar_height = bit_reader.read_u16::<BigEndian>()?;
+			}
+
+			aspect_ratio_idc = Some(idc);
+		}
+
+		// overscan_info_present_flag
+		let overscan_appropriate_flag = if bit_reader.read_bit()? {
+			Some(bit_reader.read_bit()?)
+		} else {
+			None
+		};
+
+		let mut video_format = 5;
+		let mut color_config = None;
+
+		// video_signal_type_present_flag
+		if bit_reader.read_bit()? {
+			video_format = bit_reader.read_bits(3)? as u8;
+			let full_range = bit_reader.read_bit()?; // video_full_range_flag
+
+			let color_primaries;
+			let transfer_characteristics;
+			let matrix_coefficients;
+
+			if bit_reader.read_bit()? {
+				// colour_description_present_flag
+				color_primaries = bit_reader.read_u8()?; // colour_primaries
+				transfer_characteristics = bit_reader.read_u8()?; // transfer_characteristics
+				matrix_coefficients = bit_reader.read_u8()?; // matrix_coefficients
+			} else {
+				color_primaries = 2; // UNSPECIFIED
+				transfer_characteristics = 2; // UNSPECIFIED
+				matrix_coefficients = 2; // UNSPECIFIED
+			}
+
+			color_config = Some(ColorConfig {
+				full_range,
+				color_primaries,
+				transfer_characteristics,
+				matrix_coefficients,
+			});
+		}
+
+		// chroma_loc_info_present_flag
+		let chroma_sample_loc_type = if bit_reader.read_bit()? {
+			let top_field = read_exp_golomb(bit_reader)?; // chroma_sample_loc_type_top_field
+			let bottom_field = read_exp_golomb(bit_reader)?; // chroma_sample_loc_type_bottom_field
+			Some((top_field, bottom_field))
+		} else {
+			None
+		};
+
+		// timing_info_present_flag
+		let timing_info = if bit_reader.read_bit()? {
+			Some(TimingInfo {
+				num_units_in_tick: bit_reader.read_u32::<BigEndian>()?,
+				time_scale: bit_reader.read_u32::<BigEndian>()?,
+				fixed_frame_rate_flag: bit_reader.read_bit()?,
+			})
+		} else {
+			None
+		};
+
+		// nal_hrd_parameters_present_flag
+		let nal_hrd_parameters = if bit_reader.read_bit()? {
+			Some(HrdParameters::parse(bit_reader)?)
+		} else {
+			None
+		};
+
+		// vcl_hrd_parameters_present_flag
+		let vcl_hrd_parameters = if bit_reader.read_bit()? {
+			Some(HrdParameters::parse(bit_reader)?)
+		} else {
+			None
+		};
+
+		let low_delay_hrd_flag = if nal_hrd_parameters.is_some() || vcl_hrd_parameters.is_some() {
+			bit_reader.read_bit()?
+		} else {
+			false
+		};
+
+		let pic_struct_present_flag = bit_reader.read_bit()?;
+
+		// bitstream_restriction_flag
+		let bitstream_restriction = if bit_reader.read_bit()? {
+			Some(BitstreamRestriction {
+				motion_vectors_over_pic_boundaries_flag: bit_reader.read_bit()?,
+				max_bytes_per_pic_denom: read_exp_golomb(bit_reader)?,
+				max_bits_per_mb_denom: read_exp_golomb(bit_reader)?,
+				log2_max_mv_length_horizontal: read_exp_golomb(bit_reader)?,
+				log2_max_mv_length_vertical: read_exp_golomb(bit_reader)?,
+				max_num_reorder_frames: read_exp_golomb(bit_reader)?,
+				max_dec_frame_buffering: read_exp_golomb(bit_reader)?,
+			})
+		} else {
+			None
+		};
+
+		Ok(Vui {
+			aspect_ratio_idc,
+			sar_width,
+			sar_height,
+			overscan_appropriate_flag,
+			video_format,
+			color_config,
+			chroma_sample_loc_type,
+			timing_info,
+			nal_hrd_parameters,
+			vcl_hrd_parameters,
+			low_delay_hrd_flag,
+			pic_struct_present_flag,
+			bitstream_restriction,
+		})
+	}
+
+	pub fn mux(&self, bit_writer: &mut BitWriter) -> io::Result<()> {
+		bit_writer.write_bit(self.aspect_ratio_idc.is_some())?;
+		if let Some(aspect_ratio_idc) = self.aspect_ratio_idc {
+			bit_writer.write_u8(aspect_ratio_idc)?;
+			if aspect_ratio_idc == 255 {
+				bit_writer.write_u16::<BigEndian>(self.sar_width)?;
+				bit_writer.write_u16::<BigEndian>(self.sar_height)?;
+			}
+		}
+
+		bit_writer.write_bit(self.overscan_appropriate_flag.is_some())?;
+		if let Some(overscan_appropriate_flag) = self.overscan_appropriate_flag {
+			bit_writer.write_bit(overscan_appropriate_flag)?;
+		}
+
+		bit_writer.write_bit(self.color_config.is_some())?;
+		if let Some(color_config) = &self.color_config {
+			bit_writer.write_bits(self.video_format as u64, 3)?;
+			bit_writer.write_bit(color_config.full_range)?;
+			color_config.mux_colour_description(bit_writer)?;
+		}
+
+		bit_writer.write_bit(self.chroma_sample_loc_type.is_some())?;
+		if let Some((top_field, bottom_field)) = self.chroma_sample_loc_type {
+			write_exp_golomb(bit_writer, top_field)?;
+			write_exp_golomb(bit_writer, bottom_field)?;
+		}
+
+		bit_writer.write_bit(self.timing_info.is_some())?;
+		if let Some(timing_info) = &self.timing_info {
+			bit_writer.write_u32::<BigEndian>(timing_info.num_units_in_tick)?;
+			bit_writer.write_u32::<BigEndian>(timing_info.time_scale)?;
+			bit_writer.write_bit(timing_info.fixed_frame_rate_flag)?;
+		}
+
+		bit_writer.write_bit(self.nal_hrd_parameters.is_some())?;
+		if let Some(nal_hrd_parameters) = &self.nal_hrd_parameters {
+			nal_hrd_parameters.mux(bit_writer)?;
+		}
+
+		bit_writer.write_bit(self.vcl_hrd_parameters.is_some())?;
+		if let Some(vcl_hrd_parameters) = &self.vcl_hrd_parameters {
+			vcl_hrd_parameters.mux(bit_writer)?;
+		}
+
+		if self.nal_hrd_parameters.is_some() || self.vcl_hrd_parameters.is_some() {
+			bit_writer.write_bit(self.low_delay_hrd_flag)?;
+		}
+
+		bit_writer.write_bit(self.pic_struct_present_flag)?;
+
+		bit_writer.write_bit(self.bitstream_restriction.is_some())?;
+		if let Some(bitstream_restriction) = &self.bitstream_restriction {
+			bit_writer.write_bit(bitstream_restriction.motion_vectors_over_pic_boundaries_flag)?;
+			write_exp_golomb(bit_writer, bitstream_restriction.max_bytes_per_pic_denom)?;
+			write_exp_golomb(bit_writer, bitstream_restriction.max_bits_per_mb_denom)?;
+			write_exp_golomb(bit_writer, bitstream_restriction.log2_max_mv_length_horizontal)?;
+			write_exp_golomb(bit_writer, bitstream_restriction.log2_max_mv_length_vertical)?;
+			write_exp_golomb(bit_writer, bitstream_restriction.max_num_reorder_frames)?;
+			write_exp_golomb(bit_writer, bitstream_restriction.max_dec_frame_buffering)?;
+		}
+
+		Ok(())
+	}
+
+	/// The frame rate from the timing info, 0 if there is no timing info.
+	pub fn frame_rate(&self) -> f64 {
+		self.timing_info.as_ref().map_or(0.0, |timing_info| {
+			timing_info.time_scale as f64 / (2.0 * timing_info.num_units_in_tick as f64)
+		})
+	}
+}
+
+impl ColorConfig {
+	/// Writes the colour_description_present_flag and the colour description,
+	/// which is left out if all values are unspecified.
+	pub(crate) fn mux_colour_description(&self, bit_writer: &mut BitWriter) -> io::Result<()> {
+		let colour_description_present_flag =
+			self.color_primaries != 2 || self.transfer_characteristics != 2 || self.matrix_coefficients != 2;
+
+		bit_writer.write_bit(colour_description_present_flag)?;
+		if colour_description_present_flag {
+			bit_writer.write_u8(self.color_primaries)?;
+			bit_writer.write_u8(self.transfer_characteristics)?;
+			bit_writer.write_u8(self.matrix_coefficients)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl HrdParameters {
+	pub fn parse(bit_reader: &mut BitReader) -> io::Result<Self> {
+		let cpb_cnt_minus1 = read_exp_golomb(bit_reader)?;
+		if cpb_cnt_minus1 > 31 {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "cpb_cnt_minus1 is out of range"));
+		}
+
+		let bit_rate_scale = bit_reader.read_bits(4)? as u8;
+		let cpb_size_scale = bit_reader.read_bits(4)? as u8;
+
+		let mut cpb_specifications = Vec::with_capacity(cpb_cnt_minus1 as usize + 1);
+		for _ in 0..=cpb_cnt_minus1 {
+			cpb_specifications.push(CpbSpecification {
+				bit_rate_value_minus1: read_exp_golomb(bit_reader)?,
+				cpb_size_value_minus1: read_exp_golomb(bit_reader)?,
+				cbr_flag: bit_reader.read_bit()?,
+			});
+		}
+
+		Ok(HrdParameters {
+			bit_rate_scale,
+			cpb_size_scale,
+			cpb_specifications,
+			initial_cpb_removal_delay_length_minus1: bit_reader.read_bits(5)? as u8,
+			cpb_removal_delay_length_minus1: bit_reader.read_bits(5)? as u8,
+			dpb_output_delay_length_minus1: bit_reader.read_bits(5)? as u8,
+			time_offset_length: bit_reader.read_bits(5)? as u8,
+		})
+	}
+
+	pub fn mux(&self, bit_writer: &mut BitWriter) -> io::Result<()> {
+		if self.cpb_specifications.is_empty() || self.cpb_specifications.len() > 32 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"there must be between 1 and 32 CPB specifications",
+			));
+		}
+
+		write_exp_golomb(bit_writer, self.cpb_specifications.len() as u64 - 1)?;
+		bit_writer.write_bits(self.bit_rate_scale as u64, 4)?;
+		bit_writer.write_bits(self.cpb_size_scale as u64, 4)?;
+
+		for cpb_specification in &self.cpb_specifications {
+			write_exp_golomb(bit_writer, cpb_specification.bit_rate_value_minus1)?;
+			write_exp_golomb(bit_writer, cpb_specification.cpb_size_value_minus1)?;
+			bit_writer.write_bit(cpb_specification.cbr_flag)?;
+		}
+
+		bit_writer.write_bits(self.initial_cpb_removal_delay_length_minus1 as u64, 5)?;
+		bit_writer.write_bits(self.cpb_removal_delay_length_minus1 as u64, 5)?;
+		bit_writer.write_bits(self.dpb_output_delay_length_minus1 as u64, 5)?;
+		bit_writer.write_bits(self.time_offset_length as u64, 5)?;
+
+		Ok(())
+	}
+}
@@ -7,6 +7,8 @@ use bytes::Bytes;
 use bytesio::bit_reader::BitReader;
 use bytesio::bit_writer::BitWriter;
 
+use crate::nal::NaluType;
+
 #[derive(Debug, Clone, PartialEq)]
 /// HEVC Decoder Configuration Record
 /// ISO/IEC 14496-15:2022(E) - 8.3.2.1
@@ -40,38 +42,6 @@ pub struct NaluArray {
 	pub nalus: Vec<Bytes>,
 }
 
-#[derive(Debug, Clone, PartialEq, Copy)]
-/// Nalu Type
-/// ISO/IEC 23008-2:2020(E) - 7.4.2.2 (Table 7-1)
-pub enum NaluType {
-	Vps,
-	Pps,
-	Sps,
-	Unknown(u8),
-}
-
-impl From<u8> for NaluType {
-	fn from(value: u8) -> Self {
-		match value {
-			32 => NaluType::Vps,
-			33 => NaluType::Sps,
-			34 => NaluType::Pps,
-			_ => NaluType::Unknown(value),
-		}
-	}
-}
-
-impl From<NaluType> for u8 {
-	fn from(value: NaluType) -> Self {
-		match value {
-			NaluType::Vps => 32,
-			NaluType::Sps => 33,
-			NaluType::Pps => 34,
-			NaluType::Unknown(value) => value,
-		}
-	}
-}
-
 impl HEVCDecoderConfigurationRecord {
 	pub fn demux(data: &mut io::Cursor<Bytes>) -> io::Result<Self> {
 		let mut bit_reader = BitReader::new(data);
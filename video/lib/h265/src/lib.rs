@@ -1,8 +1,25 @@
 mod config;
+mod nal;
+mod pps;
+mod profile_tier_level;
+mod sei;
 mod sps;
+mod vps;
+mod vui;
 
-pub use self::config::{HEVCDecoderConfigurationRecord, NaluArray, NaluType};
-pub use self::sps::{ColorConfig, Sps};
+pub use self::config::{HEVCDecoderConfigurationRecord, NaluArray};
+pub use self::nal::{NalUnit, NalUnits, NaluType};
+pub use self::pps::{DeblockingFilterControl, Pps, Tiles};
+pub use self::profile_tier_level::{ProfileTierLevel, SubLayerProfile, SubLayerProfileTierLevel};
+pub use self::sei::{ContentLightLevelInfo, MasteringDisplayColourVolume, SeiMessage, SeiPayloadType};
+pub use self::sps::{
+	ColorConfig, DeltaPoc, LongTermRefPic, Pcm, PredictedRefPic, ScalingList, ScalingListData, ShortTermRefPicSet, Sps,
+	SpsRangeExtension, SubLayerOrderingInfo,
+};
+pub use self::vps::Vps;
+pub use self::vui::{
+	BitstreamRestriction, CpbSpecification, HrdParameters, SubLayerHrd, SubPicHrdParams, TimingInfo, Vui, Window,
+};
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,268 @@
+use std::io;
+
+use bytes::Bytes;
+use bytesio::emulation_prevention::remove_emulation_prevention;
+
+#[derive(Debug, Clone, PartialEq)]
+/// NAL unit
+/// ISO/IEC 23008-2:2020(E) - 7.3.1.1
+pub struct NalUnit {
+	pub nal_unit_type: NaluType,
+	pub nuh_layer_id: u8,
+	pub nuh_temporal_id_plus1: u8,
+	/// The whole NAL unit including the header
+	pub data: Bytes,
+}
+
+impl NalUnit {
+	pub fn parse(data: Bytes) -> io::Result<Self> {
+		if data.len() < 2 {
+			return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "NAL unit header is too short"));
+		}
+
+		let header = u16::from_be_bytes([data[0], data[1]]);
+		if header & 0x8000 != 0 {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "forbidden_zero_bit is not zero"));
+		}
+
+		let nuh_temporal_id_plus1 = (header & 0b111) as u8;
+		if nuh_temporal_id_plus1 == 0 {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "nuh_temporal_id_plus1 is zero"));
+		}
+
+		Ok(Self {
+			nal_unit_type: NaluType::from(((header >> 9) & 0b11_1111) as u8),
+			nuh_layer_id: ((header >> 3) & 0b11_1111) as u8,
+			nuh_temporal_id_plus1,
+			data,
+		})
+	}
+
+	/// The raw byte sequence payload, this is the NAL unit without the header
+	/// and without the emulation prevention bytes.
+	pub fn rbsp(&self) -> Bytes {
+		Bytes::from(remove_emulation_prevention(&self.data[2..]))
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+/// Nalu Type
+/// ISO/IEC 23008-2:2020(E) - 7.4.2.2 (Table 7-1)
+pub enum NaluType {
+	TrailN,
+	TrailR,
+	TsaN,
+	TsaR,
+	StsaN,
+	StsaR,
+	RadlN,
+	RadlR,
+	RaslN,
+	RaslR,
+	BlaWLp,
+	BlaWRadl,
+	BlaNLp,
+	IdrWRadl,
+	IdrNLp,
+	Cra,
+	Vps,
+	Pps,
+	Sps,
+	Aud,
+	Eos,
+	Eob,
+	Fd,
+	PrefixSei,
+	SuffixSei,
+	Unknown(u8),
+}
+
+impl NaluType {
+	/// Intra random access point pictures, decoding can start at these.
+	pub fn is_irap(&self) -> bool {
+		matches!(
+			self,
+			NaluType::BlaWLp | NaluType::BlaWRadl | NaluType::BlaNLp | NaluType::IdrWRadl | NaluType::IdrNLp | NaluType::Cra
+		) || matches!(self, NaluType::Unknown(22..=23))
+	}
+
+	pub fn is_idr(&self) -> bool {
+		matches!(self, NaluType::IdrWRadl | NaluType::IdrNLp)
+	}
+}
+
+impl From<u8> for NaluType {
+	fn from(value: u8) -> Self {
+		match value {
+			0 => NaluType::TrailN,
+			1 => NaluType::TrailR,
+			2 => NaluType::TsaN,
+			3 => NaluType::TsaR,
+			4 => NaluType::StsaN,
+			5 => NaluType::StsaR,
+			6 => NaluType::RadlN,
+			7 => NaluType::RadlR,
+			8 => NaluType::RaslN,
+			9 => NaluType::RaslR,
+			16 => NaluType::BlaWLp,
+			17 => NaluType::BlaWRadl,
+			18 => NaluType::BlaNLp,
+			19 => NaluType::IdrWRadl,
+			20 => NaluType::IdrNLp,
+			21 => NaluType::Cra,
+			32 => NaluType::Vps,
+			33 => NaluType::Sps,
+			34 => NaluType::Pps,
+			35 => NaluType::Aud,
+			36 => NaluType::Eos,
+			37 => NaluType::Eob,
+			38 => NaluType::Fd,
+			39 => NaluType::PrefixSei,
+			40 => NaluType::SuffixSei,
+			_ => NaluType::Unknown(value),
+		}
+	}
+}
+
+impl From<NaluType> for u8 {
+	fn from(value: NaluType) -> Self {
+		match value {
+			NaluType::TrailN => 0,
+			NaluType::TrailR => 1,
+			NaluType::TsaN => 2,
+			NaluType::TsaR => 3,
+			NaluType::StsaN => 4,
+			NaluType::StsaR => 5,
+			NaluType::RadlN => 6,
+			NaluType::RadlR => 7,
+			NaluType::RaslN => 8,
+			NaluType::RaslR => 9,
+			NaluType::BlaWLp => 16,
+			NaluType::BlaWRadl => 17,
+			NaluType::BlaNLp => 18,
+			NaluType::IdrWRadl => 19,
+			NaluType::IdrNLp => 20,
+			NaluType::Cra => 21,
+			NaluType::Vps => 32,
+			NaluType::Sps => 33,
+			NaluType::Pps => 34,
+			NaluType::Aud => 35,
+			NaluType::Eos => 36,
+			NaluType::Eob => 37,
+			NaluType::Fd => 38,
+			NaluType::PrefixSei => 39,
+			NaluType::SuffixSei => 40,
+			NaluType::Unknown(value) => value,
+		}
+	}
+}
+
+/// An iterator over the NAL units of an access unit.
+pub struct NalUnits {
+	data: Bytes,
+	framing: Framing,
+}
+
+enum Framing {
+	/// Every NAL unit has a big endian length prefix of this size
+	/// ISO/IEC 14496-15:2022(E) - 8.3.3
+	Hvcc(u8),
+	/// NAL units are separated by start codes
+	/// ISO/IEC 23008-2:2020(E) - B.2
+	AnnexB,
+}
+
+impl NalUnits {
+	/// NAL units with a length prefix, like in MP4 and FLV. The length size is
+	/// `length_size_minus_one + 1` from the decoder configuration record.
+	pub fn hvcc(data: Bytes, length_size: u8) -> Self {
+		Self {
+			data,
+			framing: Framing::Hvcc(length_size),
+		}
+	}
+
+	/// NAL units with start codes, like in MPEG-TS. Anything in front of the
+	/// first start code is ignored.
+	pub fn annex_b(mut data: Bytes) -> Self {
+		let start = find_start_code(&data).map_or(data.len(), |(_, end)| end);
+		data = data.slice(start..);
+
+		Self {
+			data,
+			framing: Framing::AnnexB,
+		}
+	}
+
+	fn next_hvcc(&mut self, length_size: u8) -> io::Result<Bytes> {
+		let length_size = length_size as usize;
+		if length_size == 0 || length_size > 4 || self.data.len() < length_size {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid NAL unit length prefix"));
+		}
+
+		let length = self.data[..length_size]
+			.iter()
+			.fold(0, |length, byte| (length << 8) | *byte as usize);
+
+		if self.data.len() < length_size + length {
+			return Err(io::Error::new(
+				io::ErrorKind::UnexpectedEof,
+				"NAL unit is longer than the remaining data",
+			));
+		}
+
+		let nal_unit = self.data.slice(length_size..length_size + length);
+		self.data = self.data.slice(length_size + length..);
+
+		Ok(nal_unit)
+	}
+
+	fn next_annex_b(&mut self) -> Bytes {
+		let nal_unit = match find_start_code(&self.data) {
+			Some((start, end)) => {
+				let nal_unit = self.data.slice(..start);
+				self.data = self.data.slice(end..);
+				nal_unit
+			}
+			None => std::mem::take(&mut self.data),
+		};
+
+		// The zero of a 4 byte start code and trailing_zero_8bits end up at the
+		// end of the NAL unit, which never ends with a zero byte itself.
+		let end = nal_unit.iter().rposition(|b| *b != 0).map_or(0, |end| end + 1);
+		nal_unit.slice(..end)
+	}
+}
+
+impl Iterator for NalUnits {
+	type Item = io::Result<NalUnit>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while !self.data.is_empty() {
+			let nal_unit = match self.framing {
+				Framing::Hvcc(length_size) => match self.next_hvcc(length_size) {
+					Ok(nal_unit) => nal_unit,
+					Err(err) => {
+						// We can not find the next NAL unit after an error
+						self.data.clear();
+						return Some(Err(err));
+					}
+				},
+				Framing::AnnexB => self.next_annex_b(),
+			};
+
+			if !nal_unit.is_empty() {
+				return Some(NalUnit::parse(nal_unit));
+			}
+		}
+
+		None
+	}
+}
+
+/// Returns the start and end of the first 3 byte start code.
+fn find_start_code(data: &[u8]) -> Option<(usize, usize)> {
+	data.windows(3)
+		.position(|window| window == [0, 0, 1])
+		.map(|start| (start, start + 3))
+}
@@ -0,0 +1,205 @@
+use std::io;
+
+use bytes::Bytes;
+use bytesio::bit_reader::BitReader;
+use exp_golomb::{read_exp_golomb, read_signed_exp_golomb};
+
+use crate::nal::{NalUnit, NaluType};
+use crate::sps::ScalingListData;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Picture parameter set
+/// ISO/IEC 23008-2:2020(E) - 7.3.2.3.1
+///
+/// The PPS extensions are not parsed.
+pub struct Pps {
+	pub pic_parameter_set_id: u64,
+	pub seq_parameter_set_id: u64,
+	pub dependent_slice_segments_enabled_flag: bool,
+	pub output_flag_present_flag: bool,
+	pub num_extra_slice_header_bits: u8,
+	pub sign_data_hiding_enabled_flag: bool,
+	pub cabac_init_present_flag: bool,
+	pub num_ref_idx_l0_default_active_minus1: u64,
+	pub num_ref_idx_l1_default_active_minus1: u64,
+	pub init_qp_minus26: i64,
+	pub constrained_intra_pred_flag: bool,
+	pub transform_skip_enabled_flag: bool,
+	/// Only present if the cu_qp_delta_enabled_flag is set
+	pub diff_cu_qp_delta_depth: Option<u64>,
+	pub cb_qp_offset: i64,
+	pub cr_qp_offset: i64,
+	pub slice_chroma_qp_offsets_present_flag: bool,
+	pub weighted_pred_flag: bool,
+	pub weighted_bipred_flag: bool,
+	pub transquant_bypass_enabled_flag: bool,
+	/// Only present if the tiles_enabled_flag is set
+	pub tiles: Option<Tiles>,
+	pub entropy_coding_sync_enabled_flag: bool,
+	pub loop_filter_across_slices_enabled_flag: bool,
+	/// Only present if the deblocking_filter_control_present_flag is set
+	pub deblocking_filter_control: Option<DeblockingFilterControl>,
+	pub scaling_list_data_present_flag: bool,
+	pub lists_modification_present_flag: bool,
+	pub log2_parallel_merge_level_minus2: u64,
+	pub slice_segment_header_extension_present_flag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tiles {
+	pub num_tile_columns_minus1: u64,
+	pub num_tile_rows_minus1: u64,
+	pub uniform_spacing_flag: bool,
+	/// Empty if the uniform_spacing_flag is set
+	pub column_width_minus1: Vec<u64>,
+	/// Empty if the uniform_spacing_flag is set
+	pub row_height_minus1: Vec<u64>,
+	pub loop_filter_across_tiles_enabled_flag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeblockingFilterControl {
+	pub deblocking_filter_override_enabled_flag: bool,
+	pub deblocking_filter_disabled_flag: bool,
+	/// Only used if the deblocking filter is not disabled
+	pub beta_offset_div2: i64,
+	pub tc_offset_div2: i64,
+}
+
+impl Pps {
+	pub fn parse(data: Bytes) -> io::Result<Self> {
+		let nal_unit = NalUnit::parse(data)?;
+		if nal_unit.nal_unit_type != NaluType::Pps {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "nalu_type is not 34"));
+		}
+
+		let mut bit_reader = BitReader::from(nal_unit.rbsp());
+
+		let pic_parameter_set_id = read_exp_golomb(&mut bit_reader)?;
+		let seq_parameter_set_id = read_exp_golomb(&mut bit_reader)?;
+		let dependent_slice_segments_enabled_flag = bit_reader.read_bit()?;
+		let output_flag_present_flag = bit_reader.read_bit()?;
+		let num_extra_slice_header_bits = bit_reader.read_bits(3)? as u8;
+		let sign_data_hiding_enabled_flag = bit_reader.read_bit()?;
+		let cabac_init_present_flag = bit_reader.read_bit()?;
+		let num_ref_idx_l0_default_active_minus1 = read_exp_golomb(&mut bit_reader)?;
+		let num_ref_idx_l1_default_active_minus1 = read_exp_golomb(&mut bit_reader)?;
+		let init_qp_minus26 = read_signed_exp_golomb(&mut bit_reader)?;
+		let constrained_intra_pred_flag = bit_reader.read_bit()?;
+		let transform_skip_enabled_flag = bit_reader.read_bit()?;
+
+		// cu_qp_delta_enabled_flag
+		let diff_cu_qp_delta_depth = if bit_reader.read_bit()? {
+			Some(read_exp_golomb(&mut bit_reader)?)
+		} else {
+			None
+		};
+
+		let cb_qp_offset = read_signed_exp_golomb(&mut bit_reader)?;
+		let cr_qp_offset = read_signed_exp_golomb(&mut bit_reader)?;
+		let slice_chroma_qp_offsets_present_flag = bit_reader.read_bit()?;
+		let weighted_pred_flag = bit_reader.read_bit()?;
+		let weighted_bipred_flag = bit_reader.read_bit()?;
+		let transquant_bypass_enabled_flag = bit_reader.read_bit()?;
+		let tiles_enabled_flag = bit_reader.read_bit()?;
+		let entropy_coding_sync_enabled_flag = bit_reader.read_bit()?;
+
+		let tiles = if tiles_enabled_flag {
+			let num_tile_columns_minus1 = read_exp_golomb(&mut bit_reader)?;
+			let num_tile_rows_minus1 = read_exp_golomb(&mut bit_reader)?;
+			// The limits are much lower for every level, this only keeps the
+			// allocations sane.
+			if num_tile_columns_minus1 > 255 || num_tile_rows_minus1 > 255 {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, "too many tiles"));
+			}
+
+			let uniform_spacing_flag = bit_reader.read_bit()?;
+
+			let mut column_width_minus1 = Vec::new();
+			let mut row_height_minus1 = Vec::new();
+			if !uniform_spacing_flag {
+				for _ in 0..num_tile_columns_minus1 {
+					column_width_minus1.push(read_exp_golomb(&mut bit_reader)?);
+				}
+				for _ in 0..num_tile_rows_minus1 {
+					row_height_minus1.push(read_exp_golomb(&mut bit_reader)?);
+				}
+			}
+
+			Some(Tiles {
+				num_tile_columns_minus1,
+				num_tile_rows_minus1,
+				uniform_spacing_flag,
+				column_width_minus1,
+				row_height_minus1,
+				loop_filter_across_tiles_enabled_flag: bit_reader.read_bit()?,
+			})
+		} else {
+			None
+		};
+
+		let loop_filter_across_slices_enabled_flag = bit_reader.read_bit()?;
+
+		// deblocking_filter_control_present_flag
+		let deblocking_filter_control = if bit_reader.read_bit()? {
+			let deblocking_filter_override_enabled_flag = bit_reader.read_bit()?;
+			let deblocking_filter_disabled_flag = bit_reader.read_bit()?;
+
+			let mut beta_offset_div2 = 0;
+			let mut tc_offset_div2 = 0;
+			if !deblocking_filter_disabled_flag {
+				beta_offset_div2 = read_signed_exp_golomb(&mut bit_reader)?;
+				tc_offset_div2 = read_signed_exp_golomb(&mut bit_reader)?;
+			}
+
+			Some(DeblockingFilterControl {
+				deblocking_filter_override_enabled_flag,
+				deblocking_filter_disabled_flag,
+				beta_offset_div2,
+				tc_offset_div2,
+			})
+		} else {
+			None
+		};
+
+		let scaling_list_data_present_flag = bit_reader.read_bit()?;
+		if scaling_list_data_present_flag {
+			// The scaling lists are only needed for decoding
+			ScalingListData::parse(&mut bit_reader)?;
+		}
+
+		let lists_modification_present_flag = bit_reader.read_bit()?;
+		let log2_parallel_merge_level_minus2 = read_exp_golomb(&mut bit_reader)?;
+		let slice_segment_header_extension_present_flag = bit_reader.read_bit()?;
+
+		Ok(Pps {
+			pic_parameter_set_id,
+			seq_parameter_set_id,
+			dependent_slice_segments_enabled_flag,
+			output_flag_present_flag,
+			num_extra_slice_header_bits,
+			sign_data_hiding_enabled_flag,
+			cabac_init_present_flag,
+			num_ref_idx_l0_default_active_minus1,
+			num_ref_idx_l1_default_active_minus1,
+			init_qp_minus26,
+			constrained_intra_pred_flag,
+			transform_skip_enabled_flag,
+			diff_cu_qp_delta_depth,
+			cb_qp_offset,
+			cr_qp_offset,
+			slice_chroma_qp_offsets_present_flag,
+			weighted_pred_flag,
+			weighted_bipred_flag,
+			transquant_bypass_enabled_flag,
+			tiles,
+			entropy_coding_sync_enabled_flag,
+			loop_filter_across_slices_enabled_flag,
+			deblocking_filter_control,
+			scaling_list_data_present_flag,
+			lists_modification_present_flag,
+			log2_parallel_merge_level_minus2,
+			slice_segment_header_extension_present_flag,
+		})
+	}
+}
@@ -0,0 +1,128 @@
+use std::io;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use bytesio::bit_reader::BitReader;
+use bytesio::bit_writer::BitWriter;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Profile, tier and level
+/// ISO/IEC 23008-2:2020(E) - 7.3.3
+pub struct ProfileTierLevel {
+	pub general_profile_space: u8,
+	pub general_tier_flag: bool,
+	pub general_profile_idc: u8,
+	pub general_profile_compatibility_flags: u32,
+	/// The 48 bits from general_progressive_source_flag to
+	/// general_inbld_flag, like in the decoder configuration record.
+	pub general_constraint_indicator_flags: u64,
+	pub general_level_idc: u8,
+	/// One entry for every sub layer except the highest one
+	pub sub_layers: Vec<SubLayerProfileTierLevel>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubLayerProfileTierLevel {
+	/// Only present if the sub_layer_profile_present_flag is set
+	pub profile: Option<SubLayerProfile>,
+	/// Only present if the sub_layer_level_present_flag is set
+	pub level_idc: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubLayerProfile {
+	pub profile_space: u8,
+	pub tier_flag: bool,
+	pub profile_idc: u8,
+	pub profile_compatibility_flags: u32,
+	pub constraint_indicator_flags: u64,
+}
+
+impl ProfileTierLevel {
+	/// The profilePresentFlag is always 1 in the VPS and SPS of the base layer.
+	pub fn parse(bit_reader: &mut BitReader, max_sub_layers_minus1: u8) -> io::Result<Self> {
+		let general_profile_space = bit_reader.read_bits(2)? as u8;
+		let general_tier_flag = bit_reader.read_bit()?;
+		let general_profile_idc = bit_reader.read_bits(5)? as u8;
+		let general_profile_compatibility_flags = bit_reader.read_u32::<BigEndian>()?;
+		let general_constraint_indicator_flags = bit_reader.read_u48::<BigEndian>()?;
+		let general_level_idc = bit_reader.read_u8()?;
+
+		let mut present_flags = Vec::with_capacity(max_sub_layers_minus1 as usize);
+		for _ in 0..max_sub_layers_minus1 {
+			let sub_layer_profile_present_flag = bit_reader.read_bit()?;
+			let sub_layer_level_present_flag = bit_reader.read_bit()?;
+			present_flags.push((sub_layer_profile_present_flag, sub_layer_level_present_flag));
+		}
+
+		if max_sub_layers_minus1 > 0 {
+			bit_reader.seek_bits(2 * (8 - max_sub_layers_minus1 as i64))?; // reserved_zero_2bits
+		}
+
+		let mut sub_layers = Vec::with_capacity(max_sub_layers_minus1 as usize);
+		for (profile_present_flag, level_present_flag) in present_flags {
+			let profile = if profile_present_flag {
+				Some(SubLayerProfile {
+					profile_space: bit_reader.read_bits(2)? as u8,
+					tier_flag: bit_reader.read_bit()?,
+					profile_idc: bit_reader.read_bits(5)? as u8,
+					profile_compatibility_flags: bit_reader.read_u32::<BigEndian>()?,
+					constraint_indicator_flags: bit_reader.read_u48::<BigEndian>()?,
+				})
+			} else {
+				None
+			};
+
+			let level_idc = if level_present_flag {
+				Some(bit_reader.read_u8()?)
+			} else {
+				None
+			};
+
+			sub_layers.push(SubLayerProfileTierLevel { profile, level_idc });
+		}
+
+		Ok(ProfileTierLevel {
+			general_profile_space,
+			general_tier_flag,
+			general_profile_idc,
+			general_profile_compatibility_flags,
+			general_constraint_indicator_flags,
+			general_level_idc,
+			sub_layers,
+		})
+	}
+
+	pub fn mux(&self, bit_writer: &mut BitWriter) -> io::Result<()> {
+		bit_writer.write_bits(self.general_profile_space as u64, 2)?;
+		bit_writer.write_bit(self.general_tier_flag)?;
+		bit_writer.write_bits(self.general_profile_idc as u64, 5)?;
+		bit_writer.write_u32::<BigEndian>(self.general_profile_compatibility_flags)?;
+		bit_writer.write_u48::<BigEndian>(self.general_constraint_indicator_flags)?;
+		bit_writer.write_u8(self.general_level_idc)?;
+
+		for sub_layer in &self.sub_layers {
+			bit_writer.write_bit(sub_layer.profile.is_some())?;
+			bit_writer.write_bit(sub_layer.level_idc.is_some())?;
+		}
+
+		if !self.sub_layers.is_empty() {
+			bit_writer.write_bits(0, 2 * (8 - self.sub_layers.len()))?; // reserved_zero_2bits
+		}
+
+		for sub_layer in &self.sub_layers {
+			if let Some(profile) = &sub_layer.profile {
+				bit_writer.write_bits(profile.profile_space as u64, 2)?;
+				bit_writer.write_bit(profile.tier_flag)?;
+				bit_writer.write_bits(profile.profile_idc as u64, 5)?;
+				bit_writer.write_u32::<BigEndian>(profile.profile_compatibility_flags)?;
+				bit_writer.write_u48::<BigEndian>(profile.constraint_indicator_flags)?;
+			}
+
+			if let Some(level_idc) = sub_layer.level_idc {
+				bit_writer.write_u8(level_idc)?;
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,169 @@
+use std::io;
+
+use byteorder::{BigEndian, ReadBytesExt};
+use bytes::{Buf, Bytes};
+use bytesio::bit_reader::BitReader;
+
+use crate::nal::{NalUnit, NaluType};
+
+#[derive(Debug, Clone, PartialEq)]
+/// SEI message
+/// ISO/IEC 23008-2:2020(E) - 7.3.5
+pub struct SeiMessage {
+	pub payload_type: SeiPayloadType,
+	pub payload: Bytes,
+}
+
+impl SeiMessage {
+	/// Parses every SEI message in a prefix or suffix SEI NAL unit.
+	pub fn parse(data: Bytes) -> io::Result<Vec<Self>> {
+		let nal_unit = NalUnit::parse(data)?;
+		if !matches!(nal_unit.nal_unit_type, NaluType::PrefixSei | NaluType::SuffixSei) {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "NAL unit type is not SEI"));
+		}
+
+		let mut reader = BitReader::from(nal_unit.rbsp());
+		let mut messages = Vec::new();
+
+		while reader.more_rbsp_data() {
+			let payload_type = read_sei_value(&mut reader)?;
+			let payload_size = read_sei_value(&mut reader)? as usize;
+
+			if reader.remaining_bits() / 8 < payload_size {
+				return Err(io::Error::new(
+					io::ErrorKind::UnexpectedEof,
+					"SEI payload is longer than the remaining data",
+				));
+			}
+
+			messages.push(SeiMessage {
+				payload_type: SeiPayloadType::from(payload_type),
+				payload: reader.get_mut().copy_to_bytes(payload_size),
+			});
+		}
+
+		Ok(messages)
+	}
+}
+
+/// The payload type and size are coded as a sum of bytes, every 0xFF byte
+/// adds 255 and the first other byte ends the value.
+fn read_sei_value(reader: &mut impl io::Read) -> io::Result<u32> {
+	let mut value = 0;
+	loop {
+		let byte = reader.read_u8()?;
+		value += byte as u32;
+		if byte != 0xFF {
+			return Ok(value);
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// SEI payload type
+/// ISO/IEC 23008-2:2020(E) - 7.3.5
+pub enum SeiPayloadType {
+	BufferingPeriod,
+	PicTiming,
+	UserDataRegisteredItuTT35,
+	UserDataUnregistered,
+	RecoveryPoint,
+	DecodedPictureHash,
+	MasteringDisplayColourVolume,
+	ContentLightLevelInfo,
+	AlternativeTransferCharacteristics,
+	Other(u32),
+}
+
+impl From<u32> for SeiPayloadType {
+	fn from(value: u32) -> Self {
+		match value {
+			0 => SeiPayloadType::BufferingPeriod,
+			1 => SeiPayloadType::PicTiming,
+			4 => SeiPayloadType::UserDataRegisteredItuTT35,
+			5 => SeiPayloadType::UserDataUnregistered,
+			6 => SeiPayloadType::RecoveryPoint,
+			132 => SeiPayloadType::DecodedPictureHash,
+			137 => SeiPayloadType::MasteringDisplayColourVolume,
+			144 => SeiPayloadType::ContentLightLevelInfo,
+			147 => SeiPayloadType::AlternativeTransferCharacteristics,
+			_ => SeiPayloadType::Other(value),
+		}
+	}
+}
+
+impl From<SeiPayloadType> for u32 {
+	fn from(value: SeiPayloadType) -> Self {
+		match value {
+			SeiPayloadType::BufferingPeriod => 0,
+			SeiPayloadType::PicTiming => 1,
+			SeiPayloadType::UserDataRegisteredItuTT35 => 4,
+			SeiPayloadType::UserDataUnregistered => 5,
+			SeiPayloadType::RecoveryPoint => 6,
+			SeiPayloadType::DecodedPictureHash => 132,
+			SeiPayloadType::MasteringDisplayColourVolume => 137,
+			SeiPayloadType::ContentLightLevelInfo => 144,
+			SeiPayloadType::AlternativeTransferCharacteristics => 147,
+			SeiPayloadType::Other(value) => value,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Mastering display colour volume SEI message
+/// ISO/IEC 23008-2:2020(E) - D.2.28
+///
+/// The chromaticity coordinates are in units of 0.00002 and the luminance
+/// values are in units of 0.0001 candelas per square metre.
+pub struct MasteringDisplayColourVolume {
+	/// The (x, y) coordinates of the green, blue and red primaries in the
+	/// order of the bitstream.
+	pub display_primaries: [(u16, u16); 3],
+	/// The (x, y) coordinates of the white point
+	pub white_point: (u16, u16),
+	pub max_display_mastering_luminance: u32,
+	pub min_display_mastering_luminance: u32,
+}
+
+impl MasteringDisplayColourVolume {
+	pub fn parse(payload: Bytes) -> io::Result<Self> {
+		let mut reader = io::Cursor::new(payload);
+
+		let mut display_primaries = [(0, 0); 3];
+		for primary in display_primaries.iter_mut() {
+			*primary = (reader.read_u16::<BigEndian>()?, reader.read_u16::<BigEndian>()?);
+		}
+
+		let white_point = (reader.read_u16::<BigEndian>()?, reader.read_u16::<BigEndian>()?);
+		let max_display_mastering_luminance = reader.read_u32::<BigEndian>()?;
+		let min_display_mastering_luminance = reader.read_u32::<BigEndian>()?;
+
+		Ok(MasteringDisplayColourVolume {
+			display_primaries,
+			white_point,
+			max_display_mastering_luminance,
+			min_display_mastering_luminance,
+		})
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Content light level information SEI message
+/// ISO/IEC 23008-2:2020(E) - D.2.35
+///
+/// Both values are in candelas per square metre, zero means unknown.
+pub struct ContentLightLevelInfo {
+	pub max_content_light_level: u16,
+	pub max_pic_average_light_level: u16,
+}
+
+impl ContentLightLevelInfo {
+	pub fn parse(payload: Bytes) -> io::Result<Self> {
+		let mut reader = io::Cursor::new(payload);
+
+		Ok(ContentLightLevelInfo {
+			max_content_light_level: reader.read_u16::<BigEndian>()?,
+			max_pic_average_light_level: reader.read_u16::<BigEndian>()?,
+		})
+	}
+}
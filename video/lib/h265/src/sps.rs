@@ -1,14 +1,66 @@
 use std::io;
 
-use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
 use bytes::Bytes;
 use bytesio::bit_reader::BitReader;
-use exp_golomb::{read_exp_golomb, read_signed_exp_golomb};
+use bytesio::bit_writer::BitWriter;
+use bytesio::emulation_prevention::{add_emulation_prevention, remove_emulation_prevention};
+use exp_golomb::{read_exp_golomb, read_signed_exp_golomb, write_exp_golomb, write_signed_exp_golomb};
+
+use crate::profile_tier_level::ProfileTierLevel;
+use crate::vui::{Vui, Window};
 
 #[derive(Debug, Clone, PartialEq)]
 /// Sequence parameter set
-/// ISO/IEC-14496-10-2022 - 7.3.2
+/// ISO/IEC 23008-2:2020(E) - 7.3.2.2
+///
+/// Only the range extension is parsed, the other SPS extensions are skipped.
+/// The fields after `extension_flags` are derived from the syntax elements
+/// when the SPS is parsed, they are not used when the SPS is written.
 pub struct Sps {
+	pub video_parameter_set_id: u8,
+	pub max_sub_layers_minus1: u8,
+	pub temporal_id_nesting_flag: bool,
+	pub profile_tier_level: ProfileTierLevel,
+	pub seq_parameter_set_id: u64,
+	pub chroma_format_idc: u64,
+	pub separate_colour_plane_flag: bool,
+	pub pic_width_in_luma_samples: u64,
+	pub pic_height_in_luma_samples: u64,
+	/// Only present if the conformance_window_flag is set
+	pub conformance_window: Option<Window>,
+	pub bit_depth_luma_minus8: u64,
+	pub bit_depth_chroma_minus8: u64,
+	pub log2_max_pic_order_cnt_lsb_minus4: u64,
+	/// One entry for every sub layer, or only one for the highest sub layer
+	/// if the sps_sub_layer_ordering_info_present_flag is not set.
+	pub sub_layer_ordering_info: Vec<SubLayerOrderingInfo>,
+	pub log2_min_luma_coding_block_size_minus3: u64,
+	pub log2_diff_max_min_luma_coding_block_size: u64,
+	pub log2_min_luma_transform_block_size_minus2: u64,
+	pub log2_diff_max_min_luma_transform_block_size: u64,
+	pub max_transform_hierarchy_depth_inter: u64,
+	pub max_transform_hierarchy_depth_intra: u64,
+	pub scaling_list_enabled_flag: bool,
+	/// Only present if the sps_scaling_list_data_present_flag is set
+	pub scaling_list_data: Option<ScalingListData>,
+	pub amp_enabled_flag: bool,
+	pub sample_adaptive_offset_enabled_flag: bool,
+	/// Only present if the pcm_enabled_flag is set
+	pub pcm: Option<Pcm>,
+	pub short_term_ref_pic_sets: Vec<ShortTermRefPicSet>,
+	/// Only present if the long_term_ref_pics_present_flag is set
+	pub long_term_ref_pics: Option<Vec<LongTermRefPic>>,
+	pub sps_temporal_mvp_enabled_flag: bool,
+	pub strong_intra_smoothing_enabled_flag: bool,
+	/// Only present if the vui_parameters_present_flag is set
+	pub vui: Option<Vui>,
+	/// Only present if the sps_range_extension_flag is set
+	pub range_extension: Option<SpsRangeExtension>,
+	/// The sps_multilayer_extension_flag, sps_3d_extension_flag,
+	/// sps_scc_extension_flag and sps_extension_4bits. The extensions they
+	/// signal are not parsed and cannot be written.
+	pub extension_flags: u8,
 	pub width: u64,
 	pub height: u64,
 	pub frame_rate: f64,
@@ -24,22 +76,121 @@ pub struct ColorConfig {
 	pub matrix_coefficients: u8,
 }
 
+impl ColorConfig {
+	/// Writes the colour_description_present_flag and the colour description,
+	/// which is left out if all values are unspecified.
+	pub(crate) fn mux_colour_description(&self, bit_writer: &mut BitWriter) -> io::Result<()> {
+		let colour_description_present_flag =
+			self.color_primaries != 2 || self.transfer_characteristics != 2 || self.matrix_coefficients != 2;
+
+		bit_writer.write_bit(colour_description_present_flag)?;
+		if colour_description_present_flag {
+			bit_writer.write_u8(self.color_primaries)?;
+			bit_writer.write_u8(self.transfer_characteristics)?;
+			bit_writer.write_u8(self.matrix_coefficients)?;
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// ISO/IEC 23008-2:2020(E) - 7.3.2.2.1
+pub struct Pcm {
+	pub pcm_sample_bit_depth_luma_minus1: u8,
+	pub pcm_sample_bit_depth_chroma_minus1: u8,
+	pub log2_min_pcm_luma_coding_block_size_minus3: u64,
+	pub log2_diff_max_min_pcm_luma_coding_block_size: u64,
+	pub pcm_loop_filter_disabled_flag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Short-term reference picture set
+/// ISO/IEC 23008-2:2020(E) - 7.3.7
+pub enum ShortTermRefPicSet {
+	/// Predicted from the previous set, this is the case if the
+	/// inter_ref_pic_set_prediction_flag is set.
+	Predicted {
+		delta_rps_sign: bool,
+		abs_delta_rps_minus1: u64,
+		/// One entry for every picture of the previous set and one for the
+		/// previous set itself
+		entries: Vec<PredictedRefPic>,
+	},
+	Explicit {
+		negative_pics: Vec<DeltaPoc>,
+		positive_pics: Vec<DeltaPoc>,
+	},
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredictedRefPic {
+	pub used_by_curr_pic_flag: bool,
+	/// Only written if the used_by_curr_pic_flag is not set
+	pub use_delta_flag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeltaPoc {
+	pub delta_poc_minus1: u64,
+	pub used_by_curr_pic_flag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LongTermRefPic {
+	pub lt_ref_pic_poc_lsb_sps: u64,
+	pub used_by_curr_pic_lt_sps_flag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Scaling list data, this is used in the SPS and PPS.
+/// ISO/IEC 23008-2:2020(E) - 7.3.4
+pub struct ScalingListData {
+	/// The lists in the order of the syntax, 6 lists for each of the first
+	/// three size ids and 2 lists for the 32x32 size id.
+	pub scaling_lists: Vec<ScalingList>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScalingList {
+	/// Copied from a previous list or the default list, this is the case if
+	/// the scaling_list_pred_mode_flag is not set.
+	Predicted { pred_matrix_id_delta: u64 },
+	Explicit {
+		/// Only present for the 16x16 and 32x32 lists
+		dc_coef_minus8: Option<i64>,
+		delta_coefs: Vec<i64>,
+	},
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// SPS range extension
+/// ISO/IEC 23008-2:2020(E) - 7.3.2.2.2
+pub struct SpsRangeExtension {
+	pub transform_skip_rotation_enabled_flag: bool,
+	pub transform_skip_context_enabled_flag: bool,
+	pub implicit_rdpcm_enabled_flag: bool,
+	pub explicit_rdpcm_enabled_flag: bool,
+	pub extended_precision_processing_flag: bool,
+	pub intra_smoothing_disabled_flag: bool,
+	pub high_precision_offsets_enabled_flag: bool,
+	pub persistent_rice_adaptation_enabled_flag: bool,
+	pub cabac_bypass_alignment_enabled_flag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The DPB size and reordering of a sub layer, this is used in the VPS and
+/// SPS.
+pub struct SubLayerOrderingInfo {
+	pub max_dec_pic_buffering_minus1: u64,
+	pub max_num_reorder_pics: u64,
+	pub max_latency_increase_plus1: u64,
+}
+
 impl Sps {
 	pub fn parse(data: Bytes) -> io::Result<Self> {
-		let mut vec = Vec::with_capacity(data.len());
-
-		// ISO/IEC-23008-2-2022 - 7.3.1.1
-		let mut i = 0;
-		while i < data.len() - 3 {
-			if data[i] == 0x00 && data[i + 1] == 0x00 && data[i + 2] == 0x03 {
-				vec.push(0x00);
-				vec.push(0x00);
-				i += 3;
-			} else {
-				vec.push(data[i]);
-				i += 1;
-			}
-		}
+		// ISO/IEC 23008-2:2020(E) - 7.3.1.1
+		let vec = remove_emulation_prevention(&data);
 
 		let mut bit_reader = BitReader::from(vec);
 
@@ -58,82 +209,30 @@ impl Sps {
 
 		bit_reader.seek_bits(
 			6 // nuh_layer_id
-            + 3 // nuh_temporal_id_plus1
-            + 4, // sps_video_parameter_set_id
+            + 3, // nuh_temporal_id_plus1
 		)?;
 
-		let sps_max_sub_layers_minus1 = bit_reader.read_bits(3)?;
-		bit_reader.seek_bits(1)?; // sps_temporal_id_nesting_flag
-		{
-			bit_reader.seek_bits(
-				2 // general_profile_space 
-                + 1 // general_tier_flag 
-                + 5 // general_profile_idc 
-                + 32 // general_profile_compatibility_flag 
-                + 1 // general_progressive_source_flag 
-                + 1 // general_interlaced_source_flag 
-                + 1 // general_non_packed_constraint_flag 
-                + 1 // general_frame_only_constraint_flag 
-                + 43 // general_reserved_zero_43bits 
-                + 1 // general_reserved_zero_bit
-                + 8, // general_level_idc
-			)?;
-
-			let mut sub_layer_level_present_flags = vec![false; sps_max_sub_layers_minus1 as usize];
-			for v in sub_layer_level_present_flags.iter_mut() {
-				bit_reader.seek_bits(1)?; // sub_layer_profile_present_flag
-				*v = bit_reader.read_bit()?; // sub_layer_level_present_flag
-			}
-
-			if sps_max_sub_layers_minus1 > 0 && sps_max_sub_layers_minus1 < 8 {
-				bit_reader.seek_bits(2 * (8 - sps_max_sub_layers_minus1 as i64))?;
-				// reserved_zero_2bits
-			}
+		let video_parameter_set_id = bit_reader.read_bits(4)? as u8;
+		let max_sub_layers_minus1 = bit_reader.read_bits(3)? as u8;
+		let temporal_id_nesting_flag = bit_reader.read_bit()?;
+		let profile_tier_level = ProfileTierLevel::parse(&mut bit_reader, max_sub_layers_minus1)?;
 
-			for v in sub_layer_level_present_flags.drain(..) {
-				bit_reader.seek_bits(
-					2 // sub_layer_profile_space
-                    + 1 // sub_layer_tier_flag
-                    + 5 // sub_layer_profile_idc
-                    + 32 // sub_layer_profile_compatibility_flag[32]
-                    + 1 // sub_layer_progressive_source_flag
-                    + 1 // sub_layer_interlaced_source_flag
-                    + 1 // sub_layer_non_packed_constraint_flag
-                    + 1 // sub_layer_frame_only_constraint_flag
-                    + 43 // sub_layer_reserved_zero_44bits
-                    + 1, // sub_layer_reserved_zero_bit
-				)?;
-				if v {
-					bit_reader.seek_bits(8)?; // sub_layer_level_idc
-				}
-			}
-		}
-
-		read_exp_golomb(&mut bit_reader)?; // sps_seq_parameter_set_id
+		let seq_parameter_set_id = read_exp_golomb(&mut bit_reader)?;
 		let chroma_format_idc = read_exp_golomb(&mut bit_reader)?;
-		if chroma_format_idc == 3 {
-			bit_reader.read_bit()?;
-		}
+		let separate_colour_plane_flag = if chroma_format_idc == 3 {
+			bit_reader.read_bit()?
+		} else {
+			false
+		};
 		let pic_width_in_luma_samples = read_exp_golomb(&mut bit_reader)?;
 		let pic_height_in_luma_samples = read_exp_golomb(&mut bit_reader)?;
-		let conformance_window_flag = bit_reader.read_bit()?;
-
-		let conf_win_left_offset;
-		let conf_win_right_offset;
-		let conf_win_top_offset;
-		let conf_win_bottom_offset;
-
-		if conformance_window_flag {
-			conf_win_left_offset = read_exp_golomb(&mut bit_reader)?;
-			conf_win_right_offset = read_exp_golomb(&mut bit_reader)?;
-			conf_win_top_offset = read_exp_golomb(&mut bit_reader)?;
-			conf_win_bottom_offset = read_exp_golomb(&mut bit_reader)?;
+
+		// conformance_window_flag
+		let conformance_window = if bit_reader.read_bit()? {
+			Some(Window::parse(&mut bit_reader)?)
 		} else {
-			conf_win_left_offset = 0;
-			conf_win_right_offset = 0;
-			conf_win_top_offset = 0;
-			conf_win_bottom_offset = 0;
-		}
+			None
+		};
 
 		let (sub_width_c, sub_height_c) = match chroma_format_idc {
 			0 => (1, 1),
@@ -143,196 +242,546 @@ impl Sps {
 			_ => return Err(io::Error::new(io::ErrorKind::InvalidData, "chroma_format_idc is not 0-3")),
 		};
 
-		let width = pic_width_in_luma_samples - sub_width_c * (conf_win_left_offset + conf_win_right_offset);
-		let height = pic_height_in_luma_samples - sub_height_c * (conf_win_top_offset + conf_win_bottom_offset);
-
-		read_exp_golomb(&mut bit_reader)?; // bit_depth_luma_minus8
-		read_exp_golomb(&mut bit_reader)?; // bit_depth_chroma_minus8
-		read_exp_golomb(&mut bit_reader)?; // log2_max_pic_order_cnt_lsb_minus4
-		let sps_sub_layer_ordering_info_present_flag = bit_reader.read_bit()?;
-
-		if sps_sub_layer_ordering_info_present_flag {
-			for _ in 0..=sps_max_sub_layers_minus1 {
-				read_exp_golomb(&mut bit_reader)?; // sps_max_dec_pic_buffering_minus1
-				read_exp_golomb(&mut bit_reader)?; // sps_max_num_reorder_pics
-				read_exp_golomb(&mut bit_reader)?; // sps_max_latency_increase_plus1
-			}
+		let (width, height) = match &conformance_window {
+			Some(window) => (
+				pic_width_in_luma_samples - sub_width_c * (window.left_offset + window.right_offset),
+				pic_height_in_luma_samples - sub_height_c * (window.top_offset + window.bottom_offset),
+			),
+			None => (pic_width_in_luma_samples, pic_height_in_luma_samples),
 		};
 
-		read_exp_golomb(&mut bit_reader)?; // log2_min_luma_coding_block_size_minus3
-		read_exp_golomb(&mut bit_reader)?; // log2_diff_max_min_luma_coding_block_size
-		read_exp_golomb(&mut bit_reader)?; // log2_min_transform_block_size_minus2
-		read_exp_golomb(&mut bit_reader)?; // log2_diff_max_min_transform_block_size
-		read_exp_golomb(&mut bit_reader)?; // max_transform_hierarchy_depth_inter
-		read_exp_golomb(&mut bit_reader)?; // max_transform_hierarchy_depth_intra
+		let bit_depth_luma_minus8 = read_exp_golomb(&mut bit_reader)?;
+		let bit_depth_chroma_minus8 = read_exp_golomb(&mut bit_reader)?;
+		let log2_max_pic_order_cnt_lsb_minus4 = read_exp_golomb(&mut bit_reader)?;
+		let sub_layer_ordering_info = SubLayerOrderingInfo::parse(&mut bit_reader, max_sub_layers_minus1)?;
+
+		let log2_min_luma_coding_block_size_minus3 = read_exp_golomb(&mut bit_reader)?;
+		let log2_diff_max_min_luma_coding_block_size = read_exp_golomb(&mut bit_reader)?;
+		let log2_min_luma_transform_block_size_minus2 = read_exp_golomb(&mut bit_reader)?;
+		let log2_diff_max_min_luma_transform_block_size = read_exp_golomb(&mut bit_reader)?;
+		let max_transform_hierarchy_depth_inter = read_exp_golomb(&mut bit_reader)?;
+		let max_transform_hierarchy_depth_intra = read_exp_golomb(&mut bit_reader)?;
 
 		let scaling_list_enabled_flag = bit_reader.read_bit()?;
+		let mut scaling_list_data = None;
 		if scaling_list_enabled_flag {
 			let sps_scaling_list_data_present_flag = bit_reader.read_bit()?;
 			if sps_scaling_list_data_present_flag {
-				for size_id in 0..4 {
-					let mut matrix_id = 0;
-					while matrix_id < 6 {
-						let scaling_list_pred_mode_flag = bit_reader.read_bit()?;
-						if !scaling_list_pred_mode_flag {
-							read_exp_golomb(&mut bit_reader)?; // scaling_list_pred_matrix_id_delta
-						} else {
-							let coef_num = 64.min(1 << (4 + (size_id << 1)));
-							let mut next_coef = 8;
-							if size_id > 1 {
-								let scaling_list_dc_coef_minus8 = read_signed_exp_golomb(&mut bit_reader)?;
-								next_coef = 8 + scaling_list_dc_coef_minus8;
-							}
-							for _ in 0..coef_num {
-								let scaling_list_delta_coef = read_signed_exp_golomb(&mut bit_reader)?;
-								next_coef = (next_coef + scaling_list_delta_coef + 256) % 256;
-							}
-						}
-						matrix_id += if size_id == 3 { 3 } else { 1 };
-					}
-				}
+				scaling_list_data = Some(ScalingListData::parse(&mut bit_reader)?);
 			}
 		}
 
-		bit_reader.seek_bits(1)?; // amp_enabled_flag
-		bit_reader.seek_bits(1)?; // sample_adaptive_offset_enabled_flag
+		let amp_enabled_flag = bit_reader.read_bit()?;
+		let sample_adaptive_offset_enabled_flag = bit_reader.read_bit()?;
+
+		// pcm_enabled_flag
+		let pcm = if bit_reader.read_bit()? {
+			Some(Pcm {
+				pcm_sample_bit_depth_luma_minus1: bit_reader.read_bits(4)? as u8,
+				pcm_sample_bit_depth_chroma_minus1: bit_reader.read_bits(4)? as u8,
+				log2_min_pcm_luma_coding_block_size_minus3: read_exp_golomb(&mut bit_reader)?,
+				log2_diff_max_min_pcm_luma_coding_block_size: read_exp_golomb(&mut bit_reader)?,
+				pcm_loop_filter_disabled_flag: bit_reader.read_bit()?,
+			})
+		} else {
+			None
+		};
 
-		if bit_reader.read_bit()? {
-			// pcm_enabled_flag
-			bit_reader.seek_bits(4)?; // pcm_sample_bit_depth_luma_minus1
-			bit_reader.seek_bits(4)?; // pcm_sample_bit_depth_chroma_minus1
-			read_exp_golomb(&mut bit_reader)?; // log2_min_pcm_luma_coding_block_size_minus3
-			read_exp_golomb(&mut bit_reader)?; // log2_diff_max_min_pcm_luma_coding_block_size
-			bit_reader.seek_bits(1)?; // pcm_loop_filter_disabled_flag
+		let num_short_term_ref_pic_sets = read_exp_golomb(&mut bit_reader)?;
+		if num_short_term_ref_pic_sets > 64 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"num_short_term_ref_pic_sets is out of range",
+			));
 		}
 
-		let num_short_term_ref_pic_sets = read_exp_golomb(&mut bit_reader)?;
-		let mut num_delta_pocs = vec![0; num_short_term_ref_pic_sets as usize];
-		for st_rps_idx in 0..num_short_term_ref_pic_sets {
-			if st_rps_idx != 0 && bit_reader.read_bit()? {
-				bit_reader.seek_bits(1)?;
-				read_exp_golomb(&mut bit_reader)?; // delta_rps_sign
-
-				num_delta_pocs[st_rps_idx as usize] = 0;
-
-				for _ in 0..num_delta_pocs[(st_rps_idx - 1) as usize] {
-					let used_by_curr_pic_flag = bit_reader.read_bit()?;
-					let use_delta_flag = if !used_by_curr_pic_flag {
-						bit_reader.read_bit()? // use_delta_flag
-					} else {
-						false
-					};
-
-					if used_by_curr_pic_flag || use_delta_flag {
-						num_delta_pocs[st_rps_idx as usize] += 1;
-					}
-				}
-			} else {
-				let num_negative_pics = read_exp_golomb(&mut bit_reader)?;
-				let num_positive_pics = read_exp_golomb(&mut bit_reader)?;
-
-				num_delta_pocs[st_rps_idx as usize] = num_negative_pics + num_positive_pics;
-				for _ in 0..num_negative_pics {
-					read_exp_golomb(&mut bit_reader)?; // delta_poc_s0_minus1
-					bit_reader.seek_bits(1)?; // used_by_curr_pic_s0_flag
-				}
-				for _ in 0..num_positive_pics {
-					read_exp_golomb(&mut bit_reader)?; // delta_poc_s1_minus1
-					bit_reader.seek_bits(1)?; // used_by_curr_pic_s1_flag
-				}
-			}
+		let mut short_term_ref_pic_sets: Vec<ShortTermRefPicSet> = Vec::with_capacity(num_short_term_ref_pic_sets as usize);
+		for _ in 0..num_short_term_ref_pic_sets {
+			let short_term_ref_pic_set = ShortTermRefPicSet::parse(&mut bit_reader, short_term_ref_pic_sets.last())?;
+			short_term_ref_pic_sets.push(short_term_ref_pic_set);
 		}
 
-		let long_term_ref_pics_present_flag = bit_reader.read_bit()?;
-		if long_term_ref_pics_present_flag {
+		// long_term_ref_pics_present_flag
+		let long_term_ref_pics = if bit_reader.read_bit()? {
 			let num_long_term_ref_pics_sps = read_exp_golomb(&mut bit_reader)?;
+			if num_long_term_ref_pics_sps > 32 {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					"num_long_term_ref_pics_sps is out of range",
+				));
+			}
+
+			let mut long_term_ref_pics = Vec::with_capacity(num_long_term_ref_pics_sps as usize);
 			for _ in 0..num_long_term_ref_pics_sps {
-				read_exp_golomb(&mut bit_reader)?; // lt_ref_pic_poc_lsb_sps
-				bit_reader.seek_bits(1)?; // used_by_curr_pic_lt_sps_flag
+				long_term_ref_pics.push(LongTermRefPic {
+					lt_ref_pic_poc_lsb_sps: bit_reader.read_bits(log2_max_pic_order_cnt_lsb_minus4 as u8 + 4)?,
+					used_by_curr_pic_lt_sps_flag: bit_reader.read_bit()?,
+				});
 			}
-		}
 
-		bit_reader.seek_bits(1)?; // sps_temporal_mvp_enabled_flag
-		bit_reader.seek_bits(1)?; // strong_intra_smoothing_enabled_flag
+			Some(long_term_ref_pics)
+		} else {
+			None
+		};
+
+		let sps_temporal_mvp_enabled_flag = bit_reader.read_bit()?;
+		let strong_intra_smoothing_enabled_flag = bit_reader.read_bit()?;
 		let vui_parameters_present_flag = bit_reader.read_bit()?;
 
-		let mut color_config = None;
+		let vui = if vui_parameters_present_flag {
+			Some(Vui::parse(&mut bit_reader, max_sub_layers_minus1)?)
+		} else {
+			None
+		};
+
+		let mut range_extension = None;
+		let mut extension_flags = 0;
 
-		let mut frame_rate = 0.0;
-		if vui_parameters_present_flag {
-			let aspect_ratio_info_present_flag = bit_reader.read_bit()?;
-			if aspect_ratio_info_present_flag {
-				let aspect_ratio_idc = bit_reader.read_bits(8)?;
-				if aspect_ratio_idc == 255 {
-					bit_reader.seek_bits(16)?; // sar_width
-					bit_reader.seek_bits(16)?; // sar_height
-				}
+		// sps_extension_present_flag
+		if bit_reader.read_bit()? {
+			let sps_range_extension_flag = bit_reader.read_bit()?;
+			extension_flags = bit_reader.read_bits(7)? as u8;
+
+			if sps_range_extension_flag {
+				range_extension = Some(SpsRangeExtension {
+					transform_skip_rotation_enabled_flag: bit_reader.read_bit()?,
+					transform_skip_context_enabled_flag: bit_reader.read_bit()?,
+					implicit_rdpcm_enabled_flag: bit_reader.read_bit()?,
+					explicit_rdpcm_enabled_flag: bit_reader.read_bit()?,
+					extended_precision_processing_flag: bit_reader.read_bit()?,
+					intra_smoothing_disabled_flag: bit_reader.read_bit()?,
+					high_precision_offsets_enabled_flag: bit_reader.read_bit()?,
+					persistent_rice_adaptation_enabled_flag: bit_reader.read_bit()?,
+					cabac_bypass_alignment_enabled_flag: bit_reader.read_bit()?,
+				});
+			}
+		}
+
+		let color_config = vui.as_ref().and_then(|vui| vui.color_config.clone());
+		let frame_rate = vui
+			.as_ref()
+			.and_then(|vui| vui.timing_info.as_ref())
+			.map_or(0.0, |timing_info| {
+				timing_info.time_scale as f64 / timing_info.num_units_in_tick as f64
+			});
+
+		Ok(Sps {
+			video_parameter_set_id,
+			max_sub_layers_minus1,
+			temporal_id_nesting_flag,
+			profile_tier_level,
+			seq_parameter_set_id,
+			chroma_format_idc,
+			separate_colour_plane_flag,
+			pic_width_in_luma_samples,
+			pic_height_in_luma_samples,
+			conformance_window,
+			bit_depth_luma_minus8,
+			bit_depth_chroma_minus8,
+			log2_max_pic_order_cnt_lsb_minus4,
+			sub_layer_ordering_info,
+			log2_min_luma_coding_block_size_minus3,
+			log2_diff_max_min_luma_coding_block_size,
+			log2_min_luma_transform_block_size_minus2,
+			log2_diff_max_min_luma_transform_block_size,
+			max_transform_hierarchy_depth_inter,
+			max_transform_hierarchy_depth_intra,
+			scaling_list_enabled_flag,
+			scaling_list_data,
+			amp_enabled_flag,
+			sample_adaptive_offset_enabled_flag,
+			pcm,
+			short_term_ref_pic_sets,
+			long_term_ref_pics,
+			sps_temporal_mvp_enabled_flag,
+			strong_intra_smoothing_enabled_flag,
+			vui,
+			range_extension,
+			extension_flags,
+			width,
+			height,
+			frame_rate,
+			color_config,
+		})
+	}
+
+	/// The bit depth of the luma samples
+	pub fn bit_depth_luma(&self) -> u64 {
+		self.bit_depth_luma_minus8 + 8
+	}
+
+	/// The bit depth of the chroma samples
+	pub fn bit_depth_chroma(&self) -> u64 {
+		self.bit_depth_chroma_minus8 + 8
+	}
+
+	/// Writes the SPS NAL unit with emulation prevention, the NAL unit header
+	/// is written with a nuh_layer_id of 0 and a nuh_temporal_id_plus1 of 1.
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		if self.extension_flags != 0 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"SPS extensions other than the range extension cannot be written",
+			));
+		}
+
+		let mut bit_writer = BitWriter::default();
+
+		bit_writer.write_bit(false)?; // forbidden_zero_bit
+		bit_writer.write_bits(33, 6)?; // nal_unit_type
+		bit_writer.write_bits(0, 6)?; // nuh_layer_id
+		bit_writer.write_bits(1, 3)?; // nuh_temporal_id_plus1
+
+		bit_writer.write_bits(self.video_parameter_set_id as u64, 4)?;
+		bit_writer.write_bits(self.max_sub_layers_minus1 as u64, 3)?;
+		bit_writer.write_bit(self.temporal_id_nesting_flag)?;
+		self.profile_tier_level.mux(&mut bit_writer)?;
+
+		write_exp_golomb(&mut bit_writer, self.seq_parameter_set_id)?;
+		write_exp_golomb(&mut bit_writer, self.chroma_format_idc)?;
+		if self.chroma_format_idc == 3 {
+			bit_writer.write_bit(self.separate_colour_plane_flag)?;
+		}
+		write_exp_golomb(&mut bit_writer, self.pic_width_in_luma_samples)?;
+		write_exp_golomb(&mut bit_writer, self.pic_height_in_luma_samples)?;
+
+		bit_writer.write_bit(self.conformance_window.is_some())?;
+		if let Some(conformance_window) = &self.conformance_window {
+			conformance_window.mux(&mut bit_writer)?;
+		}
+
+		write_exp_golomb(&mut bit_writer, self.bit_depth_luma_minus8)?;
+		write_exp_golomb(&mut bit_writer, self.bit_depth_chroma_minus8)?;
+		write_exp_golomb(&mut bit_writer, self.log2_max_pic_order_cnt_lsb_minus4)?;
+		SubLayerOrderingInfo::mux(&self.sub_layer_ordering_info, &mut bit_writer, self.max_sub_layers_minus1)?;
+
+		write_exp_golomb(&mut bit_writer, self.log2_min_luma_coding_block_size_minus3)?;
+		write_exp_golomb(&mut bit_writer, self.log2_diff_max_min_luma_coding_block_size)?;
+		write_exp_golomb(&mut bit_writer, self.log2_min_luma_transform_block_size_minus2)?;
+		write_exp_golomb(&mut bit_writer, self.log2_diff_max_min_luma_transform_block_size)?;
+		write_exp_golomb(&mut bit_writer, self.max_transform_hierarchy_depth_inter)?;
+		write_exp_golomb(&mut bit_writer, self.max_transform_hierarchy_depth_intra)?;
+
+		bit_writer.write_bit(self.scaling_list_enabled_flag)?;
+		if self.scaling_list_enabled_flag {
+			bit_writer.write_bit(self.scaling_list_data.is_some())?;
+			if let Some(scaling_list_data) = &self.scaling_list_data {
+				scaling_list_data.mux(&mut bit_writer)?;
 			}
+		}
+
+		bit_writer.write_bit(self.amp_enabled_flag)?;
+		bit_writer.write_bit(self.sample_adaptive_offset_enabled_flag)?;
 
-			let overscan_info_present_flag = bit_reader.read_bit()?;
-			if overscan_info_present_flag {
-				bit_reader.seek_bits(1)?; // overscan_appropriate_flag
+		bit_writer.write_bit(self.pcm.is_some())?;
+		if let Some(pcm) = &self.pcm {
+			bit_writer.write_bits(pcm.pcm_sample_bit_depth_luma_minus1 as u64, 4)?;
+			bit_writer.write_bits(pcm.pcm_sample_bit_depth_chroma_minus1 as u64, 4)?;
+			write_exp_golomb(&mut bit_writer, pcm.log2_min_pcm_luma_coding_block_size_minus3)?;
+			write_exp_golomb(&mut bit_writer, pcm.log2_diff_max_min_pcm_luma_coding_block_size)?;
+			bit_writer.write_bit(pcm.pcm_loop_filter_disabled_flag)?;
+		}
+
+		write_exp_golomb(&mut bit_writer, self.short_term_ref_pic_sets.len() as u64)?;
+		for (idx, short_term_ref_pic_set) in self.short_term_ref_pic_sets.iter().enumerate() {
+			let previous = idx.checked_sub(1).map(|idx| &self.short_term_ref_pic_sets[idx]);
+			short_term_ref_pic_set.mux(&mut bit_writer, previous)?;
+		}
+
+		bit_writer.write_bit(self.long_term_ref_pics.is_some())?;
+		if let Some(long_term_ref_pics) = &self.long_term_ref_pics {
+			write_exp_golomb(&mut bit_writer, long_term_ref_pics.len() as u64)?;
+			for long_term_ref_pic in long_term_ref_pics {
+				bit_writer.write_bits(
+					long_term_ref_pic.lt_ref_pic_poc_lsb_sps,
+					self.log2_max_pic_order_cnt_lsb_minus4 as usize + 4,
+				)?;
+				bit_writer.write_bit(long_term_ref_pic.used_by_curr_pic_lt_sps_flag)?;
 			}
+		}
 
-			let video_signal_type_present_flag = bit_reader.read_bit()?;
-			if video_signal_type_present_flag {
-				bit_reader.seek_bits(3)?; // video_format
-				let full_range = bit_reader.read_bit()?; // video_full_range_flag
-				let color_primaries;
-				let transfer_characteristics;
-				let matrix_coefficients;
-
-				let colour_description_present_flag = bit_reader.read_bit()?;
-				if colour_description_present_flag {
-					color_primaries = bit_reader.read_u8()?; // colour_primaries
-					transfer_characteristics = bit_reader.read_u8()?; // transfer_characteristics
-					matrix_coefficients = bit_reader.read_u8()?; // matrix_coeffs
-				} else {
-					color_primaries = 2; // Unspecified
-					transfer_characteristics = 2; // Unspecified
-					matrix_coefficients = 2; // Unspecified
-				}
+		bit_writer.write_bit(self.sps_temporal_mvp_enabled_flag)?;
+		bit_writer.write_bit(self.strong_intra_smoothing_enabled_flag)?;
+
+		bit_writer.write_bit(self.vui.is_some())?;
+		if let Some(vui) = &self.vui {
+			vui.mux(&mut bit_writer)?;
+		}
+
+		bit_writer.write_bit(self.range_extension.is_some())?; // sps_extension_present_flag
+		if let Some(range_extension) = &self.range_extension {
+			bit_writer.write_bit(true)?; // sps_range_extension_flag
+			bit_writer.write_bits(0, 7)?; // sps_multilayer_extension_flag to sps_extension_4bits
+
+			bit_writer.write_bit(range_extension.transform_skip_rotation_enabled_flag)?;
+			bit_writer.write_bit(range_extension.transform_skip_context_enabled_flag)?;
+			bit_writer.write_bit(range_extension.implicit_rdpcm_enabled_flag)?;
+			bit_writer.write_bit(range_extension.explicit_rdpcm_enabled_flag)?;
+			bit_writer.write_bit(range_extension.extended_precision_processing_flag)?;
+			bit_writer.write_bit(range_extension.intra_smoothing_disabled_flag)?;
+			bit_writer.write_bit(range_extension.high_precision_offsets_enabled_flag)?;
+			bit_writer.write_bit(range_extension.persistent_rice_adaptation_enabled_flag)?;
+			bit_writer.write_bit(range_extension.cabac_bypass_alignment_enabled_flag)?;
+		}
+
+		bit_writer.write_rbsp_trailing_bits()?;
 
-				color_config = Some(ColorConfig {
-					full_range,
-					color_primaries,
-					transfer_characteristics,
-					matrix_coefficients,
+		writer.write_all(&add_emulation_prevention(&bit_writer.into_inner()))
+	}
+
+	/// Writes the SPS NAL unit to a new buffer.
+	pub fn build(&self) -> io::Result<Bytes> {
+		let mut data = Vec::new();
+		self.mux(&mut data)?;
+		Ok(Bytes::from(data))
+	}
+}
+
+impl ShortTermRefPicSet {
+	/// The previous set is used for inter ref pic set prediction, it is
+	/// `None` for the first set.
+	pub fn parse(bit_reader: &mut BitReader, previous: Option<&Self>) -> io::Result<Self> {
+		let inter_ref_pic_set_prediction_flag = if previous.is_some() { bit_reader.read_bit()? } else { false };
+
+		if let Some(previous) = previous.filter(|_| inter_ref_pic_set_prediction_flag) {
+			let delta_rps_sign = bit_reader.read_bit()?;
+			let abs_delta_rps_minus1 = read_exp_golomb(bit_reader)?;
+
+			let mut entries = Vec::with_capacity(previous.num_delta_pocs() + 1);
+			for _ in 0..=previous.num_delta_pocs() {
+				let used_by_curr_pic_flag = bit_reader.read_bit()?;
+				// use_delta_flag is inferred to be 1 if it is not present
+				let use_delta_flag = used_by_curr_pic_flag || bit_reader.read_bit()?;
+
+				entries.push(PredictedRefPic {
+					used_by_curr_pic_flag,
+					use_delta_flag,
 				});
 			}
 
-			let chroma_loc_info_present_flag = bit_reader.read_bit()?;
-			if chroma_loc_info_present_flag {
-				read_exp_golomb(&mut bit_reader)?; // chroma_sample_loc_type_top_field
-				read_exp_golomb(&mut bit_reader)?; // chroma_sample_loc_type_bottom_field
+			return Ok(ShortTermRefPicSet::Predicted {
+				delta_rps_sign,
+				abs_delta_rps_minus1,
+				entries,
+			});
+		}
+
+		let num_negative_pics = read_exp_golomb(bit_reader)?;
+		let num_positive_pics = read_exp_golomb(bit_reader)?;
+		if num_negative_pics > 16 || num_positive_pics > 16 {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "num_delta_pocs is out of range"));
+		}
+
+		let mut read_delta_pocs = |count| {
+			(0..count)
+				.map(|_| {
+					Ok(DeltaPoc {
+						delta_poc_minus1: read_exp_golomb(bit_reader)?,
+						used_by_curr_pic_flag: bit_reader.read_bit()?,
+					})
+				})
+				.collect::<io::Result<Vec<_>>>()
+		};
+
+		let negative_pics = read_delta_pocs(num_negative_pics)?;
+		let positive_pics = read_delta_pocs(num_positive_pics)?;
+
+		Ok(ShortTermRefPicSet::Explicit {
+			negative_pics,
+			positive_pics,
+		})
+	}
+
+	pub fn mux(&self, bit_writer: &mut BitWriter, previous: Option<&Self>) -> io::Result<()> {
+		if previous.is_some() {
+			bit_writer.write_bit(matches!(self, ShortTermRefPicSet::Predicted { .. }))?; // inter_ref_pic_set_prediction_flag
+		}
+
+		match self {
+			ShortTermRefPicSet::Predicted {
+				delta_rps_sign,
+				abs_delta_rps_minus1,
+				entries,
+			} => {
+				if previous.map(|previous| previous.num_delta_pocs() + 1) != Some(entries.len()) {
+					return Err(io::Error::new(
+						io::ErrorKind::InvalidInput,
+						"the predicted ref pic set does not match the previous set",
+					));
+				}
+
+				bit_writer.write_bit(*delta_rps_sign)?;
+				write_exp_golomb(bit_writer, *abs_delta_rps_minus1)?;
+				for entry in entries {
+					bit_writer.write_bit(entry.used_by_curr_pic_flag)?;
+					if !entry.used_by_curr_pic_flag {
+						bit_writer.write_bit(entry.use_delta_flag)?;
+					}
+				}
+			}
+			ShortTermRefPicSet::Explicit {
+				negative_pics,
+				positive_pics,
+			} => {
+				write_exp_golomb(bit_writer, negative_pics.len() as u64)?;
+				write_exp_golomb(bit_writer, positive_pics.len() as u64)?;
+				for delta_poc in negative_pics.iter().chain(positive_pics) {
+					write_exp_golomb(bit_writer, delta_poc.delta_poc_minus1)?;
+					bit_writer.write_bit(delta_poc.used_by_curr_pic_flag)?;
+				}
 			}
+		}
 
-			bit_reader.seek_bits(1)?;
-			bit_reader.seek_bits(1)?;
-			bit_reader.seek_bits(1)?;
-			let default_display_window_flag = bit_reader.read_bit()?;
+		Ok(())
+	}
 
-			if default_display_window_flag {
-				read_exp_golomb(&mut bit_reader)?; // def_disp_win_left_offset
-				read_exp_golomb(&mut bit_reader)?; // def_disp_win_right_offset
-				read_exp_golomb(&mut bit_reader)?; // def_disp_win_top_offset
-				read_exp_golomb(&mut bit_reader)?; // def_disp_win_bottom_offset
+	/// The number of pictures in the set (NumDeltaPocs)
+	pub fn num_delta_pocs(&self) -> usize {
+		match self {
+			ShortTermRefPicSet::Predicted { entries, .. } => entries
+				.iter()
+				.filter(|entry| entry.used_by_curr_pic_flag || entry.use_delta_flag)
+				.count(),
+			ShortTermRefPicSet::Explicit {
+				negative_pics,
+				positive_pics,
+			} => negative_pics.len() + positive_pics.len(),
+		}
+	}
+}
+
+impl ScalingListData {
+	pub fn parse(bit_reader: &mut BitReader) -> io::Result<Self> {
+		let mut scaling_lists = Vec::with_capacity(20);
+		for size_id in 0..4 {
+			for _ in scaling_list_matrix_ids(size_id) {
+				let scaling_list_pred_mode_flag = bit_reader.read_bit()?;
+				if !scaling_list_pred_mode_flag {
+					scaling_lists.push(ScalingList::Predicted {
+						pred_matrix_id_delta: read_exp_golomb(bit_reader)?,
+					});
+					continue;
+				}
+
+				let coef_num = 64.min(1 << (4 + (size_id << 1)));
+				let dc_coef_minus8 = if size_id > 1 {
+					Some(read_signed_exp_golomb(bit_reader)?)
+				} else {
+					None
+				};
+
+				let mut delta_coefs = Vec::with_capacity(coef_num);
+				for _ in 0..coef_num {
+					delta_coefs.push(read_signed_exp_golomb(bit_reader)?);
+				}
+
+				scaling_lists.push(ScalingList::Explicit {
+					dc_coef_minus8,
+					delta_coefs,
+				});
 			}
+		}
 
-			let vui_timing_info_present_flag = bit_reader.read_bit()?;
-			if vui_timing_info_present_flag {
-				let num_units_in_tick = bit_reader.read_bits(32)?; // vui_num_units_in_tick
-				let time_scale = bit_reader.read_bits(32)?; // vui_time_scale
+		Ok(Self { scaling_lists })
+	}
 
-				frame_rate = time_scale as f64 / num_units_in_tick as f64;
+	pub fn mux(&self, bit_writer: &mut BitWriter) -> io::Result<()> {
+		let mut scaling_lists = self.scaling_lists.iter();
+		for size_id in 0..4 {
+			for _ in scaling_list_matrix_ids(size_id) {
+				let scaling_list = scaling_lists
+					.next()
+					.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "there are not enough scaling lists"))?;
+
+				match scaling_list {
+					ScalingList::Predicted { pred_matrix_id_delta } => {
+						bit_writer.write_bit(false)?; // scaling_list_pred_mode_flag
+						write_exp_golomb(bit_writer, *pred_matrix_id_delta)?;
+					}
+					ScalingList::Explicit {
+						dc_coef_minus8,
+						delta_coefs,
+					} => {
+						let coef_num = 64.min(1 << (4 + (size_id << 1)));
+						if delta_coefs.len() != coef_num || dc_coef_minus8.is_some() != (size_id > 1) {
+							return Err(io::Error::new(
+								io::ErrorKind::InvalidInput,
+								"the scaling list does not match its size",
+							));
+						}
+
+						bit_writer.write_bit(true)?; // scaling_list_pred_mode_flag
+						if let Some(dc_coef_minus8) = dc_coef_minus8 {
+							write_signed_exp_golomb(bit_writer, *dc_coef_minus8)?;
+						}
+						for delta_coef in delta_coefs {
+							write_signed_exp_golomb(bit_writer, *delta_coef)?;
+						}
+					}
+				}
 			}
 		}
 
-		Ok(Sps {
-			width,
-			height,
-			frame_rate,
-			color_config,
-		})
+		if scaling_lists.next().is_some() {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"there are too many scaling lists",
+			));
+		}
+
+		Ok(())
+	}
+}
+
+/// There are only two 32x32 lists, one for luma of intra and inter blocks.
+fn scaling_list_matrix_ids(size_id: usize) -> impl Iterator<Item = usize> {
+	(0..6).step_by(if size_id == 3 { 3 } else { 1 })
+}
+
+impl SubLayerOrderingInfo {
+	/// Parses the sub_layer_ordering_info_present_flag and the ordering info
+	/// which follows it.
+	pub fn parse(bit_reader: &mut BitReader, max_sub_layers_minus1: u8) -> io::Result<Vec<Self>> {
+		let sub_layer_ordering_info_present_flag = bit_reader.read_bit()?;
+
+		// Only the highest sub layer is present if the flag is not set
+		let first = if sub_layer_ordering_info_present_flag {
+			0
+		} else {
+			max_sub_layers_minus1
+		};
+
+		let mut sub_layer_ordering_info = Vec::with_capacity((max_sub_layers_minus1 - first) as usize + 1);
+		for _ in first..=max_sub_layers_minus1 {
+			sub_layer_ordering_info.push(SubLayerOrderingInfo {
+				max_dec_pic_buffering_minus1: read_exp_golomb(bit_reader)?,
+				max_num_reorder_pics: read_exp_golomb(bit_reader)?,
+				max_latency_increase_plus1: read_exp_golomb(bit_reader)?,
+			});
+		}
+
+		Ok(sub_layer_ordering_info)
+	}
+
+	/// Writes the sub_layer_ordering_info_present_flag and the ordering info.
+	/// The flag is set if there is an entry for every sub layer, so a single
+	/// sub layer is always written with the flag set.
+	pub fn mux(infos: &[Self], bit_writer: &mut BitWriter, max_sub_layers_minus1: u8) -> io::Result<()> {
+		let sub_layer_ordering_info_present_flag = infos.len() == max_sub_layers_minus1 as usize + 1;
+		if !sub_layer_ordering_info_present_flag && infos.len() != 1 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"the sub layer ordering info does not match max_sub_layers_minus1",
+			));
+		}
+
+		bit_writer.write_bit(sub_layer_ordering_info_present_flag)?;
+		for info in infos {
+			write_exp_golomb(bit_writer, info.max_dec_pic_buffering_minus1)?;
+			write_exp_golomb(bit_writer, info.max_num_reorder_pics)?;
+			write_exp_golomb(bit_writer, info.max_latency_increase_plus1)?;
+		}
+
+		Ok(())
 	}
 }
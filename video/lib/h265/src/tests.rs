@@ -2,8 +2,16 @@ use std::io;
 
 use bytes::Bytes;
 
-use crate::sps::{ColorConfig, Sps};
-use crate::{HEVCDecoderConfigurationRecord, NaluType};
+use crate::sps::{
+	ColorConfig, DeltaPoc, LongTermRefPic, Pcm, PredictedRefPic, ScalingList, ScalingListData, ShortTermRefPicSet, Sps,
+	SpsRangeExtension, SubLayerOrderingInfo,
+};
+use crate::{
+	ContentLightLevelInfo, CpbSpecification, HEVCDecoderConfigurationRecord, HrdParameters, MasteringDisplayColourVolume,
+	NalUnits, NaluType, Pps, ProfileTierLevel, SeiMessage, SeiPayloadType, SubLayerHrd, TimingInfo, Vps, Vui,
+};
+
+const CONFIG: &[u8] = b"\x01\x01@\0\0\0\x90\0\0\0\0\0\x99\xf0\0\xfc\xfd\xf8\xf8\0\0\x0f\x03 \0\x01\0\x18@\x01\x0c\x01\xff\xff\x01@\0\0\x03\0\x90\0\0\x03\0\0\x03\0\x99\x95@\x90!\0\x01\0=B\x01\x01\x01@\0\0\x03\0\x90\0\0\x03\0\0\x03\0\x99\xa0\x01@ \x05\xa1e\x95R\x90\x84d_\xf8\xc0Z\x80\x80\x80\x82\0\0\x03\0\x02\0\0\x03\x01 \xc0\x0b\xbc\xa2\0\x02bX\0\x011-\x08\"\0\x01\0\x07D\x01\xc0\x93|\x0c\xc9";
 
 #[test]
 fn test_sps_parse() {
@@ -13,6 +21,108 @@ fn test_sps_parse() {
 	assert_eq!(
 		sps,
 		Sps {
+			video_parameter_set_id: 0,
+			max_sub_layers_minus1: 0,
+			temporal_id_nesting_flag: true,
+			profile_tier_level: ProfileTierLevel {
+				general_profile_space: 0,
+				general_tier_flag: false,
+				general_profile_idc: 1,
+				general_profile_compatibility_flags: 0x4000_0000,
+				general_constraint_indicator_flags: 0x9000_0000_0000,
+				general_level_idc: 153,
+				sub_layers: vec![],
+			},
+			seq_parameter_set_id: 0,
+			chroma_format_idc: 1,
+			separate_colour_plane_flag: false,
+			pic_width_in_luma_samples: 2560,
+			pic_height_in_luma_samples: 1440,
+			conformance_window: None,
+			bit_depth_luma_minus8: 0,
+			bit_depth_chroma_minus8: 0,
+			log2_max_pic_order_cnt_lsb_minus4: 4,
+			sub_layer_ordering_info: vec![SubLayerOrderingInfo {
+				max_dec_pic_buffering_minus1: 4,
+				max_num_reorder_pics: 1,
+				max_latency_increase_plus1: 0,
+			}],
+			log2_min_luma_coding_block_size_minus3: 1,
+			log2_diff_max_min_luma_coding_block_size: 1,
+			log2_min_luma_transform_block_size_minus2: 0,
+			log2_diff_max_min_luma_transform_block_size: 3,
+			max_transform_hierarchy_depth_inter: 3,
+			max_transform_hierarchy_depth_intra: 3,
+			scaling_list_enabled_flag: false,
+			scaling_list_data: None,
+			amp_enabled_flag: true,
+			sample_adaptive_offset_enabled_flag: true,
+			pcm: None,
+			short_term_ref_pic_sets: vec![ShortTermRefPicSet::Explicit {
+				negative_pics: vec![
+					DeltaPoc {
+						delta_poc_minus1: 0,
+						used_by_curr_pic_flag: true,
+					};
+					4
+				],
+				positive_pics: vec![],
+			}],
+			long_term_ref_pics: None,
+			sps_temporal_mvp_enabled_flag: false,
+			strong_intra_smoothing_enabled_flag: false,
+			vui: Some(Vui {
+				aspect_ratio_idc: Some(1),
+				sar_width: 0,
+				sar_height: 0,
+				overscan_appropriate_flag: None,
+				video_format: 5,
+				color_config: Some(ColorConfig {
+					full_range: false,
+					color_primaries: 1,
+					matrix_coefficients: 1,
+					transfer_characteristics: 1,
+				}),
+				chroma_sample_loc_type: None,
+				neutral_chroma_indication_flag: false,
+				field_seq_flag: false,
+				frame_field_info_present_flag: false,
+				default_display_window: None,
+				timing_info: Some(TimingInfo {
+					num_units_in_tick: 1,
+					time_scale: 144,
+					num_ticks_poc_diff_one_minus1: None,
+				}),
+				hrd_parameters: Some(HrdParameters {
+					nal_hrd_parameters_present_flag: true,
+					vcl_hrd_parameters_present_flag: false,
+					sub_pic_hrd_params: None,
+					bit_rate_scale: 0,
+					cpb_size_scale: 0,
+					cpb_size_du_scale: 0,
+					initial_cpb_removal_delay_length_minus1: 23,
+					au_cpb_removal_delay_length_minus1: 15,
+					dpb_output_delay_length_minus1: 5,
+					sub_layers: vec![SubLayerHrd {
+						fixed_pic_rate_general_flag: false,
+						fixed_pic_rate_within_cvs_flag: false,
+						elemental_duration_in_tc_minus1: None,
+						low_delay_hrd_flag: false,
+						cpb_cnt_minus1: 0,
+						nal_cpb_specifications: vec![CpbSpecification {
+							bit_rate_value_minus1: 39061,
+							cpb_size_value_minus1: 156249,
+							cpb_size_du_value_minus1: None,
+							bit_rate_du_value_minus1: None,
+							cbr_flag: false,
+						}],
+						vcl_cpb_specifications: vec![],
+					}],
+				}),
+				bitstream_restriction: None,
+			}),
+			range_extension: None,
+			extension_flags: 0,
 			color_config: Some(ColorConfig {
 				full_range: false,
 				color_primaries: 1,
@@ -24,12 +134,132 @@ fn test_sps_parse() {
 			height: 1440,
 		}
 	);
+	assert_eq!(sps.bit_depth_luma(), 8);
+	assert_eq!(sps.bit_depth_chroma(), 8);
+}
+
+#[test]
+fn test_sps_build() {
+	let data = b"B\x01\x01\x01@\0\0\x03\0\x90\0\0\x03\0\0\x03\0\x99\xa0\x01@ \x05\xa1e\x95R\x90\x84d_\xf8\xc0Z\x80\x80\x80\x82\0\0\x03\0\x02\0\0\x03\x01 \xc0\x0b\xbc\xa2\0\x02bX\0\x011-\x08".to_vec();
+
+	let sps = Sps::parse(Bytes::from(data.clone())).unwrap();
+	assert_eq!(sps.build().unwrap(), data);
+
+	// Predicted ref pic sets, long term ref pics, scaling lists, PCM and the
+	// range extension
+	let mut sps = sps;
+	sps.short_term_ref_pic_sets.push(ShortTermRefPicSet::Predicted {
+		delta_rps_sign: false,
+		abs_delta_rps_minus1: 0,
+		entries: vec![
+			PredictedRefPic {
+				used_by_curr_pic_flag: true,
+				use_delta_flag: true,
+			},
+			PredictedRefPic {
+				used_by_curr_pic_flag: false,
+				use_delta_flag: false,
+			},
+			PredictedRefPic {
+				used_by_curr_pic_flag: false,
+				use_delta_flag: true,
+			},
+			PredictedRefPic {
+				used_by_curr_pic_flag: true,
+				use_delta_flag: true,
+			},
+			PredictedRefPic {
+				used_by_curr_pic_flag: false,
+				use_delta_flag: false,
+			},
+		],
+	});
+	sps.long_term_ref_pics = Some(vec![LongTermRefPic {
+		lt_ref_pic_poc_lsb_sps: 200,
+		used_by_curr_pic_lt_sps_flag: true,
+	}]);
+	sps.scaling_list_enabled_flag = true;
+	sps.scaling_list_data = Some(ScalingListData {
+		scaling_lists: (0..20)
+			.map(|idx| match idx {
+				0 => ScalingList::Explicit {
+					dc_coef_minus8: None,
+					delta_coefs: vec![8; 16],
+				},
+				19 => ScalingList::Explicit {
+					dc_coef_minus8: Some(-3),
+					delta_coefs: vec![-1; 64],
+				},
+				_ => ScalingList::Predicted { pred_matrix_id_delta: 0 },
+			})
+			.collect(),
+	});
+	sps.pcm = Some(Pcm {
+		pcm_sample_bit_depth_luma_minus1: 7,
+		pcm_sample_bit_depth_chroma_minus1: 7,
+		log2_min_pcm_luma_coding_block_size_minus3: 0,
+		log2_diff_max_min_pcm_luma_coding_block_size: 2,
+		pcm_loop_filter_disabled_flag: true,
+	});
+	sps.range_extension = Some(SpsRangeExtension {
+		transform_skip_rotation_enabled_flag: true,
+		transform_skip_context_enabled_flag: false,
+		implicit_rdpcm_enabled_flag: true,
+		explicit_rdpcm_enabled_flag: false,
+		extended_precision_processing_flag: false,
+		intra_smoothing_disabled_flag: false,
+		high_precision_offsets_enabled_flag: true,
+		persistent_rice_adaptation_enabled_flag: false,
+		cabac_bypass_alignment_enabled_flag: false,
+	});
+
+	assert_eq!(Sps::parse(sps.build().unwrap()).unwrap(), sps);
+
+	// The number of predicted entries has to match the previous set
+	sps.short_term_ref_pic_sets.swap(0, 1);
+	assert!(sps.build().is_err());
+}
+
+#[test]
+fn test_sps_rewrite_vui() {
+	let data = b"B\x01\x01\x01@\0\0\x03\0\x90\0\0\x03\0\0\x03\0\x99\xa0\x01@ \x05\xa1e\x95R\x90\x84d_\xf8\xc0Z\x80\x80\x80\x82\0\0\x03\0\x02\0\0\x03\x01 \xc0\x0b\xbc\xa2\0\x02bX\0\x011-\x08".to_vec();
+
+	let mut sps = Sps::parse(Bytes::from(data)).unwrap();
+	let vui = sps.vui.as_mut().unwrap();
+	vui.color_config = Some(ColorConfig {
+		full_range: true,
+		color_primaries: 9,
+		transfer_characteristics: 16,
+		matrix_coefficients: 9,
+	});
+	vui.timing_info = Some(TimingInfo {
+		num_units_in_tick: 1001,
+		time_scale: 60000,
+		num_ticks_poc_diff_one_minus1: None,
+	});
+
+	let rewritten = Sps::parse(sps.build().unwrap()).unwrap();
+	assert_eq!(rewritten.vui, sps.vui);
+	assert_eq!(rewritten.color_config, sps.vui.as_ref().unwrap().color_config);
+	assert_eq!(rewritten.frame_rate, 60000.0 / 1001.0);
+
+	// Without colour description
+	sps.vui.as_mut().unwrap().color_config = Some(ColorConfig {
+		full_range: false,
+		color_primaries: 2,
+		transfer_characteristics: 2,
+		matrix_coefficients: 2,
+	});
+	sps.vui.as_mut().unwrap().hrd_parameters = None;
+
+	let rewritten = Sps::parse(sps.build().unwrap()).unwrap();
+	assert_eq!(rewritten.vui, sps.vui);
 }
 
 #[test]
 fn test_config_demux() {
 	// h265 config
-	let data = Bytes::from(b"\x01\x01@\0\0\0\x90\0\0\0\0\0\x99\xf0\0\xfc\xfd\xf8\xf8\0\0\x0f\x03 \0\x01\0\x18@\x01\x0c\x01\xff\xff\x01@\0\0\x03\0\x90\0\0\x03\0\0\x03\0\x99\x95@\x90!\0\x01\0=B\x01\x01\x01@\0\0\x03\0\x90\0\0\x03\0\0\x03\0\x99\xa0\x01@ \x05\xa1e\x95R\x90\x84d_\xf8\xc0Z\x80\x80\x80\x82\0\0\x03\0\x02\0\0\x03\x01 \xc0\x0b\xbc\xa2\0\x02bX\0\x011-\x08\"\0\x01\0\x07D\x01\xc0\x93|\x0c\xc9".to_vec());
+	let data = Bytes::from(CONFIG.to_vec());
 
 	let config = HEVCDecoderConfigurationRecord::demux(&mut io::Cursor::new(data)).unwrap();
 
@@ -63,19 +293,17 @@ fn test_config_demux() {
 	assert_eq!(sps.nalus.len(), 1);
 	let sps = Sps::parse(sps.nalus[0].clone()).unwrap();
 	assert_eq!(
-		sps,
-		Sps {
-			color_config: Some(ColorConfig {
-				full_range: false,
-				color_primaries: 1,
-				matrix_coefficients: 1,
-				transfer_characteristics: 1,
-			}),
-			frame_rate: 144.0,
-			width: 2560,
-			height: 1440,
-		}
+		sps.color_config,
+		Some(ColorConfig {
+			full_range: false,
+			color_primaries: 1,
+			matrix_coefficients: 1,
+			transfer_characteristics: 1,
+		})
 	);
+	assert_eq!(sps.frame_rate, 144.0);
+	assert_eq!(sps.width, 2560);
+	assert_eq!(sps.height, 1440);
 
 	let pps = &config.arrays[2];
 	assert!(!pps.array_completeness);
@@ -85,7 +313,7 @@ fn test_config_demux() {
 
 #[test]
 fn test_config_mux() {
-	let data = Bytes::from(b"\x01\x01@\0\0\0\x90\0\0\0\0\0\x99\xf0\0\xfc\xfd\xf8\xf8\0\0\x0f\x03 \0\x01\0\x18@\x01\x0c\x01\xff\xff\x01@\0\0\x03\0\x90\0\0\x03\0\0\x03\0\x99\x95@\x90!\0\x01\0=B\x01\x01\x01@\0\0\x03\0\x90\0\0\x03\0\0\x03\0\x99\xa0\x01@ \x05\xa1e\x95R\x90\x84d_\xf8\xc0Z\x80\x80\x80\x82\0\0\x03\0\x02\0\0\x03\x01 \xc0\x0b\xbc\xa2\0\x02bX\0\x011-\x08\"\0\x01\0\x07D\x01\xc0\x93|\x0c\xc9".to_vec());
+	let data = Bytes::from(CONFIG.to_vec());
 
 	let config = HEVCDecoderConfigurationRecord::demux(&mut io::Cursor::new(data.clone())).unwrap();
 
@@ -96,3 +324,152 @@ fn test_config_mux() {
 
 	assert_eq!(buf, data.to_vec());
 }
+
+#[test]
+fn test_parse_vps() {
+	let data = Bytes::from(b"@\x01\x0c\x01\xff\xff\x01@\0\0\x03\0\x90\0\0\x03\0\0\x03\0\x99\x95@\x90".to_vec());
+
+	let vps = Vps::parse(data).unwrap();
+	assert_eq!(
+		vps,
+		Vps {
+			video_parameter_set_id: 0,
+			base_layer_internal_flag: true,
+			base_layer_available_flag: true,
+			max_layers_minus1: 0,
+			max_sub_layers_minus1: 0,
+			temporal_id_nesting_flag: true,
+			profile_tier_level: ProfileTierLevel {
+				general_profile_space: 0,
+				general_tier_flag: false,
+				general_profile_idc: 1,
+				general_profile_compatibility_flags: 0x4000_0000,
+				general_constraint_indicator_flags: 0x9000_0000_0000,
+				general_level_idc: 153,
+				sub_layers: vec![],
+			},
+			sub_layer_ordering_info: vec![SubLayerOrderingInfo {
+				max_dec_pic_buffering_minus1: 4,
+				max_num_reorder_pics: 1,
+				max_latency_increase_plus1: 0,
+			}],
+			max_layer_id: 0,
+			num_layer_sets_minus1: 0,
+			timing_info: None,
+			hrd_parameters: vec![],
+		}
+	);
+}
+
+#[test]
+fn test_parse_pps() {
+	let data = Bytes::from(b"D\x01\xc0\x93|\x0c\xc9".to_vec());
+
+	let pps = Pps::parse(data).unwrap();
+	assert_eq!(pps.pic_parameter_set_id, 0);
+	assert_eq!(pps.seq_parameter_set_id, 0);
+	assert!(!pps.dependent_slice_segments_enabled_flag);
+	assert_eq!(pps.num_extra_slice_header_bits, 0);
+	assert!(pps.cabac_init_present_flag);
+	assert_eq!(pps.num_ref_idx_l0_default_active_minus1, 3);
+	assert_eq!(pps.num_ref_idx_l1_default_active_minus1, 0);
+	assert_eq!(pps.init_qp_minus26, 0);
+	assert!(pps.transform_skip_enabled_flag);
+	assert_eq!(pps.diff_cu_qp_delta_depth, Some(0));
+	assert_eq!(pps.tiles, None);
+	assert!(pps.loop_filter_across_slices_enabled_flag);
+	assert!(pps.deblocking_filter_control.is_some());
+	assert!(!pps.scaling_list_data_present_flag);
+	assert_eq!(pps.log2_parallel_merge_level_minus2, 0);
+
+	// A VPS is not a PPS
+	assert!(Pps::parse(Bytes::from(b"@\x01\x0c\x01\xff\xff".to_vec())).is_err());
+}
+
+#[test]
+fn test_nal_units_annex_b() {
+	let config = HEVCDecoderConfigurationRecord::demux(&mut io::Cursor::new(Bytes::from(CONFIG.to_vec()))).unwrap();
+
+	let mut data = vec![0, 0, 0, 1];
+	data.extend_from_slice(&config.arrays[0].nalus[0]);
+	data.extend_from_slice(&[0, 0, 0, 1]);
+	data.extend_from_slice(&config.arrays[1].nalus[0]);
+	data.extend_from_slice(&[0, 0, 1]);
+	data.extend_from_slice(&config.arrays[2].nalus[0]);
+	data.extend_from_slice(&[0, 0]);
+
+	let nal_units = NalUnits::annex_b(Bytes::from(data)).collect::<io::Result<Vec<_>>>().unwrap();
+	assert_eq!(nal_units.len(), 3);
+	assert_eq!(nal_units[0].nal_unit_type, NaluType::Vps);
+	assert_eq!(nal_units[0].data, config.arrays[0].nalus[0]);
+	assert_eq!(nal_units[1].nal_unit_type, NaluType::Sps);
+	assert_eq!(nal_units[1].data, config.arrays[1].nalus[0]);
+	assert_eq!(nal_units[2].nal_unit_type, NaluType::Pps);
+	assert_eq!(nal_units[2].data, config.arrays[2].nalus[0]);
+	assert_eq!(nal_units[2].nuh_layer_id, 0);
+	assert_eq!(nal_units[2].nuh_temporal_id_plus1, 1);
+}
+
+#[test]
+fn test_nal_units_hvcc() {
+	let config = HEVCDecoderConfigurationRecord::demux(&mut io::Cursor::new(Bytes::from(CONFIG.to_vec()))).unwrap();
+
+	let mut data = Vec::new();
+	for array in &config.arrays {
+		data.extend_from_slice(&(array.nalus[0].len() as u32).to_be_bytes());
+		data.extend_from_slice(&array.nalus[0]);
+	}
+
+	let nal_units = NalUnits::hvcc(Bytes::from(data.clone()), config.length_size_minus_one + 1)
+		.collect::<io::Result<Vec<_>>>()
+		.unwrap();
+	assert_eq!(nal_units.len(), 3);
+	for (nal_unit, array) in nal_units.iter().zip(&config.arrays) {
+		assert_eq!(nal_unit.nal_unit_type, array.nal_unit_type);
+		assert_eq!(nal_unit.data, array.nalus[0]);
+	}
+
+	// A truncated NAL unit is an error and ends the iterator
+	data.truncate(data.len() - 1);
+	let mut nal_units = NalUnits::hvcc(Bytes::from(data), 4);
+	assert!(nal_units.next().unwrap().is_ok());
+	assert!(nal_units.next().unwrap().is_ok());
+	assert!(nal_units.next().unwrap().is_err());
+	assert!(nal_units.next().is_none());
+}
+
+#[test]
+fn test_parse_hdr_sei() {
+	// Prefix SEI NAL unit with a mastering display colour volume and a content
+	// light level information message, the min luminance needs an emulation
+	// prevention byte.
+	let data = Bytes::from(
+		b"N\x01\x89\x18\x33\xc2\x86\xc4\x1d\x4c\x0b\xb8\x84\xd0\x3e\x80\x3d\x13\x40\x42\x00\x98\x96\x80\x00\x00\x03\x00\x32\x90\x04\x03\xe8\x01\x90\x80"
+			.to_vec(),
+	);
+
+	let messages = SeiMessage::parse(data).unwrap();
+	assert_eq!(messages.len(), 2);
+
+	assert_eq!(messages[0].payload_type, SeiPayloadType::MasteringDisplayColourVolume);
+	let mdcv = MasteringDisplayColourVolume::parse(messages[0].payload.clone()).unwrap();
+	assert_eq!(
+		mdcv,
+		MasteringDisplayColourVolume {
+			display_primaries: [(13250, 34500), (7500, 3000), (34000, 16000)],
+			white_point: (15635, 16450),
+			max_display_mastering_luminance: 10_000_000,
+			min_display_mastering_luminance: 50,
+		}
+	);
+
+	assert_eq!(messages[1].payload_type, SeiPayloadType::ContentLightLevelInfo);
+	let clli = ContentLightLevelInfo::parse(messages[1].payload.clone()).unwrap();
+	assert_eq!(
+		clli,
+		ContentLightLevelInfo {
+			max_content_light_level: 1000,
+			max_pic_average_light_level: 400,
+		}
+	);
+}
@@ -0,0 +1,116 @@
+use std::io;
+
+use bytes::Bytes;
+use bytesio::bit_reader::BitReader;
+use exp_golomb::read_exp_golomb;
+
+use crate::nal::{NalUnit, NaluType};
+use crate::profile_tier_level::ProfileTierLevel;
+use crate::sps::SubLayerOrderingInfo;
+use crate::vui::{HrdParameters, TimingInfo};
+
+#[derive(Debug, Clone, PartialEq)]
+/// Video parameter set
+/// ISO/IEC 23008-2:2020(E) - 7.3.2.1
+///
+/// The VPS extension is not parsed.
+pub struct Vps {
+	pub video_parameter_set_id: u8,
+	pub base_layer_internal_flag: bool,
+	pub base_layer_available_flag: bool,
+	pub max_layers_minus1: u8,
+	pub max_sub_layers_minus1: u8,
+	pub temporal_id_nesting_flag: bool,
+	pub profile_tier_level: ProfileTierLevel,
+	/// One entry for every sub layer, or only one for the highest sub layer
+	/// if the vps_sub_layer_ordering_info_present_flag is not set.
+	pub sub_layer_ordering_info: Vec<SubLayerOrderingInfo>,
+	pub max_layer_id: u8,
+	pub num_layer_sets_minus1: u64,
+	/// Only present if the vps_timing_info_present_flag is set
+	pub timing_info: Option<TimingInfo>,
+	/// The HRD parameters and the layer set they apply to
+	pub hrd_parameters: Vec<(u64, HrdParameters)>,
+}
+
+impl Vps {
+	pub fn parse(data: Bytes) -> io::Result<Self> {
+		let nal_unit = NalUnit::parse(data)?;
+		if nal_unit.nal_unit_type != NaluType::Vps {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "nalu_type is not 32"));
+		}
+
+		let mut bit_reader = BitReader::from(nal_unit.rbsp());
+
+		let video_parameter_set_id = bit_reader.read_bits(4)? as u8;
+		let base_layer_internal_flag = bit_reader.read_bit()?;
+		let base_layer_available_flag = bit_reader.read_bit()?;
+		let max_layers_minus1 = bit_reader.read_bits(6)? as u8;
+		let max_sub_layers_minus1 = bit_reader.read_bits(3)? as u8;
+		let temporal_id_nesting_flag = bit_reader.read_bit()?;
+
+		let vps_reserved_0xffff_16bits = bit_reader.read_bits(16)?;
+		if vps_reserved_0xffff_16bits != 0xFFFF {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"vps_reserved_0xffff_16bits is not 0xFFFF",
+			));
+		}
+
+		let profile_tier_level = ProfileTierLevel::parse(&mut bit_reader, max_sub_layers_minus1)?;
+		let sub_layer_ordering_info = SubLayerOrderingInfo::parse(&mut bit_reader, max_sub_layers_minus1)?;
+
+		let max_layer_id = bit_reader.read_bits(6)? as u8;
+		let num_layer_sets_minus1 = read_exp_golomb(&mut bit_reader)?;
+		if num_layer_sets_minus1 > 1023 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"vps_num_layer_sets_minus1 is out of range",
+			));
+		}
+
+		// layer_id_included_flag
+		bit_reader.seek_bits(num_layer_sets_minus1 as i64 * (max_layer_id as i64 + 1))?;
+
+		let mut timing_info = None;
+		let mut hrd_parameters = Vec::new();
+
+		// vps_timing_info_present_flag
+		if bit_reader.read_bit()? {
+			timing_info = Some(TimingInfo::parse(&mut bit_reader)?);
+
+			let num_hrd_parameters = read_exp_golomb(&mut bit_reader)?;
+			if num_hrd_parameters > num_layer_sets_minus1 + 1 {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					"vps_num_hrd_parameters is out of range",
+				));
+			}
+
+			for i in 0..num_hrd_parameters {
+				let hrd_layer_set_idx = read_exp_golomb(&mut bit_reader)?;
+				// cprms_present_flag, the first one is always 1
+				let cprms_present_flag = i == 0 || bit_reader.read_bit()?;
+				hrd_parameters.push((
+					hrd_layer_set_idx,
+					HrdParameters::parse(&mut bit_reader, cprms_present_flag, max_sub_layers_minus1)?,
+				));
+			}
+		}
+
+		Ok(Vps {
+			video_parameter_set_id,
+			base_layer_internal_flag,
+			base_layer_available_flag,
+			max_layers_minus1,
+			max_sub_layers_minus1,
+			temporal_id_nesting_flag,
+			profile_tier_level,
+			sub_layer_ordering_info,
+			max_layer_id,
+			num_layer_sets_minus1,
+			timing_info,
+			hrd_parameters,
+		})
+	}
+}
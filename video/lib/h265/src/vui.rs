@@ -0,0 +1,587 @@
+use std::io;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use bytesio::bit_reader::BitReader;
+use bytesio::bit_writer::BitWriter;
+use exp_golomb::{read_exp_golomb, write_exp_golomb};
+
+use crate::sps::ColorConfig;
+
+#[derive(Debug, Clone, PartialEq)]
+/// VUI parameters
+/// ISO/IEC 23008-2:2020(E) - E.2.1
+pub struct Vui {
+	/// Only present if the aspect_ratio_info_present_flag is set
+	pub aspect_ratio_idc: Option<u8>,
+	/// Only used if the aspect_ratio_idc is 255 (EXTENDED_SAR)
+	pub sar_width: u16,
+	pub sar_height: u16,
+	/// Only present if the overscan_info_present_flag is set
+	pub overscan_appropriate_flag: Option<bool>,
+	/// 5 means unspecified
+	pub video_format: u8,
+	/// Only present if the video_signal_type_present_flag is set. The colour
+	/// description is only written if one of the values is not 2
+	/// (unspecified).
+	pub color_config: Option<ColorConfig>,
+	/// Only present if the chroma_loc_info_present_flag is set
+	pub chroma_sample_loc_type: Option<(u64, u64)>,
+	pub neutral_chroma_indication_flag: bool,
+	pub field_seq_flag: bool,
+	pub frame_field_info_present_flag: bool,
+	/// Only present if the default_display_window_flag is set
+	pub default_display_window: Option<Window>,
+	/// Only present if the vui_timing_info_present_flag is set
+	pub timing_info: Option<TimingInfo>,
+	/// Only present if the vui_hrd_parameters_present_flag is set
+	pub hrd_parameters: Option<HrdParameters>,
+	/// Only present if the bitstream_restriction_flag is set
+	pub bitstream_restriction: Option<BitstreamRestriction>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A window in luma samples, scaled by the chroma subsampling. This is used
+/// for the conformance window and the default display window.
+pub struct Window {
+	pub left_offset: u64,
+	pub right_offset: u64,
+	pub top_offset: u64,
+	pub bottom_offset: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Timing info from the VPS or VUI
+/// ISO/IEC 23008-2:2020(E) - 7.3.2.1, E.2.1
+pub struct TimingInfo {
+	pub num_units_in_tick: u32,
+	pub time_scale: u32,
+	/// Only present if the poc_proportional_to_timing_flag is set
+	pub num_ticks_poc_diff_one_minus1: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// ISO/IEC 23008-2:2020(E) - E.2.1
+pub struct BitstreamRestriction {
+	pub tiles_fixed_structure_flag: bool,
+	pub motion_vectors_over_pic_boundaries_flag: bool,
+	pub restricted_ref_pic_lists_flag: bool,
+	pub min_spatial_segmentation_idc: u64,
+	pub max_bytes_per_pic_denom: u64,
+	pub max_bits_per_min_cu_denom: u64,
+	pub log2_max_mv_length_horizontal: u64,
+	pub log2_max_mv_length_vertical: u64,
+}
+
+impl Vui {
+	pub fn parse(bit_reader: &mut BitReader, max_sub_layers_minus1: u8) -> io::Result<Self> {
+		let mut aspect_ratio_idc = None;
+		let mut sar_width = 0;
+		let mut sar_height = 0;
+
+		// aspect_ratio_info_present_flag
+		if bit_reader.read_bit()? {
+			let idc = bit_reader.read_u8()?;
+			if idc == 255 {
+				sar_width = bit_reader.read_u16::<BigEndian>()?;
+				sar_height = bit_reader.read_u16::<BigEndian>()?;
+			}
+
+			aspect_ratio_idc = Some(idc);
+		}
+
+		// overscan_info_present_flag
+		let overscan_appropriate_flag = if bit_reader.read_bit()? {
+			Some(bit_reader.read_bit()?)
+		} else {
+			None
+		};
+
+		let mut video_format = 5;
+		let mut color_config = None;
+
+		// video_signal_type_present_flag
+		if bit_reader.read_bit()? {
+			video_format = bit_reader.read_bits(3)? as u8;
+			let full_range = bit_reader.read_bit()?; // video_full_range_flag
+			let color_primaries;
+			let transfer_characteristics;
+			let matrix_coefficients;
+
+			let colour_description_present_flag = bit_reader.read_bit()?;
+			if colour_description_present_flag {
+				color_primaries = bit_reader.read_u8()?; // colour_primaries
+				transfer_characteristics = bit_reader.read_u8()?; // transfer_characteristics
+				matrix_coefficients = bit_reader.read_u8()?; // matrix_coeffs
+			} else {
+				color_primaries = 2; // Unspecified
+				transfer_characteristics = 2; // Unspecified
+				matrix_coefficients = 2; // Unspecified
+			}
+
+			color_config = Some(ColorConfig {
+				full_range,
+				color_primaries,
+				transfer_characteristics,
+				matrix_coefficients,
+			});
+		}
+
+		// chroma_loc_info_present_flag
+		let chroma_sample_loc_type = if bit_reader.read_bit()? {
+			let top_field = read_exp_golomb(bit_reader)?; // chroma_sample_loc_type_top_field
+			let bottom_field = read_exp_golomb(bit_reader)?; // chroma_sample_loc_type_bottom_field
+			Some((top_field, bottom_field))
+		} else {
+			None
+		};
+
+		let neutral_chroma_indication_flag = bit_reader.read_bit()?;
+		let field_seq_flag = bit_reader.read_bit()?;
+		let frame_field_info_present_flag = bit_reader.read_bit()?;
+
+		// default_display_window_flag
+		let default_display_window = if bit_reader.read_bit()? {
+			Some(Window::parse(bit_reader)?)
+		} else {
+			None
+		};
+
+		let mut timing_info = None;
+		let mut hrd_parameters = None;
+
+		// vui_timing_info_present_flag
+		if bit_reader.read_bit()? {
+			timing_info = Some(TimingInfo::parse(bit_reader)?);
+
+			// vui_hrd_parameters_present_flag
+			if bit_reader.read_bit()? {
+				hrd_parameters = Some(HrdParameters::parse(bit_reader, true, max_sub_layers_minus1)?);
+			}
+		}
+
+		// bitstream_restriction_flag
+		let bitstream_restriction = if bit_reader.read_bit()? {
+			Some(BitstreamRestriction {
+				tiles_fixed_structure_flag: bit_reader.read_bit()?,
+				motion_vectors_over_pic_boundaries_flag: bit_reader.read_bit()?,
+				restricted_ref_pic_lists_flag: bit_reader.read_bit()?,
+				min_spatial_segmentation_idc: read_exp_golomb(bit_reader)?,
+				max_bytes_per_pic_denom: read_exp_golomb(bit_reader)?,
+				max_bits_per_min_cu_denom: read_exp_golomb(bit_reader)?,
+				log2_max_mv_length_horizontal: read_exp_golomb(bit_reader)?,
+				log2_max_mv_length_vertical: read_exp_golomb(bit_reader)?,
+			})
+		} else {
+			None
+		};
+
+		Ok(Vui {
+			aspect_ratio_idc,
+			sar_width,
+			sar_height,
+			overscan_appropriate_flag,
+			video_format,
+			color_config,
+			chroma_sample_loc_type,
+			neutral_chroma_indication_flag,
+			field_seq_flag,
+			frame_field_info_present_flag,
+			default_display_window,
+			timing_info,
+			hrd_parameters,
+			bitstream_restriction,
+		})
+	}
+
+	pub fn mux(&self, bit_writer: &mut BitWriter) -> io::Result<()> {
+		bit_writer.write_bit(self.aspect_ratio_idc.is_some())?;
+		if let Some(aspect_ratio_idc) = self.aspect_ratio_idc {
+			bit_writer.write_u8(aspect_ratio_idc)?;
+			if aspect_ratio_idc == 255 {
+				bit_writer.write_u16::<BigEndian>(self.sar_width)?;
+				bit_writer.write_u16::<BigEndian>(self.sar_height)?;
+			}
+		}
+
+		bit_writer.write_bit(self.overscan_appropriate_flag.is_some())?;
+		if let Some(overscan_appropriate_flag) = self.overscan_appropriate_flag {
+			bit_writer.write_bit(overscan_appropriate_flag)?;
+		}
+
+		bit_writer.write_bit(self.color_config.is_some())?;
+		if let Some(color_config) = &self.color_config {
+			bit_writer.write_bits(self.video_format as u64, 3)?;
+			bit_writer.write_bit(color_config.full_range)?;
+			color_config.mux_colour_description(bit_writer)?;
+		}
+
+		bit_writer.write_bit(self.chroma_sample_loc_type.is_some())?;
+		if let Some((top_field, bottom_field)) = self.chroma_sample_loc_type {
+			write_exp_golomb(bit_writer, top_field)?;
+			write_exp_golomb(bit_writer, bottom_field)?;
+		}
+
+		bit_writer.write_bit(self.neutral_chroma_indication_flag)?;
+		bit_writer.write_bit(self.field_seq_flag)?;
+		bit_writer.write_bit(self.frame_field_info_present_flag)?;
+
+		bit_writer.write_bit(self.default_display_window.is_some())?;
+		if let Some(default_display_window) = &self.default_display_window {
+			default_display_window.mux(bit_writer)?;
+		}
+
+		bit_writer.write_bit(self.timing_info.is_some())?;
+		if let Some(timing_info) = &self.timing_info {
+			timing_info.mux(bit_writer)?;
+
+			bit_writer.write_bit(self.hrd_parameters.is_some())?;
+			if let Some(hrd_parameters) = &self.hrd_parameters {
+				hrd_parameters.mux(bit_writer, true)?;
+			}
+		} else if self.hrd_parameters.is_some() {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"the HRD parameters require the timing info",
+			));
+		}
+
+		bit_writer.write_bit(self.bitstream_restriction.is_some())?;
+		if let Some(bitstream_restriction) = &self.bitstream_restriction {
+			bit_writer.write_bit(bitstream_restriction.tiles_fixed_structure_flag)?;
+			bit_writer.write_bit(bitstream_restriction.motion_vectors_over_pic_boundaries_flag)?;
+			bit_writer.write_bit(bitstream_restriction.restricted_ref_pic_lists_flag)?;
+			write_exp_golomb(bit_writer, bitstream_restriction.min_spatial_segmentation_idc)?;
+			write_exp_golomb(bit_writer, bitstream_restriction.max_bytes_per_pic_denom)?;
+			write_exp_golomb(bit_writer, bitstream_restriction.max_bits_per_min_cu_denom)?;
+			write_exp_golomb(bit_writer, bitstream_restriction.log2_max_mv_length_horizontal)?;
+			write_exp_golomb(bit_writer, bitstream_restriction.log2_max_mv_length_vertical)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl Window {
+	pub fn parse(bit_reader: &mut BitReader) -> io::Result<Self> {
+		Ok(Window {
+			left_offset: read_exp_golomb(bit_reader)?,
+			right_offset: read_exp_golomb(bit_reader)?,
+			top_offset: read_exp_golomb(bit_reader)?,
+			bottom_offset: read_exp_golomb(bit_reader)?,
+		})
+	}
+
+	pub fn mux(&self, bit_writer: &mut BitWriter) -> io::Result<()> {
+		write_exp_golomb(bit_writer, self.left_offset)?;
+		write_exp_golomb(bit_writer, self.right_offset)?;
+		write_exp_golomb(bit_writer, self.top_offset)?;
+		write_exp_golomb(bit_writer, self.bottom_offset)
+	}
+}
+
+impl TimingInfo {
+	pub fn parse(bit_reader: &mut BitReader) -> io::Result<Self> {
+		let num_units_in_tick = bit_reader.read_u32::<BigEndian>()?;
+		let time_scale = bit_reader.read_u32::<BigEndian>()?;
+
+		// poc_proportional_to_timing_flag
+		let num_ticks_poc_diff_one_minus1 = if bit_reader.read_bit()? {
+			Some(read_exp_golomb(bit_reader)?)
+		} else {
+			None
+		};
+
+		Ok(TimingInfo {
+			num_units_in_tick,
+			time_scale,
+			num_ticks_poc_diff_one_minus1,
+		})
+	}
+
+	pub fn mux(&self, bit_writer: &mut BitWriter) -> io::Result<()> {
+		bit_writer.write_u32::<BigEndian>(self.num_units_in_tick)?;
+		bit_writer.write_u32::<BigEndian>(self.time_scale)?;
+
+		bit_writer.write_bit(self.num_ticks_poc_diff_one_minus1.is_some())?;
+		if let Some(num_ticks_poc_diff_one_minus1) = self.num_ticks_poc_diff_one_minus1 {
+			write_exp_golomb(bit_writer, num_ticks_poc_diff_one_minus1)?;
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// HRD parameters
+/// ISO/IEC 23008-2:2020(E) - E.2.2
+pub struct HrdParameters {
+	pub nal_hrd_parameters_present_flag: bool,
+	pub vcl_hrd_parameters_present_flag: bool,
+	/// Only present if the sub_pic_hrd_params_present_flag is set
+	pub sub_pic_hrd_params: Option<SubPicHrdParams>,
+	pub bit_rate_scale: u8,
+	pub cpb_size_scale: u8,
+	/// Only used if there are sub picture HRD parameters
+	pub cpb_size_du_scale: u8,
+	pub initial_cpb_removal_delay_length_minus1: u8,
+	pub au_cpb_removal_delay_length_minus1: u8,
+	pub dpb_output_delay_length_minus1: u8,
+	/// One entry for every sub layer
+	pub sub_layers: Vec<SubLayerHrd>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// ISO/IEC 23008-2:2020(E) - E.2.2
+pub struct SubPicHrdParams {
+	pub tick_divisor_minus2: u8,
+	pub du_cpb_removal_delay_increment_length_minus1: u8,
+	pub sub_pic_cpb_params_in_pic_timing_sei_flag: bool,
+	pub dpb_output_delay_du_length_minus1: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubLayerHrd {
+	pub fixed_pic_rate_general_flag: bool,
+	pub fixed_pic_rate_within_cvs_flag: bool,
+	/// Only present if the fixed_pic_rate_within_cvs_flag is set
+	pub elemental_duration_in_tc_minus1: Option<u64>,
+	pub low_delay_hrd_flag: bool,
+	pub cpb_cnt_minus1: u64,
+	/// One entry for every CPB if the nal_hrd_parameters_present_flag is set
+	pub nal_cpb_specifications: Vec<CpbSpecification>,
+	/// One entry for every CPB if the vcl_hrd_parameters_present_flag is set
+	pub vcl_cpb_specifications: Vec<CpbSpecification>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The bit rate and buffer size of a CPB
+/// ISO/IEC 23008-2:2020(E) - E.2.3
+pub struct CpbSpecification {
+	pub bit_rate_value_minus1: u64,
+	pub cpb_size_value_minus1: u64,
+	/// Only present if there are sub picture HRD parameters
+	pub cpb_size_du_value_minus1: Option<u64>,
+	/// Only present if there are sub picture HRD parameters
+	pub bit_rate_du_value_minus1: Option<u64>,
+	pub cbr_flag: bool,
+}
+
+impl HrdParameters {
+	pub fn parse(bit_reader: &mut BitReader, common_inf_present_flag: bool, max_sub_layers_minus1: u8) -> io::Result<Self> {
+		let mut nal_hrd_parameters_present_flag = false;
+		let mut vcl_hrd_parameters_present_flag = false;
+		let mut sub_pic_hrd_params = None;
+		let mut bit_rate_scale = 0;
+		let mut cpb_size_scale = 0;
+		let mut cpb_size_du_scale = 0;
+		// The lengths are 24 bits if they are not present
+		let mut initial_cpb_removal_delay_length_minus1 = 23;
+		let mut au_cpb_removal_delay_length_minus1 = 23;
+		let mut dpb_output_delay_length_minus1 = 23;
+
+		if common_inf_present_flag {
+			nal_hrd_parameters_present_flag = bit_reader.read_bit()?;
+			vcl_hrd_parameters_present_flag = bit_reader.read_bit()?;
+
+			if nal_hrd_parameters_present_flag || vcl_hrd_parameters_present_flag {
+				// sub_pic_hrd_params_present_flag
+				if bit_reader.read_bit()? {
+					sub_pic_hrd_params = Some(SubPicHrdParams {
+						tick_divisor_minus2: bit_reader.read_bits(8)? as u8,
+						du_cpb_removal_delay_increment_length_minus1: bit_reader.read_bits(5)? as u8,
+						sub_pic_cpb_params_in_pic_timing_sei_flag: bit_reader.read_bit()?,
+						dpb_output_delay_du_length_minus1: bit_reader.read_bits(5)? as u8,
+					});
+				}
+
+				bit_rate_scale = bit_reader.read_bits(4)? as u8;
+				cpb_size_scale = bit_reader.read_bits(4)? as u8;
+
+				if sub_pic_hrd_params.is_some() {
+					cpb_size_du_scale = bit_reader.read_bits(4)? as u8;
+				}
+
+				initial_cpb_removal_delay_length_minus1 = bit_reader.read_bits(5)? as u8;
+				au_cpb_removal_delay_length_minus1 = bit_reader.read_bits(5)? as u8;
+				dpb_output_delay_length_minus1 = bit_reader.read_bits(5)? as u8;
+			}
+		}
+
+		let sub_pic_hrd_params_present_flag = sub_pic_hrd_params.is_some();
+
+		let mut sub_layers = Vec::with_capacity(max_sub_layers_minus1 as usize + 1);
+		for _ in 0..=max_sub_layers_minus1 {
+			let fixed_pic_rate_general_flag = bit_reader.read_bit()?;
+			let fixed_pic_rate_within_cvs_flag = if fixed_pic_rate_general_flag {
+				true
+			} else {
+				bit_reader.read_bit()?
+			};
+
+			let mut elemental_duration_in_tc_minus1 = None;
+			let mut low_delay_hrd_flag = false;
+			if fixed_pic_rate_within_cvs_flag {
+				elemental_duration_in_tc_minus1 = Some(read_exp_golomb(bit_reader)?);
+			} else {
+				low_delay_hrd_flag = bit_reader.read_bit()?;
+			}
+
+			let cpb_cnt_minus1 = if !low_delay_hrd_flag {
+				read_exp_golomb(bit_reader)?
+			} else {
+				0
+			};
+
+			if cpb_cnt_minus1 > 31 {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, "cpb_cnt_minus1 is out of range"));
+			}
+
+			// sub_layer_hrd_parameters() for the NAL and VCL HRD
+			let mut nal_cpb_specifications = Vec::new();
+			if nal_hrd_parameters_present_flag {
+				nal_cpb_specifications =
+					CpbSpecification::parse_list(bit_reader, cpb_cnt_minus1, sub_pic_hrd_params_present_flag)?;
+			}
+
+			let mut vcl_cpb_specifications = Vec::new();
+			if vcl_hrd_parameters_present_flag {
+				vcl_cpb_specifications =
+					CpbSpecification::parse_list(bit_reader, cpb_cnt_minus1, sub_pic_hrd_params_present_flag)?;
+			}
+
+			sub_layers.push(SubLayerHrd {
+				fixed_pic_rate_general_flag,
+				fixed_pic_rate_within_cvs_flag,
+				elemental_duration_in_tc_minus1,
+				low_delay_hrd_flag,
+				cpb_cnt_minus1,
+				nal_cpb_specifications,
+				vcl_cpb_specifications,
+			});
+		}
+
+		Ok(HrdParameters {
+			nal_hrd_parameters_present_flag,
+			vcl_hrd_parameters_present_flag,
+			sub_pic_hrd_params,
+			bit_rate_scale,
+			cpb_size_scale,
+			cpb_size_du_scale,
+			initial_cpb_removal_delay_length_minus1,
+			au_cpb_removal_delay_length_minus1,
+			dpb_output_delay_length_minus1,
+			sub_layers,
+		})
+	}
+
+	pub fn mux(&self, bit_writer: &mut BitWriter, common_inf_present_flag: bool) -> io::Result<()> {
+		if common_inf_present_flag {
+			bit_writer.write_bit(self.nal_hrd_parameters_present_flag)?;
+			bit_writer.write_bit(self.vcl_hrd_parameters_present_flag)?;
+
+			if self.nal_hrd_parameters_present_flag || self.vcl_hrd_parameters_present_flag {
+				bit_writer.write_bit(self.sub_pic_hrd_params.is_some())?;
+				if let Some(sub_pic_hrd_params) = &self.sub_pic_hrd_params {
+					bit_writer.write_bits(sub_pic_hrd_params.tick_divisor_minus2 as u64, 8)?;
+					bit_writer.write_bits(sub_pic_hrd_params.du_cpb_removal_delay_increment_length_minus1 as u64, 5)?;
+					bit_writer.write_bit(sub_pic_hrd_params.sub_pic_cpb_params_in_pic_timing_sei_flag)?;
+					bit_writer.write_bits(sub_pic_hrd_params.dpb_output_delay_du_length_minus1 as u64, 5)?;
+				}
+
+				bit_writer.write_bits(self.bit_rate_scale as u64, 4)?;
+				bit_writer.write_bits(self.cpb_size_scale as u64, 4)?;
+
+				if self.sub_pic_hrd_params.is_some() {
+					bit_writer.write_bits(self.cpb_size_du_scale as u64, 4)?;
+				}
+
+				bit_writer.write_bits(self.initial_cpb_removal_delay_length_minus1 as u64, 5)?;
+				bit_writer.write_bits(self.au_cpb_removal_delay_length_minus1 as u64, 5)?;
+				bit_writer.write_bits(self.dpb_output_delay_length_minus1 as u64, 5)?;
+			}
+		}
+
+		for sub_layer in &self.sub_layers {
+			bit_writer.write_bit(sub_layer.fixed_pic_rate_general_flag)?;
+			if !sub_layer.fixed_pic_rate_general_flag {
+				bit_writer.write_bit(sub_layer.fixed_pic_rate_within_cvs_flag)?;
+			}
+
+			if sub_layer.fixed_pic_rate_general_flag || sub_layer.fixed_pic_rate_within_cvs_flag {
+				write_exp_golomb(bit_writer, sub_layer.elemental_duration_in_tc_minus1.unwrap_or(0))?;
+			} else {
+				bit_writer.write_bit(sub_layer.low_delay_hrd_flag)?;
+			}
+
+			if !sub_layer.low_delay_hrd_flag {
+				write_exp_golomb(bit_writer, sub_layer.cpb_cnt_minus1)?;
+			}
+
+			if self.nal_hrd_parameters_present_flag {
+				CpbSpecification::mux_list(bit_writer, &sub_layer.nal_cpb_specifications, sub_layer.cpb_cnt_minus1)?;
+			}
+
+			if self.vcl_hrd_parameters_present_flag {
+				CpbSpecification::mux_list(bit_writer, &sub_layer.vcl_cpb_specifications, sub_layer.cpb_cnt_minus1)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl CpbSpecification {
+	/// Parses the sub_layer_hrd_parameters()
+	fn parse_list(
+		bit_reader: &mut BitReader,
+		cpb_cnt_minus1: u64,
+		sub_pic_hrd_params_present_flag: bool,
+	) -> io::Result<Vec<Self>> {
+		let mut cpb_specifications = Vec::with_capacity(cpb_cnt_minus1 as usize + 1);
+		for _ in 0..=cpb_cnt_minus1 {
+			let bit_rate_value_minus1 = read_exp_golomb(bit_reader)?;
+			let cpb_size_value_minus1 = read_exp_golomb(bit_reader)?;
+
+			let mut cpb_size_du_value_minus1 = None;
+			let mut bit_rate_du_value_minus1 = None;
+			if sub_pic_hrd_params_present_flag {
+				cpb_size_du_value_minus1 = Some(read_exp_golomb(bit_reader)?);
+				bit_rate_du_value_minus1 = Some(read_exp_golomb(bit_reader)?);
+			}
+
+			cpb_specifications.push(CpbSpecification {
+				bit_rate_value_minus1,
+				cpb_size_value_minus1,
+				cpb_size_du_value_minus1,
+				bit_rate_du_value_minus1,
+				cbr_flag: bit_reader.read_bit()?,
+			});
+		}
+
+		Ok(cpb_specifications)
+	}
+
+	/// Writes the sub_layer_hrd_parameters()
+	fn mux_list(bit_writer: &mut BitWriter, cpb_specifications: &[Self], cpb_cnt_minus1: u64) -> io::Result<()> {
+		if cpb_specifications.len() as u64 != cpb_cnt_minus1 + 1 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"the number of CPB specifications does not match cpb_cnt_minus1",
+			));
+		}
+
+		for cpb_specification in cpb_specifications {
+			write_exp_golomb(bit_writer, cpb_specification.bit_rate_value_minus1)?;
+			write_exp_golomb(bit_writer, cpb_specification.cpb_size_value_minus1)?;
+			if let Some(cpb_size_du_value_minus1) = cpb_specification.cpb_size_du_value_minus1 {
+				write_exp_golomb(bit_writer, cpb_size_du_value_minus1)?;
+			}
+			if let Some(bit_rate_du_value_minus1) = cpb_specification.bit_rate_du_value_minus1 {
+				write_exp_golomb(bit_writer, bit_rate_du_value_minus1)?;
+			}
+			bit_writer.write_bit(cpb_specification.cbr_flag)?;
+		}
+
+		Ok(())
+	}
+}
@@ -21,10 +21,12 @@ use crate::boxes::types::avc1::Avc1;
 use crate::boxes::types::avcc::AvcC;
 use crate::boxes::types::btrt::Btrt;
 use crate::boxes::types::clap::Clap;
+use crate::boxes::types::clli::Clli;
 use crate::boxes::types::co64::Co64;
 use crate::boxes::types::colr::Colr;
 use crate::boxes::types::ctts::Ctts;
 use crate::boxes::types::dinf::Dinf;
+use crate::boxes::types::dops::Dops;
 use crate::boxes::types::dref::Dref;
 use crate::boxes::types::edts::Edts;
 use crate::boxes::types::elst::Elst;
@@ -33,8 +35,10 @@ use crate::boxes::types::ftyp::Ftyp;
 use crate::boxes::types::hdlr::Hdlr;
 use crate::boxes::types::hev1::Hev1;
 use crate::boxes::types::hmhd::Hmhd;
+use crate::boxes::types::hvc1::Hvc1;
 use crate::boxes::types::hvcc::HvcC;
 use crate::boxes::types::mdat::Mdat;
+use crate::boxes::types::mdcv::Mdcv;
 use crate::boxes::types::mdhd::Mdhd;
 use crate::boxes::types::mdia::Mdia;
 use crate::boxes::types::mehd::Mehd;
@@ -84,5 +88,6 @@ impl_box!(
     Url, Avc1, Clap, Pasp, AvcC, Btrt,
     Mp4a, Esds, Moof, Mfhd, Traf, Tfhd,
     Tfdt, Trun, Mdat, Av01, Av1C, Colr,
-    Hev1, HvcC, Opus,
+    Hev1, HvcC, Opus, Dops, Mdcv, Clli,
+    Hvc1,
 );
@@ -56,6 +56,7 @@ impl Av01 {
 			monochrome: seq_obu.color_config.mono_chrome,
 			sub_sampling_x: seq_obu.color_config.subsampling_x,
 			sub_sampling_y: seq_obu.color_config.subsampling_y,
+			chroma_sample_position: seq_obu.color_config.chroma_sample_position,
 			color_primaries: seq_obu.color_config.color_primaries,
 			transfer_characteristics: seq_obu.color_config.transfer_characteristics,
 			matrix_coefficients: seq_obu.color_config.matrix_coefficients,
@@ -94,6 +95,12 @@ impl BoxType for Av01 {
 				DynBox::Colr(b) => {
 					visual_sample_entry.extension.colr = Some(b);
 				}
+				DynBox::Mdcv(b) => {
+					visual_sample_entry.extension.mdcv = Some(b);
+				}
+				DynBox::Clli(b) => {
+					visual_sample_entry.extension.clli = Some(b);
+				}
 				_ => {
 					unknown.push(dyn_box);
 				}
@@ -71,6 +71,12 @@ impl BoxType for Avc1 {
 				DynBox::Colr(b) => {
 					visual_sample_entry.extension.colr = Some(b);
 				}
+				DynBox::Mdcv(b) => {
+					visual_sample_entry.extension.mdcv = Some(b);
+				}
+				DynBox::Clli(b) => {
+					visual_sample_entry.extension.clli = Some(b);
+				}
 				_ => {
 					unknown.push(dyn_box);
 				}
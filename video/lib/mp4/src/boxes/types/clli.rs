@@ -0,0 +1,57 @@
+use std::io;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use bytes::Bytes;
+
+use crate::boxes::header::BoxHeader;
+use crate::boxes::traits::BoxType;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Content Light Level Box
+/// ISO/IEC 14496-12:2022(E) - 12.1.6
+///
+/// Both values are in candelas per square metre, zero means unknown.
+pub struct Clli {
+	pub header: BoxHeader,
+	pub max_content_light_level: u16,
+	pub max_pic_average_light_level: u16,
+}
+
+impl Clli {
+	pub fn new(max_content_light_level: u16, max_pic_average_light_level: u16) -> Self {
+		Self {
+			header: BoxHeader::new(Self::NAME),
+			max_content_light_level,
+			max_pic_average_light_level,
+		}
+	}
+}
+
+impl BoxType for Clli {
+	const NAME: [u8; 4] = *b"clli";
+
+	fn demux(header: BoxHeader, data: Bytes) -> io::Result<Self> {
+		let mut reader = io::Cursor::new(data);
+
+		let max_content_light_level = reader.read_u16::<BigEndian>()?;
+		let max_pic_average_light_level = reader.read_u16::<BigEndian>()?;
+
+		Ok(Self {
+			header,
+			max_content_light_level,
+			max_pic_average_light_level,
+		})
+	}
+
+	fn primitive_size(&self) -> u64 {
+		2 // max_content_light_level
+        + 2 // max_pic_average_light_level
+	}
+
+	fn primitive_mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		writer.write_u16::<BigEndian>(self.max_content_light_level)?;
+		writer.write_u16::<BigEndian>(self.max_pic_average_light_level)?;
+
+		Ok(())
+	}
+}
@@ -11,7 +11,16 @@ use crate::boxes::traits::BoxType;
 /// ISO/IEC 14496-12:2022(E) - 8.7.5
 pub struct Co64 {
 	pub header: FullBoxHeader,
-	pub chunk_offset: Vec<u32>,
+	pub chunk_offset: Vec<u64>,
+}
+
+impl Co64 {
+	pub fn new(chunk_offset: Vec<u64>) -> Self {
+		Self {
+			header: FullBoxHeader::new(Self::NAME, 0, 0),
+			chunk_offset,
+		}
+	}
 }
 
 impl BoxType for Co64 {
@@ -25,7 +34,7 @@ impl BoxType for Co64 {
 		let entry_count = reader.read_u32::<BigEndian>()?;
 		let mut chunk_offset = Vec::with_capacity(entry_count as usize);
 		for _ in 0..entry_count {
-			let offset = reader.read_u32::<BigEndian>()?;
+			let offset = reader.read_u64::<BigEndian>()?;
 			chunk_offset.push(offset);
 		}
 
@@ -35,7 +44,7 @@ impl BoxType for Co64 {
 	fn primitive_size(&self) -> u64 {
 		self.header.size()
         + 4 // entry_count
-        + (self.chunk_offset.len() as u64 * 4) // chunk_offset
+        + (self.chunk_offset.len() as u64 * 8) // chunk_offset
 	}
 
 	fn primitive_mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
@@ -43,7 +52,7 @@ impl BoxType for Co64 {
 
 		writer.write_u32::<BigEndian>(self.chunk_offset.len() as u32)?;
 		for offset in &self.chunk_offset {
-			writer.write_u32::<BigEndian>(*offset)?;
+			writer.write_u64::<BigEndian>(*offset)?;
 		}
 
 		Ok(())
@@ -21,6 +21,18 @@ pub struct CttsEntry {
 	pub sample_offset: i64,
 }
 
+impl Ctts {
+	pub fn new(entries: Vec<CttsEntry>) -> Self {
+		// Version 1 is needed for negative offsets.
+		let version = if entries.iter().any(|e| e.sample_offset < 0) { 1 } else { 0 };
+
+		Self {
+			header: FullBoxHeader::new(Self::NAME, version, 0),
+			entries,
+		}
+	}
+}
+
 impl BoxType for Ctts {
 	const NAME: [u8; 4] = *b"ctts";
 
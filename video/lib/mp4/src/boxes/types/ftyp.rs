@@ -71,24 +71,30 @@ impl BoxType for Ftyp {
 #[derive(Debug, Clone, PartialEq)]
 /// FourCC (Four Character Code)
 pub enum FourCC {
+	Isom,
+	Iso2,
 	Iso5,
 	Iso6,
 	Mp41,
 	Avc1,
 	Av01,
 	Hev1,
+	Hvc1,
 	Unknown([u8; 4]),
 }
 
 impl FourCC {
 	pub fn to_bytes(&self) -> [u8; 4] {
 		match self {
+			Self::Isom => *b"isom",
+			Self::Iso2 => *b"iso2",
 			Self::Iso5 => *b"iso5",
 			Self::Iso6 => *b"iso6",
 			Self::Mp41 => *b"mp41",
 			Self::Avc1 => *b"avc1",
 			Self::Av01 => *b"av01",
 			Self::Hev1 => *b"hev1",
+			Self::Hvc1 => *b"hvc1",
 			Self::Unknown(bytes) => *bytes,
 		}
 	}
@@ -97,12 +103,15 @@ impl FourCC {
 impl From<[u8; 4]> for FourCC {
 	fn from(bytes: [u8; 4]) -> Self {
 		match &bytes {
+			b"isom" => Self::Isom,
+			b"iso2" => Self::Iso2,
 			b"iso5" => Self::Iso5,
 			b"iso6" => Self::Iso6,
 			b"mp41" => Self::Mp41,
 			b"avc1" => Self::Avc1,
 			b"av01" => Self::Av01,
 			b"hev1" => Self::Hev1,
+			b"hvc1" => Self::Hvc1,
 			_ => Self::Unknown(bytes),
 		}
 	}
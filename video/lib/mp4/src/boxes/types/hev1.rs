@@ -8,7 +8,7 @@ use super::stsd::{SampleEntry, VisualSampleEntry};
 use crate::boxes::header::BoxHeader;
 use crate::boxes::traits::BoxType;
 use crate::boxes::DynBox;
-use crate::codec::VideoCodec;
+use crate::codec::{HevcSampleEntry, VideoCodec};
 
 #[derive(Debug, Clone, PartialEq)]
 /// HEVC (H.265) Codec Box
@@ -34,6 +34,7 @@ impl Hev1 {
 
 	pub fn codec(&self) -> io::Result<VideoCodec> {
 		Ok(VideoCodec::Hevc {
+			sample_entry: HevcSampleEntry::Hev1,
 			constraint_indicator: self.hvcc.hevc_config.general_constraint_indicator_flags,
 			level: self.hvcc.hevc_config.general_level_idc,
 			profile: self.hvcc.hevc_config.general_profile_idc,
@@ -74,6 +75,12 @@ impl BoxType for Hev1 {
 				DynBox::Colr(b) => {
 					visual_sample_entry.extension.colr = Some(b);
 				}
+				DynBox::Mdcv(b) => {
+					visual_sample_entry.extension.mdcv = Some(b);
+				}
+				DynBox::Clli(b) => {
+					visual_sample_entry.extension.clli = Some(b);
+				}
 				_ => {
 					unknown.push(dyn_box);
 				}
@@ -0,0 +1,83 @@
+use std::io;
+
+use bytes::Bytes;
+
+use super::btrt::Btrt;
+use super::hev1::Hev1;
+use super::hvcc::HvcC;
+use super::stsd::{SampleEntry, VisualSampleEntry};
+use crate::boxes::header::BoxHeader;
+use crate::boxes::traits::BoxType;
+use crate::boxes::DynBox;
+use crate::codec::{HevcSampleEntry, VideoCodec};
+
+#[derive(Debug, Clone, PartialEq)]
+/// HEVC (H.265) Codec Box, the parameter sets are only in the sample entry.
+/// ISO/IEC 14496-15:2022 - 8.4
+pub struct Hvc1 {
+	pub header: BoxHeader,
+	pub visual_sample_entry: SampleEntry<VisualSampleEntry>,
+	pub hvcc: HvcC,
+	pub btrt: Option<Btrt>,
+	pub unknown: Vec<DynBox>,
+}
+
+impl Hvc1 {
+	pub fn new(visual_sample_entry: SampleEntry<VisualSampleEntry>, hvcc: HvcC, btrt: Option<Btrt>) -> Self {
+		Self {
+			header: BoxHeader::new(Self::NAME),
+			visual_sample_entry,
+			hvcc,
+			btrt,
+			unknown: Vec::new(),
+		}
+	}
+
+	pub fn codec(&self) -> io::Result<VideoCodec> {
+		Ok(VideoCodec::Hevc {
+			sample_entry: HevcSampleEntry::Hvc1,
+			constraint_indicator: self.hvcc.hevc_config.general_constraint_indicator_flags,
+			level: self.hvcc.hevc_config.general_level_idc,
+			profile: self.hvcc.hevc_config.general_profile_idc,
+			profile_compatibility: self.hvcc.hevc_config.general_profile_compatibility_flags,
+			tier: self.hvcc.hevc_config.general_tier_flag,
+			general_profile_space: self.hvcc.hevc_config.general_profile_space,
+		})
+	}
+}
+
+impl BoxType for Hvc1 {
+	const NAME: [u8; 4] = *b"hvc1";
+
+	fn demux(header: BoxHeader, data: Bytes) -> io::Result<Self> {
+		// The box has the same layout as the hev1 box, only the type differs.
+		let hev1 = Hev1::demux(header.clone(), data)?;
+
+		Ok(Self {
+			header,
+			visual_sample_entry: hev1.visual_sample_entry,
+			hvcc: hev1.hvcc,
+			btrt: hev1.btrt,
+			unknown: hev1.unknown,
+		})
+	}
+
+	fn primitive_size(&self) -> u64 {
+		self.visual_sample_entry.size()
+			+ self.hvcc.size()
+			+ self.btrt.as_ref().map(|b| b.size()).unwrap_or(0)
+			+ self.unknown.iter().map(|b| b.size()).sum::<u64>()
+	}
+
+	fn primitive_mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		self.visual_sample_entry.mux(writer)?;
+		self.hvcc.mux(writer)?;
+		if let Some(btrt) = &self.btrt {
+			btrt.mux(writer)?;
+		}
+		for unknown in &self.unknown {
+			unknown.mux(writer)?;
+		}
+		Ok(())
+	}
+}
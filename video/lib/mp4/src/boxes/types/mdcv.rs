@@ -0,0 +1,89 @@
+use std::io;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use bytes::Bytes;
+
+use crate::boxes::header::BoxHeader;
+use crate::boxes::traits::BoxType;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Mastering Display Colour Volume Box
+/// ISO/IEC 14496-12:2022(E) - 12.1.7
+///
+/// The fields have the same semantics as the mastering display colour volume
+/// SEI message of HEVC.
+pub struct Mdcv {
+	pub header: BoxHeader,
+	/// The (x, y) coordinates of the display primaries in the order of the
+	/// SEI message, in units of 0.00002.
+	pub display_primaries: [(u16, u16); 3],
+	/// The (x, y) coordinates of the white point, in units of 0.00002.
+	pub white_point: (u16, u16),
+	/// In units of 0.0001 candelas per square metre.
+	pub max_display_mastering_luminance: u32,
+	/// In units of 0.0001 candelas per square metre.
+	pub min_display_mastering_luminance: u32,
+}
+
+impl Mdcv {
+	pub fn new(
+		display_primaries: [(u16, u16); 3],
+		white_point: (u16, u16),
+		max_display_mastering_luminance: u32,
+		min_display_mastering_luminance: u32,
+	) -> Self {
+		Self {
+			header: BoxHeader::new(Self::NAME),
+			display_primaries,
+			white_point,
+			max_display_mastering_luminance,
+			min_display_mastering_luminance,
+		}
+	}
+}
+
+impl BoxType for Mdcv {
+	const NAME: [u8; 4] = *b"mdcv";
+
+	fn demux(header: BoxHeader, data: Bytes) -> io::Result<Self> {
+		let mut reader = io::Cursor::new(data);
+
+		let mut display_primaries = [(0, 0); 3];
+		for primary in display_primaries.iter_mut() {
+			*primary = (reader.read_u16::<BigEndian>()?, reader.read_u16::<BigEndian>()?);
+		}
+
+		let white_point = (reader.read_u16::<BigEndian>()?, reader.read_u16::<BigEndian>()?);
+		let max_display_mastering_luminance = reader.read_u32::<BigEndian>()?;
+		let min_display_mastering_luminance = reader.read_u32::<BigEndian>()?;
+
+		Ok(Self {
+			header,
+			display_primaries,
+			white_point,
+			max_display_mastering_luminance,
+			min_display_mastering_luminance,
+		})
+	}
+
+	fn primitive_size(&self) -> u64 {
+		3 * (2 + 2) // display_primaries
+        + 2 + 2 // white_point
+        + 4 // max_display_mastering_luminance
+        + 4 // min_display_mastering_luminance
+	}
+
+	fn primitive_mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		for (x, y) in self.display_primaries {
+			writer.write_u16::<BigEndian>(x)?;
+			writer.write_u16::<BigEndian>(y)?;
+		}
+
+		writer.write_u16::<BigEndian>(self.white_point.0)?;
+		writer.write_u16::<BigEndian>(self.white_point.1)?;
+		writer.write_u32::<BigEndian>(self.max_display_mastering_luminance)?;
+		writer.write_u32::<BigEndian>(self.min_display_mastering_luminance)?;
+
+		Ok(())
+	}
+}
@@ -4,10 +4,12 @@ pub mod avc1;
 pub mod avcc;
 pub mod btrt;
 pub mod clap;
+pub mod clli;
 pub mod co64;
 pub mod colr;
 pub mod ctts;
 pub mod dinf;
+pub mod dops;
 pub mod dref;
 pub mod edts;
 pub mod elst;
@@ -16,8 +18,10 @@ pub mod ftyp;
 pub mod hdlr;
 pub mod hev1;
 pub mod hmhd;
+pub mod hvc1;
 pub mod hvcc;
 pub mod mdat;
+pub mod mdcv;
 pub mod mdhd;
 pub mod mdia;
 pub mod mehd;
@@ -10,6 +10,11 @@ use crate::boxes::traits::BoxType;
 use crate::boxes::DynBox;
 use crate::codec::AudioCodec;
 
+/// The object type indication of MPEG-1 audio in the decoder config, which
+/// is how MP3 is stored.
+/// ISO/IEC 14496-1:2010(E) - 7.2.6.6.2
+pub const MP3_OBJECT_TYPE_INDICATION: u8 = 0x6B;
+
 #[derive(Debug, Clone, PartialEq)]
 /// AAC Audio Sample Entry
 /// ISO/IEC 14496-14:2020(E) - 6.7
@@ -32,19 +37,21 @@ impl Mp4a {
 		}
 	}
 
-	#[allow(clippy::useless_asref)]
 	pub fn codec(&self) -> io::Result<AudioCodec> {
-		let info = self
-			.esds
-			.es_descriptor
-			.decoder_config
-			.as_ref()
+		let decoder_config = self.esds.es_descriptor.decoder_config.as_ref();
+
+		// MP3 has no decoder specific info.
+		if decoder_config.is_some_and(|c| c.object_type_indication == MP3_OBJECT_TYPE_INDICATION) {
+			return Ok(AudioCodec::Mp3);
+		}
+
+		let info = decoder_config
 			.and_then(|c| c.decoder_specific_info.as_ref().map(|c| c.data.clone()))
 			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing decoder specific info"))?;
 		let aac_config = aac::AudioSpecificConfig::parse(info)?;
 
 		Ok(AudioCodec::Aac {
-			object_type: aac_config.audio_object_type,
+			object_type: aac_config.codec_object_type(),
 		})
 	}
 }
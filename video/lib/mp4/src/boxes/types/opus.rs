@@ -3,6 +3,7 @@ use std::io;
 use bytes::{Buf, Bytes};
 
 use super::btrt::Btrt;
+use super::dops::Dops;
 use super::stsd::{AudioSampleEntry, SampleEntry};
 use crate::boxes::header::BoxHeader;
 use crate::boxes::traits::BoxType;
@@ -15,15 +16,17 @@ use crate::codec::AudioCodec;
 pub struct Opus {
 	pub header: BoxHeader,
 	pub audio_sample_entry: SampleEntry<AudioSampleEntry>,
+	pub dops: Dops,
 	pub btrt: Option<Btrt>,
 	pub unknown: Vec<DynBox>,
 }
 
 impl Opus {
-	pub fn new(audio_sample_entry: SampleEntry<AudioSampleEntry>, btrt: Option<Btrt>) -> Self {
+	pub fn new(audio_sample_entry: SampleEntry<AudioSampleEntry>, dops: Dops, btrt: Option<Btrt>) -> Self {
 		Self {
 			header: BoxHeader::new(Self::NAME),
 			audio_sample_entry,
+			dops,
 			btrt,
 			unknown: Vec::new(),
 		}
@@ -42,6 +45,7 @@ impl BoxType for Opus {
 
 		let audio_sample_entry = SampleEntry::<AudioSampleEntry>::demux(&mut reader)?;
 		let mut btrt = None;
+		let mut dops = None;
 		let mut unknown = Vec::new();
 
 		while reader.has_remaining() {
@@ -50,15 +54,21 @@ impl BoxType for Opus {
 				DynBox::Btrt(btrt_box) => {
 					btrt = Some(btrt_box);
 				}
+				DynBox::Dops(dops_box) => {
+					dops = Some(dops_box);
+				}
 				_ => {
 					unknown.push(dyn_box);
 				}
 			}
 		}
 
+		let dops = dops.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing dOps box"))?;
+
 		Ok(Self {
 			header,
 			audio_sample_entry,
+			dops,
 			btrt,
 			unknown,
 		})
@@ -66,12 +76,14 @@ impl BoxType for Opus {
 
 	fn primitive_size(&self) -> u64 {
 		self.audio_sample_entry.size()
+			+ self.dops.size()
 			+ self.btrt.as_ref().map(|b| b.size()).unwrap_or(0)
 			+ self.unknown.iter().map(|b| b.size()).sum::<u64>()
 	}
 
 	fn primitive_mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
 		self.audio_sample_entry.mux(writer)?;
+		self.dops.mux(writer)?;
 		if let Some(btrt) = &self.btrt {
 			btrt.mux(writer)?;
 		}
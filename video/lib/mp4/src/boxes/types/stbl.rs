@@ -32,7 +32,7 @@ pub struct Stbl {
 	pub stsc: Stsc,
 	pub stsz: Option<Stsz>,
 	pub stz2: Option<Stz2>,
-	pub stco: Stco,
+	pub stco: Option<Stco>,
 	pub co64: Option<Co64>,
 	pub stss: Option<Stss>,
 	pub stsh: Option<Stsh>,
@@ -54,7 +54,7 @@ impl Stbl {
 			stsc,
 			stsz,
 			stz2: None,
-			stco,
+			stco: Some(stco),
 			co64: None,
 			stss: None,
 			stsh: None,
@@ -148,7 +148,13 @@ impl BoxType for Stbl {
 		let stsd = stsd.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "stsd box not found in stbl box"))?;
 		let stts = stts.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "stts box not found in stbl box"))?;
 		let stsc = stsc.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "stsc box not found in stbl box"))?;
-		let stco = stco.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "stco box not found in stbl box"))?;
+
+		if stco.is_none() && co64.is_none() {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"stco or co64 box not found in stbl box",
+			));
+		}
 
 		Ok(Self {
 			header,
@@ -178,7 +184,7 @@ impl BoxType for Stbl {
 		size += self.stsc.size();
 		size += self.stsz.as_ref().map(|b| b.size()).unwrap_or(0);
 		size += self.stz2.as_ref().map(|b| b.size()).unwrap_or(0);
-		size += self.stco.size();
+		size += self.stco.as_ref().map(|b| b.size()).unwrap_or(0);
 		size += self.co64.as_ref().map(|b| b.size()).unwrap_or(0);
 		size += self.stss.as_ref().map(|b| b.size()).unwrap_or(0);
 		size += self.stsh.as_ref().map(|b| b.size()).unwrap_or(0);
@@ -204,7 +210,9 @@ impl BoxType for Stbl {
 		if let Some(stz2) = &self.stz2 {
 			stz2.mux(writer)?;
 		}
-		self.stco.mux(writer)?;
+		if let Some(stco) = &self.stco {
+			stco.mux(writer)?;
+		}
 		if let Some(co64) = &self.co64 {
 			co64.mux(writer)?;
 		}
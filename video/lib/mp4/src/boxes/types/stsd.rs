@@ -7,7 +7,9 @@ use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use bytes::Bytes;
 
 use super::clap::Clap;
+use super::clli::Clli;
 use super::colr::Colr;
+use super::mdcv::Mdcv;
 use super::pasp::Pasp;
 use crate::boxes::header::{BoxHeader, FullBoxHeader};
 use crate::boxes::traits::BoxType;
@@ -34,6 +36,7 @@ impl Stsd {
 			DynBox::Av01(av01) => av01.codec().ok().map(|c| c.to_string()),
 			DynBox::Avc1(avc1) => avc1.codec().ok().map(|c| c.to_string()),
 			DynBox::Hev1(hev1) => hev1.codec().ok().map(|c| c.to_string()),
+			DynBox::Hvc1(hvc1) => hvc1.codec().ok().map(|c| c.to_string()),
 			DynBox::Opus(opus) => opus.codec().ok().map(|c| c.to_string()),
 			DynBox::Mp4a(mp4a) => mp4a.codec().ok().map(|c| c.to_string()),
 			_ => None,
@@ -47,7 +50,7 @@ impl Stsd {
 	pub fn is_video(&self) -> bool {
 		self.entries
 			.iter()
-			.any(|e| matches!(e, DynBox::Av01(_) | DynBox::Avc1(_) | DynBox::Hev1(_)))
+			.any(|e| matches!(e, DynBox::Av01(_) | DynBox::Avc1(_) | DynBox::Hev1(_) | DynBox::Hvc1(_)))
 	}
 }
 
@@ -232,6 +235,8 @@ pub struct VisualSampleEntry {
 	pub clap: Option<Clap>,
 	pub colr: Option<Colr>,
 	pub pasp: Option<Pasp>,
+	pub mdcv: Option<Mdcv>,
+	pub clli: Option<Clli>,
 }
 
 impl VisualSampleEntry {
@@ -252,6 +257,8 @@ impl VisualSampleEntry {
 			clap: None,
 			colr,
 			pasp: Some(Pasp::new()),
+			mdcv: None,
+			clli: None,
 		}
 	}
 }
@@ -292,6 +299,8 @@ impl SampleEntryExtension for VisualSampleEntry {
 			colr: None,
 			clap: None,
 			pasp: None,
+			mdcv: None,
+			clli: None,
 		})
 	}
 
@@ -313,6 +322,8 @@ impl SampleEntryExtension for VisualSampleEntry {
         + self.clap.as_ref().map_or(0, |clap| clap.size())
         + self.pasp.as_ref().map_or(0, |pasp| pasp.size())
         + self.colr.as_ref().map_or(0, |colr| colr.size())
+        + self.mdcv.as_ref().map_or(0, |mdcv| mdcv.size())
+        + self.clli.as_ref().map_or(0, |clli| clli.size())
 	}
 
 	fn mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
@@ -339,6 +350,12 @@ impl SampleEntryExtension for VisualSampleEntry {
 		if let Some(colr) = &self.colr {
 			colr.mux(writer).unwrap();
 		}
+		if let Some(mdcv) = &self.mdcv {
+			mdcv.mux(writer).unwrap();
+		}
+		if let Some(clli) = &self.clli {
+			clli.mux(writer).unwrap();
+		}
 		Ok(())
 	}
 
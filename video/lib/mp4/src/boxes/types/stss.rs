@@ -14,6 +14,15 @@ pub struct Stss {
 	pub entries: Vec<u32>,
 }
 
+impl Stss {
+	pub fn new(entries: Vec<u32>) -> Self {
+		Self {
+			header: FullBoxHeader::new(Self::NAME, 0, 0),
+			entries,
+		}
+	}
+}
+
 impl BoxType for Stss {
 	const NAME: [u8; 4] = *b"stss";
 
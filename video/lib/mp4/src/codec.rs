@@ -7,9 +7,11 @@ use aac::AudioObjectType;
 pub enum VideoCodec {
 	/// https://developer.mozilla.org/en-US/docs/Web/Media/Formats/codecs_parameter
 	Avc { profile: u8, constraint_set: u8, level: u8 },
-	/// There is barely any documentation on this.
-	/// http://hevcvideo.xp3.biz/html5_video.html
+	/// ISO/IEC 14496-15:2022 - Annex E.3
+	/// The profile compatibility and constraint indicator flags are stored the
+	/// same way the HEVCDecoderConfigurationRecord reads them.
 	Hevc {
+		sample_entry: HevcSampleEntry,
 		general_profile_space: u8,
 		profile_compatibility: u32,
 		profile: u8,
@@ -17,7 +19,7 @@ pub enum VideoCodec {
 		tier: bool,
 		constraint_indicator: u64,
 	},
-	/// https://developer.mozilla.org/en-US/docs/Web/Media/Formats/codecs_parameter#av1
+	/// https://aomediacodec.github.io/av1-isobmff/#codecsparam
 	Av1 {
 		profile: u8,
 		level: u8,
@@ -26,6 +28,7 @@ pub enum VideoCodec {
 		monochrome: bool,
 		sub_sampling_x: bool,
 		sub_sampling_y: bool,
+		chroma_sample_position: u8,
 		color_primaries: u8,
 		transfer_characteristics: u8,
 		matrix_coefficients: u8,
@@ -33,6 +36,27 @@ pub enum VideoCodec {
 	},
 }
 
+/// The sample entry a HEVC track uses, which is also the first element of its
+/// codec string.
+/// ISO/IEC 14496-15:2022 - 8.4.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HevcSampleEntry {
+	/// The parameter sets may also be sent in the samples.
+	Hev1,
+	/// The parameter sets are only in the sample entry, this is what Apple
+	/// devices require.
+	Hvc1,
+}
+
+impl HevcSampleEntry {
+	pub fn fourcc(&self) -> &'static str {
+		match self {
+			HevcSampleEntry::Hev1 => "hev1",
+			HevcSampleEntry::Hvc1 => "hvc1",
+		}
+	}
+}
+
 impl fmt::Display for VideoCodec {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
@@ -42,27 +66,40 @@ impl fmt::Display for VideoCodec {
 				level,
 			} => write!(f, "avc1.{:02x}{:02x}{:02x}", profile, constraint_set, level),
 			VideoCodec::Hevc {
+				sample_entry,
 				general_profile_space,
 				profile,
 				level,
 				tier,
 				profile_compatibility,
 				constraint_indicator,
-			} => write!(
-				f,
-				"hev1.{}{:x}.{:x}.{}{:x}.{:x}",
-				match general_profile_space {
-					1 => "A",
-					2 => "B",
-					3 => "C",
-					_ => "",
-				},
-				profile, // 1 or 2 chars (hex)
-				profile_compatibility,
-				if *tier { 'H' } else { 'L' },
-				level, // 1 or 2 chars (hex)
-				constraint_indicator,
-			),
+			} => {
+				write!(
+					f,
+					"{}.{}{}.{:X}.{}{}",
+					sample_entry.fourcc(),
+					match general_profile_space {
+						1 => "A",
+						2 => "B",
+						3 => "C",
+						_ => "",
+					},
+					profile,
+					// The flags in reverse bit order, so flag 0 is the least significant bit.
+					profile_compatibility.swap_bytes().reverse_bits(),
+					if *tier { 'H' } else { 'L' },
+					level,
+				)?;
+
+				// Every byte of the constraint flags, trailing zero bytes are omitted.
+				let constraint_bytes = constraint_indicator.to_le_bytes();
+				let len = constraint_bytes[..6].iter().rposition(|b| *b != 0).map_or(0, |idx| idx + 1);
+				for byte in &constraint_bytes[..len] {
+					write!(f, ".{:X}", byte)?;
+				}
+
+				Ok(())
+			}
 			VideoCodec::Av1 {
 				profile,
 				level,
@@ -71,13 +108,14 @@ impl fmt::Display for VideoCodec {
 				monochrome,
 				sub_sampling_x,
 				sub_sampling_y,
+				chroma_sample_position,
 				color_primaries,
 				transfer_characteristics,
 				matrix_coefficients,
 				full_range_flag,
 			} => write!(
 				f,
-				"av01.{}.{}{}.{:02}.{}.{}{}{}.{:02}.{:02}.{:02}.{}",
+				"av01.{}.{:02}{}.{:02}.{}.{}{}{}.{:02}.{:02}.{:02}.{}",
 				profile,
 				level,
 				if *tier { 'H' } else { 'M' },
@@ -85,7 +123,7 @@ impl fmt::Display for VideoCodec {
 				if *monochrome { 1 } else { 0 },
 				if *sub_sampling_x { 1 } else { 0 },
 				if *sub_sampling_y { 1 } else { 0 },
-				if *monochrome { 1 } else { 0 },
+				chroma_sample_position,
 				color_primaries,
 				transfer_characteristics,
 				matrix_coefficients,
@@ -95,6 +133,14 @@ impl fmt::Display for VideoCodec {
 	}
 }
 
+fn parse_flag(value: &str, name: &str) -> Result<bool, String> {
+	match value {
+		"1" => Ok(true),
+		"0" => Ok(false),
+		_ => Err(format!("invalid codec, invalid {}: {}", name, value)),
+	}
+}
+
 impl FromStr for VideoCodec {
 	type Err = String;
 
@@ -110,6 +156,10 @@ impl FromStr for VideoCodec {
 					return Err("invalid codec, missing profile".into());
 				}
 
+				if splits[1].len() != 6 || !splits[1].is_ascii() {
+					return Err(format!("invalid codec, invalid profile: {}", splits[1]));
+				}
+
 				let profile = u8::from_str_radix(&splits[1][..2], 16)
 					.map_err(|e| format!("invalid codec, invalid profile: {}, {}", splits[1], e))?;
 				let constraint_set = u8::from_str_radix(&splits[1][2..4], 16)
@@ -123,106 +173,131 @@ impl FromStr for VideoCodec {
 					level,
 				})
 			}
-			"hev1" => {
-				if splits.len() < 6 {
+			"hev1" | "hvc1" => {
+				if splits.len() < 4 {
 					return Err("invalid codec, missing profile".into());
 				}
 
-				let general_profile_space = match splits[1] {
-					"A" => 1,
-					"B" => 2,
-					"C" => 3,
-					_ => {
-						return Err(format!("invalid codec, invalid general profile space: {}", splits[1]));
-					}
+				if splits.len() > 10 {
+					return Err(format!("invalid codec, too many constraint flags: {}", s));
+				}
+
+				let sample_entry = if splits[0] == "hvc1" {
+					HevcSampleEntry::Hvc1
+				} else {
+					HevcSampleEntry::Hev1
 				};
 
-				let profile = u8::from_str_radix(splits[2], 16)
-					.map_err(|e| format!("invalid codec, invalid profile: {}, {}", splits[2], e))?;
+				let (general_profile_space, profile) = match splits[1].as_bytes().first() {
+					Some(b'A') => (1, &splits[1][1..]),
+					Some(b'B') => (2, &splits[1][1..]),
+					Some(b'C') => (3, &splits[1][1..]),
+					_ => (0, splits[1]),
+				};
 
-				let profile_compatibility = u32::from_str_radix(splits[3], 16)
-					.map_err(|e| format!("invalid codec, invalid profile compatibility: {}, {}", splits[3], e))?;
+				let profile = profile
+					.parse::<u8>()
+					.map_err(|e| format!("invalid codec, invalid profile: {}, {}", splits[1], e))?;
 
-				let tier = match splits[4] {
-					"H" => true,
-					"L" => false,
-					_ => return Err(format!("invalid codec, invalid tier: {}", splits[4])),
+				let profile_compatibility = u32::from_str_radix(splits[2], 16)
+					.map_err(|e| format!("invalid codec, invalid profile compatibility: {}, {}", splits[2], e))?
+					.reverse_bits()
+					.swap_bytes();
+
+				let tier = match splits[3].as_bytes().first() {
+					Some(b'H') => true,
+					Some(b'L') => false,
+					_ => return Err(format!("invalid codec, invalid tier: {}", splits[3])),
 				};
 
-				let level = u8::from_str_radix(splits[5], 16)
-					.map_err(|e| format!("invalid codec, invalid level: {}, {}", splits[5], e))?;
+				let level = splits[3][1..]
+					.parse::<u8>()
+					.map_err(|e| format!("invalid codec, invalid level: {}, {}", splits[3], e))?;
 
-				let constraint_indicator = u64::from_str_radix(splits[6], 16)
-					.map_err(|e| format!("invalid codec, invalid constraint indicator: {}, {}", splits[6], e))?;
+				let mut constraint_bytes = [0; 8];
+				for (idx, byte) in splits[4..].iter().enumerate() {
+					constraint_bytes[idx] = u8::from_str_radix(byte, 16)
+						.map_err(|e| format!("invalid codec, invalid constraint indicator: {}, {}", byte, e))?;
+				}
 
 				Ok(VideoCodec::Hevc {
+					sample_entry,
 					general_profile_space,
 					profile,
 					level,
 					tier,
 					profile_compatibility,
-					constraint_indicator,
+					constraint_indicator: u64::from_le_bytes(constraint_bytes),
 				})
 			}
 			"av01" => {
-				if splits.len() < 12 {
+				// The color fields are optional, but they must be given all together.
+				if splits.len() != 4 && splits.len() != 10 {
 					return Err("invalid codec, missing profile".into());
 				}
 
-				let profile = u8::from_str_radix(splits[1], 16)
+				let profile = splits[1]
+					.parse::<u8>()
 					.map_err(|e| format!("invalid codec, invalid profile: {}, {}", splits[1], e))?;
 
-				let level = u8::from_str_radix(splits[2], 16)
-					.map_err(|e| format!("invalid codec, invalid level: {}, {}", splits[2], e))?;
-
-				let tier = match splits[3] {
-					"H" => true,
-					"M" => false,
-					_ => return Err(format!("invalid codec, invalid tier: {}", splits[3])),
+				let tier = match splits[2].as_bytes().last() {
+					Some(b'H') => true,
+					Some(b'M') => false,
+					_ => return Err(format!("invalid codec, invalid tier: {}", splits[2])),
 				};
 
-				let depth = splits[4]
+				let level = splits[2][..splits[2].len() - 1]
 					.parse::<u8>()
-					.map_err(|e| format!("invalid codec, invalid depth: {}, {}", splits[4], e))?;
+					.map_err(|e| format!("invalid codec, invalid level: {}, {}", splits[2], e))?;
 
-				let monochrome = match splits[5] {
-					"1" => true,
-					"0" => false,
-					_ => return Err(format!("invalid codec, invalid monochrome: {}", splits[5])),
-				};
+				let depth = splits[3]
+					.parse::<u8>()
+					.map_err(|e| format!("invalid codec, invalid depth: {}, {}", splits[3], e))?;
 
-				let sub_sampling_x = match splits[6] {
-					"1" => true,
-					"0" => false,
-					_ => {
-						return Err(format!("invalid codec, invalid sub_sampling_x: {}", splits[6]));
-					}
+				// The defaults are 4:2:0 BT.709 in the limited range.
+				let color = if splits.len() == 10 {
+					&splits[4..]
+				} else {
+					&["0", "110", "01", "01", "01", "0"][..]
 				};
 
-				let sub_sampling_y = match splits[7] {
-					"1" => true,
-					"0" => false,
-					_ => {
-						return Err(format!("invalid codec, invalid sub_sampling_y: {}", splits[7]));
-					}
-				};
+				let (
+					monochrome,
+					chroma_subsampling,
+					color_primaries,
+					transfer_characteristics,
+					matrix_coefficients,
+					full_range,
+				) = (color[0], color[1], color[2], color[3], color[4], color[5]);
 
-				let color_primaries = splits[8]
-					.parse::<u8>()
-					.map_err(|e| format!("invalid codec, invalid color_primaries: {}, {}", splits[8], e))?;
+				let monochrome = parse_flag(monochrome, "monochrome")?;
+
+				if chroma_subsampling.len() != 3 || !chroma_subsampling.is_ascii() {
+					return Err(format!("invalid codec, invalid chroma subsampling: {}", chroma_subsampling));
+				}
 
-				let transfer_characteristics = splits[9]
+				let sub_sampling_x = parse_flag(&chroma_subsampling[0..1], "sub_sampling_x")?;
+				let sub_sampling_y = parse_flag(&chroma_subsampling[1..2], "sub_sampling_y")?;
+				let chroma_sample_position = chroma_subsampling[2..3]
 					.parse::<u8>()
-					.map_err(|e| format!("invalid codec, invalid transfer_characteristics: {}, {}", splits[9], e))?;
+					.map_err(|e| format!("invalid codec, invalid chroma_sample_position: {}, {}", chroma_subsampling, e))?;
 
-				let matrix_coefficients = splits[10]
+				let color_primaries = color_primaries
 					.parse::<u8>()
-					.map_err(|e| format!("invalid codec, invalid matrix_coefficients: {}, {}", splits[10], e))?;
+					.map_err(|e| format!("invalid codec, invalid color_primaries: {}, {}", color_primaries, e))?;
+
+				let transfer_characteristics = transfer_characteristics.parse::<u8>().map_err(|e| {
+					format!(
+						"invalid codec, invalid transfer_characteristics: {}, {}",
+						transfer_characteristics, e
+					)
+				})?;
 
-				let full_range_flag = splits[11]
+				let matrix_coefficients = matrix_coefficients
 					.parse::<u8>()
-					.map_err(|e| format!("invalid codec, invalid full_range_flag: {}, {}", splits[11], e))?
-					== 1;
+					.map_err(|e| format!("invalid codec, invalid matrix_coefficients: {}, {}", matrix_coefficients, e))?;
+
+				let full_range_flag = parse_flag(full_range, "full_range_flag")?;
 
 				Ok(VideoCodec::Av1 {
 					profile,
@@ -232,6 +307,7 @@ impl FromStr for VideoCodec {
 					monochrome,
 					sub_sampling_x,
 					sub_sampling_y,
+					chroma_sample_position,
 					color_primaries,
 					transfer_characteristics,
 					matrix_coefficients,
@@ -247,6 +323,7 @@ impl FromStr for VideoCodec {
 pub enum AudioCodec {
 	Aac { object_type: AudioObjectType },
 	Opus,
+	Mp3,
 }
 
 impl fmt::Display for AudioCodec {
@@ -254,6 +331,7 @@ impl fmt::Display for AudioCodec {
 		match self {
 			AudioCodec::Aac { object_type } => write!(f, "mp4a.40.{}", u16::from(*object_type)),
 			AudioCodec::Opus => write!(f, "opus"),
+			AudioCodec::Mp3 => write!(f, "mp4a.6B"),
 		}
 	}
 }
@@ -268,6 +346,7 @@ impl FromStr for AudioCodec {
 		}
 
 		match splits[0] {
+			"mp4a" if splits.len() == 2 && splits[1].eq_ignore_ascii_case("6b") => Ok(AudioCodec::Mp3),
 			"mp4a" => {
 				if splits.len() < 3 {
 					return Err("invalid codec, missing object type".into());
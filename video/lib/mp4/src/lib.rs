@@ -1,8 +1,12 @@
 mod boxes;
 
 pub mod codec;
+mod reader;
+mod writer;
 
 pub use boxes::{header, types, BoxType, DynBox};
+pub use reader::{FragmentReader, Mp4Reader, Sample, TrackInfo};
+pub use writer::Mp4Writer;
 
 #[cfg(test)]
 mod tests;
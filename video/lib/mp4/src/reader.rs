@@ -0,0 +1,450 @@
+use std::io;
+
+use bytes::{Buf, Bytes};
+
+use crate::boxes::types::hdlr::HandlerType;
+use crate::boxes::types::moof::Moof;
+use crate::boxes::types::moov::Moov;
+use crate::boxes::types::stbl::Stbl;
+use crate::boxes::types::tfhd::Tfhd;
+use crate::boxes::types::trak::Trak;
+use crate::boxes::types::trun::TrunSampleFlag;
+use crate::boxes::DynBox;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A track described by the moov box.
+pub struct TrackInfo {
+	pub track_id: u32,
+	pub handler_type: HandlerType,
+	/// The number of time units per second, all sample times are in this
+	/// timescale.
+	pub timescale: u32,
+	/// The duration from the mdhd box, this is 0 for fragmented files.
+	pub duration: u64,
+	pub width: u32,
+	pub height: u32,
+	/// The codec string of the first sample entry, if it is a codec we know.
+	pub codec: Option<String>,
+	/// The entries of the stsd box, samples refer to these by their 1-based
+	/// `sample_description_index`.
+	pub sample_entries: Vec<DynBox>,
+}
+
+impl TrackInfo {
+	fn new(trak: &Trak) -> Self {
+		let stsd = &trak.mdia.minf.stbl.stsd;
+
+		Self {
+			track_id: trak.tkhd.track_id,
+			handler_type: trak.mdia.hdlr.handler_type.clone(),
+			timescale: trak.mdia.mdhd.timescale,
+			duration: trak.mdia.mdhd.duration,
+			width: trak.tkhd.width.saturating_to_num(),
+			height: trak.tkhd.height.saturating_to_num(),
+			codec: stsd.get_codecs().next(),
+			sample_entries: stsd.entries.clone(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A single sample of a track.
+pub struct Sample {
+	pub track_id: u32,
+	/// The decode timestamp in the timescale of the track.
+	pub dts: u64,
+	/// The difference between the presentation and decode timestamp.
+	pub composition_offset: i64,
+	pub duration: u32,
+	pub size: u32,
+	pub is_sync: bool,
+	pub sample_description_index: u32,
+	/// The offset of the sample data from the start of the data that was read.
+	pub offset: u64,
+	pub data: Bytes,
+}
+
+impl Sample {
+	/// The presentation timestamp in the timescale of the track.
+	pub fn pts(&self) -> i64 {
+		self.dts as i64 + self.composition_offset
+	}
+}
+
+#[derive(Debug, Clone)]
+/// The state we keep for a track between fragments.
+struct TrackState {
+	track_id: u32,
+	default_sample_description_index: u32,
+	default_sample_duration: u32,
+	default_sample_size: u32,
+	default_sample_flags: TrunSampleFlag,
+	/// The decode time of the next sample, used if a fragment has no tfdt box.
+	next_dts: u64,
+}
+
+/// Reads the samples of fragmented MP4 segments.
+/// The reader is created from the init segment (ftyp + moov) and is then fed
+/// the media segments (moof + mdat) one at a time, like they are stored by a
+/// recording. Sample offsets are relative to the start of the segment they
+/// were read from.
+#[derive(Debug, Clone)]
+pub struct FragmentReader {
+	tracks: Vec<TrackInfo>,
+	state: Vec<TrackState>,
+}
+
+impl FragmentReader {
+	/// Create a reader from an init segment.
+	pub fn new(init_segment: Bytes) -> io::Result<Self> {
+		let mut reader = io::Cursor::new(init_segment);
+
+		while reader.has_remaining() {
+			if let DynBox::Moov(moov) = DynBox::demux(&mut reader)? {
+				return Ok(Self::from_moov(&moov));
+			}
+		}
+
+		Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"moov box not found in init segment",
+		))
+	}
+
+	fn from_moov(moov: &Moov) -> Self {
+		let tracks = moov.traks.iter().map(TrackInfo::new).collect::<Vec<_>>();
+
+		let state = tracks
+			.iter()
+			.map(|track| {
+				let trex = moov
+					.mvex
+					.as_ref()
+					.and_then(|mvex| mvex.trex.iter().find(|trex| trex.track_id == track.track_id));
+
+				TrackState {
+					track_id: track.track_id,
+					default_sample_description_index: trex.map(|t| t.default_sample_description_index).unwrap_or(1),
+					default_sample_duration: trex.map(|t| t.default_sample_duration).unwrap_or(0),
+					default_sample_size: trex.map(|t| t.default_sample_size).unwrap_or(0),
+					default_sample_flags: trex.map(|t| t.default_sample_flags).unwrap_or(0).into(),
+					next_dts: 0,
+				}
+			})
+			.collect();
+
+		Self { tracks, state }
+	}
+
+	/// The tracks of the init segment.
+	pub fn tracks(&self) -> &[TrackInfo] {
+		&self.tracks
+	}
+
+	/// Read all samples from a media segment, in the order they are stored
+	/// in the segment.
+	pub fn read_segment(&mut self, segment: Bytes) -> io::Result<Vec<Sample>> {
+		let mut samples = Vec::new();
+
+		let mut reader = io::Cursor::new(segment.clone());
+		while reader.has_remaining() {
+			let offset = reader.position();
+			if let DynBox::Moof(moof) = DynBox::demux(&mut reader)? {
+				samples.extend(self.read_moof(&moof, offset, &segment)?);
+			}
+		}
+
+		Ok(samples)
+	}
+
+	/// Read the samples of a moof box which starts at `moof_offset` in `data`.
+	fn read_moof(&mut self, moof: &Moof, moof_offset: u64, data: &Bytes) -> io::Result<Vec<Sample>> {
+		let mut samples = Vec::new();
+
+		// Without a base data offset the first traf is relative to the moof and every
+		// other traf follows the data of the one before it, unless default-base-is-moof
+		// is set.
+		let mut data_end = moof_offset;
+
+		for (idx, traf) in moof.traf.iter().enumerate() {
+			let tfhd = &traf.tfhd;
+
+			let state = self
+				.state
+				.iter_mut()
+				.find(|state| state.track_id == tfhd.track_id)
+				.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "traf box references an unknown track"))?;
+
+			let Some(trun) = &traf.trun else {
+				continue;
+			};
+
+			let base_data_offset = if let Some(base_data_offset) = tfhd.base_data_offset {
+				base_data_offset
+			} else if idx == 0 || tfhd.header.flags & Tfhd::DEFAULT_BASE_IS_MOOF_FLAG != 0 {
+				moof_offset
+			} else {
+				data_end
+			};
+
+			let mut offset = base_data_offset
+				.checked_add_signed(trun.data_offset.unwrap_or(0) as i64)
+				.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "trun data offset is out of range"))?;
+
+			let mut dts = traf
+				.tfdt
+				.as_ref()
+				.map(|tfdt| tfdt.base_media_decode_time)
+				.unwrap_or(state.next_dts);
+
+			let sample_description_index = tfhd
+				.sample_description_index
+				.unwrap_or(state.default_sample_description_index);
+
+			for (sample_idx, trun_sample) in trun.samples.iter().enumerate() {
+				let duration = trun_sample
+					.duration
+					.or(tfhd.default_sample_duration)
+					.unwrap_or(state.default_sample_duration);
+
+				let size = trun_sample
+					.size
+					.or(tfhd.default_sample_size)
+					.unwrap_or(state.default_sample_size);
+
+				let flags = if sample_idx == 0 { trun.first_sample_flags } else { None }
+					.or(trun_sample.flags)
+					.or(tfhd.default_sample_flags)
+					.unwrap_or(state.default_sample_flags);
+
+				samples.push(Sample {
+					track_id: state.track_id,
+					dts,
+					composition_offset: trun_sample.composition_time_offset.unwrap_or(0),
+					duration,
+					size,
+					is_sync: !flags.sample_is_non_sync_sample,
+					sample_description_index,
+					offset,
+					data: sample_data(data, offset, size)?,
+				});
+
+				dts += duration as u64;
+				offset += size as u64;
+			}
+
+			state.next_dts = dts;
+			data_end = offset;
+		}
+
+		Ok(samples)
+	}
+}
+
+/// Reads the samples of a complete MP4 file.
+/// Both progressive files, where the samples are described by the sample
+/// tables in the moov box, and fragmented files, where they are described
+/// by moof boxes, are supported. Sample offsets are relative to the start of
+/// the file.
+#[derive(Debug, Clone)]
+pub struct Mp4Reader {
+	tracks: Vec<TrackInfo>,
+	/// The samples of each track, in the same order as the tracks.
+	samples: Vec<Vec<Sample>>,
+}
+
+impl Mp4Reader {
+	pub fn new(data: Bytes) -> io::Result<Self> {
+		let mut boxes = Vec::new();
+
+		let mut reader = io::Cursor::new(data.clone());
+		while reader.has_remaining() {
+			let offset = reader.position();
+			boxes.push((offset, DynBox::demux(&mut reader)?));
+		}
+
+		// The moov box can be after the mdat box in progressive files, so we have to find
+		// it first.
+		let moov = boxes
+			.iter()
+			.find_map(|(_, b)| match b {
+				DynBox::Moov(moov) => Some(moov),
+				_ => None,
+			})
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "moov box not found"))?;
+
+		let mut fragments = FragmentReader::from_moov(moov);
+
+		let mut samples = Vec::with_capacity(moov.traks.len());
+		for (trak, state) in moov.traks.iter().zip(fragments.state.iter_mut()) {
+			let track_samples = stbl_samples(trak.tkhd.track_id, &trak.mdia.minf.stbl, &data)?;
+
+			// Fragments continue where the sample table ends.
+			state.next_dts = track_samples.last().map(|s| s.dts + s.duration as u64).unwrap_or(0);
+
+			samples.push(track_samples);
+		}
+
+		for (offset, b) in &boxes {
+			if let DynBox::Moof(moof) = b {
+				for sample in fragments.read_moof(moof, *offset, &data)? {
+					let idx = fragments
+						.tracks
+						.iter()
+						.position(|track| track.track_id == sample.track_id)
+						.expect("read_moof only returns samples of known tracks");
+
+					samples[idx].push(sample);
+				}
+			}
+		}
+
+		Ok(Self {
+			tracks: fragments.tracks,
+			samples,
+		})
+	}
+
+	/// The tracks of the file.
+	pub fn tracks(&self) -> &[TrackInfo] {
+		&self.tracks
+	}
+
+	/// The samples of a track in decode order, or None if there is no track
+	/// with this id.
+	pub fn samples(&self, track_id: u32) -> Option<&[Sample]> {
+		self.tracks
+			.iter()
+			.position(|track| track.track_id == track_id)
+			.map(|idx| self.samples[idx].as_slice())
+	}
+}
+
+/// Build the samples of a track from its sample table.
+fn stbl_samples(track_id: u32, stbl: &Stbl, data: &Bytes) -> io::Result<Vec<Sample>> {
+	let sample_count = stbl.stts.entries.iter().map(|e| e.sample_count as u64).sum::<u64>();
+	if sample_count == 0 {
+		return Ok(Vec::new());
+	}
+
+	let sizes: Vec<u32> = match (&stbl.stsz, &stbl.stz2) {
+		(Some(stsz), _) if stsz.sample_size != 0 => {
+			// Every sample has the same size, so the samples must fit in the data. This also
+			// stops us from allocating a huge table for a corrupt sample count.
+			if stsz.sample_size as u64 * sample_count > data.len() as u64 {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, "samples do not fit in the data"));
+			}
+
+			vec![stsz.sample_size; sample_count as usize]
+		}
+		(Some(stsz), _) => stsz.samples.clone(),
+		(None, Some(stz2)) => stz2.samples.iter().map(|&size| size as u32).collect(),
+		(None, None) => {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"stsz or stz2 box not found in stbl box",
+			));
+		}
+	};
+
+	if sizes.len() as u64 != sample_count {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"sample size count does not match the stts sample count",
+		));
+	}
+
+	let chunk_offsets: Vec<u64> = match (&stbl.co64, &stbl.stco) {
+		(Some(co64), _) => co64.chunk_offset.clone(),
+		(None, Some(stco)) => stco.entries.iter().map(|&offset| offset as u64).collect(),
+		(None, None) => Vec::new(),
+	};
+
+	// The chunk and its offset for every sample, from the sample to chunk table.
+	let mut sample_offsets = Vec::with_capacity(sizes.len());
+	let mut sample_description_indexes = Vec::with_capacity(sizes.len());
+	for (idx, entry) in stbl.stsc.entries.iter().enumerate() {
+		let last_chunk = stbl
+			.stsc
+			.entries
+			.get(idx + 1)
+			.map(|next| next.first_chunk)
+			.unwrap_or(chunk_offsets.len() as u32 + 1);
+
+		for chunk in entry.first_chunk..last_chunk {
+			let mut offset = *chunk
+				.checked_sub(1)
+				.and_then(|chunk| chunk_offsets.get(chunk as usize))
+				.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "stsc references an unknown chunk"))?;
+
+			for _ in 0..entry.samples_per_chunk {
+				let Some(size) = sizes.get(sample_offsets.len()) else {
+					break;
+				};
+
+				sample_offsets.push(offset);
+				sample_description_indexes.push(entry.sample_description_index);
+				offset += *size as u64;
+			}
+		}
+	}
+
+	if sample_offsets.len() != sizes.len() {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "stsc does not cover all samples"));
+	}
+
+	let durations = stbl
+		.stts
+		.entries
+		.iter()
+		.flat_map(|e| (0..e.sample_count).map(move |_| e.sample_delta));
+
+	let mut composition_offsets = stbl
+		.ctts
+		.as_ref()
+		.map(|ctts| ctts.entries.as_slice())
+		.unwrap_or_default()
+		.iter()
+		.flat_map(|e| (0..e.sample_count).map(move |_| e.sample_offset));
+
+	let mut samples = Vec::with_capacity(sizes.len());
+	let mut dts = 0;
+	for (idx, duration) in durations.enumerate() {
+		// Without a stss box every sample is a sync sample.
+		let is_sync = stbl
+			.stss
+			.as_ref()
+			.map(|stss| stss.entries.binary_search(&(idx as u32 + 1)).is_ok())
+			.unwrap_or(true);
+
+		let offset = sample_offsets[idx];
+		let size = sizes[idx];
+
+		samples.push(Sample {
+			track_id,
+			dts,
+			composition_offset: composition_offsets.next().unwrap_or(0),
+			duration,
+			size,
+			is_sync,
+			sample_description_index: sample_description_indexes[idx],
+			offset,
+			data: sample_data(data, offset, size)?,
+		});
+
+		dts += duration as u64;
+	}
+
+	Ok(samples)
+}
+
+fn sample_data(data: &Bytes, offset: u64, size: u32) -> io::Result<Bytes> {
+	let start = offset as usize;
+	let end = start + size as usize;
+
+	if offset > data.len() as u64 || end > data.len() {
+		return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "sample data is out of bounds"));
+	}
+
+	Ok(data.slice(start..end))
+}
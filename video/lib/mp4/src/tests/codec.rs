@@ -0,0 +1,105 @@
+use crate::codec::{HevcSampleEntry, VideoCodec};
+
+#[test]
+fn test_avc_codec_string() {
+	let codec = VideoCodec::Avc {
+		profile: 100,
+		constraint_set: 0,
+		level: 51,
+	};
+
+	assert_eq!(codec.to_string(), "avc1.640033");
+	assert_eq!("avc1.640033".parse::<VideoCodec>().unwrap(), codec);
+}
+
+#[test]
+fn test_hevc_codec_string() {
+	// Main profile, which is compatible with the main and main 10 profiles, with
+	// the progressive source and frame only constraint flags.
+	let codec = VideoCodec::Hevc {
+		sample_entry: HevcSampleEntry::Hvc1,
+		general_profile_space: 0,
+		profile_compatibility: 0x60,
+		profile: 1,
+		level: 153,
+		tier: false,
+		constraint_indicator: 0x90,
+	};
+
+	assert_eq!(codec.to_string(), "hvc1.1.6.L153.90");
+	assert_eq!("hvc1.1.6.L153.90".parse::<VideoCodec>().unwrap(), codec);
+
+	let codec = VideoCodec::Hevc {
+		sample_entry: HevcSampleEntry::Hev1,
+		general_profile_space: 1,
+		profile_compatibility: 0x04,
+		profile: 2,
+		level: 120,
+		tier: true,
+		constraint_indicator: 0xb0 | (0x01 << 16),
+	};
+
+	assert_eq!(codec.to_string(), "hev1.A2.20.H120.B0.0.1");
+	assert_eq!("hev1.A2.20.H120.B0.0.1".parse::<VideoCodec>().unwrap(), codec);
+
+	// The constraint flags can be left out entirely.
+	assert_eq!(
+		"hev1.1.6.L93".parse::<VideoCodec>().unwrap(),
+		VideoCodec::Hevc {
+			sample_entry: HevcSampleEntry::Hev1,
+			general_profile_space: 0,
+			profile_compatibility: 0x60,
+			profile: 1,
+			level: 93,
+			tier: false,
+			constraint_indicator: 0,
+		}
+	);
+
+	assert!("hvc1.1.6".parse::<VideoCodec>().is_err());
+	assert!("hvc1.1.6.X93.90".parse::<VideoCodec>().is_err());
+	assert!("hvc1.1.6.L93.90.0.0.0.0.0.0".parse::<VideoCodec>().is_err());
+}
+
+#[test]
+fn test_av1_codec_string() {
+	let codec = VideoCodec::Av1 {
+		profile: 0,
+		level: 8,
+		tier: false,
+		depth: 10,
+		monochrome: false,
+		sub_sampling_x: true,
+		sub_sampling_y: true,
+		chroma_sample_position: 1,
+		color_primaries: 9,
+		transfer_characteristics: 16,
+		matrix_coefficients: 9,
+		full_range_flag: false,
+	};
+
+	assert_eq!(codec.to_string(), "av01.0.08M.10.0.111.09.16.09.0");
+	assert_eq!("av01.0.08M.10.0.111.09.16.09.0".parse::<VideoCodec>().unwrap(), codec);
+
+	// The short form uses the default color settings.
+	assert_eq!(
+		"av01.0.13H.08".parse::<VideoCodec>().unwrap(),
+		VideoCodec::Av1 {
+			profile: 0,
+			level: 13,
+			tier: true,
+			depth: 8,
+			monochrome: false,
+			sub_sampling_x: true,
+			sub_sampling_y: true,
+			chroma_sample_position: 0,
+			color_primaries: 1,
+			transfer_characteristics: 1,
+			matrix_coefficients: 1,
+			full_range_flag: false,
+		}
+	);
+
+	assert!("av01.0.13X.08".parse::<VideoCodec>().is_err());
+	assert!("av01.0.13M.08.0.110".parse::<VideoCodec>().is_err());
+}
@@ -50,9 +50,11 @@ use crate::boxes::types::vmhd::Vmhd;
 use crate::boxes::DynBox;
 use crate::types::av01::Av01;
 use crate::types::av1c::Av1C;
+use crate::types::clli::Clli;
 use crate::types::colr::{ColorType, Colr};
 use crate::types::hev1::Hev1;
 use crate::types::hvcc::HvcC;
+use crate::types::mdcv::Mdcv;
 use crate::types::mvex::Mvex;
 use crate::types::trex::Trex;
 
@@ -269,6 +271,8 @@ fn test_demux_avc_aac() {
 									h_spacing: 1,
 									v_spacing: 1,
 								}),
+								mdcv: None,
+								clli: None,
 							}
 						},
 						avcc: AvcC {
@@ -337,14 +341,14 @@ fn test_demux_avc_aac() {
 
 			assert_eq!(
 				video_trak.mdia.minf.stbl.stco,
-				Stco {
+				Some(Stco {
 					header: FullBoxHeader {
 						header: BoxHeader { box_type: *b"stco" },
 						version: 0,
 						flags: 0,
 					},
 					entries: vec![],
-				}
+				})
 			);
 
 			assert_eq!(video_trak.mdia.minf.stbl.co64, None);
@@ -554,14 +558,14 @@ fn test_demux_avc_aac() {
 						sbgp: None,
 						sdtp: None,
 						stdp: None,
-						stco: Stco {
+						stco: Some(Stco {
 							header: FullBoxHeader {
 								header: BoxHeader { box_type: *b"stco" },
 								version: 0,
 								flags: 0,
 							},
 							entries: vec![],
-						},
+						}),
 						stsc: Stsc {
 							header: FullBoxHeader {
 								header: BoxHeader { box_type: *b"stsc" },
@@ -1193,6 +1197,8 @@ fn test_demux_av1_aac() {
 								reserved: 0,
 								reserved2: 0,
 								pasp: None,
+								mdcv: None,
+								clli: None,
 							}
 						},
 						av1c: Av1C {
@@ -1259,14 +1265,14 @@ fn test_demux_av1_aac() {
 
 			assert_eq!(
 				video_trak.mdia.minf.stbl.stco,
-				Stco {
+				Some(Stco {
 					header: FullBoxHeader {
 						header: BoxHeader { box_type: *b"stco" },
 						version: 0,
 						flags: 0,
 					},
 					entries: vec![],
-				}
+				})
 			);
 
 			assert_eq!(video_trak.mdia.minf.stbl.co64, None);
@@ -1476,14 +1482,14 @@ fn test_demux_av1_aac() {
 						sbgp: None,
 						sdtp: None,
 						stdp: None,
-						stco: Stco {
+						stco: Some(Stco {
 							header: FullBoxHeader {
 								header: BoxHeader { box_type: *b"stco" },
 								version: 0,
 								flags: 0,
 							},
 							entries: vec![],
-						},
+						}),
 						stsc: Stsc {
 							header: FullBoxHeader {
 								header: BoxHeader { box_type: *b"stsc" },
@@ -1883,7 +1889,9 @@ fn test_demux_hevc_aac() {
                                         header: BoxHeader { box_type: *b"pasp" },
                                         h_spacing: 1,
                                         v_spacing: 1
-                                    })
+                                    }),
+                                    mdcv: None,
+                                    clli: None
                                 }
                             },
                             hvcc: HvcC {
@@ -1924,7 +1932,7 @@ fn test_demux_hevc_aac() {
                                         },
                                         NaluArray {
                                             array_completeness: false,
-                                            nal_unit_type: NaluType::Unknown(39),
+                                            nal_unit_type: NaluType::PrefixSei,
                                             nalus: vec![b"N\x01\x05\xff\xff\xff\xff\xff\xff\xff\xff\xf5,\xa2\xde\t\xb5\x17G\xdb\xbbU\xa4\xfe\x7f\xc2\xfcNx265 (build 199) - 3.5+1-f0c1022b6:[Linux][GCC 11.2.0][64 bit] 8bit+10bit+12bit - H.265/HEVC codec - Copyright 2013-2018 (c) Multicoreware, Inc - http://x265.org - options: cpuid=1111039 frame-threads=6 no-wpp no-pmode no-pme no-psnr no-ssim log-level=2 bitdepth=8 input-csp=1 fps=60/1 input-res=3840x2160 interlace=0 total-frames=0 level-idc=0 high-tier=1 uhd-bd=0 ref=3 no-allow-non-conformance no-repeat-headers annexb no-aud no-hrd info hash=0 no-temporal-layers open-gop min-keyint=25 keyint=250 gop-lookahead=0 bframes=4 b-adapt=2 b-pyramid bframe-bias=0 rc-lookahead=20 lookahead-slices=0 scenecut=40 hist-scenecut=0 radl=0 no-splice no-intra-refresh ctu=64 min-cu-size=8 no-rect no-amp max-tu-size=32 tu-inter-depth=1 tu-intra-depth=1 limit-tu=0 rdoq-level=0 dynamic-rd=0.00 no-ssim-rd signhide no-tskip nr-intra=0 nr-inter=0 no-constrained-intra strong-intra-smoothing max-merge=3 limit-refs=1 no-limit-modes me=1 subme=2 merange=57 temporal-mvp no-frame-dup no-hme weightp no-weightb no-analyze-src-pics deblock=0:0 sao no-sao-non-deblock rd=3 selective-sao=4 early-skip rskip no-fast-intra no-tskip-fast no-cu-lossless b-intra no-splitrd-skip rdpenalty=0 psy-rd=2.00 psy-rdoq=0.00 no-rd-refine no-lossless cbqpoffs=0 crqpoffs=0 rc=crf crf=28.0 qcomp=0.60 qpstep=4 stats-write=0 stats-read=0 ipratio=1.40 pbratio=1.30 aq-mode=2 aq-strength=1.00 cutree zone-count=0 no-strict-cbr qg-size=32 no-rc-grain qpmax=69 qpmin=0 no-const-vbv sar=1 overscan=0 videoformat=5 range=0 colorprim=2 transfer=2 colormatrix=2 chromaloc=0 display-window=0 cll=0,0 min-luma=0 max-luma=255 log2-max-poc-lsb=8 vui-timing-info vui-hrd-info slices=1 no-opt-qp-pps no-opt-ref-list-length-pps no-multi-pass-opt-rps scenecut-bias=0.05 hist-threshold=0.03 no-opt-cu-delta-qp no-aq-motion no-hdr10 no-hdr10-opt no-dhdr10-opt no-idr-recovery-sei analysis-reuse-level=0 analysis-save-reuse-level=0 analysis-load-reuse-level=0 scale-factor=0 refine-intra=0 refine-inter=0 refine-mv=1 refine-ctu-distortion=0 no-limit-sao ctu-info=0 no-lowpass-dct refine-analysis-type=0 copy-pic=1 max-ausize-factor=1.0 no-dynamic-refine no-single-sei no-hevc-aq no-svt no-field qp-adaptation-range=1.00 scenecut-aware-qp=0conformance-window-offsets right=0 bottom=0 decoder-max-rate=0 no-vbv-live-multi-pass\x80".to_vec().into()]
                                         }
                                     ]
@@ -1978,14 +1986,14 @@ fn test_demux_hevc_aac() {
 
 			assert_eq!(
 				video_trak.mdia.minf.stbl.stco,
-				Stco {
+				Some(Stco {
 					header: FullBoxHeader {
 						header: BoxHeader { box_type: *b"stco" },
 						version: 0,
 						flags: 0,
 					},
 					entries: vec![],
-				}
+				})
 			);
 
 			assert_eq!(video_trak.mdia.minf.stbl.co64, None);
@@ -2195,14 +2203,14 @@ fn test_demux_hevc_aac() {
 						sbgp: None,
 						sdtp: None,
 						stdp: None,
-						stco: Stco {
+						stco: Some(Stco {
 							header: FullBoxHeader {
 								header: BoxHeader { box_type: *b"stco" },
 								version: 0,
 								flags: 0,
 							},
 							entries: vec![],
-						},
+						}),
 						stsc: Stsc {
 							header: FullBoxHeader {
 								header: BoxHeader { box_type: *b"stsc" },
@@ -2535,3 +2543,29 @@ fn test_mux_hevc_aac() {
 	assert_eq!(json["streams"][1]["sample_rate"], "48000");
 	assert_eq!(json["streams"][1]["channels"], 2);
 }
+
+#[test]
+fn test_mux_hev1_hdr() {
+	let data = Bytes::from(b"\x01\x01@\0\0\0\x90\0\0\0\0\0\x99\xf0\0\xfc\xfd\xf8\xf8\0\0\x0f\x03 \0\x01\0\x18@\x01\x0c\x01\xff\xff\x01@\0\0\x03\0\x90\0\0\x03\0\0\x03\0\x99\x95@\x90!\0\x01\0=B\x01\x01\x01@\0\0\x03\0\x90\0\0\x03\0\0\x03\0\x99\xa0\x01@ \x05\xa1e\x95R\x90\x84d_\xf8\xc0Z\x80\x80\x80\x82\0\0\x03\0\x02\0\0\x03\x01 \xc0\x0b\xbc\xa2\0\x02bX\0\x011-\x08\"\0\x01\0\x07D\x01\xc0\x93|\x0c\xc9".to_vec());
+	let config = HEVCDecoderConfigurationRecord::demux(&mut io::Cursor::new(data)).unwrap();
+
+	let mut visual_sample_entry = VisualSampleEntry::new(2560, 1440, None);
+	visual_sample_entry.mdcv = Some(Mdcv::new(
+		[(13250, 34500), (7500, 3000), (34000, 16000)],
+		(15635, 16450),
+		10_000_000,
+		50,
+	));
+	visual_sample_entry.clli = Some(Clli::new(1000, 400));
+
+	let hev1: DynBox = Hev1::new(SampleEntry::new(visual_sample_entry), HvcC::new(config), None).into();
+
+	let mut writer = io::Cursor::new(Vec::new());
+	hev1.mux(&mut writer).unwrap();
+
+	let data = Bytes::from(writer.into_inner());
+	assert_eq!(hev1.size(), data.len() as u64);
+
+	let new_hev1 = DynBox::demux(&mut io::Cursor::new(data)).unwrap();
+	assert_eq!(hev1, new_hev1);
+}
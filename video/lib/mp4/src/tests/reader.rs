@@ -0,0 +1,346 @@
+use bytes::Bytes;
+
+use crate::boxes::header::FullBoxHeader;
+use crate::boxes::types::co64::Co64;
+use crate::boxes::types::ctts::{Ctts, CttsEntry};
+use crate::boxes::types::ftyp::{FourCC, Ftyp};
+use crate::boxes::types::hdlr::{HandlerType, Hdlr};
+use crate::boxes::types::mdat::Mdat;
+use crate::boxes::types::mdhd::Mdhd;
+use crate::boxes::types::mdia::Mdia;
+use crate::boxes::types::mfhd::Mfhd;
+use crate::boxes::types::minf::Minf;
+use crate::boxes::types::moof::Moof;
+use crate::boxes::types::moov::Moov;
+use crate::boxes::types::mvex::Mvex;
+use crate::boxes::types::mvhd::Mvhd;
+use crate::boxes::types::smhd::Smhd;
+use crate::boxes::types::stbl::Stbl;
+use crate::boxes::types::stco::Stco;
+use crate::boxes::types::stsc::{Stsc, StscEntry};
+use crate::boxes::types::stsd::Stsd;
+use crate::boxes::types::stss::Stss;
+use crate::boxes::types::stsz::Stsz;
+use crate::boxes::types::stts::{Stts, SttsEntry};
+use crate::boxes::types::tfdt::Tfdt;
+use crate::boxes::types::tfhd::Tfhd;
+use crate::boxes::types::tkhd::Tkhd;
+use crate::boxes::types::traf::Traf;
+use crate::boxes::types::trak::Trak;
+use crate::boxes::types::trex::Trex;
+use crate::boxes::types::trun::{Trun, TrunSample, TrunSampleFlag};
+use crate::boxes::types::vmhd::Vmhd;
+use crate::{BoxType, FragmentReader, Mp4Reader};
+
+fn trak(track_id: u32, handler_type: HandlerType, timescale: u32, stbl: Stbl) -> Trak {
+	let (vmhd, smhd, width_height) = match handler_type {
+		HandlerType::Vide => (Some(Vmhd::new()), None, Some((1280, 720))),
+		_ => (None, Some(Smhd::new()), None),
+	};
+
+	Trak::new(
+		Tkhd::new(0, 0, track_id, 0, width_height),
+		None,
+		Mdia::new(
+			Mdhd::new(0, 0, timescale, 0),
+			Hdlr::new(handler_type, "Handler".to_string()),
+			Minf::new(stbl, vmhd, smhd),
+		),
+	)
+}
+
+fn empty_stbl() -> Stbl {
+	Stbl::new(
+		Stsd::new(vec![]),
+		Stts::new(vec![]),
+		Stsc::new(vec![]),
+		Stco::new(vec![]),
+		Some(Stsz::new(0, vec![])),
+	)
+}
+
+fn init_segment() -> Vec<u8> {
+	let mut writer = Vec::new();
+
+	Ftyp::new(FourCC::Iso5, 512, vec![FourCC::Iso5, FourCC::Iso6])
+		.mux(&mut writer)
+		.unwrap();
+	Moov::new(
+		Mvhd::new(0, 0, 1000, 0, 3),
+		vec![
+			trak(1, HandlerType::Vide, 30000, empty_stbl()),
+			trak(2, HandlerType::Soun, 48000, empty_stbl()),
+		],
+		Some(Mvex::new(vec![Trex::new(1), Trex::new(2)], None)),
+	)
+	.mux(&mut writer)
+	.unwrap();
+
+	writer
+}
+
+fn sample_flags(is_sync: bool) -> Option<TrunSampleFlag> {
+	Some(TrunSampleFlag {
+		sample_depends_on: if is_sync { 2 } else { 1 },
+		sample_is_non_sync_sample: !is_sync,
+		..Default::default()
+	})
+}
+
+/// A segment with a traf per track, the data of the tracks follows each
+/// other in the mdat.
+fn media_segment(sequence_number: u32, tfdt: Option<(u64, u64)>, video: &[&[u8]], audio: &[&[u8]]) -> Vec<u8> {
+	let video_samples = video
+		.iter()
+		.enumerate()
+		.map(|(idx, data)| TrunSample {
+			duration: Some(1000),
+			size: Some(data.len() as u32),
+			flags: sample_flags(idx == 0),
+			composition_time_offset: Some(if idx == 0 { 0 } else { 2000 }),
+		})
+		.collect();
+
+	let audio_samples = audio
+		.iter()
+		.map(|data| TrunSample {
+			duration: Some(1024),
+			size: Some(data.len() as u32),
+			flags: sample_flags(true),
+			composition_time_offset: None,
+		})
+		.collect();
+
+	let mut trafs = vec![
+		Traf::new(
+			Tfhd::new(1, None, None, None, None, None),
+			Some(Trun::new(video_samples, None)),
+			tfdt.map(|(video, _)| Tfdt::new(video)),
+		),
+		Traf::new(
+			Tfhd::new(2, None, None, None, None, None),
+			Some(Trun::new(audio_samples, None)),
+			tfdt.map(|(_, audio)| Tfdt::new(audio)),
+		),
+	];
+	trafs.iter_mut().for_each(|traf| traf.optimize());
+
+	let mut moof = Moof::new(Mfhd::new(sequence_number), trafs);
+
+	let video_size = video.iter().map(|data| data.len()).sum::<usize>();
+	let data_offset = moof.size() as i32 + 8;
+	moof.traf[0].trun.as_mut().unwrap().data_offset = Some(data_offset);
+	moof.traf[1].trun.as_mut().unwrap().data_offset = Some(data_offset + video_size as i32);
+
+	let mut writer = Vec::new();
+	moof.mux(&mut writer).unwrap();
+	Mdat::new(video.iter().chain(audio).map(|data| Bytes::copy_from_slice(data)).collect())
+		.mux(&mut writer)
+		.unwrap();
+
+	writer
+}
+
+#[test]
+fn test_fragment_reader() {
+	let mut reader = FragmentReader::new(Bytes::from(init_segment())).unwrap();
+
+	let tracks = reader.tracks();
+	assert_eq!(tracks.len(), 2);
+	assert_eq!(tracks[0].track_id, 1);
+	assert_eq!(tracks[0].handler_type, HandlerType::Vide);
+	assert_eq!(tracks[0].timescale, 30000);
+	assert_eq!((tracks[0].width, tracks[0].height), (1280, 720));
+	assert_eq!(tracks[1].track_id, 2);
+	assert_eq!(tracks[1].handler_type, HandlerType::Soun);
+	assert_eq!(tracks[1].timescale, 48000);
+
+	let segment = Bytes::from(media_segment(1, Some((3000, 2048)), &[b"key", b"delta"], &[b"aac1", b"aac2"]));
+	let samples = reader.read_segment(segment.clone()).unwrap();
+	assert_eq!(samples.len(), 4);
+
+	assert_eq!(samples[0].track_id, 1);
+	assert_eq!(samples[0].dts, 3000);
+	assert_eq!(samples[0].pts(), 3000);
+	assert!(samples[0].is_sync);
+	assert_eq!(samples[0].data, Bytes::from_static(b"key"));
+	assert_eq!(&segment[samples[0].offset as usize..][..3], b"key");
+
+	assert_eq!(samples[1].track_id, 1);
+	assert_eq!(samples[1].dts, 4000);
+	assert_eq!(samples[1].pts(), 6000);
+	assert_eq!(samples[1].duration, 1000);
+	assert!(!samples[1].is_sync);
+	assert_eq!(samples[1].size, 5);
+	assert_eq!(samples[1].offset, samples[0].offset + 3);
+	assert_eq!(samples[1].data, Bytes::from_static(b"delta"));
+
+	assert_eq!(samples[2].track_id, 2);
+	assert_eq!(samples[2].dts, 2048);
+	assert_eq!(samples[2].duration, 1024);
+	assert!(samples[2].is_sync);
+	assert_eq!(samples[2].offset, samples[1].offset + 5);
+	assert_eq!(samples[2].data, Bytes::from_static(b"aac1"));
+	assert_eq!(samples[3].dts, 3072);
+	assert_eq!(samples[3].data, Bytes::from_static(b"aac2"));
+
+	// Without a tfdt box the samples continue where the last segment ended.
+	let samples = reader
+		.read_segment(Bytes::from(media_segment(2, None, &[b"key2"], &[b"aac3"])))
+		.unwrap();
+	assert_eq!(samples.len(), 2);
+	assert_eq!(samples[0].dts, 5000);
+	assert_eq!(samples[0].data, Bytes::from_static(b"key2"));
+	assert_eq!(samples[1].dts, 4096);
+	assert_eq!(samples[1].data, Bytes::from_static(b"aac3"));
+}
+
+#[test]
+fn test_mp4_reader_fragmented() {
+	let mut file = init_segment();
+	file.extend(media_segment(1, Some((0, 0)), &[b"key", b"delta"], &[b"aac1"]));
+	let second_segment_offset = file.len();
+	file.extend(media_segment(2, Some((2000, 1024)), &[b"key2"], &[b"aac2"]));
+
+	let reader = Mp4Reader::new(Bytes::from(file)).unwrap();
+	assert_eq!(reader.tracks().len(), 2);
+
+	let video = reader.samples(1).unwrap();
+	assert_eq!(video.len(), 3);
+	assert_eq!(video.iter().map(|s| s.dts).collect::<Vec<_>>(), vec![0, 1000, 2000]);
+	assert_eq!(video.iter().map(|s| s.is_sync).collect::<Vec<_>>(), vec![true, false, true]);
+	assert_eq!(video[2].data, Bytes::from_static(b"key2"));
+	assert!(video[2].offset > second_segment_offset as u64);
+
+	let audio = reader.samples(2).unwrap();
+	assert_eq!(audio.len(), 2);
+	assert_eq!(audio[1].dts, 1024);
+	assert_eq!(audio[1].data, Bytes::from_static(b"aac2"));
+
+	assert!(reader.samples(3).is_none());
+}
+
+#[test]
+fn test_mp4_reader_progressive() {
+	// 4 video samples in 2 chunks, the second chunk only has a single sample and
+	// the data is placed after the moov box using 64 bit chunk offsets.
+	let data: [&[u8]; 4] = [b"i", b"pp", b"bbb", b"iiii"];
+
+	let moov = |chunk_offsets: Vec<u64>| {
+		let mut stbl = Stbl::new(
+			Stsd::new(vec![]),
+			Stts::new(vec![
+				SttsEntry {
+					sample_count: 3,
+					sample_delta: 1000,
+				},
+				SttsEntry {
+					sample_count: 1,
+					sample_delta: 2000,
+				},
+			]),
+			Stsc::new(vec![
+				StscEntry {
+					first_chunk: 1,
+					samples_per_chunk: 3,
+					sample_description_index: 1,
+				},
+				StscEntry {
+					first_chunk: 2,
+					samples_per_chunk: 1,
+					sample_description_index: 1,
+				},
+			]),
+			Stco::new(vec![]),
+			Some(Stsz::new(0, data.iter().map(|d| d.len() as u32).collect())),
+		);
+		stbl.stco = None;
+		stbl.co64 = Some(Co64 {
+			header: FullBoxHeader::new(*b"co64", 0, 0),
+			chunk_offset: chunk_offsets,
+		});
+		stbl.ctts = Some(Ctts {
+			header: FullBoxHeader::new(*b"ctts", 0, 0),
+			entries: vec![
+				CttsEntry {
+					sample_count: 1,
+					sample_offset: 1000,
+				},
+				CttsEntry {
+					sample_count: 1,
+					sample_offset: 2000,
+				},
+				CttsEntry {
+					sample_count: 2,
+					sample_offset: 0,
+				},
+			],
+		});
+		stbl.stss = Some(Stss {
+			header: FullBoxHeader::new(*b"stss", 0, 0),
+			entries: vec![1, 4],
+		});
+
+		Moov::new(
+			Mvhd::new(0, 0, 1000, 0, 2),
+			vec![trak(1, HandlerType::Vide, 1000, stbl)],
+			None,
+		)
+	};
+
+	let ftyp = Ftyp::new(FourCC::Iso5, 512, vec![FourCC::Iso5]);
+
+	// The size of the moov does not depend on the offsets, so we can use it to find
+	// where the mdat data starts.
+	let mdat_data_offset = ftyp.size() + moov(vec![0, 0]).size() + 8;
+
+	let mut file = Vec::new();
+	ftyp.mux(&mut file).unwrap();
+	moov(vec![mdat_data_offset, mdat_data_offset + 6]).mux(&mut file).unwrap();
+	Mdat::new(data.iter().map(|d| Bytes::copy_from_slice(d)).collect())
+		.mux(&mut file)
+		.unwrap();
+
+	let reader = Mp4Reader::new(Bytes::from(file)).unwrap();
+
+	let samples = reader.samples(1).unwrap();
+	assert_eq!(samples.len(), 4);
+	assert_eq!(samples.iter().map(|s| s.dts).collect::<Vec<_>>(), vec![0, 1000, 2000, 3000]);
+	assert_eq!(
+		samples.iter().map(|s| s.pts()).collect::<Vec<_>>(),
+		vec![1000, 3000, 2000, 3000]
+	);
+	assert_eq!(
+		samples.iter().map(|s| s.duration).collect::<Vec<_>>(),
+		vec![1000, 1000, 1000, 2000]
+	);
+	assert_eq!(
+		samples.iter().map(|s| s.is_sync).collect::<Vec<_>>(),
+		vec![true, false, false, true]
+	);
+	assert_eq!(
+		samples.iter().map(|s| s.offset).collect::<Vec<_>>(),
+		vec![
+			mdat_data_offset,
+			mdat_data_offset + 1,
+			mdat_data_offset + 3,
+			mdat_data_offset + 6
+		]
+	);
+	for (sample, data) in samples.iter().zip(data) {
+		assert_eq!(sample.data, Bytes::from_static(data));
+	}
+}
+
+#[test]
+fn test_mp4_reader_errors() {
+	assert!(Mp4Reader::new(Bytes::new()).is_err());
+	assert!(FragmentReader::new(Bytes::new()).is_err());
+
+	let mut reader = FragmentReader::new(Bytes::from(init_segment())).unwrap();
+
+	// Only the moof box, so the sample data is out of bounds.
+	let segment = media_segment(1, Some((0, 0)), &[b"key"], &[b"aac"]);
+	let moof_size = segment.len() - 8 - 6;
+	assert!(reader.read_segment(Bytes::from(segment[..moof_size].to_vec())).is_err());
+}
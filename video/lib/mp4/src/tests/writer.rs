@@ -0,0 +1,192 @@
+use bytes::{Buf, Bytes};
+
+use crate::boxes::header::BoxHeader;
+use crate::boxes::types::elst::ElstEntry;
+use crate::boxes::types::hdlr::HandlerType;
+use crate::boxes::types::stsc::StscEntry;
+use crate::boxes::DynBox;
+use crate::{Mp4Reader, Mp4Writer, Sample, TrackInfo};
+
+fn track(track_id: u32, handler_type: HandlerType, timescale: u32) -> TrackInfo {
+	TrackInfo {
+		track_id,
+		handler_type,
+		timescale,
+		duration: 0,
+		width: 1280,
+		height: 720,
+		codec: None,
+		sample_entries: vec![DynBox::Unknown((
+			BoxHeader::new(*b"test"),
+			Bytes::from_static(b"sample entry"),
+		))],
+	}
+}
+
+fn sample(track_id: u32, dts: u64, composition_offset: i64, duration: u32, is_sync: bool, data: Vec<u8>) -> Sample {
+	Sample {
+		track_id,
+		dts,
+		composition_offset,
+		duration,
+		size: data.len() as u32,
+		is_sync,
+		sample_description_index: 1,
+		offset: 0,
+		data: Bytes::from(data),
+	}
+}
+
+fn write(writer: Mp4Writer) -> Bytes {
+	let mut data = Vec::new();
+	writer.finish(&mut data).unwrap();
+	Bytes::from(data)
+}
+
+#[test]
+fn test_writer_faststart() {
+	let mut writer = Mp4Writer::new();
+	writer.add_track(track(1, HandlerType::Vide, 30)).unwrap();
+	writer.add_track(track(2, HandlerType::Soun, 48000)).unwrap();
+
+	// 3 seconds of video at 30fps with a keyframe every second, and every other frame
+	// is presented later than it is decoded.
+	let video = (0..90)
+		.map(|idx| sample(1, idx, (idx % 2) as i64, 1, idx % 30 == 0, vec![idx as u8; 10 + idx as usize]))
+		.collect::<Vec<_>>();
+
+	// 3 seconds of audio with 1024 samples per frame.
+	let audio = (0..140)
+		.map(|idx| sample(2, idx * 1024, 0, 1024, true, vec![0xAA; 4]))
+		.collect::<Vec<_>>();
+
+	for sample in video.iter().chain(&audio) {
+		writer.add_sample(sample.clone()).unwrap();
+	}
+
+	let file = write(writer);
+
+	// The moov box must be in front of the mdat box.
+	let mut reader = std::io::Cursor::new(file.clone());
+	let mut boxes = Vec::new();
+	while reader.has_remaining() {
+		boxes.push(DynBox::demux(&mut reader).unwrap());
+	}
+	assert!(matches!(
+		boxes.as_slice(),
+		[DynBox::Ftyp(_), DynBox::Moov(_), DynBox::Mdat(_)]
+	));
+
+	let DynBox::Moov(moov) = &boxes[1] else {
+		unreachable!();
+	};
+	assert_eq!(moov.mvhd.duration, 3000);
+
+	let stbl = &moov.traks[0].mdia.minf.stbl;
+	assert_eq!(stbl.stss.as_ref().unwrap().entries, vec![1, 31, 61]);
+	assert_eq!(stbl.ctts.as_ref().unwrap().entries.len(), 90);
+	assert_eq!(stbl.stts.entries.len(), 1);
+	assert!(stbl.stco.is_some());
+	assert!(stbl.co64.is_none());
+	// One chunk per second.
+	assert_eq!(
+		stbl.stsc.entries,
+		vec![StscEntry {
+			first_chunk: 1,
+			samples_per_chunk: 30,
+			sample_description_index: 1,
+		}]
+	);
+	assert_eq!(stbl.stco.as_ref().unwrap().entries.len(), 3);
+
+	// Audio is always a sync sample and has no composition offsets.
+	let stbl = &moov.traks[1].mdia.minf.stbl;
+	assert!(stbl.stss.is_none());
+	assert!(stbl.ctts.is_none());
+
+	let reader = Mp4Reader::new(file).unwrap();
+	assert_eq!(reader.tracks().len(), 2);
+	assert_eq!(reader.tracks()[0].timescale, 30);
+	assert_eq!(reader.tracks()[0].duration, 90);
+	assert_eq!((reader.tracks()[0].width, reader.tracks()[0].height), (1280, 720));
+	assert_eq!(reader.tracks()[1].duration, 140 * 1024);
+
+	for (expected, samples) in [(&video, reader.samples(1).unwrap()), (&audio, reader.samples(2).unwrap())] {
+		assert_eq!(expected.len(), samples.len());
+		for (expected, sample) in expected.iter().zip(samples) {
+			assert_eq!(sample.dts, expected.dts);
+			assert_eq!(sample.composition_offset, expected.composition_offset);
+			assert_eq!(sample.duration, expected.duration);
+			assert_eq!(sample.is_sync, expected.is_sync);
+			assert_eq!(sample.data, expected.data);
+		}
+	}
+
+	// The chunks of both tracks are interleaved by time.
+	let video = reader.samples(1).unwrap();
+	let audio = reader.samples(2).unwrap();
+	assert!(video[0].offset < audio[0].offset);
+	assert!(audio[0].offset < video[30].offset);
+	assert!(video[30].offset < audio[47].offset);
+}
+
+#[test]
+fn test_writer_edit_list() {
+	let mut writer = Mp4Writer::new();
+	writer.add_track(track(1, HandlerType::Vide, 1000)).unwrap();
+
+	// The track starts after half a second and the first frame is presented 100ms
+	// after it is decoded.
+	writer.add_sample(sample(1, 500, 100, 100, true, vec![1])).unwrap();
+	writer.add_sample(sample(1, 600, 200, 100, false, vec![2])).unwrap();
+
+	let mut reader = std::io::Cursor::new(write(writer));
+	let moov = loop {
+		if let DynBox::Moov(moov) = DynBox::demux(&mut reader).unwrap() {
+			break moov;
+		}
+	};
+
+	let elst = moov.traks[0].edts.as_ref().unwrap().elst.as_ref().unwrap();
+	assert_eq!(
+		elst.entries,
+		vec![
+			ElstEntry {
+				segment_duration: 500,
+				media_time: -1,
+				media_rate_integer: 1,
+				media_rate_fraction: 0,
+			},
+			ElstEntry {
+				segment_duration: 100,
+				media_time: 100,
+				media_rate_integer: 1,
+				media_rate_fraction: 0,
+			},
+		]
+	);
+	assert_eq!(moov.traks[0].tkhd.duration, 700);
+}
+
+#[test]
+fn test_writer_errors() {
+	let mut writer = Mp4Writer::new();
+	writer.add_track(track(1, HandlerType::Vide, 1000)).unwrap();
+
+	assert!(writer.add_track(track(1, HandlerType::Soun, 1000)).is_err());
+	assert!(writer.add_track(track(0, HandlerType::Soun, 1000)).is_err());
+	assert!(writer.add_track(track(2, HandlerType::Soun, 0)).is_err());
+
+	assert!(writer.add_sample(sample(2, 0, 0, 1, true, vec![1])).is_err());
+
+	let mut invalid = sample(1, 0, 0, 1, true, vec![1]);
+	invalid.sample_description_index = 2;
+	assert!(writer.add_sample(invalid).is_err());
+
+	let mut invalid = sample(1, 0, 0, 1, true, vec![1]);
+	invalid.size = 2;
+	assert!(writer.add_sample(invalid).is_err());
+
+	writer.add_sample(sample(1, 10, 0, 1, true, vec![1])).unwrap();
+	assert!(writer.add_sample(sample(1, 9, 0, 1, true, vec![1])).is_err());
+}
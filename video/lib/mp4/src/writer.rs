@@ -0,0 +1,401 @@
+use std::io;
+
+use crate::boxes::types::co64::Co64;
+use crate::boxes::types::ctts::{Ctts, CttsEntry};
+use crate::boxes::types::edts::Edts;
+use crate::boxes::types::elst::{Elst, ElstEntry};
+use crate::boxes::types::ftyp::{FourCC, Ftyp};
+use crate::boxes::types::hdlr::{HandlerType, Hdlr};
+use crate::boxes::types::mdat::Mdat;
+use crate::boxes::types::mdhd::Mdhd;
+use crate::boxes::types::mdia::Mdia;
+use crate::boxes::types::minf::Minf;
+use crate::boxes::types::moov::Moov;
+use crate::boxes::types::mvhd::Mvhd;
+use crate::boxes::types::smhd::Smhd;
+use crate::boxes::types::stbl::Stbl;
+use crate::boxes::types::stco::Stco;
+use crate::boxes::types::stsc::{Stsc, StscEntry};
+use crate::boxes::types::stsd::Stsd;
+use crate::boxes::types::stss::Stss;
+use crate::boxes::types::stsz::Stsz;
+use crate::boxes::types::stts::{Stts, SttsEntry};
+use crate::boxes::types::tkhd::Tkhd;
+use crate::boxes::types::trak::Trak;
+use crate::boxes::types::vmhd::Vmhd;
+use crate::boxes::{BoxType, DynBox};
+use crate::{Sample, TrackInfo};
+
+/// The timescale of the movie, used for the track and movie durations.
+const MOVIE_TIMESCALE: u32 = 1000;
+
+/// The maximum duration of a chunk in seconds. Chunks of all tracks are
+/// interleaved by time, so a player reading the file from the start does not
+/// have to jump around the mdat to find samples that play at the same time.
+const CHUNK_DURATION: u64 = 1;
+
+#[derive(Debug, Clone)]
+struct WriterTrack {
+	info: TrackInfo,
+	samples: Vec<Sample>,
+}
+
+#[derive(Debug, Clone)]
+/// A run of samples of a single track, which are stored next to each other.
+struct Chunk {
+	track: usize,
+	first_sample: usize,
+	sample_count: usize,
+	sample_description_index: u32,
+	start_dts: u64,
+	timescale: u32,
+}
+
+/// Writes a progressive (non-fragmented) MP4 file.
+/// Tracks and samples are collected in memory and written by `finish` with
+/// the moov box in front of the mdat box (faststart), so the file can be
+/// played while it is being downloaded.
+#[derive(Debug, Clone, Default)]
+pub struct Mp4Writer {
+	tracks: Vec<WriterTrack>,
+}
+
+impl Mp4Writer {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a track. The duration and codec of the track are ignored, the
+	/// duration is computed from the samples.
+	pub fn add_track(&mut self, track: TrackInfo) -> io::Result<()> {
+		if track.track_id == 0 {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "track id must not be 0"));
+		}
+
+		if track.timescale == 0 {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "track timescale must not be 0"));
+		}
+
+		if self.tracks.iter().any(|t| t.info.track_id == track.track_id) {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "track id is already used"));
+		}
+
+		self.tracks.push(WriterTrack {
+			info: track,
+			samples: Vec::new(),
+		});
+
+		Ok(())
+	}
+
+	/// Add a sample to the track with the id `sample.track_id`. Samples must
+	/// be added in decode order. The duration of a sample is the difference to
+	/// the dts of the next sample, so only the duration of the last sample of
+	/// a track is used. The offset of the sample is ignored.
+	pub fn add_sample(&mut self, sample: Sample) -> io::Result<()> {
+		let track = self
+			.tracks
+			.iter_mut()
+			.find(|t| t.info.track_id == sample.track_id)
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "sample references an unknown track"))?;
+
+		if sample.sample_description_index == 0 || sample.sample_description_index as usize > track.info.sample_entries.len()
+		{
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"sample references an unknown sample description",
+			));
+		}
+
+		if let Some(last) = track.samples.last() {
+			if sample.dts < last.dts || sample.dts - last.dts > u32::MAX as u64 {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidInput,
+					"sample dts is not in decode order",
+				));
+			}
+		}
+
+		if sample.data.len() != sample.size as usize {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"sample size does not match its data",
+			));
+		}
+
+		track.samples.push(sample);
+
+		Ok(())
+	}
+
+	/// Write the file.
+	pub fn finish<T: io::Write>(mut self, writer: &mut T) -> io::Result<()> {
+		// The duration of every sample but the last is the time until the next sample.
+		for track in &mut self.tracks {
+			for idx in 1..track.samples.len() {
+				track.samples[idx - 1].duration = (track.samples[idx].dts - track.samples[idx - 1].dts) as u32;
+			}
+		}
+
+		let chunks = self.chunks();
+
+		let mut compatible_brands = vec![FourCC::Isom, FourCC::Iso2, FourCC::Mp41];
+		for entry in self.tracks.iter().flat_map(|t| &t.info.sample_entries) {
+			let brand = match entry {
+				DynBox::Avc1(_) => FourCC::Avc1,
+				DynBox::Hev1(_) => FourCC::Hev1,
+				DynBox::Hvc1(_) => FourCC::Hvc1,
+				DynBox::Av01(_) => FourCC::Av01,
+				_ => continue,
+			};
+
+			if !compatible_brands.contains(&brand) {
+				compatible_brands.push(brand);
+			}
+		}
+
+		let ftyp = Ftyp::new(FourCC::Isom, 512, compatible_brands);
+
+		let mdat = Mdat::new(
+			chunks
+				.iter()
+				.flat_map(|chunk| &self.tracks[chunk.track].samples[chunk.first_sample..][..chunk.sample_count])
+				.map(|sample| sample.data.clone())
+				.collect(),
+		);
+
+		// The size of the moov box does not depend on the chunk offsets, only on whether
+		// we need 64 bit offsets. So we build it once to find out where the sample data
+		// starts, and then again with the real offsets.
+		let mdat_header_size = mdat.size() - mdat.primitive_size();
+		let data_offset = |moov: &Moov| ftyp.size() + moov.size() + mdat_header_size;
+
+		let mut use_co64 = false;
+		let mut offset = data_offset(&self.moov(&chunks, 0, use_co64));
+		if offset + mdat.primitive_size() > u32::MAX as u64 {
+			use_co64 = true;
+			offset = data_offset(&self.moov(&chunks, 0, use_co64));
+		}
+
+		ftyp.mux(writer)?;
+		self.moov(&chunks, offset, use_co64).mux(writer)?;
+		mdat.mux(writer)?;
+
+		Ok(())
+	}
+
+	/// Split the samples of every track into chunks and order all chunks by
+	/// time.
+	fn chunks(&self) -> Vec<Chunk> {
+		let mut chunks = Vec::new();
+
+		for (track_idx, track) in self.tracks.iter().enumerate() {
+			let max_duration = CHUNK_DURATION * track.info.timescale as u64;
+
+			let mut current: Option<Chunk> = None;
+			for (idx, sample) in track.samples.iter().enumerate() {
+				if let Some(chunk) = &mut current {
+					if chunk.sample_description_index == sample.sample_description_index
+						&& sample.dts - chunk.start_dts < max_duration
+					{
+						chunk.sample_count += 1;
+						continue;
+					}
+
+					chunks.push(current.take().expect("we just checked that there is a chunk"));
+				}
+
+				current = Some(Chunk {
+					track: track_idx,
+					first_sample: idx,
+					sample_count: 1,
+					sample_description_index: sample.sample_description_index,
+					start_dts: sample.dts,
+					timescale: track.info.timescale,
+				});
+			}
+
+			chunks.extend(current);
+		}
+
+		// Compare the start times in seconds without losing precision. The sort is stable
+		// so chunks which start at the same time stay in track order.
+		chunks.sort_by(|a, b| {
+			let a_time = a.start_dts as u128 * b.timescale as u128;
+			let b_time = b.start_dts as u128 * a.timescale as u128;
+			a_time.cmp(&b_time)
+		});
+
+		chunks
+	}
+
+	/// Build the moov box, `data_offset` is the offset of the first chunk in
+	/// the file.
+	fn moov(&self, chunks: &[Chunk], data_offset: u64, use_co64: bool) -> Moov {
+		// The offset of every chunk, in the order of each track.
+		let mut chunk_offsets = vec![Vec::new(); self.tracks.len()];
+		let mut offset = data_offset;
+		for chunk in chunks {
+			chunk_offsets[chunk.track].push((offset, chunk));
+
+			let samples = &self.tracks[chunk.track].samples[chunk.first_sample..][..chunk.sample_count];
+			offset += samples.iter().map(|s| s.size as u64).sum::<u64>();
+		}
+
+		let mut traks = Vec::with_capacity(self.tracks.len());
+		let mut movie_duration = 0;
+
+		for (track, chunks) in self.tracks.iter().zip(chunk_offsets) {
+			let info = &track.info;
+			let duration = track.samples.iter().map(|s| s.duration as u64).sum::<u64>();
+
+			let mut stbl = Stbl::new(
+				Stsd::new(info.sample_entries.clone()),
+				Stts::new(stts_entries(&track.samples)),
+				Stsc::new(stsc_entries(&chunks)),
+				Stco::new(vec![]),
+				Some(Stsz::new(0, track.samples.iter().map(|s| s.size).collect())),
+			);
+
+			if use_co64 {
+				stbl.stco = None;
+				stbl.co64 = Some(Co64::new(chunks.iter().map(|(offset, _)| *offset).collect()));
+			} else {
+				stbl.stco = Some(Stco::new(chunks.iter().map(|(offset, _)| *offset as u32).collect()));
+			}
+
+			if track.samples.iter().any(|s| s.composition_offset != 0) {
+				stbl.ctts = Some(Ctts::new(ctts_entries(&track.samples)));
+			}
+
+			// Without a stss box every sample is a sync sample.
+			if track.samples.iter().any(|s| !s.is_sync) {
+				stbl.stss = Some(Stss::new(
+					track
+						.samples
+						.iter()
+						.enumerate()
+						.filter(|(_, s)| s.is_sync)
+						.map(|(idx, _)| idx as u32 + 1)
+						.collect(),
+				));
+			}
+
+			let (edts, empty_duration) = edit_list(track, duration);
+			let track_duration = empty_duration + duration * MOVIE_TIMESCALE as u64 / info.timescale as u64;
+			movie_duration = movie_duration.max(track_duration);
+
+			let (width_height, vmhd, smhd, handler_name) = match info.handler_type {
+				HandlerType::Vide => (Some((info.width, info.height)), Some(Vmhd::new()), None, "VideoHandler"),
+				HandlerType::Soun => (None, None, Some(Smhd::new()), "SoundHandler"),
+				_ => (None, None, None, "Handler"),
+			};
+
+			traks.push(Trak::new(
+				Tkhd::new(0, 0, info.track_id, track_duration, width_height),
+				edts,
+				Mdia::new(
+					Mdhd::new(0, 0, info.timescale, duration),
+					Hdlr::new(info.handler_type.clone(), handler_name.to_string()),
+					Minf::new(stbl, vmhd, smhd),
+				),
+			));
+		}
+
+		let next_track_id = self.tracks.iter().map(|t| t.info.track_id).max().unwrap_or(0) + 1;
+
+		Moov::new(Mvhd::new(0, 0, MOVIE_TIMESCALE, movie_duration, next_track_id), traks, None)
+	}
+}
+
+fn stts_entries(samples: &[Sample]) -> Vec<SttsEntry> {
+	let mut entries: Vec<SttsEntry> = Vec::new();
+
+	for sample in samples {
+		match entries.last_mut() {
+			Some(entry) if entry.sample_delta == sample.duration => entry.sample_count += 1,
+			_ => entries.push(SttsEntry {
+				sample_count: 1,
+				sample_delta: sample.duration,
+			}),
+		}
+	}
+
+	entries
+}
+
+fn ctts_entries(samples: &[Sample]) -> Vec<CttsEntry> {
+	let mut entries: Vec<CttsEntry> = Vec::new();
+
+	for sample in samples {
+		match entries.last_mut() {
+			Some(entry) if entry.sample_offset == sample.composition_offset => entry.sample_count += 1,
+			_ => entries.push(CttsEntry {
+				sample_count: 1,
+				sample_offset: sample.composition_offset,
+			}),
+		}
+	}
+
+	entries
+}
+
+fn stsc_entries(chunks: &[(u64, &Chunk)]) -> Vec<StscEntry> {
+	let mut entries: Vec<StscEntry> = Vec::new();
+
+	for (idx, (_, chunk)) in chunks.iter().enumerate() {
+		// An entry covers every chunk until the next entry, so we only need a new one if
+		// the chunk is different from the one before it.
+		if entries.last().is_some_and(|entry| {
+			entry.samples_per_chunk == chunk.sample_count as u32
+				&& entry.sample_description_index == chunk.sample_description_index
+		}) {
+			continue;
+		}
+
+		entries.push(StscEntry {
+			first_chunk: idx as u32 + 1,
+			samples_per_chunk: chunk.sample_count as u32,
+			sample_description_index: chunk.sample_description_index,
+		});
+	}
+
+	entries
+}
+
+/// Build an edit list if the track does not start at 0, returns the edit
+/// list and the duration of the empty edit in the movie timescale.
+fn edit_list(track: &WriterTrack, duration: u64) -> (Option<Edts>, u64) {
+	let Some(first) = track.samples.first() else {
+		return (None, 0);
+	};
+
+	// If the first sample has a composition offset we skip the start of the media, so
+	// the first sample is presented when the track starts.
+	let media_time = first.composition_offset.max(0);
+	if first.dts == 0 && media_time == 0 {
+		return (None, 0);
+	}
+
+	let timescale = track.info.timescale as u64;
+	let empty_duration = first.dts * MOVIE_TIMESCALE as u64 / timescale;
+
+	let mut entries = Vec::new();
+	if empty_duration > 0 {
+		entries.push(ElstEntry {
+			segment_duration: empty_duration,
+			media_time: -1,
+			media_rate_integer: 1,
+			media_rate_fraction: 0,
+		});
+	}
+
+	entries.push(ElstEntry {
+		segment_duration: duration.saturating_sub(media_time as u64) * MOVIE_TIMESCALE as u64 / timescale,
+		media_time,
+		media_rate_integer: 1,
+		media_rate_fraction: 0,
+	});
+
+	(Some(Edts::new(Some(Elst::new(entries)))), empty_duration)
+}
@@ -0,0 +1,31 @@
+/// The CRC32 used by MPEG-2 sections.
+/// ISO/IEC 13818-1 - Annex A
+pub fn crc32(data: &[u8]) -> u32 {
+	data.iter().fold(0xFFFF_FFFF, |crc, &byte| {
+		(crc << 8) ^ CRC_TABLE[((crc >> 24) as u8 ^ byte) as usize]
+	})
+}
+
+const CRC_TABLE: [u32; 256] = {
+	let mut table = [0; 256];
+
+	let mut i = 0;
+	while i < 256 {
+		let mut crc = (i as u32) << 24;
+
+		let mut bit = 0;
+		while bit < 8 {
+			crc = if crc & 0x8000_0000 != 0 {
+				(crc << 1) ^ 0x04C1_1DB7
+			} else {
+				crc << 1
+			};
+			bit += 1;
+		}
+
+		table[i] = crc;
+		i += 1;
+	}
+
+	table
+};
@@ -0,0 +1,79 @@
+use bytes::Bytes;
+
+/// The size of a transport stream packet.
+pub const TS_PACKET_SIZE: usize = 188;
+
+/// The first byte of every transport stream packet.
+pub const SYNC_BYTE: u8 = 0x47;
+
+/// The PID of the Program Association Table.
+pub const PAT_PID: u16 = 0x0000;
+
+/// The PID of null packets, which are used for padding.
+pub const NULL_PID: u16 = 0x1FFF;
+
+/// The clock of all timestamps (PTS, DTS and the PCR base) in a transport
+/// stream.
+pub const TS_TIMESCALE: u32 = 90000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The type of an elementary stream in the Program Map Table.
+/// ISO/IEC 13818-1 - Table 2-34
+pub enum StreamType {
+	/// ITU-T H.264 in Annex B format
+	H264,
+	/// ITU-T H.265 in Annex B format
+	H265,
+	/// AAC with ADTS headers
+	Aac,
+	/// Opus, carried as private data with a registration descriptor. Each
+	/// PES contains access units with an Opus control header.
+	Opus {
+		channels: u8,
+	},
+	Unknown(u8),
+}
+
+impl StreamType {
+	/// The value of the `stream_type` field.
+	pub fn to_u8(&self) -> u8 {
+		match self {
+			Self::H264 => 0x1B,
+			Self::H265 => 0x24,
+			Self::Aac => 0x0F,
+			Self::Opus { .. } => 0x06,
+			Self::Unknown(stream_type) => *stream_type,
+		}
+	}
+
+	pub fn is_video(&self) -> bool {
+		matches!(self, Self::H264 | Self::H265)
+	}
+
+	pub fn is_audio(&self) -> bool {
+		matches!(self, Self::Aac | Self::Opus { .. })
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An elementary stream of the program.
+pub struct TsStream {
+	pub pid: u16,
+	pub stream_type: StreamType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A PES packet of an elementary stream.
+/// ISO/IEC 13818-1 - 2.4.3.6
+pub struct Pes {
+	pub pid: u16,
+	pub stream_id: u8,
+	/// The presentation timestamp in 90kHz.
+	pub pts: Option<u64>,
+	/// The decode timestamp in 90kHz, only present if it differs from the
+	/// PTS.
+	pub dts: Option<u64>,
+	/// If the packet starts with a random access point, usually a keyframe.
+	pub random_access: bool,
+	pub data: Bytes,
+}
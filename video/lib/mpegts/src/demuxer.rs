@@ -0,0 +1,506 @@
+use std::collections::VecDeque;
+
+use byteorder::{BigEndian, ByteOrder};
+use bytes::{Buf, Bytes, BytesMut};
+
+use crate::{crc32, Pes, StreamType, TsDemuxerError, TsStream, NULL_PID, PAT_PID, SYNC_BYTE, TS_PACKET_SIZE};
+
+#[derive(Debug)]
+/// A PES packet which is being reassembled from transport stream packets.
+struct PesBuffer {
+	pid: u16,
+	random_access: bool,
+	data: BytesMut,
+}
+
+#[derive(Debug)]
+/// The state of a PID we are interested in.
+struct PidState {
+	pid: u16,
+	continuity_counter: Option<u8>,
+	/// The partial section of a PSI PID
+	section: Option<BytesMut>,
+	/// The partial PES of a stream PID
+	pes: Option<PesBuffer>,
+}
+
+/// An incremental MPEG-2 transport stream demuxer.
+/// Data can be fed to it in chunks of any size. The PAT and PMT of the first
+/// program are read to find the elementary streams, and complete PES packets
+/// of those streams are returned. If a packet is lost, which is detected by
+/// the continuity counter, the PES it belonged to is dropped.
+#[derive(Debug)]
+pub struct TsDemuxer {
+	buffer: BytesMut,
+	pmt_pid: Option<u16>,
+	streams: Vec<TsStream>,
+	pids: Vec<PidState>,
+	ready: VecDeque<Pes>,
+	skipped_bytes: u64,
+	discontinuities: u64,
+}
+
+impl Default for TsDemuxer {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl TsDemuxer {
+	pub fn new() -> Self {
+		Self {
+			buffer: BytesMut::new(),
+			pmt_pid: None,
+			streams: Vec::new(),
+			pids: vec![PidState::new(PAT_PID)],
+			ready: VecDeque::new(),
+			skipped_bytes: 0,
+			discontinuities: 0,
+		}
+	}
+
+	/// Add data to the demuxer.
+	pub fn extend_data(&mut self, data: &[u8]) {
+		self.buffer.extend_from_slice(data);
+	}
+
+	/// The elementary streams from the PMT, empty until the PMT has been read.
+	pub fn streams(&self) -> &[TsStream] {
+		&self.streams
+	}
+
+	/// The number of bytes which were skipped to find the next sync byte.
+	pub fn skipped_bytes(&self) -> u64 {
+		self.skipped_bytes
+	}
+
+	/// The number of times a packet was lost.
+	pub fn discontinuities(&self) -> u64 {
+		self.discontinuities
+	}
+
+	/// Read the next PES packet.
+	/// - will return Ok(None) if we need more data.
+	/// - will return Ok(Some(Pes)) if we have a full PES packet.
+	/// - will return Err if a section or PES is invalid. The invalid data is
+	///   dropped so we can keep reading after it.
+	pub fn read_pes(&mut self) -> Result<Option<Pes>, TsDemuxerError> {
+		loop {
+			if let Some(pes) = self.ready.pop_front() {
+				return Ok(Some(pes));
+			}
+
+			if !self.sync() {
+				return Ok(None);
+			}
+
+			let packet = self.buffer.split_to(TS_PACKET_SIZE).freeze();
+			self.handle_packet(packet)?;
+		}
+	}
+
+	/// Finish all PES packets which are still being reassembled, since a
+	/// PES without a length only ends when the next one starts. This should be
+	/// called at the end of the stream, the packets are then returned by
+	/// `read_pes`.
+	pub fn flush(&mut self) -> Result<(), TsDemuxerError> {
+		let mut result = Ok(());
+
+		for idx in 0..self.pids.len() {
+			if let Some(pes) = self.pids[idx].pes.take() {
+				if let Err(err) = self.finish_pes(pes) {
+					result = Err(err);
+				}
+			}
+		}
+
+		result
+	}
+
+	/// Make sure the buffer starts with a packet, returns false if we need
+	/// more data.
+	fn sync(&mut self) -> bool {
+		// A sync byte is only trusted if the next packet also starts with one, or if we
+		// do not have the next packet yet.
+		let is_sync = |buffer: &[u8], offset: usize| {
+			buffer[offset] == SYNC_BYTE && matches!(buffer.get(offset + TS_PACKET_SIZE), None | Some(&SYNC_BYTE))
+		};
+
+		if self.buffer.is_empty() {
+			return false;
+		}
+
+		if !is_sync(&self.buffer, 0) {
+			let offset = (1..self.buffer.len())
+				.find(|offset| is_sync(&self.buffer, *offset))
+				.unwrap_or(self.buffer.len());
+
+			self.buffer.advance(offset);
+			self.skipped_bytes += offset as u64;
+		}
+
+		self.buffer.len() >= TS_PACKET_SIZE
+	}
+
+	fn handle_packet(&mut self, mut packet: Bytes) -> Result<(), TsDemuxerError> {
+		let transport_error = packet[1] & 0x80 != 0;
+		let payload_unit_start = packet[1] & 0x40 != 0;
+		let pid = BigEndian::read_u16(&packet[1..3]) & 0x1FFF;
+		let adaptation_field_control = (packet[3] >> 4) & 0b11;
+		let continuity_counter = packet[3] & 0x0F;
+
+		if transport_error || pid == NULL_PID {
+			return Ok(());
+		}
+
+		let Some(idx) = self.pids.iter().position(|state| state.pid == pid) else {
+			return Ok(());
+		};
+
+		packet.advance(4);
+
+		let mut random_access = false;
+		let mut discontinuity = false;
+
+		if adaptation_field_control & 0b10 != 0 {
+			let length = packet[0] as usize;
+			if length > packet.len() - 1 {
+				// The packet is corrupt, so we treat it like a lost packet.
+				self.lost_packet(idx);
+				return Ok(());
+			}
+
+			if length > 0 {
+				discontinuity = packet[1] & 0x80 != 0;
+				random_access = packet[1] & 0x40 != 0;
+			}
+
+			packet.advance(1 + length);
+		}
+
+		// Packets without a payload do not increment the continuity counter.
+		if adaptation_field_control & 0b01 == 0 {
+			return Ok(());
+		}
+
+		let state = &mut self.pids[idx];
+		match state.continuity_counter {
+			// A duplicate packet, which we ignore.
+			Some(last) if last == continuity_counter && !discontinuity => return Ok(()),
+			Some(last) if (last + 1) % 16 != continuity_counter && !discontinuity => {
+				state.continuity_counter = Some(continuity_counter);
+				self.lost_packet(idx);
+
+				// We cannot use the rest of the PES or section, but this packet might start a
+				// new one.
+				if !payload_unit_start {
+					return Ok(());
+				}
+			}
+			_ => state.continuity_counter = Some(continuity_counter),
+		}
+
+		if pid == PAT_PID || Some(pid) == self.pmt_pid {
+			self.handle_section_payload(idx, payload_unit_start, packet)
+		} else {
+			self.handle_pes_payload(idx, payload_unit_start, random_access, packet)
+		}
+	}
+
+	/// Drop whatever we were reassembling on a PID after a lost packet.
+	fn lost_packet(&mut self, idx: usize) {
+		self.discontinuities += 1;
+		self.pids[idx].section = None;
+		self.pids[idx].pes = None;
+	}
+
+	fn handle_section_payload(
+		&mut self,
+		idx: usize,
+		payload_unit_start: bool,
+		payload: Bytes,
+	) -> Result<(), TsDemuxerError> {
+		let state = &mut self.pids[idx];
+		let pid = state.pid;
+
+		if payload_unit_start {
+			// The pointer field tells us where the new section starts.
+			let pointer = payload.first().copied().unwrap_or(0) as usize;
+			if 1 + pointer > payload.len() {
+				return Err(TsDemuxerError::InvalidSection { pid });
+			}
+
+			state.section = Some(BytesMut::from(&payload[1 + pointer..]));
+		} else if let Some(section) = &mut state.section {
+			section.extend_from_slice(&payload);
+		}
+
+		let Some(section) = &state.section else {
+			return Ok(());
+		};
+
+		if section.len() < 3 {
+			return Ok(());
+		}
+
+		let section_length = (BigEndian::read_u16(&section[1..3]) & 0x0FFF) as usize;
+		if section.len() < 3 + section_length {
+			return Ok(());
+		}
+
+		let section = state
+			.section
+			.take()
+			.expect("we just checked that there is a section")
+			.freeze();
+		let section = section.slice(..3 + section_length);
+
+		// The smallest section is a header with a CRC.
+		if section_length < 9 {
+			return Err(TsDemuxerError::InvalidSection { pid });
+		}
+
+		if crc32(&section) != 0 {
+			return Err(TsDemuxerError::InvalidCrc { pid });
+		}
+
+		// Skip the header, and drop the CRC.
+		let table_id = section[0];
+		let data = section.slice(8..section.len() - 4);
+
+		match table_id {
+			0x00 if pid == PAT_PID => self.handle_pat(data),
+			0x02 => self.handle_pmt(pid, data),
+			_ => Ok(()),
+		}
+	}
+
+	fn handle_pat(&mut self, data: Bytes) -> Result<(), TsDemuxerError> {
+		// Use the first program which is not the network information table.
+		let pmt_pid = data
+			.chunks_exact(4)
+			.find(|program| BigEndian::read_u16(&program[0..2]) != 0)
+			.map(|program| BigEndian::read_u16(&program[2..4]) & 0x1FFF);
+
+		if pmt_pid != self.pmt_pid {
+			if let Some(old) = self.pmt_pid {
+				self.pids.retain(|state| state.pid != old);
+			}
+
+			if let Some(pid) = pmt_pid {
+				self.pids.push(PidState::new(pid));
+			}
+
+			self.pmt_pid = pmt_pid;
+		}
+
+		Ok(())
+	}
+
+	fn handle_pmt(&mut self, pid: u16, mut data: Bytes) -> Result<(), TsDemuxerError> {
+		if data.len() < 4 {
+			return Err(TsDemuxerError::InvalidSection { pid });
+		}
+
+		let program_info_length = (BigEndian::read_u16(&data[2..4]) & 0x0FFF) as usize;
+		if data.len() < 4 + program_info_length {
+			return Err(TsDemuxerError::InvalidSection { pid });
+		}
+
+		data.advance(4 + program_info_length);
+
+		let mut streams = Vec::new();
+		while data.len() >= 5 {
+			let stream_type = data[0];
+			let stream_pid = BigEndian::read_u16(&data[1..3]) & 0x1FFF;
+			let es_info_length = (BigEndian::read_u16(&data[3..5]) & 0x0FFF) as usize;
+
+			if data.len() < 5 + es_info_length {
+				return Err(TsDemuxerError::InvalidSection { pid });
+			}
+
+			let descriptors = data.slice(5..5 + es_info_length);
+			data.advance(5 + es_info_length);
+
+			streams.push(TsStream {
+				pid: stream_pid,
+				stream_type: parse_stream_type(stream_type, &descriptors),
+			});
+		}
+
+		if streams == self.streams {
+			return Ok(());
+		}
+
+		// Keep the state of the streams which are still there.
+		self.pids.retain(|state| {
+			state.pid == PAT_PID || Some(state.pid) == self.pmt_pid || streams.iter().any(|s| s.pid == state.pid)
+		});
+
+		for stream in &streams {
+			if !self.pids.iter().any(|state| state.pid == stream.pid) {
+				self.pids.push(PidState::new(stream.pid));
+			}
+		}
+
+		self.streams = streams;
+
+		Ok(())
+	}
+
+	fn handle_pes_payload(
+		&mut self,
+		idx: usize,
+		payload_unit_start: bool,
+		random_access: bool,
+		payload: Bytes,
+	) -> Result<(), TsDemuxerError> {
+		let state = &mut self.pids[idx];
+		let pid = state.pid;
+
+		let mut result = Ok(());
+
+		if payload_unit_start {
+			if let Some(pes) = state.pes.take() {
+				result = self.finish_pes(pes);
+			}
+
+			self.pids[idx].pes = Some(PesBuffer {
+				pid,
+				random_access,
+				data: BytesMut::from(&payload[..]),
+			});
+		} else if let Some(pes) = &mut state.pes {
+			pes.data.extend_from_slice(&payload);
+		}
+
+		// If the PES has a length we do not have to wait for the next one to know it is
+		// complete.
+		let state = &mut self.pids[idx];
+		if let Some(pes) = &state.pes {
+			if pes.data.len() >= 6 {
+				let length = BigEndian::read_u16(&pes.data[4..6]) as usize;
+				if length != 0 && pes.data.len() >= 6 + length {
+					let pes = state.pes.take().expect("we just checked that there is a pes");
+					result = result.and(self.finish_pes(pes));
+				}
+			}
+		}
+
+		result
+	}
+
+	/// Parse a reassembled PES and queue it.
+	fn finish_pes(&mut self, pes: PesBuffer) -> Result<(), TsDemuxerError> {
+		let pid = pes.pid;
+		let data = pes.data.freeze();
+
+		if data.len() < 6 || data[0..3] != [0x00, 0x00, 0x01] {
+			return Err(TsDemuxerError::InvalidPesHeader { pid });
+		}
+
+		let stream_id = data[3];
+		let length = BigEndian::read_u16(&data[4..6]) as usize;
+
+		let end = if length == 0 {
+			data.len()
+		} else {
+			(6 + length).min(data.len())
+		};
+		let mut data = data.slice(6..end);
+
+		let mut pts = None;
+		let mut dts = None;
+
+		// These streams do not have the optional header.
+		// ISO/IEC 13818-1 - Table 2-22
+		if !matches!(stream_id, 0xBC | 0xBE | 0xBF | 0xF0 | 0xF1 | 0xF2 | 0xF8 | 0xFF) {
+			if data.len() < 3 {
+				return Err(TsDemuxerError::InvalidPesHeader { pid });
+			}
+
+			let pts_dts_flags = data[1] >> 6;
+			let header_data_length = data[2] as usize;
+			if data.len() < 3 + header_data_length {
+				return Err(TsDemuxerError::InvalidPesHeader { pid });
+			}
+
+			let header = data.slice(3..3 + header_data_length);
+			if pts_dts_flags & 0b10 != 0 && header.len() >= 5 {
+				pts = Some(read_timestamp(&header[0..5]));
+			}
+			if pts_dts_flags == 0b11 && header.len() >= 10 {
+				dts = Some(read_timestamp(&header[5..10]));
+			}
+
+			data.advance(3 + header_data_length);
+		}
+
+		self.ready.push_back(Pes {
+			pid,
+			stream_id,
+			pts,
+			dts,
+			random_access: pes.random_access,
+			data,
+		});
+
+		Ok(())
+	}
+}
+
+impl PidState {
+	fn new(pid: u16) -> Self {
+		Self {
+			pid,
+			continuity_counter: None,
+			section: None,
+			pes: None,
+		}
+	}
+}
+
+fn parse_stream_type(stream_type: u8, mut descriptors: &[u8]) -> StreamType {
+	match stream_type {
+		0x1B => return StreamType::H264,
+		0x24 => return StreamType::H265,
+		0x0F => return StreamType::Aac,
+		_ => {}
+	}
+
+	let mut is_opus = false;
+	let mut channels = 2;
+
+	while descriptors.len() >= 2 {
+		let tag = descriptors[0];
+		let length = descriptors[1] as usize;
+		let Some(descriptor) = descriptors.get(2..2 + length) else {
+			break;
+		};
+
+		match tag {
+			// Registration descriptor
+			0x05 if descriptor.starts_with(b"Opus") => is_opus = true,
+			// Extension descriptor with the Opus channel configuration
+			0x7F if descriptor.len() >= 2 && descriptor[0] == 0x80 => channels = descriptor[1],
+			_ => {}
+		}
+
+		descriptors = &descriptors[2 + length..];
+	}
+
+	if stream_type == 0x06 && is_opus {
+		StreamType::Opus { channels }
+	} else {
+		StreamType::Unknown(stream_type)
+	}
+}
+
+/// Decode a 33 bit timestamp with marker bits.
+fn read_timestamp(data: &[u8]) -> u64 {
+	(((data[0] >> 1) & 0x07) as u64) << 30
+		| (data[1] as u64) << 22
+		| ((data[2] >> 1) as u64) << 15
+		| (data[3] as u64) << 7
+		| (data[4] >> 1) as u64
+}
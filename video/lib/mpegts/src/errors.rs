@@ -0,0 +1,52 @@
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum TsDemuxerError {
+	/// The CRC of a PSI section does not match
+	InvalidCrc { pid: u16 },
+	/// A PSI section is malformed
+	InvalidSection { pid: u16 },
+	/// A PES packet does not start with a PES header
+	InvalidPesHeader { pid: u16 },
+}
+
+impl fmt::Display for TsDemuxerError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::InvalidCrc { pid } => write!(f, "invalid crc in section on pid {}", pid),
+			Self::InvalidSection { pid } => write!(f, "invalid section on pid {}", pid),
+			Self::InvalidPesHeader { pid } => write!(f, "invalid pes header on pid {}", pid),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum TsMuxerError {
+	IO(io::Error),
+	/// There is no stream with this PID
+	UnknownPid(u16),
+	/// The PES packet of an audio stream does not fit in 16 bits
+	PesTooLarge(usize),
+	/// Streams can not be added after the first packet was written
+	StreamsLocked,
+	/// The PMT would not fit in a single packet
+	TooManyStreams,
+}
+
+impl From<io::Error> for TsMuxerError {
+	fn from(error: io::Error) -> Self {
+		Self::IO(error)
+	}
+}
+
+impl fmt::Display for TsMuxerError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::IO(error) => write!(f, "io error: {}", error),
+			Self::UnknownPid(pid) => write!(f, "unknown pid: {}", pid),
+			Self::PesTooLarge(size) => write!(f, "pes too large: {}", size),
+			Self::StreamsLocked => write!(f, "streams can not be added after writing"),
+			Self::TooManyStreams => write!(f, "too many streams"),
+		}
+	}
+}
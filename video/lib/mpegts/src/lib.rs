@@ -0,0 +1,14 @@
+mod crc;
+mod define;
+mod demuxer;
+mod errors;
+mod muxer;
+
+pub use crc::crc32;
+pub use define::*;
+pub use demuxer::TsDemuxer;
+pub use errors::{TsDemuxerError, TsMuxerError};
+pub use muxer::TsMuxer;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,367 @@
+use std::io;
+
+use byteorder::{BigEndian, WriteBytesExt};
+
+use crate::{crc32, StreamType, TsMuxerError, TsStream, PAT_PID, SYNC_BYTE, TS_PACKET_SIZE};
+
+/// The PID of the Program Map Table.
+const PMT_PID: u16 = 0x1000;
+
+/// The PID of the first elementary stream, the following streams get the
+/// PIDs after it.
+const FIRST_STREAM_PID: u16 = 0x0100;
+
+/// The maximum number of streams, so the PMT always fits in a single packet.
+const MAX_STREAMS: usize = 8;
+
+/// The program number of the only program we write.
+const PROGRAM_NUMBER: u16 = 1;
+
+/// The maximum time between two PCRs in 90kHz, the spec allows up to 100ms
+/// so we use 40ms to be safe.
+const PCR_INTERVAL: u64 = 3600;
+
+/// The size of the packet header.
+const HEADER_SIZE: usize = 4;
+
+/// The payload size of a packet without an adaptation field.
+const PAYLOAD_SIZE: usize = TS_PACKET_SIZE - HEADER_SIZE;
+
+#[derive(Debug, Clone)]
+struct MuxStream {
+	stream: TsStream,
+	stream_id: u8,
+	continuity_counter: u8,
+}
+
+/// A MPEG-2 transport stream muxer for a single program.
+/// Streams are added before the first PES is written. The PAT and PMT are
+/// written before the first PES and before every keyframe of the video
+/// stream which carries the PCR, so every segment which starts with a
+/// keyframe can be decoded on its own. Audio only streams should call
+/// `write_tables` at the start of every segment.
+#[derive(Debug, Clone)]
+pub struct TsMuxer {
+	streams: Vec<MuxStream>,
+	pat_continuity_counter: u8,
+	pmt_continuity_counter: u8,
+	/// If we have written the tables at least once
+	started: bool,
+	last_pcr: Option<u64>,
+}
+
+impl Default for TsMuxer {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl TsMuxer {
+	pub fn new() -> Self {
+		Self {
+			streams: Vec::new(),
+			pat_continuity_counter: 0,
+			pmt_continuity_counter: 0,
+			started: false,
+			last_pcr: None,
+		}
+	}
+
+	/// Add an elementary stream and return its PID.
+	pub fn add_stream(&mut self, stream_type: StreamType) -> Result<u16, TsMuxerError> {
+		if self.started {
+			return Err(TsMuxerError::StreamsLocked);
+		}
+
+		if self.streams.len() >= MAX_STREAMS {
+			return Err(TsMuxerError::TooManyStreams);
+		}
+
+		let pid = FIRST_STREAM_PID + self.streams.len() as u16;
+
+		// Video streams use 0xE0..0xEF and audio streams 0xC0..0xDF, Opus is private
+		// data.
+		let stream_id = match stream_type {
+			StreamType::Opus { .. } => 0xBD,
+			stream_type if stream_type.is_video() => {
+				0xE0 + self.streams.iter().filter(|s| s.stream.stream_type.is_video()).count() as u8 % 16
+			}
+			_ => 0xC0 + self.streams.iter().filter(|s| !s.stream.stream_type.is_video()).count() as u8 % 32,
+		};
+
+		self.streams.push(MuxStream {
+			stream: TsStream { pid, stream_type },
+			stream_id,
+			continuity_counter: 0,
+		});
+
+		Ok(pid)
+	}
+
+	/// The streams of the program.
+	pub fn streams(&self) -> impl Iterator<Item = &TsStream> {
+		self.streams.iter().map(|s| &s.stream)
+	}
+
+	/// The PID of the stream which carries the PCR, which is the first video
+	/// stream or the first stream if there is no video.
+	pub fn pcr_pid(&self) -> Option<u16> {
+		self.streams
+			.iter()
+			.find(|s| s.stream.stream_type.is_video())
+			.or(self.streams.first())
+			.map(|s| s.stream.pid)
+	}
+
+	/// Write the PAT and PMT.
+	pub fn write_tables<W: io::Write>(&mut self, writer: &mut W) -> Result<(), TsMuxerError> {
+		self.started = true;
+
+		let pat = self.pat();
+		write_section(writer, PAT_PID, &mut self.pat_continuity_counter, &pat)?;
+
+		let pmt = self.pmt();
+		write_section(writer, PMT_PID, &mut self.pmt_continuity_counter, &pmt)?;
+
+		Ok(())
+	}
+
+	/// Write a PES packet for the stream with `pid`. The timestamps are in
+	/// 90kHz and `dts` is only needed if it differs from `pts`.
+	pub fn write_pes<W: io::Write>(
+		&mut self,
+		writer: &mut W,
+		pid: u16,
+		pts: u64,
+		dts: Option<u64>,
+		random_access: bool,
+		data: &[u8],
+	) -> Result<(), TsMuxerError> {
+		let idx = self
+			.streams
+			.iter()
+			.position(|s| s.stream.pid == pid)
+			.ok_or(TsMuxerError::UnknownPid(pid))?;
+
+		let is_pcr_stream = self.pcr_pid() == Some(pid);
+		let dts = dts.filter(|dts| *dts != pts);
+		let decode_time = dts.unwrap_or(pts);
+
+		let stream_type = self.streams[idx].stream.stream_type;
+		if !self.started || (is_pcr_stream && random_access && stream_type.is_video()) {
+			self.write_tables(writer)?;
+		}
+
+		let pcr_due = match self.last_pcr {
+			Some(last) => decode_time.saturating_sub(last) >= PCR_INTERVAL,
+			None => true,
+		};
+
+		let pcr = if is_pcr_stream && (random_access || pcr_due) {
+			self.last_pcr = Some(decode_time);
+			Some(decode_time)
+		} else {
+			None
+		};
+
+		let stream = &mut self.streams[idx];
+		let header = pes_header(stream, pts, dts, data.len())?;
+
+		let packet = [header.as_slice(), data].concat();
+		let mut written = 0;
+
+		while written < packet.len() {
+			let first = written == 0;
+
+			// The first packet carries the PCR and the random access flag.
+			let (pcr, random_access) = if first { (pcr, random_access) } else { (None, false) };
+			let min_adaptation_size = match (pcr, random_access) {
+				(Some(_), _) => 8,
+				(None, true) => 2,
+				(None, false) => 0,
+			};
+
+			let payload_size = (packet.len() - written).min(PAYLOAD_SIZE - min_adaptation_size);
+			let adaptation = adaptation_field(pcr, random_access, PAYLOAD_SIZE - payload_size);
+
+			write_header(writer, pid, first, !adaptation.is_empty(), &mut stream.continuity_counter)?;
+			writer.write_all(&adaptation)?;
+
+			writer.write_all(&packet[written..written + payload_size])?;
+
+			written += payload_size;
+		}
+
+		Ok(())
+	}
+
+	fn pat(&self) -> Vec<u8> {
+		let mut data = Vec::new();
+
+		// program_number and reserved + program_map_PID
+		data.extend_from_slice(&PROGRAM_NUMBER.to_be_bytes());
+		data.extend_from_slice(&(0xE000 | PMT_PID).to_be_bytes());
+
+		section(0x00, 1, &data)
+	}
+
+	fn pmt(&self) -> Vec<u8> {
+		let mut data = Vec::new();
+
+		// reserved + PCR_PID, reserved + program_info_length (no descriptors)
+		data.extend_from_slice(&(0xE000 | self.pcr_pid().unwrap_or(0x1FFF)).to_be_bytes());
+		data.extend_from_slice(&0xF000u16.to_be_bytes());
+
+		for stream in &self.streams {
+			let descriptors = match stream.stream.stream_type {
+				// A registration descriptor for "Opus" and an extension descriptor with the
+				// channel configuration.
+				StreamType::Opus { channels } => vec![0x05, 4, b'O', b'p', b'u', b's', 0x7F, 2, 0x80, channels],
+				_ => Vec::new(),
+			};
+
+			data.push(stream.stream.stream_type.to_u8());
+			data.extend_from_slice(&(0xE000 | stream.stream.pid).to_be_bytes());
+			data.extend_from_slice(&(0xF000 | descriptors.len() as u16).to_be_bytes());
+			data.extend_from_slice(&descriptors);
+		}
+
+		section(0x02, PROGRAM_NUMBER, &data)
+	}
+}
+
+/// Build a PSI section with the long header and CRC.
+/// ISO/IEC 13818-1 - 2.4.4
+fn section(table_id: u8, table_id_extension: u16, data: &[u8]) -> Vec<u8> {
+	// The section length counts everything after it: 5 bytes of header, the data
+	// and the CRC.
+	let section_length = 5 + data.len() + 4;
+
+	let mut section = Vec::with_capacity(3 + section_length);
+	section.push(table_id);
+	// section_syntax_indicator, '0', reserved and section_length
+	section.extend_from_slice(&(0xB000 | section_length as u16).to_be_bytes());
+	section.extend_from_slice(&table_id_extension.to_be_bytes());
+	// reserved, version_number 0 and current_next_indicator
+	section.push(0xC1);
+	// section_number and last_section_number
+	section.extend_from_slice(&[0, 0]);
+	section.extend_from_slice(data);
+
+	let crc = crc32(&section);
+	section.extend_from_slice(&crc.to_be_bytes());
+
+	section
+}
+
+/// Write a section in a single packet, the rest of the packet is stuffed
+/// with 0xFF.
+fn write_section<W: io::Write>(writer: &mut W, pid: u16, continuity_counter: &mut u8, section: &[u8]) -> io::Result<()> {
+	write_header(writer, pid, true, false, continuity_counter)?;
+
+	// pointer_field, the section starts right after it.
+	writer.write_u8(0)?;
+	writer.write_all(section)?;
+	writer.write_all(&[0xFF; PAYLOAD_SIZE][..PAYLOAD_SIZE - 1 - section.len()])?;
+
+	Ok(())
+}
+
+/// Write a packet header with a payload.
+fn write_header<W: io::Write>(
+	writer: &mut W,
+	pid: u16,
+	payload_unit_start: bool,
+	has_adaptation: bool,
+	continuity_counter: &mut u8,
+) -> io::Result<()> {
+	writer.write_u8(SYNC_BYTE)?;
+	writer.write_u16::<BigEndian>(((payload_unit_start as u16) << 14) | pid)?;
+
+	let adaptation_field_control = if has_adaptation { 0b11 } else { 0b01 };
+	writer.write_u8((adaptation_field_control << 4) | *continuity_counter)?;
+
+	*continuity_counter = (*continuity_counter + 1) % 16;
+
+	Ok(())
+}
+
+/// Build an adaptation field of exactly `size` bytes, the space that is not
+/// needed by the flags is filled with stuffing bytes.
+/// ISO/IEC 13818-1 - 2.4.3.4
+fn adaptation_field(pcr: Option<u64>, random_access: bool, size: usize) -> Vec<u8> {
+	if size == 0 {
+		return Vec::new();
+	}
+
+	// A single byte adaptation field only has the length.
+	let mut field = vec![(size - 1) as u8];
+	if size == 1 {
+		return field;
+	}
+
+	let mut flags = 0;
+	if random_access {
+		flags |= 0x40;
+	}
+	if pcr.is_some() {
+		flags |= 0x10;
+	}
+	field.push(flags);
+
+	if let Some(pcr) = pcr {
+		// 33 bits of base, 6 reserved bits and 9 bits of extension which we leave at 0.
+		let pcr = ((pcr & 0x1_FFFF_FFFF) << 15) | (0x3F << 9);
+		field.extend_from_slice(&pcr.to_be_bytes()[2..]);
+	}
+
+	field.resize(size, 0xFF);
+	field
+}
+
+/// Build the header of a PES packet.
+/// ISO/IEC 13818-1 - 2.4.3.6
+fn pes_header(stream: &MuxStream, pts: u64, dts: Option<u64>, data_size: usize) -> Result<Vec<u8>, TsMuxerError> {
+	let header_data_length = if dts.is_some() { 10 } else { 5 };
+
+	// The length counts everything after the length field, video streams may use 0
+	// if the packet is too large.
+	let packet_length = 3 + header_data_length + data_size;
+	let packet_length = if packet_length <= u16::MAX as usize {
+		packet_length as u16
+	} else if stream.stream.stream_type.is_video() {
+		0
+	} else {
+		return Err(TsMuxerError::PesTooLarge(data_size));
+	};
+
+	let mut header = vec![0x00, 0x00, 0x01, stream.stream_id];
+	header.extend_from_slice(&packet_length.to_be_bytes());
+	// '10' marker bits and data_alignment_indicator
+	header.push(0x84);
+	// PTS_DTS_flags
+	header.push(if dts.is_some() { 0xC0 } else { 0x80 });
+	header.push(header_data_length as u8);
+
+	if let Some(dts) = dts {
+		header.extend_from_slice(&timestamp(0b0011, pts));
+		header.extend_from_slice(&timestamp(0b0001, dts));
+	} else {
+		header.extend_from_slice(&timestamp(0b0010, pts));
+	}
+
+	Ok(header)
+}
+
+/// Encode a 33 bit timestamp with marker bits.
+fn timestamp(prefix: u8, ts: u64) -> [u8; 5] {
+	let ts = ts & 0x1_FFFF_FFFF;
+
+	[
+		(prefix << 4) | (((ts >> 29) & 0x0E) as u8) | 1,
+		(ts >> 22) as u8,
+		(((ts >> 14) & 0xFE) as u8) | 1,
+		(ts >> 7) as u8,
+		(((ts << 1) & 0xFE) as u8) | 1,
+	]
+}
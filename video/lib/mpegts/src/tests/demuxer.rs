@@ -0,0 +1,144 @@
+use bytes::Bytes;
+
+use crate::{Pes, StreamType, TsDemuxer, TsDemuxerError, TsMuxer, TsStream, TS_PACKET_SIZE};
+
+/// A stream with a H.264 and an Opus stream, returns the data and the PIDs.
+fn stream() -> (Vec<u8>, u16, u16) {
+	let mut muxer = TsMuxer::new();
+	let video = muxer.add_stream(StreamType::H264).unwrap();
+	let audio = muxer.add_stream(StreamType::Opus { channels: 2 }).unwrap();
+
+	let mut data = Vec::new();
+	for idx in 0..10u64 {
+		let frame = vec![idx as u8; 500 + idx as usize * 100];
+		muxer
+			.write_pes(&mut data, video, idx * 3000 + 3000, Some(idx * 3000), idx % 5 == 0, &frame)
+			.unwrap();
+		muxer
+			.write_pes(&mut data, audio, idx * 1800, None, true, &[0x7F, 0xE0, idx as u8])
+			.unwrap();
+	}
+
+	(data, video, audio)
+}
+
+fn read_all(demuxer: &mut TsDemuxer) -> Vec<Pes> {
+	let mut packets = Vec::new();
+	while let Some(pes) = demuxer.read_pes().unwrap() {
+		packets.push(pes);
+	}
+	packets
+}
+
+#[test]
+fn test_demuxer_round_trip() {
+	let (data, video, audio) = stream();
+
+	// Feed the data in odd sized chunks.
+	let mut demuxer = TsDemuxer::new();
+	let mut packets = Vec::new();
+	for chunk in data.chunks(100) {
+		demuxer.extend_data(chunk);
+		packets.extend(read_all(&mut demuxer));
+	}
+	demuxer.flush().unwrap();
+	packets.extend(read_all(&mut demuxer));
+
+	assert_eq!(
+		demuxer.streams(),
+		&[
+			TsStream {
+				pid: video,
+				stream_type: StreamType::H264,
+			},
+			TsStream {
+				pid: audio,
+				stream_type: StreamType::Opus { channels: 2 },
+			},
+		]
+	);
+	assert_eq!(demuxer.skipped_bytes(), 0);
+	assert_eq!(demuxer.discontinuities(), 0);
+
+	let video_packets = packets.iter().filter(|pes| pes.pid == video).collect::<Vec<_>>();
+	assert_eq!(video_packets.len(), 10);
+	for (idx, pes) in video_packets.iter().enumerate() {
+		let idx = idx as u64;
+		assert_eq!(pes.stream_id, 0xE0);
+		assert_eq!(pes.pts, Some(idx * 3000 + 3000));
+		assert_eq!(pes.dts, Some(idx * 3000));
+		assert_eq!(pes.random_access, idx % 5 == 0);
+		assert_eq!(pes.data, Bytes::from(vec![idx as u8; 500 + idx as usize * 100]));
+	}
+
+	let audio_packets = packets.iter().filter(|pes| pes.pid == audio).collect::<Vec<_>>();
+	assert_eq!(audio_packets.len(), 10);
+	for (idx, pes) in audio_packets.iter().enumerate() {
+		assert_eq!(pes.stream_id, 0xBD);
+		assert_eq!(pes.pts, Some(idx as u64 * 1800));
+		assert_eq!(pes.dts, None);
+		assert_eq!(pes.data, Bytes::from(vec![0x7F, 0xE0, idx as u8]));
+	}
+}
+
+#[test]
+fn test_demuxer_resync() {
+	let (data, video, _) = stream();
+
+	let mut corrupt = vec![0x47, 0x00, 0x47, 0x12, 0x34];
+	corrupt.extend_from_slice(&data);
+
+	let mut demuxer = TsDemuxer::new();
+	demuxer.extend_data(&corrupt);
+	let mut packets = read_all(&mut demuxer);
+	demuxer.flush().unwrap();
+	packets.extend(read_all(&mut demuxer));
+
+	assert_eq!(demuxer.skipped_bytes(), 5);
+	assert_eq!(packets.iter().filter(|pes| pes.pid == video).count(), 10);
+}
+
+#[test]
+fn test_demuxer_lost_packet() {
+	let (data, video, _) = stream();
+
+	// Drop the second packet of the first video frame, which is the fourth packet
+	// after the PAT and PMT.
+	let mut lossy = data[..3 * TS_PACKET_SIZE].to_vec();
+	lossy.extend_from_slice(&data[4 * TS_PACKET_SIZE..]);
+
+	let mut demuxer = TsDemuxer::new();
+	demuxer.extend_data(&lossy);
+	let mut packets = read_all(&mut demuxer);
+	demuxer.flush().unwrap();
+	packets.extend(read_all(&mut demuxer));
+
+	assert_eq!(demuxer.discontinuities(), 1);
+
+	// The broken frame is dropped.
+	let video_packets = packets.iter().filter(|pes| pes.pid == video).collect::<Vec<_>>();
+	assert_eq!(video_packets.len(), 9);
+	assert_eq!(video_packets[0].dts, Some(3000));
+}
+
+#[test]
+fn test_demuxer_invalid_crc() {
+	let (mut data, video, _) = stream();
+
+	// Corrupt the PAT.
+	data[10] ^= 0xFF;
+
+	let mut demuxer = TsDemuxer::new();
+	demuxer.extend_data(&data);
+	assert!(matches!(demuxer.read_pes(), Err(TsDemuxerError::InvalidCrc { pid: 0 })));
+
+	// Without a PAT we do not know about any streams, until the tables are repeated
+	// before the next keyframe.
+	let mut packets = read_all(&mut demuxer);
+	demuxer.flush().unwrap();
+	packets.extend(read_all(&mut demuxer));
+
+	assert_eq!(demuxer.streams().len(), 2);
+	assert_eq!(packets.iter().filter(|pes| pes.pid == video).count(), 5);
+	assert!(packets[0].random_access);
+}
@@ -0,0 +1,2 @@
+mod demuxer;
+mod muxer;
@@ -0,0 +1,85 @@
+use crate::{crc32, StreamType, TsMuxer, TsMuxerError, PAT_PID, SYNC_BYTE, TS_PACKET_SIZE};
+
+fn pid(packet: &[u8]) -> u16 {
+	u16::from_be_bytes([packet[1], packet[2]]) & 0x1FFF
+}
+
+#[test]
+fn test_crc32() {
+	// The check value of CRC-32/MPEG-2.
+	assert_eq!(crc32(b"123456789"), 0x0376E6E7);
+}
+
+#[test]
+fn test_muxer_packets() {
+	let mut muxer = TsMuxer::new();
+	let video = muxer.add_stream(StreamType::H264).unwrap();
+	let audio = muxer.add_stream(StreamType::Aac).unwrap();
+	assert_eq!(muxer.pcr_pid(), Some(video));
+
+	let mut data = Vec::new();
+	muxer.write_pes(&mut data, video, 3000, Some(0), true, &[0xAB; 1000]).unwrap();
+	muxer.write_pes(&mut data, audio, 0, None, true, &[0xCD; 100]).unwrap();
+	muxer
+		.write_pes(&mut data, video, 6000, Some(3000), false, &[0xAB; 10])
+		.unwrap();
+
+	assert_eq!(data.len() % TS_PACKET_SIZE, 0);
+
+	let packets = data.chunks(TS_PACKET_SIZE).collect::<Vec<_>>();
+	assert!(packets.iter().all(|packet| packet[0] == SYNC_BYTE));
+
+	// PAT and PMT come first.
+	assert_eq!(pid(packets[0]), PAT_PID);
+	assert_eq!(pid(packets[1]), 0x1000);
+
+	// The PAT section has a valid CRC.
+	let section_length = (u16::from_be_bytes([packets[0][6], packets[0][7]]) & 0x0FFF) as usize;
+	assert_eq!(crc32(&packets[0][5..8 + section_length]), 0);
+
+	// The first video packet starts a PES, and has the random access flag and a
+	// PCR.
+	let first = packets[2];
+	assert_eq!(pid(first), video);
+	assert_eq!(first[1] & 0x40, 0x40);
+	assert_eq!((first[3] >> 4) & 0b11, 0b11);
+	assert_eq!(first[5] & 0x50, 0x50);
+	assert_eq!(&first[12..16], &[0x00, 0x00, 0x01, 0xE0]);
+
+	// The continuity counter increases per PID.
+	let counters = packets
+		.iter()
+		.filter(|packet| pid(packet) == video)
+		.map(|packet| packet[3] & 0x0F)
+		.collect::<Vec<_>>();
+	assert_eq!(counters, (0..counters.len() as u8).collect::<Vec<_>>());
+
+	// The video keyframe is 1000 bytes plus a 19 byte PES header, which needs 6
+	// packets.
+	assert_eq!(counters.len(), 6 + 1);
+}
+
+#[test]
+fn test_muxer_errors() {
+	let mut muxer = TsMuxer::new();
+	let audio = muxer.add_stream(StreamType::Aac).unwrap();
+
+	let mut data = Vec::new();
+	assert!(matches!(
+		muxer.write_pes(&mut data, 0x200, 0, None, true, &[]),
+		Err(TsMuxerError::UnknownPid(0x200))
+	));
+	assert!(matches!(
+		muxer.write_pes(&mut data, audio, 0, None, true, &vec![0; u16::MAX as usize]),
+		Err(TsMuxerError::PesTooLarge(_))
+	));
+
+	muxer.write_tables(&mut data).unwrap();
+	assert!(matches!(muxer.add_stream(StreamType::H264), Err(TsMuxerError::StreamsLocked)));
+
+	let mut muxer = TsMuxer::new();
+	for _ in 0..8 {
+		muxer.add_stream(StreamType::Aac).unwrap();
+	}
+	assert!(matches!(muxer.add_stream(StreamType::Aac), Err(TsMuxerError::TooManyStreams)));
+}
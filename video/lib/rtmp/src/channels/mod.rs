@@ -38,5 +38,19 @@ pub struct PublishRequest {
 pub type PublishProducer = mpsc::Sender<PublishRequest>;
 pub type PublishConsumer = mpsc::Receiver<PublishRequest>;
 
+/// A request from a client that wants to play a stream.
+/// The response is the consumer the session will read the stream data from.
+/// The data is sent to the client as is, so the producer should start with
+/// the metadata and sequence headers followed by a keyframe.
+#[derive(Debug)]
+pub struct PlayRequest {
+	pub app_name: String,
+	pub stream_name: String,
+	pub response: oneshot::Sender<DataConsumer>,
+}
+
+pub type PlayProducer = mpsc::Sender<PlayRequest>;
+pub type PlayConsumer = mpsc::Receiver<PlayRequest>;
+
 pub type DataProducer = mpsc::Sender<ChannelData>;
 pub type DataConsumer = mpsc::Receiver<ChannelData>;
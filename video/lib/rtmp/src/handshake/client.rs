@@ -0,0 +1,173 @@
+use std::io::Write;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use bytes::{Bytes, BytesMut};
+use bytesio::bytes_reader::BytesReader;
+use bytesio::bytes_writer::BytesWriter;
+use rand::Rng;
+
+use super::define::{ClientHandshakeState, RtmpVersion};
+use super::errors::HandshakeError;
+use super::{define, utils};
+
+// Simple Handshake Client
+// RTMP Spec 1.0 - 5.2
+//
+// Order of messages:
+// Client -> C0 -> Server
+// Client -> C1 -> Server
+// Client <- S0 <- Server
+// Client <- S1 <- Server
+// Client <- S2 <- Server
+// Client -> C2 -> Server
+//
+// We only ever do the simple handshake as a client. The complex handshake is
+// only required by some Flash Player specific features (RTMPE / SWF
+// verification) which we do not support. Servers fall back to the simple
+// handshake when the version field of C1 is zero.
+pub struct HandshakeClient {
+	reader: BytesReader,
+
+	state: ClientHandshakeState,
+
+	s1_timestamp: u32,
+	s1_bytes: Bytes,
+}
+
+impl Default for HandshakeClient {
+	fn default() -> Self {
+		Self {
+			reader: BytesReader::new(BytesMut::default()),
+			state: ClientHandshakeState::WriteC0C1,
+			s1_timestamp: 0,
+			s1_bytes: Bytes::new(),
+		}
+	}
+}
+
+impl HandshakeClient {
+	pub fn extend_data(&mut self, data: &[u8]) {
+		self.reader.extend_from_slice(data);
+	}
+
+	pub fn state(&self) -> ClientHandshakeState {
+		self.state
+	}
+
+	pub fn extract_remaining_bytes(&mut self) -> BytesMut {
+		self.reader.extract_remaining_bytes()
+	}
+
+	/// The amount of data we need from the server before we can continue the
+	/// handshake. S0 is 1 byte, S1 and S2 are 1536 bytes each.
+	pub fn required_read_size(&self) -> usize {
+		match self.state {
+			ClientHandshakeState::ReadS0S1S2 => 1 + define::RTMP_HANDSHAKE_SIZE * 2,
+			_ => 0,
+		}
+	}
+
+	pub fn handshake(&mut self, writer: &mut BytesWriter) -> Result<(), HandshakeError> {
+		loop {
+			match self.state {
+				ClientHandshakeState::WriteC0C1 => {
+					self.write_c0(writer)?;
+					self.write_c1(writer)?;
+					self.state = ClientHandshakeState::ReadS0S1S2;
+					break;
+				}
+				ClientHandshakeState::ReadS0S1S2 => {
+					self.read_s0()?;
+					self.read_s1()?;
+					self.read_s2()?;
+					self.write_c2(writer)?;
+					self.state = ClientHandshakeState::Finish;
+				}
+				ClientHandshakeState::Finish => {
+					break;
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Defined in RTMP Specification 1.0 - 5.2.2
+	fn write_c0(&self, writer: &mut BytesWriter) -> Result<(), HandshakeError> {
+		// Version (8 bits): In C0, this field identifies the RTMP version
+		//  requested by the client. We only support version 3.
+		writer.write_u8(RtmpVersion::Version3 as u8)?;
+
+		Ok(())
+	}
+
+	/// Defined in RTMP Specification 1.0 - 5.2.3
+	fn write_c1(&self, writer: &mut BytesWriter) -> Result<(), HandshakeError> {
+		// Time (4 bytes): This field contains a timestamp, which SHOULD be
+		//  used as the epoch for all future chunks sent from this endpoint.
+		writer.write_u32::<BigEndian>(utils::current_time())?;
+
+		// Zero (4 bytes): This field MUST be all 0s.
+		// A zero here also tells the server that we want a simple handshake.
+		writer.write_u32::<BigEndian>(0)?;
+
+		// Random data (1528 bytes): This field can contain any arbitrary
+		//  values.
+		let mut rng = rand::thread_rng();
+		for _ in 0..define::RTMP_HANDSHAKE_SIZE - define::TIME_VERSION_LENGTH {
+			writer.write_u8(rng.gen())?;
+		}
+
+		Ok(())
+	}
+
+	fn read_s0(&mut self) -> Result<(), HandshakeError> {
+		// Version (8 bits): In S0, this field identifies the RTMP version
+		//  selected by the server. Version 3 is the only version in use, so
+		//  even if the server picks something else we just continue.
+		self.reader.read_u8()?;
+
+		Ok(())
+	}
+
+	fn read_s1(&mut self) -> Result<(), HandshakeError> {
+		// The first 4 bytes of S1 are the timestamp.
+		self.s1_timestamp = self.reader.read_u32::<BigEndian>()?;
+
+		// The next 4 bytes are either zero or the server version (complex handshake).
+		// We do not care which, since we will echo the data back anyway.
+		self.reader.read_u32::<BigEndian>()?;
+
+		// Random data (1528 bytes)
+		self.s1_bytes = self
+			.reader
+			.read_bytes(define::RTMP_HANDSHAKE_SIZE - define::TIME_VERSION_LENGTH)?
+			.freeze();
+
+		Ok(())
+	}
+
+	fn read_s2(&mut self) -> Result<(), HandshakeError> {
+		// S2 should be an echo of our C1. Same as the server we are not strict
+		// about this, since some servers send different data.
+		self.reader.read_bytes(define::RTMP_HANDSHAKE_SIZE)?;
+
+		Ok(())
+	}
+
+	fn write_c2(&self, writer: &mut BytesWriter) -> Result<(), HandshakeError> {
+		// Time (4 bytes): This field MUST contain the timestamp sent by the peer in
+		//  S1.
+		writer.write_u32::<BigEndian>(self.s1_timestamp)?;
+
+		// Time2 (4 bytes): This field MUST contain the timestamp at which the
+		//  previous packet (S1) sent by the peer was read.
+		writer.write_u32::<BigEndian>(utils::current_time())?;
+
+		// Random echo (1528 bytes): This field MUST contain the random data
+		//  field sent by the peer in S1.
+		writer.write_all(&self.s1_bytes[..])?;
+
+		Ok(())
+	}
+}
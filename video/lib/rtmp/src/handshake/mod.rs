@@ -1,10 +1,12 @@
+mod client;
 mod define;
 mod digest;
 mod errors;
 mod server;
 mod utils;
 
-pub use self::define::{ServerHandshakeState, RTMP_HANDSHAKE_SIZE};
+pub use self::client::HandshakeClient;
+pub use self::define::{ClientHandshakeState, ServerHandshakeState, RTMP_HANDSHAKE_SIZE};
 pub use self::errors::*;
 pub use self::server::HandshakeServer;
 
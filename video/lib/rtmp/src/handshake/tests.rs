@@ -5,7 +5,7 @@ use bytes::Bytes;
 use bytesio::bytes_reader::BytesCursor;
 use bytesio::bytes_writer::BytesWriter;
 
-use super::{HandshakeError, HandshakeServer};
+use super::{ClientHandshakeState, HandshakeClient, HandshakeError, HandshakeServer};
 use crate::handshake::define::{
 	SchemaVersion, {self},
 };
@@ -135,6 +135,51 @@ fn test_complex_handshake() {
 	assert_eq!(handshake_server.state(), ServerHandshakeState::Finish)
 }
 
+#[test]
+fn test_client_handshake() {
+	let mut handshake_client = HandshakeClient::default();
+	let mut handshake_server = HandshakeServer::default();
+
+	let mut writer = BytesWriter::default();
+	handshake_client.handshake(&mut writer).unwrap();
+	assert_eq!(handshake_client.state(), ClientHandshakeState::ReadS0S1S2);
+
+	let c0c1 = writer.dispose();
+	assert_eq!(c0c1.len(), 1537);
+	assert_eq!(c0c1[0], 3); // version
+	assert_eq!((&c0c1[5..9]).read_u32::<BigEndian>().unwrap(), 0); // zero
+
+	handshake_server.extend_data(&c0c1);
+
+	let mut writer = BytesWriter::default();
+	handshake_server.handshake(&mut writer).unwrap();
+
+	let s0s1s2 = writer.dispose();
+	assert_eq!(s0s1s2.len(), handshake_client.required_read_size());
+
+	handshake_client.extend_data(&s0s1s2);
+	// Some extra data the server sent after the handshake
+	handshake_client.extend_data(&[1, 2, 3]);
+
+	let mut writer = BytesWriter::default();
+	handshake_client.handshake(&mut writer).unwrap();
+	assert_eq!(handshake_client.state(), ClientHandshakeState::Finish);
+
+	let c2 = writer.dispose();
+	assert_eq!(c2.len(), 1536);
+	assert_eq!(&c2[..4], &s0s1s2[1..5]); // s1 timestamp
+	assert_eq!(&c2[8..], &s0s1s2[9..1537]); // s1 random echo
+
+	assert_eq!(handshake_client.extract_remaining_bytes(), vec![1, 2, 3]);
+
+	handshake_server.extend_data(&c2);
+
+	let mut writer = BytesWriter::default();
+	handshake_server.handshake(&mut writer).unwrap();
+
+	assert_eq!(handshake_server.state(), ServerHandshakeState::Finish)
+}
+
 #[test]
 fn test_error_display() {
 	let err = HandshakeError::Digest(DigestError::CannotGenerate);
@@ -9,8 +9,11 @@ mod protocol_control_messages;
 mod session;
 mod user_control_messages;
 
-pub use channels::{ChannelData, DataConsumer, DataProducer, PublishConsumer, PublishProducer, PublishRequest, UniqueID};
-pub use session::{Session, SessionError};
+pub use channels::{
+	ChannelData, DataConsumer, DataProducer, PlayConsumer, PlayProducer, PlayRequest, PublishConsumer, PublishProducer,
+	PublishRequest, UniqueID,
+};
+pub use session::{ClientSession, Session, SessionError};
 
 #[cfg(test)]
 mod tests;
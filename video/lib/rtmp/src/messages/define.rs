@@ -2,6 +2,8 @@ use amf0::Amf0Value;
 use bytes::Bytes;
 use num_derive::FromPrimitive;
 
+use crate::user_control_messages::UserControlEvent;
+
 #[derive(Debug)]
 pub enum RtmpMessageData {
 	Amf0Command {
@@ -22,6 +24,15 @@ pub enum RtmpMessageData {
 	VideoData {
 		data: Bytes,
 	},
+	Acknowledgement {
+		sequence_number: u32,
+	},
+	WindowAcknowledgementSize {
+		window_size: u32,
+	},
+	UserControlEvent {
+		event: UserControlEvent,
+	},
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, FromPrimitive)]
@@ -1,26 +1,35 @@
 use std::fmt;
 
-use amf0::Amf0ReadError;
+use amf0::{Amf0ReadError, Amf0WriteError};
 
 use crate::macros::from_error;
 use crate::protocol_control_messages::ProtocolControlMessageError;
+use crate::user_control_messages::EventMessagesError;
 
 #[derive(Debug)]
 pub enum MessageError {
 	Amf0Read(Amf0ReadError),
+	Amf0Write(Amf0WriteError),
 	ProtocolControlMessage(ProtocolControlMessageError),
+	EventMessages(EventMessagesError),
+	InvalidAggregate,
 }
 
 from_error!(MessageError, Self::Amf0Read, Amf0ReadError);
+from_error!(MessageError, Self::Amf0Write, Amf0WriteError);
 from_error!(MessageError, Self::ProtocolControlMessage, ProtocolControlMessageError);
+from_error!(MessageError, Self::EventMessages, EventMessagesError);
 
 impl fmt::Display for MessageError {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match &self {
 			Self::Amf0Read(error) => write!(f, "amf0 read error: {}", error),
+			Self::Amf0Write(error) => write!(f, "amf0 write error: {}", error),
 			Self::ProtocolControlMessage(error) => {
 				write!(f, "protocol control message error: {}", error)
 			}
+			Self::EventMessages(error) => write!(f, "event messages error: {}", error),
+			Self::InvalidAggregate => write!(f, "invalid aggregate message"),
 		}
 	}
 }
@@ -1,9 +1,18 @@
-use amf0::{Amf0Marker, Amf0Reader};
+use amf0::{Amf0ReadError, Amf0Reader, Amf0Value, Amf0Writer};
+use bytes::Bytes;
+use bytesio::bytes_writer::BytesWriter;
+use num_traits::FromPrimitive;
 
 use super::define::{MessageTypeID, RtmpMessageData};
 use super::errors::MessageError;
 use crate::chunk::Chunk;
 use crate::protocol_control_messages::ProtocolControlMessageReader;
+use crate::user_control_messages::EventMessagesReader;
+
+/// Size of the header of a message inside an aggregate message
+const AGGREGATE_HEADER_SIZE: usize = 11;
+/// Size of the back pointer after each message inside an aggregate message
+const AGGREGATE_BACK_POINTER_SIZE: usize = 4;
 
 pub struct MessageParser;
 
@@ -11,24 +20,8 @@ impl MessageParser {
 	pub fn parse(chunk: Chunk) -> Result<Option<RtmpMessageData>, MessageError> {
 		match chunk.message_header.msg_type_id {
 			// Protocol Control Messages
-			MessageTypeID::CommandAMF0 => {
-				let mut amf_reader = Amf0Reader::new(chunk.payload);
-				let command_name = amf_reader.read_with_type(Amf0Marker::String)?;
-				let transaction_id = amf_reader.read_with_type(Amf0Marker::Number)?;
-				let command_object = match amf_reader.read_with_type(Amf0Marker::Object) {
-					Ok(val) => val,
-					Err(_) => amf_reader.read_with_type(Amf0Marker::Null)?,
-				};
-
-				let others = amf_reader.read_all()?;
-
-				Ok(Some(RtmpMessageData::Amf0Command {
-					command_name,
-					transaction_id,
-					command_object,
-					others,
-				}))
-			}
+			MessageTypeID::CommandAMF0 => Ok(Some(Self::parse_command(chunk.payload)?)),
+			MessageTypeID::CommandAMF3 => Ok(Some(Self::parse_command(Self::strip_amf3_format(chunk.payload))?)),
 			// Data Messages - AUDIO
 			MessageTypeID::Audio => Ok(Some(RtmpMessageData::AudioData { data: chunk.payload })),
 			// Data Messages - VIDEO
@@ -39,9 +32,134 @@ impl MessageParser {
 
 				Ok(Some(RtmpMessageData::SetChunkSize { chunk_size }))
 			}
+			MessageTypeID::Acknowledgement => {
+				let sequence_number = ProtocolControlMessageReader::read_acknowledgement(chunk.payload)?;
+
+				Ok(Some(RtmpMessageData::Acknowledgement { sequence_number }))
+			}
+			MessageTypeID::WindowAcknowledgementSize => {
+				let window_size = ProtocolControlMessageReader::read_window_acknowledgement_size(chunk.payload)?;
+
+				Ok(Some(RtmpMessageData::WindowAcknowledgementSize { window_size }))
+			}
+			// User Control Messages
+			MessageTypeID::UserControlEvent => {
+				let event = EventMessagesReader::read(chunk.payload)?;
+
+				Ok(Some(RtmpMessageData::UserControlEvent { event }))
+			}
 			// Metadata
-			MessageTypeID::DataAMF0 | MessageTypeID::DataAMF3 => Ok(Some(RtmpMessageData::AmfData { data: chunk.payload })),
+			MessageTypeID::DataAMF0 => Ok(Some(RtmpMessageData::AmfData { data: chunk.payload })),
+			MessageTypeID::DataAMF3 => Ok(Some(RtmpMessageData::AmfData {
+				data: Self::normalize_amf3_data(Self::strip_amf3_format(chunk.payload))?,
+			})),
+			// Aggregate messages are split with `split_aggregate` before they are
+			// parsed, shared objects are not supported
 			_ => Ok(None),
 		}
 	}
+
+	/// Split an aggregate message into the messages it contains.
+	/// Each message has a header like an FLV tag: type (1 byte), size (3
+	/// bytes), timestamp (3 bytes + 1 extended byte) and stream id (3 bytes),
+	/// followed by the payload and a 4 byte back pointer. The timestamps are
+	/// offset so the first message has the timestamp of the aggregate message.
+	/// Only audio, video and data messages can be aggregated, anything else is
+	/// dropped.
+	pub fn split_aggregate(chunk: Chunk) -> Result<Vec<Chunk>, MessageError> {
+		let chunk_stream_id = chunk.basic_header.chunk_stream_id;
+		let msg_stream_id = chunk.message_header.msg_stream_id;
+		let aggregate_timestamp = chunk.message_header.timestamp;
+		let payload = chunk.payload;
+
+		let mut chunks = Vec::new();
+		let mut first_timestamp = None;
+		let mut offset = 0;
+
+		while offset < payload.len() {
+			let header = payload
+				.get(offset..offset + AGGREGATE_HEADER_SIZE)
+				.ok_or(MessageError::InvalidAggregate)?;
+
+			let size = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+			let timestamp = u32::from_be_bytes([header[7], header[4], header[5], header[6]]);
+			// The stream id in the header is ignored, the messages belong to the
+			// stream of the aggregate message.
+
+			let start = offset + AGGREGATE_HEADER_SIZE;
+			let end = start + size;
+			if end > payload.len() {
+				return Err(MessageError::InvalidAggregate);
+			}
+
+			let first_timestamp = *first_timestamp.get_or_insert(timestamp);
+
+			if let Some(
+				msg_type_id @ (MessageTypeID::Audio
+				| MessageTypeID::Video
+				| MessageTypeID::DataAMF0
+				| MessageTypeID::DataAMF3),
+			) = MessageTypeID::from_u8(header[0])
+			{
+				chunks.push(Chunk::new(
+					chunk_stream_id,
+					aggregate_timestamp.wrapping_add(timestamp.wrapping_sub(first_timestamp)),
+					msg_type_id,
+					msg_stream_id,
+					payload.slice(start..end),
+				));
+			}
+
+			// Some encoders leave out the back pointer of the last message
+			offset = (end + AGGREGATE_BACK_POINTER_SIZE).min(payload.len());
+		}
+
+		Ok(chunks)
+	}
+
+	fn parse_command(payload: Bytes) -> Result<RtmpMessageData, MessageError> {
+		// Any value in a command can be switched to AMF3, which is what clients
+		// using AMF3 commands do. We convert those values back to AMF0 so the
+		// session does not need to care which encoding the client uses.
+		let mut amf_reader = Amf0Reader::new(payload);
+		let mut values = amf_reader.read_all()?.into_iter().map(Amf0Value::normalize_amf3);
+
+		let command_name = values.next().ok_or(Amf0ReadError::WrongType)?;
+		let transaction_id = values.next().ok_or(Amf0ReadError::WrongType)?;
+		let command_object = values.next().ok_or(Amf0ReadError::WrongType)?;
+
+		if !matches!(command_name, Amf0Value::String(_))
+			|| !matches!(transaction_id, Amf0Value::Number(_))
+			|| !matches!(command_object, Amf0Value::Object(_) | Amf0Value::Null)
+		{
+			return Err(Amf0ReadError::WrongType.into());
+		}
+
+		Ok(RtmpMessageData::Amf0Command {
+			command_name,
+			transaction_id,
+			command_object,
+			others: values.collect(),
+		})
+	}
+
+	/// AMF3 messages start with a format byte, which is always 0.
+	fn strip_amf3_format(payload: Bytes) -> Bytes {
+		match payload.first() {
+			Some(0) => payload.slice(1..),
+			_ => payload,
+		}
+	}
+
+	/// Metadata is passed on as AMF0, so AMF3 data messages are converted.
+	fn normalize_amf3_data(payload: Bytes) -> Result<Bytes, MessageError> {
+		let values = Amf0Reader::new(payload).read_all()?;
+
+		let mut writer = BytesWriter::default();
+		for value in values {
+			Amf0Writer::write_any(&mut writer, &value.normalize_amf3())?;
+		}
+
+		Ok(writer.dispose())
+	}
 }
@@ -1,23 +1,37 @@
-use std::collections::HashMap;
+use std::io::Write;
 
-use amf0::{Amf0ReadError, Amf0Value, Amf0Writer};
+use amf0::{Amf0Object, Amf0ReadError, Amf0Value, Amf0WriteError, Amf0Writer};
+use amf3::{Amf3Object, Amf3Value};
 use bytesio::bytes_writer::BytesWriter;
 
 use super::{MessageError, MessageParser, MessageTypeID, RtmpMessageData};
 use crate::chunk::{Chunk, ChunkEncodeError};
 use crate::protocol_control_messages::ProtocolControlMessageError;
+use crate::user_control_messages::{EventMessagesError, UserControlEvent};
 
 #[test]
 fn test_error_display() {
 	let error = MessageError::Amf0Read(Amf0ReadError::WrongType);
 	assert_eq!(error.to_string(), "amf0 read error: wrong type");
 
+	let error = MessageError::Amf0Write(Amf0WriteError::NormalStringTooLong);
+	assert_eq!(error.to_string(), "amf0 write error: normal string too long");
+
 	let error =
 		MessageError::ProtocolControlMessage(ProtocolControlMessageError::ChunkEncode(ChunkEncodeError::UnknownReadState));
 	assert_eq!(
 		error.to_string(),
 		"protocol control message error: chunk encode error: unknown read state"
 	);
+
+	let error = MessageError::EventMessages(EventMessagesError::ChunkEncode(ChunkEncodeError::UnknownReadState));
+	assert_eq!(
+		error.to_string(),
+		"event messages error: chunk encode error: unknown read state"
+	);
+
+	let error = MessageError::InvalidAggregate;
+	assert_eq!(error.to_string(), "invalid aggregate message");
 }
 
 #[test]
@@ -47,6 +61,64 @@ fn test_parse_command() {
 	}
 }
 
+#[test]
+fn test_parse_command_amf3() {
+	let mut amf0_writer = BytesWriter::default();
+
+	amf0_writer.write_all(&[0x00]).unwrap(); // AMF3 format byte
+	Amf0Writer::write_string(&mut amf0_writer, "connect").unwrap();
+	Amf0Writer::write_number(&mut amf0_writer, 1.0).unwrap();
+	Amf0Writer::write_avmplus_object(
+		&mut amf0_writer,
+		&Amf3Value::Object(Amf3Object::anonymous(vec![
+			("app".to_string(), Amf3Value::String("live".to_string())),
+			("objectEncoding".to_string(), Amf3Value::Integer(3)),
+		])),
+	)
+	.unwrap();
+	Amf0Writer::write_avmplus_object(&mut amf0_writer, &Amf3Value::String("extra".to_string())).unwrap();
+
+	let chunk = Chunk::new(0, 0, MessageTypeID::CommandAMF3, 0, amf0_writer.dispose());
+
+	let message = MessageParser::parse(chunk).expect("no errors").expect("message");
+	match message {
+		RtmpMessageData::Amf0Command {
+			command_name,
+			transaction_id,
+			command_object,
+			others,
+		} => {
+			assert_eq!(command_name, Amf0Value::String("connect".to_string()));
+			assert_eq!(transaction_id, Amf0Value::Number(1.0));
+			assert_eq!(
+				command_object,
+				Amf0Value::Object(Amf0Object::from([
+					("app".to_string(), Amf0Value::String("live".to_string())),
+					("objectEncoding".to_string(), Amf0Value::Number(3.0)),
+				]))
+			);
+			assert_eq!(others, vec![Amf0Value::String("extra".to_string())]);
+		}
+		_ => unreachable!("wrong message type"),
+	}
+}
+
+#[test]
+fn test_parse_command_wrong_type() {
+	let mut amf0_writer = BytesWriter::default();
+
+	Amf0Writer::write_number(&mut amf0_writer, 1.0).unwrap();
+	Amf0Writer::write_number(&mut amf0_writer, 1.0).unwrap();
+	Amf0Writer::write_null(&mut amf0_writer).unwrap();
+
+	let chunk = Chunk::new(0, 0, MessageTypeID::CommandAMF0, 0, amf0_writer.dispose());
+
+	assert!(matches!(
+		MessageParser::parse(chunk),
+		Err(MessageError::Amf0Read(Amf0ReadError::WrongType))
+	));
+}
+
 #[test]
 fn test_parse_audio_packet() {
 	let chunk = Chunk::new(0, 0, MessageTypeID::Audio, 0, vec![0x00, 0x00, 0x00, 0x00].into());
@@ -93,7 +165,7 @@ fn test_parse_metadata() {
 	Amf0Writer::write_string(&mut amf0_writer, "onMetaData").unwrap();
 	Amf0Writer::write_object(
 		&mut amf0_writer,
-		&HashMap::from([("duration".to_string(), Amf0Value::Number(0.0))]),
+		&Amf0Object::from([("duration".to_string(), Amf0Value::Number(0.0))]),
 	)
 	.unwrap();
 
@@ -110,9 +182,172 @@ fn test_parse_metadata() {
 	}
 }
 
+#[test]
+fn test_parse_metadata_amf3() {
+	let mut amf_data = BytesWriter::default();
+
+	amf_data.write_all(&[0x00]).unwrap(); // AMF3 format byte
+	Amf0Writer::write_string(&mut amf_data, "onMetaData").unwrap();
+	Amf0Writer::write_avmplus_object(
+		&mut amf_data,
+		&Amf3Value::Array {
+			associative: vec![("duration".to_string(), Amf3Value::Double(0.0))],
+			dense: vec![],
+		},
+	)
+	.unwrap();
+
+	let chunk = Chunk::new(0, 0, MessageTypeID::DataAMF3, 0, amf_data.dispose());
+
+	// The associative array is re-encoded as an AMF0 ECMA array
+	let mut amf0_writer = BytesWriter::default();
+
+	Amf0Writer::write_string(&mut amf0_writer, "onMetaData").unwrap();
+	Amf0Writer::write_ecma_array(
+		&mut amf0_writer,
+		&Amf0Object::from([("duration".to_string(), Amf0Value::Number(0.0))]),
+	)
+	.unwrap();
+
+	let message = MessageParser::parse(chunk).expect("no errors").expect("message");
+	match message {
+		RtmpMessageData::AmfData { data } => {
+			assert_eq!(data, amf0_writer.dispose());
+		}
+		_ => unreachable!("wrong message type"),
+	}
+}
+
 #[test]
 fn test_unsupported_message_type() {
 	let chunk = Chunk::new(0, 0, MessageTypeID::Aggregate, 0, vec![0x00, 0x00, 0x00, 0x00].into());
 
 	assert!(MessageParser::parse(chunk).expect("no errors").is_none())
 }
+
+#[test]
+fn test_parse_acknowledgement() {
+	let chunk = Chunk::new(2, 0, MessageTypeID::Acknowledgement, 0, vec![0x00, 0x00, 0x10, 0x00].into());
+
+	let message = MessageParser::parse(chunk).expect("no errors").expect("message");
+	match message {
+		RtmpMessageData::Acknowledgement { sequence_number } => {
+			assert_eq!(sequence_number, 0x1000);
+		}
+		_ => unreachable!("wrong message type"),
+	}
+}
+
+#[test]
+fn test_parse_window_acknowledgement_size() {
+	let chunk = Chunk::new(
+		2,
+		0,
+		MessageTypeID::WindowAcknowledgementSize,
+		0,
+		vec![0x00, 0x26, 0x25, 0xA0].into(),
+	);
+
+	let message = MessageParser::parse(chunk).expect("no errors").expect("message");
+	match message {
+		RtmpMessageData::WindowAcknowledgementSize { window_size } => {
+			assert_eq!(window_size, 2500000);
+		}
+		_ => unreachable!("wrong message type"),
+	}
+}
+
+#[test]
+fn test_parse_user_control_event() {
+	let chunk = Chunk::new(
+		2,
+		0,
+		MessageTypeID::UserControlEvent,
+		0,
+		vec![0x00, 0x06, 0x00, 0x00, 0x01, 0x00].into(),
+	);
+
+	let message = MessageParser::parse(chunk).expect("no errors").expect("message");
+	match message {
+		RtmpMessageData::UserControlEvent { event } => {
+			assert_eq!(event, UserControlEvent::PingRequest { timestamp: 256 });
+		}
+		_ => unreachable!("wrong message type"),
+	}
+
+	// Too short for a ping request
+	let chunk = Chunk::new(2, 0, MessageTypeID::UserControlEvent, 0, vec![0x00, 0x06, 0x00].into());
+
+	assert!(matches!(
+		MessageParser::parse(chunk),
+		Err(MessageError::EventMessages(EventMessagesError::IO(_)))
+	));
+}
+
+/// Write a message the way it is stored inside an aggregate message
+fn write_aggregate_message(payload: &mut Vec<u8>, msg_type_id: MessageTypeID, timestamp: u32, data: &[u8]) {
+	payload.push(msg_type_id as u8);
+	payload.extend_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+	payload.extend_from_slice(&timestamp.to_be_bytes()[1..]);
+	payload.push((timestamp >> 24) as u8);
+	payload.extend_from_slice(&[0x00, 0x00, 0x00]); // stream id
+	payload.extend_from_slice(data);
+	payload.extend_from_slice(&(data.len() as u32 + 11).to_be_bytes()); // back pointer
+}
+
+#[test]
+fn test_split_aggregate() {
+	let mut payload = Vec::new();
+	write_aggregate_message(&mut payload, MessageTypeID::Video, 0x01000000, &[0x17, 0x01]);
+	write_aggregate_message(&mut payload, MessageTypeID::Audio, 0x01000010, &[0xAF, 0x01, 0x02]);
+	// Commands can not be aggregated and are dropped
+	write_aggregate_message(&mut payload, MessageTypeID::CommandAMF0, 0x01000020, &[0x05]);
+	write_aggregate_message(&mut payload, MessageTypeID::DataAMF0, 0x01000021, &[0x05]);
+
+	let chunk = Chunk::new(6, 1000, MessageTypeID::Aggregate, 1, payload.into());
+	let chunks = MessageParser::split_aggregate(chunk).expect("no errors");
+
+	assert_eq!(
+		chunks,
+		vec![
+			Chunk::new(6, 1000, MessageTypeID::Video, 1, vec![0x17, 0x01].into()),
+			Chunk::new(6, 1016, MessageTypeID::Audio, 1, vec![0xAF, 0x01, 0x02].into()),
+			Chunk::new(6, 1033, MessageTypeID::DataAMF0, 1, vec![0x05].into()),
+		]
+	);
+}
+
+#[test]
+fn test_split_aggregate_without_last_back_pointer() {
+	let mut payload = Vec::new();
+	write_aggregate_message(&mut payload, MessageTypeID::Video, 0, &[0x17, 0x01]);
+	payload.truncate(payload.len() - 4);
+
+	let chunk = Chunk::new(6, 0, MessageTypeID::Aggregate, 1, payload.into());
+	let chunks = MessageParser::split_aggregate(chunk).expect("no errors");
+
+	assert_eq!(
+		chunks,
+		vec![Chunk::new(6, 0, MessageTypeID::Video, 1, vec![0x17, 0x01].into())]
+	);
+}
+
+#[test]
+fn test_split_aggregate_invalid() {
+	let mut payload = Vec::new();
+	write_aggregate_message(&mut payload, MessageTypeID::Video, 0, &[0x17, 0x01]);
+
+	// The size says there are 2 bytes of data but there is only 1
+	let chunk = Chunk::new(6, 0, MessageTypeID::Aggregate, 1, payload[..12].to_vec().into());
+	assert!(matches!(
+		MessageParser::split_aggregate(chunk),
+		Err(MessageError::InvalidAggregate)
+	));
+
+	// Not enough data for a header
+	let chunk = Chunk::new(6, 0, MessageTypeID::Aggregate, 1, vec![0x09, 0x00].into());
+	assert!(matches!(
+		MessageParser::split_aggregate(chunk),
+		Err(MessageError::InvalidAggregate)
+	));
+}
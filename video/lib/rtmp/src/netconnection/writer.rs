@@ -1,6 +1,4 @@
-use std::collections::HashMap;
-
-use amf0::{Amf0Value, Amf0Writer};
+use amf0::{Amf0Object, Amf0Value, Amf0Writer};
 use bytesio::bytes_writer::BytesWriter;
 
 use super::errors::NetConnectionError;
@@ -39,14 +37,14 @@ impl NetConnection {
 		Amf0Writer::write_number(&mut amf0_writer, transaction_id)?;
 		Amf0Writer::write_object(
 			&mut amf0_writer,
-			&HashMap::from([
+			&Amf0Object::from([
 				("fmsVer".to_string(), Amf0Value::String(fmsver.to_string())),
 				("capabilities".to_string(), Amf0Value::Number(capabilities)),
 			]),
 		)?;
 		Amf0Writer::write_object(
 			&mut amf0_writer,
-			&HashMap::from([
+			&Amf0Object::from([
 				("level".to_string(), Amf0Value::String(level.to_string())),
 				("code".to_string(), Amf0Value::String(code.to_string())),
 				("description".to_string(), Amf0Value::String(description.to_string())),
@@ -72,4 +70,52 @@ impl NetConnection {
 
 		Self::write_chunk(encoder, amf0_writer, writer)
 	}
+
+	/// Write a connect command. This is sent by the client as the first
+	/// command after the handshake.
+	pub fn write_connect(
+		encoder: &ChunkEncoder,
+		writer: &mut BytesWriter,
+		transaction_id: f64,
+		app: &str,
+		tc_url: &str,
+	) -> Result<(), NetConnectionError> {
+		let mut amf0_writer = BytesWriter::default();
+
+		Amf0Writer::write_string(&mut amf0_writer, "connect")?;
+		Amf0Writer::write_number(&mut amf0_writer, transaction_id)?;
+		Amf0Writer::write_object(
+			&mut amf0_writer,
+			&Amf0Object::from([
+				("app".to_string(), Amf0Value::String(app.to_string())),
+				("type".to_string(), Amf0Value::String("nonprivate".to_string())),
+				// This is the same flash version that OBS and ffmpeg send when publishing.
+				(
+					"flashVer".to_string(),
+					Amf0Value::String("FMLE/3.0 (compatible; FMSc/1.0)".to_string()),
+				),
+				("tcUrl".to_string(), Amf0Value::String(tc_url.to_string())),
+				// We only support AMF0 encoding
+				("objectEncoding".to_string(), Amf0Value::Number(0.0)),
+			]),
+		)?;
+
+		Self::write_chunk(encoder, amf0_writer, writer)
+	}
+
+	/// Write a createStream command. The server will respond with the stream
+	/// id of the new stream.
+	pub fn write_create_stream(
+		encoder: &ChunkEncoder,
+		writer: &mut BytesWriter,
+		transaction_id: f64,
+	) -> Result<(), NetConnectionError> {
+		let mut amf0_writer = BytesWriter::default();
+
+		Amf0Writer::write_string(&mut amf0_writer, "createStream")?;
+		Amf0Writer::write_number(&mut amf0_writer, transaction_id)?;
+		Amf0Writer::write_null(&mut amf0_writer)?;
+
+		Self::write_chunk(encoder, amf0_writer, writer)
+	}
 }
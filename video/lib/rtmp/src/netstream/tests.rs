@@ -1,6 +1,4 @@
-use std::collections::HashMap;
-
-use amf0::{Amf0Reader, Amf0Value, Amf0WriteError};
+use amf0::{Amf0Object, Amf0Reader, Amf0Value, Amf0WriteError};
 use bytesio::bytes_writer::BytesWriter;
 
 use crate::chunk::{ChunkDecoder, ChunkEncodeError, ChunkEncoder};
@@ -39,10 +37,99 @@ fn test_netstream_write_on_status() {
 	assert_eq!(values[2], Amf0Value::Null); // command object
 	assert_eq!(
 		values[3],
-		Amf0Value::Object(HashMap::from([
-			("code".to_string(), Amf0Value::String("idk".to_string())),
+		Amf0Value::Object(Amf0Object::from([
 			("level".to_string(), Amf0Value::String("status".to_string())),
+			("code".to_string(), Amf0Value::String("idk".to_string())),
 			("description".to_string(), Amf0Value::String("description".to_string())),
 		]))
 	); // info object
 }
+
+#[test]
+fn test_netstream_write_publish() {
+	let encoder = ChunkEncoder::default();
+	let mut writer = BytesWriter::default();
+
+	NetStreamWriter::write_publish(&encoder, &mut writer, 3.0, 1, "stream-key").unwrap();
+
+	let mut decoder = ChunkDecoder::default();
+	decoder.extend_data(&writer.dispose());
+
+	let chunk = decoder.read_chunk().unwrap().unwrap();
+	assert_eq!(chunk.basic_header.chunk_stream_id, 0x03);
+	assert_eq!(chunk.message_header.msg_type_id as u8, 0x14);
+	assert_eq!(chunk.message_header.msg_stream_id, 1);
+
+	let mut amf0_reader = Amf0Reader::new(chunk.payload);
+	let values = amf0_reader.read_all().unwrap();
+
+	assert_eq!(
+		values,
+		vec![
+			Amf0Value::String("publish".to_string()),    // command name
+			Amf0Value::Number(3.0),                      // transaction id
+			Amf0Value::Null,                             // command object
+			Amf0Value::String("stream-key".to_string()), // stream name
+			Amf0Value::String("live".to_string()),       // publishing type
+		]
+	);
+}
+
+#[test]
+fn test_netstream_write_play() {
+	let encoder = ChunkEncoder::default();
+	let mut writer = BytesWriter::default();
+
+	NetStreamWriter::write_play(&encoder, &mut writer, 3.0, 1, "stream-key").unwrap();
+
+	let mut decoder = ChunkDecoder::default();
+	decoder.extend_data(&writer.dispose());
+
+	let chunk = decoder.read_chunk().unwrap().unwrap();
+	assert_eq!(chunk.basic_header.chunk_stream_id, 0x03);
+	assert_eq!(chunk.message_header.msg_type_id as u8, 0x14);
+	assert_eq!(chunk.message_header.msg_stream_id, 1);
+
+	let mut amf0_reader = Amf0Reader::new(chunk.payload);
+	let values = amf0_reader.read_all().unwrap();
+
+	assert_eq!(
+		values,
+		vec![
+			Amf0Value::String("play".to_string()),       // command name
+			Amf0Value::Number(3.0),                      // transaction id
+			Amf0Value::Null,                             // command object
+			Amf0Value::String("stream-key".to_string()), // stream name
+			Amf0Value::Number(-2.0),                     // start
+		]
+	);
+}
+
+#[test]
+fn test_netstream_write_delete_stream() {
+	let encoder = ChunkEncoder::default();
+	let mut writer = BytesWriter::default();
+
+	NetStreamWriter::write_delete_stream(&encoder, &mut writer, 4.0, 1).unwrap();
+
+	let mut decoder = ChunkDecoder::default();
+	decoder.extend_data(&writer.dispose());
+
+	let chunk = decoder.read_chunk().unwrap().unwrap();
+	assert_eq!(chunk.basic_header.chunk_stream_id, 0x03);
+	assert_eq!(chunk.message_header.msg_type_id as u8, 0x14);
+	assert_eq!(chunk.message_header.msg_stream_id, 0);
+
+	let mut amf0_reader = Amf0Reader::new(chunk.payload);
+	let values = amf0_reader.read_all().unwrap();
+
+	assert_eq!(
+		values,
+		vec![
+			Amf0Value::String("deleteStream".to_string()), // command name
+			Amf0Value::Number(4.0),                        // transaction id
+			Amf0Value::Null,                               // command object
+			Amf0Value::Number(1.0),                        // stream id
+		]
+	);
+}
@@ -1,6 +1,4 @@
-use std::collections::HashMap;
-
-use amf0::{Amf0Value, Amf0Writer};
+use amf0::{Amf0Object, Amf0Value, Amf0Writer};
 use bytesio::bytes_writer::BytesWriter;
 
 use super::errors::NetStreamError;
@@ -14,12 +12,29 @@ impl NetStreamWriter {
 		encoder: &ChunkEncoder,
 		amf0_writer: BytesWriter,
 		writer: &mut BytesWriter,
+	) -> Result<(), NetStreamError> {
+		Self::write_stream_chunk(encoder, amf0_writer, writer, 0)
+	}
+
+	/// Commands such as publish and play must be sent on the message stream
+	/// that was created with createStream.
+	fn write_stream_chunk(
+		encoder: &ChunkEncoder,
+		amf0_writer: BytesWriter,
+		writer: &mut BytesWriter,
+		stream_id: u32,
 	) -> Result<(), NetStreamError> {
 		let data = amf0_writer.dispose();
 
 		encoder.write_chunk(
 			writer,
-			Chunk::new(DefinedChunkStreamID::Command as u32, 0, MessageTypeID::CommandAMF0, 0, data),
+			Chunk::new(
+				DefinedChunkStreamID::Command as u32,
+				0,
+				MessageTypeID::CommandAMF0,
+				stream_id,
+				data,
+			),
 		)?;
 
 		Ok(())
@@ -40,7 +55,7 @@ impl NetStreamWriter {
 		Amf0Writer::write_null(&mut amf0_writer)?;
 		Amf0Writer::write_object(
 			&mut amf0_writer,
-			&HashMap::from([
+			&Amf0Object::from([
 				("level".to_string(), Amf0Value::String(level.to_string())),
 				("code".to_string(), Amf0Value::String(code.to_string())),
 				("description".to_string(), Amf0Value::String(description.to_string())),
@@ -49,4 +64,65 @@ impl NetStreamWriter {
 
 		Self::write_chunk(encoder, amf0_writer, writer)
 	}
+
+	/// Write a publish command, sent by a client that wants to start
+	/// publishing to `stream_name` on the stream `stream_id`.
+	pub fn write_publish(
+		encoder: &ChunkEncoder,
+		writer: &mut BytesWriter,
+		transaction_id: f64,
+		stream_id: u32,
+		stream_name: &str,
+	) -> Result<(), NetStreamError> {
+		let mut amf0_writer = BytesWriter::default();
+
+		Amf0Writer::write_string(&mut amf0_writer, "publish")?;
+		Amf0Writer::write_number(&mut amf0_writer, transaction_id)?;
+		Amf0Writer::write_null(&mut amf0_writer)?;
+		Amf0Writer::write_string(&mut amf0_writer, stream_name)?;
+		// The publishing type, we only ever publish live streams.
+		Amf0Writer::write_string(&mut amf0_writer, "live")?;
+
+		Self::write_stream_chunk(encoder, amf0_writer, writer, stream_id)
+	}
+
+	/// Write a play command, sent by a client that wants to start playing
+	/// `stream_name` on the stream `stream_id`.
+	pub fn write_play(
+		encoder: &ChunkEncoder,
+		writer: &mut BytesWriter,
+		transaction_id: f64,
+		stream_id: u32,
+		stream_name: &str,
+	) -> Result<(), NetStreamError> {
+		let mut amf0_writer = BytesWriter::default();
+
+		Amf0Writer::write_string(&mut amf0_writer, "play")?;
+		Amf0Writer::write_number(&mut amf0_writer, transaction_id)?;
+		Amf0Writer::write_null(&mut amf0_writer)?;
+		Amf0Writer::write_string(&mut amf0_writer, stream_name)?;
+		// Start (-2) means play the live stream if there is one, otherwise the
+		// recorded stream.
+		Amf0Writer::write_number(&mut amf0_writer, -2.0)?;
+
+		Self::write_stream_chunk(encoder, amf0_writer, writer, stream_id)
+	}
+
+	/// Write a deleteStream command, which tells the server we are done with
+	/// the stream `stream_id`.
+	pub fn write_delete_stream(
+		encoder: &ChunkEncoder,
+		writer: &mut BytesWriter,
+		transaction_id: f64,
+		stream_id: u32,
+	) -> Result<(), NetStreamError> {
+		let mut amf0_writer = BytesWriter::default();
+
+		Amf0Writer::write_string(&mut amf0_writer, "deleteStream")?;
+		Amf0Writer::write_number(&mut amf0_writer, transaction_id)?;
+		Amf0Writer::write_null(&mut amf0_writer)?;
+		Amf0Writer::write_number(&mut amf0_writer, stream_id as f64)?;
+
+		Self::write_chunk(encoder, amf0_writer, writer)
+	}
 }
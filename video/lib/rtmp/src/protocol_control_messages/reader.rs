@@ -14,4 +14,18 @@ impl ProtocolControlMessageReader {
 
 		Ok(chunk_size)
 	}
+
+	pub fn read_acknowledgement(data: Bytes) -> Result<u32, ProtocolControlMessageError> {
+		let mut cursor = Cursor::new(data);
+		let sequence_number = cursor.read_u32::<BigEndian>()?;
+
+		Ok(sequence_number)
+	}
+
+	pub fn read_window_acknowledgement_size(data: Bytes) -> Result<u32, ProtocolControlMessageError> {
+		let mut cursor = Cursor::new(data);
+		let window_size = cursor.read_u32::<BigEndian>()?;
+
+		Ok(window_size)
+	}
 }
@@ -21,6 +21,23 @@ fn test_reader_read_set_chunk_size() {
 	assert_eq!(chunk_size, 1);
 }
 
+#[test]
+fn test_reader_read_acknowledgement() {
+	let data = vec![0x00, 0x00, 0x01, 0x00];
+	let sequence_number = ProtocolControlMessageReader::read_acknowledgement(data.into()).unwrap();
+	assert_eq!(sequence_number, 256);
+
+	let data = vec![0x00, 0x00];
+	assert!(ProtocolControlMessageReader::read_acknowledgement(data.into()).is_err());
+}
+
+#[test]
+fn test_reader_read_window_acknowledgement_size() {
+	let data = vec![0x00, 0x26, 0x25, 0xA0];
+	let window_size = ProtocolControlMessageReader::read_window_acknowledgement_size(data.into()).unwrap();
+	assert_eq!(window_size, 2500000);
+}
+
 #[test]
 fn test_writer_write_set_chunk_size() {
 	let encoder = ChunkEncoder::default();
@@ -38,6 +55,23 @@ fn test_writer_write_set_chunk_size() {
 	assert_eq!(chunk.payload, vec![0x00, 0x00, 0x00, 0x01]);
 }
 
+#[test]
+fn test_writer_acknowledgement() {
+	let encoder = ChunkEncoder::default();
+	let mut writer = BytesWriter::default();
+
+	ProtocolControlMessagesWriter::write_acknowledgement(&encoder, &mut writer, 0x01020304).unwrap();
+
+	let mut decoder = ChunkDecoder::default();
+	decoder.extend_data(&writer.dispose());
+
+	let chunk = decoder.read_chunk().unwrap().unwrap();
+	assert_eq!(chunk.basic_header.chunk_stream_id, 0x02);
+	assert_eq!(chunk.message_header.msg_type_id as u8, 0x03);
+	assert_eq!(chunk.message_header.msg_stream_id, 0);
+	assert_eq!(chunk.payload, vec![0x01, 0x02, 0x03, 0x04]);
+}
+
 #[test]
 fn test_writer_window_acknowledgement_size() {
 	let encoder = ChunkEncoder::default();
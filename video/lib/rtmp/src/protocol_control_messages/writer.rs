@@ -31,6 +31,28 @@ impl ProtocolControlMessagesWriter {
 		Ok(())
 	}
 
+	/// Sent when the number of bytes received since the last acknowledgement
+	/// reaches the window size the peer asked for. `sequence_number` is the
+	/// total number of bytes received so far.
+	pub fn write_acknowledgement(
+		encoder: &ChunkEncoder,
+		writer: &mut BytesWriter,
+		sequence_number: u32,
+	) -> Result<(), ProtocolControlMessageError> {
+		encoder.write_chunk(
+			writer,
+			Chunk::new(
+				2, // chunk stream must be 2
+				0, // timestamps are ignored
+				MessageTypeID::Acknowledgement,
+				0, // message stream id is ignored
+				Bytes::from(sequence_number.to_be_bytes().to_vec()),
+			),
+		)?;
+
+		Ok(())
+	}
+
 	pub fn write_window_acknowledgement_size(
 		encoder: &ChunkEncoder,
 		writer: &mut BytesWriter,
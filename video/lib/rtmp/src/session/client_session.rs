@@ -0,0 +1,480 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use amf0::Amf0Value;
+use bytes::Bytes;
+use bytesio::bytes_writer::BytesWriter;
+use bytesio::bytesio::{AsyncReadWrite, BytesIO};
+use bytesio::bytesio_errors::BytesIOError;
+
+use super::errors::SessionError;
+use crate::channels::ChannelData;
+use crate::chunk::{Chunk, ChunkDecoder, ChunkEncoder, DefinedChunkStreamID, CHUNK_SIZE};
+use crate::handshake::{ClientHandshakeState, HandshakeClient};
+use crate::messages::{MessageParser, MessageTypeID, RtmpMessageData};
+use crate::netconnection::NetConnection;
+use crate::netstream::NetStreamWriter;
+use crate::protocol_control_messages::ProtocolControlMessagesWriter;
+use crate::user_control_messages::EventMessagesWriter;
+
+/// The amount of data (in milliseconds) we ask the server to buffer when we
+/// are playing a stream.
+const PLAY_BUFFER_LENGTH: u32 = 1000;
+
+/// How long we wait for the server during the handshake and while waiting for
+/// command responses.
+const COMMAND_TIMEOUT: Duration = Duration::from_millis(2500);
+
+/// A RTMP client session.
+/// This is the counterpart to the server [`Session`](super::Session), it can
+/// either publish a stream to a server or play a stream from a server.
+pub struct ClientSession<S: AsyncReadWrite> {
+	/// The app name is the first part of the url after the host
+	/// For example: rtmp://localhost:1935/live/xyz
+	/// The app name is "live"
+	app_name: String,
+
+	/// The stream name (or stream key) is the part of the url after the app
+	/// name. In the example above the stream name is "xyz"
+	stream_name: String,
+
+	/// The tcUrl sent to the server in the connect command.
+	/// Some servers use this to figure out which vhost we are connecting to.
+	tc_url: String,
+
+	/// Used to read and write data
+	io: BytesIO<S>,
+
+	/// How long we wait for data while playing, publishers can pause for a
+	/// long time (long GOPs, audio only gaps) so there is no limit by default.
+	read_timeout: Option<Duration>,
+
+	/// Sometimes when doing the handshake we read too much data,
+	/// this flag is used to indicate that we have data ready to parse and we
+	/// should not read more data from the stream
+	skip_read: bool,
+
+	/// This is used to read the data from the stream and convert it into rtmp
+	/// messages
+	chunk_decoder: ChunkDecoder,
+	/// This is used to convert rtmp messages into chunks
+	chunk_encoder: ChunkEncoder,
+
+	/// The stream id the server gave us in the createStream response.
+	stream_id: u32,
+
+	/// The transaction id of the last command we sent.
+	transaction_id: f64,
+
+	/// Data we received from the server while we were waiting for a command
+	/// response.
+	pending_data: VecDeque<ChannelData>,
+
+	/// Is Connected
+	is_connected: bool,
+
+	/// Is Publishing
+	is_publishing: bool,
+
+	/// Is Playing
+	is_playing: bool,
+}
+
+impl<S: AsyncReadWrite> ClientSession<S> {
+	/// `host` is the host (and port) `stream` is connected to, it is used to
+	/// build the tcUrl `rtmp://{host}/{app_name}`.
+	pub fn new(stream: S, host: impl AsRef<str>, app_name: impl Into<String>, stream_name: impl Into<String>) -> Self {
+		let app_name = app_name.into();
+
+		Self {
+			tc_url: format!("rtmp://{}/{}", host.as_ref(), app_name),
+			app_name,
+			stream_name: stream_name.into(),
+			io: BytesIO::new(stream),
+			read_timeout: None,
+			skip_read: false,
+			chunk_decoder: ChunkDecoder::default(),
+			chunk_encoder: ChunkEncoder::default(),
+			stream_id: 0,
+			transaction_id: 0.0,
+			pending_data: VecDeque::new(),
+			is_connected: false,
+			is_publishing: false,
+			is_playing: false,
+		}
+	}
+
+	/// Set the tcUrl sent in the connect command.
+	/// By default this is `rtmp://{host}/{app_name}`
+	pub fn with_tc_url(mut self, tc_url: impl Into<String>) -> Self {
+		self.tc_url = tc_url.into();
+		self
+	}
+
+	/// Set how long [`recv_data`](Self::recv_data) waits for the server to
+	/// send data before failing. By default it waits forever.
+	pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+		self.read_timeout = Some(timeout);
+		self
+	}
+
+	/// The stream id the server assigned to our stream.
+	/// This is 0 until we have published or played a stream.
+	pub fn stream_id(&self) -> u32 {
+		self.stream_id
+	}
+
+	/// Connect to the server and start publishing the stream.
+	/// Once this returns data can be sent with
+	/// [`send_data`](Self::send_data).
+	pub async fn publish(&mut self) -> Result<(), SessionError> {
+		if self.is_publishing || self.is_playing {
+			return Err(SessionError::ClientNotReady);
+		}
+
+		self.connect().await?;
+
+		let transaction_id = self.next_transaction_id();
+
+		let mut writer = BytesWriter::default();
+		NetStreamWriter::write_publish(
+			&self.chunk_encoder,
+			&mut writer,
+			transaction_id,
+			self.stream_id,
+			&self.stream_name,
+		)?;
+		self.write_data(writer.dispose()).await?;
+
+		self.wait_for_status("NetStream.Publish.Start").await?;
+
+		self.is_publishing = true;
+
+		Ok(())
+	}
+
+	/// Connect to the server and start playing the stream.
+	/// Once this returns data can be received with
+	/// [`recv_data`](Self::recv_data).
+	pub async fn play(&mut self) -> Result<(), SessionError> {
+		if self.is_publishing || self.is_playing {
+			return Err(SessionError::ClientNotReady);
+		}
+
+		self.connect().await?;
+
+		let transaction_id = self.next_transaction_id();
+
+		let mut writer = BytesWriter::default();
+		EventMessagesWriter::write_set_buffer_length(&self.chunk_encoder, &mut writer, self.stream_id, PLAY_BUFFER_LENGTH)?;
+		NetStreamWriter::write_play(
+			&self.chunk_encoder,
+			&mut writer,
+			transaction_id,
+			self.stream_id,
+			&self.stream_name,
+		)?;
+		self.write_data(writer.dispose()).await?;
+
+		self.wait_for_status("NetStream.Play.Start").await?;
+
+		self.is_playing = true;
+
+		Ok(())
+	}
+
+	/// Send audio, video or metadata to the server.
+	/// The session must be publishing.
+	pub async fn send_data(&mut self, data: ChannelData) -> Result<(), SessionError> {
+		if !self.is_publishing {
+			return Err(SessionError::ClientNotReady);
+		}
+
+		let (chunk_stream_id, msg_type_id) = match &data {
+			ChannelData::Audio { .. } => (DefinedChunkStreamID::Audio, MessageTypeID::Audio),
+			ChannelData::Video { .. } => (DefinedChunkStreamID::Video, MessageTypeID::Video),
+			ChannelData::Metadata { .. } => (DefinedChunkStreamID::Command, MessageTypeID::DataAMF0),
+		};
+
+		let mut writer = BytesWriter::default();
+		self.chunk_encoder.write_chunk(
+			&mut writer,
+			Chunk::new(
+				chunk_stream_id as u32,
+				data.timestamp(),
+				msg_type_id,
+				self.stream_id,
+				data.data().clone(),
+			),
+		)?;
+		self.write_data(writer.dispose()).await?;
+
+		Ok(())
+	}
+
+	/// Receive audio, video or metadata from the server.
+	/// The session must be playing.
+	/// Returns `None` when the server stops the stream or closes the
+	/// connection.
+	pub async fn recv_data(&mut self) -> Result<Option<ChannelData>, SessionError> {
+		if !self.is_playing {
+			return Err(SessionError::ClientNotReady);
+		}
+
+		if let Some(data) = self.pending_data.pop_front() {
+			return Ok(Some(data));
+		}
+
+		loop {
+			let (msg, _, timestamp) = match self.read_message(self.read_timeout).await {
+				Ok(msg) => msg,
+				Err(SessionError::BytesIO(BytesIOError::ClientClosed)) => {
+					tracing::debug!("Server closed the connection");
+					self.is_playing = false;
+					return Ok(None);
+				}
+				Err(e) => return Err(e),
+			};
+
+			match msg {
+				RtmpMessageData::AudioData { data } => return Ok(Some(ChannelData::Audio { timestamp, data })),
+				RtmpMessageData::VideoData { data } => return Ok(Some(ChannelData::Video { timestamp, data })),
+				RtmpMessageData::AmfData { data } => return Ok(Some(ChannelData::Metadata { timestamp, data })),
+				RtmpMessageData::Amf0Command {
+					command_name, others, ..
+				} if command_name == Amf0Value::String("onStatus".to_string()) => {
+					let (_, code) = Self::status_info(&others);
+					if code == "NetStream.Play.Stop" || code == "NetStream.Play.UnpublishNotify" {
+						self.is_playing = false;
+						return Ok(None);
+					}
+				}
+				_ => {}
+			}
+		}
+	}
+
+	/// Tell the server we are done with the stream.
+	pub async fn close(&mut self) -> Result<(), SessionError> {
+		if self.stream_id != 0 {
+			let transaction_id = self.next_transaction_id();
+
+			let mut writer = BytesWriter::default();
+			NetStreamWriter::write_delete_stream(&self.chunk_encoder, &mut writer, transaction_id, self.stream_id)?;
+			self.write_data(writer.dispose()).await?;
+		}
+
+		self.stream_id = 0;
+		self.is_publishing = false;
+		self.is_playing = false;
+
+		Ok(())
+	}
+
+	/// Do the handshake, send the connect command and create a stream.
+	async fn connect(&mut self) -> Result<(), SessionError> {
+		if self.is_connected {
+			return Err(SessionError::ClientNotReady);
+		}
+
+		self.do_handshake().await?;
+
+		tracing::debug!("Handshake complete");
+
+		let transaction_id = self.next_transaction_id();
+
+		let mut writer = BytesWriter::default();
+		NetConnection::write_connect(&self.chunk_encoder, &mut writer, transaction_id, &self.app_name, &self.tc_url)?;
+		self.write_data(writer.dispose()).await?;
+
+		self.wait_for_result(transaction_id).await?;
+
+		let transaction_id = self.next_transaction_id();
+
+		let mut writer = BytesWriter::default();
+		NetConnection::write_create_stream(&self.chunk_encoder, &mut writer, transaction_id)?;
+		self.write_data(writer.dispose()).await?;
+
+		// The stream id is the first value after the command object.
+		self.stream_id = match self.wait_for_result(transaction_id).await?.first() {
+			Some(Amf0Value::Number(stream_id)) => *stream_id as u32,
+			_ => return Err(SessionError::CommandRejected("createStream".to_string())),
+		};
+
+		self.is_connected = true;
+
+		Ok(())
+	}
+
+	/// The client side of the handshake, we send C0 and C1 and then wait for
+	/// S0, S1 and S2 before sending C2.
+	async fn do_handshake(&mut self) -> Result<(), SessionError> {
+		let mut handshaker = HandshakeClient::default();
+
+		let mut writer = BytesWriter::default();
+		handshaker.handshake(&mut writer)?;
+		self.write_data(writer.dispose()).await?;
+
+		let mut bytes_len = 0;
+		while bytes_len < handshaker.required_read_size() {
+			let buf = self.io.read_timeout(COMMAND_TIMEOUT).await?;
+			bytes_len += buf.len();
+			handshaker.extend_data(&buf[..]);
+		}
+
+		let mut writer = BytesWriter::default();
+		handshaker.handshake(&mut writer)?;
+		self.write_data(writer.dispose()).await?;
+
+		debug_assert_eq!(handshaker.state(), ClientHandshakeState::Finish);
+
+		let over_read = handshaker.extract_remaining_bytes();
+		if !over_read.is_empty() {
+			self.skip_read = true;
+			self.chunk_decoder.extend_data(&over_read[..]);
+		}
+
+		self.send_set_chunk_size().await?;
+
+		Ok(())
+	}
+
+	/// Wait for the `_result` of the command with the given transaction id.
+	/// Returns the values after the command object.
+	async fn wait_for_result(&mut self, transaction_id: f64) -> Result<Vec<Amf0Value>, SessionError> {
+		loop {
+			let (msg, _, _) = self.read_message(Some(COMMAND_TIMEOUT)).await?;
+
+			let RtmpMessageData::Amf0Command {
+				command_name,
+				transaction_id: msg_transaction_id,
+				others,
+				..
+			} = msg
+			else {
+				continue;
+			};
+
+			if msg_transaction_id != Amf0Value::Number(transaction_id) {
+				continue;
+			}
+
+			match command_name {
+				Amf0Value::String(name) if name == "_result" => return Ok(others),
+				Amf0Value::String(name) if name == "_error" => {
+					let (_, code) = Self::status_info(&others);
+					return Err(SessionError::CommandRejected(code));
+				}
+				_ => {}
+			}
+		}
+	}
+
+	/// Wait for an onStatus message with the given code.
+	/// Any onStatus message with a level of "error" is treated as a failure.
+	async fn wait_for_status(&mut self, expected_code: &str) -> Result<(), SessionError> {
+		loop {
+			let (msg, _, timestamp) = self.read_message(Some(COMMAND_TIMEOUT)).await?;
+
+			match msg {
+				RtmpMessageData::Amf0Command {
+					command_name, others, ..
+				} if command_name == Amf0Value::String("onStatus".to_string()) => {
+					let (level, code) = Self::status_info(&others);
+					if level == "error" {
+						return Err(SessionError::CommandRejected(code));
+					}
+
+					if code == expected_code {
+						return Ok(());
+					}
+				}
+				RtmpMessageData::AudioData { data } => self.pending_data.push_back(ChannelData::Audio { timestamp, data }),
+				RtmpMessageData::VideoData { data } => self.pending_data.push_back(ChannelData::Video { timestamp, data }),
+				RtmpMessageData::AmfData { data } => self.pending_data.push_back(ChannelData::Metadata { timestamp, data }),
+				_ => {}
+			}
+		}
+	}
+
+	/// Extract the level and code from the info object of an onStatus or
+	/// _error message.
+	fn status_info(others: &[Amf0Value]) -> (String, String) {
+		let Some(Amf0Value::Object(info)) = others.first() else {
+			return (String::new(), String::new());
+		};
+
+		let get = |key: &str| match info.get(key) {
+			Some(Amf0Value::String(value)) => value.clone(),
+			_ => String::new(),
+		};
+
+		(get("level"), get("code"))
+	}
+
+	/// Read the next message from the server, waiting at most `timeout` for
+	/// each read. Protocol control messages which affect how we decode chunks
+	/// are handled here.
+	async fn read_message(&mut self, timeout: Option<Duration>) -> Result<(RtmpMessageData, u32, u32), SessionError> {
+		loop {
+			while let Some(chunk) = self.chunk_decoder.read_chunk()? {
+				let timestamp = chunk.message_header.timestamp;
+				let msg_stream_id = chunk.message_header.msg_stream_id;
+
+				match MessageParser::parse(chunk)? {
+					Some(RtmpMessageData::SetChunkSize { chunk_size }) => {
+						self.on_set_chunk_size(chunk_size as usize)?;
+					}
+					Some(msg) => return Ok((msg, msg_stream_id, timestamp)),
+					None => {}
+				}
+			}
+
+			// If we have data ready to parse, parse it
+			if self.skip_read {
+				self.skip_read = false;
+			} else {
+				let data = match timeout {
+					Some(timeout) => self.io.read_timeout(timeout).await?,
+					None => self.io.read().await?,
+				};
+				self.chunk_decoder.extend_data(&data[..]);
+			}
+		}
+	}
+
+	/// on_set_chunk_size is called when we receive a set chunk size message
+	/// from the server We then update the chunk size of the unpacketizer
+	fn on_set_chunk_size(&mut self, chunk_size: usize) -> Result<(), SessionError> {
+		if self.chunk_decoder.update_max_chunk_size(chunk_size) {
+			Ok(())
+		} else {
+			Err(SessionError::InvalidChunkSize(chunk_size))
+		}
+	}
+
+	/// Set the client chunk size to the server
+	async fn send_set_chunk_size(&mut self) -> Result<(), SessionError> {
+		let mut writer = BytesWriter::default();
+		ProtocolControlMessagesWriter::write_set_chunk_size(&self.chunk_encoder, &mut writer, CHUNK_SIZE as u32)?;
+		self.chunk_encoder.set_chunk_size(CHUNK_SIZE);
+		self.write_data(writer.dispose()).await?;
+
+		Ok(())
+	}
+
+	fn next_transaction_id(&mut self) -> f64 {
+		self.transaction_id += 1.0;
+		self.transaction_id
+	}
+
+	/// write_data is a helper function to write data to the underlying
+	/// connection. If the data is empty, it will not write anything.
+	async fn write_data(&mut self, data: Bytes) -> Result<(), SessionError> {
+		if !data.is_empty() {
+			self.io.write_timeout(data, Duration::from_secs(2)).await?;
+		}
+
+		Ok(())
+	}
+}
@@ -1,3 +1,12 @@
+use std::time::Duration;
+
+/// How long a playing client can be quiet before we send it a ping request
+pub(super) const PING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long we wait for a response to a ping request before we consider the
+/// client gone
+pub(super) const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 
 /// RTMP Commands are defined in the RTMP specification
@@ -3,7 +3,7 @@ use std::fmt;
 use bytesio::bytesio_errors::BytesIOError;
 
 use crate::channels::UniqueID;
-use crate::chunk::ChunkDecodeError;
+use crate::chunk::{ChunkDecodeError, ChunkEncodeError};
 use crate::handshake::HandshakeError;
 use crate::macros::from_error;
 use crate::messages::MessageError;
@@ -18,6 +18,7 @@ pub enum SessionError {
 	Handshake(HandshakeError),
 	Message(MessageError),
 	ChunkDecode(ChunkDecodeError),
+	ChunkEncode(ChunkEncodeError),
 	ProtocolControlMessage(ProtocolControlMessageError),
 	NetStream(NetStreamError),
 	NetConnection(NetConnectionError),
@@ -27,16 +28,21 @@ pub enum SessionError {
 	NoAppName,
 	NoStreamName,
 	PublishRequestDenied,
+	PlayRequestDenied,
 	ConnectRequestDenied,
 	PlayNotSupported,
 	PublisherDropped,
 	InvalidChunkSize(usize),
+	ClientNotReady,
+	CommandRejected(String),
+	PingTimeout,
 }
 
 from_error!(SessionError, Self::BytesIO, BytesIOError);
 from_error!(SessionError, Self::Handshake, HandshakeError);
 from_error!(SessionError, Self::Message, MessageError);
 from_error!(SessionError, Self::ChunkDecode, ChunkDecodeError);
+from_error!(SessionError, Self::ChunkEncode, ChunkEncodeError);
 from_error!(SessionError, Self::ProtocolControlMessage, ProtocolControlMessageError);
 from_error!(SessionError, Self::NetStream, NetStreamError);
 from_error!(SessionError, Self::NetConnection, NetConnectionError);
@@ -49,6 +55,7 @@ impl fmt::Display for SessionError {
 			Self::Handshake(error) => write!(f, "handshake error: {}", error),
 			Self::Message(error) => write!(f, "message error: {}", error),
 			Self::ChunkDecode(error) => write!(f, "chunk decode error: {}", error),
+			Self::ChunkEncode(error) => write!(f, "chunk encode error: {}", error),
 			Self::ProtocolControlMessage(error) => {
 				write!(f, "protocol control message error: {}", error)
 			}
@@ -60,10 +67,14 @@ impl fmt::Display for SessionError {
 			Self::NoAppName => write!(f, "no app name"),
 			Self::NoStreamName => write!(f, "no stream name"),
 			Self::PublishRequestDenied => write!(f, "publish request denied"),
+			Self::PlayRequestDenied => write!(f, "play request denied"),
 			Self::ConnectRequestDenied => write!(f, "connect request denied"),
 			Self::InvalidChunkSize(size) => write!(f, "invalid chunk size: {}", size),
 			Self::PlayNotSupported => write!(f, "play not supported"),
 			Self::PublisherDropped => write!(f, "publisher dropped"),
+			Self::ClientNotReady => write!(f, "client not ready"),
+			Self::CommandRejected(code) => write!(f, "command rejected: {}", code),
+			Self::PingTimeout => write!(f, "ping timeout"),
 		}
 	}
 }
@@ -1,24 +1,24 @@
-use std::collections::HashMap;
 use std::time::Duration;
 
-use amf0::Amf0Value;
+use amf0::{Amf0Object, Amf0Value};
 use bytes::Bytes;
 use bytesio::bytes_writer::BytesWriter;
 use bytesio::bytesio::{AsyncReadWrite, BytesIO};
 use bytesio::bytesio_errors::BytesIOError;
 use tokio::sync::oneshot;
+use tokio::time::Instant;
 use utils::prelude::FutureTimeout;
 
-use super::define::RtmpCommand;
+use super::define::{RtmpCommand, PING_INTERVAL, PING_TIMEOUT};
 use super::errors::SessionError;
-use crate::channels::{ChannelData, DataProducer, PublishRequest, UniqueID};
-use crate::chunk::{ChunkDecoder, ChunkEncoder, CHUNK_SIZE};
+use crate::channels::{ChannelData, DataConsumer, DataProducer, PlayProducer, PlayRequest, PublishRequest, UniqueID};
+use crate::chunk::{Chunk, ChunkDecoder, ChunkEncoder, DefinedChunkStreamID, CHUNK_SIZE};
 use crate::handshake::{HandshakeServer, ServerHandshakeState};
-use crate::messages::{MessageParser, RtmpMessageData};
+use crate::messages::{MessageParser, MessageTypeID, RtmpMessageData};
 use crate::netconnection::NetConnection;
 use crate::netstream::NetStreamWriter;
 use crate::protocol_control_messages::ProtocolControlMessagesWriter;
-use crate::user_control_messages::EventMessagesWriter;
+use crate::user_control_messages::{EventMessagesWriter, UserControlEvent};
 use crate::{handshake, PublishProducer};
 
 pub struct Session<S: AsyncReadWrite> {
@@ -63,6 +63,35 @@ pub struct Session<S: AsyncReadWrite> {
 	/// when the publisher connects and tries to publish a stream, we need to
 	/// send a publish request to the server
 	publish_request_producer: PublishProducer,
+
+	/// when a client connects and tries to play a stream, we need to send a
+	/// play request to the server. If this is not set play is not supported.
+	play_request_producer: Option<PlayProducer>,
+
+	/// Data Consumer, this is set when the client is playing a stream
+	data_consumer: Option<DataConsumer>,
+
+	/// The total number of bytes we received from the client, this is the
+	/// sequence number of the acknowledgements we send (wraps around)
+	bytes_received: u32,
+
+	/// The number of bytes we had received when we sent the last
+	/// acknowledgement
+	last_acknowledgement: u32,
+
+	/// The client tells us how often it wants an acknowledgement with a window
+	/// acknowledgement size message. Some encoders stop sending data until
+	/// they get one.
+	acknowledgement_window_size: Option<u32>,
+
+	/// When the session was created, ping timestamps are relative to this
+	created_at: Instant,
+
+	/// When we last received data from the client
+	last_read_at: Instant,
+
+	/// When we sent a ping request the client has not responded to yet
+	ping_sent_at: Option<Instant>,
 }
 
 impl<S: AsyncReadWrite> Session<S> {
@@ -80,9 +109,24 @@ impl<S: AsyncReadWrite> Session<S> {
 			stream_id: 0,
 			is_publishing: false,
 			publish_request_producer,
+			play_request_producer: None,
+			data_consumer: None,
+			bytes_received: 0,
+			last_acknowledgement: 0,
+			acknowledgement_window_size: None,
+			created_at: Instant::now(),
+			last_read_at: Instant::now(),
+			ping_sent_at: None,
 		}
 	}
 
+	/// Allow clients to play streams on this session.
+	/// Each play request is sent to the given producer.
+	pub fn with_play_request_producer(mut self, play_request_producer: PlayProducer) -> Self {
+		self.play_request_producer = Some(play_request_producer);
+		self
+	}
+
 	pub fn uid(&self) -> Option<UniqueID> {
 		self.uid
 	}
@@ -146,7 +190,7 @@ impl<S: AsyncReadWrite> Session<S> {
 
 			if !over_read.is_empty() {
 				self.skip_read = true;
-				self.chunk_decoder.extend_data(&over_read[..]);
+				self.on_read(&over_read[..]).await?;
 			}
 
 			self.send_set_chunk_size().await?;
@@ -170,9 +214,38 @@ impl<S: AsyncReadWrite> Session<S> {
 		// If we have data ready to parse, parse it
 		if self.skip_read {
 			self.skip_read = false;
+		} else if let Some(data_consumer) = &mut self.data_consumer {
+			// A client that is playing a stream does not have to send us anything,
+			// so we cannot use a read timeout here. Instead we wait for either data
+			// from the client or data from the stream to send to the client.
+			// If the client is quiet for too long we ping it, so we notice when it
+			// is gone.
+			let ping_deadline = match self.ping_sent_at {
+				Some(ping_sent_at) => ping_sent_at + PING_TIMEOUT,
+				None => self.last_read_at + PING_INTERVAL,
+			};
+
+			tokio::select! {
+				data = self.io.read() => {
+					self.on_read(&data?[..]).await?;
+				}
+				data = data_consumer.recv() => {
+					match data {
+						Some(data) => self.send_play_data(data).await?,
+						None => self.stop_playing().await?,
+					}
+
+					return Ok(true);
+				}
+				_ = tokio::time::sleep_until(ping_deadline) => {
+					self.on_ping_deadline().await?;
+
+					return Ok(true);
+				}
+			}
 		} else {
 			let data = self.io.read_timeout(Duration::from_millis(2500)).await?;
-			self.chunk_decoder.extend_data(&data[..]);
+			self.on_read(&data[..]).await?;
 		}
 
 		self.parse_chunks().await?;
@@ -180,20 +253,85 @@ impl<S: AsyncReadWrite> Session<S> {
 		Ok(true)
 	}
 
+	/// on_read is called with the data we read from the client
+	/// We keep track of how much data we received so we can send
+	/// acknowledgements when the window the client asked for is full
+	async fn on_read(&mut self, data: &[u8]) -> Result<(), SessionError> {
+		self.chunk_decoder.extend_data(data);
+
+		// Any data from the client means it is still alive
+		self.last_read_at = Instant::now();
+		self.ping_sent_at = None;
+
+		self.bytes_received = self.bytes_received.wrapping_add(data.len() as u32);
+
+		let Some(window_size) = self.acknowledgement_window_size else {
+			return Ok(());
+		};
+
+		if self.bytes_received.wrapping_sub(self.last_acknowledgement) >= window_size {
+			let mut writer = BytesWriter::default();
+			ProtocolControlMessagesWriter::write_acknowledgement(&self.chunk_encoder, &mut writer, self.bytes_received)?;
+			self.write_data(writer.dispose()).await?;
+
+			self.last_acknowledgement = self.bytes_received;
+		}
+
+		Ok(())
+	}
+
+	/// on_ping_deadline is called when a playing client has not sent us
+	/// anything for a while. The first time we send a ping request, if the
+	/// client still has not sent anything by the next deadline it is gone.
+	async fn on_ping_deadline(&mut self) -> Result<(), SessionError> {
+		if self.ping_sent_at.is_some() {
+			return Err(SessionError::PingTimeout);
+		}
+
+		let mut writer = BytesWriter::default();
+		EventMessagesWriter::write_ping_request(&self.chunk_encoder, &mut writer, self.ping_timestamp())?;
+		self.write_data(writer.dispose()).await?;
+
+		self.ping_sent_at = Some(Instant::now());
+
+		Ok(())
+	}
+
+	/// The timestamp we put in ping requests, in milliseconds since the session
+	/// was created
+	fn ping_timestamp(&self) -> u32 {
+		self.created_at.elapsed().as_millis() as u32
+	}
+
 	/// Parse data from the client into rtmp messages and process them
 	async fn parse_chunks(&mut self) -> Result<(), SessionError> {
 		while let Some(chunk) = self.chunk_decoder.read_chunk()? {
-			let timestamp = chunk.message_header.timestamp;
-			let msg_stream_id = chunk.message_header.msg_stream_id;
-
-			if let Some(msg) = MessageParser::parse(chunk)? {
-				self.process_messages(msg, msg_stream_id, timestamp).await?;
+			// Aggregate messages contain several audio, video or data messages,
+			// which we process one by one
+			if chunk.message_header.msg_type_id == MessageTypeID::Aggregate {
+				for chunk in MessageParser::split_aggregate(chunk)? {
+					self.parse_chunk(chunk).await?;
+				}
+			} else {
+				self.parse_chunk(chunk).await?;
 			}
 		}
 
 		Ok(())
 	}
 
+	/// Parse a single chunk into an rtmp message and process it
+	async fn parse_chunk(&mut self, chunk: Chunk) -> Result<(), SessionError> {
+		let timestamp = chunk.message_header.timestamp;
+		let msg_stream_id = chunk.message_header.msg_stream_id;
+
+		if let Some(msg) = MessageParser::parse(chunk)? {
+			self.process_messages(msg, msg_stream_id, timestamp).await?;
+		}
+
+		Ok(())
+	}
+
 	/// Process rtmp messages
 	async fn process_messages(
 		&mut self,
@@ -223,6 +361,35 @@ impl<S: AsyncReadWrite> Session<S> {
 			RtmpMessageData::AmfData { data } => {
 				self.on_data(stream_id, ChannelData::Metadata { timestamp, data }).await?;
 			}
+			RtmpMessageData::Acknowledgement { sequence_number } => {
+				// We do not wait for acknowledgements before sending more data
+				tracing::trace!("Client acknowledged {} bytes", sequence_number);
+			}
+			RtmpMessageData::WindowAcknowledgementSize { window_size } => {
+				self.acknowledgement_window_size = Some(window_size).filter(|size| *size > 0);
+			}
+			RtmpMessageData::UserControlEvent { event } => {
+				self.on_user_control_event(event).await?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// on_user_control_event is called when we receive a user control message
+	/// from the client. We only care about pings, the other events are
+	/// informational.
+	async fn on_user_control_event(&mut self, event: UserControlEvent) -> Result<(), SessionError> {
+		match event {
+			UserControlEvent::PingRequest { timestamp } => {
+				let mut writer = BytesWriter::default();
+				EventMessagesWriter::write_ping_response(&self.chunk_encoder, &mut writer, timestamp)?;
+				self.write_data(writer.dispose()).await?;
+			}
+			UserControlEvent::PingResponse { timestamp } => {
+				tracing::trace!("Ping round trip time: {}ms", self.ping_timestamp().wrapping_sub(timestamp));
+			}
+			_ => {}
 		}
 
 		Ok(())
@@ -257,6 +424,70 @@ impl<S: AsyncReadWrite> Session<S> {
 		Ok(())
 	}
 
+	/// send_play_data is called when we receive data from the stream the
+	/// client is playing. We then forward the data to the client.
+	async fn send_play_data(&mut self, data: ChannelData) -> Result<(), SessionError> {
+		let timestamp = data.timestamp();
+
+		let (chunk_stream_id, msg_type_id, payload) = match data {
+			ChannelData::Audio { data, .. } => (DefinedChunkStreamID::Audio, MessageTypeID::Audio, data),
+			ChannelData::Video { data, .. } => (DefinedChunkStreamID::Video, MessageTypeID::Video, data),
+			ChannelData::Metadata { data, .. } => (
+				DefinedChunkStreamID::Command,
+				MessageTypeID::DataAMF0,
+				Self::strip_set_data_frame(data),
+			),
+		};
+
+		let mut writer = BytesWriter::default();
+		self.chunk_encoder.write_chunk(
+			&mut writer,
+			Chunk::new(chunk_stream_id as u32, timestamp, msg_type_id, self.stream_id, payload),
+		)?;
+		self.write_data(writer.dispose()).await?;
+
+		Ok(())
+	}
+
+	/// Publishers send metadata as `@setDataFrame("onMetaData", {...})`,
+	/// however players expect `onMetaData({...})`. So we strip the
+	/// `@setDataFrame` string if it is there.
+	fn strip_set_data_frame(data: Bytes) -> Bytes {
+		// AMF0 string marker, followed by a 2 byte length and the string itself.
+		const SET_DATA_FRAME: &[u8] = b"\x02\x00\x0d@setDataFrame";
+
+		if data.starts_with(SET_DATA_FRAME) {
+			data.slice(SET_DATA_FRAME.len()..)
+		} else {
+			data
+		}
+	}
+
+	/// stop_playing is called when the stream the client is playing has ended
+	/// (ie. the publisher disconnected). We tell the client that the stream is
+	/// over, however the client stays connected.
+	async fn stop_playing(&mut self) -> Result<(), SessionError> {
+		self.data_consumer = None;
+
+		let mut writer = BytesWriter::default();
+		EventMessagesWriter::write_stream_eof(&self.chunk_encoder, &mut writer, self.stream_id)?;
+
+		NetStreamWriter::write_on_status(
+			&self.chunk_encoder,
+			&mut writer,
+			0.0,
+			"status",
+			"NetStream.Play.UnpublishNotify",
+			"",
+		)?;
+
+		NetStreamWriter::write_on_status(&self.chunk_encoder, &mut writer, 0.0, "status", "NetStream.Play.Stop", "")?;
+
+		self.write_data(writer.dispose()).await?;
+
+		Ok(())
+	}
+
 	/// on_amf0_command_message is called when we receive an AMF0 command
 	/// message from the client We then handle the command message
 	async fn on_amf0_command_message(
@@ -279,7 +510,7 @@ impl<S: AsyncReadWrite> Session<S> {
 
 		let obj = match command_object {
 			Amf0Value::Object(obj) => obj,
-			_ => HashMap::new(),
+			_ => Amf0Object::new(),
 		};
 
 		match cmd {
@@ -293,12 +524,18 @@ impl<S: AsyncReadWrite> Session<S> {
 				self.on_command_delete_stream(transaction_id, stream_id, obj, others).await?;
 			}
 			RtmpCommand::Play => {
-				return Err(SessionError::PlayNotSupported);
+				self.on_command_play(transaction_id, stream_id, obj, others).await?;
 			}
 			RtmpCommand::Publish => {
 				self.on_command_publish(transaction_id, stream_id, obj, others).await?;
 			}
-			RtmpCommand::CloseStream | RtmpCommand::ReleaseStream => {
+			RtmpCommand::CloseStream => {
+				// Players send closeStream on the stream they want to stop playing
+				if self.stream_id == stream_id && self.data_consumer.is_some() {
+					self.data_consumer = None;
+				}
+			}
+			RtmpCommand::ReleaseStream => {
 				// Not sure what this is for
 			}
 			RtmpCommand::Unknown(_) => {}
@@ -324,7 +561,7 @@ impl<S: AsyncReadWrite> Session<S> {
 		&mut self,
 		transaction_id: f64,
 		_stream_id: u32,
-		command_obj: HashMap<String, Amf0Value>,
+		command_obj: Amf0Object,
 		_others: Vec<Amf0Value>,
 	) -> Result<(), SessionError> {
 		let mut writer = BytesWriter::default();
@@ -352,15 +589,19 @@ impl<S: AsyncReadWrite> Session<S> {
 
 		self.app_name = Some(app_name.to_owned());
 
-		// The only AMF encoding supported by this server is AMF0
-		// So we ignore the objectEncoding value sent by the client
-		// and always use AMF0
+		// We understand both AMF0 and AMF3 commands, so we accept the
+		// objectEncoding the client asks for. AMF3 commands are converted to
+		// AMF0 by the message parser, and the messages we send are always AMF0,
+		// which every client has to understand regardless of the encoding.
+		// Most encoders only use AMF0:
 		// - OBS does not support AMF3 (https://github.com/obsproject/obs-studio/blob/1be1f51635ac85b3ad768a88b3265b192bd0bf18/plugins/obs-outputs/librtmp/rtmp.c#L1737)
 		// - Ffmpeg does not support AMF3 either (https://github.com/FFmpeg/FFmpeg/blob/c125860892e931d9b10f88ace73c91484815c3a8/libavformat/rtmpproto.c#L569)
-		// - NginxRTMP does not support AMF3 (https://github.com/arut/nginx-rtmp-module/issues/313)
-		// - SRS does not support AMF3 (https://github.com/ossrs/srs/blob/dcd02fe69cdbd7f401a7b8d139d95b522deb55b1/trunk/src/protocol/srs_protocol_rtmp_stack.cpp#L599)
-		// However, the new enhanced-rtmp-v1 spec from YouTube does encourage the use of AMF3 over AMF0 (https://github.com/veovera/enhanced-rtmp)
-		// We will eventually support this spec but for now we will stick to AMF0
+		// But Flash based encoders default to AMF3, and the enhanced-rtmp-v1 spec from YouTube encourages the use of AMF3 over AMF0 (https://github.com/veovera/enhanced-rtmp)
+		let object_encoding = match command_obj.get("objectEncoding") {
+			Some(Amf0Value::Number(encoding)) if *encoding == 3.0 => 3.0,
+			_ => 0.0,
+		};
+
 		NetConnection::write_connect_response(
 			&self.chunk_encoder,
 			&mut writer,
@@ -370,7 +611,7 @@ impl<S: AsyncReadWrite> Session<S> {
 			"NetConnection.Connect.Success",
 			"status", // Again not sure what this is but other media servers use it.
 			"Connection Succeeded.",
-			0.0,
+			object_encoding,
 		)?;
 
 		self.write_data(writer.dispose()).await?;
@@ -386,7 +627,7 @@ impl<S: AsyncReadWrite> Session<S> {
 		&mut self,
 		transaction_id: f64,
 		_stream_id: u32,
-		_command_obj: HashMap<String, Amf0Value>,
+		_command_obj: Amf0Object,
 		_others: Vec<Amf0Value>,
 	) -> Result<(), SessionError> {
 		let mut writer = BytesWriter::default();
@@ -405,7 +646,7 @@ impl<S: AsyncReadWrite> Session<S> {
 		&mut self,
 		transaction_id: f64,
 		_stream_id: u32,
-		_command_obj: HashMap<String, Amf0Value>,
+		_command_obj: Amf0Object,
 		others: Vec<Amf0Value>,
 	) -> Result<(), SessionError> {
 		let mut writer = BytesWriter::default();
@@ -420,6 +661,11 @@ impl<S: AsyncReadWrite> Session<S> {
 			self.is_publishing = false;
 		}
 
+		if self.stream_id == stream_id && self.data_consumer.is_some() {
+			self.stream_id = 0;
+			self.data_consumer = None;
+		}
+
 		NetStreamWriter::write_on_status(
 			&self.chunk_encoder,
 			&mut writer,
@@ -441,7 +687,7 @@ impl<S: AsyncReadWrite> Session<S> {
 		&mut self,
 		transaction_id: f64,
 		stream_id: u32,
-		_command_obj: HashMap<String, Amf0Value>,
+		_command_obj: Amf0Object,
 		others: Vec<Amf0Value>,
 	) -> Result<(), SessionError> {
 		let stream_name = match others.first() {
@@ -496,6 +742,78 @@ impl<S: AsyncReadWrite> Session<S> {
 		Ok(())
 	}
 
+	/// on_command_play is called when we receive a amf0 command message with
+	/// the name "play" play commands are used to play a stream from the
+	/// server ie. the user wants to watch a stream
+	async fn on_command_play(
+		&mut self,
+		transaction_id: f64,
+		stream_id: u32,
+		_command_obj: Amf0Object,
+		others: Vec<Amf0Value>,
+	) -> Result<(), SessionError> {
+		let Some(play_request_producer) = &self.play_request_producer else {
+			return Err(SessionError::PlayNotSupported);
+		};
+
+		let stream_name = match others.first() {
+			Some(Amf0Value::String(val)) => val,
+			_ => {
+				return Err(SessionError::NoStreamName);
+			}
+		};
+
+		let Some(app_name) = &self.app_name else {
+			return Err(SessionError::NoAppName);
+		};
+
+		let (response, waiter) = oneshot::channel();
+
+		if play_request_producer
+			.send(PlayRequest {
+				app_name: app_name.clone(),
+				stream_name: stream_name.clone(),
+				response,
+			})
+			.await
+			.is_err()
+		{
+			return Err(SessionError::PlayRequestDenied);
+		}
+
+		let Ok(data_consumer) = waiter.await else {
+			return Err(SessionError::PlayRequestDenied);
+		};
+
+		self.data_consumer = Some(data_consumer);
+		self.stream_id = stream_id;
+
+		let mut writer = BytesWriter::default();
+		EventMessagesWriter::write_stream_begin(&self.chunk_encoder, &mut writer, stream_id)?;
+
+		NetStreamWriter::write_on_status(
+			&self.chunk_encoder,
+			&mut writer,
+			transaction_id,
+			"status",
+			"NetStream.Play.Reset",
+			"",
+		)?;
+
+		NetStreamWriter::write_on_status(
+			&self.chunk_encoder,
+			&mut writer,
+			transaction_id,
+			"status",
+			"NetStream.Play.Start",
+			"",
+		)?;
+
+		self.write_data(writer.dispose()).await?;
+
+		Ok(())
+	}
+
 	/// write_data is a helper function to write data to the underlying
 	/// connection. If the data is empty, it will not write anything.
 	/// This is to avoid writing empty bytes to the underlying connection.
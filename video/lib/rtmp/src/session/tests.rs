@@ -45,6 +45,9 @@ fn test_error_display() {
 		"event messages error: chunk encode error: unknown read state"
 	);
 
+	let error = SessionError::ChunkEncode(ChunkEncodeError::UnknownReadState);
+	assert_eq!(error.to_string(), "chunk encode error: unknown read state");
+
 	let error = SessionError::UnknownStreamID(0);
 	assert_eq!(error.to_string(), "unknown stream id: 0");
 
@@ -63,6 +66,9 @@ fn test_error_display() {
 	let error = SessionError::PublishRequestDenied;
 	assert_eq!(error.to_string(), "publish request denied");
 
+	let error = SessionError::PlayRequestDenied;
+	assert_eq!(error.to_string(), "play request denied");
+
 	let error = SessionError::ConnectRequestDenied;
 	assert_eq!(error.to_string(), "connect request denied");
 
@@ -74,4 +80,13 @@ fn test_error_display() {
 
 	let error = SessionError::InvalidChunkSize(123);
 	assert_eq!(error.to_string(), "invalid chunk size: 123");
+
+	let error = SessionError::ClientNotReady;
+	assert_eq!(error.to_string(), "client not ready");
+
+	let error = SessionError::CommandRejected("NetStream.Publish.BadName".to_string());
+	assert_eq!(error.to_string(), "command rejected: NetStream.Publish.BadName");
+
+	let error = SessionError::PingTimeout;
+	assert_eq!(error.to_string(), "ping timeout");
 }
@@ -6,7 +6,7 @@ use tokio::sync::mpsc;
 use utils::prelude::FutureTimeout;
 
 use crate::channels::{ChannelData, UniqueID};
-use crate::Session;
+use crate::{ClientSession, Session};
 
 #[tokio::test]
 async fn test_basic_rtmp_clean() {
@@ -199,3 +199,236 @@ async fn test_basic_rtmp_unclean() {
 			.expect("failed to handle ffmpeg connection")
 	);
 }
+
+#[tokio::test]
+async fn test_client_publish() {
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("failed to bind");
+	let addr = listener.local_addr().unwrap();
+
+	let client_handle = tokio::spawn(async move {
+		let stream = tokio::net::TcpStream::connect(addr).await.expect("failed to connect");
+		let mut client = ClientSession::new(stream, addr.to_string(), "live", "stream-key");
+
+		client.publish().await.expect("failed to publish");
+		assert_eq!(client.stream_id(), 1);
+
+		client
+			.send_data(ChannelData::Metadata {
+				timestamp: 0,
+				data: vec![0x02, 0x00, 0x00].into(),
+			})
+			.await
+			.expect("failed to send metadata");
+
+		for i in 0..10 {
+			client
+				.send_data(ChannelData::Video {
+					timestamp: i * 33,
+					// Larger than a single chunk
+					data: vec![i as u8; 10000].into(),
+				})
+				.await
+				.expect("failed to send video");
+
+			client
+				.send_data(ChannelData::Audio {
+					timestamp: i * 33,
+					data: vec![i as u8; 100].into(),
+				})
+				.await
+				.expect("failed to send audio");
+		}
+
+		client.close().await.expect("failed to close");
+	});
+
+	let (stream, _) = listener
+		.accept()
+		.timeout(Duration::from_millis(1000))
+		.await
+		.expect("timedout")
+		.expect("failed to accept");
+
+	let (session_handle, mut data_reciever, mut event_reciever) = {
+		let (event_producer, event_reciever) = mpsc::channel(1);
+		let (data_producer, data_reciever) = mpsc::channel(128);
+		let mut session = Session::new(stream, data_producer, event_producer);
+
+		(
+			tokio::spawn(async move { session.run().await }),
+			data_reciever,
+			event_reciever,
+		)
+	};
+
+	let event = event_reciever
+		.recv()
+		.timeout(Duration::from_millis(1000))
+		.await
+		.expect("timedout")
+		.expect("failed to recv event");
+
+	assert_eq!(event.app_name, "live");
+	assert_eq!(event.stream_name, "stream-key");
+
+	event.response.send(UniqueID::new_v4()).expect("failed to send response");
+
+	let mut video = 0;
+	let mut audio = 0;
+	let mut metadata = 0;
+
+	while let Some(data) = data_reciever
+		.recv()
+		.timeout(Duration::from_millis(1000))
+		.await
+		.expect("timedout")
+	{
+		match data {
+			ChannelData::Video { timestamp, data } => {
+				assert_eq!(timestamp, video * 33);
+				assert_eq!(data.len(), 10000);
+				video += 1;
+			}
+			ChannelData::Audio { timestamp, data } => {
+				assert_eq!(timestamp, audio * 33);
+				assert_eq!(data.len(), 100);
+				audio += 1;
+			}
+			ChannelData::Metadata { .. } => metadata += 1,
+		}
+	}
+
+	assert_eq!(video, 10);
+	assert_eq!(audio, 10);
+	assert_eq!(metadata, 1);
+
+	client_handle.await.expect("client panicked");
+
+	// The client deleted the stream before disconnecting so this is a clean
+	// disconnect
+	assert!(
+		session_handle
+			.await
+			.expect("failed to join handle")
+			.expect("failed to handle client connection")
+	);
+}
+
+#[tokio::test]
+async fn test_client_play() {
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("failed to bind");
+	let addr = listener.local_addr().unwrap();
+
+	let client_handle = tokio::spawn(async move {
+		let stream = tokio::net::TcpStream::connect(addr).await.expect("failed to connect");
+		let mut client = ClientSession::new(stream, addr.to_string(), "live", "stream-key");
+
+		client.play().await.expect("failed to play");
+
+		let mut received = Vec::new();
+		while let Some(data) = client.recv_data().await.expect("failed to recv data") {
+			received.push(data);
+		}
+
+		client.close().await.expect("failed to close");
+
+		received
+	});
+
+	let (stream, _) = listener
+		.accept()
+		.timeout(Duration::from_millis(1000))
+		.await
+		.expect("timedout")
+		.expect("failed to accept");
+
+	let (session_handle, mut event_reciever) = {
+		let (publish_producer, _) = mpsc::channel(1);
+		let (play_producer, play_reciever) = mpsc::channel(1);
+		let (data_producer, _) = mpsc::channel(1);
+		let mut session = Session::new(stream, data_producer, publish_producer).with_play_request_producer(play_producer);
+
+		(tokio::spawn(async move { session.run().await }), play_reciever)
+	};
+
+	let event = event_reciever
+		.recv()
+		.timeout(Duration::from_millis(1000))
+		.await
+		.expect("timedout")
+		.expect("failed to recv event");
+
+	assert_eq!(event.app_name, "live");
+	assert_eq!(event.stream_name, "stream-key");
+
+	let (data_producer, data_consumer) = mpsc::channel(128);
+	event.response.send(data_consumer).expect("failed to send response");
+
+	let mut metadata = vec![0x02, 0x00, 0x0d];
+	metadata.extend_from_slice(b"@setDataFrame");
+	metadata.extend_from_slice(&[0x02, 0x00, 0x0a]);
+	metadata.extend_from_slice(b"onMetaData");
+
+	data_producer
+		.send(ChannelData::Metadata {
+			timestamp: 0,
+			data: metadata.into(),
+		})
+		.await
+		.unwrap();
+
+	// The client keeps playing when the publisher pauses for longer than the
+	// timeout of the handshake
+	tokio::time::sleep(Duration::from_secs(3)).await;
+
+	for i in 0..10 {
+		data_producer
+			.send(ChannelData::Video {
+				timestamp: i * 33,
+				data: vec![i as u8; 10000].into(),
+			})
+			.await
+			.unwrap();
+
+		data_producer
+			.send(ChannelData::Audio {
+				timestamp: i * 33,
+				data: vec![i as u8; 100].into(),
+			})
+			.await
+			.unwrap();
+	}
+
+	// Dropping the producer ends the stream
+	drop(data_producer);
+
+	let received = client_handle
+		.timeout(Duration::from_millis(1000))
+		.await
+		.expect("timedout")
+		.expect("client panicked");
+
+	assert_eq!(received.len(), 21);
+
+	// The @setDataFrame prefix is removed for players
+	let mut expected_metadata = vec![0x02, 0x00, 0x0a];
+	expected_metadata.extend_from_slice(b"onMetaData");
+	assert_eq!(received[0].data().as_ref(), &expected_metadata[..]);
+
+	for (i, data) in received[1..].chunks(2).enumerate() {
+		assert!(matches!(data[0], ChannelData::Video { .. }));
+		assert_eq!(data[0].timestamp(), i as u32 * 33);
+		assert_eq!(data[0].data().len(), 10000);
+
+		assert!(matches!(data[1], ChannelData::Audio { .. }));
+		assert_eq!(data[1].timestamp(), i as u32 * 33);
+		assert_eq!(data[1].data().len(), 100);
+	}
+
+	assert!(
+		session_handle
+			.await
+			.expect("failed to join handle")
+			.expect("failed to handle client connection")
+	);
+}
@@ -1 +1,30 @@
 pub const RTMP_EVENT_STREAM_BEGIN: u16 = 0;
+pub const RTMP_EVENT_STREAM_EOF: u16 = 1;
+pub const RTMP_EVENT_STREAM_DRY: u16 = 2;
+pub const RTMP_EVENT_SET_BUFFER_LENGTH: u16 = 3;
+pub const RTMP_EVENT_STREAM_IS_RECORDED: u16 = 4;
+pub const RTMP_EVENT_PING_REQUEST: u16 = 6;
+pub const RTMP_EVENT_PING_RESPONSE: u16 = 7;
+
+/// User control events are defined in 7.1.7 of the RTMP specification.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum UserControlEvent {
+	/// The stream `stream_id` became functional
+	StreamBegin { stream_id: u32 },
+	/// Playback of the stream `stream_id` is over
+	StreamEof { stream_id: u32 },
+	/// There is no more data on the stream `stream_id`
+	StreamDry { stream_id: u32 },
+	/// The client wants to buffer `buffer_length` milliseconds of the stream
+	/// `stream_id`
+	SetBufferLength { stream_id: u32, buffer_length: u32 },
+	/// The stream `stream_id` is a recorded stream
+	StreamIsRecorded { stream_id: u32 },
+	/// The peer wants to know if we are still alive, we should respond with a
+	/// ping response containing the same timestamp
+	PingRequest { timestamp: u32 },
+	/// The response to a ping request we sent
+	PingResponse { timestamp: u32 },
+	/// An event we do not know about
+	Unknown { event_type: u16 },
+}
@@ -1,18 +1,23 @@
-use std::fmt;
+use std::{fmt, io};
 
 use crate::chunk::ChunkEncodeError;
 use crate::macros::from_error;
 
 #[derive(Debug)]
 pub enum EventMessagesError {
+	IO(io::Error),
 	ChunkEncode(ChunkEncodeError),
 }
 
+from_error!(EventMessagesError, Self::IO, io::Error);
 from_error!(EventMessagesError, Self::ChunkEncode, ChunkEncodeError);
 
 impl fmt::Display for EventMessagesError {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match &self {
+			Self::IO(e) => {
+				write!(f, "io error: {}", e)
+			}
 			Self::ChunkEncode(e) => {
 				write!(f, "chunk encode error: {}", e)
 			}
@@ -1,8 +1,11 @@
 mod define;
 mod errors;
+mod reader;
 mod writer;
 
+pub use self::define::UserControlEvent;
 pub use self::errors::EventMessagesError;
+pub use self::reader::EventMessagesReader;
 pub use self::writer::EventMessagesWriter;
 
 #[cfg(test)]
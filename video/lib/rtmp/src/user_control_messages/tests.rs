@@ -2,12 +2,62 @@ use bytes::Bytes;
 use bytesio::bytes_writer::BytesWriter;
 
 use crate::chunk::{ChunkDecoder, ChunkEncodeError, ChunkEncoder};
-use crate::user_control_messages::{EventMessagesError, EventMessagesWriter};
+use crate::user_control_messages::{EventMessagesError, EventMessagesReader, EventMessagesWriter, UserControlEvent};
 
 #[test]
 fn test_error_display() {
 	let error = EventMessagesError::ChunkEncode(ChunkEncodeError::UnknownReadState);
 	assert_eq!(format!("{}", error), "chunk encode error: unknown read state");
+
+	let error = EventMessagesError::IO(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+	assert_eq!(format!("{}", error), "io error: unexpected end of file");
+}
+
+#[test]
+fn test_read_events() {
+	let cases: [(&[u8], UserControlEvent); 8] = [
+		(
+			&[0x00, 0x00, 0x00, 0x00, 0x00, 0x01],
+			UserControlEvent::StreamBegin { stream_id: 1 },
+		),
+		(
+			&[0x00, 0x01, 0x00, 0x00, 0x00, 0x01],
+			UserControlEvent::StreamEof { stream_id: 1 },
+		),
+		(
+			&[0x00, 0x02, 0x00, 0x00, 0x00, 0x01],
+			UserControlEvent::StreamDry { stream_id: 1 },
+		),
+		(
+			&[0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x03, 0xE8],
+			UserControlEvent::SetBufferLength {
+				stream_id: 1,
+				buffer_length: 1000,
+			},
+		),
+		(
+			&[0x00, 0x04, 0x00, 0x00, 0x00, 0x01],
+			UserControlEvent::StreamIsRecorded { stream_id: 1 },
+		),
+		(
+			&[0x00, 0x06, 0x00, 0x00, 0x00, 0x02],
+			UserControlEvent::PingRequest { timestamp: 2 },
+		),
+		(
+			&[0x00, 0x07, 0x00, 0x00, 0x00, 0x02],
+			UserControlEvent::PingResponse { timestamp: 2 },
+		),
+		(&[0x00, 0x1F, 0x00], UserControlEvent::Unknown { event_type: 0x1F }),
+	];
+
+	for (data, expected) in cases {
+		assert_eq!(EventMessagesReader::read(Bytes::copy_from_slice(data)).unwrap(), expected);
+	}
+
+	assert!(matches!(
+		EventMessagesReader::read(Bytes::from_static(&[0x00, 0x03, 0x00, 0x00, 0x00, 0x01])),
+		Err(EventMessagesError::IO(_))
+	));
 }
 
 #[test]
@@ -26,3 +76,74 @@ fn test_write_stream_begin() {
 	assert_eq!(chunk.message_header.msg_stream_id, 0);
 	assert_eq!(chunk.payload, Bytes::from(vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x01]));
 }
+
+#[test]
+fn test_write_stream_eof() {
+	let mut writer = BytesWriter::default();
+	let encoder = ChunkEncoder::default();
+
+	EventMessagesWriter::write_stream_eof(&encoder, &mut writer, 1).unwrap();
+
+	let mut decoder = ChunkDecoder::default();
+	decoder.extend_data(&writer.dispose());
+
+	let chunk = decoder.read_chunk().unwrap().unwrap();
+	assert_eq!(chunk.basic_header.chunk_stream_id, 0x02);
+	assert_eq!(chunk.message_header.msg_type_id as u8, 0x04);
+	assert_eq!(chunk.message_header.msg_stream_id, 0);
+	assert_eq!(chunk.payload, Bytes::from(vec![0x00, 0x01, 0x00, 0x00, 0x00, 0x01]));
+}
+
+#[test]
+fn test_write_set_buffer_length() {
+	let mut writer = BytesWriter::default();
+	let encoder = ChunkEncoder::default();
+
+	EventMessagesWriter::write_set_buffer_length(&encoder, &mut writer, 1, 1000).unwrap();
+
+	let mut decoder = ChunkDecoder::default();
+	decoder.extend_data(&writer.dispose());
+
+	let chunk = decoder.read_chunk().unwrap().unwrap();
+	assert_eq!(chunk.basic_header.chunk_stream_id, 0x02);
+	assert_eq!(chunk.message_header.msg_type_id as u8, 0x04);
+	assert_eq!(chunk.message_header.msg_stream_id, 0);
+	assert_eq!(
+		chunk.payload,
+		Bytes::from(vec![0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x03, 0xE8])
+	);
+}
+
+#[test]
+fn test_write_ping_request() {
+	let mut writer = BytesWriter::default();
+	let encoder = ChunkEncoder::default();
+
+	EventMessagesWriter::write_ping_request(&encoder, &mut writer, 1000).unwrap();
+
+	let mut decoder = ChunkDecoder::default();
+	decoder.extend_data(&writer.dispose());
+
+	let chunk = decoder.read_chunk().unwrap().unwrap();
+	assert_eq!(chunk.basic_header.chunk_stream_id, 0x02);
+	assert_eq!(chunk.message_header.msg_type_id as u8, 0x04);
+	assert_eq!(chunk.message_header.msg_stream_id, 0);
+	assert_eq!(chunk.payload, Bytes::from(vec![0x00, 0x06, 0x00, 0x00, 0x03, 0xE8]));
+}
+
+#[test]
+fn test_write_ping_response() {
+	let mut writer = BytesWriter::default();
+	let encoder = ChunkEncoder::default();
+
+	EventMessagesWriter::write_ping_response(&encoder, &mut writer, 1000).unwrap();
+
+	let mut decoder = ChunkDecoder::default();
+	decoder.extend_data(&writer.dispose());
+
+	let chunk = decoder.read_chunk().unwrap().unwrap();
+	assert_eq!(chunk.basic_header.chunk_stream_id, 0x02);
+	assert_eq!(chunk.message_header.msg_type_id as u8, 0x04);
+	assert_eq!(chunk.message_header.msg_stream_id, 0);
+	assert_eq!(chunk.payload, Bytes::from(vec![0x00, 0x07, 0x00, 0x00, 0x03, 0xE8]));
+}
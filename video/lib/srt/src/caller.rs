@@ -0,0 +1,177 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::net::UdpSocket;
+use tokio::time::Instant;
+
+use crate::connection::{ConnectionParams, PacketSource};
+use crate::define::{
+	extension_flags, HandshakeType, FLOW_WINDOW_SIZE, HANDSHAKE_INTERVAL, MAX_STREAM_ID_SIZE, SEQUENCE_MASK, SRT_FLAGS,
+	SRT_MAGIC, SRT_VERSION,
+};
+use crate::packet::{peer_ip, Control, ControlPacket, Handshake, HandshakeExtension, Packet, SrtHandshake};
+use crate::{SrtConfig, SrtError, SrtSocket};
+
+impl SrtSocket {
+	/// Connect to a listener, the stream id tells the listener what we want.
+	pub async fn connect(addr: SocketAddr, stream_id: &str, config: SrtConfig) -> Result<Self, SrtError> {
+		if stream_id.len() > MAX_STREAM_ID_SIZE {
+			return Err(SrtError::StreamIdTooLong(stream_id.len()));
+		}
+
+		let bind_addr = if addr.is_ipv6() {
+			SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+		} else {
+			SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+		};
+
+		let socket = Arc::new(UdpSocket::bind(bind_addr).await?);
+
+		let socket_id = loop {
+			let socket_id = rand::random::<u32>() & SEQUENCE_MASK;
+			if socket_id != 0 {
+				break socket_id;
+			}
+		};
+		let initial_sequence_number = rand::random::<u32>() & SEQUENCE_MASK;
+
+		let start = Instant::now();
+
+		let handshake = tokio::time::timeout(
+			config.connect_timeout,
+			handshake(&socket, addr, socket_id, initial_sequence_number, stream_id, &config),
+		)
+		.await
+		.map_err(|_| SrtError::Timeout)??;
+
+		if let HandshakeType::Rejection(reason) = handshake.handshake_type {
+			return Err(SrtError::Rejected(reason));
+		}
+
+		let hs_rsp = handshake.hs_rsp().ok_or(SrtError::InvalidHandshake)?;
+
+		let params = ConnectionParams {
+			socket,
+			peer_addr: addr,
+			peer_socket_id: handshake.socket_id,
+			initial_sequence_number,
+			start,
+			// The listener picked the higher latencies already, the sender delay
+			// is the latency at which the listener sends to us.
+			receive_latency: config.latency.max(Duration::from_millis(hs_rsp.sender_delay as u64)),
+			send_latency: config.latency.max(Duration::from_millis(hs_rsp.receiver_delay as u64)),
+			peer_idle_timeout: config.peer_idle_timeout,
+			stream_id: stream_id.to_string(),
+		};
+
+		Ok(Self::start(params, PacketSource::Socket, ()))
+	}
+}
+
+/// The caller side of the HSv5 handshake. Returns the response to the
+/// conclusion, which may be a rejection.
+async fn handshake(
+	socket: &UdpSocket,
+	addr: SocketAddr,
+	socket_id: u32,
+	initial_sequence_number: u32,
+	stream_id: &str,
+	config: &SrtConfig,
+) -> Result<Handshake, SrtError> {
+	let mut request = Handshake {
+		// The induction uses version 4 so older listeners understand it.
+		version: 4,
+		encryption: 0,
+		// UDT_DGRAM
+		extension_field: 2,
+		initial_sequence_number,
+		mtu: 1500,
+		max_flow_window: FLOW_WINDOW_SIZE,
+		handshake_type: HandshakeType::Induction,
+		socket_id,
+		syn_cookie: 0,
+		peer_ip: peer_ip(addr.ip()),
+		extensions: Vec::new(),
+	};
+
+	let induction = exchange(socket, addr, socket_id, &request, |response| {
+		response.handshake_type == HandshakeType::Induction
+	})
+	.await?;
+
+	if induction.version != 5 || induction.extension_field != SRT_MAGIC {
+		return Err(SrtError::InvalidHandshake);
+	}
+
+	let latency = config.latency.as_millis().min(u16::MAX as u128) as u16;
+
+	request.version = 5;
+	request.extension_field = extension_flags::HSREQ;
+	request.handshake_type = HandshakeType::Conclusion;
+	request.syn_cookie = induction.syn_cookie;
+	request.extensions.push(HandshakeExtension::HsReq(SrtHandshake {
+		version: SRT_VERSION,
+		flags: SRT_FLAGS,
+		receiver_delay: latency,
+		sender_delay: latency,
+	}));
+
+	if !stream_id.is_empty() {
+		request.extension_field |= extension_flags::CONFIG;
+		request.extensions.push(HandshakeExtension::StreamId(stream_id.to_string()));
+	}
+
+	exchange(socket, addr, socket_id, &request, |response| {
+		matches!(
+			response.handshake_type,
+			HandshakeType::Conclusion | HandshakeType::Rejection(_)
+		)
+	})
+	.await
+}
+
+/// Send the handshake until we get a response which matches.
+async fn exchange(
+	socket: &UdpSocket,
+	addr: SocketAddr,
+	socket_id: u32,
+	request: &Handshake,
+	matches: impl Fn(&Handshake) -> bool,
+) -> Result<Handshake, SrtError> {
+	let request = Packet::Control(ControlPacket {
+		timestamp: 0,
+		destination_socket_id: 0,
+		control: Control::Handshake(request.clone()),
+	})
+	.to_bytes();
+
+	let mut buf = vec![0; 1500];
+
+	loop {
+		socket.send_to(&request, addr).await?;
+
+		let deadline = Instant::now() + HANDSHAKE_INTERVAL;
+		while let Ok(r) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await {
+			let (size, from) = r?;
+			if from != addr {
+				continue;
+			}
+
+			let Ok(Packet::Control(packet)) = Packet::demux(Bytes::copy_from_slice(&buf[..size])) else {
+				continue;
+			};
+
+			if packet.destination_socket_id != socket_id {
+				continue;
+			}
+
+			if let Control::Handshake(handshake) = packet.control {
+				if matches(&handshake) {
+					return Ok(handshake);
+				}
+			}
+		}
+	}
+}
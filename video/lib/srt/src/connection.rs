@@ -0,0 +1,430 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior};
+
+use crate::define::{KEEPALIVE_INTERVAL, MAX_PAYLOAD_SIZE, SYN_INTERVAL};
+use crate::packet::{Control, ControlPacket, Packet};
+use crate::receiver::Receiver;
+use crate::sender::Sender;
+use crate::SrtError;
+
+/// The number of received messages which are buffered until they are read.
+const DATA_BUFFER_SIZE: usize = 1024;
+
+/// The largest number of ranges in a single NAK.
+const MAX_NAK_RANGES: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrtConfig {
+	/// The latency we use to receive, and ask the peer to use. The higher
+	/// side of the connection wins.
+	pub latency: Duration,
+	/// How long to wait for a packet from the peer before the connection is
+	/// considered dead.
+	pub peer_idle_timeout: Duration,
+	/// How long to wait for the handshake to complete.
+	pub connect_timeout: Duration,
+}
+
+impl Default for SrtConfig {
+	fn default() -> Self {
+		Self {
+			latency: Duration::from_millis(120),
+			peer_idle_timeout: Duration::from_secs(5),
+			connect_timeout: Duration::from_secs(3),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Statistics of a connection.
+pub struct SrtStats {
+	pub packets_sent: u64,
+	pub bytes_sent: u64,
+	pub packets_retransmitted: u64,
+	pub packets_received: u64,
+	pub bytes_received: u64,
+	/// Packets we noticed were lost, most of them are recovered by
+	/// retransmissions.
+	pub packets_lost: u64,
+	/// Packets which were not recovered in time and were skipped.
+	pub packets_dropped: u64,
+	pub rtt: Duration,
+}
+
+pub(crate) enum PacketSource {
+	/// The socket is shared with a listener, which forwards our packets.
+	Listener(mpsc::Receiver<Packet>),
+	/// The socket is only used by this connection.
+	Socket,
+}
+
+pub(crate) struct ConnectionParams {
+	pub socket: Arc<UdpSocket>,
+	pub peer_addr: SocketAddr,
+	pub peer_socket_id: u32,
+	pub initial_sequence_number: u32,
+	pub start: Instant,
+	pub receive_latency: Duration,
+	pub send_latency: Duration,
+	pub peer_idle_timeout: Duration,
+	pub stream_id: String,
+}
+
+enum Command {
+	Send(Bytes),
+	Close,
+}
+
+/// A SRT connection in live mode. Messages are sent as single packets and are
+/// delivered to the peer after the negotiated latency, lost packets are
+/// retransmitted as long as there is time left.
+pub struct SrtSocket {
+	commands: mpsc::UnboundedSender<Command>,
+	data: mpsc::Receiver<Result<Bytes, SrtError>>,
+	stats: Arc<Mutex<SrtStats>>,
+	task: JoinHandle<()>,
+
+	peer_addr: SocketAddr,
+	stream_id: String,
+	latency: Duration,
+}
+
+impl SrtSocket {
+	/// Start the connection after the handshake completed. The guard is
+	/// dropped once the connection is closed.
+	pub(crate) fn start<G: Send + 'static>(params: ConnectionParams, source: PacketSource, guard: G) -> Self {
+		let (commands_sender, commands) = mpsc::unbounded_channel();
+		let (data_sender, data) = mpsc::channel(DATA_BUFFER_SIZE);
+		let stats = Arc::new(Mutex::new(SrtStats::default()));
+
+		let peer_addr = params.peer_addr;
+		let stream_id = params.stream_id.clone();
+		let latency = params.receive_latency;
+
+		let connection = Connection {
+			socket: params.socket,
+			peer_addr: params.peer_addr,
+			peer_socket_id: params.peer_socket_id,
+			peer_idle_timeout: params.peer_idle_timeout,
+			sender: Sender::new(
+				params.start,
+				params.initial_sequence_number,
+				params.send_latency,
+				params.peer_socket_id,
+			),
+			receiver: Receiver::new(params.initial_sequence_number, params.receive_latency),
+			source,
+			commands,
+			data: data_sender,
+			stats: stats.clone(),
+			last_received: Instant::now(),
+			last_sent: Instant::now(),
+			peer_rtt: Duration::ZERO,
+			closing: false,
+		};
+
+		let task = tokio::spawn(async move {
+			connection.run().await;
+			drop(guard);
+		});
+
+		Self {
+			commands: commands_sender,
+			data,
+			stats,
+			task,
+			peer_addr,
+			stream_id,
+			latency,
+		}
+	}
+
+	/// Send a message, it has to fit in a single packet.
+	pub fn send(&self, data: Bytes) -> Result<(), SrtError> {
+		if data.len() > MAX_PAYLOAD_SIZE {
+			return Err(SrtError::PayloadTooLarge(data.len()));
+		}
+
+		self.commands.send(Command::Send(data)).map_err(|_| SrtError::Closed)
+	}
+
+	/// Receive the next message. Returns `None` when the peer closed the
+	/// connection.
+	pub async fn recv(&mut self) -> Result<Option<Bytes>, SrtError> {
+		self.data.recv().await.transpose()
+	}
+
+	/// Close the connection after every message we sent has been
+	/// acknowledged or dropped.
+	pub async fn close(self) {
+		if self.commands.send(Command::Close).is_ok() {
+			self.task.await.ok();
+		}
+	}
+
+	pub fn peer_addr(&self) -> SocketAddr {
+		self.peer_addr
+	}
+
+	/// The stream id the caller sent in the handshake.
+	pub fn stream_id(&self) -> &str {
+		&self.stream_id
+	}
+
+	/// The latency at which we receive messages.
+	pub fn latency(&self) -> Duration {
+		self.latency
+	}
+
+	pub fn stats(&self) -> SrtStats {
+		*self.stats.lock().unwrap()
+	}
+}
+
+struct Connection {
+	socket: Arc<UdpSocket>,
+	peer_addr: SocketAddr,
+	peer_socket_id: u32,
+	peer_idle_timeout: Duration,
+
+	sender: Sender,
+	receiver: Receiver,
+
+	source: PacketSource,
+	commands: mpsc::UnboundedReceiver<Command>,
+	data: mpsc::Sender<Result<Bytes, SrtError>>,
+	stats: Arc<Mutex<SrtStats>>,
+
+	last_received: Instant,
+	last_sent: Instant,
+	/// The round trip time the peer measured, which is the only one we know
+	/// if we only send.
+	peer_rtt: Duration,
+	closing: bool,
+}
+
+impl Connection {
+	async fn run(mut self) {
+		let mut syn = tokio::time::interval(SYN_INTERVAL);
+		syn.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+		let mut buf = vec![0; 1500];
+
+		loop {
+			let deadline = self.receiver.next_deadline();
+
+			let running = tokio::select! {
+				packet = recv_packet(&mut self.source, &self.socket, self.peer_addr, &mut buf) => match packet {
+					Some(packet) => self.on_packet(packet).await,
+					None => false,
+				},
+				command = self.commands.recv(), if !self.closing => match command {
+					Some(Command::Send(data)) => {
+						let packet = self.sender.push(data, Instant::now());
+						self.send(Packet::Data(packet)).await;
+						true
+					}
+					Some(Command::Close) => {
+						self.closing = true;
+						true
+					}
+					None => {
+						// The socket was dropped, so nobody is interested in the
+						// connection anymore.
+						self.send_control(Control::Shutdown).await;
+						false
+					}
+				},
+				_ = syn.tick() => self.on_tick().await,
+				_ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => true,
+			};
+
+			self.deliver();
+
+			if !running {
+				break;
+			}
+		}
+
+		// The peer only closes the connection once we acknowledged everything,
+		// so the packets which are still waiting for their time are complete.
+		while let Some(deadline) = self.receiver.next_deadline() {
+			if self.data.is_closed() {
+				break;
+			}
+
+			tokio::time::sleep_until(deadline).await;
+			self.deliver();
+		}
+
+		self.update_stats();
+	}
+
+	fn deliver(&mut self) {
+		let now = Instant::now();
+		while let Some(payload) = self.receiver.pop(now) {
+			if self.data.try_send(Ok(payload)).is_err() {
+				// Nobody reads the data fast enough, since this is live data we
+				// skip it instead of waiting.
+				self.receiver.packets_dropped += 1;
+			}
+		}
+	}
+
+	async fn on_packet(&mut self, packet: Packet) -> bool {
+		let now = Instant::now();
+		self.last_received = now;
+
+		let control = match packet {
+			Packet::Data(packet) => {
+				let lost = self.receiver.on_data(packet, now);
+				if !lost.is_empty() {
+					self.send_control(Control::Nak(lost)).await;
+				}
+
+				return true;
+			}
+			Packet::Control(packet) => packet.control,
+		};
+
+		match control {
+			Control::Ack { ack_number, ack } => {
+				self.sender.on_ack(ack.last_acknowledged);
+				self.peer_rtt = Duration::from_micros(ack.rtt as u64);
+				self.send_control(Control::AckAck { ack_number }).await;
+			}
+			Control::AckAck { ack_number } => self.receiver.on_ack_ack(ack_number, now),
+			Control::Nak(ranges) => {
+				for packet in self.sender.on_nak(&ranges) {
+					self.send(Packet::Data(packet)).await;
+				}
+			}
+			Control::DropRequest { first, last, .. } => self.receiver.on_drop_request(first, last),
+			Control::Shutdown => {
+				tracing::debug!("peer closed the connection");
+				return false;
+			}
+			Control::Handshake(_) | Control::KeepAlive | Control::Unknown { .. } => {}
+		}
+
+		true
+	}
+
+	async fn on_tick(&mut self) -> bool {
+		let now = Instant::now();
+
+		if now - self.last_received >= self.peer_idle_timeout {
+			tracing::debug!("peer timed out");
+			self.data.try_send(Err(SrtError::Timeout)).ok();
+			return false;
+		}
+
+		if let Some((ack_number, ack)) = self.receiver.ack(now) {
+			self.send_control(Control::Ack { ack_number, ack }).await;
+		}
+
+		let lost = self.receiver.nak(now);
+		for ranges in lost.chunks(MAX_NAK_RANGES) {
+			self.send_control(Control::Nak(ranges.to_vec())).await;
+		}
+
+		if let Some((message_number, first, last)) = self.sender.drop_expired(now) {
+			self.send_control(Control::DropRequest {
+				message_number,
+				first,
+				last,
+			})
+			.await;
+		}
+
+		if self.closing && self.sender.is_empty() {
+			self.send_control(Control::Shutdown).await;
+			return false;
+		}
+
+		if now - self.last_sent >= KEEPALIVE_INTERVAL {
+			self.send_control(Control::KeepAlive).await;
+		}
+
+		self.update_stats();
+
+		true
+	}
+
+	fn update_stats(&self) {
+		let rtt = if self.receiver.packets_received > 0 {
+			self.receiver.rtt()
+		} else {
+			self.peer_rtt
+		};
+
+		*self.stats.lock().unwrap() = SrtStats {
+			packets_sent: self.sender.packets_sent,
+			bytes_sent: self.sender.bytes_sent,
+			packets_retransmitted: self.sender.packets_retransmitted,
+			packets_received: self.receiver.packets_received,
+			bytes_received: self.receiver.bytes_received,
+			packets_lost: self.receiver.packets_lost,
+			packets_dropped: self.receiver.packets_dropped,
+			rtt,
+		};
+	}
+
+	async fn send_control(&mut self, control: Control) {
+		let packet = Packet::Control(ControlPacket {
+			timestamp: self.sender.timestamp(Instant::now()),
+			destination_socket_id: self.peer_socket_id,
+			control,
+		});
+
+		self.send(packet).await;
+	}
+
+	async fn send(&mut self, packet: Packet) {
+		self.last_sent = Instant::now();
+
+		// Lost packets are recovered by the protocol, so there is nothing to do
+		// about errors here.
+		if let Err(err) = self.socket.send_to(&packet.to_bytes(), self.peer_addr).await {
+			tracing::debug!(error = %err, "failed to send packet");
+		}
+	}
+}
+
+async fn recv_packet(
+	source: &mut PacketSource,
+	socket: &UdpSocket,
+	peer_addr: SocketAddr,
+	buf: &mut [u8],
+) -> Option<Packet> {
+	match source {
+		PacketSource::Listener(packets) => packets.recv().await,
+		PacketSource::Socket => loop {
+			let (size, addr) = match socket.recv_from(buf).await {
+				Ok(r) => r,
+				Err(err) => {
+					// ICMP errors show up here on some platforms, so they are not
+					// fatal.
+					tracing::debug!(error = %err, "failed to receive packet");
+					continue;
+				}
+			};
+
+			if addr != peer_addr {
+				continue;
+			}
+
+			match Packet::demux(Bytes::copy_from_slice(&buf[..size])) {
+				Ok(packet) => return Some(packet),
+				Err(err) => tracing::debug!(error = %err, "invalid packet"),
+			}
+		},
+	}
+}
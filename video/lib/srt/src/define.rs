@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+/// The size of the header in front of every packet.
+pub const HEADER_SIZE: usize = 16;
+
+/// The largest payload of a data packet, this is the payload of a 1500 byte
+/// MTU minus the IP, UDP and SRT headers.
+pub const MAX_PAYLOAD_SIZE: usize = 1456;
+
+/// The payload size commonly used for live streams, 7 transport stream
+/// packets.
+pub const LIVE_PAYLOAD_SIZE: usize = 1316;
+
+/// The largest stream id, in bytes.
+pub const MAX_STREAM_ID_SIZE: usize = 512;
+
+/// The flags of the features we support, which are sent in the handshake.
+pub const SRT_FLAGS: u32 =
+	srt_flags::TSBPD_SND | srt_flags::TSBPD_RCV | srt_flags::TLPKTDROP | srt_flags::PERIODIC_NAK | srt_flags::REXMIT_FLAG;
+
+/// The version of the SRT protocol we implement (1.5.0).
+pub const SRT_VERSION: u32 = 0x01_05_00;
+
+/// The magic value of the extension field in the induction response, which
+/// tells the caller that the listener speaks HSv5.
+pub const SRT_MAGIC: u16 = 0x4A17;
+
+/// The interval at which ACKs are sent and timers are checked.
+pub const SYN_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How often a handshake is retransmitted when there is no response.
+pub const HANDSHAKE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often a keepalive is sent when nothing else was sent.
+pub const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The number of packets the receive buffer can hold.
+pub const FLOW_WINDOW_SIZE: u32 = 8192;
+
+/// The mask of a 31 bit sequence number.
+pub const SEQUENCE_MASK: u32 = 0x7FFF_FFFF;
+
+/// The mask of a 26 bit message number.
+pub const MESSAGE_NUMBER_MASK: u32 = 0x03FF_FFFF;
+
+/// The next sequence number.
+pub fn seq_add(seq: u32, count: u32) -> u32 {
+	seq.wrapping_add(count) & SEQUENCE_MASK
+}
+
+/// The distance from `from` to `to`, taking the wrap around of sequence
+/// numbers into account. Negative if `to` is before `from`.
+pub fn seq_offset(from: u32, to: u32) -> i32 {
+	let diff = to.wrapping_sub(from) & SEQUENCE_MASK;
+	if diff > SEQUENCE_MASK / 2 {
+		(diff as i64 - (SEQUENCE_MASK as i64 + 1)) as i32
+	} else {
+		diff as i32
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The position of a packet within a message.
+pub enum PacketPosition {
+	Middle = 0b00,
+	Last = 0b01,
+	First = 0b10,
+	/// The packet contains the entire message, this is always the case in live
+	/// mode.
+	Only = 0b11,
+}
+
+impl From<u32> for PacketPosition {
+	fn from(value: u32) -> Self {
+		match value & 0b11 {
+			0b00 => Self::Middle,
+			0b01 => Self::Last,
+			0b10 => Self::First,
+			_ => Self::Only,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+/// The type of a control packet.
+pub enum ControlType {
+	Handshake = 0x0000,
+	KeepAlive = 0x0001,
+	Ack = 0x0002,
+	Nak = 0x0003,
+	CongestionWarning = 0x0004,
+	Shutdown = 0x0005,
+	AckAck = 0x0006,
+	DropRequest = 0x0007,
+	PeerError = 0x0008,
+	UserDefined = 0x7FFF,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The type of a handshake packet.
+pub enum HandshakeType {
+	Done,
+	Agreement,
+	Conclusion,
+	WaveAHand,
+	Induction,
+	/// The handshake was rejected, the value is the reason.
+	Rejection(u32),
+}
+
+impl From<u32> for HandshakeType {
+	fn from(value: u32) -> Self {
+		match value {
+			0xFFFF_FFFD => Self::Done,
+			0xFFFF_FFFE => Self::Agreement,
+			0xFFFF_FFFF => Self::Conclusion,
+			0x0000_0000 => Self::WaveAHand,
+			0x0000_0001 => Self::Induction,
+			reason => Self::Rejection(reason),
+		}
+	}
+}
+
+impl From<HandshakeType> for u32 {
+	fn from(value: HandshakeType) -> Self {
+		match value {
+			HandshakeType::Done => 0xFFFF_FFFD,
+			HandshakeType::Agreement => 0xFFFF_FFFE,
+			HandshakeType::Conclusion => 0xFFFF_FFFF,
+			HandshakeType::WaveAHand => 0x0000_0000,
+			HandshakeType::Induction => 0x0000_0001,
+			HandshakeType::Rejection(reason) => reason,
+		}
+	}
+}
+
+/// Flags of the extension field in a conclusion handshake, which announce the
+/// extensions that follow.
+pub mod extension_flags {
+	pub const HSREQ: u16 = 0x0001;
+	pub const KMREQ: u16 = 0x0002;
+	pub const CONFIG: u16 = 0x0004;
+}
+
+/// The types of the handshake extensions.
+pub mod extension_type {
+	pub const HSREQ: u16 = 1;
+	pub const HSRSP: u16 = 2;
+	pub const KMREQ: u16 = 3;
+	pub const KMRSP: u16 = 4;
+	pub const SID: u16 = 5;
+}
+
+/// The flags of the SRT handshake extension, which negotiate the features
+/// used by the connection.
+pub mod srt_flags {
+	pub const TSBPD_SND: u32 = 0x0000_0001;
+	pub const TSBPD_RCV: u32 = 0x0000_0002;
+	pub const CRYPT: u32 = 0x0000_0004;
+	pub const TLPKTDROP: u32 = 0x0000_0008;
+	pub const PERIODIC_NAK: u32 = 0x0000_0010;
+	pub const REXMIT_FLAG: u32 = 0x0000_0020;
+	pub const STREAM: u32 = 0x0000_0040;
+	pub const PACKET_FILTER: u32 = 0x0000_0080;
+}
+
+/// Reasons for rejecting a connection, these are sent in place of the
+/// handshake type.
+pub mod reject_reason {
+	/// The peer rejected the connection
+	pub const PEER: u32 = 1002;
+	/// The listener is out of resources
+	pub const RESOURCE: u32 = 1003;
+	/// The handshake was malformed
+	pub const ROGUE: u32 = 1004;
+	/// The peer uses a version we do not support
+	pub const VERSION: u32 = 1008;
+	/// The peer wants encryption, which we do not support
+	pub const UNSECURE: u32 = 1011;
+	/// The stream id is malformed
+	pub const BAD_REQUEST: u32 = 1400;
+	/// The stream id does not grant access
+	pub const UNAUTHORIZED: u32 = 1401;
+	/// The stream id grants access, but not to this resource
+	pub const FORBIDDEN: u32 = 1403;
+	/// The resource of the stream id does not exist
+	pub const NOT_FOUND: u32 = 1404;
+	/// The resource is already in use
+	pub const CONFLICT: u32 = 1409;
+}
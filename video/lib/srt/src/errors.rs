@@ -0,0 +1,38 @@
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum SrtError {
+	IO(io::Error),
+	/// The peer rejected the connection, the value is the reason
+	Rejected(u32),
+	/// The peer did not respond in time
+	Timeout,
+	/// The connection is closed
+	Closed,
+	/// The peer sent a handshake we don't understand
+	InvalidHandshake,
+	/// The stream id is longer than 512 bytes
+	StreamIdTooLong(usize),
+	/// The payload does not fit in a single packet
+	PayloadTooLarge(usize),
+}
+
+impl From<io::Error> for SrtError {
+	fn from(error: io::Error) -> Self {
+		Self::IO(error)
+	}
+}
+
+impl fmt::Display for SrtError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::IO(error) => write!(f, "io error: {}", error),
+			Self::Rejected(reason) => write!(f, "connection rejected: {}", reason),
+			Self::Timeout => write!(f, "connection timed out"),
+			Self::Closed => write!(f, "connection closed"),
+			Self::InvalidHandshake => write!(f, "invalid handshake"),
+			Self::StreamIdTooLong(size) => write!(f, "stream id too long: {}", size),
+			Self::PayloadTooLarge(size) => write!(f, "payload too large: {}", size),
+		}
+	}
+}
@@ -0,0 +1,17 @@
+mod caller;
+mod connection;
+mod define;
+mod errors;
+mod listener;
+mod packet;
+mod receiver;
+mod sender;
+
+pub use connection::{SrtConfig, SrtSocket, SrtStats};
+pub use define::*;
+pub use errors::SrtError;
+pub use listener::{SrtListener, SrtRequest};
+pub use packet::{Ack, Control, ControlPacket, DataPacket, Handshake, HandshakeExtension, Packet, SrtHandshake};
+
+#[cfg(test)]
+mod tests;
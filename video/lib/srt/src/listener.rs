@@ -0,0 +1,423 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::net::UdpSocket;
+use tokio::sync::{mpsc, Notify};
+use tokio::time::Instant;
+
+use crate::connection::{ConnectionParams, PacketSource};
+use crate::define::{
+	extension_flags, reject_reason, HandshakeType, FLOW_WINDOW_SIZE, SEQUENCE_MASK, SRT_FLAGS, SRT_MAGIC, SRT_VERSION,
+};
+use crate::packet::{peer_ip, Control, ControlPacket, Handshake, HandshakeExtension, Packet, SrtHandshake};
+use crate::{SrtConfig, SrtSocket};
+
+/// The number of handshakes which can wait to be accepted.
+const ACCEPT_BACKLOG: usize = 64;
+
+/// The number of packets buffered for a connection.
+const CONNECTION_BUFFER_SIZE: usize = 1024;
+
+/// How long the response to a handshake is kept, so it can be sent again if
+/// the caller did not receive it.
+const HANDSHAKE_LINGER: Duration = Duration::from_secs(10);
+
+enum HandshakeState {
+	/// The request was handed to the application, which has not decided yet.
+	Pending,
+	/// The request was accepted or rejected.
+	Done { response: Bytes, at: Instant },
+}
+
+#[derive(Default)]
+struct Shared {
+	/// The connections by our socket id.
+	connections: HashMap<u32, mpsc::Sender<Packet>>,
+	/// The handshakes by the address and socket id of the caller.
+	handshakes: HashMap<(SocketAddr, u32), HandshakeState>,
+}
+
+/// A socket which accepts SRT connections. The socket is shared by all
+/// connections, packets are dispatched by their destination socket id.
+pub struct SrtListener {
+	requests: mpsc::Receiver<SrtRequest>,
+	local_addr: SocketAddr,
+}
+
+impl SrtListener {
+	pub async fn bind(addr: SocketAddr, config: SrtConfig) -> io::Result<Self> {
+		let socket = Arc::new(UdpSocket::bind(addr).await?);
+		let local_addr = socket.local_addr()?;
+
+		let (requests_sender, requests) = mpsc::channel(ACCEPT_BACKLOG);
+
+		let task = ListenerTask {
+			socket,
+			config,
+			shared: Arc::new(Mutex::new(Shared::default())),
+			closed: Arc::new(Notify::new()),
+			requests: requests_sender,
+			cookie_secret: rand::random(),
+		};
+
+		tokio::spawn(task.run());
+
+		Ok(Self { requests, local_addr })
+	}
+
+	/// Wait for the next connection request. The request has to be accepted
+	/// or rejected, the caller keeps waiting until then.
+	pub async fn accept(&mut self) -> Option<SrtRequest> {
+		self.requests.recv().await
+	}
+
+	pub fn local_addr(&self) -> SocketAddr {
+		self.local_addr
+	}
+}
+
+struct ListenerTask {
+	socket: Arc<UdpSocket>,
+	config: SrtConfig,
+	shared: Arc<Mutex<Shared>>,
+	/// Notified when a connection is closed.
+	closed: Arc<Notify>,
+	requests: mpsc::Sender<SrtRequest>,
+	cookie_secret: u64,
+}
+
+impl ListenerTask {
+	async fn run(self) {
+		let mut buf = vec![0; 1500];
+		let mut listener_closed = false;
+
+		loop {
+			tokio::select! {
+				r = self.socket.recv_from(&mut buf) => {
+					let Ok((size, addr)) = r else {
+						continue;
+					};
+
+					let packet = match Packet::demux(Bytes::copy_from_slice(&buf[..size])) {
+						Ok(packet) => packet,
+						Err(err) => {
+							tracing::debug!(error = %err, %addr, "invalid packet");
+							continue;
+						}
+					};
+
+					self.on_packet(packet, addr, listener_closed).await;
+				}
+				_ = self.requests.closed(), if !listener_closed => {
+					listener_closed = true;
+				}
+				_ = self.closed.notified() => {}
+			}
+
+			// The socket has to stay open as long as there are connections using
+			// it.
+			if listener_closed && self.shared.lock().unwrap().connections.is_empty() {
+				break;
+			}
+		}
+	}
+
+	async fn on_packet(&self, packet: Packet, addr: SocketAddr, listener_closed: bool) {
+		let destination_socket_id = packet.destination_socket_id();
+
+		let handshake = match packet {
+			Packet::Control(ControlPacket {
+				control: Control::Handshake(handshake),
+				..
+			}) if destination_socket_id == 0 => handshake,
+			packet => {
+				let connection = self.shared.lock().unwrap().connections.get(&destination_socket_id).cloned();
+				if let Some(connection) = connection {
+					// If the connection can not keep up, the packet is lost and will be
+					// recovered like any other lost packet.
+					connection.try_send(packet).ok();
+				}
+
+				return;
+			}
+		};
+
+		match handshake.handshake_type {
+			HandshakeType::Induction if !listener_closed => {
+				let response = Handshake {
+					version: 5,
+					encryption: 0,
+					extension_field: SRT_MAGIC,
+					initial_sequence_number: handshake.initial_sequence_number,
+					mtu: handshake.mtu,
+					max_flow_window: handshake.max_flow_window,
+					handshake_type: HandshakeType::Induction,
+					socket_id: 0,
+					syn_cookie: self.cookie(addr, 0),
+					peer_ip: peer_ip(addr.ip()),
+					extensions: Vec::new(),
+				};
+
+				self.send_handshake(response, handshake.socket_id, addr).await;
+			}
+			HandshakeType::Conclusion => self.on_conclusion(handshake, addr, listener_closed).await,
+			_ => {}
+		}
+	}
+
+	async fn on_conclusion(&self, handshake: Handshake, addr: SocketAddr, listener_closed: bool) {
+		if handshake.syn_cookie != self.cookie(addr, 0) && handshake.syn_cookie != self.cookie(addr, 1) {
+			tracing::debug!(%addr, "invalid cookie");
+			return;
+		}
+
+		let key = (addr, handshake.socket_id);
+
+		let previous = {
+			let mut shared = self.shared.lock().unwrap();
+
+			let now = Instant::now();
+			shared.handshakes.retain(|_, state| match state {
+				HandshakeState::Pending => true,
+				HandshakeState::Done { at, .. } => *at + HANDSHAKE_LINGER > now,
+			});
+
+			match shared.handshakes.get(&key) {
+				Some(HandshakeState::Pending) => return,
+				Some(HandshakeState::Done { response, .. }) => Some(response.clone()),
+				None => None,
+			}
+		};
+
+		if let Some(response) = previous {
+			// The caller did not receive our response.
+			self.socket.send_to(&response, addr).await.ok();
+			return;
+		}
+
+		let reason = if handshake.version != 5 {
+			Some(reject_reason::VERSION)
+		} else if handshake.encryption != 0 || handshake.extension_field & extension_flags::KMREQ != 0 {
+			Some(reject_reason::UNSECURE)
+		} else if handshake.hs_req().is_none() {
+			Some(reject_reason::ROGUE)
+		} else if listener_closed {
+			Some(reject_reason::PEER)
+		} else {
+			None
+		};
+
+		let request = SrtRequest {
+			stream_id: handshake.stream_id().unwrap_or_default().to_string(),
+			peer_addr: addr,
+			inner: Some(RequestInner {
+				socket: self.socket.clone(),
+				shared: self.shared.clone(),
+				closed: self.closed.clone(),
+				config: self.config.clone(),
+				handshake,
+			}),
+		};
+
+		if let Some(reason) = reason {
+			request.reject(reason);
+			return;
+		}
+
+		self.shared.lock().unwrap().handshakes.insert(key, HandshakeState::Pending);
+
+		if let Err(err) = self.requests.try_send(request) {
+			let request = match err {
+				mpsc::error::TrySendError::Full(request) | mpsc::error::TrySendError::Closed(request) => request,
+			};
+
+			request.reject(reject_reason::RESOURCE);
+		}
+	}
+
+	async fn send_handshake(&self, handshake: Handshake, destination_socket_id: u32, addr: SocketAddr) {
+		let packet = Packet::Control(ControlPacket {
+			timestamp: 0,
+			destination_socket_id,
+			control: Control::Handshake(handshake),
+		});
+
+		self.socket.send_to(&packet.to_bytes(), addr).await.ok();
+	}
+
+	/// The cookie proves that the caller received our induction response, so
+	/// we don't keep state for spoofed addresses. It changes every minute.
+	fn cookie(&self, addr: SocketAddr, minutes_ago: u64) -> u32 {
+		let minute = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.unwrap_or_default()
+			.as_secs()
+			/ 60;
+
+		let mut hasher = DefaultHasher::new();
+		self.cookie_secret.hash(&mut hasher);
+		addr.hash(&mut hasher);
+		(minute - minutes_ago).hash(&mut hasher);
+		hasher.finish() as u32
+	}
+}
+
+struct RequestInner {
+	socket: Arc<UdpSocket>,
+	shared: Arc<Mutex<Shared>>,
+	closed: Arc<Notify>,
+	config: SrtConfig,
+	handshake: Handshake,
+}
+
+/// A caller which wants to connect. Dropping the request rejects it.
+pub struct SrtRequest {
+	stream_id: String,
+	peer_addr: SocketAddr,
+	inner: Option<RequestInner>,
+}
+
+/// Removes the connection from the listener once it is closed.
+struct ConnectionGuard {
+	shared: Arc<Mutex<Shared>>,
+	closed: Arc<Notify>,
+	socket_id: u32,
+}
+
+impl Drop for ConnectionGuard {
+	fn drop(&mut self) {
+		self.shared.lock().unwrap().connections.remove(&self.socket_id);
+		self.closed.notify_one();
+	}
+}
+
+impl SrtRequest {
+	/// The stream id the caller sent, which usually identifies the resource
+	/// and the user.
+	pub fn stream_id(&self) -> &str {
+		&self.stream_id
+	}
+
+	pub fn peer_addr(&self) -> SocketAddr {
+		self.peer_addr
+	}
+
+	pub fn accept(mut self) -> SrtSocket {
+		let inner = self.inner.take().expect("request already handled");
+		let hs_req = *inner.handshake.hs_req().expect("checked by the listener");
+
+		let (packets_sender, packets) = mpsc::channel(CONNECTION_BUFFER_SIZE);
+
+		let socket_id = {
+			let mut shared = inner.shared.lock().unwrap();
+			let socket_id = loop {
+				let socket_id = rand::random::<u32>() & SEQUENCE_MASK;
+				if socket_id != 0 && !shared.connections.contains_key(&socket_id) {
+					break socket_id;
+				}
+			};
+
+			shared.connections.insert(socket_id, packets_sender);
+			socket_id
+		};
+
+		// Each side uses the higher latency of the two.
+		let receive_latency = inner.config.latency.max(Duration::from_millis(hs_req.sender_delay as u64));
+		let send_latency = inner.config.latency.max(Duration::from_millis(hs_req.receiver_delay as u64));
+
+		let response = Handshake {
+			version: 5,
+			encryption: 0,
+			extension_field: extension_flags::HSREQ,
+			initial_sequence_number: inner.handshake.initial_sequence_number,
+			mtu: inner.handshake.mtu.min(1500),
+			max_flow_window: FLOW_WINDOW_SIZE,
+			handshake_type: HandshakeType::Conclusion,
+			socket_id,
+			syn_cookie: inner.handshake.syn_cookie,
+			peer_ip: peer_ip(self.peer_addr.ip()),
+			extensions: vec![HandshakeExtension::HsRsp(SrtHandshake {
+				version: SRT_VERSION,
+				flags: SRT_FLAGS,
+				receiver_delay: receive_latency.as_millis() as u16,
+				sender_delay: send_latency.as_millis() as u16,
+			})],
+		};
+
+		respond(&inner, self.peer_addr, response);
+
+		let params = ConnectionParams {
+			socket: inner.socket.clone(),
+			peer_addr: self.peer_addr,
+			peer_socket_id: inner.handshake.socket_id,
+			initial_sequence_number: inner.handshake.initial_sequence_number,
+			start: Instant::now(),
+			receive_latency,
+			send_latency,
+			peer_idle_timeout: inner.config.peer_idle_timeout,
+			stream_id: std::mem::take(&mut self.stream_id),
+		};
+
+		let guard = ConnectionGuard {
+			shared: inner.shared,
+			closed: inner.closed,
+			socket_id,
+		};
+
+		SrtSocket::start(params, PacketSource::Listener(packets), guard)
+	}
+
+	/// Reject the request, see [`reject_reason`] for the reasons.
+	pub fn reject(mut self, reason: u32) {
+		let inner = self.inner.take().expect("request already handled");
+		reject(&inner, self.peer_addr, reason);
+	}
+}
+
+impl Drop for SrtRequest {
+	fn drop(&mut self) {
+		if let Some(inner) = self.inner.take() {
+			reject(&inner, self.peer_addr, reject_reason::PEER);
+		}
+	}
+}
+
+fn reject(inner: &RequestInner, addr: SocketAddr, reason: u32) {
+	let response = Handshake {
+		extensions: Vec::new(),
+		handshake_type: HandshakeType::Rejection(reason),
+		peer_ip: peer_ip(addr.ip()),
+		..inner.handshake.clone()
+	};
+
+	respond(inner, addr, response);
+}
+
+/// Send the response to a conclusion handshake and remember it, in case the
+/// caller sends the handshake again.
+fn respond(inner: &RequestInner, addr: SocketAddr, response: Handshake) {
+	let response = Packet::Control(ControlPacket {
+		timestamp: 0,
+		destination_socket_id: inner.handshake.socket_id,
+		control: Control::Handshake(response),
+	})
+	.to_bytes();
+
+	// If the socket is busy the caller will send the handshake again, at which
+	// point we send the stored response.
+	inner.socket.try_send_to(&response, addr).ok();
+
+	inner.shared.lock().unwrap().handshakes.insert(
+		(addr, inner.handshake.socket_id),
+		HandshakeState::Done {
+			response,
+			at: Instant::now(),
+		},
+	);
+}
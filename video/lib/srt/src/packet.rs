@@ -0,0 +1,510 @@
+use std::io;
+use std::net::IpAddr;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use bytes::{Buf, Bytes};
+use bytesio::bytes_reader::BytesCursor;
+
+use crate::define::{
+	extension_type, ControlType, HandshakeType, PacketPosition, MAX_STREAM_ID_SIZE, MESSAGE_NUMBER_MASK, SEQUENCE_MASK,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+/// A SRT packet, which is either a data or a control packet.
+/// draft-sharabayko-srt-01 - 3. Packet Structure
+pub enum Packet {
+	Data(DataPacket),
+	Control(ControlPacket),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// draft-sharabayko-srt-01 - 3.1. Data Packets
+pub struct DataPacket {
+	pub sequence_number: u32,
+	pub position: PacketPosition,
+	pub in_order: bool,
+	/// The key used to encrypt the payload, 0 if it is not encrypted.
+	pub encryption: u8,
+	pub retransmitted: bool,
+	pub message_number: u32,
+	/// The time the packet was sent in microseconds since the connection was
+	/// established.
+	pub timestamp: u32,
+	pub destination_socket_id: u32,
+	pub payload: Bytes,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// draft-sharabayko-srt-01 - 3.2. Control Packets
+pub struct ControlPacket {
+	pub timestamp: u32,
+	pub destination_socket_id: u32,
+	pub control: Control,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Control {
+	Handshake(Handshake),
+	KeepAlive,
+	Ack {
+		/// The number of the ACK, which is echoed by the ACKACK.
+		ack_number: u32,
+		ack: Ack,
+	},
+	/// Lost packets, as inclusive ranges of sequence numbers.
+	Nak(Vec<(u32, u32)>),
+	Shutdown,
+	AckAck {
+		ack_number: u32,
+	},
+	/// The sender dropped a message, the receiver should stop waiting for it.
+	DropRequest {
+		message_number: u32,
+		first: u32,
+		last: u32,
+	},
+	/// Control packets we don't handle
+	Unknown {
+		control_type: u16,
+		subtype: u16,
+		type_specific: u32,
+		data: Bytes,
+	},
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// draft-sharabayko-srt-01 - 3.2.4. ACK (Acknowledgment)
+pub struct Ack {
+	/// The sequence number of the first packet which has not been received.
+	pub last_acknowledged: u32,
+	/// The round trip time in microseconds.
+	pub rtt: u32,
+	/// The variance of the round trip time in microseconds.
+	pub rtt_variance: u32,
+	/// The free space in the receive buffer, in packets.
+	pub available_buffer: u32,
+	pub packets_receiving_rate: u32,
+	pub estimated_link_capacity: u32,
+	pub receiving_rate: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// draft-sharabayko-srt-01 - 3.2.1. Handshake
+pub struct Handshake {
+	pub version: u32,
+	pub encryption: u16,
+	pub extension_field: u16,
+	pub initial_sequence_number: u32,
+	pub mtu: u32,
+	pub max_flow_window: u32,
+	pub handshake_type: HandshakeType,
+	pub socket_id: u32,
+	pub syn_cookie: u32,
+	pub peer_ip: [u8; 16],
+	pub extensions: Vec<HandshakeExtension>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// draft-sharabayko-srt-01 - 3.2.1.1. Handshake Extension Message
+pub enum HandshakeExtension {
+	HsReq(SrtHandshake),
+	HsRsp(SrtHandshake),
+	StreamId(String),
+	/// Extensions we don't handle, the data is the raw content
+	Unknown {
+		extension_type: u16,
+		data: Bytes,
+	},
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The content of the handshake request and response extensions.
+pub struct SrtHandshake {
+	pub version: u32,
+	pub flags: u32,
+	/// The latency the sender of the handshake uses to receive, in
+	/// milliseconds.
+	pub receiver_delay: u16,
+	/// The latency the sender of the handshake wants the peer to use, in
+	/// milliseconds.
+	pub sender_delay: u16,
+}
+
+impl Packet {
+	pub fn demux(data: Bytes) -> io::Result<Self> {
+		let mut reader = io::Cursor::new(data);
+
+		let first = reader.read_u32::<BigEndian>()?;
+		let second = reader.read_u32::<BigEndian>()?;
+		let timestamp = reader.read_u32::<BigEndian>()?;
+		let destination_socket_id = reader.read_u32::<BigEndian>()?;
+
+		if first & 0x8000_0000 == 0 {
+			return Ok(Self::Data(DataPacket {
+				sequence_number: first,
+				position: PacketPosition::from(second >> 30),
+				in_order: second & 0x2000_0000 != 0,
+				encryption: ((second >> 27) & 0b11) as u8,
+				retransmitted: second & 0x0400_0000 != 0,
+				message_number: second & MESSAGE_NUMBER_MASK,
+				timestamp,
+				destination_socket_id,
+				payload: reader.extract_remaining(),
+			}));
+		}
+
+		let control_type = ((first >> 16) & 0x7FFF) as u16;
+		let subtype = (first & 0xFFFF) as u16;
+
+		let control = match control_type {
+			t if t == ControlType::Handshake as u16 => Control::Handshake(Handshake::demux(&mut reader)?),
+			t if t == ControlType::KeepAlive as u16 => Control::KeepAlive,
+			t if t == ControlType::Ack as u16 => {
+				// A light ACK only contains the sequence number, the other fields
+				// are left at zero.
+				let mut fields = [0; 7];
+				for field in fields.iter_mut() {
+					if reader.remaining() < 4 {
+						break;
+					}
+
+					*field = reader.read_u32::<BigEndian>()?;
+				}
+
+				if reader.position() == 16 {
+					return Err(io::Error::new(io::ErrorKind::InvalidData, "ack without a sequence number"));
+				}
+
+				Control::Ack {
+					ack_number: second,
+					ack: Ack {
+						last_acknowledged: fields[0] & SEQUENCE_MASK,
+						rtt: fields[1],
+						rtt_variance: fields[2],
+						available_buffer: fields[3],
+						packets_receiving_rate: fields[4],
+						estimated_link_capacity: fields[5],
+						receiving_rate: fields[6],
+					},
+				}
+			}
+			t if t == ControlType::Nak as u16 => {
+				let mut ranges = Vec::new();
+				while reader.remaining() >= 4 {
+					let first = reader.read_u32::<BigEndian>()?;
+					if first & 0x8000_0000 != 0 {
+						let last = reader.read_u32::<BigEndian>()?;
+						ranges.push((first & SEQUENCE_MASK, last & SEQUENCE_MASK));
+					} else {
+						ranges.push((first, first));
+					}
+				}
+
+				Control::Nak(ranges)
+			}
+			t if t == ControlType::Shutdown as u16 => Control::Shutdown,
+			t if t == ControlType::AckAck as u16 => Control::AckAck { ack_number: second },
+			t if t == ControlType::DropRequest as u16 => Control::DropRequest {
+				message_number: second,
+				first: reader.read_u32::<BigEndian>()? & SEQUENCE_MASK,
+				last: reader.read_u32::<BigEndian>()? & SEQUENCE_MASK,
+			},
+			_ => Control::Unknown {
+				control_type,
+				subtype,
+				type_specific: second,
+				data: reader.extract_remaining(),
+			},
+		};
+
+		Ok(Self::Control(ControlPacket {
+			timestamp,
+			destination_socket_id,
+			control,
+		}))
+	}
+
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		match self {
+			Self::Data(packet) => packet.mux(writer),
+			Self::Control(packet) => packet.mux(writer),
+		}
+	}
+
+	/// Mux the packet into a new buffer.
+	pub fn to_bytes(&self) -> Bytes {
+		let mut data = Vec::new();
+		self.mux(&mut data).expect("writing to a vec can not fail");
+		Bytes::from(data)
+	}
+
+	pub fn destination_socket_id(&self) -> u32 {
+		match self {
+			Self::Data(packet) => packet.destination_socket_id,
+			Self::Control(packet) => packet.destination_socket_id,
+		}
+	}
+}
+
+impl DataPacket {
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		writer.write_u32::<BigEndian>(self.sequence_number & SEQUENCE_MASK)?;
+		writer.write_u32::<BigEndian>(
+			(self.position as u32) << 30
+				| (self.in_order as u32) << 29
+				| ((self.encryption & 0b11) as u32) << 27
+				| (self.retransmitted as u32) << 26
+				| self.message_number & MESSAGE_NUMBER_MASK,
+		)?;
+		writer.write_u32::<BigEndian>(self.timestamp)?;
+		writer.write_u32::<BigEndian>(self.destination_socket_id)?;
+		writer.write_all(&self.payload)?;
+
+		Ok(())
+	}
+}
+
+impl ControlPacket {
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		let (control_type, subtype, type_specific) = match &self.control {
+			Control::Handshake(_) => (ControlType::Handshake as u16, 0, 0),
+			Control::KeepAlive => (ControlType::KeepAlive as u16, 0, 0),
+			Control::Ack { ack_number, .. } => (ControlType::Ack as u16, 0, *ack_number),
+			Control::Nak(_) => (ControlType::Nak as u16, 0, 0),
+			Control::Shutdown => (ControlType::Shutdown as u16, 0, 0),
+			Control::AckAck { ack_number } => (ControlType::AckAck as u16, 0, *ack_number),
+			Control::DropRequest { message_number, .. } => (ControlType::DropRequest as u16, 0, *message_number),
+			Control::Unknown {
+				control_type,
+				subtype,
+				type_specific,
+				..
+			} => (*control_type, *subtype, *type_specific),
+		};
+
+		writer.write_u32::<BigEndian>(0x8000_0000 | ((control_type & 0x7FFF) as u32) << 16 | subtype as u32)?;
+		writer.write_u32::<BigEndian>(type_specific)?;
+		writer.write_u32::<BigEndian>(self.timestamp)?;
+		writer.write_u32::<BigEndian>(self.destination_socket_id)?;
+
+		match &self.control {
+			Control::Handshake(handshake) => handshake.mux(writer)?,
+			Control::KeepAlive | Control::Shutdown | Control::AckAck { .. } => {
+				// These packets have no content, but some implementations expect
+				// at least 4 bytes.
+				writer.write_u32::<BigEndian>(0)?;
+			}
+			Control::Ack { ack, .. } => {
+				writer.write_u32::<BigEndian>(ack.last_acknowledged & SEQUENCE_MASK)?;
+				writer.write_u32::<BigEndian>(ack.rtt)?;
+				writer.write_u32::<BigEndian>(ack.rtt_variance)?;
+				writer.write_u32::<BigEndian>(ack.available_buffer)?;
+				writer.write_u32::<BigEndian>(ack.packets_receiving_rate)?;
+				writer.write_u32::<BigEndian>(ack.estimated_link_capacity)?;
+				writer.write_u32::<BigEndian>(ack.receiving_rate)?;
+			}
+			Control::Nak(ranges) => {
+				for (first, last) in ranges {
+					if first == last {
+						writer.write_u32::<BigEndian>(first & SEQUENCE_MASK)?;
+					} else {
+						writer.write_u32::<BigEndian>(0x8000_0000 | first & SEQUENCE_MASK)?;
+						writer.write_u32::<BigEndian>(last & SEQUENCE_MASK)?;
+					}
+				}
+			}
+			Control::DropRequest { first, last, .. } => {
+				writer.write_u32::<BigEndian>(first & SEQUENCE_MASK)?;
+				writer.write_u32::<BigEndian>(last & SEQUENCE_MASK)?;
+			}
+			Control::Unknown { data, .. } => writer.write_all(data)?,
+		}
+
+		Ok(())
+	}
+}
+
+impl Handshake {
+	pub fn demux(reader: &mut io::Cursor<Bytes>) -> io::Result<Self> {
+		let version = reader.read_u32::<BigEndian>()?;
+		let encryption = reader.read_u16::<BigEndian>()?;
+		let extension_field = reader.read_u16::<BigEndian>()?;
+		let initial_sequence_number = reader.read_u32::<BigEndian>()? & SEQUENCE_MASK;
+		let mtu = reader.read_u32::<BigEndian>()?;
+		let max_flow_window = reader.read_u32::<BigEndian>()?;
+		let handshake_type = HandshakeType::from(reader.read_u32::<BigEndian>()?);
+		let socket_id = reader.read_u32::<BigEndian>()?;
+		let syn_cookie = reader.read_u32::<BigEndian>()?;
+		let mut peer_ip = [0; 16];
+		io::Read::read_exact(reader, &mut peer_ip)?;
+
+		let mut extensions = Vec::new();
+		while reader.remaining() >= 4 {
+			let extension_type = reader.read_u16::<BigEndian>()?;
+			// The length is in 4 byte words
+			let length = reader.read_u16::<BigEndian>()? as usize * 4;
+			let data = reader.read_slice(length)?;
+
+			extensions.push(HandshakeExtension::demux(extension_type, data)?);
+		}
+
+		Ok(Self {
+			version,
+			encryption,
+			extension_field,
+			initial_sequence_number,
+			mtu,
+			max_flow_window,
+			handshake_type,
+			socket_id,
+			syn_cookie,
+			peer_ip,
+			extensions,
+		})
+	}
+
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		writer.write_u32::<BigEndian>(self.version)?;
+		writer.write_u16::<BigEndian>(self.encryption)?;
+		writer.write_u16::<BigEndian>(self.extension_field)?;
+		writer.write_u32::<BigEndian>(self.initial_sequence_number & SEQUENCE_MASK)?;
+		writer.write_u32::<BigEndian>(self.mtu)?;
+		writer.write_u32::<BigEndian>(self.max_flow_window)?;
+		writer.write_u32::<BigEndian>(self.handshake_type.into())?;
+		writer.write_u32::<BigEndian>(self.socket_id)?;
+		writer.write_u32::<BigEndian>(self.syn_cookie)?;
+		writer.write_all(&self.peer_ip)?;
+
+		for extension in &self.extensions {
+			extension.mux(writer)?;
+		}
+
+		Ok(())
+	}
+
+	/// The stream id extension, if there is one.
+	pub fn stream_id(&self) -> Option<&str> {
+		self.extensions.iter().find_map(|extension| match extension {
+			HandshakeExtension::StreamId(stream_id) => Some(stream_id.as_str()),
+			_ => None,
+		})
+	}
+
+	/// The handshake request extension, if there is one.
+	pub fn hs_req(&self) -> Option<&SrtHandshake> {
+		self.extensions.iter().find_map(|extension| match extension {
+			HandshakeExtension::HsReq(hs) => Some(hs),
+			_ => None,
+		})
+	}
+
+	/// The handshake response extension, if there is one.
+	pub fn hs_rsp(&self) -> Option<&SrtHandshake> {
+		self.extensions.iter().find_map(|extension| match extension {
+			HandshakeExtension::HsRsp(hs) => Some(hs),
+			_ => None,
+		})
+	}
+}
+
+impl HandshakeExtension {
+	pub fn demux(extension_type: u16, data: Bytes) -> io::Result<Self> {
+		match extension_type {
+			extension_type::HSREQ | extension_type::HSRSP => {
+				let mut reader = io::Cursor::new(data);
+				let version = reader.read_u32::<BigEndian>()?;
+				let flags = reader.read_u32::<BigEndian>()?;
+				let receiver_delay = reader.read_u16::<BigEndian>()?;
+				let sender_delay = reader.read_u16::<BigEndian>()?;
+
+				let hs = SrtHandshake {
+					version,
+					flags,
+					receiver_delay,
+					sender_delay,
+				};
+
+				if extension_type == extension_type::HSREQ {
+					Ok(Self::HsReq(hs))
+				} else {
+					Ok(Self::HsRsp(hs))
+				}
+			}
+			extension_type::SID => {
+				if data.len() > MAX_STREAM_ID_SIZE {
+					return Err(io::Error::new(io::ErrorKind::InvalidData, "stream id too long"));
+				}
+
+				// The stream id is sent as 32 bit words in little endian, padded
+				// with zeros.
+				let mut stream_id = data.chunks(4).flat_map(|word| word.iter().rev()).copied().collect::<Vec<_>>();
+				while stream_id.last() == Some(&0) {
+					stream_id.pop();
+				}
+
+				String::from_utf8(stream_id)
+					.map(Self::StreamId)
+					.map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "stream id is not utf-8"))
+			}
+			extension_type => Ok(Self::Unknown { extension_type, data }),
+		}
+	}
+
+	pub fn mux<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+		match self {
+			Self::HsReq(hs) | Self::HsRsp(hs) => {
+				writer.write_u16::<BigEndian>(if matches!(self, Self::HsReq(_)) {
+					extension_type::HSREQ
+				} else {
+					extension_type::HSRSP
+				})?;
+				writer.write_u16::<BigEndian>(3)?;
+				writer.write_u32::<BigEndian>(hs.version)?;
+				writer.write_u32::<BigEndian>(hs.flags)?;
+				writer.write_u16::<BigEndian>(hs.receiver_delay)?;
+				writer.write_u16::<BigEndian>(hs.sender_delay)?;
+			}
+			Self::StreamId(stream_id) => {
+				if stream_id.len() > MAX_STREAM_ID_SIZE {
+					return Err(io::Error::new(io::ErrorKind::InvalidInput, "stream id too long"));
+				}
+
+				let words = stream_id.len().div_ceil(4);
+
+				writer.write_u16::<BigEndian>(extension_type::SID)?;
+				writer.write_u16::<BigEndian>(words as u16)?;
+
+				let mut data = stream_id.as_bytes().to_vec();
+				data.resize(words * 4, 0);
+				for word in data.chunks(4) {
+					writer.write_all(&[word[3], word[2], word[1], word[0]])?;
+				}
+			}
+			Self::Unknown { extension_type, data } => {
+				if data.len() % 4 != 0 {
+					return Err(io::Error::new(
+						io::ErrorKind::InvalidInput,
+						"extension size is not a multiple of 4",
+					));
+				}
+
+				writer.write_u16::<BigEndian>(*extension_type)?;
+				writer.write_u16::<BigEndian>((data.len() / 4) as u16)?;
+				writer.write_all(data)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// The address of the peer as it is sent in the handshake.
+pub(crate) fn peer_ip(ip: IpAddr) -> [u8; 16] {
+	let mut peer_ip = [0; 16];
+	match ip {
+		IpAddr::V4(ip) => peer_ip[..4].copy_from_slice(&ip.octets()),
+		IpAddr::V6(ip) => peer_ip.copy_from_slice(&ip.octets()),
+	}
+
+	peer_ip
+}
@@ -0,0 +1,327 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::time::Instant;
+
+use crate::define::{seq_add, seq_offset, FLOW_WINDOW_SIZE};
+use crate::packet::{Ack, DataPacket};
+
+/// The shortest interval at which a lost packet is reported again.
+const MIN_NAK_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The number of ACKs we remember to measure the round trip time.
+const MAX_PENDING_ACKS: usize = 64;
+
+#[derive(Debug)]
+enum Slot {
+	/// The packet has not been received yet.
+	Empty,
+	/// The packet was received and waits to be delivered.
+	Data { payload: Bytes, deliver_at: Instant },
+	/// The sender dropped the packet, so we should not wait for it.
+	Dropped,
+}
+
+#[derive(Debug)]
+struct Loss {
+	sequence_number: u32,
+	last_report: Instant,
+}
+
+#[derive(Debug)]
+/// The receiving side of a connection. It puts packets back in order,
+/// reports lost packets and delivers every packet exactly `latency` after it
+/// was sent (timestamp based packet delivery). Packets which are not
+/// recovered in time are skipped.
+pub struct Receiver {
+	latency: Duration,
+
+	/// The local time at which we received the first packet and its
+	/// timestamp, the send time of every other packet is estimated from it.
+	time_base: Option<(Instant, u64)>,
+	/// The last timestamp extended to 64 bits, because the 32 bit timestamps
+	/// wrap around after about 71 minutes.
+	last_timestamp: u64,
+
+	/// The sequence number of the first slot in the buffer, which is the next
+	/// packet to deliver.
+	head: u32,
+	buffer: VecDeque<Slot>,
+	/// The sequence number after the largest one we received.
+	next_expected: u32,
+	/// Lost packets, sorted by sequence number.
+	loss_list: Vec<Loss>,
+
+	ack_number: u32,
+	last_acknowledged: u32,
+	pending_acks: VecDeque<(u32, Instant)>,
+
+	rtt: Duration,
+	rtt_variance: Duration,
+
+	pub packets_received: u64,
+	pub bytes_received: u64,
+	pub packets_lost: u64,
+	pub packets_dropped: u64,
+}
+
+impl Receiver {
+	pub fn new(initial_sequence_number: u32, latency: Duration) -> Self {
+		Self {
+			latency,
+			time_base: None,
+			last_timestamp: 0,
+			head: initial_sequence_number,
+			buffer: VecDeque::new(),
+			next_expected: initial_sequence_number,
+			loss_list: Vec::new(),
+			ack_number: 0,
+			last_acknowledged: initial_sequence_number,
+			pending_acks: VecDeque::new(),
+			rtt: Duration::from_millis(100),
+			rtt_variance: Duration::from_millis(50),
+			packets_received: 0,
+			bytes_received: 0,
+			packets_lost: 0,
+			packets_dropped: 0,
+		}
+	}
+
+	pub fn rtt(&self) -> Duration {
+		self.rtt
+	}
+
+	/// Handle a data packet. Returns the ranges of packets which we detected
+	/// as lost because of this packet, they should be reported immediately.
+	pub fn on_data(&mut self, packet: DataPacket, now: Instant) -> Vec<(u32, u32)> {
+		let offset = seq_offset(self.head, packet.sequence_number);
+		if offset < 0 || offset as u32 >= FLOW_WINDOW_SIZE {
+			// Either the packet was already delivered or skipped, or it is too far
+			// ahead for our buffer.
+			return Vec::new();
+		}
+
+		let offset = offset as usize;
+		if matches!(self.buffer.get(offset), Some(Slot::Data { .. } | Slot::Dropped)) {
+			return Vec::new();
+		}
+
+		self.packets_received += 1;
+		self.bytes_received += packet.payload.len() as u64;
+
+		let timestamp = self.extend_timestamp(packet.timestamp);
+		let (base, base_timestamp) = *self.time_base.get_or_insert((now, timestamp));
+		let sent_at = if timestamp >= base_timestamp {
+			base + Duration::from_micros(timestamp - base_timestamp)
+		} else {
+			base.checked_sub(Duration::from_micros(base_timestamp - timestamp))
+				.unwrap_or(base)
+		};
+		let deliver_at = sent_at + self.latency;
+
+		if self.buffer.len() <= offset {
+			self.buffer.resize_with(offset + 1, || Slot::Empty);
+		}
+		self.buffer[offset] = Slot::Data {
+			payload: packet.payload,
+			deliver_at,
+		};
+
+		self.remove_losses(packet.sequence_number, packet.sequence_number);
+
+		let mut lost = Vec::new();
+
+		let gap = seq_offset(self.next_expected, packet.sequence_number);
+		if gap > 0 {
+			let last = seq_add(packet.sequence_number, u32::MAX);
+			lost.push((self.next_expected, last));
+
+			for idx in 0..gap as u32 {
+				self.loss_list.push(Loss {
+					sequence_number: seq_add(self.next_expected, idx),
+					last_report: now,
+				});
+			}
+
+			self.packets_lost += gap as u64;
+		}
+
+		if gap >= 0 {
+			self.next_expected = seq_add(packet.sequence_number, 1);
+		}
+
+		lost
+	}
+
+	/// The sender gave up on these packets.
+	pub fn on_drop_request(&mut self, first: u32, last: u32) {
+		let first_offset = seq_offset(self.head, first).max(0);
+		let last_offset = seq_offset(self.head, last);
+		if last_offset < first_offset || last_offset as u32 >= FLOW_WINDOW_SIZE {
+			return;
+		}
+
+		if self.buffer.len() <= last_offset as usize {
+			self.buffer.resize_with(last_offset as usize + 1, || Slot::Empty);
+		}
+
+		for slot in self.buffer.range_mut(first_offset as usize..=last_offset as usize) {
+			if matches!(slot, Slot::Empty) {
+				*slot = Slot::Dropped;
+			}
+		}
+
+		if seq_offset(self.next_expected, last) >= 0 {
+			self.next_expected = seq_add(last, 1);
+		}
+
+		self.remove_losses(first, last);
+	}
+
+	/// The next packet which is ready to be delivered. Packets which are still
+	/// missing when a later packet is due are skipped.
+	pub fn pop(&mut self, now: Instant) -> Option<Bytes> {
+		loop {
+			match self.buffer.front()? {
+				Slot::Dropped => {
+					self.advance();
+				}
+				Slot::Data { deliver_at, .. } => {
+					if *deliver_at > now {
+						return None;
+					}
+
+					let Some(Slot::Data { payload, .. }) = self.advance() else {
+						unreachable!();
+					};
+
+					return Some(payload);
+				}
+				Slot::Empty => {
+					let (idx, deliver_at) = self.buffer.iter().enumerate().find_map(|(idx, slot)| match slot {
+						Slot::Data { deliver_at, .. } => Some((idx, *deliver_at)),
+						_ => None,
+					})?;
+
+					if deliver_at > now {
+						return None;
+					}
+
+					// The packet after the gap is due, so we give up on the missing
+					// packets.
+					let first = self.head;
+					for _ in 0..idx {
+						if matches!(self.advance(), Some(Slot::Empty)) {
+							self.packets_dropped += 1;
+						}
+					}
+
+					self.remove_losses(first, seq_add(self.head, u32::MAX));
+				}
+			}
+		}
+	}
+
+	/// The time at which the next packet is due.
+	pub fn next_deadline(&self) -> Option<Instant> {
+		self.buffer.iter().find_map(|slot| match slot {
+			Slot::Data { deliver_at, .. } => Some(*deliver_at),
+			_ => None,
+		})
+	}
+
+	/// Build an ACK if we received new packets since the last one.
+	pub fn ack(&mut self, now: Instant) -> Option<(u32, Ack)> {
+		let received = self.buffer.iter().take_while(|slot| !matches!(slot, Slot::Empty)).count() as u32;
+		let last_acknowledged = seq_add(self.head, received);
+
+		if self.last_acknowledged == last_acknowledged {
+			return None;
+		}
+
+		self.last_acknowledged = last_acknowledged;
+		self.ack_number = self.ack_number.wrapping_add(1);
+
+		if self.pending_acks.len() >= MAX_PENDING_ACKS {
+			self.pending_acks.pop_front();
+		}
+		self.pending_acks.push_back((self.ack_number, now));
+
+		Some((
+			self.ack_number,
+			Ack {
+				last_acknowledged,
+				rtt: self.rtt.as_micros() as u32,
+				rtt_variance: self.rtt_variance.as_micros() as u32,
+				available_buffer: FLOW_WINDOW_SIZE.saturating_sub(self.buffer.len() as u32),
+				..Default::default()
+			},
+		))
+	}
+
+	/// The peer acknowledged one of our ACKs, which gives us the round trip
+	/// time.
+	pub fn on_ack_ack(&mut self, ack_number: u32, now: Instant) {
+		let Some(idx) = self.pending_acks.iter().position(|(number, _)| *number == ack_number) else {
+			return;
+		};
+
+		let (_, sent_at) = self.pending_acks[idx];
+		self.pending_acks.drain(..=idx);
+
+		let sample = now - sent_at;
+		let deviation = sample.abs_diff(self.rtt);
+
+		self.rtt_variance = (self.rtt_variance * 3 + deviation) / 4;
+		self.rtt = (self.rtt * 7 + sample) / 8;
+	}
+
+	/// Lost packets which have not been reported for a while, they are
+	/// reported again in case the retransmission was lost too.
+	pub fn nak(&mut self, now: Instant) -> Vec<(u32, u32)> {
+		let interval = (self.rtt + self.rtt_variance * 4).max(MIN_NAK_INTERVAL);
+
+		let mut ranges: Vec<(u32, u32)> = Vec::new();
+		for loss in &mut self.loss_list {
+			if loss.last_report + interval > now {
+				continue;
+			}
+
+			loss.last_report = now;
+
+			match ranges.last_mut() {
+				Some((_, last)) if seq_add(*last, 1) == loss.sequence_number => *last = loss.sequence_number,
+				_ => ranges.push((loss.sequence_number, loss.sequence_number)),
+			}
+		}
+
+		ranges
+	}
+
+	fn advance(&mut self) -> Option<Slot> {
+		let slot = self.buffer.pop_front()?;
+		self.head = seq_add(self.head, 1);
+		Some(slot)
+	}
+
+	fn remove_losses(&mut self, first: u32, last: u32) {
+		self.loss_list
+			.retain(|loss| seq_offset(first, loss.sequence_number) < 0 || seq_offset(loss.sequence_number, last) < 0);
+	}
+
+	fn extend_timestamp(&mut self, timestamp: u32) -> u64 {
+		// Pick the wrap around period which is closest to the last timestamp.
+		let period = self.last_timestamp >> 32;
+		let extended = [period.checked_sub(1), Some(period), Some(period + 1)]
+			.into_iter()
+			.flatten()
+			.map(|period| (period << 32) | timestamp as u64)
+			.min_by_key(|extended| extended.abs_diff(self.last_timestamp))
+			.unwrap_or(timestamp as u64);
+
+		self.last_timestamp = self.last_timestamp.max(extended);
+
+		extended
+	}
+}
@@ -0,0 +1,160 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::time::Instant;
+
+use crate::define::{seq_add, seq_offset, PacketPosition, MESSAGE_NUMBER_MASK};
+use crate::packet::DataPacket;
+
+/// How long a packet is kept for retransmission after the receiver would have
+/// delivered it.
+const DROP_MARGIN: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+struct SentPacket {
+	packet: DataPacket,
+	sent_at: Instant,
+}
+
+#[derive(Debug)]
+/// The sending side of a connection. It numbers and timestamps packets and
+/// keeps them until they are acknowledged, so they can be retransmitted when
+/// the receiver reports them as lost. Packets which the receiver would skip
+/// anyway are dropped.
+pub struct Sender {
+	start: Instant,
+	latency: Duration,
+	destination_socket_id: u32,
+
+	next_sequence_number: u32,
+	next_message_number: u32,
+	/// Packets which have not been acknowledged, in order of their sequence
+	/// numbers.
+	buffer: VecDeque<SentPacket>,
+
+	pub packets_sent: u64,
+	pub bytes_sent: u64,
+	pub packets_retransmitted: u64,
+	pub packets_dropped: u64,
+}
+
+impl Sender {
+	pub fn new(start: Instant, initial_sequence_number: u32, latency: Duration, destination_socket_id: u32) -> Self {
+		Self {
+			start,
+			latency,
+			destination_socket_id,
+			next_sequence_number: initial_sequence_number,
+			next_message_number: 1,
+			buffer: VecDeque::new(),
+			packets_sent: 0,
+			bytes_sent: 0,
+			packets_retransmitted: 0,
+			packets_dropped: 0,
+		}
+	}
+
+	/// If every packet has been acknowledged.
+	pub fn is_empty(&self) -> bool {
+		self.buffer.is_empty()
+	}
+
+	/// Turn a message into a packet, which has to be sent to the peer.
+	pub fn push(&mut self, payload: Bytes, now: Instant) -> DataPacket {
+		let packet = DataPacket {
+			sequence_number: self.next_sequence_number,
+			position: PacketPosition::Only,
+			in_order: false,
+			encryption: 0,
+			retransmitted: false,
+			message_number: self.next_message_number,
+			timestamp: self.timestamp(now),
+			destination_socket_id: self.destination_socket_id,
+			payload,
+		};
+
+		self.next_sequence_number = seq_add(self.next_sequence_number, 1);
+		// Message number 0 is reserved
+		self.next_message_number = (self.next_message_number % MESSAGE_NUMBER_MASK) + 1;
+
+		self.packets_sent += 1;
+		self.bytes_sent += packet.payload.len() as u64;
+
+		self.buffer.push_back(SentPacket {
+			packet: packet.clone(),
+			sent_at: now,
+		});
+
+		packet
+	}
+
+	/// The receiver got every packet before `last_acknowledged`.
+	pub fn on_ack(&mut self, last_acknowledged: u32) {
+		while self
+			.buffer
+			.front()
+			.is_some_and(|sent| seq_offset(sent.packet.sequence_number, last_acknowledged) > 0)
+		{
+			self.buffer.pop_front();
+		}
+	}
+
+	/// The receiver lost these packets. Returns the packets we still have,
+	/// which have to be sent again.
+	pub fn on_nak(&mut self, ranges: &[(u32, u32)]) -> Vec<DataPacket> {
+		let Some(head) = self.buffer.front().map(|sent| sent.packet.sequence_number) else {
+			return Vec::new();
+		};
+
+		let mut packets = Vec::new();
+		for (first, last) in ranges {
+			let first = seq_offset(head, *first).max(0);
+			let last = seq_offset(head, *last).min(self.buffer.len() as i32 - 1);
+			if last < first {
+				continue;
+			}
+
+			for sent in self.buffer.range(first as usize..=last as usize) {
+				packets.push(DataPacket {
+					retransmitted: true,
+					..sent.packet.clone()
+				});
+			}
+		}
+
+		self.packets_retransmitted += packets.len() as u64;
+
+		packets
+	}
+
+	/// Drop packets which the receiver would skip even if they arrived now.
+	/// Returns the message number and range of the dropped packets, which
+	/// should be sent to the receiver in a drop request.
+	pub fn drop_expired(&mut self, now: Instant) -> Option<(u32, u32, u32)> {
+		let mut dropped: Option<(u32, u32, u32)> = None;
+
+		while let Some(sent) = self.buffer.front() {
+			if sent.sent_at + self.latency + DROP_MARGIN > now {
+				break;
+			}
+
+			let packet = &sent.packet;
+			match &mut dropped {
+				Some((_, _, last)) => *last = packet.sequence_number,
+				None => dropped = Some((packet.message_number, packet.sequence_number, packet.sequence_number)),
+			}
+
+			self.packets_dropped += 1;
+			self.buffer.pop_front();
+		}
+
+		dropped
+	}
+
+	/// The time since the connection was established in microseconds, which
+	/// wraps around after about 71 minutes.
+	pub fn timestamp(&self, now: Instant) -> u32 {
+		(now - self.start).as_micros() as u32
+	}
+}
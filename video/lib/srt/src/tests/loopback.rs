@@ -0,0 +1,255 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::net::UdpSocket;
+use tokio::time::Instant;
+
+use crate::{reject_reason, DataPacket, Packet, SrtConfig, SrtError, SrtListener, SrtSocket};
+
+fn config(latency: u64) -> SrtConfig {
+	SrtConfig {
+		latency: Duration::from_millis(latency),
+		..Default::default()
+	}
+}
+
+async fn listener(latency: u64) -> SrtListener {
+	SrtListener::bind("127.0.0.1:0".parse().unwrap(), config(latency))
+		.await
+		.unwrap()
+}
+
+/// Forwards packets between a caller and a listener, and drops data packets
+/// which match the filter. Returns the address to connect to and the number
+/// of dropped packets.
+async fn lossy_relay(
+	listener_addr: SocketAddr,
+	filter: impl Fn(&DataPacket) -> bool + Send + 'static,
+) -> (SocketAddr, Arc<AtomicU64>) {
+	let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+	let addr = socket.local_addr().unwrap();
+	let dropped = Arc::new(AtomicU64::new(0));
+
+	tokio::spawn({
+		let dropped = dropped.clone();
+		async move {
+			let mut caller_addr = None;
+			let mut buf = vec![0; 1500];
+
+			loop {
+				let (size, from) = socket.recv_from(&mut buf).await.unwrap();
+				let data = Bytes::copy_from_slice(&buf[..size]);
+
+				if let Ok(Packet::Data(packet)) = Packet::demux(data.clone()) {
+					if filter(&packet) {
+						dropped.fetch_add(1, Ordering::Relaxed);
+						continue;
+					}
+				}
+
+				let to = if from == listener_addr {
+					let Some(caller_addr) = caller_addr else {
+						continue;
+					};
+
+					caller_addr
+				} else {
+					caller_addr = Some(from);
+					listener_addr
+				};
+
+				socket.send_to(&data, to).await.unwrap();
+			}
+		}
+	});
+
+	(addr, dropped)
+}
+
+#[tokio::test]
+async fn test_loopback_handshake() {
+	let mut listener = listener(200).await;
+	let addr = listener.local_addr();
+
+	let (caller, accepted) = tokio::time::timeout(Duration::from_secs(5), async {
+		tokio::join!(SrtSocket::connect(addr, "#!::r=live,m=publish", config(100)), async {
+			let request = listener.accept().await.unwrap();
+			assert_eq!(request.stream_id(), "#!::r=live,m=publish");
+			request.accept()
+		})
+	})
+	.await
+	.unwrap();
+
+	let caller = caller.unwrap();
+	let mut accepted = accepted;
+
+	// Both sides use the higher latency.
+	assert_eq!(accepted.latency(), Duration::from_millis(200));
+	assert_eq!(caller.latency(), Duration::from_millis(200));
+	assert_eq!(accepted.stream_id(), "#!::r=live,m=publish");
+	assert_eq!(caller.peer_addr(), addr);
+	assert!(accepted.peer_addr().ip().is_loopback());
+
+	for idx in 0..10u8 {
+		caller.send(Bytes::from(vec![idx; 188])).unwrap();
+	}
+
+	assert!(matches!(
+		caller.send(Bytes::from(vec![0; 1500])),
+		Err(SrtError::PayloadTooLarge(1500))
+	));
+
+	for idx in 0..10u8 {
+		let data = tokio::time::timeout(Duration::from_secs(5), accepted.recv())
+			.await
+			.unwrap()
+			.unwrap()
+			.unwrap();
+		assert_eq!(data, Bytes::from(vec![idx; 188]));
+	}
+
+	// Closing waits until everything is acknowledged, after which the peer
+	// sees a clean shutdown.
+	caller.close().await;
+	let data = tokio::time::timeout(Duration::from_secs(5), accepted.recv()).await.unwrap();
+	assert!(data.unwrap().is_none());
+
+	let stats = accepted.stats();
+	assert_eq!(stats.packets_received, 10);
+	assert_eq!(stats.bytes_received, 1880);
+	assert_eq!(stats.packets_lost, 0);
+}
+
+#[tokio::test]
+async fn test_loopback_reject() {
+	let mut listener = listener(120).await;
+	let addr = listener.local_addr();
+
+	tokio::spawn(async move {
+		while let Some(request) = listener.accept().await {
+			if request.stream_id() == "unauthorized" {
+				request.reject(reject_reason::UNAUTHORIZED);
+			}
+			// Dropping the request rejects it as well.
+		}
+	});
+
+	let result = SrtSocket::connect(addr, "unauthorized", config(120)).await;
+	assert!(matches!(result, Err(SrtError::Rejected(reject_reason::UNAUTHORIZED))));
+
+	let result = SrtSocket::connect(addr, "dropped", config(120)).await;
+	assert!(matches!(result, Err(SrtError::Rejected(reject_reason::PEER))));
+
+	let result = SrtSocket::connect(addr, &"a".repeat(513), config(120)).await;
+	assert!(matches!(result, Err(SrtError::StreamIdTooLong(513))));
+}
+
+#[tokio::test]
+async fn test_loopback_timeout() {
+	// Nobody answers on this socket.
+	let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+	let result = SrtSocket::connect(
+		socket.local_addr().unwrap(),
+		"",
+		SrtConfig {
+			connect_timeout: Duration::from_millis(600),
+			..Default::default()
+		},
+	)
+	.await;
+	assert!(matches!(result, Err(SrtError::Timeout)));
+}
+
+#[tokio::test]
+async fn test_loopback_retransmission() {
+	let mut listener = listener(200).await;
+
+	// Every 5th packet is lost the first time it is sent.
+	let (addr, dropped) = lossy_relay(listener.local_addr(), |packet| {
+		!packet.retransmitted && packet.payload[0] % 5 == 2
+	})
+	.await;
+
+	let (caller, accepted) = tokio::join!(SrtSocket::connect(addr, "retransmission", config(200)), async {
+		listener.accept().await.unwrap().accept()
+	});
+	let caller = caller.unwrap();
+	let mut accepted = accepted;
+
+	let sender = tokio::spawn(async move {
+		for idx in 0..100u8 {
+			caller.send(Bytes::from(vec![idx; 1316])).unwrap();
+			tokio::time::sleep(Duration::from_millis(2)).await;
+		}
+
+		// Every lost packet is retransmitted once.
+		while caller.stats().packets_retransmitted < 20 {
+			tokio::time::sleep(Duration::from_millis(10)).await;
+		}
+
+		caller.close().await;
+	});
+
+	let mut received = Vec::new();
+	while let Some(data) = tokio::time::timeout(Duration::from_secs(5), accepted.recv())
+		.await
+		.unwrap()
+		.unwrap()
+	{
+		received.push(data[0]);
+	}
+
+	// Every packet arrived in order, even though 20 of them were lost.
+	assert_eq!(received, (0..100).collect::<Vec<_>>());
+	assert_eq!(dropped.load(Ordering::Relaxed), 20);
+
+	let stats = accepted.stats();
+	assert_eq!(stats.packets_lost, 20);
+	assert_eq!(stats.packets_dropped, 0);
+	assert_eq!(stats.packets_received, 100);
+
+	tokio::time::timeout(Duration::from_secs(5), sender).await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_loopback_latency_window() {
+	let mut listener = listener(300).await;
+	let addr = listener.local_addr();
+
+	let (caller, accepted) = tokio::join!(SrtSocket::connect(addr, "latency", config(300)), async {
+		listener.accept().await.unwrap().accept()
+	});
+	let caller = caller.unwrap();
+	let mut accepted = accepted;
+
+	let start = Instant::now();
+	let sender = tokio::spawn(async move {
+		for idx in 0..10u8 {
+			caller.send(Bytes::from(vec![idx])).unwrap();
+			tokio::time::sleep(Duration::from_millis(20)).await;
+		}
+
+		caller
+	});
+
+	let mut delivered = Vec::new();
+	for _ in 0..10 {
+		let data = accepted.recv().await.unwrap().unwrap();
+		delivered.push((data[0], Instant::now() - start));
+	}
+
+	// Every packet is held back for the latency, while keeping the spacing
+	// with which it was sent.
+	for (idx, elapsed) in delivered {
+		let sent = Duration::from_millis(idx as u64 * 20);
+		assert!(elapsed >= sent + Duration::from_millis(290), "{idx}: {elapsed:?}");
+		assert!(elapsed <= sent + Duration::from_millis(400), "{idx}: {elapsed:?}");
+	}
+
+	sender.await.unwrap().close().await;
+}
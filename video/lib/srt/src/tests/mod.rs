@@ -0,0 +1,3 @@
+mod loopback;
+mod packet;
+mod receiver;
@@ -0,0 +1,179 @@
+use bytes::Bytes;
+
+use crate::{
+	extension_flags, seq_add, seq_offset, Ack, Control, ControlPacket, DataPacket, Handshake, HandshakeExtension,
+	HandshakeType, Packet, PacketPosition, SrtHandshake,
+};
+
+#[test]
+fn test_sequence_numbers() {
+	assert_eq!(seq_add(0x7FFF_FFFF, 1), 0);
+	assert_eq!(seq_add(5, 3), 8);
+	assert_eq!(seq_offset(0x7FFF_FFFE, 1), 3);
+	assert_eq!(seq_offset(1, 0x7FFF_FFFE), -3);
+	assert_eq!(seq_offset(10, 10), 0);
+}
+
+#[test]
+fn test_data_packet() {
+	let packet = Packet::Data(DataPacket {
+		sequence_number: 0x1234_5678,
+		position: PacketPosition::Only,
+		in_order: false,
+		encryption: 0,
+		retransmitted: true,
+		message_number: 42,
+		timestamp: 1_000_000,
+		destination_socket_id: 0xABCD,
+		payload: Bytes::from_static(b"payload"),
+	});
+
+	let data = packet.to_bytes();
+	assert_eq!(
+		&data[..16],
+		&[
+			0x12, 0x34, 0x56, 0x78, // sequence number
+			0xC4, 0x00, 0x00, 0x2A, // position, retransmitted flag and message number
+			0x00, 0x0F, 0x42, 0x40, // timestamp
+			0x00, 0x00, 0xAB, 0xCD, // destination socket id
+		]
+	);
+	assert_eq!(&data[16..], b"payload");
+
+	assert_eq!(Packet::demux(data).unwrap(), packet);
+}
+
+#[test]
+fn test_handshake() {
+	let packet = Packet::Control(ControlPacket {
+		timestamp: 0,
+		destination_socket_id: 0,
+		control: Control::Handshake(Handshake {
+			version: 5,
+			encryption: 0,
+			extension_field: extension_flags::HSREQ | extension_flags::CONFIG,
+			initial_sequence_number: 100,
+			mtu: 1500,
+			max_flow_window: 8192,
+			handshake_type: HandshakeType::Conclusion,
+			socket_id: 1,
+			syn_cookie: 2,
+			peer_ip: [127, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+			extensions: vec![
+				HandshakeExtension::HsReq(SrtHandshake {
+					version: 0x010500,
+					flags: 0x3F,
+					receiver_delay: 120,
+					sender_delay: 200,
+				}),
+				HandshakeExtension::StreamId("live_stream".to_string()),
+			],
+		}),
+	});
+
+	let data = packet.to_bytes();
+	assert_eq!(data.len(), 16 + 48 + 16 + 16);
+	// The control type of a handshake is 0
+	assert_eq!(&data[..4], &[0x80, 0x00, 0x00, 0x00]);
+	// The latencies of the handshake request
+	assert_eq!(&data[76..80], &[0x00, 0x78, 0x00, 0xC8]);
+	// The stream id is sent in little endian words, padded with zeros
+	assert_eq!(&data[80..84], &[0x00, 0x05, 0x00, 0x03]);
+	assert_eq!(&data[84..], b"evilrts_\0mae");
+
+	let demuxed = Packet::demux(data.clone()).unwrap();
+	assert_eq!(demuxed, packet);
+
+	let Packet::Control(ControlPacket {
+		control: Control::Handshake(handshake),
+		..
+	}) = demuxed
+	else {
+		unreachable!();
+	};
+	assert_eq!(handshake.stream_id(), Some("live_stream"));
+	assert_eq!(handshake.hs_req().unwrap().sender_delay, 200);
+	assert!(handshake.hs_rsp().is_none());
+
+	// A rejection uses the handshake type for the reason.
+	let mut data = data.to_vec();
+	data[36..40].copy_from_slice(&1401u32.to_be_bytes());
+	let Packet::Control(ControlPacket {
+		control: Control::Handshake(handshake),
+		..
+	}) = Packet::demux(Bytes::from(data)).unwrap()
+	else {
+		unreachable!();
+	};
+	assert_eq!(handshake.handshake_type, HandshakeType::Rejection(1401));
+}
+
+#[test]
+fn test_control_packets() {
+	for control in [
+		Control::KeepAlive,
+		Control::Shutdown,
+		Control::AckAck { ack_number: 7 },
+		Control::Ack {
+			ack_number: 7,
+			ack: Ack {
+				last_acknowledged: 100,
+				rtt: 20_000,
+				rtt_variance: 5_000,
+				available_buffer: 8000,
+				..Default::default()
+			},
+		},
+		Control::Nak(vec![(10, 10), (12, 20)]),
+		Control::DropRequest {
+			message_number: 3,
+			first: 10,
+			last: 12,
+		},
+	] {
+		let packet = Packet::Control(ControlPacket {
+			timestamp: 123,
+			destination_socket_id: 456,
+			control,
+		});
+
+		assert_eq!(Packet::demux(packet.to_bytes()).unwrap(), packet);
+	}
+
+	// A single lost packet takes one word, a range takes two.
+	let data = Packet::Control(ControlPacket {
+		timestamp: 0,
+		destination_socket_id: 0,
+		control: Control::Nak(vec![(10, 10), (12, 20)]),
+	})
+	.to_bytes();
+	assert_eq!(
+		&data[16..],
+		&[0x00, 0x00, 0x00, 0x0A, 0x80, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00, 0x14]
+	);
+
+	// A light ACK only has the sequence number.
+	let light_ack = Bytes::from_static(&[
+		0x80, 0x02, 0x00, 0x00, // control type
+		0x00, 0x00, 0x00, 0x00, // ack number
+		0x00, 0x00, 0x00, 0x00, // timestamp
+		0x00, 0x00, 0x00, 0x01, // destination socket id
+		0x00, 0x00, 0x00, 0x64, // last acknowledged
+	]);
+	assert_eq!(
+		Packet::demux(light_ack).unwrap(),
+		Packet::Control(ControlPacket {
+			timestamp: 0,
+			destination_socket_id: 1,
+			control: Control::Ack {
+				ack_number: 0,
+				ack: Ack {
+					last_acknowledged: 100,
+					..Default::default()
+				},
+			},
+		})
+	);
+
+	assert!(Packet::demux(Bytes::from_static(&[0x80, 0x00])).is_err());
+}
@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::time::Instant;
+
+use crate::receiver::Receiver;
+use crate::sender::Sender;
+use crate::seq_add;
+
+const LATENCY: Duration = Duration::from_millis(100);
+
+/// The sequence numbers wrap around during the tests.
+const INITIAL_SEQUENCE_NUMBER: u32 = 0x7FFF_FFFE;
+
+fn ms(ms: u64) -> Duration {
+	Duration::from_millis(ms)
+}
+
+fn setup() -> (Instant, Sender, Receiver) {
+	let start = Instant::now();
+	let sender = Sender::new(start, INITIAL_SEQUENCE_NUMBER, LATENCY, 1);
+	let receiver = Receiver::new(INITIAL_SEQUENCE_NUMBER, LATENCY);
+
+	(start, sender, receiver)
+}
+
+#[test]
+fn test_receiver_latency() {
+	let (start, mut sender, mut receiver) = setup();
+
+	for idx in 0..5u8 {
+		let packet = sender.push(Bytes::from(vec![idx]), start + ms(idx as u64 * 10));
+		// Every packet takes 5ms to arrive
+		assert!(receiver.on_data(packet, start + ms(idx as u64 * 10 + 5)).is_empty());
+	}
+
+	// Nothing is delivered before the latency has passed since the packet was
+	// sent.
+	assert_eq!(receiver.pop(start + ms(104)), None);
+	assert_eq!(receiver.next_deadline(), Some(start + ms(105)));
+	assert_eq!(receiver.pop(start + ms(105)), Some(Bytes::from_static(&[0])));
+	assert_eq!(receiver.pop(start + ms(105)), None);
+
+	// Packets keep their spacing
+	assert_eq!(receiver.pop(start + ms(135)), Some(Bytes::from_static(&[1])));
+	assert_eq!(receiver.pop(start + ms(135)), Some(Bytes::from_static(&[2])));
+	assert_eq!(receiver.pop(start + ms(135)), Some(Bytes::from_static(&[3])));
+	assert_eq!(receiver.pop(start + ms(135)), None);
+
+	let (_, ack) = receiver.ack(start + ms(135)).unwrap();
+	assert_eq!(ack.last_acknowledged, seq_add(INITIAL_SEQUENCE_NUMBER, 5));
+	// Nothing changed, so there is no new ACK
+	assert!(receiver.ack(start + ms(136)).is_none());
+
+	assert!(!sender.is_empty());
+	sender.on_ack(ack.last_acknowledged);
+	assert!(sender.is_empty());
+
+	assert_eq!(receiver.packets_received, 5);
+}
+
+#[test]
+fn test_receiver_retransmission() {
+	let (start, mut sender, mut receiver) = setup();
+
+	let packets = (0..6u8)
+		.map(|idx| sender.push(Bytes::from(vec![idx]), start + ms(idx as u64)))
+		.collect::<Vec<_>>();
+
+	// Packets 2 and 3 are lost
+	let now = start + ms(10);
+	for packet in packets.iter().filter(|p| ![2, 3].contains(&p.payload[0])) {
+		let lost = receiver.on_data(packet.clone(), now);
+		if packet.payload[0] == 4 {
+			assert_eq!(
+				lost,
+				vec![(seq_add(INITIAL_SEQUENCE_NUMBER, 2), seq_add(INITIAL_SEQUENCE_NUMBER, 3))]
+			);
+		} else {
+			assert!(lost.is_empty());
+		}
+	}
+	assert_eq!(receiver.packets_lost, 2);
+
+	// The ACK stops at the first lost packet
+	let (ack_number, ack) = receiver.ack(now).unwrap();
+	assert_eq!(ack.last_acknowledged, seq_add(INITIAL_SEQUENCE_NUMBER, 2));
+	sender.on_ack(ack.last_acknowledged);
+
+	// The round trip time is measured with the ACKACK
+	receiver.on_ack_ack(ack_number, now + ms(20));
+	assert_eq!(receiver.rtt(), (ms(100) * 7 + ms(20)) / 8);
+
+	// The loss was just reported, so it is not reported again yet
+	assert!(receiver.nak(now + ms(1)).is_empty());
+
+	let retransmitted = sender.on_nak(&[(seq_add(INITIAL_SEQUENCE_NUMBER, 2), seq_add(INITIAL_SEQUENCE_NUMBER, 3))]);
+	assert_eq!(retransmitted.len(), 2);
+	assert!(retransmitted.iter().all(|p| p.retransmitted));
+	assert_eq!(sender.packets_retransmitted, 2);
+
+	// Packet 2 is lost again, so it is reported again after a while
+	assert!(receiver.on_data(retransmitted[1].clone(), now + ms(20)).is_empty());
+	assert!(receiver.nak(now + ms(21)).is_empty());
+	assert_eq!(
+		receiver.nak(now + ms(500)),
+		vec![(seq_add(INITIAL_SEQUENCE_NUMBER, 2), seq_add(INITIAL_SEQUENCE_NUMBER, 2))]
+	);
+
+	assert!(receiver.on_data(retransmitted[0].clone(), now + ms(30)).is_empty());
+
+	// Everything is delivered in order
+	let delivered = std::iter::from_fn(|| receiver.pop(start + ms(200))).collect::<Vec<_>>();
+	assert_eq!(delivered, (0..6u8).map(|idx| Bytes::from(vec![idx])).collect::<Vec<_>>());
+	assert_eq!(receiver.packets_dropped, 0);
+}
+
+#[test]
+fn test_receiver_too_late() {
+	let (start, mut sender, mut receiver) = setup();
+
+	let packets = (0..3u8)
+		.map(|idx| sender.push(Bytes::from(vec![idx]), start + ms(idx as u64 * 10)))
+		.collect::<Vec<_>>();
+
+	receiver.on_data(packets[0].clone(), start);
+	receiver.on_data(packets[2].clone(), start + ms(20));
+
+	assert_eq!(receiver.pop(start + ms(100)), Some(Bytes::from_static(&[0])));
+	// We keep waiting for the lost packet until the packet after it is due
+	assert_eq!(receiver.pop(start + ms(119)), None);
+	assert_eq!(receiver.pop(start + ms(120)), Some(Bytes::from_static(&[2])));
+	assert_eq!(receiver.packets_dropped, 1);
+
+	// The lost packet is not reported anymore, and is ignored when it arrives
+	assert!(receiver.nak(start + ms(1000)).is_empty());
+	assert!(receiver.on_data(packets[1].clone(), start + ms(130)).is_empty());
+	assert_eq!(receiver.pop(start + ms(1000)), None);
+
+	// The sender gives up on packets when the receiver would skip them
+	assert_eq!(sender.drop_expired(start + ms(1000)), None);
+	assert_eq!(
+		sender.drop_expired(start + ms(1110)),
+		Some((1, INITIAL_SEQUENCE_NUMBER, seq_add(INITIAL_SEQUENCE_NUMBER, 1)))
+	);
+	assert_eq!(sender.packets_dropped, 2);
+}
+
+#[test]
+fn test_receiver_drop_request() {
+	let (start, mut sender, mut receiver) = setup();
+
+	let packets = (0..3u8)
+		.map(|idx| sender.push(Bytes::from(vec![idx]), start))
+		.collect::<Vec<_>>();
+
+	receiver.on_data(packets[0].clone(), start);
+	receiver.on_data(packets[2].clone(), start);
+
+	// The sender dropped the packet, so there is no reason to wait for it
+	receiver.on_drop_request(packets[1].sequence_number, packets[1].sequence_number);
+	assert!(receiver.nak(start + ms(1000)).is_empty());
+
+	let (_, ack) = receiver.ack(start).unwrap();
+	assert_eq!(ack.last_acknowledged, seq_add(INITIAL_SEQUENCE_NUMBER, 3));
+
+	let delivered = std::iter::from_fn(|| receiver.pop(start + ms(100))).collect::<Vec<_>>();
+	assert_eq!(delivered, vec![Bytes::from_static(&[0]), Bytes::from_static(&[2])]);
+	assert_eq!(receiver.packets_dropped, 0);
+}
+
+#[test]
+fn test_receiver_timestamp_wrap_around() {
+	let start = Instant::now();
+	let mut receiver = Receiver::new(0, LATENCY);
+
+	let packet = |sequence_number: u32, timestamp: u32| crate::DataPacket {
+		sequence_number,
+		position: crate::PacketPosition::Only,
+		in_order: false,
+		encryption: 0,
+		retransmitted: false,
+		message_number: 1,
+		timestamp,
+		destination_socket_id: 1,
+		payload: Bytes::from(vec![sequence_number as u8]),
+	};
+
+	receiver.on_data(packet(0, u32::MAX - 9_999), start);
+	// 20ms later the timestamp wrapped around
+	receiver.on_data(packet(1, 10_000), start + ms(20));
+
+	assert_eq!(receiver.pop(start + ms(100)), Some(Bytes::from_static(&[0])));
+	assert_eq!(receiver.pop(start + ms(119)), None);
+	assert_eq!(receiver.next_deadline(), Some(start + ms(120)));
+	assert_eq!(receiver.pop(start + ms(120)), Some(Bytes::from_static(&[1])));
+}
@@ -1,11 +1,15 @@
+use av1::frame::FrameHeaderObu;
+use av1::metadata::{Metadata, MetadataObu};
 use av1::seq::SequenceHeaderObu;
-use av1::{AV1CodecConfigurationRecord, ObuHeader, ObuType};
+use av1::{AV1CodecConfigurationRecord, ObuHeader, ObuType, Obus};
 use bytes::Bytes;
 use bytesio::bit_reader::BitReader;
 use flv::FrameType;
 use mp4::types::av01::Av01;
 use mp4::types::av1c::Av1C;
+use mp4::types::clli::Clli;
 use mp4::types::colr::{ColorType, Colr};
+use mp4::types::mdcv::Mdcv;
 use mp4::types::stsd::{SampleEntry, VisualSampleEntry};
 use mp4::types::trun::{TrunSample, TrunSampleFlag};
 use mp4::DynBox;
@@ -26,26 +30,67 @@ pub fn stsd_entry(config: AV1CodecConfigurationRecord) -> Result<(DynBox, Sequen
 	// Which it almost never is.
 	// So for AV1 we rely on the framerate being set in the scriptdata tag
 
+	let mut visual_sample_entry = VisualSampleEntry::new(
+		seq_obu.max_frame_width as u16,
+		seq_obu.max_frame_height as u16,
+		Some(Colr::new(ColorType::Nclx {
+			color_primaries: seq_obu.color_config.color_primaries as u16,
+			matrix_coefficients: seq_obu.color_config.matrix_coefficients as u16,
+			transfer_characteristics: seq_obu.color_config.transfer_characteristics as u16,
+			full_range_flag: seq_obu.color_config.full_color_range,
+		})),
+	);
+
+	// The config OBUs can contain HDR metadata OBUs after the sequence header.
+	for obu in Obus::new(config.config_obu.clone()).skip(1) {
+		let (header, data) = obu?;
+		if header.obu_type != ObuType::Metadata {
+			continue;
+		}
+
+		match MetadataObu::parse(header, data)?.metadata {
+			Metadata::HdrMdcv(mdcv) => {
+				visual_sample_entry.mdcv = Some(Mdcv::new(
+					mdcv.display_primaries(),
+					mdcv.white_point(),
+					mdcv.max_display_mastering_luminance(),
+					mdcv.min_display_mastering_luminance(),
+				));
+			}
+			Metadata::HdrCll(cll) => {
+				visual_sample_entry.clli = Some(Clli::new(cll.max_cll, cll.max_fall));
+			}
+			_ => {}
+		}
+	}
+
 	Ok((
-		Av01::new(
-			SampleEntry::new(VisualSampleEntry::new(
-				seq_obu.max_frame_width as u16,
-				seq_obu.max_frame_height as u16,
-				Some(Colr::new(ColorType::Nclx {
-					color_primaries: seq_obu.color_config.color_primaries as u16,
-					matrix_coefficients: seq_obu.color_config.matrix_coefficients as u16,
-					transfer_characteristics: seq_obu.color_config.transfer_characteristics as u16,
-					full_range_flag: seq_obu.color_config.full_color_range,
-				})),
-			)),
-			Av1C::new(config),
-			None,
-		)
-		.into(),
+		Av01::new(SampleEntry::new(visual_sample_entry), Av1C::new(config), None).into(),
 		seq_obu,
 	))
 }
 
+/// Checks if the temporal unit starts with a shown key frame. The sequence
+/// header is updated if the temporal unit contains one. Returns `None` if
+/// there is no frame header or no sequence header to parse it with.
+pub fn is_keyframe(data: &Bytes, sequence_header: &mut Option<SequenceHeaderObu>) -> Option<bool> {
+	for obu in Obus::new(data.clone()) {
+		let (header, data) = obu.ok()?;
+		match header.obu_type {
+			ObuType::SequenceHeader => {
+				*sequence_header = Some(SequenceHeaderObu::parse(header, data).ok()?);
+			}
+			ObuType::FrameHeader | ObuType::Frame => {
+				let frame_header = FrameHeaderObu::parse(header, data, sequence_header.as_ref()?).ok()?;
+				return Some(frame_header.is_keyframe());
+			}
+			_ => {}
+		}
+	}
+
+	None
+}
+
 pub fn trun_sample(frame_type: FrameType, duration: u32, data: &Bytes) -> Result<TrunSample, TransmuxError> {
 	Ok(TrunSample {
 		composition_time_offset: None,
@@ -1,9 +1,13 @@
 use bytes::Bytes;
 use flv::FrameType;
-use h265::{HEVCDecoderConfigurationRecord, Sps};
+use h265::{
+	ContentLightLevelInfo, HEVCDecoderConfigurationRecord, MasteringDisplayColourVolume, SeiMessage, SeiPayloadType, Sps,
+};
+use mp4::types::clli::Clli;
 use mp4::types::colr::{ColorType, Colr};
 use mp4::types::hev1::Hev1;
 use mp4::types::hvcc::HvcC;
+use mp4::types::mdcv::Mdcv;
 use mp4::types::stsd::{SampleEntry, VisualSampleEntry};
 use mp4::types::trun::{TrunSample, TrunSampleFlag};
 use mp4::DynBox;
@@ -31,13 +35,39 @@ pub fn stsd_entry(config: HEVCDecoderConfigurationRecord) -> Result<(DynBox, Sps
 		})
 	});
 
+	let mut visual_sample_entry = VisualSampleEntry::new(sps.width as u16, sps.height as u16, colr);
+
+	// HDR10 metadata is carried in the prefix SEI NAL units of the decoder
+	// configuration record.
+	for sei in config
+		.arrays
+		.iter()
+		.filter(|a| a.nal_unit_type == h265::NaluType::PrefixSei)
+		.flat_map(|a| a.nalus.iter())
+	{
+		for message in SeiMessage::parse(sei.clone())? {
+			match message.payload_type {
+				SeiPayloadType::MasteringDisplayColourVolume => {
+					let mdcv = MasteringDisplayColourVolume::parse(message.payload)?;
+					visual_sample_entry.mdcv = Some(Mdcv::new(
+						mdcv.display_primaries,
+						mdcv.white_point,
+						mdcv.max_display_mastering_luminance,
+						mdcv.min_display_mastering_luminance,
+					));
+				}
+				SeiPayloadType::ContentLightLevelInfo => {
+					let clli = ContentLightLevelInfo::parse(message.payload)?;
+					visual_sample_entry.clli =
+						Some(Clli::new(clli.max_content_light_level, clli.max_pic_average_light_level));
+				}
+				_ => {}
+			}
+		}
+	}
+
 	Ok((
-		Hev1::new(
-			SampleEntry::new(VisualSampleEntry::new(sps.width as u16, sps.height as u16, colr)),
-			HvcC::new(config),
-			None,
-		)
-		.into(),
+		Hev1::new(SampleEntry::new(visual_sample_entry), HvcC::new(config), None).into(),
 		sps,
 	))
 }
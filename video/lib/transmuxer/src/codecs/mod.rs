@@ -2,3 +2,5 @@ pub mod aac;
 pub mod av1;
 pub mod avc;
 pub mod hevc;
+pub mod mp3;
+pub mod opus;
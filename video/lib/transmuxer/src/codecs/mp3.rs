@@ -0,0 +1,161 @@
+use bytes::Bytes;
+use mp4::types::esds::descriptor::types::decoder_config::DecoderConfigDescriptor;
+use mp4::types::esds::descriptor::types::es::EsDescriptor;
+use mp4::types::esds::Esds;
+use mp4::types::mp4a::{Mp4a, MP3_OBJECT_TYPE_INDICATION};
+use mp4::types::stsd::{AudioSampleEntry, SampleEntry};
+use mp4::types::trun::{TrunSample, TrunSampleFlag};
+use mp4::DynBox;
+
+use crate::TransmuxError;
+
+/// The bitrates in kbit/s, by bitrate index. Index 0 is the free format and
+/// index 15 is invalid.
+/// ISO/IEC 11172-3 - 2.4.2.3, ISO/IEC 13818-3 - 2.4.2.3
+const MPEG1_LAYER1_BITRATES: [u32; 15] = [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448];
+const MPEG1_LAYER2_BITRATES: [u32; 15] = [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384];
+const MPEG1_LAYER3_BITRATES: [u32; 15] = [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320];
+const MPEG2_LAYER1_BITRATES: [u32; 15] = [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256];
+const MPEG2_LAYER23_BITRATES: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The header in front of every MPEG audio frame.
+/// ISO/IEC 11172-3 - 2.4.1.3
+pub struct Mp3Header {
+	pub sample_rate: u32,
+	pub channels: u8,
+	/// The number of samples in the frame
+	pub samples: u32,
+	/// The size of the frame including the header, this is unknown for the
+	/// free format.
+	pub frame_size: Option<usize>,
+}
+
+impl Mp3Header {
+	pub fn parse(data: &[u8]) -> Option<Self> {
+		if data.len() < 4 || data[0] != 0xFF || data[1] & 0xE0 != 0xE0 {
+			return None;
+		}
+
+		// 0 is MPEG 2.5, 2 is MPEG 2 and 3 is MPEG 1
+		let version = (data[1] >> 3) & 0b11;
+		// 1 is layer III, 2 is layer II and 3 is layer I
+		let layer = (data[1] >> 1) & 0b11;
+		let bitrate_index = (data[2] >> 4) as usize;
+		let sample_rate_index = ((data[2] >> 2) & 0b11) as usize;
+		let padding = ((data[2] >> 1) & 0b1) as u32;
+		let channel_mode = data[3] >> 6;
+
+		if version == 1 || layer == 0 || bitrate_index == 15 || sample_rate_index == 3 {
+			return None;
+		}
+
+		let mpeg1 = version == 3;
+
+		let sample_rate = [44100, 48000, 32000][sample_rate_index]
+			>> match version {
+				3 => 0,
+				2 => 1,
+				_ => 2,
+			};
+
+		let samples = match (layer, mpeg1) {
+			(3, _) => 384,
+			(2, _) | (1, true) => 1152,
+			_ => 576,
+		};
+
+		let bitrate = match (layer, mpeg1) {
+			(3, true) => MPEG1_LAYER1_BITRATES,
+			(2, true) => MPEG1_LAYER2_BITRATES,
+			(1, true) => MPEG1_LAYER3_BITRATES,
+			(3, false) => MPEG2_LAYER1_BITRATES,
+			_ => MPEG2_LAYER23_BITRATES,
+		}[bitrate_index]
+			* 1000;
+
+		let frame_size = if bitrate == 0 {
+			None
+		} else if layer == 3 {
+			// Layer I slots are 4 bytes long
+			Some(((12 * bitrate / sample_rate + padding) * 4) as usize)
+		} else {
+			Some((samples / 8 * bitrate / sample_rate + padding) as usize)
+		};
+
+		Some(Self {
+			sample_rate,
+			channels: if channel_mode == 3 { 1 } else { 2 },
+			samples,
+			frame_size,
+		})
+	}
+}
+
+/// MP3 has no sequence header, so the stsd entry is created from the header
+/// of the first frame.
+pub fn stsd_entry(data: Bytes) -> Result<(DynBox, Mp3Header), TransmuxError> {
+	let header = Mp3Header::parse(&data).ok_or(TransmuxError::InvalidMp3Header)?;
+
+	Ok((
+		Mp4a::new(
+			SampleEntry::new(AudioSampleEntry::new(header.channels as u16, 16, header.sample_rate)),
+			Esds::new(EsDescriptor::new(
+				2,
+				0,
+				Some(0),
+				None,
+				Some(0),
+				Some(DecoderConfigDescriptor::new(
+					MP3_OBJECT_TYPE_INDICATION,
+					0x05, // audio stream
+					0,    // max bitrate
+					0,    // avg bitrate
+					None,
+				)),
+				None,
+			)),
+			None,
+		)
+		.into(),
+		header,
+	))
+}
+
+/// A tag can contain multiple frames, the duration is the number of samples
+/// in all of them.
+pub fn trun_sample(data: &Bytes) -> Result<(TrunSample, u32), TransmuxError> {
+	let mut duration = 0;
+	let mut offset = 0;
+
+	while offset < data.len() {
+		let header = Mp3Header::parse(&data[offset..]).ok_or(TransmuxError::InvalidMp3Header)?;
+		duration += header.samples;
+
+		match header.frame_size {
+			Some(frame_size) => offset += frame_size,
+			// We can not find the next frame of the free format, so we assume
+			// there is only one.
+			None => break,
+		}
+	}
+
+	Ok((
+		TrunSample {
+			duration: Some(duration),
+			composition_time_offset: None,
+			flags: Some(TrunSampleFlag {
+				reserved: 0,
+				is_leading: 0,
+				sample_degradation_priority: 0,
+				sample_depends_on: 2,
+				sample_has_redundancy: 0,
+				sample_is_depended_on: 0,
+				sample_is_non_sync_sample: false,
+				sample_padding_value: 0,
+			}),
+			size: Some(data.len() as u32),
+		},
+		duration,
+	))
+}
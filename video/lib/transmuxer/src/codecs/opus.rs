@@ -0,0 +1,144 @@
+use byteorder::{LittleEndian, ReadBytesExt};
+use bytes::{Buf, Bytes};
+use mp4::types::dops::{ChannelMappingTable, Dops};
+use mp4::types::opus::Opus;
+use mp4::types::stsd::{AudioSampleEntry, SampleEntry};
+use mp4::types::trun::{TrunSample, TrunSampleFlag};
+use mp4::DynBox;
+
+use crate::TransmuxError;
+
+/// Opus is always decoded at 48kHz, the input sample rate in the header is
+/// only informational.
+pub const OPUS_SAMPLE_RATE: u32 = 48000;
+
+/// Create the stsd entry from the `OpusHead` header, which is what the
+/// enhanced RTMP sequence start contains.
+/// RFC 7845 - 5.1
+pub fn stsd_entry(data: Bytes) -> Result<(DynBox, Dops), TransmuxError> {
+	let dops = parse_opus_head(data).ok_or(TransmuxError::InvalidOpusHead)?;
+
+	Ok((
+		Opus::new(
+			SampleEntry::new(AudioSampleEntry::new(dops.output_channel_count as u16, 16, OPUS_SAMPLE_RATE)),
+			dops.clone(),
+			None,
+		)
+		.into(),
+		dops,
+	))
+}
+
+fn parse_opus_head(data: Bytes) -> Option<Dops> {
+	let mut reader = std::io::Cursor::new(data);
+
+	let mut magic = [0; 8];
+	if reader.remaining() < magic.len() {
+		return None;
+	}
+
+	reader.copy_to_slice(&mut magic);
+	if &magic != b"OpusHead" {
+		return None;
+	}
+
+	// Only the major version has to match, the minor version is backwards
+	// compatible.
+	let version = reader.read_u8().ok()?;
+	if version >> 4 != 0 {
+		return None;
+	}
+
+	let output_channel_count = reader.read_u8().ok()?;
+	let pre_skip = reader.read_u16::<LittleEndian>().ok()?;
+	let input_sample_rate = reader.read_u32::<LittleEndian>().ok()?;
+	let output_gain = reader.read_i16::<LittleEndian>().ok()?;
+	let channel_mapping_family = reader.read_u8().ok()?;
+
+	if output_channel_count == 0 {
+		return None;
+	}
+
+	let channel_mapping_table = if channel_mapping_family != 0 {
+		let stream_count = reader.read_u8().ok()?;
+		let coupled_count = reader.read_u8().ok()?;
+
+		if reader.remaining() < output_channel_count as usize {
+			return None;
+		}
+
+		let mut channel_mapping = vec![0; output_channel_count as usize];
+		reader.copy_to_slice(&mut channel_mapping);
+
+		Some(ChannelMappingTable {
+			stream_count,
+			coupled_count,
+			channel_mapping,
+		})
+	} else {
+		None
+	};
+
+	Some(Dops::new(
+		output_channel_count,
+		pre_skip,
+		input_sample_rate,
+		output_gain,
+		channel_mapping_family,
+		channel_mapping_table,
+	))
+}
+
+pub fn trun_sample(data: &Bytes) -> Result<(TrunSample, u32), TransmuxError> {
+	let duration = packet_duration(data).ok_or(TransmuxError::InvalidOpusPacket)?;
+
+	Ok((
+		TrunSample {
+			duration: Some(duration),
+			composition_time_offset: None,
+			flags: Some(TrunSampleFlag {
+				reserved: 0,
+				is_leading: 0,
+				sample_degradation_priority: 0,
+				sample_depends_on: 2,
+				sample_has_redundancy: 0,
+				sample_is_depended_on: 0,
+				sample_is_non_sync_sample: false,
+				sample_padding_value: 0,
+			}),
+			size: Some(data.len() as u32),
+		},
+		duration,
+	))
+}
+
+/// The duration of a packet in 48kHz samples, from the TOC byte.
+/// RFC 6716 - 3.1
+fn packet_duration(data: &[u8]) -> Option<u32> {
+	let toc = *data.first()?;
+	let config = toc >> 3;
+
+	let frame_size = match config {
+		// SILK, 10, 20, 40 or 60ms
+		0..=11 => [480, 960, 1920, 2880][config as usize % 4],
+		// Hybrid, 10 or 20ms
+		12..=15 => [480, 960][config as usize % 2],
+		// CELT, 2.5, 5, 10 or 20ms
+		_ => [120, 240, 480, 960][config as usize % 4],
+	};
+
+	let frames = match toc & 0b11 {
+		0 => 1,
+		1 | 2 => 2,
+		// The frame count is in the next byte
+		_ => (*data.get(1)? & 0b0011_1111) as u32,
+	};
+
+	// A packet is at most 120ms long.
+	let duration = frame_size * frames;
+	if duration == 0 || duration > 5760 {
+		return None;
+	}
+
+	Some(duration)
+}
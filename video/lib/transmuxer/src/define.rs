@@ -1,6 +1,8 @@
+use amf0::Amf0Object;
+use av1::seq::SequenceHeaderObu;
 use av1::AV1CodecConfigurationRecord;
 use bytes::Bytes;
-use flv::{SoundSize, SoundType};
+use flv::{FlvTag, SoundSize, SoundType};
 use h264::AVCDecoderConfigurationRecord;
 use h265::HEVCDecoderConfigurationRecord;
 use mp4::codec::{AudioCodec, VideoCodec};
@@ -18,19 +20,92 @@ pub(crate) struct AudioSequenceHeader {
 }
 
 pub(crate) enum AudioSequenceHeaderData {
+	/// The AudioSpecificConfig
 	Aac(Bytes),
+	/// The OpusHead header
+	Opus(Bytes),
+	/// MP3 has no sequence header, this is the first frame
+	Mp3(Bytes),
+}
+
+#[derive(Default)]
+pub(crate) struct SequenceHeaders {
+	/// The video sequence headers and the track they belong to
+	pub video: Vec<(u8, VideoSequenceHeader)>,
+	/// The audio sequence headers and the track they belong to
+	pub audio: Vec<(u8, AudioSequenceHeader)>,
+	/// The onMetaData object
+	pub metadata: Option<Amf0Object>,
+	/// If any media frames have been received
+	pub has_frames: bool,
+}
+
+impl SequenceHeaders {
+	/// Add a video sequence header, unless the track already has one.
+	pub fn push_video(&mut self, track_id: u8, header: VideoSequenceHeader) {
+		if !self.video.iter().any(|(id, _)| *id == track_id) {
+			self.video.push((track_id, header));
+		}
+	}
+
+	/// Add an audio sequence header, unless the track already has one.
+	pub fn push_audio(&mut self, track_id: u8, header: AudioSequenceHeader) {
+		if !self.audio.iter().any(|(id, _)| *id == track_id) {
+			self.audio.push((track_id, header));
+		}
+	}
+}
+
+/// A tag for a single track, multitrack tags are split into one tag per
+/// track. Tags which are not multitrack belong to track 0.
+#[derive(Debug, Clone)]
+pub(crate) struct TrackTag {
+	pub track_id: u8,
+	pub tag: FlvTag,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TrackState {
+	pub track: Track,
+	/// Measured in the timescale of the track
+	pub duration: u64,
+	pub last_timestamp: u32,
+	/// The last AV1 sequence header seen in the bitstream, which is needed to
+	/// parse the frame headers
+	pub av1_sequence_header: Option<SequenceHeaderObu>,
 }
 
 #[derive(Debug, Clone)]
 pub enum TransmuxResult {
 	InitSegment {
+		/// The settings of the first video track
 		video_settings: VideoSettings,
+		/// The settings of the first audio track
 		audio_settings: AudioSettings,
+		/// Every track in the init segment, including the first video and
+		/// audio tracks
+		tracks: Vec<Track>,
 		data: Bytes,
 	},
 	MediaSegment(MediaSegment),
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+	/// The track id in the fMP4 output
+	pub id: u32,
+	/// The track id in the FLV input, this is always 0 unless the stream uses
+	/// enhanced RTMP multitrack
+	pub source_id: u8,
+	pub settings: TrackSettings,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackSettings {
+	Video(VideoSettings),
+	Audio(AudioSettings),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct VideoSettings {
 	pub width: u32,
@@ -59,6 +134,8 @@ pub enum MediaType {
 #[derive(Debug, Clone)]
 pub struct MediaSegment {
 	pub data: Bytes,
+	/// The fMP4 track id this segment belongs to
+	pub track_id: u32,
 	pub ty: MediaType,
 	pub keyframe: bool,
 	pub timestamp: u64,
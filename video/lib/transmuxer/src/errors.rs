@@ -9,8 +9,13 @@ pub enum TransmuxError {
 	InvalidAv1DecoderConfigurationRecord,
 	InvalidAVCDecoderConfigurationRecord,
 	NoSequenceHeaders,
+	InvalidAdtsHeader,
+	InvalidOpusHead,
+	InvalidOpusPacket,
+	InvalidMp3Header,
 	IO(io::Error),
 	FlvDemuxer(flv::FlvDemuxerError),
+	TsDemuxer(mpegts::TsDemuxerError),
 }
 
 impl From<flv::FlvDemuxerError> for TransmuxError {
@@ -19,6 +24,12 @@ impl From<flv::FlvDemuxerError> for TransmuxError {
 	}
 }
 
+impl From<mpegts::TsDemuxerError> for TransmuxError {
+	fn from(err: mpegts::TsDemuxerError) -> Self {
+		Self::TsDemuxer(err)
+	}
+}
+
 impl From<io::Error> for TransmuxError {
 	fn from(err: io::Error) -> Self {
 		Self::IO(err)
@@ -41,8 +52,13 @@ impl std::fmt::Display for TransmuxError {
 				write!(f, "invalid avc decoder configuration record")
 			}
 			Self::NoSequenceHeaders => write!(f, "no sequence headers"),
+			Self::InvalidAdtsHeader => write!(f, "invalid adts header"),
+			Self::InvalidOpusHead => write!(f, "invalid opus head"),
+			Self::InvalidOpusPacket => write!(f, "invalid opus packet"),
+			Self::InvalidMp3Header => write!(f, "invalid mp3 header"),
 			Self::IO(err) => write!(f, "io error: {}", err),
 			Self::FlvDemuxer(err) => write!(f, "flv demuxer error: {}", err),
+			Self::TsDemuxer(err) => write!(f, "ts demuxer error: {}", err),
 		}
 	}
 }
@@ -0,0 +1,310 @@
+use std::collections::VecDeque;
+
+use amf0::{Amf0Object, Amf0Value};
+use bytes::Bytes;
+use flv::{
+	AacPacket, AudioFourCC, AvcPacket, EnhancedAudioPacket, FlvTag, FlvTagAudioData, FlvTagData, FlvTagVideoData, FrameType,
+	SoundRate, SoundSize, SoundType,
+};
+use h264::{AVCDecoderConfigurationRecord, AvccExtendedConfig, Sps};
+
+use crate::TransmuxError;
+
+/// The timescale of the timestamps passed to the remuxer.
+pub const ES_TIMESCALE: u64 = 90000;
+
+/// How many tags we hold back while waiting for the second video frame,
+/// before we give up on estimating the framerate.
+const MAX_HELD_TAGS: usize = 256;
+
+/// The sample rate Opus is always decoded at.
+const OPUS_SAMPLE_RATE: u32 = 48000;
+
+/// Converts elementary stream frames into FLV tags, so that streams which
+/// are not ingested over RTMP can be fed to the same `Transmuxer`.
+/// H.264 video and AAC or Opus audio are supported.
+///
+/// Timestamps are in a 90kHz timescale (`ES_TIMESCALE`) relative to the start
+/// of the stream. The framerate is estimated from the first two video frames
+/// and sent in an `onMetaData` tag, for streams where the SPS does not
+/// contain the timing info. Tags are held back until then, so the metadata
+/// comes before the first frame.
+#[derive(Debug, Default)]
+pub struct EsRemuxer {
+	tags: VecDeque<FlvTag>,
+
+	sps: Option<Bytes>,
+	pps: Option<Bytes>,
+	/// If the current SPS and PPS have been sent in a sequence header.
+	sequence_header_sent: bool,
+	/// The decode time of the first video frame, until the framerate is known.
+	first_video_dts: Option<u64>,
+	/// If the tags are released, which happens once we know the framerate.
+	released: bool,
+
+	/// The last audio codec configuration which was sent.
+	audio_config: Option<Bytes>,
+}
+
+impl EsRemuxer {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Get the next FLV tag, or None if we need more frames.
+	pub fn read_tag(&mut self) -> Option<FlvTag> {
+		if self.released || self.tags.len() > MAX_HELD_TAGS {
+			self.released = true;
+			return self.tags.pop_front();
+		}
+
+		None
+	}
+
+	/// Finish the stream, the remaining tags are then returned by `read_tag`.
+	pub fn flush(&mut self) {
+		self.released = true;
+	}
+
+	/// Add a H.264 access unit, the NAL units are without start codes or
+	/// length prefixes. SPS and PPS NAL units are sent in a sequence header
+	/// whenever they change.
+	pub fn push_h264(
+		&mut self,
+		pts: u64,
+		dts: u64,
+		nalus: impl IntoIterator<Item = Bytes>,
+		random_access: bool,
+	) -> Result<(), TransmuxError> {
+		let mut keyframe = random_access;
+		let mut data = Vec::new();
+
+		for nalu in nalus {
+			if nalu.is_empty() {
+				continue;
+			}
+
+			match nalu[0] & 0x1F {
+				// SPS and PPS go into the sequence header
+				7 => {
+					if self.sps.as_ref() != Some(&nalu) {
+						self.sps = Some(nalu);
+						self.sequence_header_sent = false;
+					}
+				}
+				8 => {
+					if self.pps.as_ref() != Some(&nalu) {
+						self.pps = Some(nalu);
+						self.sequence_header_sent = false;
+					}
+				}
+				// Access unit delimiters are not used in FLV
+				9 => {}
+				nal_unit_type => {
+					// IDR
+					keyframe |= nal_unit_type == 5;
+
+					data.extend_from_slice(&(nalu.len() as u32).to_be_bytes());
+					data.extend_from_slice(&nalu);
+				}
+			}
+		}
+
+		if !self.sequence_header_sent {
+			// Frames can not be decoded without a sequence header.
+			let (Some(sps), Some(pps)) = (&self.sps, &self.pps) else {
+				return Ok(());
+			};
+
+			let config = avc_decoder_configuration_record(sps.clone(), pps.clone())?;
+
+			self.tags.push_back(FlvTag {
+				timestamp: timestamp(dts),
+				stream_id: 0,
+				data: FlvTagData::Video {
+					frame_type: FrameType::Keyframe,
+					data: FlvTagVideoData::Avc(AvcPacket::SequenceHeader(config)),
+				},
+			});
+
+			self.sequence_header_sent = true;
+		}
+
+		if data.is_empty() {
+			return Ok(());
+		}
+
+		self.estimate_framerate(dts);
+
+		self.tags.push_back(FlvTag {
+			timestamp: timestamp(dts),
+			stream_id: 0,
+			data: FlvTagData::Video {
+				frame_type: if keyframe {
+					FrameType::Keyframe
+				} else {
+					FrameType::Interframe
+				},
+				data: FlvTagVideoData::Avc(AvcPacket::Nalu {
+					composition_time: timestamp(pts.saturating_sub(dts)),
+					data: Bytes::from(data),
+				}),
+			},
+		});
+
+		Ok(())
+	}
+
+	/// Add a raw AAC frame, `config` is the AudioSpecificConfig which
+	/// describes it.
+	pub fn push_aac(&mut self, pts: u64, config: Bytes, sound_type: SoundType, frame: Bytes) {
+		if self.audio_config.as_ref() != Some(&config) {
+			self.tags.push_back(FlvTag {
+				timestamp: timestamp(pts),
+				stream_id: 0,
+				data: FlvTagData::Audio {
+					sound_rate: SoundRate::Hz44000,
+					sound_size: SoundSize::Bit16,
+					sound_type,
+					data: FlvTagAudioData::Aac(AacPacket::SequenceHeader(config.clone())),
+				},
+			});
+
+			self.audio_config = Some(config);
+		}
+
+		self.tags.push_back(FlvTag {
+			timestamp: timestamp(pts),
+			stream_id: 0,
+			data: FlvTagData::Audio {
+				sound_rate: SoundRate::Hz44000,
+				sound_size: SoundSize::Bit16,
+				sound_type,
+				data: FlvTagAudioData::Aac(AacPacket::Raw(frame)),
+			},
+		});
+	}
+
+	/// Add an Opus packet. Opus has no configuration in band, so the
+	/// `OpusHead` which is sent in the sequence start is created from the
+	/// number of channels.
+	pub fn push_opus(&mut self, pts: u64, channels: u8, packet: Bytes) {
+		let config = opus_head(channels);
+		let sound_type = if channels == 1 { SoundType::Mono } else { SoundType::Stereo };
+
+		if self.audio_config.as_ref() != Some(&config) {
+			self.tags.push_back(FlvTag {
+				timestamp: timestamp(pts),
+				stream_id: 0,
+				data: FlvTagData::Audio {
+					sound_rate: SoundRate::Hz44000,
+					sound_size: SoundSize::Bit16,
+					sound_type,
+					data: FlvTagAudioData::Enhanced(EnhancedAudioPacket::SequenceStart {
+						audio_codec: AudioFourCC::Opus,
+						data: config.clone(),
+					}),
+				},
+			});
+
+			self.audio_config = Some(config);
+		}
+
+		self.tags.push_back(FlvTag {
+			timestamp: timestamp(pts),
+			stream_id: 0,
+			data: FlvTagData::Audio {
+				sound_rate: SoundRate::Hz44000,
+				sound_size: SoundSize::Bit16,
+				sound_type,
+				data: FlvTagAudioData::Enhanced(EnhancedAudioPacket::CodedFrames {
+					audio_codec: AudioFourCC::Opus,
+					data: packet,
+				}),
+			},
+		});
+	}
+
+	/// Send the framerate in an `onMetaData` tag in front of the held back
+	/// tags, once we know the decode time of the second video frame.
+	fn estimate_framerate(&mut self, dts: u64) {
+		if self.released {
+			return;
+		}
+
+		let Some(first_dts) = self.first_video_dts else {
+			self.first_video_dts = Some(dts);
+			return;
+		};
+
+		if dts <= first_dts {
+			return;
+		}
+
+		let metadata = [("framerate", Amf0Value::Number(ES_TIMESCALE as f64 / (dts - first_dts) as f64))]
+			.into_iter()
+			.collect::<Amf0Object>();
+
+		self.tags.push_front(FlvTag {
+			timestamp: 0,
+			stream_id: 0,
+			data: FlvTagData::ScriptData {
+				name: "onMetaData".to_string(),
+				data: vec![Amf0Value::Object(metadata)],
+			},
+		});
+
+		self.released = true;
+	}
+}
+
+/// Convert a 90kHz timestamp to milliseconds.
+fn timestamp(timestamp: u64) -> u32 {
+	(timestamp * 1000 / ES_TIMESCALE) as u32
+}
+
+/// Create the decoder configuration record for a SPS and PPS.
+fn avc_decoder_configuration_record(sps: Bytes, pps: Bytes) -> Result<AVCDecoderConfigurationRecord, TransmuxError> {
+	if sps.len() < 4 {
+		return Err(TransmuxError::InvalidAVCDecoderConfigurationRecord);
+	}
+
+	let parsed = Sps::parse(sps.clone())?;
+
+	// Profiles other than baseline, main and extended have the extended config.
+	let extended_config = match sps[1] {
+		66 | 77 | 88 => None,
+		_ => parsed.ext.map(|ext| AvccExtendedConfig {
+			chroma_format: ext.chroma_format_idc as u8,
+			bit_depth_luma_minus8: ext.bit_depth_luma_minus8 as u8,
+			bit_depth_chroma_minus8: ext.bit_depth_chroma_minus8 as u8,
+			sequence_parameter_set_ext: Vec::new(),
+		}),
+	};
+
+	Ok(AVCDecoderConfigurationRecord {
+		configuration_version: 1,
+		profile_indication: sps[1],
+		profile_compatibility: sps[2],
+		level_indication: sps[3],
+		length_size_minus_one: 3,
+		sps: vec![sps],
+		pps: vec![pps],
+		extended_config,
+	})
+}
+
+/// The identification header of an Opus stream.
+/// RFC 7845 - 5.1
+fn opus_head(channels: u8) -> Bytes {
+	let mut head = Vec::with_capacity(19);
+	head.extend_from_slice(b"OpusHead");
+	head.push(1); // version
+	head.push(channels);
+	head.extend_from_slice(&0u16.to_le_bytes()); // pre-skip, which is not signaled over RTP or MPEG-TS
+	head.extend_from_slice(&OPUS_SAMPLE_RATE.to_le_bytes());
+	head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+	head.push(0); // channel mapping family, mono or stereo
+
+	Bytes::from(head)
+}
@@ -1,18 +1,19 @@
 #![allow(clippy::single_match)]
 
-use std::collections::{HashMap, VecDeque};
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::io;
 
+use aac::AudioSpecificConfig;
 use amf0::Amf0Value;
 use byteorder::{BigEndian, ReadBytesExt};
 use bytes::{Buf, Bytes};
 use bytesio::bytes_writer::BytesWriter;
 use flv::{
-	AacPacket, Av1Packet, AvcPacket, EnhancedPacket, FlvTag, FlvTagAudioData, FlvTagData, FlvTagVideoData, FrameType,
-	HevcPacket, SoundType,
+	AacPacket, AudioFourCC, Av1Packet, AvcPacket, EnhancedAudioPacket, EnhancedPacket, FlvTag, FlvTagAudioData, FlvTagData,
+	FlvTagVideoData, FrameType, HevcPacket, SoundType,
 };
-use mp4::codec::{AudioCodec, VideoCodec};
+use mp4::codec::{AudioCodec, HevcSampleEntry, VideoCodec};
 use mp4::types::ftyp::{FourCC, Ftyp};
 use mp4::types::hdlr::{HandlerType, Hdlr};
 use mp4::types::mdat::Mdat;
@@ -39,26 +40,26 @@ use mp4::types::trak::Trak;
 use mp4::types::trex::Trex;
 use mp4::types::trun::Trun;
 use mp4::types::vmhd::Vmhd;
-use mp4::BoxType;
+use mp4::{BoxType, DynBox};
 
 mod codecs;
 mod define;
 mod errors;
+mod es;
+mod ts;
 
 pub use define::*;
 pub use errors::TransmuxError;
+pub use es::{EsRemuxer, ES_TIMESCALE};
+pub use ts::TsRemuxer;
 
 #[derive(Debug, Clone)]
 pub struct Transmuxer {
-	// These durations are measured in timescales
-	/// sample_freq * 1000
-	audio_duration: u64,
-	/// fps * 1000
-	video_duration: u64,
 	sequence_number: u32,
-	last_video_timestamp: u32,
-	settings: Option<(VideoSettings, AudioSettings)>,
-	tags: VecDeque<FlvTag>,
+	/// The tracks in the init segment, empty until the init segment has been
+	/// created
+	tracks: Vec<TrackState>,
+	tags: VecDeque<TrackTag>,
 }
 
 impl Default for Transmuxer {
@@ -72,10 +73,7 @@ impl Transmuxer {
 		Self {
 			sequence_number: 1,
 			tags: VecDeque::new(),
-			audio_duration: 0,
-			video_duration: 0,
-			last_video_timestamp: 0,
-			settings: None,
+			tracks: Vec::new(),
 		}
 	}
 
@@ -89,7 +87,7 @@ impl Transmuxer {
 			}
 
 			let tag = flv::FlvTag::demux(&mut cursor)?;
-			self.tags.push_back(tag);
+			self.add_tag(tag);
 		}
 
 		Ok(())
@@ -97,7 +95,46 @@ impl Transmuxer {
 
 	/// Feed a single FLV tag to the transmuxer.
 	pub fn add_tag(&mut self, tag: FlvTag) {
-		self.tags.push_back(tag);
+		// Multitrack tags are split up so that every track can be muxed on its own.
+		match tag.data {
+			FlvTagData::Video {
+				frame_type,
+				data: FlvTagVideoData::Multitrack(tracks),
+			} => {
+				self.tags.extend(tracks.into_iter().map(|track| TrackTag {
+					track_id: track.track_id,
+					tag: FlvTag {
+						timestamp: tag.timestamp,
+						stream_id: tag.stream_id,
+						data: FlvTagData::Video {
+							frame_type,
+							data: FlvTagVideoData::Enhanced(track.packet),
+						},
+					},
+				}));
+			}
+			FlvTagData::Audio {
+				sound_rate,
+				sound_size,
+				sound_type,
+				data: FlvTagAudioData::Multitrack(tracks),
+			} => {
+				self.tags.extend(tracks.into_iter().map(|track| TrackTag {
+					track_id: track.track_id,
+					tag: FlvTag {
+						timestamp: tag.timestamp,
+						stream_id: tag.stream_id,
+						data: FlvTagData::Audio {
+							sound_rate,
+							sound_size,
+							sound_type,
+							data: FlvTagAudioData::Enhanced(track.packet),
+						},
+					},
+				}));
+			}
+			_ => self.tags.push_back(TrackTag { track_id: 0, tag }),
+		}
 	}
 
 	/// Get the next transmuxed packet. This will return `None` if there is not
@@ -105,8 +142,8 @@ impl Transmuxer {
 	pub fn mux(&mut self) -> Result<Option<TransmuxResult>, TransmuxError> {
 		let mut writer = BytesWriter::default();
 
-		let Some((video_settings, _)) = &self.settings else {
-			let Some((video_settings, audio_settings)) = self.init_sequence(&mut writer)? else {
+		if self.tracks.is_empty() {
+			let Some(tracks) = self.init_sequence(&mut writer)? else {
 				if self.tags.len() > 30 {
 					// We are clearly not getting any sequence headers, so we should just give up
 					return Err(TransmuxError::NoSequenceHeaders);
@@ -116,7 +153,31 @@ impl Transmuxer {
 				return Ok(None);
 			};
 
-			self.settings = Some((video_settings.clone(), audio_settings.clone()));
+			self.tracks = tracks
+				.iter()
+				.map(|track| TrackState {
+					track: track.clone(),
+					duration: 0,
+					last_timestamp: 0,
+					av1_sequence_header: None,
+				})
+				.collect();
+
+			// init_sequence only succeeds with at least one video and one audio track.
+			let video_settings = tracks
+				.iter()
+				.find_map(|track| match &track.settings {
+					TrackSettings::Video(settings) => Some(settings.clone()),
+					_ => None,
+				})
+				.expect("init segment has a video track");
+			let audio_settings = tracks
+				.iter()
+				.find_map(|track| match &track.settings {
+					TrackSettings::Audio(settings) => Some(settings.clone()),
+					_ => None,
+				})
+				.expect("init segment has an audio track");
 
 			let data = writer.dispose();
 
@@ -124,60 +185,118 @@ impl Transmuxer {
 				data,
 				audio_settings,
 				video_settings,
+				tracks,
 			}));
-		};
+		}
 
 		loop {
-			let Some(tag) = self.tags.pop_front() else {
+			let Some(TrackTag { track_id, tag }) = self.tags.pop_front() else {
 				return Ok(None);
 			};
 
+			let is_audio = matches!(tag.data, FlvTagData::Audio { .. });
+
+			// Tags for tracks which are not in the init segment are dropped
+			let Some(track) = self.tracks.iter_mut().find(|track| {
+				track.track.source_id == track_id
+					&& match track.track.settings {
+						TrackSettings::Audio(_) => is_audio,
+						TrackSettings::Video(_) => !is_audio,
+					}
+			}) else {
+				continue;
+			};
+
+			let framerate = match &track.track.settings {
+				TrackSettings::Video(settings) => settings.framerate,
+				TrackSettings::Audio(_) => 0.0,
+			};
+
 			let mdat_data;
 			let total_duration;
 			let trun_sample;
-			let mut is_audio = false;
 			let mut is_keyframe = false;
 
-			let duration =
-				if self.last_video_timestamp == 0 || tag.timestamp == 0 || tag.timestamp < self.last_video_timestamp {
-					1000 // the first frame is always 1000 ticks where the timescale
-					// is 1000 * fps.
+			let duration = if track.last_timestamp == 0 || tag.timestamp == 0 || tag.timestamp < track.last_timestamp {
+				1000 // the first frame is always 1000 ticks where the timescale
+			// is 1000 * fps.
+			} else {
+				// Since the delta is in milliseconds (ie 1/1000 of a second)
+				// Rounding errors happen. Our presision is only 1/1000 of a second.
+				// So if we have a 30fps video the delta should be 33.33ms (1000/30)
+				// But we can only represent this as 33ms or 34ms. So we will get rounding
+				// errors. To fix this we just check if the delta is 1 more or 1 less than the
+				// expected delta. And if it is we just use the expected delta.
+				// The reason we use a timescale which is 1000 * fps is because then we can
+				// always represent the delta as an integer. If we use a timescale of 1000, we
+				// would run into the same rounding errors.
+				let delta = tag.timestamp as f64 - track.last_timestamp as f64;
+				let expected_delta = 1000.0 / framerate;
+				if (delta - expected_delta).abs() <= 1.0 {
+					1000
 				} else {
-					// Since the delta is in milliseconds (ie 1/1000 of a second)
-					// Rounding errors happen. Our presision is only 1/1000 of a second.
-					// So if we have a 30fps video the delta should be 33.33ms (1000/30)
-					// But we can only represent this as 33ms or 34ms. So we will get rounding
-					// errors. To fix this we just check if the delta is 1 more or 1 less than the
-					// expected delta. And if it is we just use the expected delta.
-					// The reason we use a timescale which is 1000 * fps is because then we can
-					// always represent the delta as an integer. If we use a timescale of 1000, we
-					// would run into the same rounding errors.
-					let delta = tag.timestamp as f64 - self.last_video_timestamp as f64;
-					let expected_delta = 1000.0 / video_settings.framerate;
-					if (delta - expected_delta).abs() <= 1.0 {
-						1000
-					} else {
-						(delta * video_settings.framerate) as u32
-					}
-				};
+					(delta * framerate) as u32
+				}
+			};
 
 			match tag.data {
 				FlvTagData::Audio {
 					data: FlvTagAudioData::Aac(AacPacket::Raw(data)),
 					..
+				}
+				| FlvTagData::Audio {
+					data:
+						FlvTagAudioData::Enhanced(EnhancedAudioPacket::CodedFrames {
+							audio_codec: AudioFourCC::Aac,
+							data,
+						}),
+					..
 				} => {
 					let (sample, duration) = codecs::aac::trun_sample(&data)?;
 
 					trun_sample = sample;
 					mdat_data = data;
 					total_duration = duration;
-					is_audio = true;
+				}
+				FlvTagData::Audio {
+					data:
+						FlvTagAudioData::Enhanced(EnhancedAudioPacket::CodedFrames {
+							audio_codec: AudioFourCC::Opus,
+							data,
+						}),
+					..
+				} => {
+					let (sample, duration) = codecs::opus::trun_sample(&data)?;
+
+					trun_sample = sample;
+					mdat_data = data;
+					total_duration = duration;
+				}
+				FlvTagData::Audio {
+					data: FlvTagAudioData::Mp3(data),
+					..
+				}
+				| FlvTagData::Audio {
+					data:
+						FlvTagAudioData::Enhanced(EnhancedAudioPacket::CodedFrames {
+							audio_codec: AudioFourCC::Mp3,
+							data,
+						}),
+					..
+				} => {
+					let (sample, duration) = codecs::mp3::trun_sample(&data)?;
+
+					trun_sample = sample;
+					mdat_data = data;
+					total_duration = duration;
 				}
 				FlvTagData::Video {
 					frame_type,
-					data: FlvTagVideoData::Avc(AvcPacket::Nalu { composition_time, data }),
+					data:
+						FlvTagVideoData::Avc(AvcPacket::Nalu { composition_time, data })
+						| FlvTagVideoData::Enhanced(EnhancedPacket::Avc(AvcPacket::Nalu { composition_time, data })),
 				} => {
-					let composition_time = ((composition_time as f64 * video_settings.framerate) / 1000.0).floor() * 1000.0;
+					let composition_time = ((composition_time as f64 * framerate) / 1000.0).floor() * 1000.0;
 
 					let sample = codecs::avc::trun_sample(frame_type, composition_time as u32, duration, &data)?;
 
@@ -191,6 +310,13 @@ impl Transmuxer {
 					frame_type,
 					data: FlvTagVideoData::Enhanced(EnhancedPacket::Av1(Av1Packet::Raw(data))),
 				} => {
+					// The frame type of the tag is only used if the frame header can not be parsed.
+					let frame_type = match codecs::av1::is_keyframe(&data, &mut track.av1_sequence_header) {
+						Some(true) => FrameType::Keyframe,
+						Some(false) => FrameType::Interframe,
+						None => frame_type,
+					};
+
 					let sample = codecs::av1::trun_sample(frame_type, duration, &data)?;
 
 					trun_sample = sample;
@@ -204,7 +330,7 @@ impl Transmuxer {
 					data: FlvTagVideoData::Enhanced(EnhancedPacket::Hevc(HevcPacket::Nalu { composition_time, data })),
 				} => {
 					let composition_time =
-						((composition_time.unwrap_or_default() as f64 * video_settings.framerate) / 1000.0).floor() * 1000.0;
+						((composition_time.unwrap_or_default() as f64 * framerate) / 1000.0).floor() * 1000.0;
 
 					let sample = codecs::hevc::trun_sample(frame_type, composition_time as i32, duration, &data)?;
 
@@ -221,16 +347,10 @@ impl Transmuxer {
 			}
 
 			let trafs = {
-				let (main_duration, main_id) = if is_audio {
-					(self.audio_duration, 2)
-				} else {
-					(self.video_duration, 1)
-				};
-
 				let mut traf = Traf::new(
-					Tfhd::new(main_id, None, None, None, None, None),
+					Tfhd::new(track.track.id, None, None, None, None, None),
 					Some(Trun::new(vec![trun_sample], None)),
-					Some(Tfdt::new(main_duration)),
+					Some(Tfdt::new(track.duration)),
 				);
 				traf.optimize();
 
@@ -242,16 +362,15 @@ impl Transmuxer {
 			// We need to get the moof size so that we can set the data offsets.
 			let moof_size = moof.size();
 
-			// We just created the moof, and therefore we know that the first traf is the
-			// video traf and the second traf is the audio traf. So we can just unwrap them
-			// and set the data offsets.
+			// We just created the moof with a single traf, so we can just unwrap it
+			// and set the data offset.
 			let traf = moof.traf.get_mut(0).expect("we just created the moof with a traf");
 
 			// Again we know that these exist because we just created it.
-			let trun = traf.trun.as_mut().expect("we just created the video traf with a trun");
+			let trun = traf.trun.as_mut().expect("we just created the traf with a trun");
 
 			// We now define the offsets.
-			// So the video offset will be the size of the moof + 8 bytes for the mdat
+			// So the offset will be the size of the moof + 8 bytes for the mdat
 			// header.
 			trun.data_offset = Some(moof_size as i32 + 8);
 
@@ -264,63 +383,49 @@ impl Transmuxer {
 			// Increase our sequence number and duration.
 			self.sequence_number += 1;
 
-			if is_audio {
-				self.audio_duration += total_duration as u64;
-				return Ok(Some(TransmuxResult::MediaSegment(MediaSegment {
-					data: writer.dispose(),
-					ty: MediaType::Audio,
-					keyframe: false,
-					timestamp: self.audio_duration - total_duration as u64,
-				})));
-			} else {
-				self.video_duration += total_duration as u64;
-				self.last_video_timestamp = tag.timestamp;
-				return Ok(Some(TransmuxResult::MediaSegment(MediaSegment {
-					data: writer.dispose(),
-					ty: MediaType::Video,
-					keyframe: is_keyframe,
-					timestamp: self.video_duration - total_duration as u64,
-				})));
+			track.duration += total_duration as u64;
+			if !is_audio {
+				track.last_timestamp = tag.timestamp;
 			}
+
+			return Ok(Some(TransmuxResult::MediaSegment(MediaSegment {
+				data: writer.dispose(),
+				track_id: track.track.id,
+				ty: if is_audio { MediaType::Audio } else { MediaType::Video },
+				keyframe: is_keyframe,
+				timestamp: track.duration - total_duration as u64,
+			})));
 		}
 	}
 
 	/// Internal function to find the tags we need to create the init segment.
-	fn find_tags(
-		&self,
-	) -> (
-		Option<VideoSequenceHeader>,
-		Option<AudioSequenceHeader>,
-		Option<HashMap<String, Amf0Value>>,
-	) {
-		let tags = self.tags.iter();
-		let mut video_sequence_header = None;
-		let mut audio_sequence_header = None;
-		let mut scriptdata_tag = None;
-
-		for tag in tags {
-			if video_sequence_header.is_some() && audio_sequence_header.is_some() && scriptdata_tag.is_some() {
-				break;
-			}
+	/// Only the first sequence header of every track is used.
+	fn find_tags(&self) -> SequenceHeaders {
+		let mut headers = SequenceHeaders::default();
+
+		for TrackTag { track_id, tag } in &self.tags {
+			let track_id = *track_id;
 
 			match &tag.data {
 				FlvTagData::Video {
 					frame_type: _,
-					data: FlvTagVideoData::Avc(AvcPacket::SequenceHeader(data)),
+					data:
+						FlvTagVideoData::Avc(AvcPacket::SequenceHeader(data))
+						| FlvTagVideoData::Enhanced(EnhancedPacket::Avc(AvcPacket::SequenceHeader(data))),
 				} => {
-					video_sequence_header = Some(VideoSequenceHeader::Avc(data.clone()));
+					headers.push_video(track_id, VideoSequenceHeader::Avc(data.clone()));
 				}
 				FlvTagData::Video {
 					frame_type: _,
 					data: FlvTagVideoData::Enhanced(EnhancedPacket::Av1(Av1Packet::SequenceStart(config))),
 				} => {
-					video_sequence_header = Some(VideoSequenceHeader::Av1(config.clone()));
+					headers.push_video(track_id, VideoSequenceHeader::Av1(config.clone()));
 				}
 				FlvTagData::Video {
 					frame_type: _,
 					data: FlvTagVideoData::Enhanced(EnhancedPacket::Hevc(HevcPacket::SequenceStart(config))),
 				} => {
-					video_sequence_header = Some(VideoSequenceHeader::Hevc(config.clone()));
+					headers.push_video(track_id, VideoSequenceHeader::Hevc(config.clone()));
 				}
 				FlvTagData::Audio {
 					sound_size,
@@ -328,18 +433,98 @@ impl Transmuxer {
 					sound_rate: _,
 					data: FlvTagAudioData::Aac(AacPacket::SequenceHeader(data)),
 				} => {
-					audio_sequence_header = Some(AudioSequenceHeader {
-						data: AudioSequenceHeaderData::Aac(data.clone()),
-						sound_size: *sound_size,
-						sound_type: *sound_type,
-					});
+					headers.push_audio(
+						track_id,
+						AudioSequenceHeader {
+							data: AudioSequenceHeaderData::Aac(data.clone()),
+							sound_size: *sound_size,
+							sound_type: *sound_type,
+						},
+					);
+				}
+				FlvTagData::Audio {
+					sound_size,
+					data:
+						FlvTagAudioData::Enhanced(EnhancedAudioPacket::SequenceStart {
+							audio_codec: AudioFourCC::Aac,
+							data,
+						}),
+					..
+				} => {
+					// Enhanced audio has no sound type, so we take the channels from the config.
+					let sound_type = match AudioSpecificConfig::parse(data.clone()) {
+						Ok(config) if config.channel_configuration == 1 => SoundType::Mono,
+						_ => SoundType::Stereo,
+					};
+
+					headers.push_audio(
+						track_id,
+						AudioSequenceHeader {
+							data: AudioSequenceHeaderData::Aac(data.clone()),
+							sound_size: *sound_size,
+							sound_type,
+						},
+					);
+				}
+				FlvTagData::Audio {
+					sound_size,
+					data:
+						FlvTagAudioData::Enhanced(EnhancedAudioPacket::SequenceStart {
+							audio_codec: AudioFourCC::Opus,
+							data,
+						}),
+					..
+				} => {
+					// The channels are in the OpusHead, the sound type is not used.
+					headers.push_audio(
+						track_id,
+						AudioSequenceHeader {
+							data: AudioSequenceHeaderData::Opus(data.clone()),
+							sound_size: *sound_size,
+							sound_type: SoundType::Stereo,
+						},
+					);
+				}
+				FlvTagData::Audio {
+					sound_size,
+					sound_type,
+					data: FlvTagAudioData::Mp3(data),
+					..
+				}
+				| FlvTagData::Audio {
+					sound_size,
+					sound_type,
+					data:
+						FlvTagAudioData::Enhanced(EnhancedAudioPacket::CodedFrames {
+							audio_codec: AudioFourCC::Mp3,
+							data,
+						}),
+					..
+				} => {
+					// MP3 has no sequence header, every frame describes itself.
+					headers.push_audio(
+						track_id,
+						AudioSequenceHeader {
+							data: AudioSequenceHeaderData::Mp3(data.clone()),
+							sound_size: *sound_size,
+							sound_type: *sound_type,
+						},
+					);
+					headers.has_frames = true;
+				}
+				FlvTagData::Video { .. } | FlvTagData::Audio { .. } => {
+					headers.has_frames = true;
 				}
 				FlvTagData::ScriptData { data, name } => {
 					if name == "@setDataFrame" || name == "onMetaData" {
-						let meta_object = data.iter().find(|v| matches!(v, Amf0Value::Object(_)));
-
-						if let Some(Amf0Value::Object(meta_object)) = meta_object {
-							scriptdata_tag = Some(meta_object.clone());
+						// Encoders send the metadata either as an object or as an ECMA array
+						let meta_object = data.iter().find_map(|v| match v {
+							Amf0Value::Object(object) | Amf0Value::EcmaArray(object) => Some(object),
+							_ => None,
+						});
+
+						if let Some(meta_object) = meta_object {
+							headers.metadata = Some(meta_object.clone());
 						}
 					}
 				}
@@ -347,34 +532,28 @@ impl Transmuxer {
 			}
 		}
 
-		(video_sequence_header, audio_sequence_header, scriptdata_tag)
+		headers
 	}
 
 	/// Create the init segment.
-	fn init_sequence(&mut self, writer: &mut BytesWriter) -> Result<Option<(VideoSettings, AudioSettings)>, TransmuxError> {
-		// We need to find the tag that is the video sequence header
-		// and the audio sequence header
-		let (video_sequence_header, audio_sequence_header, scriptdata_tag) = self.find_tags();
-
-		let Some(video_sequence_header) = video_sequence_header else {
+	fn init_sequence(&mut self, writer: &mut BytesWriter) -> Result<Option<Vec<Track>>, TransmuxError> {
+		// We need to find the tags that are the video sequence headers
+		// and the audio sequence headers
+		let headers = self.find_tags();
+
+		// We need at least one video and one audio track. Tracks of a multitrack
+		// stream may send their sequence headers in separate tags, so we wait for
+		// the first frame before we stop looking for more of them.
+		if headers.video.is_empty() || headers.audio.is_empty() || !headers.has_frames {
 			return Ok(None);
-		};
-		let Some(audio_sequence_header) = audio_sequence_header else {
-			return Ok(None);
-		};
+		}
 
-		let video_codec;
-		let audio_codec;
-		let video_width;
-		let video_height;
-		let audio_channels;
-		let audio_sample_rate;
 		let mut video_fps = 0.0;
 
 		let mut estimated_video_bitrate = 0;
 		let mut estimated_audio_bitrate = 0;
 
-		if let Some(scriptdata_tag) = scriptdata_tag {
+		if let Some(scriptdata_tag) = headers.metadata {
 			video_fps = scriptdata_tag
 				.get("framerate")
 				.and_then(|v| match v {
@@ -402,9 +581,115 @@ impl Transmuxer {
 
 		let mut compatiable_brands = vec![FourCC::Iso5, FourCC::Iso6];
 
-		let video_stsd_entry = match video_sequence_header {
+		let mut tracks = Vec::new();
+		let mut traks = Vec::new();
+
+		// Video tracks come first so that a stream with a single video and audio
+		// track always gets video track 1 and audio track 2.
+		for (source_id, header) in headers.video {
+			let id = tracks.len() as u32 + 1;
+
+			// The metadata only describes the bitrate of the main track.
+			let bitrate = if tracks.is_empty() { estimated_video_bitrate } else { 0 };
+
+			let (settings, stsd_entry) = Self::video_track(header, video_fps, bitrate, &mut compatiable_brands)?;
+
+			traks.push(Trak::new(
+				Tkhd::new(0, 0, id, 0, Some((settings.width, settings.height))),
+				None,
+				Mdia::new(
+					Mdhd::new(0, 0, settings.timescale, 0),
+					Hdlr::new(HandlerType::Vide, "VideoHandler".to_string()),
+					Minf::new(
+						Stbl::new(
+							Stsd::new(vec![stsd_entry]),
+							Stts::new(vec![]),
+							Stsc::new(vec![]),
+							Stco::new(vec![]),
+							Some(Stsz::new(0, vec![])),
+						),
+						Some(Vmhd::new()),
+						None,
+					),
+				),
+			));
+
+			tracks.push(Track {
+				id,
+				source_id,
+				settings: TrackSettings::Video(settings),
+			});
+		}
+
+		let video_tracks = tracks.len();
+
+		for (source_id, header) in headers.audio {
+			let id = tracks.len() as u32 + 1;
+
+			let bitrate = if tracks.len() == video_tracks {
+				estimated_audio_bitrate
+			} else {
+				0
+			};
+
+			let (settings, stsd_entry) = Self::audio_track(header, bitrate, &mut compatiable_brands)?;
+
+			traks.push(Trak::new(
+				Tkhd::new(0, 0, id, 0, None),
+				None,
+				Mdia::new(
+					Mdhd::new(0, 0, settings.timescale, 0),
+					Hdlr::new(HandlerType::Soun, "SoundHandler".to_string()),
+					Minf::new(
+						Stbl::new(
+							Stsd::new(vec![stsd_entry]),
+							Stts::new(vec![]),
+							Stsc::new(vec![]),
+							Stco::new(vec![]),
+							Some(Stsz::new(0, vec![])),
+						),
+						None,
+						Some(Smhd::new()),
+					),
+				),
+			));
+
+			tracks.push(Track {
+				id,
+				source_id,
+				settings: TrackSettings::Audio(settings),
+			});
+		}
+
+		Ftyp::new(FourCC::Iso5, 512, compatiable_brands).mux(writer)?;
+		Moov::new(
+			Mvhd::new(0, 0, 1000, 0, 1),
+			traks,
+			Some(Mvex::new(tracks.iter().map(|track| Trex::new(track.id)).collect(), None)),
+		)
+		.mux(writer)?;
+
+		Ok(Some(tracks))
+	}
+
+	/// Create the stsd entry and settings for a video track. `video_fps` is
+	/// the framerate from the metadata, which is used if the sequence header
+	/// does not contain one.
+	fn video_track(
+		sequence_header: VideoSequenceHeader,
+		mut video_fps: f64,
+		bitrate: u32,
+		compatiable_brands: &mut Vec<FourCC>,
+	) -> Result<(VideoSettings, DynBox), TransmuxError> {
+		let video_codec;
+		let video_width;
+		let video_height;
+
+		let brand;
+
+		let stsd_entry = match sequence_header {
 			VideoSequenceHeader::Avc(config) => {
-				compatiable_brands.push(FourCC::Avc1);
+				brand = FourCC::Avc1;
 				video_codec = VideoCodec::Avc {
 					constraint_set: config.profile_compatibility,
 					level: config.level_indication,
@@ -422,7 +707,7 @@ impl Transmuxer {
 				entry
 			}
 			VideoSequenceHeader::Av1(config) => {
-				compatiable_brands.push(FourCC::Av01);
+				brand = FourCC::Av01;
 				let (entry, seq_obu) = codecs::av1::stsd_entry(config)?;
 
 				video_height = seq_obu.max_frame_height as u32;
@@ -438,6 +723,7 @@ impl Transmuxer {
 					monochrome: seq_obu.color_config.mono_chrome,
 					sub_sampling_x: seq_obu.color_config.subsampling_x,
 					sub_sampling_y: seq_obu.color_config.subsampling_y,
+					chroma_sample_position: seq_obu.color_config.chroma_sample_position,
 					color_primaries: seq_obu.color_config.color_primaries,
 					transfer_characteristics: seq_obu.color_config.transfer_characteristics,
 					matrix_coefficients: seq_obu.color_config.matrix_coefficients,
@@ -447,8 +733,9 @@ impl Transmuxer {
 				entry
 			}
 			VideoSequenceHeader::Hevc(config) => {
-				compatiable_brands.push(FourCC::Hev1);
+				brand = FourCC::Hev1;
 				video_codec = VideoCodec::Hevc {
+					sample_entry: HevcSampleEntry::Hev1,
 					constraint_indicator: config.general_constraint_indicator_flags,
 					level: config.general_level_idc,
 					profile: config.general_profile_idc,
@@ -469,25 +756,9 @@ impl Transmuxer {
 			}
 		};
 
-		let audio_stsd_entry = match audio_sequence_header.data {
-			AudioSequenceHeaderData::Aac(data) => {
-				compatiable_brands.push(FourCC::Mp41);
-				let (entry, config) =
-					codecs::aac::stsd_entry(audio_sequence_header.sound_size, audio_sequence_header.sound_type, data)?;
-
-				audio_sample_rate = config.sampling_frequency;
-
-				audio_codec = AudioCodec::Aac {
-					object_type: config.audio_object_type,
-				};
-				audio_channels = match audio_sequence_header.sound_type {
-					SoundType::Mono => 1,
-					SoundType::Stereo => 2,
-				};
-
-				entry
-			}
-		};
+		if !compatiable_brands.contains(&brand) {
+			compatiable_brands.push(brand);
+		}
 
 		if video_fps == 0.0 {
 			return Err(TransmuxError::InvalidVideoFrameRate);
@@ -497,10 +768,6 @@ impl Transmuxer {
 			return Err(TransmuxError::InvalidVideoDimensions);
 		}
 
-		if audio_sample_rate == 0 {
-			return Err(TransmuxError::InvalidAudioSampleRate);
-		}
-
 		// The reason we multiply the FPS by 1000 is to avoid rounding errors
 		// Consider If we had a video with a framerate of 30fps. That would imply each
 		// frame is 33.333333ms So we are limited to a u32 and therefore we could only
@@ -508,70 +775,87 @@ impl Transmuxer {
 		// units per second, making each frame 1000 units long instead of 33ms long.
 		let video_timescale = (1000.0 * video_fps) as u32;
 
-		Ftyp::new(FourCC::Iso5, 512, compatiable_brands).mux(writer)?;
-		Moov::new(
-			Mvhd::new(0, 0, 1000, 0, 1),
-			vec![
-				Trak::new(
-					Tkhd::new(0, 0, 1, 0, Some((video_width, video_height))),
-					None,
-					Mdia::new(
-						Mdhd::new(0, 0, video_timescale, 0),
-						Hdlr::new(HandlerType::Vide, "VideoHandler".to_string()),
-						Minf::new(
-							Stbl::new(
-								Stsd::new(vec![video_stsd_entry]),
-								Stts::new(vec![]),
-								Stsc::new(vec![]),
-								Stco::new(vec![]),
-								Some(Stsz::new(0, vec![])),
-							),
-							Some(Vmhd::new()),
-							None,
-						),
-					),
-				),
-				Trak::new(
-					Tkhd::new(0, 0, 2, 0, None),
-					None,
-					Mdia::new(
-						Mdhd::new(0, 0, audio_sample_rate, 0),
-						Hdlr::new(HandlerType::Soun, "SoundHandler".to_string()),
-						Minf::new(
-							Stbl::new(
-								Stsd::new(vec![audio_stsd_entry]),
-								Stts::new(vec![]),
-								Stsc::new(vec![]),
-								Stco::new(vec![]),
-								Some(Stsz::new(0, vec![])),
-							),
-							None,
-							Some(Smhd::new()),
-						),
-					),
-				),
-			],
-			Some(Mvex::new(vec![Trex::new(1), Trex::new(2)], None)),
-		)
-		.mux(writer)?;
-
-		Ok(Some((
+		Ok((
 			VideoSettings {
 				width: video_width,
 				height: video_height,
 				framerate: video_fps,
 				codec: video_codec,
-				bitrate: estimated_video_bitrate,
+				bitrate,
 				timescale: video_timescale,
 			},
+			stsd_entry,
+		))
+	}
+
+	/// Create the stsd entry and settings for an audio track.
+	fn audio_track(
+		sequence_header: AudioSequenceHeader,
+		bitrate: u32,
+		compatiable_brands: &mut Vec<FourCC>,
+	) -> Result<(AudioSettings, DynBox), TransmuxError> {
+		let audio_codec;
+		let audio_channels;
+		let audio_sample_rate;
+
+		let stsd_entry = match sequence_header.data {
+			AudioSequenceHeaderData::Aac(data) => {
+				if !compatiable_brands.contains(&FourCC::Mp41) {
+					compatiable_brands.push(FourCC::Mp41);
+				}
+
+				let (entry, config) = codecs::aac::stsd_entry(sequence_header.sound_size, sequence_header.sound_type, data)?;
+
+				audio_sample_rate = config.sampling_frequency;
+
+				audio_codec = AudioCodec::Aac {
+					object_type: config.codec_object_type(),
+				};
+				audio_channels = match sequence_header.sound_type {
+					SoundType::Mono => 1,
+					SoundType::Stereo => 2,
+				};
+
+				entry
+			}
+			AudioSequenceHeaderData::Opus(data) => {
+				let (entry, dops) = codecs::opus::stsd_entry(data)?;
+
+				audio_sample_rate = codecs::opus::OPUS_SAMPLE_RATE;
+				audio_codec = AudioCodec::Opus;
+				audio_channels = dops.output_channel_count;
+
+				entry
+			}
+			AudioSequenceHeaderData::Mp3(data) => {
+				if !compatiable_brands.contains(&FourCC::Mp41) {
+					compatiable_brands.push(FourCC::Mp41);
+				}
+
+				let (entry, header) = codecs::mp3::stsd_entry(data)?;
+
+				audio_sample_rate = header.sample_rate;
+				audio_codec = AudioCodec::Mp3;
+				audio_channels = header.channels;
+
+				entry
+			}
+		};
+
+		if audio_sample_rate == 0 {
+			return Err(TransmuxError::InvalidAudioSampleRate);
+		}
+
+		Ok((
 			AudioSettings {
 				codec: audio_codec,
 				sample_rate: audio_sample_rate,
 				channels: audio_channels,
-				bitrate: estimated_audio_bitrate,
+				bitrate,
 				timescale: audio_sample_rate,
 			},
-		)))
+			stsd_entry,
+		))
 	}
 }
 
@@ -4,13 +4,21 @@ use std::io::{
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
-use aac::AudioObjectType;
+use aac::{AdtsHeader, AudioObjectType, AudioSpecificConfig};
+use amf0::Amf0Value;
+use bytes::Bytes;
 use bytesio::bytes_writer::BytesWriter;
-use flv::FlvHeader;
-use mp4::codec::{AudioCodec, VideoCodec};
+use flv::{
+	AacPacket, AudioFourCC, AudioTrack, AvcPacket, EnhancedAudioPacket, EnhancedPacket, Flv, FlvHeader, FlvTag,
+	FlvTagAudioData, FlvTagData, FlvTagVideoData, FrameType, VideoTrack,
+};
+use mp4::codec::{AudioCodec, HevcSampleEntry, VideoCodec};
+use mp4::types::dops::Dops;
+use mp4::DynBox;
+use mpegts::{StreamType, TsMuxer};
 
 use crate::define::{AudioSettings, VideoSettings};
-use crate::{TransmuxResult, Transmuxer};
+use crate::{EsRemuxer, MediaType, TrackSettings, TransmuxResult, Transmuxer, TsRemuxer, ES_TIMESCALE};
 
 #[test]
 fn test_transmuxer_avc_aac() {
@@ -163,6 +171,7 @@ fn test_transmuxer_av1_aac() {
 							depth: 8,
 							sub_sampling_x: true,
 							sub_sampling_y: true,
+							chroma_sample_position: 0,
 							monochrome: false,
 							full_range_flag: false,
 							color_primaries: 1,
@@ -235,6 +244,43 @@ fn test_transmuxer_av1_aac() {
 	assert_eq!(json["streams"][1]["channels"], 2);
 }
 
+#[test]
+fn test_transmuxer_av1_keyframes() {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../assets");
+	let data = std::fs::read(dir.join("av1_aac.flv").to_str().unwrap()).unwrap();
+
+	let flv = Flv::demux(&mut io::Cursor::new(data.into())).unwrap();
+
+	let mut transmuxer = Transmuxer::new();
+	let mut keyframes = 0;
+
+	// Mark every video tag as an interframe, the keyframes have to be found in
+	// the frame headers.
+	for mut tag in flv.tags {
+		if let FlvTagData::Video { frame_type, .. } = &mut tag.data {
+			if *frame_type == FrameType::Keyframe {
+				keyframes += 1;
+			}
+			*frame_type = FrameType::Interframe;
+		}
+
+		transmuxer.add_tag(tag);
+	}
+
+	let mut video_keyframes = Vec::new();
+	while let Some(result) = transmuxer.mux().unwrap() {
+		if let TransmuxResult::MediaSegment(segment) = result {
+			if segment.ty == MediaType::Video {
+				video_keyframes.push(segment.keyframe);
+			}
+		}
+	}
+
+	assert!(video_keyframes[0]);
+	// The sequence start tag is a keyframe tag as well
+	assert_eq!(video_keyframes.iter().filter(|keyframe| **keyframe).count(), keyframes - 1);
+}
+
 #[test]
 fn test_transmuxer_hevc_aac() {
 	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../assets");
@@ -270,6 +316,7 @@ fn test_transmuxer_hevc_aac() {
 						bitrate: 2560000,
 						timescale: 144000,
 						codec: VideoCodec::Hevc {
+							sample_entry: HevcSampleEntry::Hev1,
 							general_profile_space: 0,
 							profile_compatibility: 64,
 							profile: 1,
@@ -279,7 +326,7 @@ fn test_transmuxer_hevc_aac() {
 						}
 					}
 				);
-				assert_eq!(video_settings.codec.to_string(), "hev1.1.40.L99.90");
+				assert_eq!(video_settings.codec.to_string(), "hev1.1.2.L153.90");
 
 				assert_eq!(
 					audio_settings,
@@ -347,3 +394,633 @@ fn test_transmuxer_hevc_aac() {
 	assert_eq!(json["streams"][1]["sample_rate"], "48000");
 	assert_eq!(json["streams"][1]["channels"], 2);
 }
+
+#[test]
+fn test_transmuxer_multitrack() {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../assets");
+	let data = std::fs::read(dir.join("avc_aac.flv").to_str().unwrap()).unwrap();
+
+	let flv = Flv::demux(&mut io::Cursor::new(data.into())).unwrap();
+
+	let mut transmuxer = Transmuxer::new();
+
+	// Turn every media tag into a multitrack tag with two copies of the track.
+	for tag in flv.tags {
+		let data = match tag.data {
+			FlvTagData::Video {
+				frame_type,
+				data: FlvTagVideoData::Avc(packet),
+			} => FlvTagData::Video {
+				frame_type,
+				data: FlvTagVideoData::Multitrack(vec![
+					VideoTrack {
+						track_id: 0,
+						packet: EnhancedPacket::Avc(packet.clone()),
+					},
+					VideoTrack {
+						track_id: 1,
+						packet: EnhancedPacket::Avc(packet),
+					},
+				]),
+			},
+			FlvTagData::Audio {
+				sound_rate,
+				sound_size,
+				sound_type,
+				data: FlvTagAudioData::Aac(packet),
+			} => {
+				let packet = match packet {
+					AacPacket::SequenceHeader(data) => EnhancedAudioPacket::SequenceStart {
+						audio_codec: AudioFourCC::Aac,
+						data,
+					},
+					AacPacket::Raw(data) => EnhancedAudioPacket::CodedFrames {
+						audio_codec: AudioFourCC::Aac,
+						data,
+					},
+					_ => continue,
+				};
+
+				FlvTagData::Audio {
+					sound_rate,
+					sound_size,
+					sound_type,
+					data: FlvTagAudioData::Multitrack(vec![
+						AudioTrack {
+							track_id: 0,
+							packet: packet.clone(),
+						},
+						AudioTrack { track_id: 3, packet },
+					]),
+				}
+			}
+			data => data,
+		};
+
+		transmuxer.add_tag(FlvTag { data, ..tag });
+	}
+
+	let mut segments = [0; 4];
+	let mut timestamps = [0; 4];
+
+	while let Some(data) = transmuxer.mux().unwrap() {
+		match data {
+			TransmuxResult::InitSegment {
+				video_settings,
+				audio_settings,
+				tracks,
+				..
+			} => {
+				let ids = tracks.iter().map(|track| (track.id, track.source_id)).collect::<Vec<_>>();
+				assert_eq!(ids, vec![(1, 0), (2, 1), (3, 0), (4, 3)]);
+
+				assert_eq!(tracks[0].settings, TrackSettings::Video(video_settings.clone()));
+				assert_eq!(
+					tracks[1].settings,
+					TrackSettings::Video(VideoSettings {
+						bitrate: 0,
+						..video_settings
+					})
+				);
+
+				assert_eq!(tracks[2].settings, TrackSettings::Audio(audio_settings.clone()));
+				assert_eq!(
+					tracks[3].settings,
+					TrackSettings::Audio(AudioSettings {
+						bitrate: 0,
+						..audio_settings
+					})
+				);
+			}
+			TransmuxResult::MediaSegment(segment) => {
+				let index = segment.track_id as usize - 1;
+				assert_eq!(segment.ty, if index < 2 { MediaType::Video } else { MediaType::Audio });
+				assert_eq!(segment.timestamp, timestamps[index]);
+
+				segments[index] += 1;
+				timestamps[index] += if index < 2 { 1000 } else { 1024 };
+			}
+		}
+	}
+
+	assert!(segments[0] > 0);
+	assert!(segments[2] > 0);
+	assert_eq!(segments[0], segments[1]);
+	assert_eq!(segments[2], segments[3]);
+}
+
+/// Mux the tags of a FLV file into a transport stream, the way an encoder
+/// would send it over SRT.
+fn flv_to_ts(flv: &Flv) -> Vec<u8> {
+	let mut muxer = TsMuxer::new();
+	let video = muxer.add_stream(StreamType::H264).unwrap();
+	let audio = muxer.add_stream(StreamType::Aac).unwrap();
+
+	let mut data = Vec::new();
+	let mut avc_config = None;
+	let mut audio_config = None;
+
+	for tag in &flv.tags {
+		let timestamp = tag.timestamp as u64 * 90;
+
+		match &tag.data {
+			FlvTagData::Video {
+				data: FlvTagVideoData::Avc(AvcPacket::SequenceHeader(config)),
+				..
+			} => avc_config = Some(config.clone()),
+			FlvTagData::Video {
+				frame_type,
+				data: FlvTagVideoData::Avc(AvcPacket::Nalu {
+					composition_time,
+					data: nalus,
+				}),
+			} => {
+				let config = avc_config.as_ref().unwrap();
+				let keyframe = *frame_type == FrameType::Keyframe;
+
+				// An access unit delimiter, followed by the SPS and PPS on keyframes.
+				let mut annex_b = vec![0, 0, 0, 1, 0x09, 0xF0];
+				if keyframe {
+					for nalu in config.sps.iter().chain(&config.pps) {
+						annex_b.extend_from_slice(&[0, 0, 0, 1]);
+						annex_b.extend_from_slice(nalu);
+					}
+				}
+
+				let length_size = config.length_size_minus_one as usize + 1;
+				let mut nalus = &nalus[..];
+				while !nalus.is_empty() {
+					let size = nalus[..length_size].iter().fold(0, |size, b| size << 8 | *b as usize);
+					annex_b.extend_from_slice(&[0, 0, 1]);
+					annex_b.extend_from_slice(&nalus[length_size..length_size + size]);
+					nalus = &nalus[length_size + size..];
+				}
+
+				let pts = timestamp + *composition_time as u64 * 90;
+				muxer
+					.write_pes(&mut data, video, pts, Some(timestamp), keyframe, &annex_b)
+					.unwrap();
+			}
+			FlvTagData::Audio {
+				data: FlvTagAudioData::Aac(AacPacket::SequenceHeader(config)),
+				..
+			} => audio_config = Some(AudioSpecificConfig::parse(config.clone()).unwrap()),
+			FlvTagData::Audio {
+				data: FlvTagAudioData::Aac(AacPacket::Raw(frame)),
+				..
+			} => {
+				let config = audio_config.as_ref().unwrap();
+
+				let mut adts = Vec::new();
+				AdtsHeader::new(config, frame.len()).unwrap().mux(&mut adts).unwrap();
+				adts.extend_from_slice(frame);
+
+				muxer.write_pes(&mut data, audio, timestamp, None, true, &adts).unwrap();
+			}
+			_ => {}
+		}
+	}
+
+	data
+}
+
+#[test]
+fn test_transmuxer_ts() {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../assets");
+	let data = std::fs::read(dir.join("avc_aac.flv").to_str().unwrap()).unwrap();
+
+	let flv = Flv::demux(&mut io::Cursor::new(data.into())).unwrap();
+	let ts = flv_to_ts(&flv);
+
+	let mut remuxer = TsRemuxer::new();
+	let mut tags = Vec::new();
+
+	// SRT sends the stream in chunks of 7 packets.
+	for chunk in ts.chunks(1316) {
+		remuxer.extend_data(chunk);
+		while let Some(tag) = remuxer.read_tag().unwrap() {
+			tags.push(tag);
+		}
+	}
+
+	remuxer.flush().unwrap();
+	while let Some(tag) = remuxer.read_tag().unwrap() {
+		tags.push(tag);
+	}
+
+	// The metadata with the estimated framerate comes first.
+	let FlvTagData::ScriptData { name, .. } = &tags[0].data else {
+		panic!("expected metadata, got {:?}", tags[0]);
+	};
+	assert_eq!(name, "onMetaData");
+
+	let video_frames = |tags: &[FlvTag]| {
+		tags.iter()
+			.filter_map(|tag| match &tag.data {
+				FlvTagData::Video {
+					frame_type,
+					data: FlvTagVideoData::Avc(AvcPacket::Nalu { composition_time, .. }),
+				} => Some((tag.timestamp, *frame_type, *composition_time)),
+				_ => None,
+			})
+			.collect::<Vec<_>>()
+	};
+	let audio_frames = |tags: &[FlvTag]| {
+		tags.iter()
+			.filter_map(|tag| match &tag.data {
+				FlvTagData::Audio {
+					data: FlvTagAudioData::Aac(AacPacket::Raw(data)),
+					..
+				} => Some((tag.timestamp, data.clone())),
+				_ => None,
+			})
+			.collect::<Vec<_>>()
+	};
+
+	// Every frame keeps its timestamps, and audio frames their data.
+	assert_eq!(video_frames(&tags), video_frames(&flv.tags));
+	assert_eq!(audio_frames(&tags), audio_frames(&flv.tags));
+
+	let transmux = |tags: Vec<FlvTag>| {
+		let mut transmuxer = Transmuxer::new();
+		for tag in tags {
+			transmuxer.add_tag(tag);
+		}
+
+		let mut settings = None;
+		let mut segments = Vec::new();
+		while let Some(result) = transmuxer.mux().unwrap() {
+			match result {
+				TransmuxResult::InitSegment {
+					video_settings,
+					audio_settings,
+					..
+				} => settings = Some((video_settings, audio_settings)),
+				TransmuxResult::MediaSegment(segment) => {
+					segments.push((segment.ty, segment.keyframe, segment.timestamp));
+				}
+			}
+		}
+
+		(settings.unwrap(), segments)
+	};
+
+	let ((video_settings, audio_settings), segments) = transmux(tags);
+	let ((expected_video_settings, expected_audio_settings), expected_segments) = transmux(flv.tags);
+
+	// The stream has no bitrate metadata
+	assert_eq!(
+		video_settings,
+		VideoSettings {
+			bitrate: 0,
+			..expected_video_settings
+		}
+	);
+	assert_eq!(
+		audio_settings,
+		AudioSettings {
+			bitrate: 0,
+			..expected_audio_settings
+		}
+	);
+	assert_eq!(segments, expected_segments);
+}
+
+#[test]
+fn test_ts_remuxer_sequence_headers() {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../assets");
+	let data = std::fs::read(dir.join("avc_aac.flv").to_str().unwrap()).unwrap();
+
+	let flv = Flv::demux(&mut io::Cursor::new(data.into())).unwrap();
+
+	let mut remuxer = TsRemuxer::new();
+	remuxer.extend_data(&flv_to_ts(&flv));
+	remuxer.flush().unwrap();
+
+	let mut video_headers = Vec::new();
+	let mut audio_headers = Vec::new();
+	while let Some(tag) = remuxer.read_tag().unwrap() {
+		match tag.data {
+			FlvTagData::Video {
+				data: FlvTagVideoData::Avc(AvcPacket::SequenceHeader(config)),
+				..
+			} => video_headers.push(config),
+			FlvTagData::Audio {
+				data: FlvTagAudioData::Aac(AacPacket::SequenceHeader(config)),
+				..
+			} => audio_headers.push(config),
+			_ => {}
+		}
+	}
+
+	// The SPS and PPS are repeated on every keyframe, but the sequence header is
+	// only sent again if they change.
+	let expected = flv.tags.iter().find_map(|tag| match &tag.data {
+		FlvTagData::Video {
+			data: FlvTagVideoData::Avc(AvcPacket::SequenceHeader(config)),
+			..
+		} => Some(config.clone()),
+		_ => None,
+	});
+	assert_eq!(video_headers.len(), 1);
+	assert_eq!(video_headers[0].sps, expected.as_ref().unwrap().sps);
+	assert_eq!(video_headers[0].pps, expected.as_ref().unwrap().pps);
+	assert_eq!(video_headers[0].profile_indication, 100);
+
+	// The AudioSpecificConfig is rebuilt from the ADTS header.
+	assert_eq!(audio_headers, vec![Bytes::from_static(&[0x11, 0x90])]);
+}
+
+#[test]
+fn test_es_remuxer_opus() {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../assets");
+	let data = std::fs::read(dir.join("avc_aac.flv").to_str().unwrap()).unwrap();
+
+	let flv = Flv::demux(&mut io::Cursor::new(data.into())).unwrap();
+	let config = flv
+		.tags
+		.iter()
+		.find_map(|tag| match &tag.data {
+			FlvTagData::Video {
+				data: FlvTagVideoData::Avc(AvcPacket::SequenceHeader(config)),
+				..
+			} => Some(config.clone()),
+			_ => None,
+		})
+		.unwrap();
+
+	let mut remuxer = EsRemuxer::new();
+
+	// 20ms Opus packets and 30fps video, without timing info from the stream.
+	remuxer.push_opus(0, 2, Bytes::from_static(&[0xFC, 0x01]));
+	remuxer
+		.push_h264(
+			0,
+			0,
+			[
+				config.sps[0].clone(),
+				config.pps[0].clone(),
+				Bytes::from_static(&[0x65, 0x88]),
+			],
+			false,
+		)
+		.unwrap();
+
+	// Nothing is released until the framerate is known.
+	assert!(remuxer.read_tag().is_none());
+
+	remuxer.push_opus(ES_TIMESCALE / 50, 2, Bytes::from_static(&[0xFC, 0x02]));
+	remuxer
+		.push_h264(
+			ES_TIMESCALE / 30,
+			ES_TIMESCALE / 30,
+			[Bytes::from_static(&[0x41, 0x9A])],
+			false,
+		)
+		.unwrap();
+
+	let mut tags = Vec::new();
+	while let Some(tag) = remuxer.read_tag() {
+		tags.push(tag);
+	}
+
+	assert_eq!(tags.len(), 7);
+
+	let FlvTagData::ScriptData { name, data } = &tags[0].data else {
+		panic!("expected metadata, got {:?}", tags[0]);
+	};
+	assert_eq!(name, "onMetaData");
+	let Amf0Value::Object(metadata) = &data[0] else {
+		panic!("expected object, got {:?}", data[0]);
+	};
+	assert_eq!(metadata.get("framerate"), Some(&Amf0Value::Number(30.0)));
+
+	// The OpusHead is created for the sequence start.
+	assert_eq!(
+		tags[1].data,
+		FlvTagData::Audio {
+			sound_rate: flv::SoundRate::Hz44000,
+			sound_size: flv::SoundSize::Bit16,
+			sound_type: flv::SoundType::Stereo,
+			data: FlvTagAudioData::Enhanced(EnhancedAudioPacket::SequenceStart {
+				audio_codec: AudioFourCC::Opus,
+				data: Bytes::from_static(b"OpusHead\x01\x02\x00\x00\x80\xBB\x00\x00\x00\x00\x00"),
+			}),
+		}
+	);
+	assert!(matches!(
+		&tags[2].data,
+		FlvTagData::Audio {
+			data: FlvTagAudioData::Enhanced(EnhancedAudioPacket::CodedFrames { audio_codec: AudioFourCC::Opus, data }),
+			..
+		} if data.as_ref() == [0xFC, 0x01]
+	));
+
+	assert!(matches!(
+		&tags[3].data,
+		FlvTagData::Video {
+			frame_type: FrameType::Keyframe,
+			data: FlvTagVideoData::Avc(AvcPacket::SequenceHeader(_)),
+		}
+	));
+	// The IDR frame is a keyframe, even though it was not flagged as one.
+	assert!(matches!(
+		&tags[4].data,
+		FlvTagData::Video {
+			frame_type: FrameType::Keyframe,
+			data: FlvTagVideoData::Avc(AvcPacket::Nalu { composition_time: 0, data }),
+		} if data.as_ref() == [0, 0, 0, 2, 0x65, 0x88]
+	));
+
+	assert_eq!(tags[5].timestamp, 20);
+	assert_eq!(tags[6].timestamp, 33);
+	assert!(matches!(
+		&tags[6].data,
+		FlvTagData::Video {
+			frame_type: FrameType::Interframe,
+			..
+		}
+	));
+}
+
+/// Replace the audio of the avc_aac.flv asset, every AAC frame is replaced
+/// with the tag created by `frame`, and the sequence header is replaced with
+/// `sequence_header` if there is one.
+fn transmux_with_audio(
+	sequence_header: Option<FlvTagAudioData>,
+	frame: impl Fn(usize) -> FlvTagAudioData,
+) -> (AudioSettings, Bytes, Vec<(u64, usize)>) {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../assets");
+	let data = std::fs::read(dir.join("avc_aac.flv").to_str().unwrap()).unwrap();
+
+	let flv = Flv::demux(&mut io::Cursor::new(data.into())).unwrap();
+
+	let mut transmuxer = Transmuxer::new();
+	let mut frames = 0;
+
+	for tag in flv.tags {
+		let FlvTagData::Audio {
+			sound_rate,
+			sound_size,
+			sound_type,
+			data,
+		} = tag.data
+		else {
+			transmuxer.add_tag(tag);
+			continue;
+		};
+
+		let data = match data {
+			FlvTagAudioData::Aac(AacPacket::SequenceHeader(_)) => match &sequence_header {
+				Some(sequence_header) => sequence_header.clone(),
+				None => continue,
+			},
+			_ => {
+				frames += 1;
+				frame(frames - 1)
+			}
+		};
+
+		transmuxer.add_tag(FlvTag {
+			timestamp: tag.timestamp,
+			stream_id: tag.stream_id,
+			data: FlvTagData::Audio {
+				sound_rate,
+				sound_size,
+				sound_type,
+				data,
+			},
+		});
+	}
+
+	let mut audio_settings = None;
+	let mut init_segment = None;
+	let mut audio_segments = Vec::new();
+
+	while let Some(result) = transmuxer.mux().unwrap() {
+		match result {
+			TransmuxResult::InitSegment {
+				audio_settings: settings,
+				data,
+				..
+			} => {
+				audio_settings = Some(settings);
+				init_segment = Some(data);
+			}
+			TransmuxResult::MediaSegment(segment) if segment.ty == MediaType::Audio => {
+				audio_segments.push((segment.timestamp, segment.data.len()));
+			}
+			_ => {}
+		}
+	}
+
+	(audio_settings.unwrap(), init_segment.unwrap(), audio_segments)
+}
+
+/// The sample entry of the audio track in the init segment.
+fn audio_sample_entry(init_segment: Bytes) -> DynBox {
+	let mut reader = io::Cursor::new(init_segment);
+	while let Ok(dyn_box) = DynBox::demux(&mut reader) {
+		if let DynBox::Moov(moov) = dyn_box {
+			return moov
+				.traks
+				.into_iter()
+				.find(|trak| trak.mdia.minf.smhd.is_some())
+				.unwrap()
+				.mdia
+				.minf
+				.stbl
+				.stsd
+				.entries
+				.remove(0);
+		}
+	}
+
+	panic!("init segment has no moov box");
+}
+
+#[test]
+fn test_transmuxer_opus() {
+	let opus_head = Bytes::from_static(b"OpusHead\x01\x02\x38\x01\x80\xBB\x00\x00\x00\x00\x00");
+
+	// A 20ms CELT packet.
+	let (audio_settings, init_segment, audio_segments) = transmux_with_audio(
+		Some(FlvTagAudioData::Enhanced(EnhancedAudioPacket::SequenceStart {
+			audio_codec: AudioFourCC::Opus,
+			data: opus_head,
+		})),
+		|_| {
+			FlvTagAudioData::Enhanced(EnhancedAudioPacket::CodedFrames {
+				audio_codec: AudioFourCC::Opus,
+				data: Bytes::from_static(&[0xFC, 0xFF, 0xFE]),
+			})
+		},
+	);
+
+	assert_eq!(
+		audio_settings,
+		AudioSettings {
+			sample_rate: 48000,
+			channels: 2,
+			bitrate: 130127,
+			timescale: 48000,
+			codec: AudioCodec::Opus,
+		}
+	);
+	assert_eq!(audio_settings.codec.to_string(), "opus");
+
+	let DynBox::Opus(opus) = audio_sample_entry(init_segment) else {
+		panic!("expected an opus sample entry");
+	};
+	assert_eq!(opus.audio_sample_entry.extension.channel_count, 2);
+	assert_eq!(opus.dops, Dops::new(2, 312, 48000, 0, 0, None));
+
+	assert!(audio_segments.len() > 10);
+	for (idx, (timestamp, _)) in audio_segments.iter().enumerate() {
+		assert_eq!(*timestamp, idx as u64 * 960);
+	}
+}
+
+#[test]
+fn test_transmuxer_mp3() {
+	// MPEG-1 layer III, 128kbit/s, 44.1kHz, stereo. Every frame is 417 bytes
+	// long without padding.
+	let mut frame = vec![0; 417];
+	frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+	let frame = Bytes::from(frame);
+
+	// Every other tag has two frames in it.
+	let (audio_settings, init_segment, audio_segments) = transmux_with_audio(None, |idx| {
+		if idx % 2 == 0 {
+			FlvTagAudioData::Mp3(frame.clone())
+		} else {
+			FlvTagAudioData::Mp3(Bytes::from([frame.clone(), frame.clone()].concat()))
+		}
+	});
+
+	assert_eq!(
+		audio_settings,
+		AudioSettings {
+			sample_rate: 44100,
+			channels: 2,
+			bitrate: 130127,
+			timescale: 44100,
+			codec: AudioCodec::Mp3,
+		}
+	);
+	assert_eq!(audio_settings.codec.to_string(), "mp4a.6B");
+
+	let DynBox::Mp4a(mp4a) = audio_sample_entry(init_segment) else {
+		panic!("expected a mp4a sample entry");
+	};
+	assert_eq!(mp4a.audio_sample_entry.extension.channel_count, 2);
+	assert_eq!(mp4a.audio_sample_entry.extension.sample_rate, 44100);
+	assert_eq!(mp4a.codec().unwrap(), AudioCodec::Mp3);
+
+	assert!(audio_segments.len() > 10);
+	let mut expected = 0;
+	for (idx, (timestamp, _)) in audio_segments.iter().enumerate() {
+		assert_eq!(*timestamp, expected);
+		expected += if idx % 2 == 0 { 1152 } else { 2304 };
+	}
+}
@@ -0,0 +1,125 @@
+use aac::AdtsFrames;
+use flv::{FlvTag, SoundType};
+use h264::NalUnits;
+use mpegts::{Pes, StreamType, TsDemuxer, TS_TIMESCALE};
+
+use crate::{EsRemuxer, TransmuxError};
+
+/// The mask of the 33 bit PTS and DTS.
+const TIMESTAMP_MASK: u64 = (1 << 33) - 1;
+
+/// The number of samples in an AAC frame.
+const AAC_FRAME_SAMPLES: u64 = 1024;
+
+/// Converts a MPEG-2 transport stream into FLV tags, so that streams which
+/// are not ingested over RTMP can be fed to the same `Transmuxer`.
+/// H.264 and AAC (ADTS) streams are converted, other streams are ignored.
+///
+/// Timestamps start at 0 with the first PES. The frames are converted by an
+/// `EsRemuxer`, which also estimates the framerate.
+#[derive(Debug, Default)]
+pub struct TsRemuxer {
+	demuxer: TsDemuxer,
+	/// The decode time of the first PES.
+	base_timestamp: Option<u64>,
+	remuxer: EsRemuxer,
+}
+
+impl TsRemuxer {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Feed raw transport stream data to the remuxer.
+	pub fn extend_data(&mut self, data: &[u8]) {
+		self.demuxer.extend_data(data);
+	}
+
+	/// Get the next FLV tag.
+	/// - will return Ok(None) if we need more data.
+	/// - will return Err if the stream is invalid, the invalid data is dropped
+	///   so we can keep reading after it.
+	pub fn read_tag(&mut self) -> Result<Option<FlvTag>, TransmuxError> {
+		loop {
+			if let Some(tag) = self.remuxer.read_tag() {
+				return Ok(Some(tag));
+			}
+
+			let Some(pes) = self.demuxer.read_pes()? else {
+				return Ok(None);
+			};
+
+			let Some(stream) = self.demuxer.streams().iter().find(|stream| stream.pid == pes.pid) else {
+				continue;
+			};
+
+			match stream.stream_type {
+				StreamType::H264 => self.on_h264(pes)?,
+				StreamType::Aac => self.on_aac(pes)?,
+				_ => {}
+			}
+		}
+	}
+
+	/// Finish the stream, the remaining tags are then returned by `read_tag`.
+	pub fn flush(&mut self) -> Result<(), TransmuxError> {
+		self.remuxer.flush();
+		self.demuxer.flush()?;
+		Ok(())
+	}
+
+	/// Convert a timestamp to the time since the first PES. Timestamps before
+	/// the first PES are clamped to 0.
+	fn timestamp(&mut self, timestamp: u64) -> u64 {
+		let base = *self.base_timestamp.get_or_insert(timestamp);
+
+		let offset = timestamp.wrapping_sub(base) & TIMESTAMP_MASK;
+		if offset > TIMESTAMP_MASK / 2 {
+			return 0;
+		}
+
+		offset
+	}
+
+	fn on_h264(&mut self, pes: Pes) -> Result<(), TransmuxError> {
+		let Some(pts) = pes.pts else {
+			return Ok(());
+		};
+
+		let dts = self.timestamp(pes.dts.unwrap_or(pts));
+		let pts = self.timestamp(pts);
+
+		// NAL units with the forbidden bit set are dropped
+		let nalus = NalUnits::annex_b(pes.data)
+			.filter_map(|nal_unit| nal_unit.ok())
+			.map(|nal_unit| nal_unit.data);
+
+		self.remuxer.push_h264(pts, dts, nalus, pes.random_access)
+	}
+
+	fn on_aac(&mut self, pes: Pes) -> Result<(), TransmuxError> {
+		let Some(pts) = pes.pts else {
+			return Ok(());
+		};
+
+		let pts = self.timestamp(pts);
+
+		for (frames, frame) in AdtsFrames::new(pes.data).enumerate() {
+			let (header, payload) = frame.map_err(|_| TransmuxError::InvalidAdtsHeader)?;
+			let config = header.audio_specific_config().map_err(|_| TransmuxError::InvalidAdtsHeader)?;
+
+			// Every frame after the first one in the PES starts 1024 samples later.
+			let offset = frames as u64 * AAC_FRAME_SAMPLES * TS_TIMESCALE as u64 / config.sampling_frequency as u64;
+
+			let sound_type = if config.channel_configuration == 1 {
+				SoundType::Mono
+			} else {
+				SoundType::Stereo
+			};
+
+			self.remuxer.push_aac(pts + offset, config.data, sound_type, payload);
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,52 @@
+use std::net::IpAddr;
+use std::time::Duration;
+
+use bytes::Bytes;
+
+/// The RTP clock rate of H.264.
+/// RFC 6184 - 8.2.1
+pub const H264_CLOCK_RATE: u32 = 90000;
+
+/// The RTP clock rate of Opus, regardless of the sample rate it was encoded
+/// with.
+/// RFC 7587 - 4.1
+pub const OPUS_CLOCK_RATE: u32 = 48000;
+
+#[derive(Debug, Clone)]
+pub struct WhipConfig {
+	/// The addresses put in the ICE candidates instead of the local addresses,
+	/// for when the listener is behind a 1:1 NAT.
+	pub public_ips: Vec<IpAddr>,
+	/// The range of UDP ports used for the media, any port is used if this is
+	/// not set.
+	pub udp_ports: Option<(u16, u16)>,
+	/// How long we wait for the peer to connect after sending the answer.
+	pub connect_timeout: Duration,
+	/// The largest SDP offer we accept.
+	pub max_offer_size: usize,
+}
+
+impl Default for WhipConfig {
+	fn default() -> Self {
+		Self {
+			public_ips: Vec::new(),
+			udp_ports: None,
+			connect_timeout: Duration::from_secs(10),
+			max_offer_size: 64 * 1024,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A frame received from the peer. The timestamps of all tracks are relative
+/// to the start of the session, so they can be muxed together.
+pub enum Frame {
+	/// A H.264 access unit, the NAL units are without start codes.
+	H264 {
+		timestamp: Duration,
+		keyframe: bool,
+		nalus: Vec<Bytes>,
+	},
+	/// An Opus packet.
+	Opus { timestamp: Duration, channels: u8, data: Bytes },
+}
@@ -0,0 +1,227 @@
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+use webrtc::rtp::packet::Packet;
+
+/// Aggregation packet with multiple NAL units of the same time.
+/// RFC 6184 - 5.7.1
+const NAL_TYPE_STAP_A: u8 = 24;
+
+/// Fragmentation unit, one NAL unit split over multiple packets.
+/// RFC 6184 - 5.8
+const NAL_TYPE_FU_A: u8 = 28;
+
+/// Instantaneous decoding refresh, the start of a keyframe.
+const NAL_TYPE_IDR: u8 = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A H.264 access unit, all NAL units of a frame.
+pub struct AccessUnit {
+	pub timestamp: u32,
+	pub keyframe: bool,
+	/// The NAL units without start codes.
+	pub nalus: Vec<Bytes>,
+}
+
+#[derive(Debug, Default)]
+/// Puts H.264 access units back together from RTP packets in packetization
+/// mode 1 (single NAL units, STAP-A and FU-A).
+/// RFC 6184
+///
+/// Packets have to be pushed in order. An access unit is complete when we
+/// see the marker bit or a packet of the next access unit. Access units with
+/// a gap in the sequence numbers can not be decoded, they are dropped and
+/// counted in `frames_dropped`.
+pub struct H264Depacketizer {
+	timestamp: Option<u32>,
+	nalus: Vec<Bytes>,
+	/// The NAL unit which is being reassembled from FU-A packets.
+	fragment: Option<Vec<u8>>,
+	next_sequence_number: Option<u16>,
+	/// If a packet of the current access unit was lost.
+	broken: bool,
+	/// If packets were lost right before the next access unit.
+	next_broken: bool,
+	access_units: VecDeque<AccessUnit>,
+
+	pub frames_dropped: u64,
+}
+
+impl H264Depacketizer {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Push the next packet, completed access units are returned by `pop`.
+	pub fn push(&mut self, packet: &Packet) {
+		let header = &packet.header;
+
+		if let Some(next_sequence_number) = self.next_sequence_number {
+			if next_sequence_number != header.sequence_number {
+				// We can not tell if the lost packets belong to the current access
+				// unit or to the next one, so we give up on both.
+				self.broken = true;
+				self.fragment = None;
+				self.next_broken = true;
+			}
+		}
+
+		self.next_sequence_number = Some(header.sequence_number.wrapping_add(1));
+
+		// A packet of the next access unit completes the current one, in case
+		// the packet with the marker bit was lost.
+		if self.timestamp.is_some_and(|timestamp| timestamp != header.timestamp) {
+			self.complete();
+		}
+
+		if std::mem::take(&mut self.next_broken) {
+			self.broken = true;
+		}
+
+		self.timestamp = Some(header.timestamp);
+		self.depacketize(packet.payload.clone());
+
+		if header.marker {
+			self.complete();
+		}
+	}
+
+	/// Get the next complete access unit.
+	pub fn pop(&mut self) -> Option<AccessUnit> {
+		self.access_units.pop_front()
+	}
+
+	fn depacketize(&mut self, payload: Bytes) {
+		if payload.is_empty() {
+			return;
+		}
+
+		match payload[0] & 0x1F {
+			NAL_TYPE_STAP_A => {
+				let mut data = payload.slice(1..);
+				while data.len() >= 2 {
+					let size = u16::from_be_bytes([data[0], data[1]]) as usize;
+					if size == 0 || data.len() < size + 2 {
+						self.broken = true;
+						return;
+					}
+
+					self.nalus.push(data.slice(2..size + 2));
+					data = data.slice(size + 2..);
+				}
+			}
+			NAL_TYPE_FU_A => {
+				if payload.len() < 2 {
+					self.broken = true;
+					return;
+				}
+
+				let start = payload[1] & 0x80 != 0;
+				let end = payload[1] & 0x40 != 0;
+
+				if start {
+					// The NAL unit header is split between the indicator and the
+					// FU header.
+					let mut fragment = Vec::with_capacity(payload.len() * 4);
+					fragment.push((payload[0] & 0xE0) | (payload[1] & 0x1F));
+					fragment.extend_from_slice(&payload[2..]);
+					self.fragment = Some(fragment);
+				} else if let Some(fragment) = &mut self.fragment {
+					fragment.extend_from_slice(&payload[2..]);
+				} else {
+					// The start of this NAL unit was lost.
+					self.broken = true;
+					return;
+				}
+
+				if end {
+					if let Some(fragment) = self.fragment.take() {
+						self.nalus.push(Bytes::from(fragment));
+					}
+				}
+			}
+			1..=23 => self.nalus.push(payload),
+			// STAP-B, MTAP and FU-B are only allowed in the interleaved mode.
+			_ => self.broken = true,
+		}
+	}
+
+	fn complete(&mut self) {
+		let Some(timestamp) = self.timestamp.take() else {
+			return;
+		};
+
+		let nalus = std::mem::take(&mut self.nalus);
+
+		// A NAL unit which was not finished is lost.
+		let broken = std::mem::take(&mut self.broken) || self.fragment.take().is_some();
+
+		if nalus.is_empty() && !broken {
+			return;
+		}
+
+		if broken {
+			self.frames_dropped += 1;
+			return;
+		}
+
+		self.access_units.push_back(AccessUnit {
+			timestamp,
+			keyframe: nalus.iter().any(|nalu| nalu[0] & 0x1F == NAL_TYPE_IDR),
+			nalus,
+		});
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpusPacket {
+	pub timestamp: u32,
+	pub data: Bytes,
+}
+
+#[derive(Debug, Default)]
+/// Opus packets are not fragmented, every RTP packet contains one Opus
+/// packet.
+/// RFC 7587 - 4.2
+pub struct OpusDepacketizer {
+	next_sequence_number: Option<u16>,
+	packets: VecDeque<OpusPacket>,
+
+	pub packets_lost: u64,
+}
+
+impl OpusDepacketizer {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Push the next packet. Lost packets are only counted, the decoder
+	/// conceals them.
+	pub fn push(&mut self, packet: &Packet) {
+		let header = &packet.header;
+
+		if let Some(next_sequence_number) = self.next_sequence_number {
+			let gap = header.sequence_number.wrapping_sub(next_sequence_number) as i16;
+			if gap > 0 {
+				self.packets_lost += gap as u64;
+			}
+		}
+
+		self.next_sequence_number = Some(header.sequence_number.wrapping_add(1));
+
+		// Empty packets are sent during discontinuous transmission.
+		if packet.payload.is_empty() {
+			return;
+		}
+
+		self.packets.push_back(OpusPacket {
+			timestamp: header.timestamp,
+			data: packet.payload.clone(),
+		});
+	}
+
+	/// Get the next packet.
+	pub fn pop(&mut self) -> Option<OpusPacket> {
+		self.packets.pop_front()
+	}
+}
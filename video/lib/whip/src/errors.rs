@@ -0,0 +1,36 @@
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum WhipError {
+	IO(io::Error),
+	WebRtc(webrtc::Error),
+	/// The peer did not connect in time
+	Timeout,
+	/// The connection to the peer failed
+	ConnectionFailed,
+}
+
+impl From<io::Error> for WhipError {
+	fn from(error: io::Error) -> Self {
+		Self::IO(error)
+	}
+}
+
+impl From<webrtc::Error> for WhipError {
+	fn from(error: webrtc::Error) -> Self {
+		Self::WebRtc(error)
+	}
+}
+
+impl fmt::Display for WhipError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::IO(error) => write!(f, "io error: {}", error),
+			Self::WebRtc(error) => write!(f, "webrtc error: {}", error),
+			Self::Timeout => write!(f, "connection timed out"),
+			Self::ConnectionFailed => write!(f, "connection failed"),
+		}
+	}
+}
+
+impl std::error::Error for WhipError {}
@@ -0,0 +1,15 @@
+mod define;
+mod depacketizer;
+mod errors;
+mod listener;
+mod reorder;
+mod session;
+
+pub use define::*;
+pub use depacketizer::{AccessUnit, H264Depacketizer, OpusDepacketizer, OpusPacket};
+pub use errors::WhipError;
+pub use listener::{WhipListener, WhipRequest};
+pub use session::WhipSession;
+
+#[cfg(test)]
+mod tests;
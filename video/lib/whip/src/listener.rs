@@ -0,0 +1,343 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderValue};
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, oneshot};
+use webrtc::api::interceptor_registry::register_default_interceptors;
+use webrtc::api::media_engine::{MediaEngine, MIME_TYPE_H264, MIME_TYPE_OPUS};
+use webrtc::api::setting_engine::SettingEngine;
+use webrtc::api::{APIBuilder, API};
+use webrtc::ice::udp_network::{EphemeralUDP, UDPNetwork};
+use webrtc::ice_transport::ice_candidate_type::RTCIceCandidateType;
+use webrtc::interceptor::registry::Registry;
+use webrtc::rtp_transceiver::rtp_codec::{RTCRtpCodecCapability, RTCRtpCodecParameters, RTPCodecType};
+use webrtc::rtp_transceiver::RTCPFeedback;
+
+use crate::session::Sessions;
+use crate::{WhipConfig, WhipError, WhipSession};
+
+/// The number of offers which can wait to be accepted.
+const ACCEPT_BACKLOG: usize = 64;
+
+/// The path of the session resources, the session id follows it.
+const SESSION_PATH: &str = "/session/";
+
+/// The H.264 profiles we accept, all in packetization mode 1.
+/// Constrained baseline, baseline and high.
+const H264_PROFILES: [(u8, &str); 3] = [(102, "42e01f"), (104, "42001f"), (106, "640032")];
+
+type HttpResponse = Response<Full<Bytes>>;
+
+struct Shared {
+	api: API,
+	config: WhipConfig,
+	sessions: Sessions,
+	requests: mpsc::Sender<WhipRequest>,
+}
+
+/// A HTTP server which accepts WHIP offers. Every offer is handed to the
+/// application as a `WhipRequest`, which is accepted or rejected.
+/// draft-ietf-wish-whip
+///
+/// Offers are posted to any path, the session resources are at
+/// `/session/{id}` and are deleted to end the session. Trickle ICE is not
+/// supported, so the answer contains all of our candidates.
+pub struct WhipListener {
+	requests: mpsc::Receiver<WhipRequest>,
+	local_addr: SocketAddr,
+}
+
+impl WhipListener {
+	pub async fn bind(addr: SocketAddr, config: WhipConfig) -> Result<Self, WhipError> {
+		let listener = TcpListener::bind(addr).await?;
+		let local_addr = listener.local_addr()?;
+
+		let (requests_sender, requests) = mpsc::channel(ACCEPT_BACKLOG);
+
+		let shared = Arc::new(Shared {
+			api: build_api(&config)?,
+			config,
+			sessions: Default::default(),
+			requests: requests_sender,
+		});
+
+		tokio::spawn(serve(listener, shared));
+
+		Ok(Self { requests, local_addr })
+	}
+
+	/// Wait for the next offer. The request has to be accepted or rejected,
+	/// the peer keeps waiting for the answer until then.
+	pub async fn accept(&mut self) -> Option<WhipRequest> {
+		self.requests.recv().await
+	}
+
+	pub fn local_addr(&self) -> SocketAddr {
+		self.local_addr
+	}
+}
+
+/// An offer which was posted to the listener.
+pub struct WhipRequest {
+	offer: String,
+	token: Option<String>,
+	peer_addr: SocketAddr,
+	shared: Arc<Shared>,
+	response: Option<oneshot::Sender<HttpResponse>>,
+}
+
+impl WhipRequest {
+	/// The bearer token from the `Authorization` header, which is used to
+	/// authenticate the publisher.
+	pub fn token(&self) -> Option<&str> {
+		self.token.as_deref()
+	}
+
+	pub fn peer_addr(&self) -> SocketAddr {
+		self.peer_addr
+	}
+
+	/// The SDP offer.
+	pub fn offer(&self) -> &str {
+		&self.offer
+	}
+
+	/// Accept the offer, which creates the session and answers the peer.
+	pub async fn accept(mut self) -> Result<WhipSession, WhipError> {
+		let result = WhipSession::new(
+			&self.shared.api,
+			&self.shared.config,
+			self.shared.sessions.clone(),
+			std::mem::take(&mut self.offer),
+			self.peer_addr,
+		)
+		.await;
+
+		let http_response = match &result {
+			Ok((session, answer)) => {
+				let mut http_response = response(StatusCode::CREATED, Bytes::from(answer.clone()));
+				let headers = http_response.headers_mut();
+				headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/sdp"));
+				if let Ok(location) = HeaderValue::from_str(&format!("{SESSION_PATH}{}", session.id())) {
+					headers.insert(header::LOCATION, location);
+				}
+
+				http_response
+			}
+			Err(_) => response(StatusCode::BAD_REQUEST, Bytes::new()),
+		};
+
+		if let Some(sender) = self.response.take() {
+			sender.send(http_response).ok();
+		}
+
+		result.map(|(session, _)| session)
+	}
+
+	/// Reject the offer with the status code.
+	pub fn reject(mut self, status: StatusCode) {
+		if let Some(sender) = self.response.take() {
+			sender.send(response(status, Bytes::new())).ok();
+		}
+	}
+}
+
+impl Drop for WhipRequest {
+	fn drop(&mut self) {
+		if let Some(sender) = self.response.take() {
+			sender.send(response(StatusCode::SERVICE_UNAVAILABLE, Bytes::new())).ok();
+		}
+	}
+}
+
+fn build_api(config: &WhipConfig) -> Result<API, WhipError> {
+	let mut media_engine = MediaEngine::default();
+
+	media_engine.register_codec(
+		RTCRtpCodecParameters {
+			capability: RTCRtpCodecCapability {
+				mime_type: MIME_TYPE_OPUS.to_string(),
+				clock_rate: crate::OPUS_CLOCK_RATE,
+				channels: 2,
+				sdp_fmtp_line: "minptime=10;useinbandfec=1".to_string(),
+				rtcp_feedback: Vec::new(),
+			},
+			payload_type: 111,
+			..Default::default()
+		},
+		RTPCodecType::Audio,
+	)?;
+
+	let feedback = [("nack", ""), ("nack", "pli"), ("ccm", "fir"), ("goog-remb", "")]
+		.into_iter()
+		.map(|(typ, parameter)| RTCPFeedback {
+			typ: typ.to_string(),
+			parameter: parameter.to_string(),
+		})
+		.collect::<Vec<_>>();
+
+	for (payload_type, profile_level_id) in H264_PROFILES {
+		media_engine.register_codec(
+			RTCRtpCodecParameters {
+				capability: RTCRtpCodecCapability {
+					mime_type: MIME_TYPE_H264.to_string(),
+					clock_rate: crate::H264_CLOCK_RATE,
+					channels: 0,
+					sdp_fmtp_line: format!(
+						"level-asymmetry-allowed=1;packetization-mode=1;profile-level-id={profile_level_id}"
+					),
+					rtcp_feedback: feedback.clone(),
+				},
+				payload_type,
+				..Default::default()
+			},
+			RTPCodecType::Video,
+		)?;
+	}
+
+	let registry = register_default_interceptors(Registry::new(), &mut media_engine)?;
+
+	let mut setting_engine = SettingEngine::default();
+	if !config.public_ips.is_empty() {
+		setting_engine.set_nat_1to1_ips(
+			config.public_ips.iter().map(|ip| ip.to_string()).collect(),
+			RTCIceCandidateType::Host,
+		);
+	}
+
+	if let Some((min, max)) = config.udp_ports {
+		let ephemeral = EphemeralUDP::new(min, max).map_err(webrtc::Error::from)?;
+		setting_engine.set_udp_network(UDPNetwork::Ephemeral(ephemeral));
+	}
+
+	Ok(APIBuilder::new()
+		.with_media_engine(media_engine)
+		.with_interceptor_registry(registry)
+		.with_setting_engine(setting_engine)
+		.build())
+}
+
+async fn serve(listener: TcpListener, shared: Arc<Shared>) {
+	loop {
+		let (stream, peer_addr) = tokio::select! {
+			result = listener.accept() => match result {
+				Ok(accepted) => accepted,
+				Err(err) => {
+					tracing::debug!(error = %err, "failed to accept connection");
+					continue;
+				}
+			},
+			// The listener was dropped.
+			_ = shared.requests.closed() => return,
+		};
+
+		let shared = shared.clone();
+		tokio::spawn(async move {
+			let service = service_fn(|request| handle(shared.clone(), peer_addr, request));
+
+			if let Err(err) = hyper::server::conn::http1::Builder::new()
+				.serve_connection(TokioIo::new(stream), service)
+				.await
+			{
+				tracing::debug!(error = %err, "http connection error");
+			}
+		});
+	}
+}
+
+async fn handle(shared: Arc<Shared>, peer_addr: SocketAddr, request: Request<Incoming>) -> Result<HttpResponse, Infallible> {
+	let path = request.uri().path();
+
+	Ok(match *request.method() {
+		// CORS preflight, so browsers can publish from any page.
+		Method::OPTIONS => response(StatusCode::NO_CONTENT, Bytes::new()),
+		Method::DELETE => match path.strip_prefix(SESSION_PATH) {
+			Some(id) => {
+				let peer_connection = shared.sessions.lock().unwrap().remove(id);
+				match peer_connection {
+					Some(peer_connection) => {
+						peer_connection.close().await.ok();
+						response(StatusCode::OK, Bytes::new())
+					}
+					None => response(StatusCode::NOT_FOUND, Bytes::new()),
+				}
+			}
+			None => response(StatusCode::NOT_FOUND, Bytes::new()),
+		},
+		Method::POST if !path.starts_with(SESSION_PATH) => offer(shared, peer_addr, request).await,
+		_ => response(StatusCode::METHOD_NOT_ALLOWED, Bytes::new()),
+	})
+}
+
+async fn offer(shared: Arc<Shared>, peer_addr: SocketAddr, request: Request<Incoming>) -> HttpResponse {
+	let content_type = request
+		.headers()
+		.get(header::CONTENT_TYPE)
+		.and_then(|value| value.to_str().ok());
+	if content_type.map(|value| value.split(';').next().unwrap_or_default().trim()) != Some("application/sdp") {
+		return response(StatusCode::UNSUPPORTED_MEDIA_TYPE, Bytes::new());
+	}
+
+	let token = request
+		.headers()
+		.get(header::AUTHORIZATION)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.strip_prefix("Bearer "))
+		.map(|token| token.trim().to_string());
+
+	let body = match Limited::new(request.into_body(), shared.config.max_offer_size)
+		.collect()
+		.await
+	{
+		Ok(body) => body.to_bytes(),
+		Err(_) => return response(StatusCode::PAYLOAD_TOO_LARGE, Bytes::new()),
+	};
+
+	let Ok(offer) = String::from_utf8(body.to_vec()) else {
+		return response(StatusCode::BAD_REQUEST, Bytes::new());
+	};
+
+	let (sender, receiver) = oneshot::channel();
+	let request = WhipRequest {
+		offer,
+		token,
+		peer_addr,
+		shared: shared.clone(),
+		response: Some(sender),
+	};
+
+	if shared.requests.try_send(request).is_err() {
+		return response(StatusCode::SERVICE_UNAVAILABLE, Bytes::new());
+	}
+
+	receiver
+		.await
+		.unwrap_or_else(|_| response(StatusCode::SERVICE_UNAVAILABLE, Bytes::new()))
+}
+
+fn response(status: StatusCode, body: Bytes) -> HttpResponse {
+	let mut response = Response::new(Full::new(body));
+	*response.status_mut() = status;
+
+	let headers = response.headers_mut();
+	headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+	headers.insert(
+		header::ACCESS_CONTROL_ALLOW_METHODS,
+		HeaderValue::from_static("POST, DELETE, OPTIONS"),
+	);
+	headers.insert(
+		header::ACCESS_CONTROL_ALLOW_HEADERS,
+		HeaderValue::from_static("Authorization, Content-Type"),
+	);
+	headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, HeaderValue::from_static("Location"));
+
+	response
+}
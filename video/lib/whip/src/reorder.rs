@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use tokio::time::Instant;
+use webrtc::rtp::packet::Packet;
+
+/// The most packets we hold back, after which we stop waiting for the
+/// missing ones.
+const MAX_PACKETS: usize = 1024;
+
+#[derive(Debug)]
+/// Puts RTP packets back in order. When a packet is missing the packets after
+/// it are held back for up to `delay`, so the retransmission we asked for with
+/// a NACK can arrive. After that the missing packets are skipped.
+pub struct ReorderBuffer {
+	delay: Duration,
+	/// The sequence number we expect next, extended to 64 bits so it does not
+	/// wrap around.
+	next: Option<u64>,
+	highest: u64,
+	packets: BTreeMap<u64, (Packet, Instant)>,
+}
+
+impl ReorderBuffer {
+	pub fn new(delay: Duration) -> Self {
+		Self {
+			delay,
+			next: None,
+			highest: 0,
+			packets: BTreeMap::new(),
+		}
+	}
+
+	pub fn push(&mut self, packet: Packet, now: Instant) {
+		let sequence_number = self.extend(packet.header.sequence_number);
+
+		// The packet was already released or skipped.
+		if self.next.is_some_and(|next| sequence_number < next) {
+			return;
+		}
+
+		self.packets.entry(sequence_number).or_insert((packet, now));
+	}
+
+	/// The next packet, if it is not missing or we gave up on the missing
+	/// packets before it.
+	pub fn pop(&mut self, now: Instant) -> Option<Packet> {
+		let (&sequence_number, (_, received_at)) = self.packets.first_key_value()?;
+		let next = *self.next.get_or_insert(sequence_number);
+
+		if sequence_number != next && *received_at + self.delay > now && self.packets.len() < MAX_PACKETS {
+			return None;
+		}
+
+		self.next = Some(sequence_number + 1);
+		self.packets.pop_first().map(|(_, (packet, _))| packet)
+	}
+
+	/// The time at which we stop waiting for a missing packet.
+	pub fn next_deadline(&self) -> Option<Instant> {
+		let (&sequence_number, (_, received_at)) = self.packets.first_key_value()?;
+		if self.next.is_some_and(|next| next == sequence_number) {
+			return None;
+		}
+
+		Some(*received_at + self.delay)
+	}
+
+	fn extend(&mut self, sequence_number: u16) -> u64 {
+		// Pick the wrap around period which is closest to the highest sequence
+		// number.
+		let period = self.highest >> 16;
+		let extended = [period.checked_sub(1), Some(period), Some(period + 1)]
+			.into_iter()
+			.flatten()
+			.map(|period| (period << 16) | sequence_number as u64)
+			.min_by_key(|extended| extended.abs_diff(self.highest))
+			.unwrap_or(sequence_number as u64);
+
+		self.highest = self.highest.max(extended);
+
+		extended
+	}
+}
@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+use webrtc::api::media_engine::{MIME_TYPE_H264, MIME_TYPE_OPUS};
+use webrtc::api::API;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::rtcp::payload_feedbacks::picture_loss_indication::PictureLossIndication;
+use webrtc::rtp::packet::Packet;
+use webrtc::track::track_remote::TrackRemote;
+
+use crate::depacketizer::{H264Depacketizer, OpusDepacketizer};
+use crate::reorder::ReorderBuffer;
+use crate::{Frame, WhipConfig, WhipError};
+
+/// The number of frames buffered for a session.
+const FRAME_BUFFER_SIZE: usize = 256;
+
+/// How long we wait for a lost packet to be retransmitted.
+const REORDER_DELAY: Duration = Duration::from_millis(150);
+
+/// The shortest interval at which we ask for a keyframe after losing a
+/// frame.
+const MIN_KEYFRAME_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+pub(crate) type Sessions = Arc<Mutex<HashMap<String, Arc<RTCPeerConnection>>>>;
+
+/// A WHIP session, which receives the media the peer publishes.
+pub struct WhipSession {
+	id: String,
+	peer_addr: SocketAddr,
+	frames: mpsc::Receiver<Frame>,
+	state: watch::Receiver<RTCPeerConnectionState>,
+	/// When we give up if the peer has not connected yet.
+	connect_deadline: Option<Instant>,
+	peer_connection: Arc<RTCPeerConnection>,
+	sessions: Sessions,
+}
+
+impl WhipSession {
+	/// Create a peer connection for the offer. Returns the session and the
+	/// answer, which includes all of our ICE candidates since we don't
+	/// support trickle ICE.
+	pub(crate) async fn new(
+		api: &API,
+		config: &WhipConfig,
+		sessions: Sessions,
+		offer: String,
+		peer_addr: SocketAddr,
+	) -> Result<(Self, String), WhipError> {
+		let peer_connection = Arc::new(api.new_peer_connection(RTCConfiguration::default()).await?);
+
+		let (frames_sender, frames) = mpsc::channel(FRAME_BUFFER_SIZE);
+		let (state_sender, state) = watch::channel(RTCPeerConnectionState::New);
+
+		peer_connection.on_peer_connection_state_change(Box::new(move |peer_connection_state| {
+			state_sender.send_replace(peer_connection_state);
+			Box::pin(async {})
+		}));
+
+		let start = Instant::now();
+		let weak_peer_connection = Arc::downgrade(&peer_connection);
+		peer_connection.on_track(Box::new(move |track, _| {
+			if let Some(track) = track {
+				tokio::spawn(read_track(track, weak_peer_connection.clone(), frames_sender.clone(), start));
+			}
+
+			Box::pin(async {})
+		}));
+
+		let answer = match negotiate(&peer_connection, offer).await {
+			Ok(answer) => answer,
+			Err(err) => {
+				peer_connection.close().await.ok();
+				return Err(err);
+			}
+		};
+
+		let id = (0..16).map(|_| format!("{:02x}", rand::random::<u8>())).collect::<String>();
+		sessions.lock().unwrap().insert(id.clone(), peer_connection.clone());
+
+		Ok((
+			Self {
+				id,
+				peer_addr,
+				frames,
+				state,
+				connect_deadline: Some(start + config.connect_timeout),
+				peer_connection,
+				sessions,
+			},
+			answer,
+		))
+	}
+
+	/// Wait until the peer has connected.
+	/// - will return Err if the connection failed or was closed, or the peer
+	///   did not connect in time.
+	pub async fn connected(&mut self) -> Result<(), WhipError> {
+		while let Some(connect_deadline) = self.connect_deadline {
+			tokio::select! {
+				result = self.state.changed() => {
+					if result.is_err() {
+						return Err(WhipError::ConnectionFailed);
+					}
+
+					match *self.state.borrow_and_update() {
+						RTCPeerConnectionState::Connected => self.connect_deadline = None,
+						RTCPeerConnectionState::Failed | RTCPeerConnectionState::Closed => {
+							return Err(WhipError::ConnectionFailed);
+						}
+						_ => {}
+					}
+				}
+				_ = tokio::time::sleep_until(connect_deadline) => return Err(WhipError::Timeout),
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Get the next frame.
+	/// - will return Ok(None) if the session was closed, either by the peer
+	///   or by deleting the session resource.
+	/// - will return Err if the connection failed or the peer did not connect
+	///   in time.
+	pub async fn recv(&mut self) -> Result<Option<Frame>, WhipError> {
+		loop {
+			let connect_deadline = self.connect_deadline;
+
+			tokio::select! {
+				biased;
+				Some(frame) = self.frames.recv() => return Ok(Some(frame)),
+				result = self.state.changed() => {
+					if result.is_err() {
+						return Ok(None);
+					}
+
+					match *self.state.borrow_and_update() {
+						RTCPeerConnectionState::Connected => self.connect_deadline = None,
+						RTCPeerConnectionState::Failed => return Err(WhipError::ConnectionFailed),
+						RTCPeerConnectionState::Closed => return Ok(None),
+						_ => {}
+					}
+				}
+				_ = tokio::time::sleep_until(connect_deadline.unwrap_or_else(Instant::now)), if connect_deadline.is_some() => {
+					return Err(WhipError::Timeout);
+				}
+			}
+		}
+	}
+
+	/// Close the session.
+	pub async fn close(self) {
+		self.peer_connection.close().await.ok();
+	}
+
+	/// The id of the session, which is used in the URL of the session
+	/// resource.
+	pub fn id(&self) -> &str {
+		&self.id
+	}
+
+	pub fn peer_addr(&self) -> SocketAddr {
+		self.peer_addr
+	}
+}
+
+impl Drop for WhipSession {
+	fn drop(&mut self) {
+		self.sessions.lock().unwrap().remove(&self.id);
+
+		let peer_connection = self.peer_connection.clone();
+		tokio::spawn(async move {
+			peer_connection.close().await.ok();
+		});
+	}
+}
+
+async fn negotiate(peer_connection: &RTCPeerConnection, offer: String) -> Result<String, WhipError> {
+	peer_connection
+		.set_remote_description(RTCSessionDescription::offer(offer)?)
+		.await?;
+
+	let answer = peer_connection.create_answer(None).await?;
+
+	let mut gathering_complete = peer_connection.gathering_complete_promise().await;
+	peer_connection.set_local_description(answer).await?;
+	gathering_complete.recv().await;
+
+	let answer = peer_connection.local_description().await.ok_or(WhipError::ConnectionFailed)?;
+
+	Ok(answer.sdp)
+}
+
+enum Depacketizer {
+	H264(H264Depacketizer),
+	Opus(OpusDepacketizer, u8),
+}
+
+/// Converts RTP timestamps to the time since the start of the session. The
+/// first packet of a track is placed at the time it arrived, so tracks are
+/// only as well synchronized as their first packets.
+struct RtpClock {
+	clock_rate: u32,
+	start: Instant,
+	/// The time of the first packet since the start of the session.
+	offset: Option<Duration>,
+	last_timestamp: u32,
+	/// The RTP ticks since the first packet, which can go back for B-frames.
+	ticks: i64,
+}
+
+impl RtpClock {
+	fn new(clock_rate: u32, start: Instant) -> Self {
+		Self {
+			clock_rate,
+			start,
+			offset: None,
+			last_timestamp: 0,
+			ticks: 0,
+		}
+	}
+
+	fn timestamp(&mut self, timestamp: u32) -> Duration {
+		let offset = match self.offset {
+			Some(offset) => {
+				self.ticks += timestamp.wrapping_sub(self.last_timestamp) as i32 as i64;
+				offset
+			}
+			None => *self.offset.insert(Instant::now() - self.start),
+		};
+
+		self.last_timestamp = timestamp;
+
+		let elapsed = Duration::from_nanos((self.ticks.max(0) as u64) * 1_000_000_000 / self.clock_rate as u64);
+		offset + elapsed
+	}
+}
+
+/// Read the packets of a track and send the frames in them to the session.
+async fn read_track(
+	track: Arc<TrackRemote>,
+	peer_connection: Weak<RTCPeerConnection>,
+	frames: mpsc::Sender<Frame>,
+	start: Instant,
+) {
+	let codec = track.codec().await;
+
+	let mime_type = &codec.capability.mime_type;
+	let mut depacketizer = if mime_type.eq_ignore_ascii_case(MIME_TYPE_H264) {
+		Depacketizer::H264(H264Depacketizer::new())
+	} else if mime_type.eq_ignore_ascii_case(MIME_TYPE_OPUS) {
+		Depacketizer::Opus(OpusDepacketizer::new(), codec.capability.channels.max(1) as u8)
+	} else {
+		tracing::debug!(mime_type, "unsupported track");
+		return;
+	};
+
+	let mut clock = RtpClock::new(codec.capability.clock_rate, start);
+	let mut reorder_buffer = ReorderBuffer::new(REORDER_DELAY);
+
+	let mut last_keyframe_request: Option<Instant> = None;
+	let mut frames_dropped = 0;
+
+	loop {
+		// Ask for a keyframe right away so we don't have to wait for the next
+		// one, and again when we had to drop a frame.
+		if let Depacketizer::H264(depacketizer) = &depacketizer {
+			let request_keyframe = match last_keyframe_request {
+				Some(last) => {
+					depacketizer.frames_dropped > frames_dropped && last.elapsed() >= MIN_KEYFRAME_REQUEST_INTERVAL
+				}
+				None => true,
+			};
+
+			if request_keyframe {
+				frames_dropped = depacketizer.frames_dropped;
+				last_keyframe_request = Some(Instant::now());
+				send_keyframe_request(&peer_connection, track.ssrc()).await;
+			}
+		}
+
+		while let Some(packet) = reorder_buffer.pop(Instant::now()) {
+			for frame in depacketize(&mut depacketizer, &mut clock, &packet) {
+				if frames.send(frame).await.is_err() {
+					return;
+				}
+			}
+		}
+
+		let deadline = reorder_buffer.next_deadline();
+
+		tokio::select! {
+			result = track.read_rtp() => match result {
+				Ok((packet, _)) => reorder_buffer.push(packet, Instant::now()),
+				Err(err) => {
+					tracing::debug!(error = %err, "track ended");
+					return;
+				}
+			},
+			_ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {}
+		}
+	}
+}
+
+async fn send_keyframe_request(peer_connection: &Weak<RTCPeerConnection>, media_ssrc: u32) {
+	let Some(peer_connection) = peer_connection.upgrade() else {
+		return;
+	};
+
+	let pli = PictureLossIndication {
+		sender_ssrc: 0,
+		media_ssrc,
+	};
+
+	if let Err(err) = peer_connection.write_rtcp(&[Box::new(pli)]).await {
+		tracing::debug!(error = %err, "failed to request keyframe");
+	}
+}
+
+fn depacketize(depacketizer: &mut Depacketizer, clock: &mut RtpClock, packet: &Packet) -> Vec<Frame> {
+	let mut frames = Vec::new();
+
+	match depacketizer {
+		Depacketizer::H264(depacketizer) => {
+			depacketizer.push(packet);
+			while let Some(access_unit) = depacketizer.pop() {
+				frames.push(Frame::H264 {
+					timestamp: clock.timestamp(access_unit.timestamp),
+					keyframe: access_unit.keyframe,
+					nalus: access_unit.nalus,
+				});
+			}
+		}
+		Depacketizer::Opus(depacketizer, channels) => {
+			depacketizer.push(packet);
+			while let Some(opus_packet) = depacketizer.pop() {
+				frames.push(Frame::Opus {
+					timestamp: clock.timestamp(opus_packet.timestamp),
+					channels: *channels,
+					data: opus_packet.data,
+				});
+			}
+		}
+	}
+
+	frames
+}
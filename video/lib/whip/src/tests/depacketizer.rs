@@ -0,0 +1,156 @@
+use bytes::Bytes;
+use webrtc::rtp::header::Header;
+use webrtc::rtp::packet::Packet;
+
+use crate::{AccessUnit, H264Depacketizer, OpusDepacketizer, OpusPacket};
+
+fn packet(sequence_number: u16, timestamp: u32, marker: bool, payload: &[u8]) -> Packet {
+	Packet {
+		header: Header {
+			version: 2,
+			marker,
+			payload_type: 102,
+			sequence_number,
+			timestamp,
+			ssrc: 1,
+			..Default::default()
+		},
+		payload: Bytes::copy_from_slice(payload),
+	}
+}
+
+fn access_units(depacketizer: &mut H264Depacketizer) -> Vec<AccessUnit> {
+	std::iter::from_fn(|| depacketizer.pop()).collect()
+}
+
+#[test]
+fn test_h264_depacketizer() {
+	let mut depacketizer = H264Depacketizer::new();
+
+	// SPS and PPS in a STAP-A, followed by an IDR slice in three FU-A packets.
+	depacketizer.push(&packet(
+		65534,
+		3000,
+		false,
+		&[0x78, 0x00, 0x03, 0x67, 0x42, 0x00, 0x00, 0x02, 0x68, 0xCE],
+	));
+	depacketizer.push(&packet(65535, 3000, false, &[0x7C, 0x85, 0x01, 0x02]));
+	depacketizer.push(&packet(0, 3000, false, &[0x7C, 0x05, 0x03]));
+	assert!(depacketizer.pop().is_none());
+	depacketizer.push(&packet(1, 3000, true, &[0x7C, 0x45, 0x04]));
+
+	// A single NAL unit packet, which completes the access unit even though the
+	// marker bit is missing.
+	depacketizer.push(&packet(2, 6000, false, &[0x41, 0x9A, 0x01]));
+	depacketizer.push(&packet(3, 9000, true, &[0x41, 0x9A, 0x02]));
+
+	assert_eq!(
+		access_units(&mut depacketizer),
+		vec![
+			AccessUnit {
+				timestamp: 3000,
+				keyframe: true,
+				nalus: vec![
+					Bytes::from_static(&[0x67, 0x42, 0x00]),
+					Bytes::from_static(&[0x68, 0xCE]),
+					Bytes::from_static(&[0x65, 0x01, 0x02, 0x03, 0x04]),
+				],
+			},
+			AccessUnit {
+				timestamp: 6000,
+				keyframe: false,
+				nalus: vec![Bytes::from_static(&[0x41, 0x9A, 0x01])],
+			},
+			AccessUnit {
+				timestamp: 9000,
+				keyframe: false,
+				nalus: vec![Bytes::from_static(&[0x41, 0x9A, 0x02])],
+			},
+		]
+	);
+	assert_eq!(depacketizer.frames_dropped, 0);
+}
+
+#[test]
+fn test_h264_depacketizer_loss() {
+	let mut depacketizer = H264Depacketizer::new();
+
+	// The middle fragment is lost.
+	depacketizer.push(&packet(10, 3000, false, &[0x7C, 0x85, 0x01]));
+	depacketizer.push(&packet(12, 3000, true, &[0x7C, 0x45, 0x03]));
+
+	// The first packet of this frame is lost, so it starts in the middle of a
+	// NAL unit.
+	depacketizer.push(&packet(14, 6000, true, &[0x7C, 0x41, 0x02]));
+
+	// The packet with the marker bit is lost, we can not tell if the frame
+	// was complete.
+	depacketizer.push(&packet(15, 9000, false, &[0x41, 0x9A]));
+	depacketizer.push(&packet(17, 12000, true, &[0x41, 0x9B]));
+
+	depacketizer.push(&packet(18, 15000, true, &[0x41, 0x9C]));
+
+	assert_eq!(
+		access_units(&mut depacketizer),
+		vec![AccessUnit {
+			timestamp: 15000,
+			keyframe: false,
+			nalus: vec![Bytes::from_static(&[0x41, 0x9C])],
+		}]
+	);
+	assert_eq!(depacketizer.frames_dropped, 4);
+}
+
+#[test]
+fn test_h264_depacketizer_invalid() {
+	let mut depacketizer = H264Depacketizer::new();
+
+	// The size in the STAP-A is larger than the packet.
+	depacketizer.push(&packet(0, 3000, true, &[0x78, 0x00, 0x05, 0x67, 0x42]));
+	// FU-B is not allowed in packetization mode 1.
+	depacketizer.push(&packet(1, 6000, true, &[0x7D, 0x85, 0x00, 0x00, 0x01]));
+	// Empty packets are ignored.
+	depacketizer.push(&packet(2, 9000, false, &[]));
+	depacketizer.push(&packet(3, 9000, true, &[0x41, 0x9A]));
+
+	assert_eq!(
+		access_units(&mut depacketizer),
+		vec![AccessUnit {
+			timestamp: 9000,
+			keyframe: false,
+			nalus: vec![Bytes::from_static(&[0x41, 0x9A])],
+		}]
+	);
+	assert_eq!(depacketizer.frames_dropped, 2);
+}
+
+#[test]
+fn test_opus_depacketizer() {
+	let mut depacketizer = OpusDepacketizer::new();
+
+	depacketizer.push(&packet(65535, 0, true, &[0xFC, 0x01]));
+	depacketizer.push(&packet(0, 960, false, &[0xFC, 0x02]));
+	// Discontinuous transmission.
+	depacketizer.push(&packet(1, 1920, false, &[]));
+	depacketizer.push(&packet(4, 4800, false, &[0xFC, 0x05]));
+
+	let packets = std::iter::from_fn(|| depacketizer.pop()).collect::<Vec<_>>();
+	assert_eq!(
+		packets,
+		vec![
+			OpusPacket {
+				timestamp: 0,
+				data: Bytes::from_static(&[0xFC, 0x01]),
+			},
+			OpusPacket {
+				timestamp: 960,
+				data: Bytes::from_static(&[0xFC, 0x02]),
+			},
+			OpusPacket {
+				timestamp: 4800,
+				data: Bytes::from_static(&[0xFC, 0x05]),
+			},
+		]
+	);
+	assert_eq!(depacketizer.packets_lost, 2);
+}
@@ -0,0 +1,402 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::header::{self, HeaderValue};
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+use webrtc::api::interceptor_registry::register_default_interceptors;
+use webrtc::api::media_engine::{MediaEngine, MIME_TYPE_H264, MIME_TYPE_OPUS};
+use webrtc::api::APIBuilder;
+use webrtc::interceptor::registry::Registry;
+use webrtc::media::Sample;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
+
+use crate::{Frame, WhipConfig, WhipError, WhipListener};
+
+const SPS: &[u8] = &[
+	0x67, 0x42, 0xC0, 0x1F, 0xDA, 0x01, 0x40, 0x16, 0xEC, 0x04, 0x40, 0x00, 0x00, 0x03, 0x00, 0x40, 0x00, 0x00, 0x0F, 0x23,
+	0xC6, 0x0C, 0xA8,
+];
+const PPS: &[u8] = &[0x68, 0xCE, 0x3C, 0x80];
+
+/// A publisher which sends a H.264 and an Opus track, like a browser would.
+struct Publisher {
+	peer_connection: RTCPeerConnection,
+	state: watch::Receiver<RTCPeerConnectionState>,
+	video: Arc<TrackLocalStaticSample>,
+	audio: Arc<TrackLocalStaticSample>,
+}
+
+impl Publisher {
+	async fn new() -> Self {
+		let mut media_engine = MediaEngine::default();
+		media_engine.register_default_codecs().unwrap();
+		let registry = register_default_interceptors(Registry::new(), &mut media_engine).unwrap();
+
+		let api = APIBuilder::new()
+			.with_media_engine(media_engine)
+			.with_interceptor_registry(registry)
+			.build();
+
+		let peer_connection = api.new_peer_connection(RTCConfiguration::default()).await.unwrap();
+
+		let (state_sender, state) = watch::channel(RTCPeerConnectionState::New);
+		peer_connection.on_peer_connection_state_change(Box::new(move |peer_connection_state| {
+			state_sender.send_replace(peer_connection_state);
+			Box::pin(async {})
+		}));
+
+		let video = Arc::new(TrackLocalStaticSample::new(
+			RTCRtpCodecCapability {
+				mime_type: MIME_TYPE_H264.to_string(),
+				..Default::default()
+			},
+			"video".to_string(),
+			"loopback".to_string(),
+		));
+		let audio = Arc::new(TrackLocalStaticSample::new(
+			RTCRtpCodecCapability {
+				mime_type: MIME_TYPE_OPUS.to_string(),
+				..Default::default()
+			},
+			"audio".to_string(),
+			"loopback".to_string(),
+		));
+
+		peer_connection.add_track(video.clone()).await.unwrap();
+		peer_connection.add_track(audio.clone()).await.unwrap();
+
+		Self {
+			peer_connection,
+			state,
+			video,
+			audio,
+		}
+	}
+
+	async fn connected(&self) {
+		let mut state = self.state.clone();
+		state
+			.wait_for(|state| *state == RTCPeerConnectionState::Connected)
+			.await
+			.unwrap();
+	}
+
+	async fn offer(&self) -> String {
+		let offer = self.peer_connection.create_offer(None).await.unwrap();
+
+		let mut gathering_complete = self.peer_connection.gathering_complete_promise().await;
+		self.peer_connection.set_local_description(offer).await.unwrap();
+		gathering_complete.recv().await;
+
+		self.peer_connection.local_description().await.unwrap().sdp
+	}
+}
+
+async fn request(addr: SocketAddr, method: Method, path: &str, headers: &[(&str, &str)], body: String) -> Response<Bytes> {
+	let stream = TcpStream::connect(addr).await.unwrap();
+	let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream)).await.unwrap();
+	tokio::spawn(connection);
+
+	let mut request = Request::builder().method(method).uri(path);
+	for (name, value) in headers {
+		request = request.header(*name, *value);
+	}
+
+	let response = sender
+		.send_request(request.body(Full::new(Bytes::from(body))).unwrap())
+		.await
+		.unwrap();
+	let (parts, body) = response.into_parts();
+
+	Response::from_parts(parts, body.collect().await.unwrap().to_bytes())
+}
+
+async fn post_offer(addr: SocketAddr, token: &str, offer: String) -> Response<Bytes> {
+	let authorization = format!("Bearer {token}");
+	request(
+		addr,
+		Method::POST,
+		"/whip",
+		&[("content-type", "application/sdp"), ("authorization", &authorization)],
+		offer,
+	)
+	.await
+}
+
+fn annex_b(nalus: &[&[u8]]) -> Bytes {
+	let mut data = Vec::new();
+	for nalu in nalus {
+		data.extend_from_slice(&[0, 0, 0, 1]);
+		data.extend_from_slice(nalu);
+	}
+
+	Bytes::from(data)
+}
+
+#[tokio::test]
+async fn test_loopback_publish() {
+	let mut listener = WhipListener::bind("127.0.0.1:0".parse().unwrap(), WhipConfig::default())
+		.await
+		.unwrap();
+	let addr = listener.local_addr();
+
+	let publisher = Publisher::new().await;
+	let offer = publisher.offer().await;
+
+	let (response, session) = tokio::time::timeout(Duration::from_secs(10), async {
+		tokio::join!(post_offer(addr, "stream-key", offer), async {
+			let request = listener.accept().await.unwrap();
+			assert_eq!(request.token(), Some("stream-key"));
+			assert!(request.peer_addr().ip().is_loopback());
+			request.accept().await.unwrap()
+		})
+	})
+	.await
+	.unwrap();
+	let mut session = session;
+
+	assert_eq!(response.status(), StatusCode::CREATED);
+	assert_eq!(
+		response.headers().get(header::CONTENT_TYPE),
+		Some(&HeaderValue::from_static("application/sdp"))
+	);
+	let location = response
+		.headers()
+		.get(header::LOCATION)
+		.unwrap()
+		.to_str()
+		.unwrap()
+		.to_string();
+	assert_eq!(location, format!("/session/{}", session.id()));
+
+	let answer = String::from_utf8(response.into_body().to_vec()).unwrap();
+	publisher
+		.peer_connection
+		.set_remote_description(RTCSessionDescription::answer(answer).unwrap())
+		.await
+		.unwrap();
+
+	// A keyframe large enough to be fragmented, followed by interframes.
+	let idr = [&[0x65, 0x88][..], &[0xAB; 3000]].concat();
+	let frames = [
+		annex_b(&[SPS, PPS, &idr]),
+		annex_b(&[&[0x41, 0x9A, 0x01]]),
+		annex_b(&[&[0x41, 0x9A, 0x02]]),
+	];
+
+	// Samples written before the publisher is connected are dropped.
+	tokio::time::timeout(Duration::from_secs(10), publisher.connected())
+		.await
+		.unwrap();
+
+	tokio::time::timeout(Duration::from_secs(10), session.connected())
+		.await
+		.unwrap()
+		.unwrap();
+
+	let sender = tokio::spawn({
+		let video = publisher.video.clone();
+		let audio = publisher.audio.clone();
+
+		async move {
+			for idx in 0.. {
+				let frame = frames[idx.min(2)].clone();
+				video
+					.write_sample(&Sample {
+						data: frame,
+						duration: Duration::from_millis(40),
+						..Default::default()
+					})
+					.await
+					.unwrap();
+
+				for _ in 0..2 {
+					audio
+						.write_sample(&Sample {
+							data: Bytes::from_static(&[0xFC, 0xFF, 0xFE]),
+							duration: Duration::from_millis(20),
+							..Default::default()
+						})
+						.await
+						.unwrap();
+				}
+
+				tokio::time::sleep(Duration::from_millis(40)).await;
+			}
+		}
+	});
+
+	let mut video = Vec::new();
+	let mut audio = Vec::new();
+
+	while video.len() < 10 || audio.len() < 10 {
+		let frame = tokio::time::timeout(Duration::from_secs(10), session.recv())
+			.await
+			.unwrap()
+			.unwrap()
+			.unwrap();
+
+		match frame {
+			Frame::H264 {
+				timestamp,
+				keyframe,
+				nalus,
+			} => video.push((timestamp, keyframe, nalus)),
+			Frame::Opus {
+				timestamp,
+				channels,
+				data,
+			} => {
+				assert_eq!(channels, 2);
+				assert_eq!(data, Bytes::from_static(&[0xFC, 0xFF, 0xFE]));
+				audio.push(timestamp);
+			}
+		}
+	}
+
+	sender.abort();
+
+	// The keyframe is put back together, with the SPS and PPS in front of it.
+	let (_, keyframe, nalus) = &video[0];
+	assert!(*keyframe);
+	assert_eq!(
+		nalus,
+		&vec![Bytes::from_static(SPS), Bytes::from_static(PPS), Bytes::from(idr)]
+	);
+	assert!(video[1..].iter().all(|(_, keyframe, nalus)| !keyframe && nalus.len() == 1));
+
+	// The timestamps come from the RTP timestamps, so they have the spacing of
+	// the sample durations.
+	for pair in video.windows(2) {
+		assert_eq!(pair[1].0 - pair[0].0, Duration::from_millis(40));
+	}
+	for pair in audio.windows(2) {
+		assert_eq!(pair[1] - pair[0], Duration::from_millis(20));
+	}
+
+	// Deleting the session resource ends the session.
+	let response = request(addr, Method::DELETE, &location, &[], String::new()).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let result = tokio::time::timeout(Duration::from_secs(5), async {
+		loop {
+			match session.recv().await {
+				Ok(Some(_)) => continue,
+				result => return result,
+			}
+		}
+	})
+	.await
+	.unwrap();
+	assert!(matches!(result, Ok(None)));
+
+	let response = request(addr, Method::DELETE, &location, &[], String::new()).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+	publisher.peer_connection.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_loopback_reject() {
+	let mut listener = WhipListener::bind("127.0.0.1:0".parse().unwrap(), WhipConfig::default())
+		.await
+		.unwrap();
+	let addr = listener.local_addr();
+
+	tokio::spawn(async move {
+		while let Some(request) = listener.accept().await {
+			if request.token() == Some("unauthorized") {
+				request.reject(StatusCode::UNAUTHORIZED);
+			}
+			// Dropping the request rejects it as well.
+		}
+	});
+
+	let response = post_offer(addr, "unauthorized", "v=0".to_string()).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+	let response = post_offer(addr, "dropped", "v=0".to_string()).await;
+	assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+	// Only SDP offers are accepted.
+	let response = request(
+		addr,
+		Method::POST,
+		"/whip",
+		&[("content-type", "text/plain")],
+		"v=0".to_string(),
+	)
+	.await;
+	assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+	// Trickle ICE is not supported.
+	let response = request(addr, Method::PATCH, "/session/abc", &[], String::new()).await;
+	assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+	// CORS preflight for browsers.
+	let response = request(addr, Method::OPTIONS, "/whip", &[], String::new()).await;
+	assert_eq!(response.status(), StatusCode::NO_CONTENT);
+	assert_eq!(
+		response.headers().get(header::ACCESS_CONTROL_EXPOSE_HEADERS),
+		Some(&HeaderValue::from_static("Location"))
+	);
+}
+
+#[tokio::test]
+async fn test_loopback_invalid_offer() {
+	let mut listener = WhipListener::bind("127.0.0.1:0".parse().unwrap(), WhipConfig::default())
+		.await
+		.unwrap();
+	let addr = listener.local_addr();
+
+	let (response, result) = tokio::join!(post_offer(addr, "key", "not sdp".to_string()), async {
+		listener.accept().await.unwrap().accept().await
+	});
+
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+	assert!(matches!(result, Err(WhipError::WebRtc(_))));
+}
+
+#[tokio::test]
+async fn test_loopback_timeout() {
+	let mut listener = WhipListener::bind(
+		"127.0.0.1:0".parse().unwrap(),
+		WhipConfig {
+			connect_timeout: Duration::from_millis(500),
+			..Default::default()
+		},
+	)
+	.await
+	.unwrap();
+	let addr = listener.local_addr();
+
+	// The publisher never gets the answer, so it never connects.
+	let publisher = Publisher::new().await;
+	let offer = publisher.offer().await;
+
+	let (_, session) = tokio::join!(post_offer(addr, "key", offer), async {
+		listener.accept().await.unwrap().accept().await.unwrap()
+	});
+	let mut session = session;
+
+	let result = tokio::time::timeout(Duration::from_secs(5), session.connected())
+		.await
+		.unwrap();
+	assert!(matches!(result, Err(WhipError::Timeout)));
+
+	let result = tokio::time::timeout(Duration::from_secs(5), session.recv()).await.unwrap();
+	assert!(matches!(result, Err(WhipError::Timeout)));
+
+	publisher.peer_connection.close().await.unwrap();
+}
@@ -0,0 +1,3 @@
+mod depacketizer;
+mod loopback;
+mod reorder;
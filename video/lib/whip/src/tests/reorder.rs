@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::time::Instant;
+use webrtc::rtp::header::Header;
+use webrtc::rtp::packet::Packet;
+
+use crate::reorder::ReorderBuffer;
+
+fn packet(sequence_number: u16) -> Packet {
+	Packet {
+		header: Header {
+			sequence_number,
+			..Default::default()
+		},
+		payload: Bytes::new(),
+	}
+}
+
+fn pop_all(buffer: &mut ReorderBuffer, now: Instant) -> Vec<u16> {
+	std::iter::from_fn(|| buffer.pop(now))
+		.map(|packet| packet.header.sequence_number)
+		.collect()
+}
+
+#[test]
+fn test_reorder_buffer() {
+	let start = Instant::now();
+	let mut buffer = ReorderBuffer::new(Duration::from_millis(100));
+
+	buffer.push(packet(65534), start);
+	buffer.push(packet(0), start);
+	assert_eq!(pop_all(&mut buffer, start), vec![65534]);
+	assert_eq!(buffer.next_deadline(), Some(start + Duration::from_millis(100)));
+
+	// The missing packet arrives in time, the packets are put back in order
+	// across the wrap around.
+	buffer.push(packet(1), start);
+	buffer.push(packet(65535), start + Duration::from_millis(50));
+	assert_eq!(pop_all(&mut buffer, start + Duration::from_millis(50)), vec![65535, 0, 1]);
+	assert_eq!(buffer.next_deadline(), None);
+
+	// Duplicates and packets which are too late are dropped.
+	buffer.push(packet(1), start);
+	buffer.push(packet(65535), start);
+	assert_eq!(pop_all(&mut buffer, start + Duration::from_millis(50)), Vec::<u16>::new());
+
+	// We give up on a missing packet after the delay.
+	buffer.push(packet(3), start + Duration::from_millis(60));
+	buffer.push(packet(4), start + Duration::from_millis(60));
+	assert_eq!(pop_all(&mut buffer, start + Duration::from_millis(159)), Vec::<u16>::new());
+	assert_eq!(pop_all(&mut buffer, start + Duration::from_millis(160)), vec![3, 4]);
+
+	buffer.push(packet(2), start + Duration::from_millis(170));
+	assert_eq!(pop_all(&mut buffer, start + Duration::from_millis(170)), Vec::<u16>::new());
+}
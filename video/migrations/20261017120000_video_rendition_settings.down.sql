@@ -0,0 +1,19 @@
+ALTER TABLE transcoding_configs DROP COLUMN IF EXISTS video_settings;
+
+-- Enum values can only be dropped once nothing references them, the configs fall back to the HD rendition.
+UPDATE transcoding_configs SET renditions = array_append(renditions, 'VIDEO_HD')
+    WHERE ('VIDEO_FHD' = ANY(renditions) OR 'VIDEO_QHD' = ANY(renditions)) AND NOT 'VIDEO_HD' = ANY(renditions);
+UPDATE transcoding_configs SET renditions = array_remove(array_remove(renditions, 'VIDEO_FHD'), 'VIDEO_QHD')
+    WHERE 'VIDEO_FHD' = ANY(renditions) OR 'VIDEO_QHD' = ANY(renditions);
+
+UPDATE recording_configs SET renditions = array_append(renditions, 'VIDEO_HD')
+    WHERE ('VIDEO_FHD' = ANY(renditions) OR 'VIDEO_QHD' = ANY(renditions)) AND NOT 'VIDEO_HD' = ANY(renditions);
+UPDATE recording_configs SET renditions = array_remove(array_remove(renditions, 'VIDEO_FHD'), 'VIDEO_QHD')
+    WHERE 'VIDEO_FHD' = ANY(renditions) OR 'VIDEO_QHD' = ANY(renditions);
+
+-- Recorded 1080p and 1440p renditions cannot be mapped to another rendition, their segments are left in s3.
+DELETE FROM recording_rendition_segments WHERE rendition IN ('VIDEO_FHD', 'VIDEO_QHD');
+DELETE FROM recording_renditions WHERE rendition IN ('VIDEO_FHD', 'VIDEO_QHD');
+
+ALTER TYPE rendition DROP VALUE 'VIDEO_FHD';
+ALTER TYPE rendition DROP VALUE 'VIDEO_QHD';
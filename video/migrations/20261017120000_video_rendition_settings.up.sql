@@ -0,0 +1,5 @@
+ALTER TYPE rendition ADD VALUE 'VIDEO_QHD' BEFORE 'VIDEO_HD';
+ALTER TYPE rendition ADD VALUE 'VIDEO_FHD' BEFORE 'VIDEO_HD';
+
+-- The encoder settings of the transcoded video renditions, encoded as VideoRenditionSettings protobufs.
+ALTER TABLE transcoding_configs ADD COLUMN video_settings bytes[] NOT NULL DEFAULT ARRAY[];
@@ -0,0 +1,21 @@
+-- Recording thumbnail sprites are sheets of small thumbnails laid out in a grid, generated from the recording.
+-- They are used to display seek previews, and are stored in the s3 bucket that the recording is stored in.
+-- They are scoped to an organization and contain a reference to the recording they were generated from.
+CREATE TABLE recording_thumbnail_sprites (
+    organization_id UUID NOT NULL,
+    recording_id UUID NOT NULL,
+    idx INT4 NOT NULL,
+    id UUID NOT NULL,
+    start_time REAL NOT NULL,
+    end_time REAL NOT NULL,
+    tile_width INT4 NOT NULL,
+    tile_height INT4 NOT NULL,
+    tile_columns INT4 NOT NULL,
+    tile_start_times REAL[] NOT NULL,
+    size_bytes BIGINT NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (organization_id, recording_id, idx)
+);
+
+ALTER TABLE recording_thumbnail_sprites ADD CONSTRAINT recording_thumbnail_sprites_organization_id_fkey FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE;
+ALTER TABLE recording_thumbnail_sprites ADD CONSTRAINT recording_thumbnail_sprites_recording_id_fkey FOREIGN KEY (organization_id, recording_id) REFERENCES recordings(organization_id, id);
@@ -0,0 +1,20 @@
+ALTER TABLE transcoding_configs DROP COLUMN IF EXISTS audio_normalization;
+ALTER TABLE transcoding_configs DROP COLUMN IF EXISTS audio_settings;
+
+-- Enum values can only be dropped once nothing references them, the configs fall back to the source audio.
+UPDATE transcoding_configs SET renditions = array_append(renditions, 'AUDIO_SOURCE')
+    WHERE ('AUDIO_HIGH' = ANY(renditions) OR 'AUDIO_LOW' = ANY(renditions)) AND NOT 'AUDIO_SOURCE' = ANY(renditions);
+UPDATE transcoding_configs SET renditions = array_remove(array_remove(renditions, 'AUDIO_HIGH'), 'AUDIO_LOW')
+    WHERE 'AUDIO_HIGH' = ANY(renditions) OR 'AUDIO_LOW' = ANY(renditions);
+
+UPDATE recording_configs SET renditions = array_append(renditions, 'AUDIO_SOURCE')
+    WHERE ('AUDIO_HIGH' = ANY(renditions) OR 'AUDIO_LOW' = ANY(renditions)) AND NOT 'AUDIO_SOURCE' = ANY(renditions);
+UPDATE recording_configs SET renditions = array_remove(array_remove(renditions, 'AUDIO_HIGH'), 'AUDIO_LOW')
+    WHERE 'AUDIO_HIGH' = ANY(renditions) OR 'AUDIO_LOW' = ANY(renditions);
+
+-- Recorded audio renditions other than the source cannot be mapped, their segments are left in s3.
+DELETE FROM recording_rendition_segments WHERE rendition IN ('AUDIO_HIGH', 'AUDIO_LOW');
+DELETE FROM recording_renditions WHERE rendition IN ('AUDIO_HIGH', 'AUDIO_LOW');
+
+ALTER TYPE rendition DROP VALUE 'AUDIO_LOW';
+ALTER TYPE rendition DROP VALUE 'AUDIO_HIGH';
@@ -0,0 +1,7 @@
+ALTER TYPE rendition ADD VALUE 'AUDIO_HIGH';
+ALTER TYPE rendition ADD VALUE 'AUDIO_LOW';
+
+-- The encoder settings of the transcoded audio renditions, encoded as AudioRenditionSettings protobufs.
+ALTER TABLE transcoding_configs ADD COLUMN audio_settings bytes[] NOT NULL DEFAULT ARRAY[];
+-- The loudness normalization of the transcoded audio renditions, encoded as an AudioNormalization protobuf.
+ALTER TABLE transcoding_configs ADD COLUMN audio_normalization bytes;
@@ -0,0 +1,2 @@
+-- The watermark burned into the transcoded video renditions, encoded as a Watermark protobuf.
+ALTER TABLE transcoding_configs ADD COLUMN watermark bytes;
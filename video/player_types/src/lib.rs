@@ -1,7 +1,12 @@
 mod rendition_playlist;
 mod session_playlist;
 mod session_refresh;
+mod thumbnail_track;
+
+#[cfg(test)]
+mod tests;
 
 pub use rendition_playlist::*;
 pub use session_playlist::*;
 pub use session_refresh::*;
+pub use thumbnail_track::*;
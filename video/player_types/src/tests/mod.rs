@@ -0,0 +1 @@
+mod thumbnail_track;
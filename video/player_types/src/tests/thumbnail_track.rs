@@ -0,0 +1,39 @@
+use crate::{ThumbnailTrack, ThumbnailTrackSprite};
+
+#[test]
+fn test_thumbnail_track_to_vtt() {
+	let track = ThumbnailTrack {
+		sprites: vec![
+			ThumbnailTrackSprite {
+				url: "https://example.com/org/rec/sprites/1.a.jpg".parse().unwrap(),
+				tile_width: 160,
+				tile_height: 90,
+				columns: 2,
+				tile_start_times: vec![0.0, 5.0, 10.0],
+				end_time: 15.0,
+			},
+			ThumbnailTrackSprite {
+				url: "https://example.com/org/rec/sprites/2.b.jpg".parse().unwrap(),
+				tile_width: 160,
+				tile_height: 90,
+				columns: 2,
+				tile_start_times: vec![3720.5],
+				end_time: 3725.5,
+			},
+		],
+	};
+
+	assert_eq!(
+		track.to_vtt(),
+		"WEBVTT\n\
+		\n00:00:00.000 --> 00:00:05.000\nhttps://example.com/org/rec/sprites/1.a.jpg#xywh=0,0,160,90\n\
+		\n00:00:05.000 --> 00:00:10.000\nhttps://example.com/org/rec/sprites/1.a.jpg#xywh=160,0,160,90\n\
+		\n00:00:10.000 --> 00:00:15.000\nhttps://example.com/org/rec/sprites/1.a.jpg#xywh=0,90,160,90\n\
+		\n01:02:00.500 --> 01:02:05.500\nhttps://example.com/org/rec/sprites/2.b.jpg#xywh=0,0,160,90\n"
+	);
+}
+
+#[test]
+fn test_thumbnail_track_empty() {
+	assert_eq!(ThumbnailTrack::default().to_vtt(), "WEBVTT\n");
+}
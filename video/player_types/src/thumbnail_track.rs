@@ -0,0 +1,72 @@
+use url::Url;
+
+/// A WebVTT track of seek preview thumbnails, each cue points at a tile in a
+/// sprite sheet using a `#xywh=` media fragment.
+#[derive(Debug, Clone, Default)]
+pub struct ThumbnailTrack {
+	pub sprites: Vec<ThumbnailTrackSprite>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThumbnailTrackSprite {
+	pub url: Url,
+	pub tile_width: u32,
+	pub tile_height: u32,
+	/// The number of tiles in each row, tiles are laid out left to right, top
+	/// to bottom.
+	pub columns: u32,
+	/// The time each tile starts being shown in seconds.
+	pub tile_start_times: Vec<f64>,
+	/// The time the last tile stops being shown in seconds.
+	pub end_time: f64,
+}
+
+fn vtt_timestamp(time: f64) -> String {
+	let millis = (time.max(0.0) * 1000.0).round() as u64;
+
+	format!(
+		"{:02}:{:02}:{:02}.{:03}",
+		millis / 3_600_000,
+		millis / 60_000 % 60,
+		millis / 1000 % 60,
+		millis % 1000
+	)
+}
+
+impl ThumbnailTrack {
+	pub fn to_vtt(&self) -> String {
+		let mut vtt = String::new();
+
+		vtt.push_str("WEBVTT\n");
+
+		for sprite in &self.sprites {
+			let columns = sprite.columns.max(1);
+
+			for (idx, start_time) in sprite.tile_start_times.iter().copied().enumerate() {
+				// A tile is shown until the next tile on the same sheet starts. We
+				// dont look at the next sheet because there might be a gap between
+				// them if the stream was interrupted.
+				let end_time = sprite.tile_start_times.get(idx + 1).copied().unwrap_or(sprite.end_time);
+
+				if end_time <= start_time {
+					continue;
+				}
+
+				let idx = idx as u32;
+				let x = idx % columns * sprite.tile_width;
+				let y = idx / columns * sprite.tile_height;
+
+				vtt.push_str(&format!(
+					"\n{} --> {}\n{}#xywh={x},{y},{},{}\n",
+					vtt_timestamp(start_time),
+					vtt_timestamp(end_time),
+					sprite.url,
+					sprite.tile_width,
+					sprite.tile_height,
+				));
+			}
+		}
+
+		vtt
+	}
+}
@@ -42,6 +42,24 @@ pub struct TranscoderConfig {
 	/// H264 encoder options
 	#[config(cli(skip), env(skip))]
 	pub h264_encoder_options: HashMap<String, String>,
+
+	/// The encoder to use for h265
+	pub h265_encoder: Option<String>,
+
+	/// H265 encoder options
+	#[config(cli(skip), env(skip))]
+	pub h265_encoder_options: HashMap<String, String>,
+
+	/// The encoder to use for av1
+	pub av1_encoder: Option<String>,
+
+	/// AV1 encoder options
+	#[config(cli(skip), env(skip))]
+	pub av1_encoder_options: HashMap<String, String>,
+
+	/// The font file to draw watermark text with, if not set the default font
+	/// of fontconfig is used
+	pub watermark_font_file: Option<String>,
 }
 
 impl Default for TranscoderConfig {
@@ -59,6 +77,13 @@ impl Default for TranscoderConfig {
 			playlist_segments: 5,
 			h264_encoder: Some("libx264".to_string()),
 			h264_encoder_options: vec![("tune".into(), "zerolatency".into())].into_iter().collect(),
+			h265_encoder: Some("libx265".to_string()),
+			h265_encoder_options: vec![("preset".into(), "veryfast".into()), ("tune".into(), "zerolatency".into())]
+				.into_iter()
+				.collect(),
+			av1_encoder: Some("libsvtav1".to_string()),
+			av1_encoder_options: vec![("preset".into(), "10".into())].into_iter().collect(),
+			watermark_font_file: None,
 		}
 	}
 }
@@ -1,2 +1,5 @@
 mod global;
+mod renditions;
+mod sprite;
 mod transcoder;
+mod watermark;
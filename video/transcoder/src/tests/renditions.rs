@@ -0,0 +1,294 @@
+use pb::scuffle::video::v1::types::{
+	AudioCodec, AudioConfig, AudioRenditionSettings, Rendition, TranscodingConfig, VideoCodec, VideoConfig,
+	VideoRenditionSettings,
+};
+
+use crate::transcoder::job::renditions::determine_output_renditions;
+
+fn video_input(width: i32, height: i32) -> VideoConfig {
+	VideoConfig {
+		rendition: Rendition::VideoSource.into(),
+		bitrate: 7358 * 1024,
+		codec: "avc1.64002a".to_string(),
+		fps: 60,
+		height,
+		width,
+		gop_size: 0,
+	}
+}
+
+fn audio_input() -> AudioConfig {
+	AudioConfig {
+		rendition: Rendition::AudioSource.into(),
+		bitrate: 130 * 1024,
+		codec: "mp4a.40.2".to_string(),
+		channels: 2,
+		sample_rate: 48000,
+	}
+}
+
+fn output(video_configs: &[VideoConfig]) -> Vec<(Rendition, i32, i32, i32, i64, i32, &str)> {
+	video_configs
+		.iter()
+		.map(|c| {
+			(
+				c.rendition(),
+				c.width,
+				c.height,
+				c.fps,
+				c.bitrate,
+				c.gop_size,
+				c.codec.as_str(),
+			)
+		})
+		.collect()
+}
+
+#[test]
+fn test_default_ladder() {
+	let transcoding_config = TranscodingConfig {
+		renditions: vec![
+			Rendition::VideoSource.into(),
+			Rendition::VideoQhd.into(),
+			Rendition::VideoFhd.into(),
+			Rendition::VideoHd.into(),
+			Rendition::VideoSd.into(),
+			Rendition::VideoLd.into(),
+			Rendition::AudioSource.into(),
+		],
+		..Default::default()
+	};
+
+	let (video_configs, audio_configs) =
+		determine_output_renditions(&video_input(3840, 2160), &audio_input(), &transcoding_config);
+
+	assert_eq!(audio_configs, vec![audio_input()]);
+	assert_eq!(
+		output(&video_configs),
+		vec![
+			(Rendition::VideoSource, 3840, 2160, 60, 7358 * 1024, 0, "avc1.64002a"),
+			(Rendition::VideoQhd, 2560, 1440, 60, 9000 * 1024, 120, "avc1.640033"),
+			(Rendition::VideoFhd, 1920, 1080, 60, 6000 * 1024, 120, "avc1.640033"),
+			(Rendition::VideoHd, 1280, 720, 60, 4000 * 1024, 120, "avc1.640033"),
+			(Rendition::VideoSd, 854, 480, 30, 2000 * 1024, 60, "avc1.640033"),
+			(Rendition::VideoLd, 640, 360, 30, 1000 * 1024, 60, "avc1.640033"),
+		]
+	);
+}
+
+#[test]
+fn test_custom_ladder() {
+	let transcoding_config = TranscodingConfig {
+		renditions: vec![
+			Rendition::VideoFhd.into(),
+			Rendition::VideoHd.into(),
+			Rendition::VideoSd.into(),
+			Rendition::AudioSource.into(),
+		],
+		video_settings: vec![
+			VideoRenditionSettings {
+				rendition: Rendition::VideoFhd.into(),
+				max_fps: 30,
+				bitrate: 5_000_000,
+				profile: 77,
+				level: 42,
+				keyframe_interval_ms: 1000,
+				..Default::default()
+			},
+			VideoRenditionSettings {
+				rendition: Rendition::VideoSd.into(),
+				side: 540,
+				..Default::default()
+			},
+			// Settings for renditions which are not in the list are ignored.
+			VideoRenditionSettings {
+				rendition: Rendition::VideoQhd.into(),
+				side: 1440,
+				..Default::default()
+			},
+		],
+		..Default::default()
+	};
+
+	let (video_configs, _) = determine_output_renditions(&video_input(3840, 2160), &audio_input(), &transcoding_config);
+
+	// The 720p rendition would run at 60 fps, but it is rate limited from the
+	// 1080p rendition which runs at 30 fps.
+	assert_eq!(
+		output(&video_configs),
+		vec![
+			(Rendition::VideoFhd, 1920, 1080, 30, 5_000_000, 30, "avc1.4d002a"),
+			(Rendition::VideoHd, 1280, 720, 30, 4000 * 1024, 60, "avc1.640033"),
+			(Rendition::VideoSd, 960, 540, 30, 2000 * 1024, 60, "avc1.640033"),
+		]
+	);
+}
+
+#[test]
+fn test_mixed_codec_ladder() {
+	let transcoding_config = TranscodingConfig {
+		renditions: vec![
+			Rendition::VideoFhd.into(),
+			Rendition::VideoHd.into(),
+			Rendition::VideoSd.into(),
+			Rendition::AudioSource.into(),
+		],
+		video_settings: vec![
+			VideoRenditionSettings {
+				rendition: Rendition::VideoFhd.into(),
+				codec: VideoCodec::Av1.into(),
+				bitrate: 4_000_000,
+				..Default::default()
+			},
+			VideoRenditionSettings {
+				rendition: Rendition::VideoHd.into(),
+				codec: VideoCodec::Hevc.into(),
+				level: 123,
+				..Default::default()
+			},
+		],
+		..Default::default()
+	};
+
+	let (video_configs, _) = determine_output_renditions(&video_input(3840, 2160), &audio_input(), &transcoding_config);
+
+	assert_eq!(
+		output(&video_configs),
+		vec![
+			(
+				Rendition::VideoFhd,
+				1920,
+				1080,
+				60,
+				4_000_000,
+				120,
+				"av01.0.13M.08.0.110.01.01.01.0"
+			),
+			(Rendition::VideoHd, 1280, 720, 60, 4000 * 1024, 120, "hvc1.1.6.L123.90"),
+			(Rendition::VideoSd, 854, 480, 30, 2000 * 1024, 60, "avc1.640033"),
+		]
+	);
+
+	// The transcoder parses the codec back from the config.
+	for config in &video_configs {
+		assert_eq!(
+			config.codec.parse::<mp4::codec::VideoCodec>().unwrap().to_string(),
+			config.codec
+		);
+	}
+}
+
+#[test]
+fn test_ladder_limits() {
+	let transcoding_config = TranscodingConfig {
+		renditions: vec![
+			Rendition::VideoQhd.into(),
+			Rendition::VideoFhd.into(),
+			Rendition::VideoHd.into(),
+			Rendition::VideoSd.into(),
+			Rendition::AudioSource.into(),
+		],
+		..Default::default()
+	};
+
+	// Renditions which are not smaller than the input are skipped.
+	let (video_configs, _) = determine_output_renditions(&video_input(1280, 720), &audio_input(), &transcoding_config);
+	assert_eq!(
+		output(&video_configs),
+		vec![(Rendition::VideoSd, 854, 480, 30, 2000 * 1024, 60, "avc1.640033")]
+	);
+
+	// A 4:1 input would make the 1080p and 720p renditions as expensive as 4k and
+	// 1080p, so only the 480p rendition is transcoded.
+	let (video_configs, _) = determine_output_renditions(&video_input(5760, 1440), &audio_input(), &transcoding_config);
+	assert_eq!(
+		output(&video_configs),
+		vec![(Rendition::VideoSd, 1920, 480, 30, 2000 * 1024, 60, "avc1.640033")]
+	);
+}
+
+#[test]
+fn test_audio_ladder() {
+	let transcoding_config = TranscodingConfig {
+		renditions: vec![
+			Rendition::VideoSource.into(),
+			Rendition::AudioSource.into(),
+			Rendition::AudioHigh.into(),
+			Rendition::AudioLow.into(),
+		],
+		..Default::default()
+	};
+
+	let (_, audio_configs) = determine_output_renditions(&video_input(1920, 1080), &audio_input(), &transcoding_config);
+	assert_eq!(
+		audio_configs,
+		vec![
+			audio_input(),
+			AudioConfig {
+				rendition: Rendition::AudioHigh.into(),
+				codec: "mp4a.40.2".to_string(),
+				bitrate: 160_000,
+				channels: 2,
+				sample_rate: 48000,
+			},
+			AudioConfig {
+				rendition: Rendition::AudioLow.into(),
+				codec: "mp4a.40.2".to_string(),
+				bitrate: 64_000,
+				channels: 2,
+				sample_rate: 48000,
+			},
+		]
+	);
+
+	let transcoding_config = TranscodingConfig {
+		renditions: vec![
+			Rendition::VideoSource.into(),
+			Rendition::AudioHigh.into(),
+			Rendition::AudioLow.into(),
+		],
+		audio_settings: vec![
+			AudioRenditionSettings {
+				rendition: Rendition::AudioHigh.into(),
+				codec: AudioCodec::Opus.into(),
+				..Default::default()
+			},
+			AudioRenditionSettings {
+				rendition: Rendition::AudioLow.into(),
+				bitrate: 32_000,
+				sample_rate: 22050,
+				channels: 1,
+				..Default::default()
+			},
+		],
+		..Default::default()
+	};
+
+	// 5.1 input is downmixed to stereo.
+	let audio_input = AudioConfig {
+		channels: 6,
+		sample_rate: 44100,
+		..audio_input()
+	};
+
+	let (_, audio_configs) = determine_output_renditions(&video_input(1920, 1080), &audio_input, &transcoding_config);
+	assert_eq!(
+		audio_configs,
+		vec![
+			AudioConfig {
+				rendition: Rendition::AudioHigh.into(),
+				codec: "opus".to_string(),
+				bitrate: 128_000,
+				channels: 2,
+				sample_rate: 48000,
+			},
+			AudioConfig {
+				rendition: Rendition::AudioLow.into(),
+				codec: "mp4a.40.2".to_string(),
+				bitrate: 32_000,
+				channels: 1,
+				sample_rate: 22050,
+			},
+		]
+	);
+}
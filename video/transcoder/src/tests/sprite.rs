@@ -0,0 +1,54 @@
+use image::{Rgba, RgbaImage};
+
+use crate::transcoder::job::sprite::{tile_size, SpriteSheet, SPRITE_COLUMNS, SPRITE_ROWS};
+
+fn screenshot(idx: u32) -> RgbaImage {
+	RgbaImage::from_pixel(320, 180, Rgba([idx as u8, 0, 0, 255]))
+}
+
+#[test]
+fn test_tile_size() {
+	assert_eq!(tile_size(320, 180), (160, 90));
+	assert_eq!(tile_size(180, 320), (90, 160));
+	assert_eq!(tile_size(64, 48), (64, 48));
+}
+
+#[test]
+fn test_sprite_sheet() {
+	let tiles = SPRITE_COLUMNS * SPRITE_ROWS;
+
+	let mut sheet = SpriteSheet::new(5.0);
+
+	let sprites = (0..tiles + 3)
+		.map(|idx| sheet.push(&screenshot(idx), idx as f64 * 5.0).unwrap())
+		.collect::<Vec<_>>();
+
+	// Every screenshot emits the sheet it was added to, so the newest tiles have
+	// previews before the sheet is full.
+	let sprite = &sprites[2];
+	assert!(!sprite.complete);
+	assert_eq!(sprite.tile_start_times, vec![0.0, 5.0, 10.0]);
+	assert_eq!(sprite.end_time, 15.0);
+
+	let image = image::load_from_memory(&sprite.data).unwrap();
+	assert_eq!((image.width(), image.height()), (160 * 3, 90));
+
+	let sprite = &sprites[tiles as usize - 1];
+	assert!(sprite.complete, "expected a complete sprite once the sheet is full");
+	assert_eq!((sprite.tile_width, sprite.tile_height), (160, 90));
+	assert_eq!(sprite.columns, SPRITE_COLUMNS);
+	assert_eq!(sprite.tile_start_times.len(), tiles as usize);
+	assert_eq!(sprite.end_time, tiles as f64 * 5.0);
+
+	let image = image::load_from_memory(&sprite.data).unwrap();
+	assert_eq!(image.width(), 160 * SPRITE_COLUMNS);
+	assert_eq!(image.height(), 90 * SPRITE_ROWS);
+
+	assert_eq!(sprites.iter().filter(|s| s.complete).count(), 1);
+
+	// The screenshots after a complete sheet start a new one.
+	let sprite = sprites.last().unwrap();
+	assert!(!sprite.complete);
+	assert_eq!(sprite.tile_start_times, vec![125.0, 130.0, 135.0]);
+	assert_eq!(sprite.end_time, 140.0);
+}
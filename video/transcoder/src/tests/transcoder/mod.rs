@@ -161,6 +161,7 @@ async fn test_transcode() {
 			height: 2160,
 			width: 3840,
 			rendition: Rendition::VideoSource.into(),
+			gop_size: 0,
 		}
 		.encode_to_vec(),
 	)
@@ -226,6 +227,7 @@ async fn test_transcode() {
 					data,
 					video_settings,
 					audio_settings,
+					..
 				} => {
 					video = Some(video_settings);
 					audio = Some(audio_settings);
@@ -698,6 +700,7 @@ async fn test_transcode_reconnect() {
 			height: 3840,
 			width: 2160,
 			rendition: Rendition::VideoSource.into(),
+			gop_size: 0,
 		}
 		.encode_to_vec(),
 	)
@@ -773,6 +776,7 @@ async fn test_transcode_reconnect() {
 					data,
 					audio_settings,
 					video_settings,
+					..
 				} => {
 					audio = Some(audio_settings);
 					video = Some(video_settings);
@@ -940,6 +944,7 @@ async fn test_transcode_reconnect() {
 					data,
 					audio_settings,
 					video_settings,
+					..
 				} => {
 					audio = Some(audio_settings);
 					video = Some(video_settings);
@@ -1115,6 +1120,7 @@ async fn test_transcode_reconnect() {
 					data,
 					audio_settings,
 					video_settings,
+					..
 				} => {
 					audio = Some(audio_settings);
 					video = Some(video_settings);
@@ -1300,6 +1306,7 @@ async fn test_transcode_reconnect() {
 					data,
 					audio_settings,
 					video_settings,
+					..
 				} => {
 					audio = Some(audio_settings);
 					video = Some(video_settings);
@@ -0,0 +1,93 @@
+use ffmpeg::ffi::AVPixelFormat;
+use pb::scuffle::video::v1::types::{watermark, Watermark, WatermarkPosition};
+
+use crate::transcoder::job::ffmpeg::watermark::{escape_option, filter_spec};
+
+fn image() -> watermark::Image {
+	watermark::Image {
+		s3_bucket_id: None,
+		key: "watermark.png".to_string(),
+		position: WatermarkPosition::BottomRight.into(),
+		scale: 0.0,
+		opacity: 0.0,
+		margin: 0.0,
+	}
+}
+
+fn text(text: &str) -> watermark::Text {
+	watermark::Text {
+		text: text.to_string(),
+		timestamp: false,
+		position: WatermarkPosition::TopLeft.into(),
+		size: 0.0,
+		color: String::new(),
+		opacity: 0.0,
+		background: false,
+		margin: 0.0,
+	}
+}
+
+#[test]
+fn test_escape_option() {
+	assert_eq!(escape_option("LIVE"), "LIVE");
+	assert_eq!(escape_option("it's [a], b; c:d"), r"it\\\'s \[a\]\, b\; c\\:d");
+	assert_eq!(escape_option(r"a\b"), r"a\\\\b");
+}
+
+#[test]
+fn test_filter_spec_image_and_text() {
+	let watermark = Watermark {
+		image: Some(image()),
+		text: Some(text("LIVE")),
+	};
+
+	assert_eq!(
+		filter_spec(&watermark, true, 1280, 720, AVPixelFormat::AV_PIX_FMT_YUV420P, None),
+		"[image]format=rgba,scale=w=128:h=-1,colorchannelmixer=aa=1[watermark];[in][watermark]overlay=x=W-w-26:y=H-h-26,\
+		 drawtext=text=LIVE:fontsize=36:fontcolor=0xFFFFFF@1:x=26:y=26,format=pix_fmts=0[out]"
+	);
+}
+
+#[test]
+fn test_filter_spec_missing_image() {
+	let watermark = Watermark {
+		image: Some(image()),
+		text: Some(watermark::Text {
+			position: WatermarkPosition::Center.into(),
+			color: "#ff0000".to_string(),
+			opacity: 0.5,
+			background: true,
+			..text("LIVE")
+		}),
+	};
+
+	// The image could not be loaded, only the text is drawn.
+	assert_eq!(
+		filter_spec(
+			&watermark,
+			false,
+			1280,
+			720,
+			AVPixelFormat::AV_PIX_FMT_YUV420P,
+			Some("/fonts/a.ttf")
+		),
+		"[in]drawtext=fontfile=/fonts/a.ttf:text=LIVE:fontsize=36:fontcolor=0xff0000@0.5:x=(w-tw)/2:y=(h-th)/2:box=1:\
+		 boxcolor=black@0.5:boxborderw=9,format=pix_fmts=0[out]"
+	);
+}
+
+#[test]
+fn test_filter_spec_timestamp() {
+	let watermark = Watermark {
+		image: None,
+		text: Some(watermark::Text {
+			timestamp: true,
+			..text("100%")
+		}),
+	};
+
+	assert_eq!(
+		filter_spec(&watermark, false, 640, 360, AVPixelFormat::AV_PIX_FMT_YUV420P, None),
+		r"[in]drawtext=text=100\\\\% %{gmtime\\:%Y-%m-%d %H\\\\\\:%M\\\\\\:%S}:fontsize=18:fontcolor=0xFFFFFF@1:x=13:y=13,format=pix_fmts=0[out]"
+	);
+}
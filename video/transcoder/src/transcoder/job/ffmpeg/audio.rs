@@ -3,48 +3,177 @@ use ffmpeg::codec::EncoderCodec;
 use ffmpeg::dict::Dictionary;
 use ffmpeg::encoder::{AudioEncoderSettings, MuxerEncoder, MuxerSettings};
 use ffmpeg::error::FfmpegError;
-use ffmpeg::ffi::{AVCodecID, AVPictureType};
+use ffmpeg::ffi::{AVCodecID, AVPictureType, AVRational, AVSampleFormat, AV_CH_LAYOUT_MONO, AV_CH_LAYOUT_STEREO};
+use ffmpeg::filter_graph::{Filter, FilterGraph};
 use ffmpeg::io::channel::ChannelCompatSend;
 use ffmpeg::io::OutputOptions;
 use ffmpeg::packet::Packet;
 use mp4::codec::AudioCodec;
-use pb::scuffle::video::v1::types::AudioConfig;
+use pb::scuffle::video::v1::types::{AudioConfig, AudioNormalization};
 use tokio::sync::mpsc;
 
-use super::{muxer_options, Transcoder};
+use super::{muxer_options, AudioDecoder, Encoder, Transcoder};
 
-pub fn codec_options(codec: AudioCodec) -> anyhow::Result<(EncoderCodec, Dictionary)> {
+/// The EBU R128 targets, used when the transcoding config leaves a field unset.
+/// EBU R 128 - 2
+const DEFAULT_INTEGRATED_LOUDNESS: f64 = -23.0;
+const DEFAULT_TRUE_PEAK: f64 = -1.0;
+const DEFAULT_LOUDNESS_RANGE: f64 = 7.0;
+
+/// The encoder of an audio rendition, along with the sample format and the
+/// number of samples per frame it expects.
+pub struct AudioEncoderOptions {
+	pub codec: EncoderCodec,
+	pub options: Dictionary,
+	pub sample_fmt: AVSampleFormat,
+	pub frame_size: i32,
+}
+
+pub fn codec_options(codec: AudioCodec, sample_rate: i32) -> anyhow::Result<AudioEncoderOptions> {
 	Ok(match codec {
 		AudioCodec::Aac { object_type } => {
-			let codec = ffmpeg::codec::EncoderCodec::by_name("libfdk_aac")
-				.or_else(|| ffmpeg::codec::EncoderCodec::new(AVCodecID::AV_CODEC_ID_AAC))
-				.ok_or(FfmpegError::NoEncoder)
-				.context("failed to find aac encoder")?;
+			let (codec, sample_fmt) = match ffmpeg::codec::EncoderCodec::by_name("libfdk_aac") {
+				Some(codec) => (codec, AVSampleFormat::AV_SAMPLE_FMT_S16),
+				None => (
+					ffmpeg::codec::EncoderCodec::new(AVCodecID::AV_CODEC_ID_AAC)
+						.ok_or(FfmpegError::NoEncoder)
+						.context("failed to find aac encoder")?,
+					AVSampleFormat::AV_SAMPLE_FMT_FLTP,
+				),
+			};
 
-			(
+			AudioEncoderOptions {
 				codec,
-				Dictionary::builder()
+				options: Dictionary::builder()
 					.set(
 						"profile",
 						match object_type {
 							aac::AudioObjectType::AacLowComplexity => "aac_low",
 							aac::AudioObjectType::AacMain => "aac_main",
-							aac::AudioObjectType::Unknown(profile) => {
-								anyhow::bail!("invalid aac profile: {profile}");
+							aac::AudioObjectType::SpectralBandReplication => "aac_he",
+							aac::AudioObjectType::ParametricStereo => "aac_he_v2",
+							profile => {
+								anyhow::bail!("invalid aac profile: {}", u16::from(profile));
 							}
 						},
 					)
 					.build(),
-			)
+				sample_fmt,
+				// HE-AAC doubles the frame size of the core codec.
+				// ISO/IEC 14496-3 - 4.5.1.1
+				frame_size: match object_type {
+					aac::AudioObjectType::SpectralBandReplication | aac::AudioObjectType::ParametricStereo => 2048,
+					_ => 1024,
+				},
+			}
 		}
 		AudioCodec::Opus => {
-			let codec = ffmpeg::codec::EncoderCodec::by_name("libopus")
-				.or_else(|| ffmpeg::codec::EncoderCodec::new(AVCodecID::AV_CODEC_ID_OPUS))
-				.ok_or(FfmpegError::NoEncoder)
-				.context("failed to find opus encoder")?;
+			let (codec, sample_fmt) = match ffmpeg::codec::EncoderCodec::by_name("libopus") {
+				Some(codec) => (codec, AVSampleFormat::AV_SAMPLE_FMT_S16),
+				None => (
+					ffmpeg::codec::EncoderCodec::new(AVCodecID::AV_CODEC_ID_OPUS)
+						.ok_or(FfmpegError::NoEncoder)
+						.context("failed to find opus encoder")?,
+					AVSampleFormat::AV_SAMPLE_FMT_FLTP,
+				),
+			};
 
-			(codec, Dictionary::new())
+			AudioEncoderOptions {
+				codec,
+				options: Dictionary::new(),
+				sample_fmt,
+				// The encoders default to 20ms frames.
+				frame_size: sample_rate / 50,
+			}
 		}
+		AudioCodec::Mp3 => anyhow::bail!("mp3 is not supported as an output codec"),
+	})
+}
+
+/// Builds the filter graph the frames of an audio rendition go through before
+/// they are encoded. It normalizes the loudness, downmixes and resamples the
+/// decoded audio to what the encoder expects, and cuts it into frames of the
+/// size the encoder takes.
+fn audio_filter_graph(
+	decoder: &AudioDecoder,
+	audio_config: &AudioConfig,
+	encoder_options: &AudioEncoderOptions,
+	channel_layout: &str,
+	normalization: Option<&AudioNormalization>,
+) -> anyhow::Result<FilterGraph> {
+	let time_base = decoder.time_base();
+
+	// The channel layout is not always known, in that case the default layout
+	// of the channel count is used.
+	let input_channel_layout = match decoder.channel_layout() {
+		0 => format!("{}c", decoder.channels()),
+		layout => format!("0x{layout:x}"),
+	};
+
+	let mut graph = FilterGraph::new().context("failed to create filter graph")?;
+
+	graph
+		.add(
+			Filter::get("abuffer").ok_or(FfmpegError::Arguments("missing abuffer filter"))?,
+			"in",
+			&format!(
+				"time_base={}/{}:sample_rate={}:sample_fmt={}:channel_layout={input_channel_layout}",
+				time_base.num,
+				time_base.den,
+				decoder.sample_rate(),
+				decoder.sample_format() as i32,
+			),
+		)
+		.context("failed to add abuffer filter")?;
+
+	graph
+		.add(
+			Filter::get("abuffersink").ok_or(FfmpegError::Arguments("missing abuffersink filter"))?,
+			"out",
+			"",
+		)
+		.context("failed to add abuffersink filter")?;
+
+	let mut filters = Vec::new();
+
+	if let Some(normalization) = normalization {
+		// Single pass loudnorm works in dynamic mode and outputs 192kHz, the
+		// aresample after it brings it back down.
+		filters.push(format!(
+			"loudnorm=I={}:TP={}:LRA={}",
+			normalization.integrated_loudness.unwrap_or(DEFAULT_INTEGRATED_LOUDNESS),
+			normalization.true_peak.unwrap_or(DEFAULT_TRUE_PEAK),
+			normalization.loudness_range.unwrap_or(DEFAULT_LOUDNESS_RANGE),
+		));
+	}
+
+	filters.push(format!("aresample={}", audio_config.sample_rate));
+	filters.push(format!(
+		"aformat=sample_fmts={}:channel_layouts={channel_layout}:sample_rates={}",
+		sample_fmt_name(encoder_options.sample_fmt)?,
+		audio_config.sample_rate,
+	));
+	filters.push(format!("asetnsamples=n={}:p=1", encoder_options.frame_size));
+
+	graph
+		.input("out", 0)
+		.context("failed to set filter graph input")?
+		.output("in", 0)
+		.context("failed to set filter graph output")?
+		.parse(&filters.join(","))
+		.context("failed to parse filter graph")?;
+
+	graph.validate().context("failed to validate filter graph")?;
+
+	Ok(graph)
+}
+
+fn sample_fmt_name(sample_fmt: AVSampleFormat) -> anyhow::Result<&'static str> {
+	Ok(match sample_fmt {
+		AVSampleFormat::AV_SAMPLE_FMT_S16 => "s16",
+		AVSampleFormat::AV_SAMPLE_FMT_FLT => "flt",
+		AVSampleFormat::AV_SAMPLE_FMT_FLTP => "fltp",
+		sample_fmt => anyhow::bail!("unsupported sample format: {sample_fmt:?}"),
 	})
 }
 
@@ -53,9 +182,19 @@ impl Transcoder {
 		&mut self,
 		sender: mpsc::Sender<Vec<u8>>,
 		audio_config: &AudioConfig,
-		encoder_codec: EncoderCodec,
-		encoder_options: Dictionary,
+		encoder_options: AudioEncoderOptions,
+		normalization: Option<&AudioNormalization>,
 	) -> anyhow::Result<()> {
+		let (channel_layout, channel_layout_name) = match audio_config.channels {
+			1 => (AV_CH_LAYOUT_MONO, "mono"),
+			2 => (AV_CH_LAYOUT_STEREO, "stereo"),
+			channels => anyhow::bail!("unsupported channel count: {channels}"),
+		};
+
+		let decoder = self.audio_decoder.as_ref().unwrap();
+
+		let filter_graph = audio_filter_graph(decoder, audio_config, &encoder_options, channel_layout_name, normalization)?;
+
 		let output = ffmpeg::io::Output::new(
 			sender.into_compat(),
 			OutputOptions {
@@ -65,28 +204,36 @@ impl Transcoder {
 		)
 		.context("failed to create output")?;
 
+		// The frames come out of the filter graph in the time base of the output
+		// sample rate.
+		let time_base = AVRational {
+			num: 1,
+			den: audio_config.sample_rate,
+		};
+
 		self.audio_encoders.push(MuxerEncoder::new(
-			encoder_codec,
+			encoder_options.codec,
 			output,
-			self.audio_decoder.as_ref().unwrap().time_base(),
-			self.audio_decoder.as_ref().unwrap().time_base(),
+			time_base,
+			time_base,
 			AudioEncoderSettings::builder(
 				audio_config.sample_rate,
-				self.audio_decoder.as_ref().unwrap().channel_layout(),
-				self.audio_decoder.as_ref().unwrap().channels(),
-				self.audio_decoder.as_ref().unwrap().sample_format(),
+				channel_layout,
+				audio_config.channels,
+				encoder_options.sample_fmt,
 			)
 			.bitrate(audio_config.bitrate)
 			.rc_max_rate(audio_config.bitrate)
 			.rc_buffer_size(audio_config.bitrate as i32 * 2)
 			.thread_count(1)
-			.codec_specific_options(encoder_options)
+			.codec_specific_options(encoder_options.options)
 			.build(),
 			MuxerSettings::builder()
 				.interleave(true)
 				.muxer_options(muxer_options())
 				.build(),
 		)?);
+		self.audio_filters.push(filter_graph);
 
 		Ok(())
 	}
@@ -118,7 +265,16 @@ impl Transcoder {
 
 		self.handle_audio_decoder()?;
 
-		for encoder in self.audio_encoders.iter_mut() {
+		for (filter_graph, encoder) in self.audio_filters.iter_mut().zip(self.audio_encoders.iter_mut()) {
+			filter_graph
+				.get("in")
+				.ok_or(FfmpegError::Arguments("missing filter graph input"))?
+				.source()
+				.send_eof(None)
+				.context("filter graph eof")?;
+
+			handle_audio_filter_graph(filter_graph, encoder)?;
+
 			encoder.send_eof().context("encoder eof")?;
 		}
 
@@ -132,8 +288,15 @@ impl Transcoder {
 				let frame_timestamp = frame.best_effort_timestamp();
 				frame.set_pts(frame_timestamp);
 
-				for encoder in self.audio_encoders.iter_mut() {
-					encoder.send_frame(&frame).context("encoder")?;
+				for (filter_graph, encoder) in self.audio_filters.iter_mut().zip(self.audio_encoders.iter_mut()) {
+					filter_graph
+						.get("in")
+						.ok_or(FfmpegError::Arguments("missing filter graph input"))?
+						.source()
+						.send_frame(&frame)
+						.context("filter graph")?;
+
+					handle_audio_filter_graph(filter_graph, encoder)?;
 				}
 			}
 		}
@@ -141,3 +304,16 @@ impl Transcoder {
 		Ok(())
 	}
 }
+
+fn handle_audio_filter_graph(filter_graph: &mut FilterGraph, encoder: &mut Encoder) -> anyhow::Result<()> {
+	let mut sink = filter_graph
+		.get("out")
+		.ok_or(FfmpegError::Arguments("missing filter graph output"))?
+		.sink();
+
+	while let Some(frame) = sink.receive_frame().context("filter graph receive frame")? {
+		encoder.send_frame(&frame).context("encoder")?;
+	}
+
+	Ok(())
+}
@@ -7,12 +7,13 @@ use bytes::Bytes;
 use ffmpeg::decoder::Decoder;
 use ffmpeg::dict::Dictionary;
 use ffmpeg::error::FfmpegError;
+use ffmpeg::filter_graph::FilterGraph;
 use ffmpeg::ffi::{AVMediaType, AVPixelFormat};
 use ffmpeg::frame::Frame;
 use ffmpeg::io::channel::{ChannelCompatRecv as _, ChannelCompatSend as _};
 use ffmpeg::io::OutputOptions;
 use ffmpeg::log::LogLevel;
-use pb::scuffle::video::v1::types::{AudioConfig, VideoConfig};
+use pb::scuffle::video::v1::types::{AudioConfig, TranscodingConfig, VideoConfig};
 use tokio::sync::mpsc;
 use video_common::database::Rendition;
 
@@ -20,6 +21,7 @@ use crate::global::TranscoderGlobal;
 
 mod audio;
 mod video;
+pub(crate) mod watermark;
 
 const MP4_FLAGS: &str = "frag_keyframe+frag_every_frame+empty_moov+delay_moov+default_base_moof";
 
@@ -73,6 +75,8 @@ pub struct Transcoder {
 	video_scalars: Vec<Scalar>,
 	frame_limiters: Vec<Limiter>,
 	video_encoders: Vec<Encoder>,
+	video_watermarks: Vec<Option<FilterGraph>>,
+	audio_filters: Vec<FilterGraph>,
 	audio_encoders: Vec<Encoder>,
 	last_screenshot: Instant,
 	screenshot_interval: Duration,
@@ -81,6 +85,7 @@ pub struct Transcoder {
 }
 
 impl Transcoder {
+	#[allow(clippy::too_many_arguments)]
 	pub fn new(
 		global: &Arc<impl TranscoderGlobal>,
 		input: mpsc::Receiver<Bytes>,
@@ -88,6 +93,8 @@ impl Transcoder {
 		mut outputs: HashMap<Rendition, mpsc::Sender<Vec<u8>>>,
 		mut video_configs: Vec<VideoConfig>,
 		mut audio_outputs: Vec<AudioConfig>,
+		transcoding_config: TranscodingConfig,
+		watermark_image: Option<Bytes>,
 	) -> anyhow::Result<Self> {
 		SETUP_LOGGING.call_once(|| {
 			ffmpeg::log::set_log_level(LogLevel::Trace);
@@ -138,6 +145,8 @@ impl Transcoder {
 			video_scalars: Vec::new(),
 			frame_limiters: Vec::new(),
 			video_encoders: Vec::new(),
+			video_watermarks: Vec::new(),
+			audio_filters: Vec::new(),
 			audio_encoders: Vec::new(),
 			screenshot_output,
 			screenshot_scalar,
@@ -202,6 +211,24 @@ impl Transcoder {
 
 		let config = global.config();
 
+		// The watermark is only drawn on the transcoded renditions, the source
+		// rendition is copied as is.
+		let watermark_image = match watermark_image {
+			Some(data) => match watermark::decode_image(data) {
+				Ok(image) => Some(image),
+				Err(err) => {
+					tracing::warn!("failed to decode watermark image: {:#}", err);
+					None
+				}
+			},
+			None => None,
+		};
+
+		let watermark = transcoding_config
+			.watermark
+			.as_ref()
+			.filter(|w| w.text.is_some() || (w.image.is_some() && watermark_image.is_some()));
+
 		if !video_configs.is_empty() {
 			for video_config in video_configs {
 				let codec = video_config
@@ -211,10 +238,32 @@ impl Transcoder {
 
 				let (encoder_codec, encoder_options) = video::codec_options(config, codec)?;
 
+				let watermark = watermark
+					.map(|watermark| {
+						watermark::watermark_filter_graph(
+							watermark,
+							watermark_image.as_ref(),
+							video_config.width,
+							video_config.height,
+							this.video_decoder.pixel_format(),
+							this.video_decoder.time_base(),
+							config.watermark_font_file.as_deref(),
+						)
+					})
+					.transpose()
+					.context("failed to create watermark")?;
+
 				let sender = outputs
 					.remove(&Rendition::from(video_config.rendition()))
 					.ok_or_else(|| anyhow::anyhow!("missing video output"))?;
-				this.setup_video_encoder(sender, &video_config, encoder_codec, encoder_options)?;
+				this.setup_video_encoder(
+					sender,
+					&video_config,
+					encoder_codec,
+					encoder_options,
+					video::codec_tag(codec),
+					watermark,
+				)?;
 			}
 		}
 
@@ -239,12 +288,17 @@ impl Transcoder {
 					.parse()
 					.map_err(|err| anyhow::anyhow!("failed to parse audio codec: {err}"))?;
 
-				let (encoder_codec, encoder_options) = audio::codec_options(codec)?;
+				let encoder_options = audio::codec_options(codec, audio_config.sample_rate)?;
 
 				let sender = outputs
 					.remove(&Rendition::from(audio_config.rendition()))
 					.ok_or_else(|| anyhow::anyhow!("missing audio output"))?;
-				this.setup_audio_encoder(sender, &audio_config, encoder_codec, encoder_options)?;
+				this.setup_audio_encoder(
+					sender,
+					&audio_config,
+					encoder_options,
+					transcoding_config.audio_normalization.as_ref(),
+				)?;
 			}
 		}
 
@@ -4,15 +4,44 @@ use ffmpeg::dict::Dictionary;
 use ffmpeg::encoder::{MuxerEncoder, MuxerSettings, VideoEncoderSettings};
 use ffmpeg::error::FfmpegError;
 use ffmpeg::ffi::{AVCodecID, AVPictureType, AVRational};
+use ffmpeg::filter_graph::FilterGraph;
 use ffmpeg::io::channel::ChannelCompatSend;
 use ffmpeg::io::OutputOptions;
-use mp4::codec::VideoCodec;
+use mp4::codec::{HevcSampleEntry, VideoCodec};
 use pb::scuffle::video::v1::types::VideoConfig;
 use tokio::sync::mpsc;
 
-use super::{muxer_options, Limiter, Scalar, Transcoder};
+use super::{muxer_options, Encoder, Limiter, Scalar, Transcoder};
 use crate::config::TranscoderConfig;
 
+fn find_encoder(name: Option<&String>, codec_id: AVCodecID) -> Option<EncoderCodec> {
+	name.map(|name| ffmpeg::codec::EncoderCodec::by_name(name))
+		.unwrap_or_else(|| ffmpeg::codec::EncoderCodec::new(codec_id))
+}
+
+/// Adds `key=value` pairs to an option which takes a `:` separated list, such
+/// as `x265-params`, keeping the pairs from the config.
+fn append_params(options: &mut Dictionary, key: &str, params: &str) -> anyhow::Result<()> {
+	let params = match options.get(key) {
+		Some(existing) if !existing.is_empty() => format!("{existing}:{params}"),
+		_ => params.to_string(),
+	};
+
+	options.set(key, &params).with_context(|| format!("failed to set {key}"))
+}
+
+/// The mp4 muxer writes HEVC with the hev1 sample entry unless it is told
+/// otherwise.
+pub fn codec_tag(codec: VideoCodec) -> Option<[u8; 4]> {
+	match codec {
+		VideoCodec::Hevc {
+			sample_entry: HevcSampleEntry::Hvc1,
+			..
+		} => Some(*b"hvc1"),
+		_ => None,
+	}
+}
+
 pub fn codec_options(config: &TranscoderConfig, codec: VideoCodec) -> anyhow::Result<(EncoderCodec, Dictionary)> {
 	match codec {
 		VideoCodec::Avc { level, profile, .. } => {
@@ -56,21 +85,98 @@ pub fn codec_options(config: &TranscoderConfig, codec: VideoCodec) -> anyhow::Re
 				.context("failed to set h264 level")?;
 
 			Ok((
-				config
-					.h264_encoder
-					.as_ref()
-					.map(|name| ffmpeg::codec::EncoderCodec::by_name(name))
-					.unwrap_or_else(|| ffmpeg::codec::EncoderCodec::new(AVCodecID::AV_CODEC_ID_H264))
+				find_encoder(config.h264_encoder.as_ref(), AVCodecID::AV_CODEC_ID_H264)
 					.ok_or(FfmpegError::NoEncoder)
 					.context("failed to find h264 encoder")?,
 				options,
 			))
 		}
-		VideoCodec::Av1 { .. } => {
-			anyhow::bail!("av1 encoding is not supported");
+		VideoCodec::Hevc {
+			profile, level, tier, ..
+		} => {
+			let mut options = Dictionary::from(config.h265_encoder_options.clone());
+
+			options
+				.set(
+					"profile",
+					match profile {
+						1 => "main",
+						_ => {
+							anyhow::bail!("invalid h265 profile: {profile}");
+						}
+					},
+				)
+				.context("failed to set h265 profile")?;
+
+			let level = match level {
+				90 => "3.0",
+				93 => "3.1",
+				120 => "4.0",
+				123 => "4.1",
+				150 => "5.0",
+				153 => "5.1",
+				156 => "5.2",
+				180 => "6.0",
+				183 => "6.1",
+				186 => "6.2",
+				_ => {
+					anyhow::bail!("invalid hevc level: {level}");
+				}
+			};
+
+			options.set("level", level).context("failed to set h265 level")?;
+
+			// libx265 does not read the level from the generic option, and without
+			// the tier it may signal the high tier.
+			append_params(
+				&mut options,
+				"x265-params",
+				&format!("level-idc={level}:high-tier={}", if tier { 1 } else { 0 }),
+			)?;
+
+			Ok((
+				find_encoder(config.h265_encoder.as_ref(), AVCodecID::AV_CODEC_ID_HEVC)
+					.ok_or(FfmpegError::NoEncoder)
+					.context("failed to find h265 encoder")?,
+				options,
+			))
 		}
-		VideoCodec::Hevc { .. } => {
-			anyhow::bail!("hevc encoding is not supported");
+		VideoCodec::Av1 { profile, level, .. } => {
+			let mut options = Dictionary::from(config.av1_encoder_options.clone());
+
+			// The main profile is the default of every encoder for 4:2:0 input.
+			if profile != 0 {
+				anyhow::bail!("invalid av1 profile: {profile}");
+			}
+
+			let level = match level {
+				4 => "3.0",
+				5 => "3.1",
+				8 => "4.0",
+				9 => "4.1",
+				12 => "5.0",
+				13 => "5.1",
+				14 => "5.2",
+				15 => "5.3",
+				16 => "6.0",
+				17 => "6.1",
+				18 => "6.2",
+				19 => "6.3",
+				_ => {
+					anyhow::bail!("invalid av1 level: {level}");
+				}
+			};
+
+			// libsvtav1 only takes the level through its own parameters, the other
+			// encoders pick the level from the resolution and frame rate.
+			append_params(&mut options, "svtav1-params", &format!("level={level}"))?;
+
+			Ok((
+				find_encoder(config.av1_encoder.as_ref(), AVCodecID::AV_CODEC_ID_AV1)
+					.ok_or(FfmpegError::NoEncoder)
+					.context("failed to find av1 encoder")?,
+				options,
+			))
 		}
 	}
 }
@@ -82,6 +188,8 @@ impl Transcoder {
 		video_config: &VideoConfig,
 		encoder_codec: EncoderCodec,
 		encoder_options: Dictionary,
+		codec_tag: Option<[u8; 4]>,
+		watermark: Option<FilterGraph>,
 	) -> anyhow::Result<()> {
 		let output = ffmpeg::io::Output::new(
 			sender.into_compat(),
@@ -113,6 +221,32 @@ impl Transcoder {
 			self.video_decoder.pixel_format(),
 		)?);
 
+		// Configs from before the gop size was part of the config use a keyframe every
+		// two seconds.
+		let gop_size = if video_config.gop_size > 0 {
+			video_config.gop_size
+		} else {
+			video_config.fps * 2
+		};
+
+		let mut settings = VideoEncoderSettings::builder(
+			video_config.width,
+			video_config.height,
+			video_config.fps,
+			self.video_decoder.pixel_format(),
+		)
+		.bitrate(video_config.bitrate)
+		.rc_max_rate(video_config.bitrate)
+		.rc_buffer_size(video_config.bitrate as i32 * 2)
+		.gop_size(gop_size)
+		.max_b_frames(0)
+		.thread_count(0)
+		.codec_specific_options(encoder_options);
+
+		if let Some(codec_tag) = codec_tag {
+			settings = settings.codec_tag(codec_tag);
+		}
+
 		self.video_encoders.push(MuxerEncoder::new(
 			encoder_codec,
 			output,
@@ -121,25 +255,13 @@ impl Transcoder {
 				num: 1,
 				den: 1000 * video_config.fps,
 			},
-			VideoEncoderSettings::builder(
-				video_config.width,
-				video_config.height,
-				video_config.fps,
-				self.video_decoder.pixel_format(),
-			)
-			.bitrate(video_config.bitrate)
-			.rc_max_rate(video_config.bitrate)
-			.rc_buffer_size(video_config.bitrate as i32 * 2)
-			.gop_size(video_config.fps * 2)
-			.max_b_frames(0)
-			.thread_count(0)
-			.codec_specific_options(encoder_options)
-			.build(),
+			settings.build(),
 			MuxerSettings::builder()
 				.interleave(true)
 				.muxer_options(muxer_options())
 				.build(),
 		)?);
+		self.video_watermarks.push(watermark);
 
 		Ok(())
 	}
@@ -168,7 +290,18 @@ impl Transcoder {
 
 		self.handle_video_decoder().context("decoder")?;
 
-		for encoder in self.video_encoders.iter_mut() {
+		for (encoder, watermark) in self.video_encoders.iter_mut().zip(self.video_watermarks.iter_mut()) {
+			if let Some(watermark) = watermark {
+				watermark
+					.get("in")
+					.ok_or(FfmpegError::Arguments("missing watermark input"))?
+					.source()
+					.send_eof(None)
+					.context("watermark eof")?;
+
+				handle_watermark(watermark, encoder)?;
+			}
+
 			encoder.send_eof().context("encoder eof")?;
 		}
 
@@ -198,11 +331,41 @@ impl Transcoder {
 				frames.push(scalar.process(frames.last().copied().unwrap_or(&frame.0)).context("scalar")?);
 			}
 
-			for (encoder, frame) in self.video_encoders.iter_mut().zip(frames) {
-				encoder.send_frame(frame).context("encoder")?;
+			for ((encoder, watermark), frame) in self
+				.video_encoders
+				.iter_mut()
+				.zip(self.video_watermarks.iter_mut())
+				.zip(frames)
+			{
+				match watermark {
+					Some(watermark) => {
+						watermark
+							.get("in")
+							.ok_or(FfmpegError::Arguments("missing watermark input"))?
+							.source()
+							.send_frame(frame)
+							.context("watermark")?;
+
+						handle_watermark(watermark, encoder)?;
+					}
+					None => encoder.send_frame(frame).context("encoder")?,
+				}
 			}
 		}
 
 		Ok(())
 	}
 }
+
+fn handle_watermark(watermark: &mut FilterGraph, encoder: &mut Encoder) -> anyhow::Result<()> {
+	let mut sink = watermark
+		.get("out")
+		.ok_or(FfmpegError::Arguments("missing watermark output"))?
+		.sink();
+
+	while let Some(frame) = sink.receive_frame().context("watermark receive frame")? {
+		encoder.send_frame(&frame).context("encoder")?;
+	}
+
+	Ok(())
+}
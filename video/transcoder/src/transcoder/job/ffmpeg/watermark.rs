@@ -0,0 +1,284 @@
+use anyhow::Context;
+use bytes::Bytes;
+use ffmpeg::decoder::Decoder;
+use ffmpeg::error::FfmpegError;
+use ffmpeg::ffi::{AVMediaType, AVPixelFormat, AVRational};
+use ffmpeg::filter_graph::{Filter, FilterGraph};
+use ffmpeg::frame::VideoFrame;
+use pb::scuffle::video::v1::types::{watermark, Watermark, WatermarkPosition};
+
+const DEFAULT_SCALE: f64 = 0.1;
+const DEFAULT_OPACITY: f64 = 1.0;
+const DEFAULT_MARGIN: f64 = 0.02;
+const DEFAULT_TEXT_SIZE: f64 = 0.05;
+const DEFAULT_TEXT_COLOR: &str = "#FFFFFF";
+const TEXT_BACKGROUND_COLOR: &str = "black@0.5";
+
+/// The format of the time appended to the text, with the `:` escaped for the
+/// drawtext expansion.
+const TIMESTAMP_FORMAT: &str = "%{gmtime:%Y-%m-%d %H\\:%M\\:%S}";
+
+fn or_default(value: f64, default: f64) -> f64 {
+	if value == 0.0 { default } else { value }
+}
+
+/// Escapes `value` so that it survives one level of ffmpeg's parsing, every
+/// level treats `\` as the escape character.
+/// https://ffmpeg.org/ffmpeg-filters.html#Notes-on-filtergraph-escaping
+fn escape(value: &str, special: &[char]) -> String {
+	let mut escaped = String::with_capacity(value.len());
+
+	for c in value.chars() {
+		if c == '\\' || special.contains(&c) {
+			escaped.push('\\');
+		}
+
+		escaped.push(c);
+	}
+
+	escaped
+}
+
+/// Escapes the value of a filter option, first for the option list of the
+/// filter and then for the filter graph description.
+pub fn escape_option(value: &str) -> String {
+	escape(&escape(value, &['\'', ':']), &['\'', '[', ']', ',', ';'])
+}
+
+/// The position of the overlay, as `x` and `y` expressions. `video` and
+/// `overlay` are the names of the variables the filter uses for the width and
+/// height of the video and of the overlay.
+fn position(position: WatermarkPosition, margin: i32, video: (&str, &str), overlay: (&str, &str)) -> (String, String) {
+	let ((video_width, video_height), (width, height)) = (video, overlay);
+
+	let left = margin.to_string();
+	let top = margin.to_string();
+	let right = format!("{video_width}-{width}-{margin}");
+	let bottom = format!("{video_height}-{height}-{margin}");
+
+	match position {
+		WatermarkPosition::BottomRight => (right, bottom),
+		WatermarkPosition::BottomLeft => (left, bottom),
+		WatermarkPosition::TopRight => (right, top),
+		WatermarkPosition::TopLeft => (left, top),
+		WatermarkPosition::Center => (format!("({video_width}-{width})/2"), format!("({video_height}-{height})/2")),
+	}
+}
+
+fn margin(margin: f64, width: i32) -> i32 {
+	(or_default(margin, DEFAULT_MARGIN) * width as f64).round() as i32
+}
+
+fn image_filters(image: &watermark::Image, width: i32) -> (String, String) {
+	let scaled_width = ((or_default(image.scale, DEFAULT_SCALE) * width as f64).round() as i32).max(1);
+
+	let (x, y) = position(image.position(), margin(image.margin, width), ("W", "H"), ("w", "h"));
+
+	(
+		format!(
+			"format=rgba,scale=w={scaled_width}:h=-1,colorchannelmixer=aa={}",
+			or_default(image.opacity, DEFAULT_OPACITY)
+		),
+		format!("overlay=x={x}:y={y}"),
+	)
+}
+
+fn text_filter(text: &watermark::Text, width: i32, height: i32, font_file: Option<&str>) -> String {
+	let font_size = ((or_default(text.size, DEFAULT_TEXT_SIZE) * height as f64).round() as i32).max(1);
+
+	let (x, y) = position(text.position(), margin(text.margin, width), ("w", "h"), ("tw", "th"));
+
+	// The text itself goes through the expansion of drawtext before the option
+	// and filter graph levels.
+	let mut value = escape(&text.text, &['%']);
+	if text.timestamp {
+		if !value.is_empty() {
+			value.push(' ');
+		}
+
+		value.push_str(TIMESTAMP_FORMAT);
+	}
+
+	let color = if text.color.is_empty() {
+		DEFAULT_TEXT_COLOR
+	} else {
+		text.color.as_str()
+	};
+
+	let mut filter = String::from("drawtext=");
+
+	if let Some(font_file) = font_file {
+		filter.push_str(&format!("fontfile={}:", escape_option(font_file)));
+	}
+
+	filter.push_str(&format!(
+		"text={}:fontsize={font_size}:fontcolor=0x{}@{}:x={x}:y={y}",
+		escape_option(&value),
+		color.trim_start_matches('#'),
+		or_default(text.opacity, DEFAULT_OPACITY),
+	));
+
+	if text.background {
+		filter.push_str(&format!(
+			":box=1:boxcolor={TEXT_BACKGROUND_COLOR}:boxborderw={}",
+			(font_size / 4).max(1)
+		));
+	}
+
+	filter
+}
+
+/// The filter graph description of a watermark drawn on a rendition of
+/// `width` by `height`. The frames of the rendition come in through `[in]` and
+/// the image through `[image]`, if there is one.
+pub fn filter_spec(
+	watermark: &Watermark,
+	has_image: bool,
+	width: i32,
+	height: i32,
+	pixel_format: AVPixelFormat,
+	font_file: Option<&str>,
+) -> String {
+	let mut filters = Vec::new();
+	let mut spec = String::new();
+
+	match watermark.image.as_ref().filter(|_| has_image) {
+		Some(image) => {
+			let (image_filters, overlay) = image_filters(image, width);
+			spec.push_str(&format!("[image]{image_filters}[watermark];[in][watermark]"));
+			filters.push(overlay);
+		}
+		None => spec.push_str("[in]"),
+	}
+
+	if let Some(text) = &watermark.text {
+		filters.push(text_filter(text, width, height, font_file));
+	}
+
+	// The encoder takes the pixel format of the decoder, overlay may change it.
+	filters.push(format!("format=pix_fmts={}", pixel_format as i32));
+
+	spec.push_str(&filters.join(","));
+	spec.push_str("[out]");
+
+	spec
+}
+
+/// Decodes the watermark image, any format ffmpeg can probe works but the API
+/// only documents PNG.
+pub fn decode_image(data: Bytes) -> anyhow::Result<VideoFrame> {
+	let mut input = ffmpeg::io::Input::seekable(std::io::Cursor::new(data)).context("failed to open image")?;
+
+	let stream = input
+		.streams()
+		.best(AVMediaType::AVMEDIA_TYPE_VIDEO)
+		.ok_or(FfmpegError::NoStream)
+		.context("failed to find image stream")?;
+
+	let mut decoder = match Decoder::new(&stream).context("failed to create image decoder")? {
+		Decoder::Video(decoder) => decoder,
+		_ => anyhow::bail!("expected video decoder"),
+	};
+
+	while let Some(packet) = input.receive_packet().context("receive packet")? {
+		decoder.send_packet(&packet).context("decoder send packet")?;
+
+		if let Some(frame) = decoder.receive_frame().context("receive frame")? {
+			return Ok(frame);
+		}
+	}
+
+	decoder.send_eof().context("decoder eof")?;
+
+	decoder
+		.receive_frame()
+		.context("receive frame")?
+		.ok_or_else(|| anyhow::anyhow!("image has no frames"))
+}
+
+/// Builds the filter graph which draws the watermark on the frames of a
+/// rendition. The image is sent before any frame of the rendition, overlay
+/// keeps drawing the last frame of the image once it ends.
+pub fn watermark_filter_graph(
+	watermark: &Watermark,
+	image: Option<&VideoFrame>,
+	width: i32,
+	height: i32,
+	pixel_format: AVPixelFormat,
+	time_base: AVRational,
+	font_file: Option<&str>,
+) -> anyhow::Result<FilterGraph> {
+	let mut graph = FilterGraph::new().context("failed to create filter graph")?;
+
+	graph
+		.add(
+			Filter::get("buffer").ok_or(FfmpegError::Arguments("missing buffer filter"))?,
+			"in",
+			&format!(
+				"video_size={width}x{height}:pix_fmt={}:time_base={}/{}:pixel_aspect=1/1",
+				pixel_format as i32, time_base.num, time_base.den,
+			),
+		)
+		.context("failed to add buffer filter")?;
+
+	if let Some(image) = image {
+		graph
+			.add(
+				Filter::get("buffer").ok_or(FfmpegError::Arguments("missing buffer filter"))?,
+				"image",
+				&format!(
+					"video_size={}x{}:pix_fmt={}:time_base=1/1:pixel_aspect=1/1",
+					image.width(),
+					image.height(),
+					image.format(),
+				),
+			)
+			.context("failed to add image buffer filter")?;
+	}
+
+	graph
+		.add(
+			Filter::get("buffersink").ok_or(FfmpegError::Arguments("missing buffersink filter"))?,
+			"out",
+			"",
+		)
+		.context("failed to add buffersink filter")?;
+
+	let mut parser = graph
+		.input("out", 0)
+		.context("failed to set filter graph input")?
+		.output("in", 0)
+		.context("failed to set filter graph output")?;
+
+	if image.is_some() {
+		parser = parser.output("image", 0).context("failed to set filter graph output")?;
+	}
+
+	parser
+		.parse(&filter_spec(
+			watermark,
+			image.is_some(),
+			width,
+			height,
+			pixel_format,
+			font_file,
+		))
+		.context("failed to parse filter graph")?;
+
+	graph.validate().context("failed to validate filter graph")?;
+
+	if let Some(image) = image {
+		let mut image = image.clone();
+		image.set_pts(Some(0));
+
+		let mut source = graph
+			.get("image")
+			.ok_or(FfmpegError::Arguments("missing image input"))?
+			.source();
+
+		source.send_frame(&image).context("failed to send image")?;
+		source.send_eof(None).context("failed to send image eof")?;
+	}
+
+	Ok(graph)
+}
@@ -28,6 +28,7 @@ use utils::task::AsyncTask;
 use video_common::database::Rendition;
 
 use self::recording::Recording;
+use self::screenshot::Screenshot;
 use self::task::generic::GenericTask;
 use self::track::parser::TrackOut;
 use self::track::Track;
@@ -40,10 +41,11 @@ use crate::transcoder::job::task::track_parser::track_parser_task;
 use crate::transcoder::job::track::parser::TrackParser;
 
 mod breakpoint;
-mod ffmpeg;
+pub(crate) mod ffmpeg;
 mod recording;
-mod renditions;
+pub(crate) mod renditions;
 mod screenshot;
+pub(crate) mod sprite;
 mod sql_operations;
 mod task;
 mod track;
@@ -109,12 +111,14 @@ struct Job {
 	ffmpeg_send: Option<mpsc::Sender<Bytes>>,
 	ffmpeg_recv: mpsc::Receiver<(Rendition, TrackOut)>,
 
-	screenshot_recv: mpsc::Receiver<(Bytes, f64)>,
+	screenshot_recv: mpsc::Receiver<Screenshot>,
 
 	tasks: Vec<AsyncTask<anyhow::Result<()>>>,
 
 	first_init_put: bool,
 	screenshot_idx: u32,
+	sprite_idx: u32,
+	sprite_in_progress: bool,
 
 	ingest_send: mpsc::Sender<IngestWatchRequest>,
 	ingest_recv: tonic::Streaming<IngestWatchResponse>,
@@ -185,6 +189,8 @@ impl Job {
 
 			let video_configs = result.video_output.clone();
 			let audio_configs = result.audio_output.clone();
+			let transcoding_config = result.transcoding_config.clone();
+			let watermark_image = result.watermark_image.clone();
 
 			move || {
 				Transcoder::new(
@@ -194,14 +200,17 @@ impl Job {
 					ffmpeg_outputs,
 					video_configs,
 					audio_configs,
+					transcoding_config,
+					watermark_image,
 				)?
 				.run()
 			}
 		}));
 
 		let (screenshot_send, screenshot_recv) = mpsc::channel(16);
-		tasks.push(AsyncTask::spawn_blocking("screenshot", || {
-			screenshot::screenshot_task(frame_recv, screenshot_send)
+		let screenshot_interval = global.config().screenshot_interval.as_secs_f64();
+		tasks.push(AsyncTask::spawn_blocking("screenshot", move || {
+			screenshot::screenshot_task(frame_recv, screenshot_send, screenshot_interval)
 		}));
 
 		let (generic_uploader, rx) = mpsc::channel(16);
@@ -243,6 +252,8 @@ impl Job {
 			connection_id,
 			recording,
 			screenshot_idx: 0,
+			sprite_idx: 0,
+			sprite_in_progress: false,
 			ingest_ready: false,
 			transcoder_ready: false,
 			tracks,
@@ -282,19 +293,8 @@ impl Job {
 
 					self.handle_track(rendition, track_out)?;
 				},
-				Some((data, time)) = self.screenshot_recv.recv() => {
-					self.screenshot_idx += 1;
-
-					if let Some(recording) = &mut self.recording {
-						recording.upload_thumbnail(self.screenshot_idx, time, data.clone())?;
-					}
-
-					self.generic_uploader
-						.try_send(GenericTask::Screenshot { data, idx: self.screenshot_idx })
-						.context("send screenshot task")?;
-
-					self.update_manifest()?;
-					self.ready()?;
+				Some(screenshot) = self.screenshot_recv.recv() => {
+					self.handle_screenshot(screenshot)?;
 				},
 				msg = self.ingest_recv.next() => {
 					let Some(msg) = msg else {
@@ -381,6 +381,49 @@ impl Job {
 		Ok(())
 	}
 
+	fn handle_screenshot(&mut self, screenshot: Screenshot) -> Result<()> {
+		match screenshot {
+			Screenshot::Thumbnail { data, time } => {
+				self.screenshot_idx += 1;
+
+				if let Some(recording) = &mut self.recording {
+					recording.upload_thumbnail(self.screenshot_idx, time, data.clone())?;
+				}
+
+				self.generic_uploader
+					.try_send(GenericTask::Screenshot {
+						data,
+						idx: self.screenshot_idx,
+					})
+					.context("send screenshot task")?;
+
+				self.update_manifest()?;
+				self.ready()?;
+			}
+			Screenshot::Sprite(sprite) => {
+				let Some(recording) = &mut self.recording else {
+					return Ok(());
+				};
+
+				// The sheet is uploaded again every time a tile is added to it, it only
+				// gets a new index once the previous sheet is complete.
+				let new_sheet = !self.sprite_in_progress;
+				if new_sheet {
+					self.sprite_idx += 1;
+				}
+
+				self.sprite_in_progress = !sprite.complete;
+				recording.upload_sprite(self.sprite_idx, sprite);
+
+				if new_sheet {
+					self.update_manifest()?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+
 	fn handle_track(&mut self, rendition: Rendition, track_out: TrackOut) -> Result<()> {
 		let track = self.tracks.get_mut(&rendition).unwrap();
 
@@ -443,6 +486,11 @@ impl Job {
 			self.handle_track(rendition, track_out)?;
 		}
 
+		// The screenshot task may still have screenshots and sprite sheets queued once ffmpeg has finished
+		while let Some(screenshot) = self.screenshot_recv.recv().await {
+			self.handle_screenshot(screenshot)?;
+		}
+
 		let is_shutdown = self.ingest_shutdown == Some(ingest_watch_response::Shutdown::Stream);
 
 		self.tracks
@@ -461,8 +509,9 @@ impl Job {
 			.drain()
 			.try_for_each(|(_, mut track)| track.update_manifest(self.recording.as_mut(), &info_map, is_shutdown))?;
 
-		// Close the generic uploader so that it can finish its tasks
+		// Close the generic and recording uploaders so that they can finish their tasks
 		drop(self.generic_uploader);
+		drop(self.recording.take());
 
 		// New tasks may have been added during shutdown, so we need to check again
 		for mut task in self.tasks.drain(..) {
@@ -495,6 +544,7 @@ impl Job {
 
 		let data = LiveManifest {
 			screenshot_idx: self.screenshot_idx,
+			sprite_idx: self.sprite_idx,
 		}
 		.encode_to_vec()
 		.into();
@@ -548,6 +598,7 @@ impl Job {
 		let manifest = LiveManifest::decode(manifest)?;
 
 		self.screenshot_idx = manifest.screenshot_idx;
+		self.sprite_idx = manifest.sprite_idx;
 
 		for (rendition, data) in rendition_manfiests {
 			let Some(data) = data else {
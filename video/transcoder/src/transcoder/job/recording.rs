@@ -9,13 +9,17 @@ use pb::ext::UlidExt;
 use pb::scuffle::video::internal::live_rendition_manifest::recording_data::RecordingThumbnail;
 use pb::scuffle::video::v1::types::{AudioConfig, RecordingConfig, Rendition as PbRendition, VideoConfig};
 use prost::Message;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use ulid::Ulid;
 use utils::database::tokio_postgres::Transaction;
 use utils::task::AsyncTask;
 use video_common::database::{Rendition, S3Bucket, Visibility};
 
-use super::task::recording::{recording_task, recording_thumbnail_task, RecordingTask, RecordingThumbnailTask};
+use super::sprite::Sprite;
+use super::task::recording::{
+	recording_sprite_task, recording_task, recording_thumbnail_task, RecordingSpriteTask, RecordingTask,
+	RecordingThumbnailTask,
+};
 use crate::global::TranscoderGlobal;
 
 pub struct PartialUpload {
@@ -32,6 +36,9 @@ pub struct Recording {
 	partial_uploads: HashMap<Rendition, PartialUpload>,
 	uploaders: HashMap<Rendition, mpsc::Sender<RecordingTask>>,
 	thumbnail_uploader: mpsc::Sender<RecordingThumbnailTask>,
+	sprite_uploader: mpsc::Sender<RecordingSpriteTask>,
+	partial_sprite_uploader: watch::Sender<Option<RecordingSpriteTask>>,
+	active_sprite: Option<(u32, Ulid)>,
 	tasks: Vec<AsyncTask<anyhow::Result<()>>>,
 	renditions: HashSet<Rendition>,
 	previous_thumbnails: Vec<RecordingThumbnail>,
@@ -127,7 +134,14 @@ impl Recording {
 		let (tx, rx) = mpsc::channel(16);
 		tasks.push(AsyncTask::new(
 			"recording(thumbnail)",
-			recording_thumbnail_task(global.clone(), organization_id, room_id, bucket.clone(), rx),
+			recording_thumbnail_task(global.clone(), organization_id, id, bucket.clone(), rx),
+		));
+
+		let (sprite_uploader, rx) = mpsc::channel(16);
+		let (partial_sprite_uploader, partial_rx) = watch::channel(None);
+		tasks.push(AsyncTask::new(
+			"recording(sprite)",
+			recording_sprite_task(global.clone(), organization_id, id, bucket.clone(), rx, partial_rx),
 		));
 
 		Ok(Self {
@@ -139,6 +153,9 @@ impl Recording {
 			tasks,
 			previous_thumbnails: Vec::new(),
 			thumbnail_uploader: tx,
+			sprite_uploader,
+			partial_sprite_uploader,
+			active_sprite: None,
 		})
 	}
 
@@ -254,4 +271,27 @@ impl Recording {
 
 		Ok(())
 	}
+
+	/// Uploads the sprite sheet at `idx`, a sheet which is uploaded again
+	/// keeps its id so it replaces the previous upload. A partial sheet
+	/// replaces the partial sheet waiting to be uploaded, if there is one.
+	pub fn upload_sprite(&mut self, idx: u32, sprite: Sprite) {
+		let id = match self.active_sprite {
+			Some((active_idx, id)) if active_idx == idx => id,
+			_ => {
+				let id = Ulid::new();
+				self.active_sprite = Some((idx, id));
+				id
+			}
+		};
+
+		let task = RecordingSpriteTask { id, idx, sprite };
+
+		if !task.sprite.complete {
+			self.partial_sprite_uploader.send_replace(Some(task));
+		} else if let Err(err) = self.sprite_uploader.try_send(task) {
+			// Seek previews are not worth failing the transcode over.
+			tracing::warn!("dropping sprite sheet {idx}: {err}");
+		}
+	}
 }
@@ -1,5 +1,66 @@
-use mp4::codec::VideoCodec;
-use pb::scuffle::video::v1::types::{AudioConfig, Rendition, TranscodingConfig, VideoConfig};
+use aac::AudioObjectType;
+use mp4::codec::{AudioCodec, HevcSampleEntry, VideoCodec};
+use pb::scuffle::video::v1::types::{
+	AudioCodec as PbAudioCodec, AudioConfig, Rendition, TranscodingConfig, VideoCodec as PbVideoCodec, VideoConfig,
+};
+
+const DEFAULT_KEYFRAME_INTERVAL_MS: u32 = 2000;
+const DEFAULT_AVC_PROFILE: u8 = 100; // High
+const DEFAULT_AVC_LEVEL: u8 = 51; // 5.1
+const DEFAULT_HEVC_PROFILE: u8 = 1; // Main
+const DEFAULT_HEVC_LEVEL: u8 = 153; // 5.1
+const DEFAULT_AV1_PROFILE: u8 = 0; // Main
+const DEFAULT_AV1_LEVEL: u8 = 13; // 5.1
+
+/// The transcoded video renditions, from the largest to the smallest.
+const TRANSCODED_RENDITIONS: [Rendition; 5] = [
+	Rendition::VideoQhd,
+	Rendition::VideoFhd,
+	Rendition::VideoHd,
+	Rendition::VideoSd,
+	Rendition::VideoLd,
+];
+
+/// Opus always runs at 48kHz internally, so resampling to anything else only
+/// costs quality.
+/// RFC 6716 - 2
+const DEFAULT_OPUS_SAMPLE_RATE: i32 = 48000;
+
+/// The highest channel count a transcoded audio rendition has, anything above
+/// it is downmixed to stereo.
+const MAX_AUDIO_CHANNELS: i32 = 2;
+
+/// The transcoded audio renditions, from the highest bitrate to the lowest.
+const TRANSCODED_AUDIO_RENDITIONS: [Rendition; 2] = [Rendition::AudioHigh, Rendition::AudioLow];
+
+/// The bitrate a transcoded audio rendition uses when the transcoding config
+/// does not override it.
+fn default_audio_bitrate(rendition: Rendition, codec: PbAudioCodec) -> Option<i64> {
+	match (rendition, codec) {
+		(Rendition::AudioHigh, PbAudioCodec::Aac) => Some(160 * 1000),
+		(Rendition::AudioHigh, PbAudioCodec::Opus) => Some(128 * 1000),
+		(Rendition::AudioLow, PbAudioCodec::Aac) => Some(64 * 1000),
+		(Rendition::AudioLow, PbAudioCodec::Opus) => Some(48 * 1000),
+		_ => None,
+	}
+}
+
+/// The side, maximum frame rate and bitrate a transcoded rendition uses when
+/// the transcoding config does not override them.
+fn default_settings(rendition: Rendition) -> Option<(u32, u32, u32)> {
+	match rendition {
+		Rendition::VideoQhd => Some((1440, 60, 9000 * 1024)),
+		Rendition::VideoFhd => Some((1080, 60, 6000 * 1024)),
+		Rendition::VideoHd => Some((720, 60, 4000 * 1024)),
+		Rendition::VideoSd => Some((480, 30, 2000 * 1024)),
+		Rendition::VideoLd => Some((360, 30, 1000 * 1024)),
+		Rendition::VideoSource | Rendition::AudioSource | Rendition::AudioHigh | Rendition::AudioLow => None,
+	}
+}
+
+fn or_default<T: Default + PartialEq>(value: T, default: T) -> T {
+	if value == T::default() { default } else { value }
+}
 
 pub fn determine_output_renditions(
 	video_input: &VideoConfig,
@@ -19,6 +80,41 @@ pub fn determine_output_renditions(
 		});
 	}
 
+	for rendition in TRANSCODED_AUDIO_RENDITIONS {
+		if !transcoding_config.renditions.contains(&rendition.into()) {
+			continue;
+		}
+
+		let settings = transcoding_config
+			.audio_settings
+			.iter()
+			.find(|s| s.rendition() == rendition)
+			.cloned()
+			.unwrap_or_default();
+
+		let Some(bitrate) = default_audio_bitrate(rendition, settings.codec()) else {
+			continue;
+		};
+
+		let (codec, sample_rate) = match settings.codec() {
+			PbAudioCodec::Aac => (
+				AudioCodec::Aac {
+					object_type: AudioObjectType::AacLowComplexity,
+				},
+				or_default(settings.sample_rate, audio_input.sample_rate),
+			),
+			PbAudioCodec::Opus => (AudioCodec::Opus, or_default(settings.sample_rate, DEFAULT_OPUS_SAMPLE_RATE)),
+		};
+
+		audio_configs.push(AudioConfig {
+			rendition: rendition as i32,
+			codec: codec.to_string(),
+			bitrate: or_default(settings.bitrate, bitrate),
+			channels: or_default(settings.channels, audio_input.channels.clamp(1, MAX_AUDIO_CHANNELS)),
+			sample_rate,
+		});
+	}
+
 	if transcoding_config.renditions.contains(&Rendition::VideoSource.into()) {
 		video_configs.push(VideoConfig {
 			rendition: Rendition::VideoSource as i32,
@@ -27,6 +123,7 @@ pub fn determine_output_renditions(
 			fps: video_input.fps,
 			height: video_input.height,
 			width: video_input.width,
+			gop_size: 0,
 		});
 	}
 
@@ -37,36 +134,72 @@ pub fn determine_output_renditions(
 		side: u32,
 		framerate: u32,
 		bitrate: u32,
+		codec: VideoCodec,
+		keyframe_interval_ms: u32,
 	}
 
 	let mut resolutions = vec![];
 
-	if transcoding_config.renditions.contains(&Rendition::VideoHd.into()) {
-		resolutions.push(Resolution {
-			rendition: Rendition::VideoHd,
-			bitrate: 4000 * 1024,
-			framerate: video_input.fps.min(60) as u32,
-			side: 720,
-		});
-	}
+	for rendition in TRANSCODED_RENDITIONS {
+		if !transcoding_config.renditions.contains(&rendition.into()) {
+			continue;
+		}
+
+		let Some((side, framerate, bitrate)) = default_settings(rendition) else {
+			continue;
+		};
+
+		let settings = transcoding_config
+			.video_settings
+			.iter()
+			.find(|s| s.rendition() == rendition)
+			.cloned()
+			.unwrap_or_default();
 
-	if transcoding_config.renditions.contains(&Rendition::VideoSd.into()) {
 		resolutions.push(Resolution {
-			rendition: Rendition::VideoSd,
-			bitrate: 2000 * 1024,
-			framerate: video_input.fps.min(30) as u32,
-			side: 480,
+			rendition,
+			side: or_default(settings.side as u32, side),
+			framerate: (video_input.fps as u32).min(or_default(settings.max_fps as u32, framerate)),
+			bitrate: or_default(settings.bitrate as u32, bitrate),
+			codec: match settings.codec() {
+				PbVideoCodec::Avc => VideoCodec::Avc {
+					profile: or_default(settings.profile as u8, DEFAULT_AVC_PROFILE),
+					level: or_default(settings.level as u8, DEFAULT_AVC_LEVEL),
+					constraint_set: 0,
+				},
+				// The main profile is compatible with the main and main 10 profiles
+				// (flags 1 and 2), and the output is progressive and frame only.
+				PbVideoCodec::Hevc => VideoCodec::Hevc {
+					sample_entry: HevcSampleEntry::Hvc1,
+					general_profile_space: 0,
+					profile: or_default(settings.profile as u8, DEFAULT_HEVC_PROFILE),
+					profile_compatibility: 0x60,
+					level: or_default(settings.level as u8, DEFAULT_HEVC_LEVEL),
+					tier: false,
+					constraint_indicator: 0x90,
+				},
+				// The color fields are the defaults of the codec string, 8-bit 4:2:0 in
+				// BT.709.
+				PbVideoCodec::Av1 => VideoCodec::Av1 {
+					profile: or_default(settings.profile as u8, DEFAULT_AV1_PROFILE),
+					level: or_default(settings.level as u8, DEFAULT_AV1_LEVEL),
+					tier: false,
+					depth: 8,
+					monochrome: false,
+					sub_sampling_x: true,
+					sub_sampling_y: true,
+					chroma_sample_position: 0,
+					color_primaries: 1,
+					transfer_characteristics: 1,
+					matrix_coefficients: 1,
+					full_range_flag: false,
+				},
+			},
+			keyframe_interval_ms: or_default(settings.keyframe_interval_ms as u32, DEFAULT_KEYFRAME_INTERVAL_MS),
 		});
 	}
 
-	if transcoding_config.renditions.contains(&Rendition::VideoLd.into()) {
-		resolutions.push(Resolution {
-			rendition: Rendition::VideoLd,
-			bitrate: 1000 * 1024,
-			framerate: video_input.fps.min(30) as u32,
-			side: 360,
-		})
-	}
+	let mut transcoded_configs = vec![];
 
 	for res in resolutions {
 		// This prevents us from upscaling the video
@@ -88,33 +221,47 @@ pub fn determine_output_renditions(
 		}
 
 		// We dont want to transcode video with resolutions less than 100px on either
-		// side We also do not want to transcode anything more expensive than 720p on a
-		// 16:9 aspect ratio (720 * 1280) This prevents us from transcoding a "720p"
-		// with an aspect ratio of 4:1 (720 * 2880) which is extremely expensive.
-		// Just some insight, 2880 / 1280 = 2.25, so this video is 2.25 times more
-		// expensive than a normal 720p video. 1080 * 1920 = 2073600
+		// side. We also do not want to transcode anything more expensive than the
+		// side on a 16:9 aspect ratio (720 * 1280 for 720p), with 720p as the floor
+		// so the smaller renditions keep working for ultra wide inputs. This
+		// prevents us from transcoding a "720p" with an aspect ratio of 4:1 (720 *
+		// 2880) which is extremely expensive. Just some insight, 2880 / 1280 = 2.25,
+		// so this video is 2.25 times more expensive than a normal 720p video.
+		// 1080 * 1920 = 2073600
 		// 720 * 2880 = 2073600
 		// So a 720p video with an aspect ratio of 4:1 is just as expensive as a 1080p
 		// video with a 16:9 aspect ratio.
-		if width < 100 || height < 100 || width * height > 720 * 1280 {
+		let max_pixels = (res.side * res.side * 16 / 9).max(720 * 1280);
+		if width < 100 || height < 100 || width * height > max_pixels {
 			continue;
 		}
 
-		video_configs.push(VideoConfig {
-			rendition: res.rendition as i32,
-			codec: VideoCodec::Avc {
-				profile: 100, // High
-				level: 51,    // 5.1
-				constraint_set: 0,
-			}
-			.to_string(),
-			bitrate: res.bitrate as i64,
-			fps: res.framerate as i32,
-			height: height as i32,
-			width: width as i32,
-		})
+		transcoded_configs.push((
+			VideoConfig {
+				rendition: res.rendition as i32,
+				codec: res.codec.to_string(),
+				bitrate: res.bitrate as i64,
+				fps: res.framerate as i32,
+				height: height as i32,
+				width: width as i32,
+				gop_size: 0,
+			},
+			res.keyframe_interval_ms,
+		))
+	}
+
+	transcoded_configs.sort_by(|(a, _), (b, _)| b.width.cmp(&a.width).then(b.fps.cmp(&a.fps)));
+
+	// The transcoder scales and rate limits each rendition from the output of the
+	// previous one, so the frame rate can never go up further down the ladder.
+	let mut max_fps = i32::MAX;
+	for (config, keyframe_interval_ms) in transcoded_configs.iter_mut() {
+		config.fps = config.fps.min(max_fps);
+		config.gop_size = ((config.fps as u32 * *keyframe_interval_ms) / 1000).max(1) as i32;
+		max_fps = config.fps;
 	}
 
+	video_configs.extend(transcoded_configs.into_iter().map(|(config, _)| config));
 	video_configs.sort_by(|a, b| b.width.cmp(&a.width));
 
 	(video_configs, audio_configs)
@@ -3,9 +3,23 @@ use bytes::Bytes;
 use ffmpeg::ffi::AVPixelFormat;
 use ffmpeg::frame::Frame;
 use image::codecs::jpeg::JpegEncoder;
+use image::RgbaImage;
 use tokio::sync::mpsc;
 
-pub fn screenshot_task(mut recv: mpsc::Receiver<Frame>, send: mpsc::Sender<(Bytes, f64)>) -> anyhow::Result<()> {
+use super::sprite::{Sprite, SpriteSheet};
+
+pub enum Screenshot {
+	Thumbnail { data: Bytes, time: f64 },
+	Sprite(Sprite),
+}
+
+pub fn screenshot_task(
+	mut recv: mpsc::Receiver<Frame>,
+	send: mpsc::Sender<Screenshot>,
+	screenshot_interval: f64,
+) -> anyhow::Result<()> {
+	let mut sprite_sheet = SpriteSheet::new(screenshot_interval);
+
 	while let Some(frame) = recv.blocking_recv() {
 		let _guard = utils::task::AbortGuard::new();
 
@@ -25,6 +39,11 @@ pub fn screenshot_task(mut recv: mpsc::Receiver<Frame>, send: mpsc::Sender<(Byte
 			.encode(data, width, height, image::ColorType::Rgba8)
 			.context("failed to encode jpeg")?;
 
+		let image = data
+			.get(..(width * height * 4) as usize)
+			.and_then(|data| RgbaImage::from_raw(width, height, data.to_vec()))
+			.ok_or_else(|| anyhow::anyhow!("frame data too small"))?;
+
 		let data = Bytes::from(writer);
 
 		let timestamp = frame
@@ -34,7 +53,12 @@ pub fn screenshot_task(mut recv: mpsc::Receiver<Frame>, send: mpsc::Sender<(Byte
 
 		let time = timestamp as f64 * frame.time_base().num as f64 / frame.time_base().den as f64;
 
-		send.blocking_send((data, time)).context("failed to send screenshot")?;
+		send.blocking_send(Screenshot::Thumbnail { data, time })
+			.context("failed to send screenshot")?;
+
+		let sprite = sprite_sheet.push(&image, time)?;
+		send.blocking_send(Screenshot::Sprite(sprite))
+			.context("failed to send sprite")?;
 	}
 
 	Ok(())
@@ -0,0 +1,129 @@
+use anyhow::Context;
+use bytes::Bytes;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{GenericImage, RgbaImage};
+
+/// The number of tiles in each row of a sprite sheet.
+pub const SPRITE_COLUMNS: u32 = 5;
+/// The number of rows in a sprite sheet.
+pub const SPRITE_ROWS: u32 = 5;
+
+/// The largest size the short side of a tile can be.
+const MAX_TILE_SIZE: u32 = 90;
+
+const SPRITE_QUALITY: u8 = 80;
+
+/// A sprite sheet encoded as a JPEG, it is partial until `complete` is set.
+#[derive(Debug, Clone)]
+pub struct Sprite {
+	pub data: Bytes,
+	pub tile_width: u32,
+	pub tile_height: u32,
+	pub columns: u32,
+	/// The time each tile starts at in seconds, in the order the tiles are laid
+	/// out (left to right, top to bottom).
+	pub tile_start_times: Vec<f64>,
+	/// The time the last tile stops being relevant in seconds.
+	pub end_time: f64,
+	/// If the sheet is full, no more tiles are added to it and the next
+	/// screenshot starts a new sheet.
+	pub complete: bool,
+}
+
+/// Builds sprite sheets out of the screenshots taken during a stream.
+/// The sheet is emitted after every screenshot so the newest part of a live
+/// stream has previews too, it is replaced by the next emit until it holds
+/// `SPRITE_COLUMNS * SPRITE_ROWS` tiles.
+pub struct SpriteSheet {
+	tile_duration: f64,
+	tile_size: Option<(u32, u32)>,
+	tiles: Vec<(RgbaImage, f64)>,
+}
+
+pub fn tile_size(width: u32, height: u32) -> (u32, u32) {
+	let short_side = width.min(height);
+	if short_side <= MAX_TILE_SIZE {
+		return (width, height);
+	}
+
+	let scale = MAX_TILE_SIZE as f64 / short_side as f64;
+
+	(
+		((width as f64 * scale).round() as u32).max(1),
+		((height as f64 * scale).round() as u32).max(1),
+	)
+}
+
+impl SpriteSheet {
+	pub fn new(tile_duration: f64) -> Self {
+		Self {
+			tile_duration,
+			tile_size: None,
+			tiles: Vec::new(),
+		}
+	}
+
+	/// Adds a screenshot taken at `time` to the sheet and returns the sheet
+	/// with the tiles it has so far.
+	pub fn push(&mut self, image: &RgbaImage, time: f64) -> anyhow::Result<Sprite> {
+		// The first screenshot decides the tile size, so every tile on a sheet
+		// has the same dimensions even if the input resolution changes.
+		let (tile_width, tile_height) = *self.tile_size.get_or_insert_with(|| tile_size(image.width(), image.height()));
+
+		let tile = if image.dimensions() == (tile_width, tile_height) {
+			image.clone()
+		} else {
+			image::imageops::resize(image, tile_width, tile_height, FilterType::Triangle)
+		};
+
+		self.tiles.push((tile, time));
+
+		let sprite = self.encode(tile_width, tile_height)?;
+
+		if sprite.complete {
+			self.tiles.clear();
+		}
+
+		Ok(sprite)
+	}
+
+	fn encode(&self, tile_width: u32, tile_height: u32) -> anyhow::Result<Sprite> {
+		let tiles = &self.tiles;
+
+		// A partial sheet is cropped to the tiles it has, but tiles keep their
+		// position so that the layout is the same as a full sheet.
+		let sheet_columns = SPRITE_COLUMNS.min(tiles.len() as u32);
+		let sheet_rows = (tiles.len() as u32).div_ceil(SPRITE_COLUMNS);
+
+		let mut sheet = RgbaImage::new(tile_width * sheet_columns, tile_height * sheet_rows);
+
+		for (idx, (tile, _)) in tiles.iter().enumerate() {
+			let idx = idx as u32;
+			sheet
+				.copy_from(
+					tile,
+					(idx % SPRITE_COLUMNS) * tile_width,
+					(idx / SPRITE_COLUMNS) * tile_height,
+				)
+				.context("failed to copy tile")?;
+		}
+
+		let mut writer = Vec::new();
+		JpegEncoder::new_with_quality(&mut writer, SPRITE_QUALITY)
+			.encode(sheet.as_raw(), sheet.width(), sheet.height(), image::ColorType::Rgba8)
+			.context("failed to encode sprite")?;
+
+		let tile_start_times = tiles.iter().map(|(_, time)| *time).collect::<Vec<_>>();
+
+		Ok(Sprite {
+			data: Bytes::from(writer),
+			tile_width,
+			tile_height,
+			columns: SPRITE_COLUMNS,
+			end_time: tile_start_times.last().copied().unwrap_or_default() + self.tile_duration,
+			complete: tile_start_times.len() >= (SPRITE_COLUMNS * SPRITE_ROWS) as usize,
+			tile_start_times,
+		})
+	}
+}
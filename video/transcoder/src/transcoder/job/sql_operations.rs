@@ -1,11 +1,15 @@
 use std::sync::Arc;
 
 use anyhow::Context;
+use aws_config::Region;
+use aws_sdk_s3::config::Credentials;
+use bytes::Bytes;
 use pb::ext::UlidExt;
-use pb::scuffle::video::v1::types::{AudioConfig, Rendition, TranscodingConfig, VideoConfig};
+use pb::scuffle::video::v1::types::{watermark, AudioConfig, Rendition, TranscodingConfig, VideoConfig};
 use prost::Message;
 use ulid::Ulid;
-use video_common::database::Room;
+use utils::database::IntoClient;
+use video_common::database::{Room, S3Bucket};
 
 use super::recording::Recording;
 use crate::global::TranscoderGlobal;
@@ -18,6 +22,47 @@ pub struct SqlOperations {
 	pub audio_input: AudioConfig,
 	pub video_output: Vec<VideoConfig>,
 	pub audio_output: Vec<AudioConfig>,
+	pub watermark_image: Option<Bytes>,
+}
+
+async fn download_watermark_image(
+	client: impl IntoClient,
+	organization_id: Ulid,
+	image: &watermark::Image,
+) -> anyhow::Result<Bytes> {
+	let s3_bucket: S3Bucket = utils::database::query(
+		r#"
+		SELECT
+			*
+		FROM
+			s3_buckets
+		WHERE
+			organization_id = $1
+			AND id = $2
+		"#,
+	)
+	.bind(organization_id)
+	.bind(image.s3_bucket_id.into_ulid())
+	.build_query_as()
+	.fetch_one(client)
+	.await
+	.context("failed to query s3 bucket")?;
+
+	let bucket = binary_helper::s3::Bucket::new(
+		s3_bucket.name,
+		Credentials::from_keys(&s3_bucket.access_key_id, &s3_bucket.secret_access_key, None),
+		Region::new(s3_bucket.region),
+		s3_bucket.endpoint,
+	);
+
+	let response = bucket.get_object(&image.key).await.context("failed to get watermark image")?;
+
+	Ok(response
+		.body
+		.collect()
+		.await
+		.context("failed to read watermark image")?
+		.into_bytes())
 }
 
 pub async fn perform_sql_operations(
@@ -161,6 +206,19 @@ pub async fn perform_sql_operations(
 
 	let (video_output, audio_output) = determine_output_renditions(&video_input, &audio_input, &transcoding_config);
 
+	// A missing watermark image should not stop the stream from being
+	// transcoded, the rest of the watermark is still drawn.
+	let watermark_image = match transcoding_config.watermark.as_ref().and_then(|w| w.image.as_ref()) {
+		Some(image) => match download_watermark_image(&client, organization_id, image).await {
+			Ok(data) => Some(data),
+			Err(err) => {
+				tracing::warn!(key = %image.key, "failed to download watermark image: {:#}", err);
+				None
+			}
+		},
+		None => None,
+	};
+
 	let tx = client.transaction().await.context("failed to start transaction")?;
 
 	utils::database::query(
@@ -218,5 +276,6 @@ pub async fn perform_sql_operations(
 		audio_input,
 		video_output,
 		audio_output,
+		watermark_image,
 	})
 }
@@ -4,13 +4,14 @@ use anyhow::Context;
 use aws_sdk_s3::types::ObjectCannedAcl;
 use binary_helper::s3::{AsyncStreamBody, PutObjectOptions};
 use bytes::Bytes;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use tokio_stream::StreamExt;
 use ulid::Ulid;
 use video_common::database::Rendition;
 
 use super::retry_task;
 use crate::global::TranscoderGlobal;
+use crate::transcoder::job::sprite::Sprite;
 
 pub enum RecordingTask {
 	Segment {
@@ -182,7 +183,8 @@ pub async fn recording_thumbnail_task(
                     $2,
                     $3,
                     $4,
-                    $5
+                    $5,
+                    $6
                 )"#,
 				)
 				.bind(organization_id)
@@ -210,3 +212,130 @@ pub async fn recording_thumbnail_task(
 
 	Ok(())
 }
+
+#[derive(Clone)]
+pub struct RecordingSpriteTask {
+	pub idx: u32,
+	pub id: Ulid,
+	pub sprite: Sprite,
+}
+
+/// Uploads the sprite sheets of a recording. Complete sheets come in through
+/// `rx`, the sheet which is still being filled through `partial_rx` which only
+/// keeps the newest version, so it is uploaded at most once at a time.
+pub async fn recording_sprite_task(
+	global: Arc<impl TranscoderGlobal>,
+	organization_id: Ulid,
+	recording_id: Ulid,
+	bucket: binary_helper::s3::Bucket,
+	mut rx: mpsc::Receiver<RecordingSpriteTask>,
+	mut partial_rx: watch::Receiver<Option<RecordingSpriteTask>>,
+) -> anyhow::Result<()> {
+	// A partial sheet which is older than the last complete sheet would replace
+	// it with fewer tiles.
+	let mut last_complete_idx = None;
+
+	loop {
+		let task = tokio::select! {
+			biased;
+			Some(task) = rx.recv() => task,
+			Ok(()) = partial_rx.changed() => {
+				let task = partial_rx.borrow_and_update().clone();
+				match task {
+					Some(task) => task,
+					None => continue,
+				}
+			}
+			else => break,
+		};
+
+		if task.sprite.complete {
+			last_complete_idx = Some(task.idx);
+		} else if last_complete_idx.is_some_and(|idx| task.idx <= idx) {
+			continue;
+		}
+
+		retry_task(
+			|| async {
+				let sprite = &task.sprite;
+				let size = sprite.data.len();
+
+				bucket
+					.put_object(
+						video_common::keys::s3_thumbnail_sprite(organization_id, recording_id, task.idx, task.id),
+						sprite.data.clone(),
+						Some(PutObjectOptions {
+							content_type: Some("image/jpeg".to_owned()),
+							acl: Some(ObjectCannedAcl::PublicRead),
+						}),
+					)
+					.await
+					.context("upload sprite")?;
+
+				if utils::database::query(
+					r#"
+                INSERT INTO recording_thumbnail_sprites (
+                    organization_id,
+                    recording_id,
+                    idx,
+                    id,
+                    start_time,
+                    end_time,
+                    tile_width,
+                    tile_height,
+                    tile_columns,
+                    tile_start_times,
+                    size_bytes
+                ) VALUES (
+                    $1,
+                    $2,
+                    $3,
+                    $4,
+                    $5,
+                    $6,
+                    $7,
+                    $8,
+                    $9,
+                    $10,
+                    $11
+                ) ON CONFLICT (organization_id, recording_id, idx) DO UPDATE SET
+                    end_time = EXCLUDED.end_time,
+                    tile_start_times = EXCLUDED.tile_start_times,
+                    size_bytes = EXCLUDED.size_bytes"#,
+				)
+				.bind(organization_id)
+				.bind(recording_id)
+				.bind(task.idx as i32)
+				.bind(task.id)
+				.bind(normalize_float(sprite.tile_start_times.first().copied().unwrap_or_default()))
+				.bind(normalize_float(sprite.end_time))
+				.bind(sprite.tile_width as i32)
+				.bind(sprite.tile_height as i32)
+				.bind(sprite.columns as i32)
+				.bind(
+					sprite
+						.tile_start_times
+						.iter()
+						.map(|time| normalize_float(*time) as f32)
+						.collect::<Vec<_>>(),
+				)
+				.bind(size as i64)
+				.build()
+				.execute(global.db())
+				.await
+				.context("insert sprite")?
+					!= 1
+				{
+					anyhow::bail!("no rows affected");
+				}
+
+				Ok(())
+			},
+			5,
+		)
+		.await
+		.context("s3_sprite_task")?;
+	}
+
+	Ok(())
+}